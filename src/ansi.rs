@@ -0,0 +1,325 @@
+use crate::effects::stage::rgb_color;
+use crossterm::style::Color;
+use unicode_width::UnicodeWidthChar;
+
+/// A visible input character along with the foreground color the input
+/// gave it, if any. Uncolored characters fall back to the effect's base color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StyledChar {
+    pub ch: char,
+    pub color: Option<Color>,
+}
+
+impl StyledChar {
+    /// Terminal columns this character occupies (zero for combining marks).
+    pub fn width(&self) -> usize {
+        self.ch.width().unwrap_or(0)
+    }
+}
+
+/// Starting terminal column of every cell, plus the total width in columns.
+pub fn column_starts(cells: &[StyledChar]) -> (Vec<usize>, usize) {
+    let mut column = 0;
+    let starts = cells
+        .iter()
+        .map(|cell| {
+            let start = column;
+            column += cell.width();
+            start
+        })
+        .collect();
+    (starts, column)
+}
+
+/// The standard and bright ANSI palette, roughly as xterm renders it.
+pub const BASIC_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Converts an xterm 256-color index to RGB.
+pub fn ansi_256_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC_COLORS[index as usize],
+        16..=231 => {
+            let cube = index - 16;
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            (level(cube / 36), level((cube / 6) % 6), level(cube % 6))
+        }
+        232..=255 => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// Bits of `Pen::attributes`, one per SGR text attribute.
+pub const BOLD: u8 = 1;
+pub const DIM: u8 = 1 << 1;
+pub const ITALIC: u8 = 1 << 2;
+pub const UNDERLINE: u8 = 1 << 3;
+pub const BLINK: u8 = 1 << 4;
+pub const REVERSE: u8 = 1 << 5;
+pub const STRIKETHROUGH: u8 = 1 << 6;
+
+/// Every attribute bit with its name, in SGR order.
+pub const ATTRIBUTE_NAMES: [(u8, &str); 7] = [
+    (BOLD, "bold"),
+    (DIM, "dim"),
+    (ITALIC, "italic"),
+    (UNDERLINE, "underline"),
+    (BLINK, "blink"),
+    (REVERSE, "reverse"),
+    (STRIKETHROUGH, "strikethrough"),
+];
+
+/// Everything SGR sequences set for the text written after them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pen {
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub attributes: u8,
+}
+
+/// The color an SGR `38`/`48` code picks with the codes after it, and how
+/// many of those it used.
+fn extended_color(rest: &[u16]) -> (Option<Color>, usize) {
+    match rest.first() {
+        Some(5) => {
+            let index = rest.get(1).copied().unwrap_or(0);
+            (Some(rgb_color(ansi_256_to_rgb(index.min(255) as u8))), 2)
+        }
+        Some(2) => {
+            let channel = |n: usize| rest.get(n).copied().unwrap_or(0).min(255) as u8;
+            (Some(rgb_color((channel(1), channel(2), channel(3)))), 4)
+        }
+        _ => (None, 0),
+    }
+}
+
+/// Applies the parameters of one SGR (`ESC [ ... m`) sequence to `pen`.
+pub fn apply_sgr_pen(params: &str, pen: &mut Pen) {
+    let codes: Vec<u16> = params
+        .split(';')
+        .map(|code| code.parse().unwrap_or(0))
+        .collect();
+    let basic = |index: u16| Some(rgb_color(BASIC_COLORS[index as usize]));
+
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => *pen = Pen::default(),
+            1 => pen.attributes |= BOLD,
+            2 => pen.attributes |= DIM,
+            3 => pen.attributes |= ITALIC,
+            4 => pen.attributes |= UNDERLINE,
+            5 | 6 => pen.attributes |= BLINK,
+            7 => pen.attributes |= REVERSE,
+            9 => pen.attributes |= STRIKETHROUGH,
+            22 => pen.attributes &= !(BOLD | DIM),
+            23 => pen.attributes &= !ITALIC,
+            24 => pen.attributes &= !UNDERLINE,
+            25 => pen.attributes &= !BLINK,
+            27 => pen.attributes &= !REVERSE,
+            29 => pen.attributes &= !STRIKETHROUGH,
+            code @ 30..=37 => pen.foreground = basic(code - 30),
+            code @ 90..=97 => pen.foreground = basic(code - 90 + 8),
+            39 => pen.foreground = None,
+            code @ 40..=47 => pen.background = basic(code - 40),
+            code @ 100..=107 => pen.background = basic(code - 100 + 8),
+            49 => pen.background = None,
+            code @ (38 | 48) => {
+                let (color, used) = extended_color(&codes[i + 1..]);
+                i += used;
+                if color.is_some() {
+                    match code {
+                        38 => pen.foreground = color,
+                        _ => pen.background = color,
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Applies the parameters of one SGR (`ESC [ ... m`) sequence to `current`.
+/// Only the foreground color is tracked; other attributes are ignored.
+pub fn apply_sgr(params: &str, current: &mut Option<Color>) {
+    let mut pen = Pen {
+        foreground: *current,
+        ..Pen::default()
+    };
+    apply_sgr_pen(params, &mut pen);
+    *current = pen.foreground;
+}
+
+/// Splits `text` into visible characters tagged with the foreground color
+/// active at that point. Escape sequences are consumed, so the result only
+/// holds characters that take up space on screen.
+pub fn parse_styled(text: &str) -> Vec<StyledChar> {
+    let mut result = Vec::new();
+    let mut current = None;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            let mut params = String::new();
+            for escape_ch in chars.by_ref() {
+                if escape_ch.is_ascii_alphabetic() {
+                    if escape_ch == 'm' {
+                        apply_sgr(&params, &mut current);
+                    }
+                    break;
+                }
+                params.push(escape_ch);
+            }
+            continue;
+        }
+
+        result.push(StyledChar { ch, color: current });
+    }
+
+    result
+}
+
+/// `text` with `color` as the foreground of every character the input
+/// didn't color itself, so effects take it as their base color there.
+/// Resets in the input go back to `color` rather than the terminal default.
+pub fn tint(text: &str, color: (u8, u8, u8)) -> String {
+    let code = format!("\x1b[38;2;{};{};{}m", color.0, color.1, color.2);
+    let mut result = code.clone();
+    let mut current = None;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' && chars.peek() == Some(&'[') {
+            result.push(ch);
+            result.push(chars.next().unwrap_or('['));
+            let mut params = String::new();
+            for escape_ch in chars.by_ref() {
+                result.push(escape_ch);
+                if escape_ch.is_ascii_alphabetic() {
+                    if escape_ch == 'm' {
+                        apply_sgr(&params, &mut current);
+                        if current.is_none() {
+                            result.push_str(&code);
+                        }
+                    }
+                    break;
+                }
+                params.push(escape_ch);
+            }
+            continue;
+        }
+
+        result.push(ch);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(cells: &[StyledChar]) -> String {
+        cells.iter().map(|cell| cell.ch).collect()
+    }
+
+    #[test]
+    fn test_tint_colors_what_the_input_left_uncolored() {
+        let blue = Some(rgb_color((0, 0, 255)));
+        let cells = parse_styled(&tint("a\x1b[31mb\x1b[0mc\x1b[1md", (0, 0, 255)));
+        assert_eq!(chars(&cells), "abcd");
+        assert_eq!(cells[0].color, blue);
+        assert_eq!(cells[1].color, Some(rgb_color((205, 0, 0))));
+        assert_eq!(cells[2].color, blue);
+        assert_eq!(cells[3].color, blue);
+    }
+
+    #[test]
+    fn test_plain_text_is_uncolored() {
+        let cells = parse_styled("hi");
+        assert_eq!(chars(&cells), "hi");
+        assert!(cells.iter().all(|cell| cell.color.is_none()));
+    }
+
+    #[test]
+    fn test_basic_colors_and_reset() {
+        let cells = parse_styled("\x1b[31mr\x1b[0mx\x1b[92mg\x1b[39m");
+        assert_eq!(chars(&cells), "rxg");
+        assert_eq!(cells[0].color, Some(rgb_color((205, 0, 0))));
+        assert_eq!(cells[1].color, None);
+        assert_eq!(cells[2].color, Some(rgb_color((0, 255, 0))));
+    }
+
+    #[test]
+    fn test_256_and_truecolor() {
+        let cells = parse_styled("\x1b[38;5;196ma\x1b[1;38;2;10;20;30mb");
+        assert_eq!(cells[0].color, Some(rgb_color((255, 0, 0))));
+        assert_eq!(cells[1].color, Some(rgb_color((10, 20, 30))));
+    }
+
+    #[test]
+    fn test_background_colors_are_skipped() {
+        let cells = parse_styled("\x1b[32;48;2;1;2;3ma\x1b[48;5;33mb");
+        assert_eq!(cells[0].color, Some(rgb_color((0, 205, 0))));
+        assert_eq!(cells[1].color, Some(rgb_color((0, 205, 0))));
+    }
+
+    #[test]
+    fn test_pen_tracks_backgrounds_and_attributes() {
+        let mut pen = Pen::default();
+        apply_sgr_pen("1;4;44;38;5;196", &mut pen);
+        assert_eq!(pen.foreground, Some(rgb_color((255, 0, 0))));
+        assert_eq!(pen.background, Some(rgb_color((0, 0, 238))));
+        assert_eq!(pen.attributes, BOLD | UNDERLINE);
+
+        apply_sgr_pen("22;7;48;2;1;2;3", &mut pen);
+        assert_eq!(pen.background, Some(rgb_color((1, 2, 3))));
+        assert_eq!(pen.attributes, UNDERLINE | REVERSE);
+
+        apply_sgr_pen("0", &mut pen);
+        assert_eq!(pen, Pen::default());
+    }
+
+    #[test]
+    fn test_non_sgr_sequences_are_dropped() {
+        let cells = parse_styled("\x1b[2Ka\x1b[1Gb");
+        assert_eq!(chars(&cells), "ab");
+        assert!(cells.iter().all(|cell| cell.color.is_none()));
+    }
+
+    #[test]
+    fn test_column_starts_count_wide_characters() {
+        let (starts, width) = column_starts(&parse_styled("a漢\x1b[31m字b"));
+        assert_eq!(starts, vec![0, 1, 3, 5]);
+        assert_eq!(width, 6);
+    }
+
+    #[test]
+    fn test_ansi_256_palette() {
+        assert_eq!(ansi_256_to_rgb(1), (205, 0, 0));
+        assert_eq!(ansi_256_to_rgb(16), (0, 0, 0));
+        assert_eq!(ansi_256_to_rgb(231), (255, 255, 255));
+        assert_eq!(ansi_256_to_rgb(232), (8, 8, 8));
+        assert_eq!(ansi_256_to_rgb(255), (238, 238, 238));
+    }
+}
@@ -0,0 +1,77 @@
+/// Height in rows of every big-digit glyph.
+pub const GLYPH_HEIGHT: usize = 5;
+
+type Glyph = [&'static str; GLYPH_HEIGHT];
+
+const DIGITS: [Glyph; 10] = [
+    ["███", "█ █", "█ █", "█ █", "███"],
+    ["  █", "  █", "  █", "  █", "  █"],
+    ["███", "  █", "███", "█  ", "███"],
+    ["███", "  █", "███", "  █", "███"],
+    ["█ █", "█ █", "███", "  █", "  █"],
+    ["███", "█  ", "███", "  █", "███"],
+    ["███", "█  ", "███", "█ █", "███"],
+    ["███", "  █", "  █", "  █", "  █"],
+    ["███", "█ █", "███", "█ █", "███"],
+    ["███", "█ █", "███", "  █", "███"],
+];
+
+const COLON: Glyph = [" ", "█", " ", "█", " "];
+const PERIOD: Glyph = [" ", " ", " ", " ", "█"];
+const MINUS: Glyph = ["   ", "   ", "███", "   ", "   "];
+const SPACE: Glyph = [" ", " ", " ", " ", " "];
+
+/// Block glyph for `ch`, if the font has one.
+pub fn glyph(ch: char) -> Option<&'static Glyph> {
+    match ch {
+        '0'..='9' => Some(&DIGITS[ch as usize - '0' as usize]),
+        ':' => Some(&COLON),
+        '.' => Some(&PERIOD),
+        '-' => Some(&MINUS),
+        ' ' => Some(&SPACE),
+        _ => None,
+    }
+}
+
+/// Renders `text` as rows of block glyphs separated by one column.
+/// Characters without a glyph are skipped.
+pub fn render(text: &str) -> Vec<String> {
+    let glyphs: Vec<&Glyph> = text.chars().filter_map(glyph).collect();
+
+    (0..GLYPH_HEIGHT)
+        .map(|row| {
+            glyphs
+                .iter()
+                .map(|glyph| glyph[row])
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_digit_rows_have_equal_width() {
+        for ch in "0123456789:.- ".chars() {
+            let glyph = glyph(ch).unwrap();
+            let width = glyph[0].chars().count();
+            assert!(glyph.iter().all(|row| row.chars().count() == width));
+        }
+    }
+
+    #[test]
+    fn test_render_joins_glyphs() {
+        let rows = render("1:0");
+        assert_eq!(rows.len(), GLYPH_HEIGHT);
+        assert_eq!(rows[0], "  █   ███");
+        assert_eq!(rows[1], "  █ █ █ █");
+    }
+
+    #[test]
+    fn test_render_skips_unknown_characters() {
+        assert_eq!(render("1x"), render("1"));
+    }
+}
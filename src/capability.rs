@@ -0,0 +1,507 @@
+use crate::ansi::{ansi_256_to_rgb, BASIC_COLORS};
+use clap::ValueEnum;
+use crossterm::style::{Attribute, Color, SetAttribute, SetForegroundColor};
+use crossterm::Command;
+use std::env;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// How many colors the terminal can show, as chosen with `--color-mode`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    /// Detect from COLORTERM and TERM
+    Auto,
+    /// 24-bit RGB
+    Truecolor,
+    /// The xterm 256-color palette
+    #[value(name = "256")]
+    Ansi256,
+    /// The 16 standard ANSI colors
+    #[value(name = "16")]
+    Ansi16,
+    /// No color at all; animation shows as bold, dim, blink or reverse video instead
+    Mono,
+}
+
+impl ColorMode {
+    /// Resolves `Auto` from the environment; other modes are returned as is.
+    pub fn resolve(self) -> Self {
+        match self {
+            ColorMode::Auto => detect(
+                env::var("NO_COLOR").ok().as_deref(),
+                env::var("COLORTERM").ok().as_deref(),
+                env::var("TERM").ok().as_deref(),
+            ),
+            mode => mode,
+        }
+    }
+}
+
+/// Guesses the color depth from the usual environment variables. A
+/// non-empty `NO_COLOR` (see no-color.org) wins over everything else.
+fn detect(no_color: Option<&str>, colorterm: Option<&str>, term: Option<&str>) -> ColorMode {
+    if no_color.is_some_and(|value| !value.is_empty()) {
+        return ColorMode::Mono;
+    }
+
+    let colorterm = colorterm.unwrap_or("").to_ascii_lowercase();
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        return ColorMode::Truecolor;
+    }
+
+    let term = term.unwrap_or("").to_ascii_lowercase();
+    if term.contains("truecolor") || term.contains("direct") {
+        ColorMode::Truecolor
+    } else if term.contains("256") {
+        ColorMode::Ansi256
+    } else {
+        ColorMode::Ansi16
+    }
+}
+
+static MODE: OnceLock<ColorMode> = OnceLock::new();
+static MONO_CUE: OnceLock<MonoCue> = OnceLock::new();
+
+/// Fixes the process-wide color depth. Only the first call has any effect.
+pub fn init(mode: ColorMode) {
+    let _ = MODE.set(mode.resolve());
+}
+
+/// The color depth effects draw in, once `init` has fixed it.
+pub fn mode() -> ColorMode {
+    *MODE.get_or_init(|| ColorMode::Auto.resolve())
+}
+
+/// How an effect's animation shows in mono, where color changes are
+/// invisible: cells closer to `peak` than to `rest` get `attribute`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonoCue {
+    pub attribute: Attribute,
+    pub rest: (u8, u8, u8),
+    pub peak: (u8, u8, u8),
+}
+
+impl MonoCue {
+    /// Text that breathes between `rest` and `peak` blinks near the top.
+    pub fn blink(rest: (u8, u8, u8), peak: (u8, u8, u8)) -> Self {
+        Self {
+            attribute: Attribute::SlowBlink,
+            rest,
+            peak,
+        }
+    }
+
+    /// Highlights moving over `rest`-colored text show in reverse video.
+    pub fn reverse(rest: (u8, u8, u8), peak: (u8, u8, u8)) -> Self {
+        Self {
+            attribute: Attribute::Reverse,
+            rest,
+            peak,
+        }
+    }
+
+    /// Text lit up from a dimmed `rest` to `peak` shows in bold.
+    pub fn bold(rest: (u8, u8, u8), peak: (u8, u8, u8)) -> Self {
+        Self {
+            attribute: Attribute::Bold,
+            rest,
+            peak,
+        }
+    }
+
+    /// Text that hasn't settled on `rest` yet, still near `peak`, shows dimmed.
+    pub fn dim(rest: (u8, u8, u8), peak: (u8, u8, u8)) -> Self {
+        Self {
+            attribute: Attribute::Dim,
+            rest,
+            peak,
+        }
+    }
+
+    fn lit(&self, color: Color) -> bool {
+        let Color::Rgb { r, g, b } = color else {
+            return false;
+        };
+        self.rest != self.peak && distance((r, g, b), self.peak) < distance((r, g, b), self.rest)
+    }
+}
+
+/// Sets the mono mapping for the running effect. Effects without one are
+/// drawn as plain text in mono. Only the first call has any effect.
+pub fn cue_mono(cue: MonoCue) {
+    let _ = MONO_CUE.set(cue);
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Nearest xterm 256-color index, checking both the color cube and the
+/// grayscale ramp.
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |c: u8| -> u8 {
+        if c < 48 {
+            0
+        } else if c < 115 {
+            1
+        } else {
+            (c - 35) / 40
+        }
+    };
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray = if average > 238 {
+        255
+    } else {
+        232 + (average.saturating_sub(3) / 10).min(23) as u8
+    };
+
+    if distance(ansi_256_to_rgb(gray), rgb) < distance(ansi_256_to_rgb(cube), rgb) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// Nearest of the 16 standard colors, as the named color crossterm emits
+/// with the basic `30`-`37`/`90`-`97` codes.
+fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    const NAMED: [Color; 16] = [
+        Color::Black,
+        Color::DarkRed,
+        Color::DarkGreen,
+        Color::DarkYellow,
+        Color::DarkBlue,
+        Color::DarkMagenta,
+        Color::DarkCyan,
+        Color::Grey,
+        Color::DarkGrey,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+    ];
+
+    let index = (0..16)
+        .min_by_key(|&i| distance(BASIC_COLORS[i], rgb))
+        .unwrap_or(15);
+    NAMED[index]
+}
+
+/// Converts `color` to what terminals in `mode` can display.
+pub fn fit_to(color: Color, mode: ColorMode) -> Color {
+    let Color::Rgb { r, g, b } = color else {
+        return color;
+    };
+
+    match mode {
+        ColorMode::Auto | ColorMode::Truecolor => color,
+        ColorMode::Ansi256 => Color::AnsiValue(nearest_256((r, g, b))),
+        ColorMode::Ansi16 => nearest_16((r, g, b)),
+        ColorMode::Mono => Color::Reset,
+    }
+}
+
+/// What `fg` writes: a color, or in mono the effect's attribute switched on
+/// or off.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Foreground {
+    Color(Color),
+    Attribute { attribute: Attribute, on: bool },
+}
+
+impl Command for Foreground {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match *self {
+            Foreground::Color(color) => SetForegroundColor(color).write_ansi(f),
+            Foreground::Attribute {
+                attribute,
+                on: true,
+            } => SetAttribute(attribute).write_ansi(f),
+            // Bold and dim share the one code that switches either off
+            Foreground::Attribute {
+                attribute: Attribute::Bold | Attribute::Dim,
+                on: false,
+            } => SetAttribute(Attribute::NormalIntensity).write_ansi(f),
+            Foreground::Attribute { on: false, .. } => {
+                SetAttribute(Attribute::NoBlink).write_ansi(f)?;
+                SetAttribute(Attribute::NoReverse).write_ansi(f)
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        match *self {
+            Foreground::Color(color) => SetForegroundColor(color).execute_winapi(),
+            Foreground::Attribute { .. } => Ok(()),
+        }
+    }
+}
+
+/// Mono stand-in for `color`: the cue's attribute while the color is lit.
+fn mono_foreground(color: Color, cue: Option<&MonoCue>) -> Foreground {
+    match cue {
+        Some(cue) => Foreground::Attribute {
+            attribute: cue.attribute,
+            on: cue.lit(color),
+        },
+        None => Foreground::Color(Color::Reset),
+    }
+}
+
+/// How the environment variable `value` reads in a fallback explanation.
+fn describe_var(value: Option<&str>) -> String {
+    match value {
+        Some(value) if !value.is_empty() => format!("'{value}'"),
+        _ => "unset".to_string(),
+    }
+}
+
+/// Why colors are drawn below full depth and what would restore them, or
+/// None if they aren't. `requested` is the `--color-mode` given, and the
+/// variables are what `detect` looked at.
+fn explain_color(
+    requested: ColorMode,
+    resolved: ColorMode,
+    cue: Option<&MonoCue>,
+    no_color: Option<&str>,
+    colorterm: Option<&str>,
+    term: Option<&str>,
+) -> Option<String> {
+    let reduced = match resolved {
+        ColorMode::Auto | ColorMode::Truecolor => return None,
+        ColorMode::Ansi256 => "reduced to the 256-color palette",
+        ColorMode::Ansi16 => "reduced to the 16 standard colors",
+        ColorMode::Mono => match cue {
+            Some(cue) => match cue.attribute {
+                Attribute::SlowBlink => "turned off, so the animation blinks instead",
+                Attribute::Bold => "turned off, so the animation shows in bold",
+                Attribute::Dim => "turned off, so the animation shows dimmed",
+                _ => "turned off, so the animation shows in reverse video",
+            },
+            None => "turned off, so this effect shows as plain text",
+        },
+    };
+    let no_color = no_color.is_some_and(|value| !value.is_empty());
+    let (why, fix) = if requested == ColorMode::Mono {
+        (
+            "--no-color or --color-mode mono asks for it".to_string(),
+            "--color-mode truecolor",
+        )
+    } else if requested != ColorMode::Auto {
+        let name = requested
+            .to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default();
+        (
+            format!("--color-mode {name} asks for it"),
+            "--color-mode truecolor",
+        )
+    } else if no_color {
+        (
+            "NO_COLOR is set".to_string(),
+            "unsetting NO_COLOR or --color-mode truecolor",
+        )
+    } else {
+        (
+            format!(
+                "COLORTERM is {} and TERM is {}",
+                describe_var(colorterm),
+                describe_var(term)
+            ),
+            "COLORTERM=truecolor or --color-mode truecolor",
+        )
+    };
+    Some(format!(
+        "colors {reduced}, because {why}; {fix} forces full color"
+    ))
+}
+
+/// The color downgrade explained for `--explain-fallbacks`, if any, once
+/// the effect has run and set its mono cue.
+pub fn color_fallback(requested: ColorMode) -> Option<String> {
+    explain_color(
+        requested,
+        mode(),
+        MONO_CUE.get(),
+        env::var("NO_COLOR").ok().as_deref(),
+        env::var("COLORTERM").ok().as_deref(),
+        env::var("TERM").ok().as_deref(),
+    )
+}
+
+/// Foreground command for `color`, reduced to the terminal's color depth.
+/// Effects blend in RGB and call this only when writing a cell.
+pub fn fg(color: Color) -> Foreground {
+    match mode() {
+        ColorMode::Mono => mono_foreground(color, MONO_CUE.get()),
+        mode => Foreground::Color(fit_to(color, mode)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect(None, Some("truecolor"), None), ColorMode::Truecolor);
+        assert_eq!(
+            detect(None, Some("24bit"), Some("xterm")),
+            ColorMode::Truecolor
+        );
+        assert_eq!(
+            detect(None, None, Some("xterm-256color")),
+            ColorMode::Ansi256
+        );
+        assert_eq!(
+            detect(None, None, Some("xterm-direct")),
+            ColorMode::Truecolor
+        );
+        assert_eq!(detect(None, None, Some("xterm")), ColorMode::Ansi16);
+        assert_eq!(detect(None, None, Some("dumb")), ColorMode::Ansi16);
+        assert_eq!(detect(None, None, None), ColorMode::Ansi16);
+    }
+
+    #[test]
+    fn test_detect_no_color() {
+        assert_eq!(detect(Some("1"), Some("truecolor"), None), ColorMode::Mono);
+        // An empty NO_COLOR doesn't count
+        assert_eq!(
+            detect(Some(""), Some("truecolor"), None),
+            ColorMode::Truecolor
+        );
+    }
+
+    #[test]
+    fn test_explicit_modes_are_not_detected() {
+        assert_eq!(ColorMode::Ansi256.resolve(), ColorMode::Ansi256);
+        assert_eq!(ColorMode::Truecolor.resolve(), ColorMode::Truecolor);
+    }
+
+    #[test]
+    fn test_nearest_256() {
+        assert_eq!(nearest_256((255, 0, 0)), 196);
+        assert_eq!(nearest_256((0, 0, 0)), 16);
+        assert_eq!(nearest_256((255, 255, 255)), 231);
+        assert_eq!(nearest_256((128, 128, 128)), 244);
+        assert_eq!(nearest_256((95, 135, 175)), 67);
+    }
+
+    #[test]
+    fn test_nearest_16() {
+        assert_eq!(nearest_16((250, 10, 10)), Color::Red);
+        assert_eq!(nearest_16((200, 0, 0)), Color::DarkRed);
+        assert_eq!(nearest_16((255, 255, 255)), Color::White);
+        assert_eq!(nearest_16((10, 10, 10)), Color::Black);
+    }
+
+    #[test]
+    fn test_fit_to_leaves_truecolor_and_named_colors_alone() {
+        let orange = Color::Rgb {
+            r: 255,
+            g: 165,
+            b: 0,
+        };
+        assert_eq!(fit_to(orange, ColorMode::Truecolor), orange);
+        assert_eq!(fit_to(Color::Reset, ColorMode::Ansi16), Color::Reset);
+        assert_eq!(fit_to(orange, ColorMode::Ansi256), Color::AnsiValue(214));
+    }
+
+    #[test]
+    fn test_mono_cue_lights_cells_near_peak() {
+        let cue = MonoCue::reverse((100, 100, 100), (255, 255, 255));
+        let lit = |r, g, b| mono_foreground(Color::Rgb { r, g, b }, Some(&cue));
+
+        assert_eq!(
+            lit(240, 240, 240),
+            Foreground::Attribute {
+                attribute: Attribute::Reverse,
+                on: true
+            }
+        );
+        assert_eq!(
+            lit(110, 110, 110),
+            Foreground::Attribute {
+                attribute: Attribute::Reverse,
+                on: false
+            }
+        );
+        // Effects without a cue are plain text
+        assert_eq!(
+            mono_foreground(Color::Rgb { r: 255, g: 0, b: 0 }, None),
+            Foreground::Color(Color::Reset)
+        );
+    }
+
+    #[test]
+    fn test_explain_color_names_what_decided_the_depth() {
+        let auto = ColorMode::Auto;
+        assert_eq!(
+            explain_color(auto, ColorMode::Truecolor, None, None, None, None),
+            None
+        );
+
+        let detected = explain_color(
+            auto,
+            ColorMode::Ansi256,
+            None,
+            None,
+            None,
+            Some("xterm-256color"),
+        )
+        .unwrap();
+        assert!(detected.contains("256-color palette"));
+        assert!(detected.contains("COLORTERM is unset and TERM is 'xterm-256color'"));
+
+        let cue = MonoCue::blink((0, 0, 0), (255, 255, 255));
+        let no_color =
+            explain_color(auto, ColorMode::Mono, Some(&cue), Some("1"), None, None).unwrap();
+        assert!(no_color.contains("blinks instead"));
+        assert!(no_color.contains("NO_COLOR is set"));
+
+        let asked =
+            explain_color(ColorMode::Ansi16, ColorMode::Ansi16, None, None, None, None).unwrap();
+        assert!(asked.contains("--color-mode 16 asks for it"));
+
+        let cue = MonoCue::bold((80, 80, 80), (255, 255, 255));
+        let off = explain_color(
+            ColorMode::Mono,
+            ColorMode::Mono,
+            Some(&cue),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(off.contains("shows in bold"));
+        assert!(off.contains("--no-color or --color-mode mono"));
+    }
+
+    #[test]
+    fn test_mono_attributes_switch_off_together() {
+        let mut ansi = String::new();
+        Foreground::Attribute {
+            attribute: Attribute::SlowBlink,
+            on: false,
+        }
+        .write_ansi(&mut ansi)
+        .unwrap();
+        assert_eq!(ansi, "\x1b[25m\x1b[27m");
+
+        let mut ansi = String::new();
+        Foreground::Attribute {
+            attribute: Attribute::Dim,
+            on: false,
+        }
+        .write_ansi(&mut ansi)
+        .unwrap();
+        assert_eq!(ansi, "\x1b[22m");
+    }
+}
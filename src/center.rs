@@ -1,4 +1,5 @@
-use crossterm::terminal;
+use crate::screen;
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct CenteringOffsets {
@@ -6,35 +7,38 @@ pub struct CenteringOffsets {
     pub left: u16,
 }
 
-pub fn calculate_centering_offsets(input_lines: &[String]) -> Result<CenteringOffsets, Box<dyn std::error::Error>> {
-    let (terminal_width, terminal_height) = terminal::size()?;
-    
-    if input_lines.is_empty() {
-        return Ok(CenteringOffsets::default());
-    }
-    
-    // Calculate content dimensions without ANSI codes
-    let content_height = input_lines.len() as u16;
-    let max_width = input_lines
+/// Offsets that center content of `content` size (width, height) on the
+/// screen.
+pub fn calculate_centering_offsets(
+    content: (u16, u16),
+) -> Result<CenteringOffsets, Box<dyn std::error::Error>> {
+    Ok(offsets_for(content, screen::size()?))
+}
+
+/// Width and height of `input_lines` in terminal cells, without ANSI codes.
+pub fn content_size(input_lines: &[String]) -> (u16, u16) {
+    let width = input_lines
         .iter()
-        .map(|line| strip_ansi_codes(line).chars().count())
+        .map(|line| strip_ansi_codes(line).width())
         .max()
         .unwrap_or(0) as u16;
-    
-    // Calculate centering offsets
-    let top = if terminal_height > content_height {
-        (terminal_height - content_height) / 2
-    } else {
-        0
-    };
-    
-    let left = if terminal_width > max_width {
-        (terminal_width - max_width) / 2
-    } else {
-        0
-    };
-    
-    Ok(CenteringOffsets { top, left })
+    (width, input_lines.len() as u16)
+}
+
+/// Offsets that center content of `content` size (width, height) in a
+/// terminal of `terminal` size. Content too big to fit starts at the edge.
+pub fn offsets_for(
+    (content_width, content_height): (u16, u16),
+    (terminal_width, terminal_height): (u16, u16),
+) -> CenteringOffsets {
+    if content_height == 0 {
+        return CenteringOffsets::default();
+    }
+
+    CenteringOffsets {
+        top: terminal_height.saturating_sub(content_height) / 2,
+        left: terminal_width.saturating_sub(content_width) / 2,
+    }
 }
 
 pub fn strip_ansi_codes(input: &str) -> String {
@@ -60,4 +64,4 @@ pub fn strip_ansi_codes(input: &str) -> String {
     }
 
     result
-}
\ No newline at end of file
+}
@@ -0,0 +1,307 @@
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use crate::{output, playback};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+static BUFFER: Mutex<Option<ClipBuffer>> = Mutex::new(None);
+/// Every frame of the run, kept for `--export-ansi` to save at exit.
+static EXPORT: Mutex<Option<ClipBuffer>> = Mutex::new(None);
+/// Raised by SIGUSR1 and checked at the end of every frame.
+static TRIGGER: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// The output of the last `window` of frames, oldest first, ready to be
+/// saved as a clip at any moment.
+struct ClipBuffer {
+    window: Duration,
+    started: Instant,
+    /// Each frame's bytes, stamped with when it finished
+    frames: VecDeque<(Duration, Vec<u8>)>,
+    /// Bytes written since the last frame finished
+    pending: Vec<u8>,
+    saved: Vec<PathBuf>,
+}
+
+impl ClipBuffer {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            started: Instant::now(),
+            frames: VecDeque::new(),
+            pending: Vec::new(),
+            saved: Vec::new(),
+        }
+    }
+
+    /// Closes the current frame at `at` and drops frames that have aged
+    /// out of the window.
+    fn end_frame(&mut self, at: Duration) {
+        self.frames
+            .push_back((at, std::mem::take(&mut self.pending)));
+        while let Some(&(oldest, _)) = self.frames.front() {
+            if at.saturating_sub(oldest) <= self.window {
+                break;
+            }
+            self.frames.pop_front();
+        }
+    }
+
+    /// The buffered frames as an asciicast v2 recording, timed from the
+    /// oldest one.
+    fn to_cast(&self, (width, height): (u16, u16), timestamp: u64) -> String {
+        let header = json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": timestamp,
+        });
+        let origin = self.frames.front().map_or(Duration::ZERO, |&(at, _)| at);
+
+        let mut cast = format!("{header}\n");
+        for (at, bytes) in &self.frames {
+            if bytes.is_empty() {
+                continue;
+            }
+            let event = json!([
+                at.saturating_sub(origin).as_secs_f64(),
+                "o",
+                String::from_utf8_lossy(bytes),
+            ]);
+            cast.push_str(&format!("{event}\n"));
+        }
+        cast
+    }
+}
+
+/// Starts keeping the last `seconds` of frames. Sending the process SIGUSR1
+/// saves them to `clifx-clip-<time>.cast` in the current directory.
+pub fn start(seconds: f64) -> Result<(), Box<dyn std::error::Error>> {
+    if !seconds.is_finite() || seconds <= 0.0 {
+        return Err("--clip-buffer needs a positive number of seconds".into());
+    }
+
+    let trigger = TRIGGER.get_or_init(|| Arc::new(AtomicBool::new(false)));
+    #[cfg(unix)]
+    signal_hook::flag::register(signal_hook::consts::SIGUSR1, Arc::clone(trigger))?;
+
+    if let Ok(mut buffer) = BUFFER.lock() {
+        *buffer = Some(ClipBuffer::new(Duration::from_secs_f64(seconds)));
+    }
+    Ok(())
+}
+
+/// Starts keeping every frame, to save as a recording at the end.
+pub fn start_export() {
+    if let Ok(mut export) = EXPORT.lock() {
+        *export = Some(ClipBuffer::new(Duration::MAX));
+    }
+}
+
+/// Adds bytes headed for the terminal to the frame being drawn.
+pub fn record(bytes: &[u8]) {
+    if let Ok(mut export) = EXPORT.lock() {
+        if let Some(export) = export.as_mut() {
+            export.pending.extend_from_slice(bytes);
+        }
+    }
+    if TRIGGER.get().is_none() {
+        return;
+    }
+    if let Ok(mut buffer) = BUFFER.lock() {
+        if let Some(buffer) = buffer.as_mut() {
+            buffer.pending.extend_from_slice(bytes);
+        }
+    }
+}
+
+/// Closes the frame being drawn, and saves a clip if one was asked for
+/// since the last frame.
+pub fn end_frame() {
+    if let Ok(mut export) = EXPORT.lock() {
+        if let Some(export) = export.as_mut() {
+            let at = export.started.elapsed();
+            export.end_frame(at);
+        }
+    }
+    let Some(trigger) = TRIGGER.get() else {
+        return;
+    };
+    let Ok(mut buffer) = BUFFER.lock() else {
+        return;
+    };
+    let Some(buffer) = buffer.as_mut() else {
+        return;
+    };
+
+    let at = buffer.started.elapsed();
+    buffer.end_frame(at);
+    if trigger.swap(false, Ordering::Relaxed) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let size = crate::screen::size().unwrap_or((80, 24));
+        let path = PathBuf::from(format!("clifx-clip-{}.cast", timestamp.as_millis()));
+        // A failed save shouldn't stop the animation; it's reported at exit
+        if fs::write(&path, buffer.to_cast(size, timestamp.as_secs())).is_ok() {
+            buffer.saved.push(path);
+        }
+    }
+}
+
+/// Lists the clips saved during the run on stderr, once drawing is done.
+pub fn report() {
+    let Ok(buffer) = BUFFER.lock() else {
+        return;
+    };
+    for path in buffer.iter().flat_map(|buffer| &buffer.saved) {
+        eprintln!("Saved clip to {}", path.display());
+    }
+}
+
+/// Saves every frame kept since `start_export` to `path` as an asciicast
+/// recording. Does nothing unless `start_export` was called.
+pub fn write_export(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut export) = EXPORT.lock().ok().and_then(|mut export| export.take()) else {
+        return Ok(());
+    };
+    // Whatever came after the last frame, like the final newline
+    let at = export.started.elapsed();
+    export.end_frame(at);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let size = crate::screen::size().unwrap_or((80, 24));
+    fs::write(path, export.to_cast(size, timestamp.as_secs()))
+        .map_err(|err| format!("couldn't write {}: {err}", path.display()).into())
+}
+
+/// The output events of an asciicast v2 recording, each with the seconds
+/// since the recording began.
+fn parse_cast(cast: &str) -> Result<Vec<(f64, String)>, Box<dyn std::error::Error>> {
+    let mut lines = cast.lines();
+    let header: serde_json::Value = lines
+        .next()
+        .and_then(|line| serde_json::from_str(line).ok())
+        .unwrap_or_default();
+    if header["version"] != 2 {
+        return Err("not an asciicast v2 recording".into());
+    }
+
+    let mut events = Vec::new();
+    for (index, line) in lines.enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: (f64, String, String) = serde_json::from_str(line)
+            .map_err(|err| format!("event {} is malformed: {err}", index + 1))?;
+        // Input and marker events have nothing to draw
+        if event.1 == "o" {
+            events.push((event.0, event.2));
+        }
+    }
+    Ok(events)
+}
+
+/// Plays a recording saved with `--export-ansi` (or a clip) back at `rate`
+/// times the speed it was recorded at, drawing each event as a frame.
+pub fn replay(path: &Path, rate: f64) -> Result<(), Box<dyn std::error::Error>> {
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err("--rate needs a positive number".into());
+    }
+    let cast = fs::read_to_string(path)
+        .map_err(|err| format!("couldn't read {}: {err}", path.display()))?;
+    let events = parse_cast(&cast).map_err(|err| format!("{}: {err}", path.display()))?;
+
+    let _terminal = TerminalGuard::hide_cursor(false)?;
+    let mut stdout = output::stdout();
+    let mut previous = 0.0;
+    for (at, bytes) in events {
+        let wait = (at - previous).max(0.0) / rate;
+        previous = at;
+        playback::sleep(Duration::from_secs_f64(wait))?;
+
+        let frame_timer = FrameTimer::start();
+        stdout.write_all(bytes.as_bytes())?;
+        stdout.flush()?;
+        frame_timer.finish();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer_with_frames(window_ms: u64, frames: &[(u64, &str)]) -> ClipBuffer {
+        let mut buffer = ClipBuffer::new(Duration::from_millis(window_ms));
+        for &(at, bytes) in frames {
+            buffer.pending.extend_from_slice(bytes.as_bytes());
+            buffer.end_frame(Duration::from_millis(at));
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_old_frames_age_out() {
+        let buffer = buffer_with_frames(1000, &[(0, "a"), (600, "b"), (1200, "c"), (1500, "d")]);
+        let kept: Vec<u64> = buffer
+            .frames
+            .iter()
+            .map(|(at, _)| at.as_millis() as u64)
+            .collect();
+        assert_eq!(kept, vec![600, 1200, 1500]);
+    }
+
+    #[test]
+    fn test_to_cast_times_from_oldest_frame() {
+        let buffer = buffer_with_frames(5000, &[(1000, "\x1b[1Ghi"), (1500, ""), (2250, "yo")]);
+        let cast = buffer.to_cast((80, 24), 1_700_000_000);
+        let lines: Vec<&str> = cast.lines().collect();
+
+        let header: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(header["version"], 2);
+        assert_eq!(header["width"], 80);
+        assert_eq!(header["height"], 24);
+
+        // Empty frames add no events
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], r#"[0.0,"o","\u001b[1Ghi"]"#);
+        assert_eq!(lines[2], r#"[1.25,"o","yo"]"#);
+    }
+
+    #[test]
+    fn test_export_keeps_every_frame() {
+        let buffer = buffer_with_frames(u64::MAX, &[(0, "a"), (60_000, "b"), (600_000, "c")]);
+        assert_eq!(buffer.frames.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_cast_reads_back_output_events() {
+        let buffer = buffer_with_frames(5000, &[(1000, "\x1b[1Ghi"), (2250, "yo")]);
+        let events = parse_cast(&buffer.to_cast((80, 24), 0)).unwrap();
+        assert_eq!(
+            events,
+            vec![(0.0, "\x1b[1Ghi".to_string()), (1.25, "yo".to_string())]
+        );
+
+        let with_input = "{\"version\": 2, \"width\": 80, \"height\": 24}\n[0.5, \"i\", \"q\"]\n[1.0, \"o\", \"x\"]\n";
+        assert_eq!(
+            parse_cast(with_input).unwrap(),
+            vec![(1.0, "x".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_cast_rejects_other_files() {
+        assert!(parse_cast("hello\n").is_err());
+        assert!(parse_cast("{\"version\": 1}\n").is_err());
+        let err = parse_cast("{\"version\": 2}\n[0.0, \"o\"]\n").unwrap_err();
+        assert!(err.to_string().contains("event 1"));
+    }
+}
@@ -0,0 +1,432 @@
+use crate::palette;
+
+pub type Rgb = (u8, u8, u8);
+
+const FORMAT_HELP: &str = "Color must be r,g,b (e.g., 255,255,0), hex (#ffcc00 or 0xffcc00), \
+a CSS color name (e.g., gold), or hsl(h,s%,l%)";
+
+/// Parses a color in any of the supported notations:
+/// `r,g,b`, `#rrggbb`, `#rgb`, `0xrrggbb`, CSS/X11 names, or `hsl(h,s%,l%)`.
+pub fn parse_color(color_str: &str) -> Result<Rgb, Box<dyn std::error::Error>> {
+    let trimmed = color_str.trim();
+
+    if trimmed.is_empty() {
+        return Err(FORMAT_HELP.into());
+    }
+
+    if let Some(hex) = trimmed.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+
+    if let Some(hex) = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+    {
+        return parse_hex(hex);
+    }
+
+    let lower = trimmed.to_ascii_lowercase();
+    if let Some(args) = lower
+        .strip_prefix("hsl(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        return parse_hsl(args);
+    }
+
+    if trimmed.contains(',') {
+        return parse_rgb_triplet(trimmed);
+    }
+
+    lookup_named_color(&lower)
+        .ok_or_else(|| format!("Unknown color '{trimmed}'. {FORMAT_HELP}").into())
+}
+
+/// Parses a comma-separated list of colors such as `"red,#ffa500,hsl(60,100%,50%)"`.
+/// Commas inside `hsl()` belong to that color, and runs of three bare numbers
+/// are read as one `r,g,b` triplet, so `"255,0,0,yellow"` is two colors.
+/// The name of a built-in palette, such as `"sunset"`, stands for its stops.
+pub fn parse_color_list(list: &str) -> Result<Vec<Rgb>, Box<dyn std::error::Error>> {
+    if let Some(palette) = palette::named(list.trim()) {
+        return Ok(palette.stops.to_vec());
+    }
+
+    let mut tokens = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, ch) in list.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                tokens.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    tokens.push(list[start..].trim());
+
+    let mut colors = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let is_number =
+            |token: &str| !token.is_empty() && token.chars().all(|c| c.is_ascii_digit());
+        if is_number(tokens[i]) {
+            let triplet = tokens.get(i..i + 3).ok_or(FORMAT_HELP)?;
+            colors.push(parse_rgb_triplet(&triplet.join(","))?);
+            i += 3;
+        } else {
+            colors.push(parse_color(tokens[i])?);
+            i += 1;
+        }
+    }
+
+    Ok(colors)
+}
+
+fn parse_rgb_triplet(color_str: &str) -> Result<Rgb, Box<dyn std::error::Error>> {
+    let parts: Vec<&str> = color_str.split(',').collect();
+    if parts.len() != 3 {
+        return Err(FORMAT_HELP.into());
+    }
+
+    let r = parts[0].trim().parse::<u8>()?;
+    let g = parts[1].trim().parse::<u8>()?;
+    let b = parts[2].trim().parse::<u8>()?;
+
+    Ok((r, g, b))
+}
+
+fn parse_hex(hex: &str) -> Result<Rgb, Box<dyn std::error::Error>> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid hex color '{hex}'. {FORMAT_HELP}").into());
+    }
+
+    match hex.len() {
+        3 => {
+            let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).map(|d| d * 17);
+            Ok((digit(0)?, digit(1)?, digit(2)?))
+        }
+        6 => {
+            let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+            Ok((byte(0)?, byte(2)?, byte(4)?))
+        }
+        _ => Err(format!("Hex color '{hex}' must have 3 or 6 digits. {FORMAT_HELP}").into()),
+    }
+}
+
+fn parse_hsl(args: &str) -> Result<Rgb, Box<dyn std::error::Error>> {
+    let parts: Vec<&str> = args.split(',').map(|part| part.trim()).collect();
+    if parts.len() != 3 {
+        return Err(format!("hsl() takes three components. {FORMAT_HELP}").into());
+    }
+
+    let hue = parts[0].trim_end_matches("deg").parse::<f32>()?;
+    let saturation = parse_percentage(parts[1])?;
+    let lightness = parse_percentage(parts[2])?;
+
+    Ok(hsl_to_rgb(hue, saturation, lightness))
+}
+
+fn parse_percentage(value: &str) -> Result<f32, Box<dyn std::error::Error>> {
+    let number = value.trim_end_matches('%').trim().parse::<f32>()?;
+    if !(0.0..=100.0).contains(&number) {
+        return Err(format!("Percentage '{value}' must be between 0% and 100%").into());
+    }
+    Ok(number / 100.0)
+}
+
+/// Converts HSL (hue in degrees, saturation and lightness in 0.0..=1.0) to RGB.
+pub fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> Rgb {
+    let hue = hue.rem_euclid(360.0);
+    let c = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = lightness - c / 2.0;
+
+    let (r_prime, g_prime, b_prime) = if hue < 60.0 {
+        (c, x, 0.0)
+    } else if hue < 120.0 {
+        (x, c, 0.0)
+    } else if hue < 180.0 {
+        (0.0, c, x)
+    } else if hue < 240.0 {
+        (0.0, x, c)
+    } else if hue < 300.0 {
+        (x, 0.0, c)
+    } else {
+        (c, 0.0, x)
+    };
+
+    let to_byte = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (to_byte(r_prime), to_byte(g_prime), to_byte(b_prime))
+}
+
+fn lookup_named_color(name: &str) -> Option<Rgb> {
+    let normalized: String = name
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .collect();
+
+    NAMED_COLORS
+        .binary_search_by(|(candidate, _)| candidate.cmp(&normalized.as_str()))
+        .ok()
+        .map(|index| NAMED_COLORS[index].1)
+}
+
+/// CSS Color Module Level 4 named colors (a superset of the common X11 names),
+/// sorted by name for binary search.
+const NAMED_COLORS: &[(&str, Rgb)] = &[
+    ("aliceblue", (240, 248, 255)),
+    ("antiquewhite", (250, 235, 215)),
+    ("aqua", (0, 255, 255)),
+    ("aquamarine", (127, 255, 212)),
+    ("azure", (240, 255, 255)),
+    ("beige", (245, 245, 220)),
+    ("bisque", (255, 228, 196)),
+    ("black", (0, 0, 0)),
+    ("blanchedalmond", (255, 235, 205)),
+    ("blue", (0, 0, 255)),
+    ("blueviolet", (138, 43, 226)),
+    ("brown", (165, 42, 42)),
+    ("burlywood", (222, 184, 135)),
+    ("cadetblue", (95, 158, 160)),
+    ("chartreuse", (127, 255, 0)),
+    ("chocolate", (210, 105, 30)),
+    ("coral", (255, 127, 80)),
+    ("cornflowerblue", (100, 149, 237)),
+    ("cornsilk", (255, 248, 220)),
+    ("crimson", (220, 20, 60)),
+    ("cyan", (0, 255, 255)),
+    ("darkblue", (0, 0, 139)),
+    ("darkcyan", (0, 139, 139)),
+    ("darkgoldenrod", (184, 134, 11)),
+    ("darkgray", (169, 169, 169)),
+    ("darkgreen", (0, 100, 0)),
+    ("darkgrey", (169, 169, 169)),
+    ("darkkhaki", (189, 183, 107)),
+    ("darkmagenta", (139, 0, 139)),
+    ("darkolivegreen", (85, 107, 47)),
+    ("darkorange", (255, 140, 0)),
+    ("darkorchid", (153, 50, 204)),
+    ("darkred", (139, 0, 0)),
+    ("darksalmon", (233, 150, 122)),
+    ("darkseagreen", (143, 188, 143)),
+    ("darkslateblue", (72, 61, 139)),
+    ("darkslategray", (47, 79, 79)),
+    ("darkslategrey", (47, 79, 79)),
+    ("darkturquoise", (0, 206, 209)),
+    ("darkviolet", (148, 0, 211)),
+    ("deeppink", (255, 20, 147)),
+    ("deepskyblue", (0, 191, 255)),
+    ("dimgray", (105, 105, 105)),
+    ("dimgrey", (105, 105, 105)),
+    ("dodgerblue", (30, 144, 255)),
+    ("firebrick", (178, 34, 34)),
+    ("floralwhite", (255, 250, 240)),
+    ("forestgreen", (34, 139, 34)),
+    ("fuchsia", (255, 0, 255)),
+    ("gainsboro", (220, 220, 220)),
+    ("ghostwhite", (248, 248, 255)),
+    ("gold", (255, 215, 0)),
+    ("goldenrod", (218, 165, 32)),
+    ("gray", (128, 128, 128)),
+    ("green", (0, 128, 0)),
+    ("greenyellow", (173, 255, 47)),
+    ("grey", (128, 128, 128)),
+    ("honeydew", (240, 255, 240)),
+    ("hotpink", (255, 105, 180)),
+    ("indianred", (205, 92, 92)),
+    ("indigo", (75, 0, 130)),
+    ("ivory", (255, 255, 240)),
+    ("khaki", (240, 230, 140)),
+    ("lavender", (230, 230, 250)),
+    ("lavenderblush", (255, 240, 245)),
+    ("lawngreen", (124, 252, 0)),
+    ("lemonchiffon", (255, 250, 205)),
+    ("lightblue", (173, 216, 230)),
+    ("lightcoral", (240, 128, 128)),
+    ("lightcyan", (224, 255, 255)),
+    ("lightgoldenrodyellow", (250, 250, 210)),
+    ("lightgray", (211, 211, 211)),
+    ("lightgreen", (144, 238, 144)),
+    ("lightgrey", (211, 211, 211)),
+    ("lightpink", (255, 182, 193)),
+    ("lightsalmon", (255, 160, 122)),
+    ("lightseagreen", (32, 178, 170)),
+    ("lightskyblue", (135, 206, 250)),
+    ("lightslategray", (119, 136, 153)),
+    ("lightslategrey", (119, 136, 153)),
+    ("lightsteelblue", (176, 196, 222)),
+    ("lightyellow", (255, 255, 224)),
+    ("lime", (0, 255, 0)),
+    ("limegreen", (50, 205, 50)),
+    ("linen", (250, 240, 230)),
+    ("magenta", (255, 0, 255)),
+    ("maroon", (128, 0, 0)),
+    ("mediumaquamarine", (102, 205, 170)),
+    ("mediumblue", (0, 0, 205)),
+    ("mediumorchid", (186, 85, 211)),
+    ("mediumpurple", (147, 112, 219)),
+    ("mediumseagreen", (60, 179, 113)),
+    ("mediumslateblue", (123, 104, 238)),
+    ("mediumspringgreen", (0, 250, 154)),
+    ("mediumturquoise", (72, 209, 204)),
+    ("mediumvioletred", (199, 21, 133)),
+    ("midnightblue", (25, 25, 112)),
+    ("mintcream", (245, 255, 250)),
+    ("mistyrose", (255, 228, 225)),
+    ("moccasin", (255, 228, 181)),
+    ("navajowhite", (255, 222, 173)),
+    ("navy", (0, 0, 128)),
+    ("oldlace", (253, 245, 230)),
+    ("olive", (128, 128, 0)),
+    ("olivedrab", (107, 142, 35)),
+    ("orange", (255, 165, 0)),
+    ("orangered", (255, 69, 0)),
+    ("orchid", (218, 112, 214)),
+    ("palegoldenrod", (238, 232, 170)),
+    ("palegreen", (152, 251, 152)),
+    ("paleturquoise", (175, 238, 238)),
+    ("palevioletred", (219, 112, 147)),
+    ("papayawhip", (255, 239, 213)),
+    ("peachpuff", (255, 218, 185)),
+    ("peru", (205, 133, 63)),
+    ("pink", (255, 192, 203)),
+    ("plum", (221, 160, 221)),
+    ("powderblue", (176, 224, 230)),
+    ("purple", (128, 0, 128)),
+    ("rebeccapurple", (102, 51, 153)),
+    ("red", (255, 0, 0)),
+    ("rosybrown", (188, 143, 143)),
+    ("royalblue", (65, 105, 225)),
+    ("saddlebrown", (139, 69, 19)),
+    ("salmon", (250, 128, 114)),
+    ("sandybrown", (244, 164, 96)),
+    ("seagreen", (46, 139, 87)),
+    ("seashell", (255, 245, 238)),
+    ("sienna", (160, 82, 45)),
+    ("silver", (192, 192, 192)),
+    ("skyblue", (135, 206, 235)),
+    ("slateblue", (106, 90, 205)),
+    ("slategray", (112, 128, 144)),
+    ("slategrey", (112, 128, 144)),
+    ("snow", (255, 250, 250)),
+    ("springgreen", (0, 255, 127)),
+    ("steelblue", (70, 130, 180)),
+    ("tan", (210, 180, 140)),
+    ("teal", (0, 128, 128)),
+    ("thistle", (216, 191, 216)),
+    ("tomato", (255, 99, 71)),
+    ("turquoise", (64, 224, 208)),
+    ("violet", (238, 130, 238)),
+    ("wheat", (245, 222, 179)),
+    ("white", (255, 255, 255)),
+    ("whitesmoke", (245, 245, 245)),
+    ("yellow", (255, 255, 0)),
+    ("yellowgreen", (154, 205, 50)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rgb_color_valid() {
+        assert_eq!(parse_color("255,0,0").unwrap(), (255, 0, 0));
+        assert_eq!(parse_color("0,255,0").unwrap(), (0, 255, 0));
+        assert_eq!(parse_color("0,0,255").unwrap(), (0, 0, 255));
+        assert_eq!(parse_color("128,128,128").unwrap(), (128, 128, 128));
+    }
+
+    #[test]
+    fn test_parse_rgb_color_with_whitespace() {
+        assert_eq!(parse_color(" 255 , 0 , 0 ").unwrap(), (255, 0, 0));
+        assert_eq!(parse_color("255, 128, 64").unwrap(), (255, 128, 64));
+    }
+
+    #[test]
+    fn test_parse_rgb_color_invalid_format() {
+        assert!(parse_color("255,0").is_err());
+        assert!(parse_color("255,0,0,255").is_err());
+        assert!(parse_color("255").is_err());
+        assert!(parse_color("").is_err());
+    }
+
+    #[test]
+    fn test_parse_rgb_color_invalid_values() {
+        assert!(parse_color("256,0,0").is_err());
+        assert!(parse_color("-1,0,0").is_err());
+        assert!(parse_color("abc,0,0").is_err());
+        assert!(parse_color("255,256,0").is_err());
+    }
+
+    #[test]
+    fn test_parse_hex_colors() {
+        assert_eq!(parse_color("#ffcc00").unwrap(), (255, 204, 0));
+        assert_eq!(parse_color("#FFCC00").unwrap(), (255, 204, 0));
+        assert_eq!(parse_color("0xffcc00").unwrap(), (255, 204, 0));
+        assert_eq!(parse_color("#fc0").unwrap(), (255, 204, 0));
+    }
+
+    #[test]
+    fn test_parse_hex_colors_invalid() {
+        assert!(parse_color("#ffcc0").is_err());
+        assert!(parse_color("#gggggg").is_err());
+        assert!(parse_color("0x").is_err());
+    }
+
+    #[test]
+    fn test_parse_named_colors() {
+        assert_eq!(parse_color("gold").unwrap(), (255, 215, 0));
+        assert_eq!(parse_color("DodgerBlue").unwrap(), (30, 144, 255));
+        assert_eq!(parse_color("dodger-blue").unwrap(), (30, 144, 255));
+        assert!(parse_color("notacolor").is_err());
+    }
+
+    #[test]
+    fn test_named_colors_sorted() {
+        for pair in NAMED_COLORS.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "{} >= {}", pair[0].0, pair[1].0);
+        }
+    }
+
+    #[test]
+    fn test_parse_hsl_colors() {
+        assert_eq!(parse_color("hsl(0,100%,50%)").unwrap(), (255, 0, 0));
+        assert_eq!(parse_color("hsl(120, 100%, 50%)").unwrap(), (0, 255, 0));
+        assert_eq!(parse_color("HSL(240,100%,50%)").unwrap(), (0, 0, 255));
+        assert_eq!(parse_color("hsl(40,100%,50%)").unwrap(), (255, 170, 0));
+        assert_eq!(parse_color("hsl(0,0%,100%)").unwrap(), (255, 255, 255));
+    }
+
+    #[test]
+    fn test_parse_hsl_colors_invalid() {
+        assert!(parse_color("hsl(0,100%)").is_err());
+        assert!(parse_color("hsl(0,150%,50%)").is_err());
+        assert!(parse_color("hsl(a,100%,50%)").is_err());
+    }
+
+    #[test]
+    fn test_parse_color_list() {
+        assert_eq!(
+            parse_color_list("red, #00ff00,hsl(240,100%,50%)").unwrap(),
+            vec![(255, 0, 0), (0, 255, 0), (0, 0, 255)]
+        );
+        assert_eq!(
+            parse_color_list("255,0,0,yellow,0,0,255").unwrap(),
+            vec![(255, 0, 0), (255, 255, 0), (0, 0, 255)]
+        );
+        assert_eq!(parse_color_list("white").unwrap(), vec![(255, 255, 255)]);
+        assert_eq!(
+            parse_color_list(" sunset ").unwrap(),
+            crate::palette::named("sunset").unwrap().stops
+        );
+    }
+
+    #[test]
+    fn test_parse_color_list_invalid() {
+        assert!(parse_color_list("255,0").is_err());
+        assert!(parse_color_list("red,,blue").is_err());
+        assert!(parse_color_list("red,notacolor").is_err());
+    }
+}
@@ -0,0 +1,282 @@
+use crate::center::offsets_for;
+use crate::effects::frame::Frame;
+use crate::effects::repaint::Repaint;
+use crate::effects::stage::rgb_color;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::Placement;
+use crate::screen::{self, TerminalGuard};
+use crate::soak::FrameTimer;
+use crate::vt::Screen;
+use crossterm::{
+    queue,
+    style::{Color, Print},
+    terminal::{self, ClearType},
+};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+use std::time::Duration;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Drawn between neighbouring panes.
+const DIVIDER: &str = " │ ";
+
+/// Color of the labels and divider, dim so the panes stand out.
+const CHROME: (u8, u8, u8) = (110, 110, 110);
+
+/// One pane of a comparison: its label, and the arguments its clifx run
+/// takes after `clifx`.
+pub struct Side {
+    pub label: String,
+    pub args: Vec<String>,
+}
+
+pub struct CompareConfig {
+    /// Milliseconds between redraws of the panes
+    pub speed: u64,
+}
+
+impl Default for CompareConfig {
+    fn default() -> Self {
+        Self { speed: 30 }
+    }
+}
+
+/// Splits a configuration like `shine --color "255, 0, 0"` into words the
+/// way a shell would, honoring quotes and backslashes.
+pub fn split_words(config: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = config.chars();
+
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some(open), ch) if ch == open => quote = None,
+            (Some('"') | None, '\\') => {
+                let escaped = chars.next().ok_or("trailing backslash")?;
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            (Some(_), ch) => word.get_or_insert_with(String::new).push(ch),
+            (None, '"' | '\'') => {
+                quote = Some(ch);
+                word.get_or_insert_with(String::new);
+            }
+            (None, ch) if ch.is_whitespace() => words.extend(word.take()),
+            (None, ch) => word.get_or_insert_with(String::new).push(ch),
+        }
+    }
+
+    if quote.is_some() {
+        return Err("unclosed quote".into());
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Places `text` on row `y` of `frame` from column `x`.
+fn put_text(frame: &mut Frame, x: usize, y: usize, text: &str, color: Color) {
+    let mut column = x;
+    for ch in text.chars() {
+        frame.put(column, y, ch, color);
+        column += ch.width().unwrap_or(0);
+    }
+}
+
+/// Every pane side by side under its label, with a divider between them.
+/// Returns the frame with its size (width, height).
+fn layout(panes: &[(&str, &Screen)]) -> (Frame, (usize, usize)) {
+    let widths: Vec<usize> = panes
+        .iter()
+        .map(|(label, pane)| pane.width().max(label.width()))
+        .collect();
+    let divider = DIVIDER.width();
+    let width = widths.iter().sum::<usize>() + divider * panes.len().saturating_sub(1);
+    let height = 1 + panes
+        .iter()
+        .map(|(_, pane)| pane.rows().len())
+        .max()
+        .unwrap_or(0);
+
+    let chrome = rgb_color(CHROME);
+    let mut frame = Frame::new(width, height);
+    let mut left = 0;
+    for (index, ((label, pane), pane_width)) in panes.iter().zip(widths).enumerate() {
+        if index > 0 {
+            for y in 0..height {
+                put_text(&mut frame, left, y, DIVIDER, chrome);
+            }
+            left += divider;
+        }
+        put_text(&mut frame, left, 0, label, chrome);
+        for (y, row) in pane.rows().iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if let Some((ch, color)) = *cell {
+                    frame.put(left + x, y + 1, ch, color.unwrap_or(Color::Reset));
+                }
+            }
+        }
+        left += pane_width;
+    }
+    (frame, (width, height))
+}
+
+/// The runs behind the panes, stopped if the comparison ends early.
+struct Runs(Vec<Child>);
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for child in &mut self.0 {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Runs clifx once per side on `input_lines` and shows their output side by
+/// side. Every run waits for the end of its input, so closing all of their
+/// stdins together starts their animations on the same clock; the panes are
+/// then redrawn together every `config.speed` milliseconds.
+pub fn run(
+    sides: &[Side],
+    input_lines: &[String],
+    config: &CompareConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let exe = std::env::current_exe()?;
+    let (sender, receiver) = mpsc::channel();
+    let mut runs = Runs(Vec::new());
+    for (index, side) in sides.iter().enumerate() {
+        let mut child = Command::new(&exe)
+            .args(&side.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut output = child.stdout.take().ok_or("no output from compared run")?;
+        let sender = sender.clone();
+        thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            while let Ok(read @ 1..) = output.read(&mut buffer) {
+                if sender.send((index, buffer[..read].to_vec())).is_err() {
+                    break;
+                }
+            }
+        });
+        runs.0.push(child);
+    }
+    drop(sender);
+
+    let input: String = input_lines.iter().map(|line| format!("{line}\n")).collect();
+    let stdins: Vec<_> = runs
+        .0
+        .iter_mut()
+        .filter_map(|child| child.stdin.take())
+        .collect();
+    for mut stdin in stdins {
+        // Effects that read no input may already be gone
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    let mut panes: Vec<Screen> = sides.iter().map(|_| Screen::default()).collect();
+    let frame_duration = Duration::from_millis(config.speed);
+    let mut stdout = stdout();
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    let mut drawn_height = None;
+    let mut running = true;
+    while running {
+        // Catch up on everything written since the last frame
+        loop {
+            match receiver.try_recv() {
+                Ok((index, bytes)) => panes[index].feed(&bytes),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    running = false;
+                    break;
+                }
+            }
+        }
+
+        let labeled: Vec<_> = sides
+            .iter()
+            .zip(&panes)
+            .map(|(side, pane)| (side.label.as_str(), pane))
+            .collect();
+        let (frame, (width, height)) = layout(&labeled);
+
+        let frame_timer = FrameTimer::start();
+        if let Some(offsets) = centering_offsets.as_mut() {
+            // The panes grow as the runs draw, so keep them centered as they do
+            let centered = offsets_for((width as u16, height as u16), screen::size()?);
+            if *offsets != (centered.top, centered.left) {
+                *offsets = (centered.top, centered.left);
+                if screen::may_clear_screen() {
+                    queue!(stdout, terminal::Clear(ClearType::All))?;
+                }
+            }
+        } else if let Some(previous) = drawn_height {
+            // Make room below for rows added since the last frame
+            for _ in previous..height {
+                queue!(stdout, Print("\r\n"))?;
+            }
+        }
+        if running {
+            repaint.draw_on_time(frame, &mut stdout, centering_offsets)?;
+        } else {
+            // Every run has finished, so this is the last frame
+            repaint.draw(frame, &mut stdout, centering_offsets)?;
+        }
+        stdout.flush()?;
+        frame_timer.finish();
+        drawn_height = Some(height);
+
+        if running {
+            playback::sleep(frame_duration)?;
+        }
+    }
+
+    for (side, child) in sides.iter().zip(&mut runs.0) {
+        let status = child.wait()?;
+        if !status.success() {
+            let mut message = String::new();
+            if let Some(mut stderr) = child.stderr.take() {
+                stderr.read_to_string(&mut message)?;
+            }
+            return Err(format!("'{}' failed: {}", side.label, message.trim()).into());
+        }
+    }
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(
+            split_words(r#"shine --color "255, 0, 0"  --speed 10"#).unwrap(),
+            vec!["shine", "--color", "255, 0, 0", "--speed", "10"]
+        );
+        assert_eq!(split_words(r"a\ b ''").unwrap(), vec!["a b", ""]);
+        assert!(split_words("shine --color 'red").is_err());
+    }
+
+    #[test]
+    fn test_layout_puts_panes_side_by_side() {
+        let mut left = Screen::default();
+        left.feed(b"one");
+        let mut right = Screen::default();
+        right.feed(b"2\n2");
+
+        let (_, size) = layout(&[("left side", &left), ("r", &right)]);
+        assert_eq!(size, (9 + DIVIDER.width() + 1, 3));
+    }
+}
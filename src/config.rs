@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The user's `config.toml`, holding named presets and sprites, and the
+/// theme to use when `--theme` isn't given:
+///
+/// ```toml
+/// theme = "ice"
+///
+/// [presets.gold-banner]
+/// args = ["--center", "shine", "--color", "gold", "--shine-color", "white"]
+///
+/// [sprites]
+/// spin = "+x*x@12"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Config {
+    /// A built-in theme's name, for every run that doesn't pick one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub presets: BTreeMap<String, Preset>,
+    /// Animated glyphs, as `GLYPHS[@FPS]`, that `--sprite` can name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sprites: BTreeMap<String, String>,
+}
+
+/// An effect and its flags, stored exactly as they'd be typed after `clifx`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Preset {
+    pub args: Vec<String>,
+}
+
+/// `$XDG_CONFIG_HOME/clifx/config.toml`, falling back to `~/.config`.
+pub fn config_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("clifx").join("config.toml"))
+}
+
+impl Config {
+    /// Reads the config at `path`; a missing file is an empty config.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text)
+                .map_err(|err| format!("invalid config {}: {err}", path.display()).into()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(format!("cannot read config {}: {err}", path.display()).into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Looks up the preset called `name` in the user's config.
+pub fn load_preset(name: &str) -> Result<Preset, Box<dyn std::error::Error>> {
+    let path = config_path().ok_or("cannot locate the config directory (HOME is not set)")?;
+    Config::load(&path)?
+        .presets
+        .remove(name)
+        .ok_or_else(|| format!("no preset named '{name}' in {}", path.display()).into())
+}
+
+/// The sprite definitions in the user's config, or none if there's no
+/// config to read them from.
+pub fn load_sprites() -> Result<BTreeMap<String, String>, Box<dyn std::error::Error>> {
+    match config_path() {
+        Some(path) => Ok(Config::load(&path)?.sprites),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// The theme the user's config picks, if there's a config and it does.
+pub fn load_theme() -> Result<Option<String>, Box<dyn std::error::Error>> {
+    match config_path() {
+        Some(path) => Ok(Config::load(&path)?.theme),
+        None => Ok(None),
+    }
+}
+
+/// Every preset in the user's config, or none if there's no config to read.
+pub fn load_presets() -> Result<BTreeMap<String, Preset>, Box<dyn std::error::Error>> {
+    match config_path() {
+        Some(path) => Ok(Config::load(&path)?.presets),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Stores `args` as the preset `name`, replacing any preset of that name.
+/// Returns the path of the config file written.
+pub fn save_preset(name: &str, args: &[String]) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = config_path().ok_or("cannot locate the config directory (HOME is not set)")?;
+    let mut config = Config::load(&path)?;
+    config.presets.insert(
+        name.to_string(),
+        Preset {
+            args: args.to_vec(),
+        },
+    );
+    config.save(&path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_presets() {
+        let config: Config = toml::from_str(
+            r#"
+            [presets.gold-banner]
+            args = ["--center", "shine", "--color", "gold"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.presets["gold-banner"].args,
+            vec!["--center", "shine", "--color", "gold"]
+        );
+    }
+
+    #[test]
+    fn test_parse_sprites() {
+        let config: Config = toml::from_str(
+            r#"
+            [sprites]
+            spin = "+x*x@12"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.sprites["spin"], "+x*x@12");
+        assert!(config.presets.is_empty());
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        let config: Config = toml::from_str(
+            r#"
+            theme = "matrix"
+
+            [presets.calm]
+            args = ["twinkle"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.theme.as_deref(), Some("matrix"));
+        assert_eq!(config.presets["calm"].args, vec!["twinkle"]);
+    }
+
+    #[test]
+    fn test_empty_config_has_no_presets() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.presets.is_empty());
+    }
+
+    #[test]
+    fn test_missing_file_loads_empty_and_save_round_trips() {
+        let dir = env::temp_dir().join(format!("clifx-config-test-{}", std::process::id()));
+        let path = dir.join("nested").join("config.toml");
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+        let mut config = Config::default();
+        config.presets.insert(
+            "calm".to_string(),
+            Preset {
+                args: vec![
+                    "twinkle".to_string(),
+                    "--speed".to_string(),
+                    "200".to_string(),
+                ],
+            },
+        );
+        config.save(&path).unwrap();
+        assert_eq!(Config::load(&path).unwrap(), config);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_config_is_an_error() {
+        let dir = env::temp_dir().join(format!("clifx-bad-config-{}", std::process::id()));
+        let path = dir.join("config.toml");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "presets = 3").unwrap();
+
+        let err = Config::load(&path).unwrap_err();
+        assert!(err.to_string().contains("invalid config"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
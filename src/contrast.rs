@@ -0,0 +1,112 @@
+/// Relative luminance of an sRGB color as defined by WCAG 2.x.
+pub fn relative_luminance(color: (u8, u8, u8)) -> f32 {
+    fn channel(value: u8) -> f32 {
+        let c = value as f32 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    0.2126 * channel(color.0) + 0.7152 * channel(color.1) + 0.0722 * channel(color.2)
+}
+
+/// WCAG contrast ratio between two colors, ranging from 1.0 to 21.0.
+pub fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f32 {
+    let la = relative_luminance(a);
+    let lb = relative_luminance(b);
+    let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+fn mix(from: (u8, u8, u8), to: (u8, u8, u8), t: f32) -> (u8, u8, u8) {
+    let t = t.clamp(0.0, 1.0);
+    let lerp = |a: u8, b: u8| (a as f32 * (1.0 - t) + b as f32 * t).round() as u8;
+    (lerp(from.0, to.0), lerp(from.1, to.1), lerp(from.2, to.2))
+}
+
+/// Adjusts `shine` so that the brightest point of the band (the shine blended
+/// over `base` at `opacity`) reaches at least `min_ratio` contrast against
+/// `base`. The shine is pushed toward white or black, whichever direction can
+/// gain more contrast over the base. If the target is unreachable the most
+/// contrasting candidate is returned.
+pub fn ensure_contrast(
+    shine: (u8, u8, u8),
+    base: (u8, u8, u8),
+    opacity: f32,
+    min_ratio: f32,
+) -> (u8, u8, u8) {
+    let peak = |candidate: (u8, u8, u8)| mix(base, candidate, opacity);
+
+    if contrast_ratio(peak(shine), base) >= min_ratio {
+        return shine;
+    }
+
+    let target = if contrast_ratio(base, (255, 255, 255)) >= contrast_ratio(base, (0, 0, 0)) {
+        (255, 255, 255)
+    } else {
+        (0, 0, 0)
+    };
+
+    const STEPS: usize = 20;
+    for step in 1..=STEPS {
+        let candidate = mix(shine, target, step as f32 / STEPS as f32);
+        if contrast_ratio(peak(candidate), base) >= min_ratio {
+            return candidate;
+        }
+    }
+
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_relative_luminance_extremes() {
+        assert_approx_eq!(relative_luminance((0, 0, 0)), 0.0, 0.0001);
+        assert_approx_eq!(relative_luminance((255, 255, 255)), 1.0, 0.0001);
+    }
+
+    #[test]
+    fn test_contrast_ratio_black_white() {
+        assert_approx_eq!(contrast_ratio((0, 0, 0), (255, 255, 255)), 21.0, 0.01);
+        assert_approx_eq!(contrast_ratio((255, 255, 255), (0, 0, 0)), 21.0, 0.01);
+        assert_approx_eq!(
+            contrast_ratio((128, 128, 128), (128, 128, 128)),
+            1.0,
+            0.0001
+        );
+    }
+
+    #[test]
+    fn test_ensure_contrast_keeps_sufficient_shine() {
+        let shine = ensure_contrast((255, 255, 255), (0, 0, 255), 1.0, 3.0);
+        assert_eq!(shine, (255, 255, 255));
+    }
+
+    #[test]
+    fn test_ensure_contrast_white_on_near_white_goes_dark() {
+        let base = (240, 240, 240);
+        let shine = ensure_contrast((255, 255, 255), base, 1.0, 3.0);
+        assert!(contrast_ratio(shine, base) >= 3.0);
+        assert!(relative_luminance(shine) < relative_luminance(base));
+    }
+
+    #[test]
+    fn test_ensure_contrast_accounts_for_opacity() {
+        let base = (20, 20, 20);
+        let shine = ensure_contrast((60, 60, 60), base, 0.5, 3.0);
+        assert!(contrast_ratio(mix(base, shine, 0.5), base) >= 3.0);
+    }
+
+    #[test]
+    fn test_ensure_contrast_unreachable_returns_extreme() {
+        // At 10% opacity nothing can reach 21:1 over mid-gray
+        let shine = ensure_contrast((128, 128, 128), (128, 128, 128), 0.1, 21.0);
+        assert!(shine == (255, 255, 255) || shine == (0, 0, 0));
+    }
+}
@@ -0,0 +1,280 @@
+use crate::playback;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// Longest a reply waits to be written before clifx carries on without it.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// What a client can ask for, one command per line:
+///
+/// ```text
+/// text Build passed\nall green    show new text (\n starts another line)
+/// effect pulse --color gold       switch to another effect and flags
+/// color red                       give the effect a new --color
+/// preset calm                     switch to a saved preset
+/// trigger confetti --from text    run an effect once, then carry on
+/// quit (or stop)                  stop
+/// ```
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// New text for the effect, one entry per line
+    Text(Vec<String>),
+    /// Effect and flags to switch the look to, as typed after `clifx`
+    Effect(Vec<String>),
+    /// A color for the current effect's `--color`
+    Color(String),
+    /// A saved preset to switch the look to
+    Preset(String),
+    /// Flags and effect to run once over the current text, as typed after `clifx`
+    Trigger(Vec<String>),
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (name, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match name {
+            "text" => Ok(Command::Text(
+                rest.split("\\n").map(str::to_string).collect(),
+            )),
+            "effect" if !rest.trim().is_empty() => Ok(Command::Effect(
+                rest.split_whitespace().map(str::to_string).collect(),
+            )),
+            "color" if !rest.trim().is_empty() => Ok(Command::Color(rest.trim().to_string())),
+            "preset" if !rest.trim().is_empty() => Ok(Command::Preset(rest.trim().to_string())),
+            "trigger" if !rest.trim().is_empty() => Ok(Command::Trigger(
+                rest.split_whitespace().map(str::to_string).collect(),
+            )),
+            "quit" | "stop" => Ok(Command::Quit),
+            "effect" | "color" | "preset" | "trigger" => Err(format!("{name} needs an argument")),
+            _ => Err(format!(
+                "unknown command '{name}' (expected text, effect, color, preset, trigger, or quit)"
+            )),
+        }
+    }
+}
+
+/// A command from a client, waiting for its outcome to be sent back.
+pub struct Request {
+    pub command: Command,
+    reply: Sender<Result<(), String>>,
+    /// Hears from the client's thread once the reply is written
+    written: Receiver<()>,
+}
+
+impl Request {
+    /// Tells the client how its command went, waiting briefly for the
+    /// reply to go out so it isn't lost if clifx exits next. Only the
+    /// first line of an error is sent, so every command gets exactly one
+    /// line back.
+    pub fn reply(self, outcome: Result<(), String>) {
+        let outcome = outcome.map_err(|err| err.lines().next().unwrap_or_default().to_string());
+        // The client may have hung up already, and then there's no one to tell
+        if self.reply.send(outcome).is_ok() {
+            let _ = self.written.recv_timeout(REPLY_TIMEOUT);
+        }
+    }
+}
+
+/// The listening socket. Commands arrive on a background thread, which
+/// interrupts whatever effect is running so the next one can be picked up
+/// straight away, and each gets a line back: `ok`, or `error: ` and what
+/// went wrong. The socket file is removed again on drop.
+pub struct ControlSocket {
+    path: PathBuf,
+    requests: Receiver<Request>,
+    sender: Sender<Request>,
+}
+
+impl ControlSocket {
+    /// Listens on `path`, replacing a socket a previous run left behind.
+    /// Any other kind of file there is left alone and is an error.
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        if let Ok(metadata) = fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and isn't a socket", path.display()),
+                ));
+            }
+            fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        let (sender, requests) = mpsc::channel();
+        let clients = sender.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = clients.clone();
+                thread::spawn(move || serve_client(stream, sender));
+            }
+        });
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            requests,
+            sender,
+        })
+    }
+
+    /// Turns Ctrl-C into a `quit` nobody waits to hear back about, so it
+    /// also ends the wait for the next command and the terminal is put back.
+    pub fn quit_on_ctrl_c(&self) {
+        let sender = self.sender.clone();
+        let _ = ctrlc::set_handler(move || {
+            let (reply, _) = mpsc::channel();
+            let (_, written) = mpsc::channel();
+            playback::interrupt();
+            let request = Request {
+                command: Command::Quit,
+                reply,
+                written,
+            };
+            if sender.send(request).is_err() {
+                playback::interrupt_handled();
+            }
+        });
+    }
+
+    /// The next command already waiting, if any. Effects stop being
+    /// interrupted once every waiting command has been taken.
+    pub fn try_next(&self) -> Option<Request> {
+        let request = self.requests.try_recv().ok()?;
+        playback::interrupt_handled();
+        Some(request)
+    }
+
+    /// Waits for the next command.
+    pub fn next(&self) -> Option<Request> {
+        let request = self.requests.recv().ok()?;
+        playback::interrupt_handled();
+        Some(request)
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Reads one client's commands until it hangs up, answering each in turn.
+fn serve_client(stream: UnixStream, requests: Sender<Request>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (written, written_receiver) = mpsc::channel();
+        let outcome = match Command::parse(&line) {
+            Ok(command) => {
+                let (reply, outcome) = mpsc::channel();
+                let request = Request {
+                    command,
+                    reply,
+                    written: written_receiver,
+                };
+                // Counted before it's sent, so it's never handled before it's asked for
+                playback::interrupt();
+                if requests.send(request).is_err() {
+                    playback::interrupt_handled();
+                    return;
+                }
+                outcome.recv().unwrap_or(Err("clifx stopped".to_string()))
+            }
+            Err(err) => Err(err),
+        };
+        let answer = match outcome {
+            Ok(()) => "ok".to_string(),
+            Err(err) => format!("error: {err}"),
+        };
+        if writeln!(writer, "{answer}").is_err() {
+            return;
+        }
+        let _ = written.send(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            Command::parse("text Build passed\\nall green"),
+            Ok(Command::Text(vec![
+                "Build passed".to_string(),
+                "all green".to_string()
+            ]))
+        );
+        assert_eq!(
+            Command::parse("preset calm\n"),
+            Ok(Command::Preset("calm".to_string()))
+        );
+        assert_eq!(
+            Command::parse("trigger confetti  --from text"),
+            Ok(Command::Trigger(vec![
+                "confetti".to_string(),
+                "--from".to_string(),
+                "text".to_string()
+            ]))
+        );
+        assert_eq!(
+            Command::parse("effect pulse --color gold"),
+            Ok(Command::Effect(vec![
+                "pulse".to_string(),
+                "--color".to_string(),
+                "gold".to_string()
+            ]))
+        );
+        assert_eq!(
+            Command::parse("color 255,0,0"),
+            Ok(Command::Color("255,0,0".to_string()))
+        );
+        assert_eq!(Command::parse("quit"), Ok(Command::Quit));
+        assert_eq!(Command::parse("stop"), Ok(Command::Quit));
+        assert!(Command::parse("preset").is_err());
+        assert!(Command::parse("color").is_err());
+        assert!(Command::parse("dance").is_err());
+    }
+
+    #[test]
+    fn test_clients_get_a_reply_per_command() {
+        let path = std::env::temp_dir().join(format!("clifx-control-{}.sock", std::process::id()));
+        let socket = ControlSocket::bind(&path).unwrap();
+        let handler = thread::spawn(move || {
+            let request = socket.next().unwrap();
+            assert_eq!(request.command, Command::Preset("calm".to_string()));
+            request.reply(Err("no preset named 'calm'\nmore detail".to_string()));
+            socket
+        });
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        writeln!(client, "dance\npreset calm").unwrap();
+        let mut replies = BufReader::new(client).lines();
+        assert!(replies
+            .next()
+            .unwrap()
+            .unwrap()
+            .starts_with("error: unknown command"));
+        assert_eq!(
+            replies.next().unwrap().unwrap(),
+            "error: no preset named 'calm'"
+        );
+
+        drop(handler.join().unwrap());
+        assert!(!path.exists());
+    }
+}
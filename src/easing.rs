@@ -0,0 +1,375 @@
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use std::f32::consts::PI;
+
+/// How an animation's progress speeds up and slows down, shared by every
+/// effect's `--easing`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EasingFunction {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Drops to the end and bounces a few times before settling
+    EaseOutBounce,
+    /// Overshoots the end and springs back and forth around it
+    EaseOutElastic,
+    /// Pulls back past the start before moving off
+    EaseInBack,
+    /// Eases in and out along a sine curve, gentler than `EaseInOut`
+    Sine,
+    /// Eases in and out exponentially, most of the move happening mid-way
+    Expo,
+    /// Eases in and out along a quarter circle, sharp at the middle
+    Circ,
+    /// A CSS-style `cubic-bezier(x1, y1, x2, y2)` timing curve
+    CubicBezier(f32, f32, f32, f32),
+}
+
+/// The curve `--easing cubic-bezier` stands for until `--bezier` gives its
+/// points: CSS's `ease`.
+const CSS_EASE: EasingFunction = EasingFunction::CubicBezier(0.25, 0.1, 0.25, 1.0);
+
+/// Every easing by name, as `--easing` takes them. A curve's control points
+/// come separately, so `cubic-bezier` names them all.
+impl ValueEnum for EasingFunction {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            EasingFunction::Linear,
+            EasingFunction::EaseIn,
+            EasingFunction::EaseOut,
+            EasingFunction::EaseInOut,
+            EasingFunction::EaseOutBounce,
+            EasingFunction::EaseOutElastic,
+            EasingFunction::EaseInBack,
+            EasingFunction::Sine,
+            EasingFunction::Expo,
+            EasingFunction::Circ,
+            CSS_EASE,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            EasingFunction::Linear => PossibleValue::new("linear"),
+            EasingFunction::EaseIn => PossibleValue::new("ease-in"),
+            EasingFunction::EaseOut => PossibleValue::new("ease-out"),
+            EasingFunction::EaseInOut => PossibleValue::new("ease-in-out"),
+            EasingFunction::EaseOutBounce => PossibleValue::new("ease-out-bounce"),
+            EasingFunction::EaseOutElastic => PossibleValue::new("ease-out-elastic"),
+            EasingFunction::EaseInBack => PossibleValue::new("ease-in-back"),
+            EasingFunction::Sine => PossibleValue::new("sine"),
+            EasingFunction::Expo => PossibleValue::new("expo"),
+            EasingFunction::Circ => PossibleValue::new("circ"),
+            EasingFunction::CubicBezier(..) => PossibleValue::new("cubic-bezier")
+                .help("A curve through the control points given with --bezier"),
+        })
+    }
+}
+
+/// How far `EaseInBack` pulls back, the usual 10% overshoot.
+const BACK_OVERSHOOT: f32 = 1.70158;
+
+/// Three ever smaller bounces after first reaching the end at 1/2.75.
+fn bounce(t: f32) -> f32 {
+    const SCALE: f32 = 7.5625;
+    const SPAN: f32 = 2.75;
+    if t < 1.0 / SPAN {
+        SCALE * t * t
+    } else if t < 2.0 / SPAN {
+        let t = t - 1.5 / SPAN;
+        SCALE * t * t + 0.75
+    } else if t < 2.5 / SPAN {
+        let t = t - 2.25 / SPAN;
+        SCALE * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / SPAN;
+        SCALE * t * t + 0.984375
+    }
+}
+
+/// Point `t` of the way along a one-dimensional cubic Bézier from 0 to 1
+/// with inner control points `p1` and `p2`.
+fn bezier(t: f32, p1: f32, p2: f32) -> f32 {
+    let u = 1.0 - t;
+    3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+}
+
+impl EasingFunction {
+    /// Parses the control points `x1,y1,x2,y2` of a CSS-style curve, as
+    /// `--bezier` takes them, e.g. `0.25,0.1,0.25,1.0`.
+    pub fn cubic_bezier(points: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let points: Vec<f32> = points
+            .split(',')
+            .map(|point| point.trim().parse::<f32>())
+            .collect::<Result<_, _>>()?;
+        let [x1, y1, x2, y2] = points[..] else {
+            return Err("cubic-bezier needs four numbers: x1, y1, x2, y2".into());
+        };
+        if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) {
+            return Err("cubic-bezier x values must be between 0 and 1".into());
+        }
+        Ok(EasingFunction::CubicBezier(x1, y1, x2, y2))
+    }
+
+    /// Parses an easing name or `cubic-bezier(x1,y1,x2,y2)` with both x
+    /// values in 0.0..=1.0, the points written in.
+    pub fn parse(value: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let value = value.trim().to_ascii_lowercase();
+        if let Some(points) = value
+            .strip_prefix("cubic-bezier(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return Self::cubic_bezier(points);
+        }
+
+        let named = Self::value_variants()
+            .iter()
+            .filter(|easing| !matches!(easing, EasingFunction::CubicBezier(..)));
+        let mut names = Vec::new();
+        for easing in named {
+            if let Some(name) = easing.to_possible_value() {
+                if name.matches(&value, false) {
+                    return Ok(easing.clone());
+                }
+                names.push(name.get_name().to_string());
+            }
+        }
+        Err(format!(
+            "unknown easing `{value}` (expected {} or cubic-bezier(x1,y1,x2,y2))",
+            names.join(", ")
+        )
+        .into())
+    }
+
+    pub fn apply(&self, t: f32) -> f32 {
+        match self {
+            EasingFunction::Linear => t,
+            EasingFunction::EaseIn => t * t,
+            EasingFunction::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            EasingFunction::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            EasingFunction::EaseOutBounce => bounce(t),
+            EasingFunction::EaseOutElastic => {
+                if t <= 0.0 || t >= 1.0 {
+                    return t.clamp(0.0, 1.0);
+                }
+                let period = 2.0 * PI / 3.0;
+                2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * period).sin() + 1.0
+            }
+            EasingFunction::EaseInBack => {
+                (BACK_OVERSHOOT + 1.0) * t * t * t - BACK_OVERSHOOT * t * t
+            }
+            EasingFunction::Sine => -((PI * t).cos() - 1.0) / 2.0,
+            EasingFunction::Expo => {
+                if t <= 0.0 || t >= 1.0 {
+                    t.clamp(0.0, 1.0)
+                } else if t < 0.5 {
+                    2f32.powf(20.0 * t - 10.0) / 2.0
+                } else {
+                    (2.0 - 2f32.powf(-20.0 * t + 10.0)) / 2.0
+                }
+            }
+            EasingFunction::Circ => {
+                let t = t.clamp(0.0, 1.0);
+                if t < 0.5 {
+                    (1.0 - (1.0 - (2.0 * t).powi(2)).sqrt()) / 2.0
+                } else {
+                    ((1.0 - (-2.0 * t + 2.0).powi(2)).sqrt() + 1.0) / 2.0
+                }
+            }
+            EasingFunction::CubicBezier(x1, y1, x2, y2) => {
+                // x rises monotonically with the curve parameter, so bisect for it
+                let t = t.clamp(0.0, 1.0);
+                let (mut low, mut high) = (0.0, 1.0);
+                for _ in 0..32 {
+                    let mid = (low + high) / 2.0;
+                    if bezier(mid, *x1, *x2) < t {
+                        low = mid;
+                    } else {
+                        high = mid;
+                    }
+                }
+                bezier((low + high) / 2.0, *y1, *y2)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    const TEST_TOLERANCE: f32 = 0.001;
+
+    #[test]
+    fn test_easing_function_linear() {
+        let easing = EasingFunction::Linear;
+
+        assert_approx_eq!(easing.apply(0.0), 0.0, TEST_TOLERANCE);
+        assert_approx_eq!(easing.apply(0.25), 0.25, TEST_TOLERANCE);
+        assert_approx_eq!(easing.apply(0.5), 0.5, TEST_TOLERANCE);
+        assert_approx_eq!(easing.apply(0.75), 0.75, TEST_TOLERANCE);
+        assert_approx_eq!(easing.apply(1.0), 1.0, TEST_TOLERANCE);
+    }
+
+    #[test]
+    fn test_easing_function_ease_in() {
+        let easing = EasingFunction::EaseIn;
+
+        assert_approx_eq!(easing.apply(0.0), 0.0, TEST_TOLERANCE);
+        assert_approx_eq!(easing.apply(0.5), 0.25, TEST_TOLERANCE);
+        assert_approx_eq!(easing.apply(1.0), 1.0, TEST_TOLERANCE);
+
+        // Ease-in should start slow and accelerate
+        assert!(easing.apply(0.1) < 0.1);
+        assert!(easing.apply(0.9) > 0.8);
+    }
+
+    #[test]
+    fn test_easing_function_ease_out() {
+        let easing = EasingFunction::EaseOut;
+
+        assert_approx_eq!(easing.apply(0.0), 0.0, TEST_TOLERANCE);
+        assert_approx_eq!(easing.apply(0.5), 0.75, TEST_TOLERANCE);
+        assert_approx_eq!(easing.apply(1.0), 1.0, TEST_TOLERANCE);
+
+        // Ease-out should start fast and decelerate
+        assert!(easing.apply(0.1) > 0.1);
+        assert!(easing.apply(0.9) < 1.0);
+    }
+
+    #[test]
+    fn test_easing_function_ease_in_out() {
+        let easing = EasingFunction::EaseInOut;
+
+        assert_approx_eq!(easing.apply(0.0), 0.0, TEST_TOLERANCE);
+        assert_approx_eq!(easing.apply(0.5), 0.5, TEST_TOLERANCE);
+        assert_approx_eq!(easing.apply(1.0), 1.0, TEST_TOLERANCE);
+
+        // Ease-in-out should be symmetric around 0.5
+        let val_25 = easing.apply(0.25);
+        let val_75 = easing.apply(0.75);
+        assert_approx_eq!(val_25, 1.0 - val_75, 0.01); // Allow small tolerance for floating point
+    }
+
+    #[test]
+    fn test_cubic_bezier_easing() {
+        // With control points on the diagonal the curve is a straight line
+        let straight = EasingFunction::CubicBezier(0.25, 0.25, 0.75, 0.75);
+        assert_approx_eq!(straight.apply(0.3), 0.3, TEST_TOLERANCE);
+
+        // CSS "ease": fast start, so well past halfway at the midpoint
+        let ease = EasingFunction::CubicBezier(0.25, 0.1, 0.25, 1.0);
+        assert_approx_eq!(ease.apply(0.0), 0.0, TEST_TOLERANCE);
+        assert_approx_eq!(ease.apply(1.0), 1.0, TEST_TOLERANCE);
+        assert!(ease.apply(0.5) > 0.75);
+    }
+
+    #[test]
+    fn test_value_names_round_trip() {
+        for easing in EasingFunction::value_variants() {
+            let name = easing.to_possible_value().unwrap();
+            assert_eq!(
+                &EasingFunction::from_str(name.get_name(), false).unwrap(),
+                easing
+            );
+        }
+        assert_eq!(
+            EasingFunction::from_str("cubic-bezier", false).unwrap(),
+            CSS_EASE
+        );
+    }
+
+    #[test]
+    fn test_parse_easing() {
+        assert_eq!(
+            EasingFunction::parse("ease-in-out").unwrap(),
+            EasingFunction::EaseInOut
+        );
+        assert_eq!(
+            EasingFunction::parse("cubic-bezier(0.1, -0.5, 0.9, 1.5)").unwrap(),
+            EasingFunction::CubicBezier(0.1, -0.5, 0.9, 1.5)
+        );
+        assert_eq!(
+            EasingFunction::parse("Ease-Out-Bounce").unwrap(),
+            EasingFunction::EaseOutBounce
+        );
+        assert_eq!(
+            EasingFunction::cubic_bezier("0.25,0.1,0.25,1.0").unwrap(),
+            EasingFunction::CubicBezier(0.25, 0.1, 0.25, 1.0)
+        );
+        assert!(EasingFunction::parse("bounce").is_err());
+        assert!(EasingFunction::parse("cubic-bezier(0.1, 0.2, 0.3)").is_err());
+        assert!(EasingFunction::parse("cubic-bezier(1.5, 0, 0.5, 1)").is_err());
+    }
+
+    #[test]
+    fn test_easing_functions_range() {
+        let functions = vec![
+            EasingFunction::Linear,
+            EasingFunction::EaseIn,
+            EasingFunction::EaseOut,
+            EasingFunction::EaseInOut,
+        ];
+
+        for easing in functions {
+            // Test edge cases
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+
+            // Test monotonic increasing property
+            let values: Vec<f32> = (0..=10).map(|i| easing.apply(i as f32 / 10.0)).collect();
+            for i in 1..values.len() {
+                assert!(
+                    values[i] >= values[i - 1],
+                    "Easing function should be monotonic increasing. {:?} at step {}: {} >= {}",
+                    easing,
+                    i,
+                    values[i],
+                    values[i - 1]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_playful_easings_start_and_end_in_place() {
+        for easing in EasingFunction::value_variants() {
+            assert_approx_eq!(easing.apply(0.0), 0.0, TEST_TOLERANCE);
+            assert_approx_eq!(easing.apply(1.0), 1.0, TEST_TOLERANCE);
+        }
+    }
+
+    #[test]
+    fn test_playful_easings_overshoot() {
+        // Back pulls behind the start, elastic springs past the end
+        assert!(EasingFunction::EaseInBack.apply(0.3) < 0.0);
+        assert!(EasingFunction::EaseOutElastic.apply(0.2) > 1.0);
+        // Bounce touches the end, then lifts off it again
+        assert_approx_eq!(
+            EasingFunction::EaseOutBounce.apply(1.0 / 2.75),
+            1.0,
+            TEST_TOLERANCE
+        );
+        assert!(EasingFunction::EaseOutBounce.apply(0.5) < 1.0);
+    }
+
+    #[test]
+    fn test_in_out_easings_are_symmetric() {
+        for easing in [
+            EasingFunction::Sine,
+            EasingFunction::Expo,
+            EasingFunction::Circ,
+        ] {
+            assert_approx_eq!(easing.apply(0.5), 0.5, TEST_TOLERANCE);
+            assert_approx_eq!(easing.apply(0.2), 1.0 - easing.apply(0.8), TEST_TOLERANCE);
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use super::pulse::scale;
+use super::stage::{blend_colors, rgb_color};
+use crate::ansi::{parse_styled, StyledChar};
+use crate::capability::fg;
+use crate::output::{newline, stdout, Stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::{LineStart, TerminalGuard};
+use crate::soak::FrameTimer;
+use crossterm::{
+    cursor, queue,
+    style::{Print, ResetColor},
+};
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthChar;
+
+/// The shortest the text may stay on or off, whatever was asked: at most
+/// five flashes a second.
+pub const MIN_STEP_MS: u64 = 100;
+
+/// The shortest a flash, on and off again, may take under
+/// `--photosensitive-safe`: at most three a second, the limit WCAG sets
+/// for content that flashes.
+pub const SAFE_FLASH_MS: u64 = 334;
+
+/// How dim the off state is under `--photosensitive-safe`, as a fraction of
+/// the way from the on color to the off one, so the text softens rather
+/// than swinging between full brightness and nothing.
+pub const SAFE_DIM: f32 = 0.5;
+
+/// One stretch of a blink: the text on or off, for `ms` milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub on: bool,
+    pub ms: u64,
+}
+
+pub struct BlinkConfig {
+    /// Color of the text while it's on
+    pub color: (u8, u8, u8),
+    /// Color of the text while it's off; it's hidden when there's none
+    pub off_color: Option<(u8, u8, u8)>,
+    /// The on and off stretches of one cycle
+    pub steps: Vec<Step>,
+    /// Times through the steps (0 for forever)
+    pub cycles: u32,
+    /// Keep to at most three soft flashes a second
+    pub photosensitive_safe: bool,
+}
+
+impl Default for BlinkConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            off_color: None,
+            steps: vec![Step { on: true, ms: 500 }, Step { on: false, ms: 500 }],
+            cycles: 3,
+            photosensitive_safe: false,
+        }
+    }
+}
+
+const MORSE: [(char, &str); 36] = [
+    ('A', ".-"),
+    ('B', "-..."),
+    ('C', "-.-."),
+    ('D', "-.."),
+    ('E', "."),
+    ('F', "..-."),
+    ('G', "--."),
+    ('H', "...."),
+    ('I', ".."),
+    ('J', ".---"),
+    ('K', "-.-"),
+    ('L', ".-.."),
+    ('M', "--"),
+    ('N', "-."),
+    ('O', "---"),
+    ('P', ".--."),
+    ('Q', "--.-"),
+    ('R', ".-."),
+    ('S', "..."),
+    ('T', "-"),
+    ('U', "..-"),
+    ('V', "...-"),
+    ('W', ".--"),
+    ('X', "-..-"),
+    ('Y', "-.--"),
+    ('Z', "--.."),
+    ('0', "-----"),
+    ('1', ".----"),
+    ('2', "..---"),
+    ('3', "...--"),
+    ('4', "....-"),
+    ('5', "....."),
+    ('6', "-...."),
+    ('7', "--..."),
+    ('8', "---.."),
+    ('9', "----."),
+];
+
+/// The steps of a `--pattern`: Morse code written as dots and dashes with
+/// spaces between letters and `/` between words (`... --- ...`), or text
+/// to spell out in it (`SOS`). A dot is on for `on_ms` and a dash three
+/// times as long; the gap after a mark is `off_ms`, after a letter three
+/// times that, and after a word, including the last, seven times.
+pub fn parse_pattern(pattern: &str, on_ms: u64, off_ms: u64) -> Result<Vec<Step>, String> {
+    let marks = pattern.trim();
+    let code = if marks.chars().all(|ch| matches!(ch, '.' | '-' | ' ' | '/')) {
+        marks.to_string()
+    } else {
+        marks
+            .split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .map(|ch| {
+                        MORSE
+                            .iter()
+                            .find(|(letter, _)| *letter == ch.to_ascii_uppercase())
+                            .map(|(_, code)| *code)
+                            .ok_or_else(|| format!("'{ch}' has no Morse code"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(|letters| letters.join(" "))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join(" / ")
+    };
+
+    let mut steps: Vec<Step> = Vec::new();
+    let gap = |steps: &mut Vec<Step>, ms: u64| match steps.last_mut() {
+        // A longer gap takes the place of the one after the last mark
+        Some(last) if !last.on => last.ms = last.ms.max(ms),
+        Some(_) => steps.push(Step { on: false, ms }),
+        None => {}
+    };
+    for ch in code.chars() {
+        match ch {
+            '.' | '-' => {
+                let ms = if ch == '.' { on_ms } else { on_ms * 3 };
+                steps.push(Step { on: true, ms });
+                steps.push(Step {
+                    on: false,
+                    ms: off_ms,
+                });
+            }
+            ' ' => gap(&mut steps, off_ms * 3),
+            _ => gap(&mut steps, off_ms * 7),
+        }
+    }
+    gap(&mut steps, off_ms * 7);
+    if steps.is_empty() {
+        return Err(format!("'{pattern}' has no dots or dashes to blink"));
+    }
+    Ok(steps)
+}
+
+/// `steps` held to the rate limit: none shorter than `MIN_STEP_MS`, or
+/// under `photosensitive_safe`, than half of `SAFE_FLASH_MS`.
+pub fn limit_rate(steps: &[Step], photosensitive_safe: bool) -> Vec<Step> {
+    let shortest = if photosensitive_safe {
+        SAFE_FLASH_MS.div_ceil(2)
+    } else {
+        MIN_STEP_MS
+    };
+    steps
+        .iter()
+        .map(|step| Step {
+            ms: step.ms.max(shortest),
+            ..*step
+        })
+        .collect()
+}
+
+fn draw_blink(
+    stdout: &mut Stdout,
+    lines: &[Vec<StyledChar>],
+    config: &BlinkConfig,
+    on: bool,
+    centering_offsets: Option<(u16, u16)>,
+    redraw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let frame_timer = FrameTimer::start();
+    if let Some((top_offset, left_offset)) = centering_offsets {
+        queue!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+    } else if redraw && lines.len() > 1 {
+        queue!(stdout, cursor::MoveUp((lines.len() - 1) as u16))?;
+    }
+
+    let base_color = rgb_color(config.color);
+    for (row, cells) in lines.iter().enumerate() {
+        match centering_offsets {
+            Some((_, left_offset)) => queue!(stdout, cursor::MoveToColumn(left_offset))?,
+            None => queue!(stdout, LineStart)?,
+        }
+
+        for cell in cells {
+            let color = cell.color.unwrap_or(base_color);
+            let off = match (config.off_color, config.photosensitive_safe) {
+                (Some(off), true) => Some(blend_colors(color, rgb_color(off), SAFE_DIM)),
+                (Some(off), false) => Some(rgb_color(off)),
+                (None, true) => Some(scale(color, 1.0 - SAFE_DIM)),
+                (None, false) => None,
+            };
+            match (on, off) {
+                (true, _) => queue!(stdout, fg(color), Print(cell.ch))?,
+                (false, Some(off)) => queue!(stdout, fg(off), Print(cell.ch))?,
+                // Hidden, with the space it takes left blank
+                (false, None) => {
+                    let blank = " ".repeat(cell.ch.width().unwrap_or(0));
+                    queue!(stdout, Print(blank))?
+                }
+            }
+        }
+
+        if row + 1 < lines.len() {
+            queue!(stdout, Print("\r\n"))?;
+        }
+    }
+
+    queue!(stdout, ResetColor)?;
+    stdout.flush_frame()?;
+    frame_timer.finish();
+    Ok(())
+}
+
+/// Blinks all of `lines` together through the steps of `config`, leaving
+/// them on at the end. However the steps were given, or however fast
+/// playback runs, the text never changes faster than the rate limit.
+pub fn apply_blink_effect(
+    lines: &[String],
+    config: &BlinkConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+
+    if styled.iter().all(|cells| cells.is_empty()) {
+        newline()?;
+        return Ok(());
+    }
+
+    let steps = limit_rate(&config.steps, config.photosensitive_safe);
+    let shortest = steps
+        .iter()
+        .map(|step| step.ms)
+        .min()
+        .unwrap_or(MIN_STEP_MS);
+    let cycles_to_run = if config.cycles == 0 {
+        usize::MAX
+    } else {
+        config.cycles as usize
+    };
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut drawn = false;
+    for _cycle in 0..cycles_to_run {
+        for step in &steps {
+            resize::track(&mut centering_offsets)?;
+            draw_blink(
+                &mut stdout,
+                &styled,
+                config,
+                step.on,
+                centering_offsets,
+                drawn,
+            )?;
+            drawn = true;
+
+            let changed = Instant::now();
+            playback::sleep(Duration::from_millis(step.ms))?;
+            // The playback clock can run fast or catch up on late frames,
+            // but the rate limit holds in real time
+            let held = changed.elapsed();
+            let floor = Duration::from_millis(shortest);
+            if held < floor {
+                std::thread::sleep(floor - held);
+            }
+        }
+    }
+
+    resize::track(&mut centering_offsets)?;
+    draw_blink(&mut stdout, &styled, config, true, centering_offsets, drawn)?;
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on(ms: u64) -> Step {
+        Step { on: true, ms }
+    }
+
+    fn off(ms: u64) -> Step {
+        Step { on: false, ms }
+    }
+
+    #[test]
+    fn test_blink_config_default() {
+        let config = BlinkConfig::default();
+
+        assert_eq!(config.steps, [on(500), off(500)]);
+        assert_eq!(config.cycles, 3);
+        assert!(!config.photosensitive_safe);
+    }
+
+    #[test]
+    fn test_parse_pattern_spells_out_morse() {
+        let sos = [
+            on(100),
+            off(100),
+            on(100),
+            off(100),
+            on(100),
+            off(300),
+            on(300),
+            off(100),
+            on(300),
+            off(100),
+            on(300),
+            off(300),
+            on(100),
+            off(100),
+            on(100),
+            off(100),
+            on(100),
+            off(700),
+        ];
+        assert_eq!(parse_pattern("SOS", 100, 100).unwrap(), sos);
+        assert_eq!(parse_pattern("sos", 100, 100).unwrap(), sos);
+        assert_eq!(parse_pattern("... --- ...", 100, 100).unwrap(), sos);
+
+        assert_eq!(
+            parse_pattern("E E", 100, 50).unwrap(),
+            [on(100), off(350), on(100), off(350)]
+        );
+        assert!(parse_pattern("S?S", 100, 100).is_err());
+        assert!(parse_pattern(" / ", 100, 100).is_err());
+    }
+
+    #[test]
+    fn test_limit_rate_holds_steps_to_the_floor() {
+        let fast = [on(20), off(500)];
+        assert_eq!(limit_rate(&fast, false), [on(MIN_STEP_MS), off(500)]);
+        // Safe, a whole flash takes at least a third of a second
+        let safe = limit_rate(&fast, true);
+        assert_eq!(safe, [on(167), off(500)]);
+        assert!(
+            limit_rate(&[on(1), off(1)], true)
+                .iter()
+                .map(|step| step.ms)
+                .sum::<u64>()
+                >= SAFE_FLASH_MS
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use super::frame::Frame;
+use super::physics::Bounce;
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled};
+use crate::capability::fg;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::rng;
+use crate::screen::{self, TerminalGuard};
+use crate::soak::FrameTimer;
+use crossterm::{
+    queue,
+    style::{Color, Print, ResetColor},
+};
+use rand::Rng;
+use std::io::Write;
+use std::time::Duration;
+
+/// Longest, in milliseconds, a character waits before it drops with
+/// `--per-char`, so they land out of step.
+const PER_CHAR_SPREAD_MS: u64 = 400;
+
+/// Longest the text bounces, in milliseconds, however lively the physics.
+const MAX_BOUNCE_MS: u64 = 15_000;
+
+pub struct BounceConfig {
+    pub color: (u8, u8, u8),
+    /// Rows per second, every second, the text speeds up as it falls
+    pub gravity: f32,
+    /// Share (0.0 to 1.0) of its speed the text loses on each bounce
+    pub damping: f32,
+    /// Drop each character on its own rather than the block as a whole
+    pub per_char: bool,
+    pub speed: u64,
+    /// Bounce on the alternate screen, then leave the settled text at the
+    /// cursor on the normal one
+    pub alt_screen: bool,
+}
+
+impl Default for BounceConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            gravity: 60.0,
+            damping: 0.45,
+            per_char: false,
+            speed: 20,
+            alt_screen: true,
+        }
+    }
+}
+
+/// A character of the text, at its column and row within the block.
+struct Piece {
+    x: usize,
+    y: usize,
+    ch: char,
+    color: Color,
+}
+
+/// The row the text comes to rest at: the centered spot, or else the
+/// bottom of a screen `rows` tall.
+fn resting_top(centering_offsets: Option<(u16, u16)>, height: usize, rows: usize) -> usize {
+    match centering_offsets {
+        Some((top, _)) => top as usize,
+        None => rows.saturating_sub(height),
+    }
+}
+
+/// What falls, each a body dropped from `drop` rows up after a delay in
+/// milliseconds: the block as a whole, or with `per_char` each of `pieces`
+/// on its own, let go of at random moments so they land out of step.
+fn falls(pieces: usize, per_char: bool, drop: f32, rng: &mut impl Rng) -> Vec<(u64, Bounce)> {
+    let bodies = if per_char { pieces } else { 1 };
+    (0..bodies)
+        .map(|_| {
+            let delay = if per_char {
+                rng.gen_range(0..=PER_CHAR_SPREAD_MS)
+            } else {
+                0
+            };
+            (delay, Bounce::dropped_from(drop))
+        })
+        .collect()
+}
+
+/// Moves every body on `step` milliseconds, the ones still waiting to be
+/// let go of only for the part of it after their delay runs out.
+fn step_falls(falls: &mut [(u64, Bounce)], gravity: f32, damping: f32, step: u64) {
+    for (delay, body) in falls {
+        let moving = step.saturating_sub(*delay);
+        *delay = delay.saturating_sub(step);
+        body.step(gravity, damping, moving);
+    }
+}
+
+/// The screen row of the piece on row `y` of the block, with `body` where
+/// it's fallen to; `None` while it's still above the top.
+fn piece_row(top: usize, y: usize, body: &Bounce) -> Option<usize> {
+    let row = (top + y) as isize - body.height.round() as isize;
+    (row >= 0).then_some(row as usize)
+}
+
+/// Drops `lines` from above the top of the screen to where they rest, the
+/// centered spot or the bottom of the screen, bouncing until they settle.
+pub fn apply_bounce_effect(
+    lines: &[String],
+    config: &BounceConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let base_color = rgb_color(config.color);
+    let mut pieces = Vec::new();
+    let mut width = 0;
+    for (y, line) in lines.iter().enumerate() {
+        let cells = parse_styled(line);
+        let (columns, line_width) = column_starts(&cells);
+        width = width.max(line_width);
+        for (cell, x) in cells.iter().zip(columns) {
+            let color = cell.color.unwrap_or(base_color);
+            pieces.push(Piece {
+                x,
+                y,
+                ch: cell.ch,
+                color,
+            });
+        }
+    }
+    if width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    {
+        let mut stdout = stdout();
+        let _screen = config
+            .alt_screen
+            .then(TerminalGuard::alt_screen)
+            .transpose()?;
+        let terminal = TerminalGuard::hide_cursor(true)?;
+
+        let height = lines.len();
+        let top = |offsets: Option<(u16, u16)>| {
+            let rows = screen::layout_size().map_or(24, |(_, rows)| rows as usize);
+            resting_top(offsets, height, rows)
+        };
+        // From just above the screen, so the text drops into sight
+        let drop = (top(centering_offsets) + height) as f32;
+        let mut falls = falls(pieces.len(), config.per_char, drop, &mut rng::new_rng());
+
+        let render = |falls: &[(u64, Bounce)], top: usize| {
+            let mut frame = Frame::new(width, top + height);
+            for (index, piece) in pieces.iter().enumerate() {
+                let (_, body) = &falls[index.min(falls.len() - 1)];
+                if let Some(y) = piece_row(top, piece.y, body) {
+                    frame.put(piece.x, y, piece.ch, piece.color);
+                }
+            }
+            frame
+        };
+        let at = |offsets: Option<(u16, u16)>| Some((0, offsets.map_or(0, |(_, left)| left)));
+
+        let step = config.speed.max(1);
+        let frame_duration = Duration::from_millis(config.speed);
+        let mut repaint = Repaint::default();
+        let mut elapsed = 0;
+        while elapsed < MAX_BOUNCE_MS && falls.iter().any(|(_, body)| !body.resting) {
+            resize::track(&mut centering_offsets)?;
+            let frame_timer = FrameTimer::start();
+            repaint.draw_on_time(
+                render(&falls, top(centering_offsets)),
+                &mut stdout,
+                at(centering_offsets),
+            )?;
+            stdout.flush()?;
+            frame_timer.finish();
+
+            playback::sleep(frame_duration)?;
+            elapsed += step;
+            step_falls(&mut falls, config.gravity, config.damping, step);
+        }
+
+        let settled = vec![(0, Bounce::dropped_from(0.0)); falls.len()];
+        repaint.draw(
+            render(&settled, top(centering_offsets)),
+            &mut stdout,
+            at(centering_offsets),
+        )?;
+        stdout.flush()?;
+        // The settled frame already left the colors reset
+        terminal.finish()?;
+    }
+
+    if config.alt_screen {
+        // What bounced on the alternate screen went with it
+        let mut stdout = stdout();
+        for row in 0..lines.len() {
+            for piece in pieces.iter().filter(|piece| piece.y == row) {
+                queue!(stdout, fg(piece.color), Print(piece.ch))?;
+            }
+            queue!(stdout, ResetColor, Print("\n"))?;
+        }
+        stdout.flush()?;
+        return Ok(());
+    }
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_bounce_config_default() {
+        let config = BounceConfig::default();
+
+        assert_eq!(config.gravity, 60.0);
+        assert_eq!(config.damping, 0.45);
+        assert!(!config.per_char);
+        assert!(config.alt_screen);
+    }
+
+    #[test]
+    fn test_resting_top_is_the_centered_spot_or_the_bottom() {
+        assert_eq!(resting_top(Some((5, 2)), 3, 24), 5);
+        assert_eq!(resting_top(None, 3, 24), 21);
+        assert_eq!(resting_top(None, 30, 24), 0);
+    }
+
+    #[test]
+    fn test_every_piece_comes_to_rest_on_its_row() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let top = 7;
+        let mut falls = falls(4, true, (top + 2) as f32, &mut rng);
+        for body in falls.iter().map(|(_, body)| body) {
+            assert_eq!(piece_row(top, 0, body), None);
+        }
+
+        for _ in 0..1000 {
+            step_falls(&mut falls, 60.0, 0.45, 20);
+        }
+        for (y, (_, body)) in falls.iter().enumerate() {
+            assert!(body.resting);
+            assert_eq!(piece_row(top, y % 2, body), Some(top + y % 2));
+        }
+    }
+
+    #[test]
+    fn test_per_char_staggers_the_drops() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut falls = falls(8, true, 10.0, &mut rng);
+        let delays: Vec<u64> = falls.iter().map(|&(delay, _)| delay).collect();
+        assert!(delays.iter().all(|&delay| delay <= PER_CHAR_SPREAD_MS));
+        assert!(delays.iter().any(|&delay| delay != delays[0]), "{delays:?}");
+
+        // Only the pieces already let go of have started to fall
+        let step = *delays.iter().min().unwrap() + 1;
+        step_falls(&mut falls, 60.0, 0.45, step);
+        for (&delay, (_, body)) in delays.iter().zip(&falls) {
+            assert_eq!(body.height < 10.0, delay < step, "{delay}");
+        }
+
+        // The block as a whole drops at once
+        let block = super::falls(8, false, 10.0, &mut rng);
+        assert_eq!(block.len(), 1);
+        assert_eq!(block[0].0, 0);
+    }
+}
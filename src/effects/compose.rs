@@ -0,0 +1,310 @@
+use super::driver::with_phase;
+use super::frame::Frame;
+use super::pulse::{breath, scale};
+use super::repaint::Repaint;
+use super::stage::{rgb_color, ColorStage};
+use crate::ansi::{column_starts, parse_styled};
+use crate::color::hsl_to_rgb;
+use crate::easing::EasingFunction;
+use crate::markup::{Document, SpanEffect, SpanStyle};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use crossterm::style::Color;
+use rand::Rng;
+use std::io::Write;
+use std::time::Duration;
+
+/// Fraction of a span covered by the shine band.
+const SHINE_BAND: f32 = 0.3;
+
+pub struct ComposeConfig {
+    /// Color of text outside any span
+    pub color: (u8, u8, u8),
+    /// Peak color for shine and twinkle spans
+    pub highlight: (u8, u8, u8),
+    pub speed: u64,
+    /// Milliseconds per cycle, for spans that don't set their own
+    pub duration: u64,
+    pub cycles: u32,
+    pub levels: Option<u32>,
+    pub phase: f32,
+}
+
+impl Default for ComposeConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            highlight: (255, 255, 255),
+            speed: 50,
+            duration: 2000,
+            cycles: 1,
+            levels: None,
+            phase: 0.0,
+        }
+    }
+}
+
+/// Where a span is in its schedule at `elapsed` milliseconds: the cycle
+/// progress while it runs, `None` before its delay is up or once it's done.
+fn span_progress(style: &SpanStyle, config: &ComposeConfig, elapsed: u64) -> Option<f32> {
+    let duration = style.duration.unwrap_or(config.duration).max(1);
+    let local = elapsed.checked_sub(style.delay)?;
+    if config.cycles != 0 && local >= duration * config.cycles as u64 {
+        return None;
+    }
+    Some(with_phase(
+        (local % duration) as f32 / duration as f32,
+        config.phase,
+    ))
+}
+
+/// Milliseconds until every span has finished, or `None` if some never do.
+fn total_duration(document: &Document, config: &ComposeConfig) -> Option<u64> {
+    if config.cycles == 0 && !document.spans.is_empty() {
+        return None;
+    }
+    let longest = document
+        .spans
+        .iter()
+        .map(|style| style.delay + style.duration.unwrap_or(config.duration) * config.cycles as u64)
+        .max();
+    Some(longest.unwrap_or(0))
+}
+
+/// Color of a span cell `position` (0.0 to 1.0) of the way through its span,
+/// `progress` through the span's cycle. `sparkle` is the cell's own random
+/// offset, so twinkling cells flash at different times.
+fn span_color(
+    effect: SpanEffect,
+    base: Color,
+    highlight: Color,
+    position: f32,
+    sparkle: f32,
+    progress: f32,
+    stage: &mut ColorStage,
+) -> Color {
+    match effect {
+        SpanEffect::Pulse => stage.shade(
+            scale(base, 0.2),
+            base,
+            breath(progress, &EasingFunction::EaseInOut),
+        ),
+        SpanEffect::Shine => {
+            // The band enters beyond the start and leaves past the end
+            let center = -SHINE_BAND + progress * (1.0 + 2.0 * SHINE_BAND);
+            let intensity = (1.0 - (position - center).abs() / SHINE_BAND).max(0.0);
+            stage.shade(base, highlight, intensity)
+        }
+        SpanEffect::Twinkle => {
+            let flash = breath((progress * 2.0 + sparkle).fract(), &EasingFunction::EaseIn);
+            stage.shade(base, highlight, ((flash - 0.6) / 0.4).max(0.0))
+        }
+        SpanEffect::Rainbow => rgb_color(hsl_to_rgb((position + progress) * 360.0, 1.0, 0.6)),
+    }
+}
+
+/// Animates the marked spans of `document`, each on its own schedule, while
+/// the text around them stays still. All lines are redrawn together every
+/// frame, so spans running across lines stay in step.
+pub fn apply_compose_effect(
+    document: &Document,
+    config: &ComposeConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let lines: Vec<_> = document
+        .lines
+        .iter()
+        .map(|line| parse_styled(line))
+        .collect();
+    let columns: Vec<_> = lines.iter().map(|cells| column_starts(cells)).collect();
+    let width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+
+    if width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    // Each cell's place along its span, counted across lines
+    let mut span_lengths = vec![0usize; document.spans.len()];
+    let mut ordinals = Vec::with_capacity(lines.len());
+    for owners in &document.owners {
+        let row: Vec<usize> = owners
+            .iter()
+            .map(|owner| match owner {
+                Some(span) => {
+                    span_lengths[*span] += 1;
+                    span_lengths[*span] - 1
+                }
+                None => 0,
+            })
+            .collect();
+        ordinals.push(row);
+    }
+
+    let mut rng = rng::new_rng();
+    let sparkles: Vec<Vec<f32>> = lines
+        .iter()
+        .map(|cells| cells.iter().map(|_| rng.gen::<f32>()).collect())
+        .collect();
+
+    let base_color = rgb_color(config.color);
+    let highlight = rgb_color(config.highlight);
+    let mut stage = ColorStage::new(config.levels);
+
+    let mut render = |elapsed: Option<u64>| {
+        let mut frame = Frame::new(width, lines.len());
+        for (row, cells) in lines.iter().enumerate() {
+            for (index, cell) in cells.iter().enumerate() {
+                let owner = document.owners[row].get(index).copied().flatten();
+                let style = owner.map(|span| &document.spans[span]);
+                let base = style
+                    .and_then(|style| style.color.map(rgb_color))
+                    .or(cell.color)
+                    .unwrap_or(base_color);
+
+                let color = match (style, owner, elapsed) {
+                    (Some(style), Some(span), Some(elapsed)) => {
+                        match span_progress(style, config, elapsed) {
+                            Some(progress) => span_color(
+                                style.effect,
+                                base,
+                                highlight,
+                                ordinals[row][index] as f32 / span_lengths[span].max(1) as f32,
+                                sparkles[row][index],
+                                progress,
+                                &mut stage,
+                            ),
+                            None => base,
+                        }
+                    }
+                    _ => base,
+                };
+                frame.put(columns[row].0[index], row, cell.ch, color);
+            }
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let total = total_duration(document, config);
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0u64;
+    while total.is_none_or(|total| elapsed < total) {
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(render(Some(elapsed)), &mut stdout, centering_offsets)?;
+        stdout.flush()?;
+        frame_timer.finish();
+
+        playback::sleep(frame_duration)?;
+        elapsed += config.speed.max(1);
+    }
+
+    repaint.draw(render(None), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::parse_document;
+
+    fn style(delay: u64, duration: Option<u64>) -> SpanStyle {
+        SpanStyle {
+            effect: SpanEffect::Pulse,
+            color: None,
+            delay,
+            duration,
+        }
+    }
+
+    #[test]
+    fn test_compose_config_default() {
+        let config = ComposeConfig::default();
+
+        assert_eq!(config.speed, 50);
+        assert_eq!(config.duration, 2000);
+        assert_eq!(config.cycles, 1);
+    }
+
+    #[test]
+    fn test_span_waits_for_its_delay() {
+        let config = ComposeConfig {
+            duration: 1000,
+            ..Default::default()
+        };
+        let delayed = style(500, None);
+
+        assert_eq!(span_progress(&delayed, &config, 0), None);
+        assert_eq!(span_progress(&delayed, &config, 500), Some(0.0));
+        assert_eq!(span_progress(&delayed, &config, 1000), Some(0.5));
+        assert_eq!(span_progress(&delayed, &config, 1500), None);
+    }
+
+    #[test]
+    fn test_span_duration_overrides_config() {
+        let config = ComposeConfig {
+            cycles: 2,
+            ..Default::default()
+        };
+        let quick = style(0, Some(100));
+
+        assert_eq!(span_progress(&quick, &config, 150), Some(0.5));
+        assert_eq!(span_progress(&quick, &config, 200), None);
+    }
+
+    #[test]
+    fn test_total_duration_covers_latest_span() {
+        let lines = vec!["{{fx:pulse delay=300}}a{{/fx}} {{fx:shine duration=500}}b{{/fx}}".into()];
+        let document = parse_document(&lines).unwrap();
+        let config = ComposeConfig {
+            duration: 1000,
+            ..Default::default()
+        };
+
+        assert_eq!(total_duration(&document, &config), Some(1300));
+
+        let forever = ComposeConfig {
+            cycles: 0,
+            ..Default::default()
+        };
+        assert_eq!(total_duration(&document, &forever), None);
+    }
+
+    #[test]
+    fn test_shine_span_peaks_under_the_band() {
+        let base = rgb_color((100, 100, 100));
+        let highlight = rgb_color((255, 255, 255));
+        let mut stage = ColorStage::new(None);
+        let mut shine = |position| {
+            span_color(
+                SpanEffect::Shine,
+                base,
+                highlight,
+                position,
+                0.0,
+                0.5,
+                &mut stage,
+            )
+        };
+
+        // Halfway through, the band sits over the middle of the span
+        let Color::Rgb { r: peak, .. } = shine(0.5) else {
+            panic!("expected an RGB shade");
+        };
+        assert!(peak >= 250);
+        assert_eq!(shine(0.0), base);
+    }
+}
@@ -0,0 +1,252 @@
+use super::frame::Frame;
+use super::particles::{density_share, screen_size, Particle, Particles};
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::quality::Quality;
+use crate::resize;
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
+use rand::Rng;
+use std::io::Write;
+use std::time::Duration;
+
+/// Shapes a piece of confetti tumbles through as it flies.
+const PIECE_GLYPHS: [char; 4] = ['■', '▪', '▬', '▪'];
+
+/// Milliseconds a piece takes to turn through one glyph.
+const TUMBLE_MS: u64 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ConfettiOrigin {
+    /// Shot up from along the bottom of the screen
+    Bottom,
+    /// Burst out of the text in every direction
+    Text,
+}
+
+pub struct ConfettiConfig {
+    /// Color the text stays highlighted in while the confetti flies
+    pub color: (u8, u8, u8),
+    /// Colors the pieces take, each one at random
+    pub palette: Vec<(u8, u8, u8)>,
+    /// Pieces in the burst
+    pub burst_count: usize,
+    /// Rows per second each piece's fall speeds up by, every second
+    pub gravity: f32,
+    pub origin: ConfettiOrigin,
+    pub speed: u64,
+    pub quality: Quality,
+}
+
+impl Default for ConfettiConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 215, 0),
+            palette: vec![
+                (255, 0, 0),
+                (255, 215, 0),
+                (50, 205, 50),
+                (0, 191, 255),
+                (255, 0, 255),
+            ],
+            burst_count: 80,
+            gravity: 30.0,
+            origin: ConfettiOrigin::Bottom,
+            speed: 30,
+            quality: Quality::High,
+        }
+    }
+}
+
+/// One piece of confetti from the configured origin, never launched hard
+/// enough to fly off the top of a `size` screen. `text` is the text's
+/// top-left corner and its size.
+fn launch_piece(
+    rng: &mut impl Rng,
+    config: &ConfettiConfig,
+    size: (usize, usize),
+    text: ((usize, usize), (usize, usize)),
+) -> Particle {
+    // Per millisecond squared, to match the particles' velocities
+    let gravity = config.gravity.max(0.1) / 1_000_000.0;
+    let launch = |rise: f32| (2.0 * gravity * rise).sqrt();
+    let ((left, top), (width, height)) = text;
+
+    let (x, y, velocity) = match config.origin {
+        ConfettiOrigin::Bottom => {
+            let up = launch(size.1 as f32) * rng.gen_range(0.6..1.0);
+            let x = rng.gen_range(0.0..size.0.max(1) as f32);
+            (x, size.1 as f32 - 1.0, (up * rng.gen_range(-0.4..0.4), -up))
+        }
+        ConfettiOrigin::Text => {
+            let x = left as f32 + rng.gen_range(0.0..width.max(1) as f32);
+            let y = top as f32 + rng.gen_range(0.0..height.max(1) as f32);
+            let speed = launch(size.1 as f32 / 2.0) * rng.gen_range(0.3..1.0);
+            let heading = rng.gen_range(std::f32::consts::PI..std::f32::consts::TAU);
+            // Columns are about half as wide as rows are tall
+            (x, y, (heading.cos() * speed * 2.0, heading.sin() * speed))
+        }
+    };
+    Particle {
+        x,
+        y,
+        velocity,
+        age: rng.gen_range(0..TUMBLE_MS * PIECE_GLYPHS.len() as u64),
+        lifetime: u64::MAX,
+        glyph: PIECE_GLYPHS[0],
+        color: rgb_color(config.palette[rng.gen_range(0..config.palette.len())]),
+    }
+}
+
+/// The glyph a piece shows `age` milliseconds into its tumble.
+fn tumble_glyph(age: u64) -> char {
+    PIECE_GLYPHS[(age / TUMBLE_MS) as usize % PIECE_GLYPHS.len()]
+}
+
+/// Fires one burst of confetti across the whole terminal while `lines`
+/// sit highlighted in the middle of it, in front of the pieces. The effect
+/// ends once the last piece has fallen out of the bottom.
+pub fn apply_confetti_effect(
+    lines: &[String],
+    config: &ConfettiConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let columns: Vec<_> = styled.iter().map(|cells| column_starts(cells)).collect();
+    let text_width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+
+    if config.palette.is_empty() {
+        newline()?;
+        return Ok(());
+    }
+
+    let text_color = rgb_color(config.color);
+    let mut rng = rng::new_rng();
+    let size = screen_size();
+    let text_at = (
+        size.0.saturating_sub(text_width) / 2,
+        size.1.saturating_sub(styled.len()) / 2,
+    );
+    let text = (text_at, (text_width, styled.len()));
+
+    let mut pieces = Particles::new(size, 0.0, 1, false);
+    let count = (config.burst_count as f32 * density_share(config.quality)).round() as usize;
+    for _ in 0..count {
+        let piece = launch_piece(&mut rng, config, size, text);
+        pieces.particles.push(piece);
+    }
+
+    let render = |pieces: &Particles| {
+        let mut frame = Frame::new(size.0, size.1);
+        pieces.draw(&mut frame, |piece| tumble_glyph(piece.age));
+        for (row, cells) in styled.iter().enumerate() {
+            for (index, cell) in cells.iter().enumerate() {
+                if cell.ch != ' ' {
+                    let color = cell.color.unwrap_or(text_color);
+                    frame.put(
+                        text_at.0 + columns[row].0[index],
+                        text_at.1 + row,
+                        cell.ch,
+                        color,
+                    );
+                }
+            }
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let step = config.speed.max(1);
+    let _terminal = TerminalGuard::hide_cursor(true)?;
+
+    // The burst fills the screen from its top-left corner, so there's no
+    // centering to redo on a resize
+    let origin = Some((0, 0));
+    let mut repaint = Repaint::default();
+    while !pieces.particles.is_empty() {
+        pieces.pull(config.gravity, step);
+        pieces.step(step, false, config.quality, || unreachable!());
+
+        resize::track(&mut None)?;
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(render(&pieces), &mut stdout, origin)?;
+        stdout.flush()?;
+        frame_timer.finish();
+
+        playback::sleep(frame_duration)?;
+    }
+
+    repaint.draw(render(&pieces), &mut stdout, origin)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_confetti_config_default() {
+        let config = ConfettiConfig::default();
+
+        assert_eq!(config.burst_count, 80);
+        assert_eq!(config.origin, ConfettiOrigin::Bottom);
+        assert_eq!(config.palette.len(), 5);
+    }
+
+    #[test]
+    fn test_pieces_from_the_bottom_rise_no_higher_than_the_screen() {
+        let config = ConfettiConfig::default();
+        let mut rng = StdRng::seed_from_u64(8);
+        let size = (40, 20);
+        let mut pieces = Particles::new(size, 0.0, 1, false);
+        for _ in 0..50 {
+            let piece = launch_piece(&mut rng, &config, size, ((0, 0), (0, 0)));
+            assert!(piece.velocity.1 < 0.0);
+            pieces.particles.push(piece);
+        }
+
+        let mut highest = f32::MAX;
+        for _ in 0..400 {
+            pieces.pull(config.gravity, 10);
+            pieces.step(10, false, Quality::High, || unreachable!());
+            for piece in &pieces.particles {
+                highest = highest.min(piece.y);
+            }
+        }
+        assert!(highest > -1.0);
+        assert!(highest < 12.0);
+        assert!(pieces.particles.is_empty());
+    }
+
+    #[test]
+    fn test_pieces_from_the_text_start_inside_it() {
+        let config = ConfettiConfig {
+            origin: ConfettiOrigin::Text,
+            ..ConfettiConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(9);
+        for _ in 0..20 {
+            let piece = launch_piece(&mut rng, &config, (40, 20), ((10, 8), (5, 2)));
+            assert!((10.0..15.0).contains(&piece.x));
+            assert!((8.0..10.0).contains(&piece.y));
+            assert!(piece.velocity.1 <= 0.0);
+        }
+    }
+
+    #[test]
+    fn test_pieces_tumble_through_their_glyphs() {
+        assert_eq!(tumble_glyph(0), '■');
+        assert_eq!(tumble_glyph(TUMBLE_MS), '▪');
+        assert_eq!(tumble_glyph(TUMBLE_MS * 4), '■');
+    }
+}
@@ -0,0 +1,213 @@
+use super::frame::Frame;
+use super::repaint::Repaint;
+use super::stage::{rgb_color, ColorStage};
+use crate::big_digits;
+use crate::figlet::FigFont;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
+use std::io::Write;
+use std::time::Duration;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Columns the shine band lights on either side of its center.
+const BAND_HALF_WIDTH: f32 = 4.0;
+
+/// Part of each second the shine takes to sweep across the digits.
+const SWEEP_SHARE: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CountdownTransition {
+    /// A band of the accent color sweeps across each new number
+    Shine,
+    /// Each new number lights up in the accent color and fades back
+    Pulse,
+}
+
+pub struct CountdownConfig {
+    /// Seconds to count down from
+    pub from: u64,
+    pub color: (u8, u8, u8),
+    /// Color of the transitions and the final flash
+    pub accent_color: (u8, u8, u8),
+    pub transition: CountdownTransition,
+    /// Milliseconds zero flashes for at the end
+    pub flash: u64,
+    pub speed: u64,
+    pub levels: Option<u32>,
+}
+
+impl Default for CountdownConfig {
+    fn default() -> Self {
+        Self {
+            from: 10,
+            color: (255, 255, 255),
+            accent_color: (255, 215, 0),
+            transition: CountdownTransition::Shine,
+            flash: 1000,
+            speed: 50,
+            levels: None,
+        }
+    }
+}
+
+/// `seconds` as the countdown shows it: plain seconds under a minute, and
+/// minutes (and hours) once `from` needs them, so the layout stays put.
+pub fn format_remaining(seconds: u64, from: u64) -> String {
+    let (hours, minutes, secs) = (seconds / 3600, (seconds / 60) % 60, seconds % 60);
+    if from >= 3600 {
+        format!("{hours}:{minutes:02}:{secs:02}")
+    } else if from >= 60 {
+        format!("{}:{secs:02}", seconds / 60)
+    } else {
+        seconds.to_string()
+    }
+}
+
+/// `text` in big digits, or as a banner in `font` when one was given.
+pub fn digit_rows(text: &str, font: Option<&FigFont>) -> Vec<String> {
+    match font {
+        Some(font) => font.render(text),
+        None => big_digits::render(text),
+    }
+}
+
+/// How lit column `x` of `width` is, `progress` of the way through a second.
+fn transition(kind: CountdownTransition, progress: f32, x: usize, width: usize) -> f32 {
+    match kind {
+        CountdownTransition::Shine => {
+            let sweep = progress / SWEEP_SHARE;
+            if sweep >= 1.0 {
+                return 0.0;
+            }
+            let travel = width as f32 + 2.0 * BAND_HALF_WIDTH;
+            let center = sweep * travel - BAND_HALF_WIDTH;
+            (1.0 - (x as f32 - center).abs() / BAND_HALF_WIDTH).max(0.0)
+        }
+        CountdownTransition::Pulse => {
+            let fade = 1.0 - progress;
+            fade * fade
+        }
+    }
+}
+
+/// Counts down from `config.from` in big digits, one number a second, each
+/// arriving with the chosen transition, and ends on a flash of zero.
+pub fn apply_countdown_effect(
+    config: &CountdownConfig,
+    font: Option<&FigFont>,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let rows_for = |seconds: u64| digit_rows(&format_remaining(seconds, config.from), font);
+    // The first number is the widest, so every frame can share its size
+    let widest = rows_for(config.from);
+    let width = widest.iter().map(|row| row.width()).max().unwrap_or(0);
+    let height = widest.len();
+    if width == 0 || height == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    let base = rgb_color(config.color);
+    let accent = rgb_color(config.accent_color);
+    let mut stage = ColorStage::new(config.levels);
+    let mut render = |seconds: u64, lit: &dyn Fn(usize) -> f32| {
+        let rows = rows_for(seconds);
+        let mut frame = Frame::new(width, height);
+        for (y, row) in rows.iter().enumerate() {
+            // Narrower numbers sit in the middle of the widest one
+            let mut x = (width - row.width().min(width)) / 2;
+            for ch in row.chars() {
+                if ch != ' ' {
+                    frame.put(x, y, ch, stage.shade(base, accent, lit(x)));
+                }
+                x += ch.width().unwrap_or(0);
+            }
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let step = config.speed.max(1);
+    let counting = config.from * 1000;
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0;
+    while elapsed < counting + config.flash {
+        let frame = if elapsed < counting {
+            let seconds = config.from - elapsed / 1000;
+            let progress = (elapsed % 1000) as f32 / 1000.0;
+            render(seconds, &|x| {
+                transition(config.transition, progress, x, width)
+            })
+        } else {
+            let age = (elapsed - counting) as f32 / config.flash as f32;
+            render(0, &|_| (1.0 - age) * (1.0 - age))
+        };
+
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(frame, &mut stdout, centering_offsets)?;
+        stdout.flush()?;
+        frame_timer.finish();
+
+        playback::sleep(frame_duration)?;
+        elapsed += step;
+    }
+
+    // Zero at rest once the flash has faded
+    repaint.draw(render(0, &|_| 0.0), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_countdown_config_default() {
+        let config = CountdownConfig::default();
+
+        assert_eq!(config.from, 10);
+        assert_eq!(config.transition, CountdownTransition::Shine);
+        assert_eq!(config.flash, 1000);
+        assert_eq!(config.speed, 50);
+    }
+
+    #[test]
+    fn test_format_remaining_keeps_the_layout_of_from() {
+        assert_eq!(format_remaining(9, 10), "9");
+        assert_eq!(format_remaining(75, 90), "1:15");
+        assert_eq!(format_remaining(5, 90), "0:05");
+        assert_eq!(format_remaining(3599, 3600), "0:59:59");
+    }
+
+    #[test]
+    fn test_shine_sweeps_then_rests() {
+        let shine = CountdownTransition::Shine;
+        // The band starts off the left edge and reaches the right one
+        assert_eq!(transition(shine, 0.0, 10, 20), 0.0);
+        let across: Vec<f32> = (0..20).map(|x| transition(shine, 0.3, x, 20)).collect();
+        assert!(across.iter().any(|&lit| lit > 0.9));
+        assert!(transition(shine, 0.5, 19, 20) > 0.9);
+        assert_eq!(transition(shine, 0.8, 10, 20), 0.0);
+    }
+
+    #[test]
+    fn test_pulse_fades_through_the_second() {
+        let pulse = CountdownTransition::Pulse;
+        assert_approx_eq!(transition(pulse, 0.0, 0, 20), 1.0, 0.001);
+        assert_approx_eq!(transition(pulse, 0.5, 7, 20), 0.25, 0.001);
+        assert_approx_eq!(transition(pulse, 1.0, 0, 20), 0.0, 0.001);
+    }
+}
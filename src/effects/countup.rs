@@ -0,0 +1,299 @@
+use super::stage::{rgb_color, ColorStage};
+use crate::big_digits::{self, GLYPH_HEIGHT};
+use crate::capability::fg;
+use crate::output::{newline, stdout, Stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use crossterm::{
+    cursor, queue,
+    style::{Print, ResetColor},
+    terminal::{self, ClearType},
+};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+pub struct CountupConfig {
+    pub color: (u8, u8, u8),
+    pub lap_color: (u8, u8, u8),
+    pub speed: u64,
+    pub duration: Option<u64>,
+    pub lap_display: u64,
+    pub tenths: bool,
+    pub levels: Option<u32>,
+}
+
+impl Default for CountupConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            lap_color: (255, 215, 0),
+            speed: 50,
+            duration: None,
+            lap_display: 1500,
+            tenths: true,
+            levels: None,
+        }
+    }
+}
+
+/// Rows below the digits: one spacer and one lap line.
+const LAP_ROWS: usize = 2;
+
+/// A one-shot overlay that starts at full intensity and decays to nothing.
+struct Flash {
+    started: Instant,
+    length: Duration,
+}
+
+impl Flash {
+    fn intensity(&self, now: Instant) -> Option<f32> {
+        let age = now.duration_since(self.started);
+        if age >= self.length {
+            return None;
+        }
+        let remaining = 1.0 - age.as_secs_f32() / self.length.as_secs_f32();
+        Some(remaining * remaining)
+    }
+}
+
+struct Lap {
+    number: usize,
+    at: Duration,
+    text: String,
+    flash: Flash,
+}
+
+fn format_elapsed(elapsed: Duration, tenths: bool) -> String {
+    let total_secs = elapsed.as_secs();
+    let hours = total_secs / 3600;
+    let minutes = (total_secs / 60) % 60;
+    let seconds = total_secs % 60;
+
+    let mut text = if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    };
+
+    if tenths {
+        text.push_str(&format!(".{}", elapsed.subsec_millis() / 100));
+    }
+
+    text
+}
+
+fn dim(color: (u8, u8, u8)) -> (u8, u8, u8) {
+    let scale = |c: u8| (c as f32 * 0.25) as u8;
+    (scale(color.0), scale(color.1), scale(color.2))
+}
+
+/// Separator brightness for the pulse, peaking at the start of each second.
+fn separator_pulse(elapsed: Duration) -> f32 {
+    let fraction = elapsed.subsec_millis() as f32 / 1000.0;
+    0.5 + 0.5 * (fraction * std::f32::consts::TAU).cos()
+}
+
+pub fn apply_countup_effect(
+    laps: Receiver<String>,
+    config: &CountupConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let frame_duration = Duration::from_millis(config.speed);
+    let lap_length = Duration::from_millis(config.lap_display);
+    let stop_after = config.duration.map(Duration::from_millis);
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut stage = ColorStage::new(config.levels);
+    let started = Instant::now();
+    let mut last_lap: Option<Lap> = None;
+    let mut lap_count = 0;
+    let mut input_open = true;
+    let mut drawn = false;
+
+    loop {
+        let now = Instant::now();
+        let elapsed = match stop_after {
+            Some(limit) => now.duration_since(started).min(limit),
+            None => now.duration_since(started),
+        };
+
+        while input_open {
+            match laps.try_recv() {
+                Ok(text) => {
+                    lap_count += 1;
+                    last_lap = Some(Lap {
+                        number: lap_count,
+                        at: elapsed,
+                        text,
+                        flash: Flash {
+                            started: now,
+                            length: lap_length,
+                        },
+                    });
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => input_open = false,
+            }
+        }
+
+        let flash = last_lap
+            .as_ref()
+            .and_then(|lap| lap.flash.intensity(now))
+            .unwrap_or(0.0);
+        let digit_color = stage.shade(rgb_color(config.color), rgb_color(config.lap_color), flash);
+        let separator_color = stage.shade(
+            rgb_color(dim(config.color)),
+            rgb_color(config.color),
+            separator_pulse(elapsed).max(flash),
+        );
+
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        if let Some((top_offset, left_offset)) = centering_offsets {
+            queue!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+        } else if drawn {
+            queue!(stdout, cursor::MoveUp((GLYPH_HEIGHT + LAP_ROWS - 1) as u16))?;
+        }
+
+        let time_text = format_elapsed(elapsed, config.tenths);
+        for row in 0..GLYPH_HEIGHT {
+            move_to_row_start(&mut stdout, centering_offsets)?;
+            for (i, ch) in time_text.chars().enumerate() {
+                if let Some(glyph) = big_digits::glyph(ch) {
+                    let color = if ch.is_ascii_digit() {
+                        digit_color
+                    } else {
+                        separator_color
+                    };
+                    if i > 0 {
+                        queue!(stdout, Print(' '))?;
+                    }
+                    queue!(stdout, fg(color), Print(glyph[row]))?;
+                }
+            }
+            queue!(
+                stdout,
+                terminal::Clear(ClearType::UntilNewLine),
+                Print("\r\n")
+            )?;
+        }
+
+        queue!(
+            stdout,
+            terminal::Clear(ClearType::UntilNewLine),
+            Print("\r\n")
+        )?;
+        move_to_row_start(&mut stdout, centering_offsets)?;
+        if let Some(lap) = last_lap.as_ref().filter(|_| flash > 0.0) {
+            let lap_color = stage.shade(
+                rgb_color(dim(config.lap_color)),
+                rgb_color(config.lap_color),
+                flash,
+            );
+            queue!(
+                stdout,
+                fg(lap_color),
+                Print(format!(
+                    "Lap {}  {}  {}",
+                    lap.number,
+                    format_elapsed(lap.at, config.tenths),
+                    lap.text
+                ))
+            )?;
+        }
+        queue!(stdout, terminal::Clear(ClearType::UntilNewLine), ResetColor)?;
+        stdout.flush_frame()?;
+        frame_timer.finish();
+        drawn = true;
+
+        let finished = match stop_after {
+            Some(limit) => elapsed >= limit,
+            None => !input_open,
+        };
+        if finished {
+            break;
+        }
+
+        playback::sleep(frame_duration)?;
+    }
+
+    newline()?;
+    Ok(())
+}
+
+fn move_to_row_start(
+    stdout: &mut Stdout,
+    centering_offsets: Option<(u16, u16)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match centering_offsets {
+        Some((_, left_offset)) => queue!(stdout, cursor::MoveToColumn(left_offset))?,
+        None => queue!(stdout, cursor::MoveToColumn(0))?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_countup_config_default() {
+        let config = CountupConfig::default();
+
+        assert_eq!(config.color, (255, 255, 255));
+        assert_eq!(config.lap_color, (255, 215, 0));
+        assert_eq!(config.speed, 50);
+        assert_eq!(config.duration, None);
+        assert_eq!(config.lap_display, 1500);
+        assert!(config.tenths);
+        assert_eq!(config.levels, None);
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_millis(0), true), "00:00.0");
+        assert_eq!(
+            format_elapsed(Duration::from_millis(83_456), true),
+            "01:23.4"
+        );
+        assert_eq!(
+            format_elapsed(Duration::from_millis(83_456), false),
+            "01:23"
+        );
+        assert_eq!(
+            format_elapsed(Duration::from_secs(3600 + 62), false),
+            "1:01:02"
+        );
+    }
+
+    #[test]
+    fn test_flash_decays_to_nothing() {
+        let started = Instant::now();
+        let flash = Flash {
+            started,
+            length: Duration::from_millis(1000),
+        };
+
+        assert_approx_eq!(flash.intensity(started).unwrap(), 1.0, 0.001);
+        let halfway = flash
+            .intensity(started + Duration::from_millis(500))
+            .unwrap();
+        assert!(halfway > 0.0 && halfway < 1.0);
+        assert!(flash
+            .intensity(started + Duration::from_millis(1000))
+            .is_none());
+    }
+
+    #[test]
+    fn test_separator_pulse_range() {
+        assert_approx_eq!(separator_pulse(Duration::from_millis(0)), 1.0, 0.001);
+        assert_approx_eq!(separator_pulse(Duration::from_millis(500)), 0.0, 0.001);
+        assert_approx_eq!(separator_pulse(Duration::from_millis(2000)), 1.0, 0.001);
+    }
+}
@@ -0,0 +1,138 @@
+use super::grid::Grid;
+use crate::ansi::{column_starts, parse_styled};
+use clap::ValueEnum;
+
+/// Columns either side of a cell that count towards its density; rows
+/// count half as far, since terminal cells are about twice as tall as wide.
+const RADIUS_X: isize = 2;
+const RADIUS_Y: isize = 1;
+
+/// Weight even the emptiest (or, for `Sparse`, fullest) cells keep, so a
+/// biased spawn still reaches every target now and then.
+const WEIGHT_FLOOR: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SpawnBias {
+    /// Every target is equally likely
+    Uniform,
+    /// Targets among many glyphs are likelier, so sparkles cluster on the art
+    Dense,
+    /// Targets in open space are likelier, away from the art
+    Sparse,
+}
+
+impl SpawnBias {
+    /// How likely a target with `density` is to spawn, relative to others.
+    pub fn weight(self, density: f32) -> f32 {
+        match self {
+            SpawnBias::Uniform => 1.0,
+            SpawnBias::Dense => density.max(WEIGHT_FLOOR),
+            SpawnBias::Sparse => (1.0 - density).max(WEIGHT_FLOOR),
+        }
+    }
+}
+
+/// Which cells of a block of text hold glyphs, for finding how crowded
+/// the area around any cell is.
+pub struct Density {
+    filled: Grid<bool>,
+}
+
+impl Density {
+    pub fn of(lines: &[String]) -> Self {
+        let rows: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                let cells = parse_styled(line);
+                let (columns, width) = column_starts(&cells);
+                (cells, columns, width)
+            })
+            .collect();
+        let width = rows.iter().map(|&(_, _, width)| width).max().unwrap_or(0);
+
+        let mut filled = Grid::new(width, rows.len(), false);
+        for (y, (cells, columns, _)) in rows.iter().enumerate() {
+            for (cell, &x) in cells.iter().zip(columns) {
+                if !cell.ch.is_whitespace() {
+                    for column in x..x + cell.width().max(1) {
+                        filled.set(column as isize, y as isize, true);
+                    }
+                }
+            }
+        }
+        Self { filled }
+    }
+
+    /// The share (0.0 to 1.0) of non-blank cells around column `x` of row
+    /// `y`. Cells past the text count as blank, so the area just around it
+    /// still has some density.
+    pub fn at(&self, x: isize, y: isize) -> f32 {
+        let window = ((2 * RADIUS_X + 1) * (2 * RADIUS_Y + 1)) as f32;
+        let around = (-RADIUS_Y..=RADIUS_Y)
+            .flat_map(|dy| (-RADIUS_X..=RADIUS_X).map(move |dx| (x + dx, y + dy)))
+            .filter(|&(x, y)| self.filled.get(x, y).unwrap_or(false))
+            .count();
+        around as f32 / window
+    }
+}
+
+/// The share (0.0 to 1.0) of non-blank cells around each character of
+/// `lines`, one value per char, found over the whole block so art spanning
+/// several lines counts as one shape.
+pub fn density_map(lines: &[String]) -> Vec<Vec<f32>> {
+    let density = Density::of(lines);
+    lines
+        .iter()
+        .enumerate()
+        .map(|(y, line)| {
+            let (columns, _) = column_starts(&parse_styled(line));
+            columns
+                .iter()
+                .map(|&x| density.at(x as isize, y as isize))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_density_is_higher_inside_the_art() {
+        let lines = vec![
+            "#####      .".to_string(),
+            "#####      .".to_string(),
+            "#####      .".to_string(),
+        ];
+        let map = density_map(&lines);
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[1].len(), 12);
+        assert_eq!(map[1][2], 1.0);
+        assert!(map[1][8] < 0.1);
+        // Neighbouring rows count, not just the cell's own line
+        assert!(map[1][11] > map[0][11]);
+    }
+
+    #[test]
+    fn test_density_reaches_just_past_the_text() {
+        let density = Density::of(&["###".to_string()]);
+        assert!(density.at(-1, 0) > 0.0);
+        assert!(density.at(4, 1) > 0.0);
+        assert_eq!(density.at(-3, 0), 0.0);
+        assert_eq!(density.at(1, 3), 0.0);
+    }
+
+    #[test]
+    fn test_bias_weights() {
+        assert_eq!(
+            SpawnBias::Uniform.weight(0.0),
+            SpawnBias::Uniform.weight(1.0)
+        );
+        assert!(SpawnBias::Dense.weight(0.9) > SpawnBias::Dense.weight(0.1));
+        assert!(SpawnBias::Sparse.weight(0.1) > SpawnBias::Sparse.weight(0.9));
+        assert!(SpawnBias::Dense.weight(0.0) > 0.0);
+        assert!(SpawnBias::Sparse.weight(1.0) > 0.0);
+    }
+}
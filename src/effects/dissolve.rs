@@ -0,0 +1,242 @@
+use super::frame::Frame;
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use super::wipe::{distance, WipeDirection};
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::easing::EasingFunction;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io::Write;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum DissolveMode {
+    /// Pop characters in until the text is whole
+    In,
+    /// Pop characters out until none are left
+    Out,
+    /// Pop them in, then back out
+    Inout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum DissolveOrder {
+    /// Any character next, at random
+    Random,
+    /// Along each line from the left, the top line first
+    LeftRight,
+    /// From the middle of the text out to its edges
+    InsideOut,
+}
+
+pub struct DissolveConfig {
+    pub color: (u8, u8, u8),
+    pub mode: DissolveMode,
+    pub order: DissolveOrder,
+    /// Milliseconds for every character to pop in, or out
+    pub duration: u64,
+    pub speed: u64,
+    /// How the rate of popping changes over the duration
+    pub easing: EasingFunction,
+}
+
+impl Default for DissolveConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            mode: DissolveMode::In,
+            order: DissolveOrder::Random,
+            duration: 1000,
+            speed: 30,
+            easing: EasingFunction::Linear,
+        }
+    }
+}
+
+/// A character of the text: where it is in the frame, and what it shows.
+struct Piece {
+    x: usize,
+    y: usize,
+    cell: StyledChar,
+}
+
+/// The order `pieces` of a `size` frame pop in, as indices into them.
+/// Inside out, pieces as far from the middle go in reading order.
+fn pop_order(
+    pieces: &[Piece],
+    size: (usize, usize),
+    order: DissolveOrder,
+    rng: &mut impl Rng,
+) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..pieces.len()).collect();
+    match order {
+        DissolveOrder::Random => indices.shuffle(rng),
+        DissolveOrder::LeftRight => {}
+        DissolveOrder::InsideOut => indices.sort_by_key(|&index| {
+            let piece = &pieces[index];
+            distance(piece.x, piece.y, size, WipeDirection::CenterOut)
+        }),
+    }
+    indices
+}
+
+/// How many of `total` pieces have popped at `progress` through a pass,
+/// with `easing` setting the pace.
+fn popped(progress: f32, total: usize, easing: &EasingFunction) -> usize {
+    let shown = easing.apply(progress.clamp(0.0, 1.0)).clamp(0.0, 1.0);
+    ((shown * total as f32).round() as usize).min(total)
+}
+
+/// Pops the characters of `lines` in or out one at a time, in
+/// `config.order`, until the text is whole or gone.
+pub fn apply_dissolve_effect(
+    lines: &[String],
+    config: &DissolveConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let mut pieces = Vec::new();
+    let mut width = 0;
+    for (y, line) in lines.iter().enumerate() {
+        let cells = parse_styled(line);
+        let (columns, line_width) = column_starts(&cells);
+        width = width.max(line_width);
+        for (cell, x) in cells.into_iter().zip(columns) {
+            if !cell.ch.is_whitespace() {
+                pieces.push(Piece { x, y, cell });
+            }
+        }
+    }
+    if pieces.is_empty() {
+        newline()?;
+        return Ok(());
+    }
+
+    let size = (width, lines.len());
+    let base_color = rgb_color(config.color);
+    let order = pop_order(&pieces, size, config.order, &mut rng::new_rng());
+    // Which pieces show, kept from frame to frame as they pop
+    let mut shown = vec![false; pieces.len()];
+    let render = |shown: &[bool]| {
+        let mut frame = Frame::new(width, size.1);
+        for (piece, _) in pieces.iter().zip(shown).filter(|(_, &on)| on) {
+            let color = piece.cell.color.unwrap_or(base_color);
+            frame.put(piece.x, piece.y, piece.cell.ch, color);
+        }
+        frame
+    };
+
+    let passes: &[bool] = match config.mode {
+        DissolveMode::In => &[true],
+        DissolveMode::Out => &[false],
+        DissolveMode::Inout => &[true, false],
+    };
+    let frame_duration = Duration::from_millis(config.speed);
+    let duration = config.duration.max(1);
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    for &popping_in in passes {
+        shown.fill(!popping_in);
+        let mut done = 0;
+        let mut elapsed = 0;
+        while elapsed < duration {
+            let due = popped(
+                elapsed as f32 / duration as f32,
+                order.len(),
+                &config.easing,
+            );
+            // Pieces stay popped even if an easing swings back
+            if due > done {
+                for &index in &order[done..due] {
+                    shown[index] = popping_in;
+                }
+                done = due;
+            }
+
+            resize::track(&mut centering_offsets)?;
+            let frame_timer = FrameTimer::start();
+            repaint.draw_on_time(render(&shown), &mut stdout, centering_offsets)?;
+            stdout.flush()?;
+            frame_timer.finish();
+
+            playback::sleep(frame_duration)?;
+            elapsed += config.speed.max(1);
+        }
+        shown.fill(popping_in);
+    }
+
+    repaint.draw(render(&shown), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn pieces(text: &str) -> Vec<Piece> {
+        text.chars()
+            .enumerate()
+            .map(|(x, ch)| Piece {
+                x,
+                y: 0,
+                cell: StyledChar { ch, color: None },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dissolve_config_default() {
+        let config = DissolveConfig::default();
+
+        assert_eq!(config.mode, DissolveMode::In);
+        assert_eq!(config.order, DissolveOrder::Random);
+        assert_eq!(config.duration, 1000);
+    }
+
+    #[test]
+    fn test_pop_order_by_order() {
+        let pieces = pieces("abcde");
+        let mut rng = StdRng::seed_from_u64(1);
+        let size = (5, 1);
+
+        assert_eq!(
+            pop_order(&pieces, size, DissolveOrder::LeftRight, &mut rng),
+            [0, 1, 2, 3, 4]
+        );
+        assert_eq!(
+            pop_order(&pieces, size, DissolveOrder::InsideOut, &mut rng),
+            [2, 1, 3, 0, 4]
+        );
+        let mut random = pop_order(&pieces, size, DissolveOrder::Random, &mut rng);
+        random.sort();
+        assert_eq!(random, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_popped_follows_the_easing() {
+        let linear = EasingFunction::Linear;
+        assert_eq!(popped(0.0, 10, &linear), 0);
+        assert_eq!(popped(0.5, 10, &linear), 5);
+        assert_eq!(popped(1.0, 10, &linear), 10);
+        // Eased in, few pop at first and the rest rush in at the end
+        assert!(popped(0.5, 10, &EasingFunction::EaseIn) < 5);
+        // Overshooting easings never pop more than there are
+        let elastic = EasingFunction::EaseOutElastic;
+        assert!((0..=20).all(|step| popped(step as f32 / 20.0, 10, &elastic) <= 10));
+    }
+}
@@ -0,0 +1,79 @@
+/// Which end of the text a sweeping effect starts from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShineStart {
+    Beginning,
+    End,
+}
+
+impl ShineStart {
+    pub fn opposite(self) -> Self {
+        match self {
+            ShineStart::Beginning => ShineStart::End,
+            ShineStart::End => ShineStart::Beginning,
+        }
+    }
+
+    /// Start side for the zero-based `cycle`. With `alternate` set, every odd
+    /// cycle begins from the opposite end so loops flow back and forth.
+    pub fn for_cycle(self, cycle: usize, alternate: bool) -> Self {
+        if alternate && cycle % 2 == 1 {
+            self.opposite()
+        } else {
+            self
+        }
+    }
+}
+
+/// Shifts cycle `progress` (0.0 to 1.0) forward by `phase`, wrapping past the
+/// end of the cycle. With a zero phase the progress is returned untouched, so
+/// the final frame still lands exactly on 1.0.
+pub fn with_phase(progress: f32, phase: f32) -> f32 {
+    if phase == 0.0 {
+        progress
+    } else {
+        (progress + phase).rem_euclid(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opposite() {
+        assert_eq!(ShineStart::Beginning.opposite(), ShineStart::End);
+        assert_eq!(ShineStart::End.opposite(), ShineStart::Beginning);
+    }
+
+    #[test]
+    fn test_for_cycle_without_alternation() {
+        for cycle in 0..4 {
+            assert_eq!(
+                ShineStart::Beginning.for_cycle(cycle, false),
+                ShineStart::Beginning
+            );
+        }
+    }
+
+    #[test]
+    fn test_for_cycle_with_alternation() {
+        assert_eq!(
+            ShineStart::Beginning.for_cycle(0, true),
+            ShineStart::Beginning
+        );
+        assert_eq!(ShineStart::Beginning.for_cycle(1, true), ShineStart::End);
+        assert_eq!(
+            ShineStart::Beginning.for_cycle(2, true),
+            ShineStart::Beginning
+        );
+        assert_eq!(ShineStart::End.for_cycle(1, true), ShineStart::Beginning);
+    }
+
+    #[test]
+    fn test_with_phase() {
+        assert_eq!(with_phase(1.0, 0.0), 1.0);
+        assert!((with_phase(0.0, 0.33) - 0.33).abs() < 1e-6);
+        assert!((with_phase(0.9, 0.25) - 0.15).abs() < 1e-6);
+        assert_eq!(with_phase(0.5, 0.5), 0.0);
+    }
+}
@@ -0,0 +1,208 @@
+use super::stage::rgb_color;
+use crate::capability::fg;
+use crate::easing::EasingFunction;
+use crate::output::{newline, stdout, Stdout};
+use crate::playback;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use crossterm::{
+    cursor, execute, queue,
+    style::{Print, ResetColor},
+};
+use std::time::Duration;
+
+const DOT: char = '●';
+const POINT: char = '•';
+
+pub struct EasingPreviewConfig {
+    pub easing: EasingFunction,
+    pub color: (u8, u8, u8),
+    /// Columns across the plot and the ruler
+    pub width: usize,
+    /// Rows in the plot
+    pub height: usize,
+    /// Milliseconds for the dot to travel the ruler once
+    pub duration: u64,
+    pub speed: u64,
+    pub cycles: u32,
+}
+
+impl Default for EasingPreviewConfig {
+    fn default() -> Self {
+        Self {
+            easing: EasingFunction::EaseInOut,
+            color: (0, 200, 255),
+            width: 40,
+            height: 12,
+            duration: 1500,
+            speed: 30,
+            cycles: 1,
+        }
+    }
+}
+
+/// An ASCII plot of `easing` from t = 0 (left) to t = 1 (right), `height`
+/// rows tall. The vertical range stretches to fit curves that overshoot 0 or
+/// 1, and those two levels are marked on the axis.
+fn plot(easing: &EasingFunction, width: usize, height: usize) -> Vec<String> {
+    let width = width.max(2);
+    let height = height.max(2);
+    let values: Vec<f32> = (0..width)
+        .map(|x| easing.apply(x as f32 / (width - 1) as f32))
+        .collect();
+    let low = values.iter().copied().fold(0.0, f32::min);
+    let high = values.iter().copied().fold(1.0, f32::max);
+    let row_of =
+        |value: f32| ((high - value) / (high - low) * (height - 1) as f32).round() as usize;
+
+    let mut grid = vec![vec![' '; width]; height];
+    let mut previous = None;
+    for (x, &value) in values.iter().enumerate() {
+        let row = row_of(value);
+        // Fill the rows a steep step skips, so the curve reads as one line
+        let (top, bottom) = match previous {
+            Some(previous) if previous < row => (previous + 1, row),
+            Some(previous) if previous > row => (row, previous - 1),
+            _ => (row, row),
+        };
+        for line in &mut grid[top..=bottom] {
+            line[x] = POINT;
+        }
+        previous = Some(row);
+    }
+
+    let (one, zero) = (row_of(1.0), row_of(0.0));
+    let mut lines: Vec<String> = grid
+        .into_iter()
+        .enumerate()
+        .map(|(row, cells)| {
+            let axis = match row {
+                _ if row == one => "1 ┤",
+                _ if row == zero => "0 ┤",
+                _ => "  │",
+            };
+            format!("{axis}{}", cells.into_iter().collect::<String>())
+        })
+        .collect();
+    lines.push(format!("  └{}", "─".repeat(width)));
+    lines
+}
+
+/// A ruler `width` columns wide with the dot `position` (0.0 to 1.0) of the
+/// way along it. Overshooting easings pin the dot to the ends.
+fn ruler(position: f32, width: usize) -> (String, String) {
+    let width = width.max(2);
+    let dot = (position.clamp(0.0, 1.0) * (width - 1) as f32).round() as usize;
+    ("─".repeat(dot), "─".repeat(width - 1 - dot))
+}
+
+fn draw_ruler(
+    stdout: &mut Stdout,
+    config: &EasingPreviewConfig,
+    progress: f32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let frame_timer = FrameTimer::start();
+    let value = config.easing.apply(progress);
+    let (before, after) = ruler(value, config.width);
+
+    queue!(
+        stdout,
+        cursor::MoveToColumn(0),
+        Print(format!("  ├{before}")),
+        fg(rgb_color(config.color)),
+        Print(DOT),
+        ResetColor,
+        Print(format!("{after}┤ t={progress:.2} → {value:>5.2}"))
+    )?;
+    stdout.flush_frame()?;
+    frame_timer.finish();
+    Ok(())
+}
+
+/// Prints a plot of the easing curve, then animates a dot along a ruler
+/// under it, moving as an effect using that easing would.
+pub fn apply_easing_preview(
+    config: &EasingPreviewConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = stdout();
+    let color = rgb_color(config.color);
+    let mut lines = plot(&config.easing, config.width, config.height);
+    let baseline = lines.pop().unwrap_or_default();
+    for line in lines {
+        let (axis, curve) = line.split_at(line.char_indices().nth(3).map_or(0, |(i, _)| i));
+        execute!(
+            stdout,
+            Print(axis),
+            fg(color),
+            Print(curve),
+            ResetColor,
+            Print("\r\n")
+        )?;
+    }
+    execute!(stdout, Print(baseline), Print("\r\n"))?;
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let frames = (config.duration / config.speed.max(1)).max(2) as usize;
+    let cycles_to_run = if config.cycles == 0 {
+        usize::MAX
+    } else {
+        config.cycles as usize
+    };
+
+    let _terminal = TerminalGuard::hide_cursor(false)?;
+    for _cycle in 0..cycles_to_run {
+        for frame in 0..frames {
+            draw_ruler(&mut stdout, config, frame as f32 / (frames - 1) as f32)?;
+            playback::sleep(frame_duration)?;
+        }
+    }
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing_preview_config_default() {
+        let config = EasingPreviewConfig::default();
+
+        assert_eq!(config.easing, EasingFunction::EaseInOut);
+        assert_eq!(config.width, 40);
+        assert_eq!(config.height, 12);
+        assert_eq!(config.cycles, 1);
+    }
+
+    #[test]
+    fn test_plot_linear_is_a_diagonal() {
+        let lines = plot(&EasingFunction::Linear, 3, 3);
+        assert_eq!(lines, vec!["1 ┤  •", "  │ • ", "0 ┤•  ", "  └───"]);
+    }
+
+    #[test]
+    fn test_plot_stretches_for_overshoot() {
+        let overshoot = EasingFunction::CubicBezier(0.3, 0.0, 0.7, 1.6);
+        let lines = plot(&overshoot, 20, 10);
+
+        // The top row is above 1, so 1 is marked further down the axis
+        assert!(lines[0].starts_with("  │"));
+        assert!(lines.iter().any(|line| line.starts_with("1 ┤")));
+        assert!(lines[9].starts_with("0 ┤"));
+    }
+
+    #[test]
+    fn test_plot_fills_steep_steps() {
+        let lines = plot(&EasingFunction::EaseInOut, 4, 8);
+        // Every row of the curve has a point, with no gaps in the middle
+        assert!(lines[..8].iter().all(|line| line.contains(POINT)));
+    }
+
+    #[test]
+    fn test_ruler_places_dot() {
+        assert_eq!(ruler(0.0, 5), (String::new(), "────".to_string()));
+        assert_eq!(ruler(0.5, 5), ("──".to_string(), "──".to_string()));
+        assert_eq!(ruler(1.4, 5), ("────".to_string(), String::new()));
+    }
+}
@@ -0,0 +1,206 @@
+use super::frame::Frame;
+use super::repaint::Repaint;
+use super::stage::{rgb_color, ColorStage};
+use crate::ansi::{column_starts, parse_styled, StyledChar, BASIC_COLORS};
+use crate::easing::EasingFunction;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
+use std::env;
+use std::io::Write;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum FadeMode {
+    /// From the background color up to the text color
+    In,
+    /// From the text color down to the background color
+    Out,
+    /// In, then back out
+    Inout,
+}
+
+pub struct FadeConfig {
+    pub color: (u8, u8, u8),
+    /// Color the text fades from and to (default: the terminal background)
+    pub from_color: Option<(u8, u8, u8)>,
+    pub mode: FadeMode,
+    /// Milliseconds for one character to fade all the way in or out
+    pub duration: u64,
+    /// Milliseconds each character starts after the one before it
+    pub stagger: u64,
+    pub speed: u64,
+    pub easing: EasingFunction,
+    pub levels: Option<u32>,
+}
+
+impl Default for FadeConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            from_color: None,
+            mode: FadeMode::In,
+            duration: 1000,
+            stagger: 0,
+            speed: 50,
+            easing: EasingFunction::Linear,
+            levels: None,
+        }
+    }
+}
+
+/// Background color from a `COLORFGBG` value such as `15;0`, which several
+/// terminals export as "foreground;background" palette indices.
+fn background_from_colorfgbg(value: &str) -> Option<(u8, u8, u8)> {
+    let index: usize = value.rsplit(';').next()?.trim().parse().ok()?;
+    BASIC_COLORS.get(index).copied()
+}
+
+/// Best guess at the terminal's background, falling back to black.
+fn terminal_background() -> (u8, u8, u8) {
+    env::var("COLORFGBG")
+        .ok()
+        .and_then(|value| background_from_colorfgbg(&value))
+        .unwrap_or((0, 0, 0))
+}
+
+/// How visible (0.0 background to 1.0 full color) a character is at
+/// `elapsed` milliseconds when it starts `offset` milliseconds late.
+fn visibility(elapsed: u64, offset: u64, config: &FadeConfig) -> f32 {
+    let duration = config.duration.max(1);
+    let local = elapsed.saturating_sub(offset);
+    let fade = |local: u64| {
+        config
+            .easing
+            .apply((local as f32 / duration as f32).min(1.0))
+    };
+
+    match config.mode {
+        FadeMode::In => fade(local),
+        FadeMode::Out => 1.0 - fade(local),
+        FadeMode::Inout if local < duration => fade(local),
+        FadeMode::Inout => 1.0 - fade(local - duration),
+    }
+}
+
+/// Fades `lines` in, out, or in and then out, between the background (or
+/// `config.from_color`) and their color. With a stagger, characters follow
+/// one another from left to right.
+pub fn apply_fade_effect(
+    lines: &[String],
+    config: &FadeConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let columns: Vec<_> = styled.iter().map(|cells| column_starts(cells)).collect();
+    let width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+
+    if width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    let background = rgb_color(config.from_color.unwrap_or_else(terminal_background));
+    let base_color = rgb_color(config.color);
+    let mut stage = ColorStage::new(config.levels);
+
+    let mut render = |elapsed: u64| {
+        let mut frame = Frame::new(width, styled.len());
+        for (row, cells) in styled.iter().enumerate() {
+            for (index, cell) in cells.iter().enumerate() {
+                let offset = index as u64 * config.stagger;
+                let color = stage.shade(
+                    background,
+                    cell.color.unwrap_or(base_color),
+                    visibility(elapsed, offset, config),
+                );
+                frame.put(columns[row].0[index], row, cell.ch, color);
+            }
+        }
+        frame
+    };
+
+    let longest_line = styled.iter().map(Vec::len).max().unwrap_or(1);
+    let fades = if config.mode == FadeMode::Inout { 2 } else { 1 };
+    let total = fades * config.duration + (longest_line as u64 - 1) * config.stagger;
+    let frame_duration = Duration::from_millis(config.speed);
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0;
+    while elapsed < total {
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(render(elapsed), &mut stdout, centering_offsets)?;
+        stdout.flush()?;
+        frame_timer.finish();
+
+        playback::sleep(frame_duration)?;
+        elapsed += config.speed.max(1);
+    }
+
+    repaint.draw(render(total), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_fade_config_default() {
+        let config = FadeConfig::default();
+
+        assert_eq!(config.mode, FadeMode::In);
+        assert_eq!(config.from_color, None);
+        assert_eq!(config.duration, 1000);
+        assert_eq!(config.stagger, 0);
+    }
+
+    #[test]
+    fn test_background_from_colorfgbg() {
+        assert_eq!(background_from_colorfgbg("15;0"), Some((0, 0, 0)));
+        assert_eq!(
+            background_from_colorfgbg("0;default;15"),
+            Some((255, 255, 255))
+        );
+        assert_eq!(background_from_colorfgbg("default"), None);
+        assert_eq!(background_from_colorfgbg("0;99"), None);
+    }
+
+    #[test]
+    fn test_visibility_by_mode() {
+        let mut config = FadeConfig::default();
+        assert_approx_eq!(visibility(0, 0, &config), 0.0, 0.001);
+        assert_approx_eq!(visibility(500, 0, &config), 0.5, 0.001);
+        assert_approx_eq!(visibility(1500, 0, &config), 1.0, 0.001);
+
+        config.mode = FadeMode::Out;
+        assert_approx_eq!(visibility(250, 0, &config), 0.75, 0.001);
+
+        config.mode = FadeMode::Inout;
+        assert_approx_eq!(visibility(1000, 0, &config), 1.0, 0.001);
+        assert_approx_eq!(visibility(1500, 0, &config), 0.5, 0.001);
+        assert_approx_eq!(visibility(2000, 0, &config), 0.0, 0.001);
+    }
+
+    #[test]
+    fn test_stagger_delays_later_characters() {
+        let config = FadeConfig {
+            stagger: 200,
+            ..Default::default()
+        };
+        assert_approx_eq!(visibility(400, 2 * config.stagger, &config), 0.0, 0.001);
+        assert_approx_eq!(visibility(900, 2 * config.stagger, &config), 0.5, 0.001);
+    }
+}
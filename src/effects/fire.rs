@@ -0,0 +1,346 @@
+use super::frame::Frame;
+use super::gradient::color_at;
+use super::grid::Grid;
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::quality::{Governor, Quality};
+use crate::resize::{self, Placement};
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
+use crossterm::style::Color;
+use rand::Rng;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Glyphs from faint to full heat for flames drawn below the text.
+const FLAME_GLYPHS: [(f32, char); 4] = [(0.1, '░'), (0.3, '▒'), (0.55, '▓'), (0.8, '█')];
+
+/// Heat text stays lit at when drawn through the fire, so cold rows fade
+/// to embers rather than disappearing.
+const MASK_FLOOR: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum FirePalette {
+    /// Black through red, orange, and yellow to white
+    Classic,
+    /// A gas flame, from deep blue to pale cyan
+    Blue,
+    /// A chemical flame, from dark to bright green
+    Green,
+}
+
+impl FirePalette {
+    /// Colors from cold (first) to hottest (last).
+    fn stops(self) -> Vec<Color> {
+        let stops: &[(u8, u8, u8)] = match self {
+            FirePalette::Classic => &[
+                (0, 0, 0),
+                (128, 0, 0),
+                (255, 40, 0),
+                (255, 140, 0),
+                (255, 220, 50),
+                (255, 255, 230),
+            ],
+            FirePalette::Blue => &[
+                (0, 0, 0),
+                (0, 0, 120),
+                (0, 80, 255),
+                (100, 200, 255),
+                (230, 250, 255),
+            ],
+            FirePalette::Green => &[
+                (0, 0, 0),
+                (0, 80, 0),
+                (0, 200, 40),
+                (150, 255, 100),
+                (240, 255, 230),
+            ],
+        };
+        stops.iter().copied().map(rgb_color).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum FireMode {
+    /// Flames burn up through the text, which shows their colors
+    Behind,
+    /// Flames burn in rows under the text
+    Below,
+}
+
+pub struct FireConfig {
+    /// Text color below the flames, and once the fire is out
+    pub color: (u8, u8, u8),
+    pub palette: FirePalette,
+    pub mode: FireMode,
+    /// Rows flames reach before burning out
+    pub height: usize,
+    /// Columns per row flames lean (negative blows left, positive right)
+    pub wind: f32,
+    /// Milliseconds to keep the fire fed; 0 burns forever
+    pub duration: u64,
+    pub speed: u64,
+    pub quality: Quality,
+}
+
+impl Default for FireConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            palette: FirePalette::Classic,
+            mode: FireMode::Behind,
+            height: 6,
+            wind: 0.0,
+            duration: 3000,
+            speed: 50,
+            quality: Quality::High,
+        }
+    }
+}
+
+/// A heat field where every step copies each row up one, cooled and pushed
+/// sideways at random, over a hidden source row along the bottom.
+struct Fire {
+    heat: Grid<f32>,
+    /// Most heat a cell can lose as it rises one row
+    cooling: f32,
+    wind: f32,
+}
+
+impl Fire {
+    fn new(width: usize, rows: usize, height: usize, wind: f32) -> Self {
+        Self {
+            heat: Grid::new(width, rows + 1, 0.0),
+            // Cells lose half this on average, so flames die out after `height` rows
+            cooling: 2.0 / height.max(1) as f32,
+            wind,
+        }
+    }
+
+    /// Heat of the visible cell at column `x` of row `y`.
+    fn at(&self, x: usize, y: usize) -> f32 {
+        self.heat.get(x as isize, y as isize).unwrap_or(0.0)
+    }
+
+    /// Advances one frame. The source flickers while `fed`, and goes cold
+    /// once it isn't, so the flames die down.
+    fn step(&mut self, fed: bool, rng: &mut impl Rng) {
+        let source = self.heat.height() - 1;
+        self.heat
+            .fill_row(source, |_| if fed { rng.gen_range(0.8..=1.0) } else { 0.0 });
+
+        for y in 0..source as isize {
+            for x in 0..self.heat.width() as isize {
+                let below = self.heat.get(x, y + 1).unwrap_or(0.0);
+                let drift = (self.wind + rng.gen_range(-1.0..=1.0)).round() as isize;
+                let cooled = (below - rng.gen_range(0.0..=self.cooling)).max(0.0);
+                self.heat.set(x + drift, y, cooled);
+            }
+        }
+    }
+
+    fn is_out(&self) -> bool {
+        (0..self.heat.height()).all(|y| (0..self.heat.width()).all(|x| self.at(x, y) < 0.01))
+    }
+}
+
+/// `heat` rounded to the few shades `quality` draws, so fewer cells change
+/// color between frames and less has to be written.
+fn shade_heat(heat: f32, quality: Quality) -> f32 {
+    let shades = match quality {
+        Quality::Low => 4.0,
+        Quality::Medium => 10.0,
+        Quality::High | Quality::Auto => return heat,
+    };
+    (heat * shades).round() / shades
+}
+
+/// The flame glyph for `heat`, if it's warm enough to show.
+fn flame_glyph(heat: f32) -> Option<char> {
+    FLAME_GLYPHS
+        .iter()
+        .rev()
+        .find(|&&(threshold, _)| heat >= threshold)
+        .map(|&(_, glyph)| glyph)
+}
+
+/// Burns a fire under `lines`: either up through the text, which takes on
+/// the flames' colors, or in `config.height` rows below it. When the
+/// duration is up the source goes cold and the flames die down.
+pub fn apply_fire_effect(
+    lines: &[String],
+    config: &FireConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let columns: Vec<_> = styled.iter().map(|cells| column_starts(cells)).collect();
+    let width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+
+    if width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    let (fire_top, rows) = match config.mode {
+        FireMode::Behind => (0, styled.len()),
+        FireMode::Below => (styled.len(), styled.len() + config.height),
+    };
+    let mut fire = Fire::new(width, rows - fire_top, config.height, config.wind);
+    let palette = config.palette.stops();
+    let text_color = rgb_color(config.color);
+    let mut rng = rng::new_rng();
+
+    let render = |fire: &Fire, burning: bool, quality: Quality| {
+        let mut frame = Frame::new(width, rows);
+        for (row, cells) in styled.iter().enumerate() {
+            for (index, cell) in cells.iter().enumerate() {
+                let column = columns[row].0[index];
+                let color = match config.mode {
+                    FireMode::Behind if burning => {
+                        let heat = shade_heat(fire.at(column, row), quality);
+                        color_at(&palette, heat.max(MASK_FLOOR))
+                    }
+                    _ => cell.color.unwrap_or(text_color),
+                };
+                frame.put(column, row, cell.ch, color);
+            }
+        }
+        if burning && config.mode == FireMode::Below {
+            for y in 0..rows - fire_top {
+                for x in 0..width {
+                    let heat = shade_heat(fire.at(x, y), quality);
+                    if let Some(glyph) = flame_glyph(heat) {
+                        frame.put(x, fire_top + y, glyph, color_at(&palette, heat));
+                    }
+                }
+            }
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    // Cells the drift never lands on keep their heat, so give up on a
+    // perfectly cold fire after a while
+    let burn_out_frames = 4 * (rows - fire_top + 1);
+
+    let mut governor = Governor::new(config.quality, frame_duration);
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0;
+    let mut unfed_frames = 0;
+    loop {
+        let started = Instant::now();
+        let fed = config.duration == 0 || elapsed < config.duration;
+        fire.step(fed, &mut rng);
+        if !fed {
+            unfed_frames += 1;
+            if fire.is_out() || unfed_frames > burn_out_frames {
+                break;
+            }
+        }
+
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(
+            render(&fire, true, governor.level()),
+            &mut stdout,
+            centering_offsets,
+        )?;
+        stdout.flush()?;
+        frame_timer.finish();
+        governor.record(started.elapsed());
+
+        playback::sleep(frame_duration)?;
+        elapsed += config.speed.max(1);
+    }
+
+    repaint.draw(
+        render(&fire, false, governor.level()),
+        &mut stdout,
+        centering_offsets,
+    )?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_fire_config_default() {
+        let config = FireConfig::default();
+
+        assert_eq!(config.mode, FireMode::Behind);
+        assert_eq!(config.palette, FirePalette::Classic);
+        assert_eq!(config.height, 6);
+        assert_eq!(config.wind, 0.0);
+    }
+
+    #[test]
+    fn test_flames_cool_as_they_rise() {
+        let mut fire = Fire::new(20, 8, 4, 0.0);
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            fire.step(true, &mut rng);
+        }
+
+        let row_heat = |y: usize| (0..20).map(|x| fire.at(x, y)).sum::<f32>();
+        assert!(row_heat(7) > row_heat(4));
+        assert!(row_heat(4) > row_heat(0));
+    }
+
+    #[test]
+    fn test_unfed_fire_burns_out() {
+        let mut fire = Fire::new(10, 4, 4, 0.0);
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..10 {
+            fire.step(true, &mut rng);
+        }
+        assert!(!fire.is_out());
+
+        for _ in 0..20 {
+            fire.step(false, &mut rng);
+        }
+        assert!(fire.is_out());
+    }
+
+    #[test]
+    fn test_wind_leans_flames() {
+        let mut fire = Fire::new(30, 6, 12, 2.0);
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            fire.step(true, &mut rng);
+        }
+
+        // Blown right, nothing below the left edge can reach it
+        assert!((0..6).all(|y| fire.at(0, y) == 0.0));
+        assert!((0..6).any(|y| fire.at(29, y) > 0.0));
+    }
+
+    #[test]
+    fn test_lower_quality_draws_fewer_shades() {
+        assert_eq!(shade_heat(0.37, Quality::High), 0.37);
+        assert_eq!(shade_heat(0.37, Quality::Medium), 0.4);
+        assert_eq!(shade_heat(0.37, Quality::Low), 0.25);
+    }
+
+    #[test]
+    fn test_flame_glyph_by_heat() {
+        assert_eq!(flame_glyph(0.05), None);
+        assert_eq!(flame_glyph(0.2), Some('░'));
+        assert_eq!(flame_glyph(0.9), Some('█'));
+    }
+}
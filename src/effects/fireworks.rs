@@ -0,0 +1,382 @@
+use super::frame::Frame;
+use super::particles::{density_share, screen_size, Particle, Particles};
+use super::repaint::Repaint;
+use super::stage::{blend_colors, rgb_color};
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::quality::Quality;
+use crate::resize;
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use crossterm::style::Color;
+use rand::Rng;
+use std::f32::consts::TAU;
+use std::io::Write;
+use std::time::Duration;
+
+const ROCKET_GLYPH: char = '|';
+
+/// Glyphs a spark shows as it burns down, indexed by how far through its
+/// life it is.
+const SPARK_GLYPHS: [char; 4] = ['*', '*', '+', '·'];
+
+/// Cells per second sparks fly out of a burst at, each at its own pace.
+const SPARK_SPEED: (f32, f32) = (6.0, 14.0);
+
+/// Milliseconds a spark burns, each for its own share of the range.
+const SPARK_LIFETIME: (u64, u64) = (700, 1300);
+
+/// Milliseconds the finale's letters take to fly to their places.
+const FINALE_MS: u64 = 1000;
+
+pub struct FireworksConfig {
+    /// Color of the text, where the input doesn't color it itself
+    pub color: (u8, u8, u8),
+    /// Colors bursts take, each one at random
+    pub colors: Vec<(u8, u8, u8)>,
+    /// Sparks in each burst
+    pub sparks: usize,
+    /// Milliseconds between launches, on average
+    pub launch_every: u64,
+    /// Rows per second rockets and sparks fall faster by, every second
+    pub gravity: f32,
+    /// Milliseconds to keep launching rockets; 0 launches forever
+    pub duration: u64,
+    /// Milliseconds in to stop launching and spell out the text, which is
+    /// hidden until then. Without a finale the text shows throughout.
+    pub finale_after: Option<u64>,
+    pub speed: u64,
+    pub quality: Quality,
+}
+
+impl Default for FireworksConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            colors: vec![
+                (255, 0, 0),
+                (255, 215, 0),
+                (50, 205, 50),
+                (0, 191, 255),
+                (255, 0, 255),
+                (255, 255, 255),
+            ],
+            sparks: 40,
+            launch_every: 500,
+            gravity: 10.0,
+            duration: 4000,
+            finale_after: None,
+            speed: 30,
+            quality: Quality::High,
+        }
+    }
+}
+
+/// A rocket rising from the bottom of a `size` screen, launched just hard
+/// enough to stop somewhere in the upper half before gravity wins.
+fn launch_rocket(rng: &mut impl Rng, config: &FireworksConfig, size: (usize, usize)) -> Particle {
+    let (width, height) = (size.0 as f32, size.1 as f32);
+    let bottom = (height - 1.0).max(0.0);
+    let apex = rng.gen_range(0.15..0.5) * height;
+    // Per millisecond squared, to match the particles' velocities
+    let gravity = config.gravity.max(0.1) / 1_000_000.0;
+    let velocity = (2.0 * gravity * (bottom - apex).max(1.0)).sqrt();
+    Particle {
+        x: rng.gen_range(0.15..0.85) * width,
+        y: bottom,
+        velocity: (0.0, -velocity),
+        age: 0,
+        lifetime: u64::MAX,
+        glyph: ROCKET_GLYPH,
+        color: rgb_color(config.colors[rng.gen_range(0..config.colors.len())]),
+    }
+}
+
+/// The sparks a rocket bursts into at `(x, y)`, flying out every way.
+fn burst(
+    rng: &mut impl Rng,
+    config: &FireworksConfig,
+    (x, y): (f32, f32),
+    color: Color,
+) -> Vec<Particle> {
+    let count = (config.sparks as f32 * density_share(config.quality)).round() as usize;
+    (0..count)
+        .map(|_| {
+            let heading = rng.gen_range(0.0..TAU);
+            let speed = rng.gen_range(SPARK_SPEED.0..SPARK_SPEED.1) / 1000.0;
+            Particle {
+                x,
+                y,
+                // Columns are about half as wide as rows are tall
+                velocity: (heading.cos() * speed * 2.0, heading.sin() * speed),
+                age: 0,
+                lifetime: rng.gen_range(SPARK_LIFETIME.0..=SPARK_LIFETIME.1),
+                glyph: SPARK_GLYPHS[0],
+                color,
+            }
+        })
+        .collect()
+}
+
+/// How a spark looks so far through its life: thinner and darker as it
+/// burns down.
+fn spark_style(spark: &Particle) -> (char, Color) {
+    let life = spark.life();
+    let index = ((life * SPARK_GLYPHS.len() as f32) as usize).min(SPARK_GLYPHS.len() - 1);
+    let faded = blend_colors(spark.color, rgb_color((0, 0, 0)), life * life);
+    (SPARK_GLYPHS[index], faded)
+}
+
+/// Where each visible character of the text sits on the screen, and how
+/// it's drawn there.
+struct Letter {
+    x: usize,
+    y: usize,
+    ch: char,
+    color: Color,
+}
+
+/// One particle per letter, flying from `from` to its place in exactly
+/// `FINALE_MS`.
+fn finale_letters(letters: &[Letter], from: (f32, f32)) -> Vec<Particle> {
+    letters
+        .iter()
+        .map(|letter| Particle {
+            x: from.0,
+            y: from.1,
+            velocity: (
+                (letter.x as f32 - from.0) / FINALE_MS as f32,
+                (letter.y as f32 - from.1) / FINALE_MS as f32,
+            ),
+            age: 0,
+            lifetime: FINALE_MS,
+            glyph: letter.ch,
+            color: letter.color,
+        })
+        .collect()
+}
+
+/// Launches rockets across the whole terminal that burst into fading
+/// sparks. `lines` sit in the middle of it throughout, or with a finale
+/// fly out of one last burst into place once the launches are over. The
+/// effect ends when the last spark has burnt out.
+pub fn apply_fireworks_effect(
+    lines: &[String],
+    config: &FireworksConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = stdout();
+    if config.colors.is_empty() {
+        newline()?;
+        return Ok(());
+    }
+
+    let size = screen_size();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let columns: Vec<_> = styled.iter().map(|cells| column_starts(cells)).collect();
+    let text_width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+    let left = size.0.saturating_sub(text_width) / 2;
+    let top = size.1.saturating_sub(styled.len()) / 2;
+    let text_color = rgb_color(config.color);
+    let letters: Vec<Letter> = styled
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| {
+            let starts = &columns[row].0;
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| cell.ch != ' ')
+                .map(move |(index, cell)| Letter {
+                    x: left + starts[index],
+                    y: top + row,
+                    ch: cell.ch,
+                    color: cell.color.unwrap_or(text_color),
+                })
+        })
+        .collect();
+    let middle = (
+        (left + text_width / 2) as f32,
+        (top + styled.len() / 2) as f32,
+    );
+
+    let mut rng = rng::new_rng();
+    let mut rockets = Particles::new(size, 0.0, 1, false);
+    let mut sparks = Particles::new(size, 0.0, 1, false);
+    let mut flying = Particles::new(size, 0.0, 1, false);
+    let mut finale_started = false;
+
+    let render = |rockets: &Particles, sparks: &Particles, flying: &Particles, text: bool| {
+        let mut frame = Frame::new(size.0, size.1);
+        if text {
+            for letter in &letters {
+                frame.put(letter.x, letter.y, letter.ch, letter.color);
+            }
+        }
+        sparks.draw_styled(&mut frame, spark_style);
+        rockets.draw(&mut frame, |rocket| rocket.glyph);
+        flying.draw(&mut frame, |letter| letter.glyph);
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let step = config.speed.max(1);
+    let launching_for = config
+        .finale_after
+        .or(Some(config.duration).filter(|&ms| ms > 0));
+    let _terminal = TerminalGuard::hide_cursor(true)?;
+
+    // The show fills the screen from its top-left corner, so there's no
+    // centering to redo on a resize
+    let origin = Some((0, 0));
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0;
+    let mut next_launch = 0;
+    let text_shown = |finale_started: bool, flying: &Particles| {
+        config.finale_after.is_none() || (finale_started && flying.particles.is_empty())
+    };
+    loop {
+        let launching = launching_for.is_none_or(|ms| elapsed < ms);
+        while launching && elapsed >= next_launch {
+            rockets
+                .particles
+                .push(launch_rocket(&mut rng, config, size));
+            next_launch += (config.launch_every as f32 * rng.gen_range(0.5..1.5)) as u64;
+        }
+        if config.finale_after.is_some() && !launching && !finale_started {
+            let color = rgb_color(config.colors[rng.gen_range(0..config.colors.len())]);
+            sparks
+                .particles
+                .extend(burst(&mut rng, config, middle, color));
+            flying.particles.extend(finale_letters(&letters, middle));
+            finale_started = true;
+        }
+
+        rockets.pull(config.gravity, step);
+        sparks.pull(config.gravity, step);
+        rockets.step(step, false, config.quality, || unreachable!());
+        sparks.step(step, false, config.quality, || unreachable!());
+        flying.step(step, false, config.quality, || unreachable!());
+
+        // Rockets burst once they stop climbing
+        let mut bursts = Vec::new();
+        rockets.particles.retain(|rocket| {
+            let climbing = rocket.velocity.1 < 0.0;
+            if !climbing {
+                bursts.push(((rocket.x, rocket.y), rocket.color));
+            }
+            climbing
+        });
+        for (at, color) in bursts {
+            sparks.particles.extend(burst(&mut rng, config, at, color));
+        }
+
+        let settled = [&rockets, &sparks, &flying]
+            .iter()
+            .all(|field| field.particles.is_empty());
+        if !launching && settled {
+            break;
+        }
+
+        resize::track(&mut None)?;
+        let frame_timer = FrameTimer::start();
+        let text = text_shown(finale_started, &flying);
+        repaint.draw_on_time(
+            render(&rockets, &sparks, &flying, text),
+            &mut stdout,
+            origin,
+        )?;
+        stdout.flush()?;
+        frame_timer.finish();
+
+        playback::sleep(frame_duration)?;
+        elapsed += step;
+    }
+
+    let text = text_shown(finale_started, &flying);
+    repaint.draw(
+        render(&rockets, &sparks, &flying, text),
+        &mut stdout,
+        origin,
+    )?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_fireworks_config_default() {
+        let config = FireworksConfig::default();
+
+        assert_eq!(config.sparks, 40);
+        assert_eq!(config.finale_after, None);
+        assert_eq!(config.colors.len(), 6);
+    }
+
+    #[test]
+    fn test_rockets_burst_in_the_upper_half() {
+        let config = FireworksConfig::default();
+        let mut rng = StdRng::seed_from_u64(10);
+        let size = (80, 20);
+        for _ in 0..20 {
+            let mut rockets = Particles::new(size, 0.0, 1, false);
+            rockets
+                .particles
+                .push(launch_rocket(&mut rng, &config, size));
+            while rockets.particles[0].velocity.1 < 0.0 {
+                rockets.pull(config.gravity, 10);
+                rockets.step(10, false, Quality::High, || unreachable!());
+            }
+            let apex = rockets.particles[0].y;
+            assert!((2.0..11.0).contains(&apex), "burst at row {apex}");
+        }
+    }
+
+    #[test]
+    fn test_bursts_scale_with_quality_and_sparks_fade() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let color = rgb_color((255, 0, 0));
+        let high = burst(&mut rng, &FireworksConfig::default(), (5.0, 5.0), color);
+        let low_config = FireworksConfig {
+            quality: Quality::Low,
+            ..FireworksConfig::default()
+        };
+        let low = burst(&mut rng, &low_config, (5.0, 5.0), color);
+        assert_eq!(high.len(), 40);
+        assert_eq!(low.len(), 12);
+
+        let mut spark = high.into_iter().next().unwrap();
+        assert_eq!(spark_style(&spark), ('*', color));
+        spark.age = spark.lifetime * 9 / 10;
+        let (glyph, faded) = spark_style(&spark);
+        assert_eq!(glyph, '·');
+        assert!(matches!(faded, Color::Rgb { r, .. } if r < 60));
+    }
+
+    #[test]
+    fn test_finale_letters_fly_to_their_places() {
+        let letters = [Letter {
+            x: 30,
+            y: 4,
+            ch: 'A',
+            color: Color::White,
+        }];
+        let mut flying = Particles::new((80, 20), 0.0, 1, false);
+        flying.particles = finale_letters(&letters, (40.0, 10.0));
+        flying.step(FINALE_MS / 2, false, Quality::High, || unreachable!());
+
+        let letter = &flying.particles[0];
+        assert_eq!((letter.x, letter.y), (35.0, 7.0));
+        assert_eq!(letter.glyph, 'A');
+        flying.step(FINALE_MS / 2, false, Quality::High, || unreachable!());
+        assert!(flying.particles.is_empty());
+    }
+}
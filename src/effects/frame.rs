@@ -0,0 +1,256 @@
+use crate::capability::fg;
+use crossterm::{
+    cursor, queue,
+    style::{Color, Print, ResetColor},
+};
+use std::io::Write;
+use unicode_width::UnicodeWidthChar;
+
+/// One terminal column of a frame. Wide characters occupy their first column
+/// and leave `Covered` in the columns after it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    Blank,
+    Char { ch: char, color: Color },
+    Covered,
+}
+
+/// A grid of cells spanning several terminal rows, drawn in one go. Effects
+/// that move characters between rows fill a fresh frame each tick instead of
+/// printing a single line in place.
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::Blank; width * height],
+        }
+    }
+
+    /// Places `ch` at column `x` of row `y`. Anything falling outside the
+    /// frame, including the right half of a wide character, is dropped.
+    pub fn put(&mut self, x: usize, y: usize, ch: char, color: Color) {
+        let width = ch.width().unwrap_or(0);
+        if width == 0 || y >= self.height || x + width > self.width {
+            return;
+        }
+
+        let row = y * self.width;
+        self.cells[row + x] = Cell::Char { ch, color };
+        for covered in &mut self.cells[row + x + 1..row + x + width] {
+            *covered = Cell::Covered;
+        }
+    }
+
+    /// Queues every row, blanks included, so the previous frame is fully
+    /// overwritten once the caller flushes. With `redraw` and no centering,
+    /// the cursor first climbs back to the frame's top row.
+    pub fn draw(
+        &self,
+        stdout: &mut impl Write,
+        centering_offsets: Option<(u16, u16)>,
+        redraw: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some((top_offset, left_offset)) = centering_offsets {
+            queue!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+        } else if redraw && self.height > 1 {
+            queue!(stdout, cursor::MoveUp((self.height - 1) as u16))?;
+        }
+
+        for (y, row) in self.cells.chunks(self.width.max(1)).enumerate() {
+            match centering_offsets {
+                Some((_, left_offset)) => queue!(stdout, cursor::MoveToColumn(left_offset))?,
+                None => queue!(stdout, cursor::MoveToColumn(0))?,
+            }
+
+            for cell in row {
+                match *cell {
+                    Cell::Blank => queue!(stdout, Print(' '))?,
+                    Cell::Char { ch, color } => queue!(stdout, fg(color), Print(ch))?,
+                    Cell::Covered => {}
+                }
+            }
+
+            if y + 1 < self.height {
+                queue!(stdout, Print("\r\n"))?;
+            }
+        }
+
+        queue!(stdout, ResetColor)?;
+        Ok(())
+    }
+
+    /// Whether `other` covers the same cells, so one can be drawn over the
+    /// other cell by cell.
+    pub fn same_size(&self, other: &Frame) -> bool {
+        (self.width, self.height) == (other.width, other.height)
+    }
+
+    /// Queues only the cells that differ from `previous`, a frame of the
+    /// same size already on screen. Without centering the cursor is expected
+    /// on the last row, where `draw` leaves it, and is left there again.
+    pub fn draw_changes(
+        &self,
+        previous: &Frame,
+        stdout: &mut impl Write,
+        centering_offsets: Option<(u16, u16)>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let last_row = self.height.saturating_sub(1);
+        let mut row = last_row;
+        let mut color = None;
+        let rows = self.cells.chunks(self.width.max(1));
+        let previous_rows = previous.cells.chunks(self.width.max(1));
+
+        for (y, (cells, before)) in rows.zip(previous_rows).enumerate() {
+            let mut x = 0;
+            while x < cells.len() {
+                if cells[x] == before[x] {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < cells.len() && cells[x] != before[x] {
+                    x += 1;
+                }
+
+                match centering_offsets {
+                    Some((top_offset, left_offset)) => queue!(
+                        stdout,
+                        cursor::MoveTo(left_offset + start as u16, top_offset + y as u16)
+                    )?,
+                    None => {
+                        if y < row {
+                            queue!(stdout, cursor::MoveUp((row - y) as u16))?;
+                        } else if y > row {
+                            queue!(stdout, cursor::MoveDown((y - row) as u16))?;
+                        }
+                        row = y;
+                        queue!(stdout, cursor::MoveToColumn(start as u16))?;
+                    }
+                }
+
+                for cell in &cells[start..x] {
+                    match *cell {
+                        Cell::Blank => queue!(stdout, Print(' '))?,
+                        Cell::Char {
+                            ch,
+                            color: cell_color,
+                        } => {
+                            // Neighbouring cells usually share a color, so only switch when it changes
+                            if color != Some(cell_color) {
+                                queue!(stdout, fg(cell_color))?;
+                                color = Some(cell_color);
+                            }
+                            queue!(stdout, Print(ch))?;
+                        }
+                        Cell::Covered => {}
+                    }
+                }
+            }
+        }
+
+        if centering_offsets.is_none() && row < last_row {
+            queue!(stdout, cursor::MoveDown((last_row - row) as u16))?;
+        }
+        queue!(stdout, ResetColor)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color::Rgb { r: 255, g: 0, b: 0 };
+
+    #[test]
+    fn test_new_frame_is_blank() {
+        let frame = Frame::new(3, 2);
+        assert_eq!(frame.cells.len(), 6);
+        assert!(frame.cells.iter().all(|&cell| cell == Cell::Blank));
+    }
+
+    #[test]
+    fn test_put_places_characters_by_row() {
+        let mut frame = Frame::new(3, 2);
+        frame.put(1, 1, 'x', RED);
+        assert_eq!(
+            frame.cells[4],
+            Cell::Char {
+                ch: 'x',
+                color: RED
+            }
+        );
+        assert_eq!(frame.cells[1], Cell::Blank);
+    }
+
+    #[test]
+    fn test_wide_characters_cover_the_next_column() {
+        let mut frame = Frame::new(4, 1);
+        frame.put(1, 0, '漢', RED);
+        assert_eq!(frame.cells[2], Cell::Covered);
+
+        // A wide character that doesn't fit is left out entirely
+        frame.put(3, 0, '字', RED);
+        assert_eq!(frame.cells[3], Cell::Blank);
+    }
+
+    #[test]
+    fn test_out_of_bounds_is_ignored() {
+        let mut frame = Frame::new(2, 2);
+        frame.put(5, 0, 'x', RED);
+        frame.put(0, 5, 'x', RED);
+        assert!(frame.cells.iter().all(|&cell| cell == Cell::Blank));
+    }
+
+    fn frame(rows: &[&str]) -> Frame {
+        let mut frame = Frame::new(rows[0].len(), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, ch) in row.chars().enumerate() {
+                frame.put(x, y, ch, RED);
+            }
+        }
+        frame
+    }
+
+    fn changes(previous: &Frame, next: &Frame, centering_offsets: Option<(u16, u16)>) -> String {
+        let mut out = Vec::new();
+        next.draw_changes(previous, &mut out, centering_offsets)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_draw_changes_repaints_changed_cells_only() {
+        let previous = frame(&["abc", "def"]);
+        let out = changes(&previous, &frame(&["abc", "dEf"]), None);
+
+        // Already on the last row, so only the column moves
+        assert!(out.starts_with("\x1b[2G"));
+        assert!(out.contains('E'));
+        assert!(!out.contains(['a', 'd', 'f']));
+    }
+
+    #[test]
+    fn test_draw_changes_returns_to_the_last_row() {
+        let previous = frame(&["abc", "def"]);
+        let out = changes(&previous, &frame(&["Abc", "def"]), None);
+        assert!(out.starts_with("\x1b[1A\x1b[1G"));
+        assert!(out.contains("\x1b[1B"));
+
+        let centered = changes(&previous, &frame(&["abC", "def"]), Some((4, 10)));
+        assert!(centered.starts_with("\x1b[5;13H"));
+    }
+
+    #[test]
+    fn test_draw_changes_of_identical_frames_moves_nothing() {
+        let previous = frame(&["abc", "def"]);
+        assert_eq!(changes(&previous, &frame(&["abc", "def"]), None), "\x1b[0m");
+    }
+}
@@ -0,0 +1,125 @@
+use crate::center::offsets_for;
+use crate::layout::{self, Flow};
+use unicode_width::UnicodeWidthChar;
+
+/// A cell in a wrapped text grid, measured in terminal columns and rows.
+#[derive(Debug, Clone)]
+pub struct Position2D {
+    pub x: usize,
+    pub y: usize,
+}
+
+/// Width in terminal columns of the widest row of `grid`.
+pub fn grid_width(grid: &[Vec<char>]) -> usize {
+    grid.iter()
+        .map(|line| line.iter().map(|ch| ch.width().unwrap_or(0)).sum())
+        .max()
+        .unwrap_or(0)
+}
+
+/// The layout pass of effects drawn over wrapped text: the grid and where
+/// each of its cells sits, worked out once per wrap width so the frame loop
+/// only does color math.
+pub struct Layout {
+    pub grid: Vec<Vec<char>>,
+    /// Column and row of every cell of `grid`, wide glyphs taking two columns
+    pub positions: Vec<Vec<Position2D>>,
+    /// Columns of the widest row
+    pub width: usize,
+}
+
+impl Layout {
+    pub fn wrap(text: &str, terminal_width: usize, flow: Flow) -> Self {
+        let grid = layout::flow(text, terminal_width, flow);
+        let positions = grid
+            .iter()
+            .enumerate()
+            .map(|(y, line)| {
+                let mut x = 0;
+                line.iter()
+                    .map(|&ch| {
+                        let pos = Position2D { x, y };
+                        x += ch.width().unwrap_or(0);
+                        pos
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            width: grid_width(&grid),
+            grid,
+            positions,
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.grid.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height() == 0
+    }
+
+    /// `measure` of every cell's position, e.g. how far along an axis it is,
+    /// for effects to cache alongside the layout.
+    pub fn map<T>(&self, measure: impl Fn(&Position2D) -> T) -> Vec<Vec<T>> {
+        self.positions
+            .iter()
+            .map(|line| line.iter().map(&measure).collect())
+            .collect()
+    }
+
+    /// Top and left offsets that center the layout in a terminal of
+    /// `terminal` size.
+    pub fn centered_in(&self, terminal: (u16, u16)) -> (u16, u16) {
+        let offsets = offsets_for((self.width as u16, self.height() as u16), terminal);
+        (offsets.top, offsets.left)
+    }
+}
+
+/// Projects `pos` onto the axis pointing along `angle` degrees
+/// (0 = left to right, 90 = top to bottom).
+pub fn project(pos: &Position2D, angle: f32) -> f32 {
+    let angle_rad = angle.to_radians();
+    angle_rad.cos() * pos.x as f32 + angle_rad.sin() * pos.y as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_position2d_creation() {
+        let pos = Position2D { x: 10, y: 20 };
+        assert_eq!(pos.x, 10);
+        assert_eq!(pos.y, 20);
+    }
+
+    #[test]
+    fn test_grid_width_counts_columns() {
+        let grid = layout::flow("ab\nＡＢＣ", 10, Flow::default());
+        assert_eq!(grid_width(&grid), 6);
+        assert_eq!(grid_width(&[]), 0);
+    }
+
+    #[test]
+    fn test_layout_places_cells_in_columns() {
+        let layout = Layout::wrap("aＢc\nd", 10, Flow::default());
+        assert_eq!(layout.width, 4);
+        assert_eq!(layout.height(), 2);
+        let columns = layout.map(|pos| (pos.x, pos.y));
+        assert_eq!(columns, vec![vec![(0, 0), (1, 0), (3, 0)], vec![(0, 1)]]);
+
+        assert_eq!(layout.centered_in((20, 10)), (4, 8));
+        assert!(Layout::wrap("\n", 10, Flow::default()).is_empty());
+    }
+
+    #[test]
+    fn test_project() {
+        let pos = Position2D { x: 3, y: 4 };
+        assert_approx_eq!(project(&pos, 0.0), 3.0, 0.001);
+        assert_approx_eq!(project(&pos, 90.0), 4.0, 0.001);
+        assert_approx_eq!(project(&pos, 45.0), 7.0 / 2.0_f32.sqrt(), 0.001);
+    }
+}
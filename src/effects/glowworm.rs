@@ -0,0 +1,188 @@
+use super::frame::Frame;
+use super::repaint::Repaint;
+use super::stage::{blend_colors, rgb_color};
+use crate::ansi::{column_starts, parse_styled};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use crossterm::style::Color;
+use std::io::Write;
+use std::time::Duration;
+
+/// Glow below which a trail counts as gone.
+const FADED: f32 = 0.01;
+
+pub struct GlowwormConfig {
+    /// Color of the text the glowworm isn't lighting, where the input
+    /// didn't color it
+    pub color: (u8, u8, u8),
+    /// Color of the glowworm itself
+    pub highlight: (u8, u8, u8),
+    /// Characters the bright segment spans
+    pub length: usize,
+    /// Share (0.0 to 1.0) of its glow the trail loses each step
+    pub tail_decay: f32,
+    /// Milliseconds per step from one character to the next
+    pub speed: u64,
+    /// Trips through the text (0 for forever)
+    pub cycles: u32,
+}
+
+impl Default for GlowwormConfig {
+    fn default() -> Self {
+        Self {
+            color: (128, 128, 128),
+            highlight: (120, 255, 80),
+            length: 4,
+            tail_decay: 0.25,
+            speed: 40,
+            cycles: 1,
+        }
+    }
+}
+
+/// A character along the glowworm's path, in reading order.
+struct Stop {
+    x: usize,
+    y: usize,
+    ch: char,
+    color: Color,
+}
+
+/// Moves the glow on a step with the glowworm's head at `head`: the
+/// `length` characters up to it shine fully, and every other one keeps
+/// what's left of its glow once `tail_decay` of it fades.
+fn step_glow(glow: &mut [f32], head: Option<usize>, length: usize, tail_decay: f32) {
+    let keep = 1.0 - tail_decay.clamp(0.0, 1.0);
+    for (index, lit) in glow.iter_mut().enumerate() {
+        let in_segment = head.is_some_and(|head| index <= head && head - index < length.max(1));
+        *lit = if in_segment { 1.0 } else { *lit * keep };
+    }
+}
+
+/// Sends a short bright glowworm crawling through the characters of
+/// `lines` in reading order, from the end of each line on to the start of
+/// the next, with a trail fading out behind it.
+pub fn apply_glowworm_effect(
+    lines: &[String],
+    config: &GlowwormConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let base_color = rgb_color(config.color);
+    let highlight = rgb_color(config.highlight);
+    let mut path = Vec::new();
+    let mut blanks = Vec::new();
+    let mut width = 0;
+    for (y, line) in lines.iter().enumerate() {
+        let cells = parse_styled(line);
+        let (columns, line_width) = column_starts(&cells);
+        width = width.max(line_width);
+        for (cell, x) in cells.iter().zip(columns) {
+            // It crawls over the characters, skipping the gaps between them
+            let stop = Stop {
+                x,
+                y,
+                ch: cell.ch,
+                color: cell.color.unwrap_or(base_color),
+            };
+            if cell.ch.is_whitespace() {
+                blanks.push(stop);
+            } else {
+                path.push(stop);
+            }
+        }
+    }
+    if path.is_empty() {
+        newline()?;
+        return Ok(());
+    }
+
+    let render = |glow: &[f32]| {
+        let mut frame = Frame::new(width, lines.len());
+        for stop in &blanks {
+            frame.put(stop.x, stop.y, stop.ch, stop.color);
+        }
+        for (stop, &lit) in path.iter().zip(glow) {
+            frame.put(
+                stop.x,
+                stop.y,
+                stop.ch,
+                blend_colors(stop.color, highlight, lit),
+            );
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let cycles_to_run = if config.cycles == 0 {
+        usize::MAX
+    } else {
+        config.cycles as usize
+    };
+    let mut glow = vec![0.0; path.len()];
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    let mut draw = |glow: &[f32],
+                    centering_offsets: &mut Option<(u16, u16)>|
+     -> Result<(), Box<dyn std::error::Error>> {
+        resize::track(centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(render(glow), &mut stdout, *centering_offsets)?;
+        stdout.flush()?;
+        frame_timer.finish();
+        playback::sleep(frame_duration)?;
+        Ok(())
+    };
+    for _cycle in 0..cycles_to_run {
+        // On past the last character, until the whole segment has left it
+        for head in 0..path.len() + config.length.max(1) - 1 {
+            step_glow(&mut glow, Some(head), config.length, config.tail_decay);
+            draw(&glow, &mut centering_offsets)?;
+        }
+    }
+    // Then the trail fades out
+    while glow.iter().any(|&lit| lit > FADED) && config.tail_decay > 0.0 {
+        step_glow(&mut glow, None, config.length, config.tail_decay);
+        draw(&glow, &mut centering_offsets)?;
+    }
+
+    glow.fill(0.0);
+    repaint.draw(render(&glow), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glowworm_config_default() {
+        let config = GlowwormConfig::default();
+
+        assert_eq!(config.length, 4);
+        assert_eq!(config.tail_decay, 0.25);
+        assert_eq!(config.cycles, 1);
+    }
+
+    #[test]
+    fn test_step_glow_lights_the_segment_and_fades_the_trail() {
+        let mut glow = vec![0.0; 6];
+        step_glow(&mut glow, Some(1), 2, 0.5);
+        assert_eq!(glow, [1.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+
+        step_glow(&mut glow, Some(3), 2, 0.5);
+        assert_eq!(glow, [0.5, 0.5, 1.0, 1.0, 0.0, 0.0]);
+
+        // With the head gone, only the trail is left, fading
+        step_glow(&mut glow, None, 2, 0.5);
+        assert_eq!(glow, [0.25, 0.25, 0.5, 0.5, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,278 @@
+use super::driver::with_phase;
+use super::geometry::{project, Layout};
+use super::stage::{blend_colors, rgb_color, ColorStage};
+use crate::capability::fg;
+use crate::center::strip_ansi_codes;
+use crate::layout::Flow;
+use crate::output::{newline, stdout, Stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::{self, TerminalGuard};
+use crate::soak::FrameTimer;
+use crossterm::{
+    cursor, queue,
+    style::{Color, Print, ResetColor},
+};
+use std::time::Duration;
+
+pub struct GradientConfig {
+    pub stops: Vec<(u8, u8, u8)>,
+    pub angle: f32,
+    pub animate: bool,
+    pub speed: u64,
+    pub duration: u64,
+    pub cycles: u32,
+    pub levels: Option<u32>,
+    pub phase: f32,
+    pub terminal_width: Option<usize>,
+    /// How the text wraps to the terminal width and lines up
+    pub flow: Flow,
+}
+
+impl Default for GradientConfig {
+    fn default() -> Self {
+        Self {
+            stops: vec![(255, 0, 0), (255, 165, 0), (255, 255, 0)],
+            angle: 0.0,
+            animate: false,
+            speed: 50,
+            duration: 2000,
+            cycles: 1,
+            levels: None,
+            phase: 0.0,
+            terminal_width: None,
+            flow: Flow::default(),
+        }
+    }
+}
+
+/// Color at `t` (0.0 to 1.0) along evenly spaced `stops`.
+pub fn color_at(stops: &[Color], t: f32) -> Color {
+    match stops {
+        [] => Color::Reset,
+        [only] => *only,
+        _ => {
+            let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+            let index = (scaled.floor() as usize).min(stops.len() - 2);
+            blend_colors(stops[index], stops[index + 1], scaled - index as f32)
+        }
+    }
+}
+
+/// Position of every cell along the gradient axis, normalized so the text
+/// spans exactly 0.0 to 1.0.
+fn gradient_positions(layout: &Layout, angle: f32) -> Vec<Vec<f32>> {
+    let projected = layout.map(|pos| project(pos, angle));
+
+    let (min, max) = projected
+        .iter()
+        .flatten()
+        .fold((f32::MAX, f32::MIN), |(min, max), &p| {
+            (min.min(p), max.max(p))
+        });
+    // cos(90°) isn't exactly zero, so treat a sliver of extent as none
+    let span = max - min;
+    let span = if span > 1e-3 { span } else { 0.0 };
+
+    projected
+        .into_iter()
+        .map(|line| {
+            line.into_iter()
+                .map(|p| if span > 0.0 { (p - min) / span } else { 0.0 })
+                .collect()
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_gradient(
+    stdout: &mut Stdout,
+    grid: &[Vec<char>],
+    positions: &[Vec<f32>],
+    stops: &[Color],
+    stage: &ColorStage,
+    offset: Option<f32>,
+    centering_offsets: Option<(u16, u16)>,
+    redraw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let frame_timer = FrameTimer::start();
+    if let Some((top_offset, left_offset)) = centering_offsets {
+        queue!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+    } else if redraw && grid.len() > 1 {
+        queue!(stdout, cursor::MoveUp((grid.len() - 1) as u16))?;
+    }
+
+    for (y, (line, line_positions)) in grid.iter().zip(positions).enumerate() {
+        if let Some((_, left_offset)) = centering_offsets {
+            queue!(stdout, cursor::MoveToColumn(left_offset))?;
+        } else {
+            queue!(stdout, cursor::MoveToColumn(0))?;
+        }
+
+        for (&ch, &t) in line.iter().zip(line_positions) {
+            let t = match offset {
+                Some(offset) => (t + offset).rem_euclid(1.0),
+                None => t,
+            };
+            let t = stage.quantize(t);
+            queue!(stdout, fg(color_at(stops, t)), Print(ch))?;
+        }
+
+        if y + 1 < grid.len() {
+            queue!(stdout, Print("\r\n"))?;
+        }
+    }
+
+    queue!(stdout, ResetColor)?;
+    stdout.flush_frame()?;
+    frame_timer.finish();
+    Ok(())
+}
+
+/// Colors `text` with a multi-stop gradient along `config.angle`. With
+/// `config.animate`, the gradient slides across the text once per cycle and
+/// wraps back to its first stop so the loop is seamless.
+pub fn apply_gradient_effect(
+    text: &str,
+    config: &GradientConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let terminal_width = config
+        .terminal_width
+        .unwrap_or_else(|| screen::size().map(|(w, _)| w as usize).unwrap_or(80));
+
+    let layout = Layout::wrap(&strip_ansi_codes(text), terminal_width, config.flow);
+    if layout.is_empty() {
+        newline()?;
+        return Ok(());
+    }
+    let grid = &layout.grid;
+
+    let positions = gradient_positions(&layout, config.angle);
+    let stage = ColorStage::new(config.levels);
+    let mut stops: Vec<Color> = config.stops.iter().copied().map(rgb_color).collect();
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    if !config.animate {
+        draw_gradient(
+            &mut stdout,
+            grid,
+            &positions,
+            &stops,
+            &stage,
+            None,
+            centering_offsets,
+            false,
+        )?;
+    } else {
+        if let Some(&first) = stops.first() {
+            stops.push(first);
+        }
+
+        let frame_duration = Duration::from_millis(config.speed);
+        let total_frames = (config.duration / config.speed).max(1) as usize;
+        let cycles_to_run = if config.cycles == 0 {
+            usize::MAX
+        } else {
+            config.cycles as usize
+        };
+
+        let mut drawn = false;
+        for _cycle in 0..cycles_to_run {
+            for frame in 0..total_frames {
+                // Sliding backwards makes the colors travel along the angle
+                let offset = 1.0 - with_phase(frame as f32 / total_frames as f32, config.phase);
+                resize::track(&mut centering_offsets)?;
+                draw_gradient(
+                    &mut stdout,
+                    grid,
+                    &positions,
+                    &stops,
+                    &stage,
+                    Some(offset),
+                    centering_offsets,
+                    drawn,
+                )?;
+                drawn = true;
+
+                playback::sleep(frame_duration)?;
+            }
+        }
+    }
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    const RED: Color = Color::Rgb { r: 255, g: 0, b: 0 };
+    const BLUE: Color = Color::Rgb { r: 0, g: 0, b: 255 };
+    const WHITE: Color = Color::Rgb {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+
+    #[test]
+    fn test_gradient_config_default() {
+        let config = GradientConfig::default();
+
+        assert_eq!(config.stops.len(), 3);
+        assert_eq!(config.angle, 0.0);
+        assert!(!config.animate);
+        assert_eq!(config.speed, 50);
+        assert_eq!(config.duration, 2000);
+        assert_eq!(config.cycles, 1);
+        assert_eq!(config.levels, None);
+    }
+
+    #[test]
+    fn test_color_at_stops() {
+        let stops = [RED, WHITE, BLUE];
+        assert_eq!(color_at(&stops, 0.0), RED);
+        assert_eq!(color_at(&stops, 0.5), WHITE);
+        assert_eq!(color_at(&stops, 1.0), BLUE);
+        assert_eq!(
+            color_at(&stops, 0.25),
+            Color::Rgb {
+                r: 255,
+                g: 127,
+                b: 127
+            }
+        );
+    }
+
+    #[test]
+    fn test_color_at_single_stop() {
+        assert_eq!(color_at(&[BLUE], 0.7), BLUE);
+    }
+
+    #[test]
+    fn test_gradient_positions_horizontal() {
+        let positions = gradient_positions(&Layout::wrap("abc\nde", 80, Flow::default()), 0.0);
+
+        assert_approx_eq!(positions[0][0], 0.0, 0.001);
+        assert_approx_eq!(positions[0][1], 0.5, 0.001);
+        assert_approx_eq!(positions[0][2], 1.0, 0.001);
+        assert_approx_eq!(positions[1][1], 0.5, 0.001);
+    }
+
+    #[test]
+    fn test_gradient_positions_vertical_and_flat() {
+        let positions = gradient_positions(&Layout::wrap("ab\ncd\nef", 80, Flow::default()), 90.0);
+        assert_approx_eq!(positions[0][1], 0.0, 0.001);
+        assert_approx_eq!(positions[1][0], 0.5, 0.001);
+        assert_approx_eq!(positions[2][1], 1.0, 0.001);
+
+        // A single-row vertical gradient has no extent to spread over
+        let flat = gradient_positions(&Layout::wrap("abc", 80, Flow::default()), 90.0);
+        assert!(flat[0].iter().all(|&t| t == 0.0));
+    }
+}
@@ -0,0 +1,91 @@
+/// A width × height field of simulation state, one value per terminal cell.
+/// Cellular effects step a grid each frame and then map its values to
+/// glyphs and colors in a `Frame`.
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Copy> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The value at column `x` of row `y`, or `None` outside the grid.
+    /// Signed coordinates let neighbours be looked up without underflow.
+    pub fn get(&self, x: isize, y: isize) -> Option<T> {
+        self.index(x, y).map(|index| self.cells[index])
+    }
+
+    /// Sets the value at column `x` of row `y`; anything outside is dropped.
+    pub fn set(&mut self, x: isize, y: isize, value: T) {
+        if let Some(index) = self.index(x, y) {
+            self.cells[index] = value;
+        }
+    }
+
+    /// Sets every cell of row `y` from `value(x)`.
+    pub fn fill_row(&mut self, y: usize, mut value: impl FnMut(usize) -> T) {
+        if y >= self.height {
+            return;
+        }
+        let row = y * self.width;
+        for (x, cell) in self.cells[row..row + self.width].iter_mut().enumerate() {
+            *cell = value(x);
+        }
+    }
+
+    fn index(&self, x: isize, y: isize) -> Option<usize> {
+        let inside =
+            (0..self.width as isize).contains(&x) && (0..self.height as isize).contains(&y);
+        inside.then(|| y as usize * self.width + x as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_and_set_by_row() {
+        let mut grid = Grid::new(3, 2, 0);
+        grid.set(2, 1, 7);
+
+        assert_eq!(grid.get(2, 1), Some(7));
+        assert_eq!(grid.get(2, 0), Some(0));
+        assert_eq!(grid.cells[5], 7);
+    }
+
+    #[test]
+    fn test_outside_the_grid_is_ignored() {
+        let mut grid = Grid::new(2, 2, 0);
+        grid.set(-1, 0, 1);
+        grid.set(0, 2, 1);
+
+        assert_eq!(grid.get(-1, 0), None);
+        assert_eq!(grid.get(2, 0), None);
+        assert!(grid.cells.iter().all(|&cell| cell == 0));
+    }
+
+    #[test]
+    fn test_fill_row() {
+        let mut grid = Grid::new(3, 2, 0);
+        grid.fill_row(1, |x| x + 1);
+        grid.fill_row(5, |_| 9);
+
+        assert_eq!(grid.cells, vec![0, 0, 0, 1, 2, 3]);
+    }
+}
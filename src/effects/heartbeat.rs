@@ -0,0 +1,187 @@
+use super::driver::with_phase;
+use super::pulse::{draw_pulse, envelope, PulseConfig};
+use super::stage::{rgb_color, ColorStage};
+use crate::ansi::{parse_styled, StyledChar};
+use crate::easing::EasingFunction;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::TerminalGuard;
+use std::time::Duration;
+
+/// One heartbeat as (progress, brightness) keyframes: a strong thump, a
+/// softer one right after it, then a rest until the next beat.
+pub const HEARTBEAT: [(f32, f32); 5] = [
+    (0.0, 0.0),
+    (0.06, 1.0),
+    (0.14, 0.25),
+    (0.22, 0.8),
+    (0.4, 0.0),
+];
+
+/// The heart `--heart` appends, from its size at rest to its size mid-thump.
+const HEARTS: [char; 2] = ['♡', '♥'];
+
+/// How bright a thump has to be for the heart to swell.
+const SWELL_AT: f32 = 0.5;
+
+pub struct HeartbeatConfig {
+    pub color: (u8, u8, u8),
+    /// Brightness between beats, as a fraction of `color`
+    pub min_brightness: f32,
+    /// Beats per minute
+    pub bpm: u32,
+    pub speed: u64,
+    pub cycles: u32,
+    /// Append a heart to the text that swells with each thump
+    pub heart: bool,
+    /// Color of the heart; the text's color when there's none
+    pub heart_color: Option<(u8, u8, u8)>,
+    pub levels: Option<u32>,
+    pub phase: f32,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            color: (220, 20, 60),
+            min_brightness: 0.3,
+            bpm: 72,
+            speed: 20,
+            cycles: 3,
+            heart: false,
+            heart_color: None,
+            levels: None,
+            phase: 0.0,
+        }
+    }
+}
+
+impl HeartbeatConfig {
+    /// Milliseconds from one beat to the next.
+    pub fn period(&self) -> u64 {
+        60_000 / u64::from(self.bpm.max(1))
+    }
+}
+
+/// The glyph of the heart at `intensity` through a beat.
+fn heart_at(intensity: f32) -> char {
+    if intensity >= SWELL_AT {
+        HEARTS[1]
+    } else {
+        HEARTS[0]
+    }
+}
+
+/// Pulses all of `lines` together to a heartbeat: two quick thumps of
+/// brightness and a rest, `config.bpm` times a minute. With `config.heart`
+/// a heart after the last line beats along, swelling on each thump.
+pub fn apply_heartbeat_effect(
+    lines: &[String],
+    config: &HeartbeatConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let mut styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+
+    if styled.iter().all(|cells| cells.is_empty()) && !config.heart {
+        newline()?;
+        return Ok(());
+    }
+    if config.heart {
+        if styled.is_empty() {
+            styled.push(Vec::new());
+        }
+        let last = styled.last_mut().expect("a line to beat beside");
+        if !last.is_empty() {
+            last.push(StyledChar {
+                ch: ' ',
+                color: None,
+            });
+        }
+        last.push(StyledChar {
+            ch: HEARTS[0],
+            color: config.heart_color.map(rgb_color),
+        });
+    }
+
+    let pulse = PulseConfig {
+        color: config.color,
+        min_brightness: config.min_brightness,
+        max_brightness: 1.0,
+        speed: config.speed,
+        cycles: config.cycles,
+        levels: config.levels,
+        phase: config.phase,
+        ..PulseConfig::default()
+    };
+    let frame_duration = Duration::from_millis(config.speed);
+    let frames_per_beat = (config.period() / config.speed.max(1)).max(1) as usize;
+    let cycles_to_run = if config.cycles == 0 {
+        usize::MAX
+    } else {
+        config.cycles as usize
+    };
+    let mut stage = ColorStage::new(config.levels);
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut drawn = false;
+    for _cycle in 0..cycles_to_run {
+        for frame in 0..frames_per_beat {
+            let progress = with_phase(frame as f32 / frames_per_beat as f32, config.phase);
+            let intensity = envelope(progress, &HEARTBEAT, &EasingFunction::EaseOut);
+            if config.heart {
+                if let Some(heart) = styled.last_mut().and_then(|line| line.last_mut()) {
+                    heart.ch = heart_at(intensity);
+                }
+            }
+
+            resize::track(&mut centering_offsets)?;
+            draw_pulse(
+                &mut stdout,
+                &styled,
+                &pulse,
+                intensity,
+                &mut stage,
+                centering_offsets,
+                drawn,
+            )?;
+            drawn = true;
+
+            playback::sleep(frame_duration)?;
+        }
+    }
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_config_default() {
+        let config = HeartbeatConfig::default();
+
+        assert_eq!(config.bpm, 72);
+        assert_eq!(config.period(), 833);
+        assert_eq!(config.cycles, 3);
+        assert!(!config.heart);
+    }
+
+    #[test]
+    fn test_heartbeat_thumps_twice_then_rests() {
+        let easing = EasingFunction::Linear;
+        let at = |progress: f32| envelope(progress, &HEARTBEAT, &easing);
+
+        assert_eq!(at(0.06), 1.0);
+        assert!(at(0.14) < at(0.22));
+        assert!(at(0.22) < at(0.06));
+        assert_eq!(at(0.7), 0.0);
+        assert_eq!(heart_at(at(0.06)), '♥');
+        assert_eq!(heart_at(at(0.7)), '♡');
+    }
+}
@@ -0,0 +1,289 @@
+use super::stage::rgb_color;
+use crate::ansi::{parse_styled, StyledChar};
+use crate::capability::fg;
+use crate::output::{newline, stdout, Stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::{LineStart, TerminalGuard};
+use crate::soak::FrameTimer;
+use crossterm::{
+    cursor, queue,
+    style::{Print, ResetColor},
+};
+use std::path::Path;
+use std::time::Duration;
+
+pub struct KaraokeConfig {
+    /// Color of the words still to come, for text the input didn't color
+    pub color: (u8, u8, u8),
+    /// Color of the word being sung
+    pub highlight: (u8, u8, u8),
+    /// Color of the words already sung; they keep the highlight when there's none
+    pub sung_color: Option<(u8, u8, u8)>,
+    /// Words a minute, when there is no timing to follow
+    pub wpm: u32,
+    /// Each word and the milliseconds it's sung for, from `--timing-file`
+    pub timing: Option<Vec<(String, u64)>>,
+}
+
+impl Default for KaraokeConfig {
+    fn default() -> Self {
+        Self {
+            color: (128, 128, 128),
+            highlight: (255, 220, 0),
+            sung_color: None,
+            wpm: 160,
+            timing: None,
+        }
+    }
+}
+
+/// Parses a timing file: one `word:ms` pair per line, in the order the words
+/// are sung, where `ms` is how long the word is highlighted. Blank lines and
+/// lines starting with `#` are skipped.
+pub fn parse_timing(text: &str) -> Result<Vec<(String, u64)>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| {
+            // The last colon splits, so a word may have one of its own
+            let (word, ms) = line
+                .trim()
+                .rsplit_once(':')
+                .ok_or_else(|| format!("line {}: '{line}' isn't word:ms", index + 1))?;
+            let ms = ms
+                .trim()
+                .parse()
+                .map_err(|err| format!("line {}: '{ms}': {err}", index + 1))?;
+            Ok((word.trim().to_string(), ms))
+        })
+        .collect()
+}
+
+/// Reads the timing file at `path`, in the form `parse_timing` takes.
+pub fn load_timing(path: &Path) -> Result<Vec<(String, u64)>, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| format!("can't read timing {}: {err}", path.display()))?;
+    Ok(parse_timing(&text).map_err(|err| format!("{}: {err}", path.display()))?)
+}
+
+/// Which word, counting through all of `lines`, each cell belongs to, with
+/// `None` for the spaces between them; and the words themselves.
+fn word_indices(lines: &[Vec<StyledChar>]) -> (Vec<Vec<Option<usize>>>, Vec<String>) {
+    let mut words: Vec<String> = Vec::new();
+    let indices = lines
+        .iter()
+        .map(|cells| {
+            let mut in_word = false;
+            cells
+                .iter()
+                .map(|cell| {
+                    if cell.ch.is_whitespace() {
+                        in_word = false;
+                        return None;
+                    }
+                    if !in_word {
+                        words.push(String::new());
+                        in_word = true;
+                    }
+                    let index = words.len() - 1;
+                    words[index].push(cell.ch);
+                    Some(index)
+                })
+                .collect()
+        })
+        .collect();
+    (indices, words)
+}
+
+/// How long each of `words` is highlighted: as the timing has it, checking
+/// it names the same words in the same order, or else at the steady pace
+/// of `config.wpm`.
+fn word_durations(words: &[String], config: &KaraokeConfig) -> Result<Vec<u64>, String> {
+    let Some(timing) = &config.timing else {
+        let ms = 60_000 / u64::from(config.wpm.max(1));
+        return Ok(vec![ms; words.len()]);
+    };
+    if timing.len() != words.len() {
+        return Err(format!(
+            "the timing has {} words but the text has {}",
+            timing.len(),
+            words.len()
+        ));
+    }
+    words
+        .iter()
+        .zip(timing)
+        .enumerate()
+        .map(|(index, (word, (timed, ms)))| {
+            if timed == word {
+                Ok(*ms)
+            } else {
+                Err(format!(
+                    "word {} of the timing is '{timed}' but the text has '{word}'",
+                    index + 1
+                ))
+            }
+        })
+        .collect()
+}
+
+fn draw_karaoke(
+    stdout: &mut Stdout,
+    lines: &[Vec<StyledChar>],
+    indices: &[Vec<Option<usize>>],
+    config: &KaraokeConfig,
+    current: usize,
+    centering_offsets: Option<(u16, u16)>,
+    redraw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let frame_timer = FrameTimer::start();
+    if let Some((top_offset, left_offset)) = centering_offsets {
+        queue!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+    } else if redraw && lines.len() > 1 {
+        queue!(stdout, cursor::MoveUp((lines.len() - 1) as u16))?;
+    }
+
+    let base_color = rgb_color(config.color);
+    let highlight = rgb_color(config.highlight);
+    let sung = config.sung_color.map_or(highlight, rgb_color);
+    for (row, (cells, words)) in lines.iter().zip(indices).enumerate() {
+        match centering_offsets {
+            Some((_, left_offset)) => queue!(stdout, cursor::MoveToColumn(left_offset))?,
+            None => queue!(stdout, LineStart)?,
+        }
+
+        for (cell, word) in cells.iter().zip(words) {
+            let color = match word {
+                Some(word) if *word < current => sung,
+                Some(word) if *word == current => highlight,
+                _ => cell.color.unwrap_or(base_color),
+            };
+            queue!(stdout, fg(color), Print(cell.ch))?;
+        }
+
+        if row + 1 < lines.len() {
+            queue!(stdout, Print("\r\n"))?;
+        }
+    }
+
+    queue!(stdout, ResetColor)?;
+    stdout.flush_frame()?;
+    frame_timer.finish();
+    Ok(())
+}
+
+/// Sweeps a highlight through the words of `lines` one whole word at a
+/// time, for as long as `config` gives each, then leaves them all sung.
+pub fn apply_karaoke_effect(
+    lines: &[String],
+    config: &KaraokeConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let (indices, words) = word_indices(&styled);
+    let durations = word_durations(&words, config)?;
+
+    if words.is_empty() {
+        newline()?;
+        return Ok(());
+    }
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    for (current, &ms) in durations.iter().enumerate() {
+        resize::track(&mut centering_offsets)?;
+        draw_karaoke(
+            &mut stdout,
+            &styled,
+            &indices,
+            config,
+            current,
+            centering_offsets,
+            current > 0,
+        )?;
+        playback::sleep(Duration::from_millis(ms))?;
+    }
+
+    resize::track(&mut centering_offsets)?;
+    draw_karaoke(
+        &mut stdout,
+        &styled,
+        &indices,
+        config,
+        words.len(),
+        centering_offsets,
+        true,
+    )?;
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words_of(text: &str) -> Vec<String> {
+        let lines: Vec<_> = text.lines().map(parse_styled).collect();
+        word_indices(&lines).1
+    }
+
+    #[test]
+    fn test_karaoke_config_default() {
+        let config = KaraokeConfig::default();
+
+        assert_eq!(config.wpm, 160);
+        assert!(config.sung_color.is_none());
+        assert!(config.timing.is_none());
+    }
+
+    #[test]
+    fn test_word_indices_count_across_lines() {
+        let lines = vec![parse_styled("la  la"), parse_styled("laa")];
+        let (indices, words) = word_indices(&lines);
+
+        assert_eq!(words, ["la", "la", "laa"]);
+        assert_eq!(indices[0], [Some(0), Some(0), None, None, Some(1), Some(1)]);
+        assert_eq!(indices[1], [Some(2); 3]);
+    }
+
+    #[test]
+    fn test_parse_timing() {
+        let timing = parse_timing("# verse 1\nHello:400\n\n  12:30:250 \n").unwrap();
+        assert_eq!(
+            timing,
+            [("Hello".to_string(), 400), ("12:30".to_string(), 250)]
+        );
+        assert!(parse_timing("Hello").unwrap_err().contains("line 1"));
+        assert!(parse_timing("ok:1\nHello:soon")
+            .unwrap_err()
+            .contains("line 2"));
+    }
+
+    #[test]
+    fn test_word_durations_follow_the_timing_or_the_pace() {
+        let words = words_of("one two");
+        let paced = KaraokeConfig {
+            wpm: 120,
+            ..KaraokeConfig::default()
+        };
+        assert_eq!(word_durations(&words, &paced).unwrap(), [500, 500]);
+
+        let timed = KaraokeConfig {
+            timing: Some(parse_timing("one:100\ntwo:900").unwrap()),
+            ..KaraokeConfig::default()
+        };
+        assert_eq!(word_durations(&words, &timed).unwrap(), [100, 900]);
+
+        let mismatched = KaraokeConfig {
+            timing: Some(parse_timing("one:100\nthree:900").unwrap()),
+            ..KaraokeConfig::default()
+        };
+        assert!(word_durations(&words, &mismatched)
+            .unwrap_err()
+            .contains("word 2"));
+        assert!(word_durations(&words_of("one"), &timed).is_err());
+    }
+}
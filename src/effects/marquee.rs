@@ -0,0 +1,187 @@
+use super::frame::Frame;
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::{self, TerminalGuard};
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
+use std::io::Write;
+use std::time::Duration;
+
+/// Window width used when there is no terminal to measure.
+const FALLBACK_WIDTH: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum MarqueeDirection {
+    /// Text enters on the right and leaves on the left
+    Left,
+    /// Text enters on the left and leaves on the right
+    Right,
+}
+
+pub struct MarqueeConfig {
+    pub color: (u8, u8, u8),
+    /// Columns the text scrolls through (default: the terminal width)
+    pub window_width: Option<usize>,
+    pub direction: MarqueeDirection,
+    /// Blank columns between the end of the text and its next repeat
+    pub gap: usize,
+    pub speed: u64,
+    pub cycles: u32,
+}
+
+impl Default for MarqueeConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            window_width: None,
+            direction: MarqueeDirection::Left,
+            gap: 4,
+            speed: 80,
+            cycles: 1,
+        }
+    }
+}
+
+/// Left edge of the window on the looping tape after `step` one-column
+/// moves. Scrolling left walks forward along the tape, right walks back.
+fn offset_at(step: usize, period: usize, direction: MarqueeDirection) -> usize {
+    let step = step % period;
+    match direction {
+        MarqueeDirection::Left => step,
+        MarqueeDirection::Right => (period - step) % period,
+    }
+}
+
+/// Window columns where a cell starting at tape column `start` appears when
+/// the window's left edge is at `offset`. The tape repeats every `period`
+/// columns, so a window wider than the text shows each cell more than once.
+fn window_columns(
+    start: usize,
+    offset: usize,
+    period: usize,
+    width: usize,
+) -> impl Iterator<Item = usize> {
+    let first = (start + period - offset % period) % period;
+    (first..width).step_by(period)
+}
+
+/// Scrolls `lines` through a fixed-width window one column per frame. The
+/// text repeats after a gap, so it loops seamlessly; one cycle brings it back
+/// to where it started. Lines scroll together, so a banner stays intact.
+pub fn apply_marquee_effect(
+    lines: &[String],
+    config: &MarqueeConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let columns: Vec<_> = styled.iter().map(|cells| column_starts(cells)).collect();
+    let text_width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+
+    if text_width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    let width = config
+        .window_width
+        .or_else(|| screen::layout_size().map(|(width, _)| width as usize))
+        .filter(|&width| width > 0)
+        .unwrap_or(FALLBACK_WIDTH);
+    let period = text_width + config.gap;
+    let base_color = rgb_color(config.color);
+
+    let render = |offset: usize| {
+        let mut frame = Frame::new(width, styled.len());
+        for (row, cells) in styled.iter().enumerate() {
+            for (index, cell) in cells.iter().enumerate() {
+                let start = columns[row].0[index];
+                for x in window_columns(start, offset, period, width) {
+                    frame.put(x, row, cell.ch, cell.color.unwrap_or(base_color));
+                }
+            }
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let total_steps = if config.cycles == 0 {
+        usize::MAX
+    } else {
+        period * config.cycles as usize
+    };
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    for step in 0..total_steps {
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(
+            render(offset_at(step, period, config.direction)),
+            &mut stdout,
+            centering_offsets,
+        )?;
+        stdout.flush()?;
+        frame_timer.finish();
+
+        playback::sleep(frame_duration)?;
+    }
+
+    // Every cycle ends where it began
+    repaint.draw(render(0), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marquee_config_default() {
+        let config = MarqueeConfig::default();
+
+        assert_eq!(config.window_width, None);
+        assert_eq!(config.direction, MarqueeDirection::Left);
+        assert_eq!(config.gap, 4);
+        assert_eq!(config.cycles, 1);
+    }
+
+    #[test]
+    fn test_offset_loops_in_either_direction() {
+        let left: Vec<usize> = (0..6)
+            .map(|step| offset_at(step, 4, MarqueeDirection::Left))
+            .collect();
+        assert_eq!(left, vec![0, 1, 2, 3, 0, 1]);
+
+        let right: Vec<usize> = (0..6)
+            .map(|step| offset_at(step, 4, MarqueeDirection::Right))
+            .collect();
+        assert_eq!(right, vec![0, 3, 2, 1, 0, 3]);
+    }
+
+    #[test]
+    fn test_window_columns_wrap_around_the_tape() {
+        // A cell at tape column 1 of a 6-column tape, seen through 4 columns
+        assert_eq!(window_columns(1, 0, 6, 4).collect::<Vec<_>>(), vec![1]);
+        // Once it scrolls off the left edge it comes back in on the right
+        assert_eq!(
+            window_columns(1, 2, 6, 4).collect::<Vec<_>>(),
+            Vec::<usize>::new()
+        );
+        assert_eq!(window_columns(1, 4, 6, 4).collect::<Vec<_>>(), vec![3]);
+        // A window wider than the tape shows every repeat
+        assert_eq!(
+            window_columns(1, 0, 6, 14).collect::<Vec<_>>(),
+            vec![1, 7, 13]
+        );
+    }
+}
@@ -1,3 +1,43 @@
+pub mod blink;
+pub mod bounce;
+pub mod compose;
+pub mod confetti;
+pub mod countdown;
+pub mod countup;
+pub mod density;
+pub mod dissolve;
+pub mod driver;
+pub mod easing_preview;
+pub mod fade;
+pub mod fire;
+pub mod fireworks;
+pub mod frame;
+pub mod geometry;
+pub mod glowworm;
+pub mod gradient;
+pub mod grid;
+pub mod heartbeat;
+pub mod karaoke;
+pub mod marquee;
+pub mod neon;
+pub mod particles;
+pub mod physics;
+pub mod progress;
+pub mod pulse;
+pub mod repaint;
+pub mod scramble;
+pub mod shake;
 pub mod shine;
 pub mod shine2d;
+pub mod slide;
+pub mod snow;
+pub mod sparkle;
+pub mod spin;
+pub mod spotlight;
+pub mod sprite;
+pub mod stage;
+pub mod topbar;
 pub mod twinkle;
+pub mod viewport;
+pub mod wave;
+pub mod wipe;
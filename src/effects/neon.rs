@@ -0,0 +1,281 @@
+use super::frame::Frame;
+use super::pulse::scale;
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use crossterm::style::Color;
+use rand::Rng;
+use std::io::Write;
+use std::time::Duration;
+
+/// The glyph the glow is drawn with around the tubes.
+const GLOW_GLYPH: char = '░';
+
+/// How bright the glow is right beside a tube, relative to the tube.
+const GLOW_STRENGTH: f32 = 0.6;
+
+/// How bright a flickering letter is while it cuts out.
+const FLICKER_DIM: f32 = 0.15;
+
+pub struct NeonConfig {
+    /// Color of the tubes, for letters the input didn't color itself
+    pub color: (u8, u8, u8),
+    /// Columns the glow reaches out from each letter; it reaches half as
+    /// many rows, since cells are about twice as tall as they are wide
+    pub glow_radius: usize,
+    /// Chance (0.0 to 1.0) each frame that a steady letter starts to flicker
+    pub flicker_chance: f32,
+    /// How far (0.0 to 1.0) the whole sign's brightness jitters from frame
+    /// to frame, like a buzzing transformer
+    pub buzz: f32,
+    /// Milliseconds the sign stays lit; 0 keeps it on until stopped
+    pub duration: u64,
+    pub speed: u64,
+}
+
+impl Default for NeonConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 40, 200),
+            glow_radius: 2,
+            flicker_chance: 0.01,
+            buzz: 0.1,
+            duration: 3000,
+            speed: 50,
+        }
+    }
+}
+
+/// Columns and rows the glow of `radius` reaches past the text on each side.
+pub fn glow_margins(radius: usize) -> (usize, usize) {
+    (radius, radius.div_ceil(2))
+}
+
+/// How bright the glow is `dx` columns and `dy` rows from a tube, from
+/// `GLOW_STRENGTH` right beside it down to nothing past `radius` columns.
+fn glow_at(dx: usize, dy: usize, radius: usize) -> f32 {
+    if radius == 0 {
+        return 0.0;
+    }
+    let distance = (dx as f32).hypot(dy as f32 * 2.0);
+    (GLOW_STRENGTH * (1.0 - (distance - 1.0) / radius as f32)).clamp(0.0, GLOW_STRENGTH)
+}
+
+/// A lit character of the sign: where it is in the frame, and its color.
+struct Letter {
+    x: usize,
+    y: usize,
+    ch: char,
+    color: Color,
+}
+
+/// How bright each letter is this frame, from 0.0 to 1.0. A steady letter
+/// starts flickering with `flicker_chance`, then cuts in and out at random
+/// for the few frames its `flicker` count runs.
+fn step_flicker(flicker: &mut [u32], flicker_chance: f32, rng: &mut impl Rng) -> Vec<f32> {
+    flicker
+        .iter_mut()
+        .map(|frames_left| {
+            if *frames_left == 0 && rng.gen_bool(flicker_chance.clamp(0.0, 1.0) as f64) {
+                *frames_left = rng.gen_range(2..=6);
+            }
+            if *frames_left == 0 {
+                return 1.0;
+            }
+            *frames_left -= 1;
+            if rng.gen_bool(0.5) {
+                FLICKER_DIM
+            } else {
+                1.0
+            }
+        })
+        .collect()
+}
+
+/// The glow of `radius` the letters give off at their `brightness`, for
+/// each cell of a `size` frame: how bright it is there, and whose color.
+/// Where glows overlap, the brighter one shows.
+fn glow(
+    letters: &[Letter],
+    brightness: &[f32],
+    size: (usize, usize),
+    radius: usize,
+) -> Vec<Option<(f32, Color)>> {
+    let (width, height) = size;
+    let (reach_x, reach_y) = glow_margins(radius);
+    let mut glow: Vec<Option<(f32, Color)>> = vec![None; width * height];
+    for (letter, &lit) in letters.iter().zip(brightness) {
+        let rows = letter.y.saturating_sub(reach_y)..(letter.y + reach_y + 1).min(height);
+        for y in rows {
+            let columns = letter.x.saturating_sub(reach_x)..(letter.x + reach_x + 1).min(width);
+            for x in columns {
+                let strength = glow_at(x.abs_diff(letter.x), y.abs_diff(letter.y), radius) * lit;
+                let cell = &mut glow[y * width + x];
+                if strength > 0.0 && cell.is_none_or(|(brightest, _)| strength > brightest) {
+                    *cell = Some((strength, letter.color));
+                }
+            }
+        }
+    }
+    glow
+}
+
+/// The sign in a `size` frame, each letter at its `brightness`, in the
+/// glow of `radius` it gives off.
+fn render(letters: &[Letter], brightness: &[f32], size: (usize, usize), radius: usize) -> Frame {
+    let width = size.0;
+    let mut frame = Frame::new(width, size.1);
+    for (index, cell) in glow(letters, brightness, size, radius).iter().enumerate() {
+        if let Some((strength, color)) = *cell {
+            frame.put(
+                index % width,
+                index / width,
+                GLOW_GLYPH,
+                scale(color, strength),
+            );
+        }
+    }
+    for (letter, &lit) in letters.iter().zip(brightness) {
+        frame.put(letter.x, letter.y, letter.ch, scale(letter.color, lit));
+    }
+    frame
+}
+
+/// Lights `lines` up like a neon sign: the letters glow onto the cells
+/// around them, the sign buzzes, and now and then a letter flickers. Once
+/// the duration is up the sign is left steady and fully lit.
+pub fn apply_neon_effect(
+    lines: &[String],
+    config: &NeonConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let (margin_x, margin_y) = glow_margins(config.glow_radius);
+    let tube = rgb_color(config.color);
+    let mut letters = Vec::new();
+    let mut width = 0;
+    for (row, line) in lines.iter().enumerate() {
+        let cells = parse_styled(line);
+        let (columns, line_width) = column_starts(&cells);
+        width = width.max(line_width);
+        for (cell, column) in cells.iter().zip(columns) {
+            if !cell.ch.is_whitespace() {
+                letters.push(Letter {
+                    x: margin_x + column,
+                    y: margin_y + row,
+                    ch: cell.ch,
+                    color: cell.color.unwrap_or(tube),
+                });
+            }
+        }
+    }
+    if letters.is_empty() {
+        newline()?;
+        return Ok(());
+    }
+
+    let size = (width + 2 * margin_x, lines.len() + 2 * margin_y);
+    let frame_duration = Duration::from_millis(config.speed);
+    let step = config.speed.max(1);
+    let mut rng = rng::new_rng();
+    let mut flicker = vec![0; letters.len()];
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0;
+    while config.duration == 0 || elapsed < config.duration {
+        let hum = 1.0 - config.buzz.clamp(0.0, 1.0) * rng.gen::<f32>();
+        let brightness: Vec<f32> = step_flicker(&mut flicker, config.flicker_chance, &mut rng)
+            .into_iter()
+            .map(|lit| lit * hum)
+            .collect();
+
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        let frame = render(&letters, &brightness, size, config.glow_radius);
+        repaint.draw_on_time(frame, &mut stdout, centering_offsets)?;
+        stdout.flush()?;
+        frame_timer.finish();
+
+        playback::sleep(frame_duration)?;
+        elapsed += step;
+    }
+
+    let steady = vec![1.0; letters.len()];
+    let frame = render(&letters, &steady, size, config.glow_radius);
+    repaint.draw(frame, &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_neon_config_default() {
+        let config = NeonConfig::default();
+
+        assert_eq!(config.glow_radius, 2);
+        assert_eq!(config.flicker_chance, 0.01);
+        assert_eq!(config.duration, 3000);
+    }
+
+    #[test]
+    fn test_glow_fades_with_distance() {
+        assert_eq!(glow_at(1, 0, 2), GLOW_STRENGTH);
+        assert!(glow_at(2, 0, 2) < GLOW_STRENGTH);
+        // A row away is as far as two columns
+        assert_eq!(glow_at(0, 1, 2), glow_at(2, 0, 2));
+        assert_eq!(glow_at(3, 0, 2), 0.0);
+        assert_eq!(glow_at(1, 0, 0), 0.0);
+        assert_eq!(glow_margins(3), (3, 2));
+    }
+
+    #[test]
+    fn test_glow_is_a_dimmer_copy_of_lit_letters() {
+        let red = Color::Rgb { r: 200, g: 0, b: 0 };
+        let letters = [Letter {
+            x: 2,
+            y: 1,
+            ch: 'A',
+            color: red,
+        }];
+        let lit = glow(&letters, &[1.0], (5, 3), 2);
+        // Beside the letter, and fainter at the corner
+        assert_eq!(lit[5 + 3], Some((GLOW_STRENGTH, red)));
+        let (corner, _) = lit[0].unwrap();
+        assert!(corner > 0.0 && corner < GLOW_STRENGTH);
+
+        // An unlit letter gives off none
+        let unlit = glow(&letters, &[0.0], (5, 3), 2);
+        assert!(unlit.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_flicker_starts_by_chance_and_runs_out() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut flicker = vec![0; 10];
+        assert!(step_flicker(&mut flicker, 0.0, &mut rng)
+            .iter()
+            .all(|&lit| lit == 1.0));
+
+        step_flicker(&mut flicker, 1.0, &mut rng);
+        assert!(flicker.iter().all(|&frames_left| frames_left > 0));
+        for _ in 0..6 {
+            step_flicker(&mut flicker, 0.0, &mut rng);
+        }
+        assert!(flicker.iter().all(|&frames_left| frames_left == 0));
+    }
+}
@@ -0,0 +1,311 @@
+use super::density::{Density, SpawnBias};
+use super::frame::Frame;
+use crate::quality::Quality;
+use crate::screen;
+use crossterm::style::Color;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+/// Field size when stdout isn't a terminal that can report its own.
+const FALLBACK_SIZE: (usize, usize) = (80, 24);
+
+/// One particle drifting in a straight line across a particle field.
+pub struct Particle {
+    pub x: f32,
+    pub y: f32,
+    /// Cells per millisecond
+    pub velocity: (f32, f32),
+    pub age: u64,
+    /// Milliseconds the particle lives, if it doesn't leave the field first
+    pub lifetime: u64,
+    pub glyph: char,
+    pub color: Color,
+}
+
+impl Particle {
+    fn step(&mut self, elapsed: u64) {
+        self.age += elapsed;
+        self.x += self.velocity.0 * elapsed as f32;
+        self.y += self.velocity.1 * elapsed as f32;
+    }
+
+    /// How far (0.0 to 1.0) through its lifetime the particle is.
+    pub fn life(&self) -> f32 {
+        (self.age as f32 / self.lifetime.max(1) as f32).min(1.0)
+    }
+}
+
+/// How much of an effect's particle density `quality` keeps; fewer
+/// particles mean fewer cells to simulate and redraw.
+pub fn density_share(quality: Quality) -> f32 {
+    match quality {
+        Quality::Low => 0.3,
+        Quality::Medium => 0.6,
+        Quality::High | Quality::Auto => 1.0,
+    }
+}
+
+/// The terminal's size, for effects whose particles fill the screen,
+/// leaving its last row for the prompt after the effect ends.
+pub fn screen_size() -> (usize, usize) {
+    match screen::size() {
+        Ok((width, height)) if width > 0 && height > 1 => (width as usize, height as usize - 1),
+        _ => FALLBACK_SIZE,
+    }
+}
+
+/// Where in a width × height field particles are born: anywhere alike, or
+/// weighted cell by cell by how crowded with text its surroundings are.
+pub struct SpawnPoints {
+    size: (usize, usize),
+    weighted: Option<WeightedIndex<f32>>,
+}
+
+impl SpawnPoints {
+    /// Points over a `size` field with the text `density` was found for
+    /// drawn at `origin` in it, which `bias` draws births towards (or away
+    /// from). `Uniform` ignores the text.
+    pub fn new(
+        size: (usize, usize),
+        bias: SpawnBias,
+        density: &Density,
+        origin: (usize, usize),
+    ) -> Self {
+        let weighted = (bias != SpawnBias::Uniform)
+            .then(|| {
+                let weights = (0..size.1).flat_map(|y| {
+                    (0..size.0).map(move |x| {
+                        let x = x as isize - origin.0 as isize;
+                        let y = y as isize - origin.1 as isize;
+                        bias.weight(density.at(x, y))
+                    })
+                });
+                WeightedIndex::new(weights).ok()
+            })
+            .flatten();
+        Self { size, weighted }
+    }
+
+    /// A point somewhere in the field.
+    pub fn cell(&self, rng: &mut impl Rng) -> (f32, f32) {
+        match &self.weighted {
+            Some(weighted) => {
+                let index = weighted.sample(rng);
+                let (x, y) = (index % self.size.0, index / self.size.0);
+                (x as f32 + rng.gen::<f32>(), y as f32 + rng.gen::<f32>())
+            }
+            None => (
+                rng.gen_range(0.0..self.size.0.max(1) as f32),
+                rng.gen_range(0.0..self.size.1.max(1) as f32),
+            ),
+        }
+    }
+
+    /// A column somewhere across the field, for particles born along an
+    /// edge; with a bias, columns are as likely as the cells down them.
+    pub fn column(&self, rng: &mut impl Rng) -> f32 {
+        match &self.weighted {
+            Some(weighted) => (weighted.sample(rng) % self.size.0) as f32 + rng.gen::<f32>(),
+            None => rng.gen_range(0.0..self.size.0.max(1) as f32),
+        }
+    }
+}
+
+/// A width × height field of particles kept near `target` strong while
+/// spawning. Particles die when their lifetime is up or when they fall
+/// out of the bottom; with `wrap_x` they come back round from the other
+/// side instead of drifting off sideways.
+pub struct Particles {
+    pub particles: Vec<Particle>,
+    size: (usize, usize),
+    target: usize,
+    /// Most particles born per step, so a field can fill up gradually
+    births_per_step: usize,
+    wrap_x: bool,
+}
+
+impl Particles {
+    /// A field holding about `density` (0.0 to 1.0) particles per cell,
+    /// at most `births_per_step` of them born each step.
+    pub fn new(size: (usize, usize), density: f32, births_per_step: usize, wrap_x: bool) -> Self {
+        let area = (size.0 * size.1) as f32;
+        Self {
+            particles: Vec::new(),
+            size,
+            target: (area * density.clamp(0.0, 1.0)).round() as usize,
+            births_per_step: births_per_step.max(1),
+            wrap_x,
+        }
+    }
+
+    /// Particles the field holds once full.
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    /// Moves every particle on `elapsed` milliseconds and, while `spawning`,
+    /// tops the field up from `spawn` towards the target scaled down for
+    /// `quality`. Returns the columns where particles fell out of the
+    /// bottom, for effects that pile them up.
+    pub fn step(
+        &mut self,
+        elapsed: u64,
+        spawning: bool,
+        quality: Quality,
+        mut spawn: impl FnMut() -> Particle,
+    ) -> Vec<usize> {
+        let (width, height) = (self.size.0 as f32, self.size.1 as f32);
+        let mut landed = Vec::new();
+        for particle in &mut self.particles {
+            particle.step(elapsed);
+            if self.wrap_x && width > 0.0 {
+                particle.x = particle.x.rem_euclid(width);
+            }
+        }
+        self.particles.retain(|particle| {
+            if particle.y >= height {
+                if (0.0..width).contains(&particle.x) {
+                    landed.push(particle.x as usize);
+                }
+                return false;
+            }
+            particle.age < particle.lifetime
+        });
+
+        if spawning {
+            let target = (self.target as f32 * density_share(quality)).round() as usize;
+            let missing = target.saturating_sub(self.particles.len());
+            for _ in 0..missing.min(self.births_per_step) {
+                self.particles.push(spawn());
+            }
+        }
+        landed
+    }
+
+    /// Speeds every particle's fall up by `gravity` rows per second, every
+    /// second, for `elapsed` milliseconds.
+    pub fn pull(&mut self, gravity: f32, elapsed: u64) {
+        let pull = gravity.max(0.0) / 1_000_000.0 * elapsed as f32;
+        for particle in &mut self.particles {
+            particle.velocity.1 += pull;
+        }
+    }
+
+    /// Draws every particle inside the field onto `frame`, each with the
+    /// glyph `glyph` gives it.
+    pub fn draw(&self, frame: &mut Frame, glyph: impl Fn(&Particle) -> char) {
+        self.draw_styled(frame, |particle| (glyph(particle), particle.color));
+    }
+
+    /// Like `draw`, with `style` picking each particle's color too, e.g.
+    /// to fade it as it ages.
+    pub fn draw_styled(&self, frame: &mut Frame, style: impl Fn(&Particle) -> (char, Color)) {
+        for particle in &self.particles {
+            if particle.x >= 0.0 && particle.y >= 0.0 {
+                let (glyph, color) = style(particle);
+                frame.put(particle.x as usize, particle.y as usize, glyph, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn particle(x: f32, y: f32, velocity: (f32, f32), lifetime: u64) -> Particle {
+        Particle {
+            x,
+            y,
+            velocity,
+            age: 0,
+            lifetime,
+            glyph: '*',
+            color: Color::White,
+        }
+    }
+
+    #[test]
+    fn test_particles_drift_in_a_straight_line() {
+        let mut field = Particles::new((20, 10), 0.0, 1, false);
+        field
+            .particles
+            .push(particle(5.0, 5.0, (0.002, -0.001), 1500));
+        field.step(500, false, Quality::High, || unreachable!());
+        field.step(500, false, Quality::High, || unreachable!());
+
+        assert_eq!((field.particles[0].x, field.particles[0].y), (7.0, 4.0));
+        field.step(500, false, Quality::High, || unreachable!());
+        assert!(field.particles.is_empty());
+    }
+
+    #[test]
+    fn test_falling_out_of_the_bottom_lands_in_a_column() {
+        let mut field = Particles::new((10, 4), 0.0, 1, true);
+        field
+            .particles
+            .push(particle(9.5, 3.0, (0.001, 0.001), u64::MAX));
+        let landed = field.step(1000, false, Quality::High, || unreachable!());
+
+        // Wrapped round from the right edge before landing
+        assert_eq!(landed, vec![0]);
+        assert!(field.particles.is_empty());
+    }
+
+    #[test]
+    fn test_spawn_points_lean_towards_or_away_from_the_text() {
+        let art: Vec<String> = vec!["####".into(); 3];
+        let density = Density::of(&art);
+        let mut rng = StdRng::seed_from_u64(4);
+        // The art sits in the middle of a field with room all round
+        let on_art = |points: &SpawnPoints, rng: &mut StdRng| {
+            (0..1000)
+                .filter(|_| {
+                    let (x, y) = points.cell(rng);
+                    (8.0..12.0).contains(&x) && (4.0..7.0).contains(&y)
+                })
+                .count()
+        };
+
+        let size = (20, 10);
+        let uniform = on_art(
+            &SpawnPoints::new(size, SpawnBias::Uniform, &density, (8, 4)),
+            &mut rng,
+        );
+        let dense = on_art(
+            &SpawnPoints::new(size, SpawnBias::Dense, &density, (8, 4)),
+            &mut rng,
+        );
+        let sparse = on_art(
+            &SpawnPoints::new(size, SpawnBias::Sparse, &density, (8, 4)),
+            &mut rng,
+        );
+        assert!(dense > 2 * uniform, "{dense} vs {uniform}");
+        assert!(sparse < uniform, "{sparse} vs {uniform}");
+
+        // Columns over the art are likelier too
+        let points = SpawnPoints::new(size, SpawnBias::Dense, &density, (8, 4));
+        let over = (0..1000)
+            .filter(|_| (8.0..12.0).contains(&points.column(&mut rng)))
+            .count();
+        assert!(over > 400, "{over}");
+    }
+
+    #[test]
+    fn test_spawning_fills_gradually_and_lower_quality_keeps_fewer() {
+        let mut field = Particles::new((10, 10), 0.5, 10, false);
+        assert_eq!(field.target(), 50);
+        field.step(50, true, Quality::High, || {
+            particle(0.0, 0.0, (0.0, 0.0), 10_000)
+        });
+        assert_eq!(field.particles.len(), 10);
+
+        let mut low = Particles::new((10, 10), 0.5, 100, false);
+        low.step(50, true, Quality::Low, || {
+            particle(0.0, 0.0, (0.0, 0.0), 10_000)
+        });
+        assert_eq!(low.particles.len(), 15);
+    }
+}
@@ -0,0 +1,94 @@
+/// Longest stretch, in milliseconds, a `Bounce` moves in one go. Frames can
+/// run long, and a body stepped past the floor in one jump would sink
+/// through it before bouncing.
+const MAX_STEP_MS: u64 = 5;
+
+/// Rows below which a rebound counts as coming to rest.
+const REST_HEIGHT: f32 = 0.25;
+
+/// A body dropped onto a floor under gravity, bouncing back up a little
+/// slower each time until it comes to rest on it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounce {
+    /// Rows above the floor
+    pub height: f32,
+    /// Rows per millisecond, upward
+    pub velocity: f32,
+    pub resting: bool,
+}
+
+impl Bounce {
+    /// A body let go of, still, `height` rows above the floor.
+    pub fn dropped_from(height: f32) -> Self {
+        Self {
+            height: height.max(0.0),
+            velocity: 0.0,
+            resting: height <= 0.0,
+        }
+    }
+
+    /// Moves the body on `elapsed` milliseconds under `gravity` (rows per
+    /// second, every second). Each bounce takes `damping` (0.0 to 1.0) of
+    /// its speed away.
+    pub fn step(&mut self, gravity: f32, damping: f32, elapsed: u64) {
+        let gravity = gravity.max(0.0) / 1_000_000.0;
+        let keep = 1.0 - damping.clamp(0.0, 1.0);
+        let mut left = elapsed;
+        while left > 0 && !self.resting {
+            let step = left.min(MAX_STEP_MS);
+            left -= step;
+            self.velocity -= gravity * step as f32;
+            self.height += self.velocity * step as f32;
+            if self.height <= 0.0 && self.velocity < 0.0 {
+                self.height = 0.0;
+                self.velocity *= -keep;
+                // Too slow to rise off the floor any more
+                if gravity <= 0.0 || self.velocity.powi(2) / (2.0 * gravity) < REST_HEIGHT {
+                    self.velocity = 0.0;
+                    self.resting = true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounce_falls_and_rebounds_lower() {
+        let mut body = Bounce::dropped_from(10.0);
+        body.step(100.0, 0.5, 100);
+        assert!(body.height < 10.0 && body.velocity < 0.0);
+
+        // Down to the floor and back up, not as high as it started
+        let mut peak: f32 = 0.0;
+        let mut landed = false;
+        for _ in 0..100 {
+            body.step(100.0, 0.5, 10);
+            landed |= body.velocity > 0.0;
+            if landed {
+                peak = peak.max(body.height);
+            }
+        }
+        assert!(landed);
+        assert!(peak > 1.0 && peak < 10.0, "{peak}");
+    }
+
+    #[test]
+    fn test_bounce_comes_to_rest() {
+        let mut body = Bounce::dropped_from(10.0);
+        for _ in 0..1000 {
+            body.step(100.0, 0.5, 20);
+        }
+        assert!(body.resting);
+        assert_eq!((body.height, body.velocity), (0.0, 0.0));
+
+        // Fully damped, the first landing is the last
+        let mut dead = Bounce::dropped_from(5.0);
+        dead.step(100.0, 1.0, 1000);
+        assert!(dead.resting);
+        assert!(Bounce::dropped_from(0.0).resting);
+    }
+}
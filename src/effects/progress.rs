@@ -0,0 +1,273 @@
+use super::stage::{rgb_color, ColorStage};
+use crate::capability::fg;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::{LineStart, TerminalGuard};
+use crate::soak::FrameTimer;
+use crossterm::{
+    cursor, queue,
+    style::{Color, Print, ResetColor},
+    terminal::{self, ClearType},
+};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+/// Blocks that fill the last, partly done cell in eighths.
+const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Cells on either side of the shine's center it still brightens.
+const SHINE_HALF_WIDTH: f32 = 3.0;
+
+/// How much of the way to a new value the bar moves each second, so jumps
+/// glide instead of snapping.
+const CATCH_UP_PER_SECOND: f32 = 6.0;
+
+pub struct ProgressConfig {
+    /// Color of the part that's done
+    pub color: (u8, u8, u8),
+    /// Color of the part still to go
+    pub empty_color: (u8, u8, u8),
+    /// Color at the center of the shine
+    pub highlight: (u8, u8, u8),
+    /// Width of the bar in columns
+    pub width: usize,
+    pub speed: u64,
+    /// Milliseconds the shine takes to cross the part that's done
+    pub period: u64,
+    pub levels: Option<u32>,
+}
+
+impl Default for ProgressConfig {
+    fn default() -> Self {
+        Self {
+            color: (0, 200, 120),
+            empty_color: (60, 60, 60),
+            highlight: (255, 255, 255),
+            width: 30,
+            speed: 50,
+            period: 1500,
+            levels: None,
+        }
+    }
+}
+
+/// Reads a line of progress: a percentage (`42` or `42%`) or a count
+/// (`3/10`), then an optional label. Returns the fraction done, from 0.0
+/// to 1.0, and the label; the fraction is `None` for a line that's only a
+/// label, and the label is empty when there's none.
+pub fn parse_progress(line: &str) -> (Option<f32>, String) {
+    let line = line.trim();
+    let (value, label) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let fraction = match value.split_once('/') {
+        Some((current, total)) => match (current.parse::<f32>(), total.parse::<f32>()) {
+            (Ok(current), Ok(total)) if total > 0.0 => Some(current / total),
+            _ => None,
+        },
+        None => value
+            .strip_suffix('%')
+            .unwrap_or(value)
+            .parse::<f32>()
+            .ok()
+            .map(|percent| percent / 100.0),
+    };
+    match fraction.filter(|fraction| fraction.is_finite()) {
+        Some(fraction) => (Some(fraction.clamp(0.0, 1.0)), label.trim().to_string()),
+        None => (None, line.to_string()),
+    }
+}
+
+/// The bar's cells when `shown` of it is done and the shine is `phase`
+/// (0.0 to 1.0) of the way across the done part.
+fn bar_cells(
+    config: &ProgressConfig,
+    stage: &mut ColorStage,
+    shown: f32,
+    phase: f32,
+) -> Vec<(char, Color)> {
+    let eighths = (shown.clamp(0.0, 1.0) * (config.width * 8) as f32).round() as usize;
+    let (full, partial) = (eighths / 8, eighths % 8);
+    let done = full as f32 + partial as f32 / 8.0;
+    // The shine enters before the start of the bar and leaves past its end
+    let center = -SHINE_HALF_WIDTH + phase * (done + 2.0 * SHINE_HALF_WIDTH);
+    let base = rgb_color(config.color);
+    let highlight = rgb_color(config.highlight);
+
+    (0..config.width)
+        .map(|column| {
+            let ch = match column {
+                column if column < full => '█',
+                column if column == full && partial > 0 => EIGHTHS[partial],
+                _ => return ('░', rgb_color(config.empty_color)),
+            };
+            let distance = (column as f32 + 0.5 - center).abs();
+            let intensity = (1.0 - distance / SHINE_HALF_WIDTH).max(0.0);
+            (ch, stage.shade(base, highlight, intensity))
+        })
+        .collect()
+}
+
+/// Draws a bar that fills as each line of `lines` reports more progress,
+/// with a shine sweeping along the part that's done and the latest label
+/// beside it, until the input ends.
+pub fn apply_progress_effect(
+    lines: Receiver<String>,
+    config: &ProgressConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let frame_duration = Duration::from_millis(config.speed);
+    let period = config.period.max(1) as f32;
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut stage = ColorStage::new(config.levels);
+    let started = Instant::now();
+    let mut last_frame = started;
+    let mut target = 0.0;
+    let mut shown = 0.0;
+    let mut label = String::new();
+    let mut input_open = true;
+
+    loop {
+        while input_open {
+            match lines.try_recv() {
+                Ok(line) => {
+                    let (fraction, text) = parse_progress(&line);
+                    if let Some(fraction) = fraction {
+                        target = fraction;
+                    }
+                    if fraction.is_none() || !text.is_empty() {
+                        label = text;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => input_open = false,
+            }
+        }
+
+        let now = Instant::now();
+        let step = (now.duration_since(last_frame).as_secs_f32() * CATCH_UP_PER_SECOND).min(1.0);
+        last_frame = now;
+        // The last value is where the bar is left, however far it had to go
+        shown = if input_open {
+            shown + (target - shown) * step
+        } else {
+            target
+        };
+        let phase = (now.duration_since(started).as_millis() as f32 % period) / period;
+
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        match centering_offsets {
+            Some((top_offset, left_offset)) => {
+                queue!(stdout, cursor::MoveTo(left_offset, top_offset))?
+            }
+            None => queue!(stdout, LineStart)?,
+        }
+        for (ch, color) in bar_cells(config, &mut stage, shown, phase) {
+            queue!(stdout, fg(color), Print(ch))?;
+        }
+        queue!(
+            stdout,
+            ResetColor,
+            Print(format!(" {:>3}%", (shown * 100.0).round() as u32))
+        )?;
+        if !label.is_empty() {
+            queue!(stdout, Print(format!("  {label}")))?;
+        }
+        queue!(stdout, terminal::Clear(ClearType::UntilNewLine))?;
+        stdout.flush_frame()?;
+        frame_timer.finish();
+
+        if !input_open {
+            break;
+        }
+        playback::sleep(frame_duration)?;
+    }
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_config_default() {
+        let config = ProgressConfig::default();
+
+        assert_eq!(config.width, 30);
+        assert_eq!(config.speed, 50);
+        assert_eq!(config.period, 1500);
+        assert_eq!(config.levels, None);
+    }
+
+    #[test]
+    fn test_parse_progress() {
+        assert_eq!(parse_progress("42"), (Some(0.42), String::new()));
+        assert_eq!(
+            parse_progress("50%  Compiling clifx"),
+            (Some(0.5), "Compiling clifx".to_string())
+        );
+        assert_eq!(
+            parse_progress("3/4 files"),
+            (Some(0.75), "files".to_string())
+        );
+        // Past the end is done, and before the start hasn't started
+        assert_eq!(parse_progress("12/10").0, Some(1.0));
+        assert_eq!(parse_progress("-5").0, Some(0.0));
+        // Anything else is a new label
+        assert_eq!(
+            parse_progress("Linking..."),
+            (None, "Linking...".to_string())
+        );
+        assert_eq!(parse_progress("1/0 nothing").0, None);
+    }
+
+    #[test]
+    fn test_bar_cells_fill_in_eighths() {
+        let config = ProgressConfig {
+            width: 4,
+            ..Default::default()
+        };
+        let mut stage = ColorStage::new(None);
+        let chars = |shown: f32, stage: &mut ColorStage| -> String {
+            bar_cells(&config, stage, shown, 0.0)
+                .iter()
+                .map(|&(ch, _)| ch)
+                .collect()
+        };
+
+        assert_eq!(chars(0.0, &mut stage), "░░░░");
+        assert_eq!(chars(0.5, &mut stage), "██░░");
+        assert_eq!(chars(0.5 + 1.0 / 32.0, &mut stage), "██▏░");
+        assert_eq!(chars(1.0, &mut stage), "████");
+    }
+
+    #[test]
+    fn test_bar_cells_shine_sweeps_the_done_part() {
+        let config = ProgressConfig {
+            width: 20,
+            ..Default::default()
+        };
+        let mut stage = ColorStage::new(None);
+        let base = rgb_color(config.color);
+        let red = |color: Color| match color {
+            Color::Rgb { r, .. } => r,
+            _ => 0,
+        };
+
+        // Halfway through the sweep, the shine sits mid-way along the done half
+        let cells = bar_cells(&config, &mut stage, 0.5, 0.5);
+        let brightest = (0..20).max_by_key(|&column| red(cells[column].1)).unwrap();
+        assert_eq!(brightest, 5);
+        // It never reaches the part still to go
+        assert_eq!(cells[15].1, rgb_color(config.empty_color));
+        // At the very start it hasn't come into view yet
+        let cells = bar_cells(&config, &mut stage, 0.5, 0.0);
+        assert!(cells[..10].iter().all(|&(_, color)| color == base));
+    }
+}
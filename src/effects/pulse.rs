@@ -0,0 +1,251 @@
+use super::driver::with_phase;
+use super::stage::{rgb_color, ColorStage};
+use crate::ansi::{parse_styled, StyledChar};
+use crate::capability::fg;
+use crate::easing::EasingFunction;
+use crate::output::{newline, stdout, Stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::{LineStart, TerminalGuard};
+use crate::soak::FrameTimer;
+use crossterm::{
+    cursor, queue,
+    style::{Color, Print, ResetColor},
+};
+use std::time::Duration;
+
+pub struct PulseConfig {
+    pub color: (u8, u8, u8),
+    /// Brightness at the bottom of a breath, as a fraction of `color`
+    pub min_brightness: f32,
+    /// Brightness at the top of a breath, as a fraction of `color`
+    pub max_brightness: f32,
+    /// Length of one breath in milliseconds
+    pub period: u64,
+    pub speed: u64,
+    pub easing: EasingFunction,
+    pub cycles: u32,
+    pub levels: Option<u32>,
+    pub phase: f32,
+}
+
+impl Default for PulseConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            min_brightness: 0.2,
+            max_brightness: 1.0,
+            period: 2000,
+            speed: 50,
+            easing: EasingFunction::EaseInOut,
+            cycles: 1,
+            levels: None,
+            phase: 0.0,
+        }
+    }
+}
+
+/// `color` with every channel multiplied by `brightness`.
+pub fn scale(color: Color, brightness: f32) -> Color {
+    let Color::Rgb { r, g, b } = color else {
+        return color;
+    };
+    let channel = |c: u8| (c as f32 * brightness.clamp(0.0, 1.0)).round() as u8;
+    Color::Rgb {
+        r: channel(r),
+        g: channel(g),
+        b: channel(b),
+    }
+}
+
+/// How far from dim (0.0) to bright (1.0) the text is at `progress` through a
+/// breath: it brightens until the midpoint, then fades back.
+pub fn breath(progress: f32, easing: &EasingFunction) -> f32 {
+    let rise = if progress < 0.5 {
+        progress * 2.0
+    } else {
+        (1.0 - progress) * 2.0
+    };
+    easing.apply(rise.clamp(0.0, 1.0))
+}
+
+/// How bright the text is at `progress` through a cycle whose envelope is
+/// given as `keyframes`: (progress, brightness) pairs in order, with the
+/// brightness eased from one to the next. Before the first keyframe and
+/// after the last, it holds.
+pub fn envelope(progress: f32, keyframes: &[(f32, f32)], easing: &EasingFunction) -> f32 {
+    let Some(&(first_at, first)) = keyframes.first() else {
+        return 0.0;
+    };
+    if progress <= first_at {
+        return first;
+    }
+    for pair in keyframes.windows(2) {
+        let ((from_at, from), (to_at, to)) = (pair[0], pair[1]);
+        if progress <= to_at {
+            let t = easing.apply(((progress - from_at) / (to_at - from_at)).clamp(0.0, 1.0));
+            return from + (to - from) * t;
+        }
+    }
+    keyframes[keyframes.len() - 1].1
+}
+
+pub(super) fn draw_pulse(
+    stdout: &mut Stdout,
+    lines: &[Vec<StyledChar>],
+    config: &PulseConfig,
+    intensity: f32,
+    stage: &mut ColorStage,
+    centering_offsets: Option<(u16, u16)>,
+    redraw: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let frame_timer = FrameTimer::start();
+    if let Some((top_offset, left_offset)) = centering_offsets {
+        queue!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+    } else if redraw && lines.len() > 1 {
+        queue!(stdout, cursor::MoveUp((lines.len() - 1) as u16))?;
+    }
+
+    let base_color = rgb_color(config.color);
+    for (row, cells) in lines.iter().enumerate() {
+        match centering_offsets {
+            Some((_, left_offset)) => queue!(stdout, cursor::MoveToColumn(left_offset))?,
+            None => queue!(stdout, LineStart)?,
+        }
+
+        for cell in cells {
+            // Colors from the input breathe too, at their own hue
+            let color = cell.color.unwrap_or(base_color);
+            let shade = stage.shade(
+                scale(color, config.min_brightness),
+                scale(color, config.max_brightness),
+                intensity,
+            );
+            queue!(stdout, fg(shade), Print(cell.ch))?;
+        }
+
+        if row + 1 < lines.len() {
+            queue!(stdout, Print("\r\n"))?;
+        }
+    }
+
+    queue!(stdout, ResetColor)?;
+    stdout.flush_frame()?;
+    frame_timer.finish();
+    Ok(())
+}
+
+/// Fades all of `lines` together between a dim and a bright version of their
+/// color, one breath per `config.period`.
+pub fn apply_pulse_effect(
+    lines: &[String],
+    config: &PulseConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+
+    if styled.iter().all(|cells| cells.is_empty()) {
+        newline()?;
+        return Ok(());
+    }
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let frames_per_breath = (config.period / config.speed.max(1)).max(1) as usize;
+    let cycles_to_run = if config.cycles == 0 {
+        usize::MAX
+    } else {
+        config.cycles as usize
+    };
+    let mut stage = ColorStage::new(config.levels);
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut drawn = false;
+    for _cycle in 0..cycles_to_run {
+        for frame in 0..frames_per_breath {
+            let progress = with_phase(frame as f32 / frames_per_breath as f32, config.phase);
+            resize::track(&mut centering_offsets)?;
+            draw_pulse(
+                &mut stdout,
+                &styled,
+                config,
+                breath(progress, &config.easing),
+                &mut stage,
+                centering_offsets,
+                drawn,
+            )?;
+            drawn = true;
+
+            playback::sleep(frame_duration)?;
+        }
+    }
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_pulse_config_default() {
+        let config = PulseConfig::default();
+
+        assert_eq!(config.color, (255, 255, 255));
+        assert_eq!(config.min_brightness, 0.2);
+        assert_eq!(config.max_brightness, 1.0);
+        assert_eq!(config.period, 2000);
+        assert_eq!(config.speed, 50);
+        assert_eq!(config.cycles, 1);
+    }
+
+    #[test]
+    fn test_breath_rises_and_falls() {
+        let easing = EasingFunction::Linear;
+        assert_approx_eq!(breath(0.0, &easing), 0.0, 0.001);
+        assert_approx_eq!(breath(0.25, &easing), 0.5, 0.001);
+        assert_approx_eq!(breath(0.5, &easing), 1.0, 0.001);
+        assert_approx_eq!(breath(0.75, &easing), 0.5, 0.001);
+        assert_approx_eq!(breath(1.0, &easing), 0.0, 0.001);
+
+        // Easing shapes each half of the breath symmetrically
+        let eased = EasingFunction::EaseIn;
+        assert_approx_eq!(breath(0.25, &eased), breath(0.75, &eased), 0.001);
+        assert!(breath(0.25, &eased) < 0.5);
+    }
+
+    #[test]
+    fn test_envelope_eases_between_keyframes() {
+        let keyframes = [(0.2, 0.0), (0.4, 1.0), (0.8, 0.5)];
+        let easing = EasingFunction::Linear;
+        assert_approx_eq!(envelope(0.0, &keyframes, &easing), 0.0, 0.001);
+        assert_approx_eq!(envelope(0.3, &keyframes, &easing), 0.5, 0.001);
+        assert_approx_eq!(envelope(0.4, &keyframes, &easing), 1.0, 0.001);
+        assert_approx_eq!(envelope(0.6, &keyframes, &easing), 0.75, 0.001);
+        assert_approx_eq!(envelope(1.0, &keyframes, &easing), 0.5, 0.001);
+        assert_eq!(envelope(0.5, &[], &easing), 0.0);
+    }
+
+    #[test]
+    fn test_scale_brightness() {
+        let orange = Color::Rgb {
+            r: 200,
+            g: 100,
+            b: 0,
+        };
+        assert_eq!(
+            scale(orange, 0.5),
+            Color::Rgb {
+                r: 100,
+                g: 50,
+                b: 0
+            }
+        );
+        assert_eq!(scale(orange, 1.0), orange);
+        assert_eq!(scale(Color::Reset, 0.5), Color::Reset);
+    }
+}
@@ -0,0 +1,176 @@
+use super::frame::Frame;
+use crate::playback;
+use crate::screen;
+use std::io::{self, IsTerminal, Write};
+
+/// Draws an effect's frames one after another. Once a frame is on screen,
+/// the next only repaints the cells that changed, which for large input
+/// is a small share of each frame.
+pub struct Repaint {
+    previous: Option<Frame>,
+    /// Where the previous frame was drawn
+    centering_offsets: Option<(u16, u16)>,
+    /// Terminal size when it was drawn
+    terminal_size: Option<(u16, u16)>,
+    /// Piped output keeps whole frames, which stay readable to tools that
+    /// don't follow cursor movement
+    partial: bool,
+}
+
+impl Default for Repaint {
+    fn default() -> Self {
+        Self {
+            previous: None,
+            centering_offsets: None,
+            terminal_size: None,
+            partial: io::stdout().is_terminal(),
+        }
+    }
+}
+
+impl Repaint {
+    /// Queues `frame` over the previous one, for the caller to flush. It's
+    /// drawn in full if it's the first, if its size changed, or if it moved
+    /// or the terminal was resized, leaving the screen's contents unknown.
+    pub fn draw(
+        &mut self,
+        frame: Frame,
+        stdout: &mut impl Write,
+        centering_offsets: Option<(u16, u16)>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let terminal_size = screen::size().ok();
+        let unmoved =
+            centering_offsets == self.centering_offsets && terminal_size == self.terminal_size;
+        match &self.previous {
+            Some(previous) if self.partial && previous.same_size(&frame) && unmoved => {
+                frame.draw_changes(previous, stdout, centering_offsets)?
+            }
+            previous => frame.draw(stdout, centering_offsets, previous.is_some())?,
+        }
+        self.previous = Some(frame);
+        self.centering_offsets = centering_offsets;
+        self.terminal_size = terminal_size;
+        Ok(())
+    }
+
+    /// Draws a frame of the animation like `draw`, but skips it if it's
+    /// already late, so the next one is repainted over what's still on
+    /// screen. Effects draw their last frame with `draw`, which never skips.
+    pub fn draw_on_time(
+        &mut self,
+        frame: Frame,
+        stdout: &mut impl Write,
+        centering_offsets: Option<(u16, u16)>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.draw_unless_late(playback::frame_is_late(), frame, stdout, centering_offsets)
+    }
+
+    fn draw_unless_late(
+        &mut self,
+        late: bool,
+        frame: Frame,
+        stdout: &mut impl Write,
+        centering_offsets: Option<(u16, u16)>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if late {
+            return Ok(());
+        }
+        self.draw(frame, stdout, centering_offsets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::style::Color;
+
+    const RED: Color = Color::Rgb { r: 255, g: 0, b: 0 };
+
+    fn frame(rows: &[&str]) -> Frame {
+        let mut frame = Frame::new(rows[0].len(), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, ch) in row.chars().enumerate() {
+                frame.put(x, y, ch, RED);
+            }
+        }
+        frame
+    }
+
+    /// A repaint as it works on a terminal, drawing only what changed.
+    fn on_terminal() -> Repaint {
+        Repaint {
+            partial: true,
+            ..Repaint::default()
+        }
+    }
+
+    fn draw(repaint: &mut Repaint, rows: &[&str], centering_offsets: Option<(u16, u16)>) -> String {
+        let mut out = Vec::new();
+        repaint
+            .draw(frame(rows), &mut out, centering_offsets)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_first_frame_is_drawn_in_full() {
+        let mut repaint = on_terminal();
+        let out = draw(&mut repaint, &["abc", "def"], None);
+
+        // Nothing on screen yet to climb back over
+        assert!(out.starts_with("\x1b[1G"));
+        assert!(out.contains("\r\n"));
+        assert!(out.contains(['a', 'b', 'c', 'd', 'e', 'f']));
+    }
+
+    #[test]
+    fn test_same_size_frame_repaints_changed_cells_only() {
+        let mut repaint = on_terminal();
+        draw(&mut repaint, &["abc", "def"], None);
+        let out = draw(&mut repaint, &["abc", "dEf"], None);
+        assert!(out.contains('E'));
+        assert!(!out.contains(['a', 'd', 'f']));
+
+        // Piped, every frame is whole
+        let mut piped = Repaint {
+            partial: false,
+            ..Repaint::default()
+        };
+        draw(&mut piped, &["abc", "def"], None);
+        let out = draw(&mut piped, &["abc", "dEf"], None);
+        assert!(out.starts_with("\x1b[1A"));
+        assert!(out.contains(['a', 'd', 'E', 'f']));
+    }
+
+    #[test]
+    fn test_moved_or_resized_frame_is_drawn_in_full() {
+        let mut repaint = on_terminal();
+        draw(&mut repaint, &["abc", "def"], Some((2, 4)));
+        let out = draw(&mut repaint, &["abc", "dEf"], Some((3, 4)));
+        assert!(out.starts_with("\x1b[4;5H"));
+        assert!(out.contains(['a', 'd', 'E', 'f']));
+
+        let out = draw(&mut repaint, &["abcd", "dEfg"], Some((3, 4)));
+        assert!(out.contains(['a', 'd', 'E', 'g']));
+    }
+
+    #[test]
+    fn test_late_frame_is_skipped() {
+        let mut repaint = on_terminal();
+        draw(&mut repaint, &["abc", "def"], None);
+
+        let mut out = Vec::new();
+        repaint
+            .draw_unless_late(true, frame(&["aXc", "def"]), &mut out, None)
+            .unwrap();
+        assert!(out.is_empty());
+
+        // The next one goes over what's still on screen, the frame before
+        repaint
+            .draw_unless_late(false, frame(&["abc", "dYf"]), &mut out, None)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains('Y'));
+        assert!(!out.contains(['a', 'b', 'X']));
+    }
+}
@@ -0,0 +1,197 @@
+use super::frame::Frame;
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use rand::Rng;
+use std::io::Write;
+use std::time::Duration;
+
+/// Named glyph sets for `--charset`; anything else is used as the glyphs.
+const CHARSETS: [(&str, &str); 5] = [
+    (
+        "alnum",
+        "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+    ),
+    ("hex", "0123456789ABCDEF"),
+    ("binary", "01"),
+    ("symbols", "!@#$%^&*()-_=+[]{}<>?/\\|~"),
+    ("blocks", "░▒▓█▀▄▌▐"),
+];
+
+pub struct ScrambleConfig {
+    pub color_locked: (u8, u8, u8),
+    pub color_scrambled: (u8, u8, u8),
+    /// Glyphs shown while a character is still scrambled
+    pub charset: Vec<char>,
+    /// Milliseconds between one character locking and the next
+    pub lock_delay: u64,
+    pub speed: u64,
+}
+
+impl Default for ScrambleConfig {
+    fn default() -> Self {
+        Self {
+            color_locked: (255, 255, 255),
+            color_scrambled: (0, 255, 70),
+            charset: CHARSETS[0].1.chars().collect(),
+            lock_delay: 40,
+            speed: 50,
+        }
+    }
+}
+
+/// The glyphs for a `--charset` value: one of the named sets, or the
+/// characters of the value itself.
+pub fn parse_charset(value: &str) -> Result<Vec<char>, Box<dyn std::error::Error>> {
+    let glyphs = CHARSETS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        .map_or(value, |(_, glyphs)| glyphs);
+
+    let glyphs: Vec<char> = glyphs.chars().filter(|ch| !ch.is_whitespace()).collect();
+    if glyphs.is_empty() {
+        return Err("charset needs at least one visible character".into());
+    }
+    Ok(glyphs)
+}
+
+/// Milliseconds after the start at which the character at `index` (counting
+/// only characters that scramble) settles on its real value.
+fn lock_time(index: usize, config: &ScrambleConfig) -> u64 {
+    (index as u64 + 1) * config.lock_delay
+}
+
+/// Cycles every character of `lines` through random glyphs, then locks them
+/// into place one by one from left to right. All lines lock in step, so a
+/// banner resolves column by column.
+pub fn apply_scramble_effect(
+    lines: &[String],
+    config: &ScrambleConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let columns: Vec<_> = styled.iter().map(|cells| column_starts(cells)).collect();
+    let width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+
+    if width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    // Whitespace stays put; everything else scrambles in reading order
+    let orders: Vec<Vec<Option<usize>>> = styled
+        .iter()
+        .map(|cells| {
+            let mut next = 0;
+            cells
+                .iter()
+                .map(|cell| {
+                    (!cell.ch.is_whitespace()).then(|| {
+                        next += 1;
+                        next - 1
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    let longest = orders
+        .iter()
+        .filter_map(|order| order.iter().flatten().max())
+        .max();
+    let Some(&longest) = longest else {
+        // Nothing to scramble, so just show the text
+        for line in lines {
+            writeln!(stdout, "{line}")?;
+        }
+        stdout.flush()?;
+        return Ok(());
+    };
+
+    let locked = rgb_color(config.color_locked);
+    let scrambled = rgb_color(config.color_scrambled);
+    let mut rng = rng::new_rng();
+
+    let mut render = |elapsed: u64| {
+        let mut frame = Frame::new(width, styled.len());
+        for (row, cells) in styled.iter().enumerate() {
+            for (index, cell) in cells.iter().enumerate() {
+                let column = columns[row].0[index];
+                match orders[row][index] {
+                    Some(order) if elapsed < lock_time(order, config) => {
+                        let glyph = config.charset[rng.gen_range(0..config.charset.len())];
+                        frame.put(column, row, glyph, scrambled);
+                    }
+                    _ => frame.put(column, row, cell.ch, cell.color.unwrap_or(locked)),
+                }
+            }
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let total = lock_time(longest, config);
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0;
+    while elapsed < total {
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(render(elapsed), &mut stdout, centering_offsets)?;
+        stdout.flush()?;
+        frame_timer.finish();
+
+        playback::sleep(frame_duration)?;
+        elapsed += config.speed.max(1);
+    }
+
+    repaint.draw(render(total), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scramble_config_default() {
+        let config = ScrambleConfig::default();
+
+        assert_eq!(config.lock_delay, 40);
+        assert_eq!(config.speed, 50);
+        assert_eq!(config.charset.len(), 62);
+    }
+
+    #[test]
+    fn test_parse_charset() {
+        assert_eq!(parse_charset("binary").unwrap(), vec!['0', '1']);
+        assert_eq!(parse_charset("HEX").unwrap().len(), 16);
+        // Anything that isn't a named set is taken literally
+        assert_eq!(parse_charset("x y").unwrap(), vec!['x', 'y']);
+        assert!(parse_charset("   ").is_err());
+    }
+
+    #[test]
+    fn test_characters_lock_left_to_right() {
+        let config = ScrambleConfig {
+            lock_delay: 100,
+            ..Default::default()
+        };
+
+        assert_eq!(lock_time(0, &config), 100);
+        assert_eq!(lock_time(4, &config), 500);
+        assert!(lock_time(1, &config) < lock_time(2, &config));
+    }
+}
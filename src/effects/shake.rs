@@ -0,0 +1,155 @@
+use super::frame::Frame;
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use crossterm::style::Color;
+use rand::Rng;
+use std::io::Write;
+use std::time::Duration;
+
+pub struct ShakeConfig {
+    pub color: (u8, u8, u8),
+    /// Columns the text jumps at most to either side, at the start
+    pub amplitude: usize,
+    /// Jolt the text up and down too, by up to half as many rows
+    pub vertical: bool,
+    /// Milliseconds the shaking takes to die down
+    pub duration: u64,
+    pub speed: u64,
+}
+
+impl Default for ShakeConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            amplitude: 2,
+            vertical: false,
+            duration: 600,
+            speed: 30,
+        }
+    }
+}
+
+/// Columns and rows the text can be shaken past its place on each side.
+pub fn shake_margins(amplitude: usize, vertical: bool) -> (usize, usize) {
+    (amplitude, if vertical { amplitude.div_ceil(2) } else { 0 })
+}
+
+/// How far, at most, the text jumps at `progress` through the shaking: all
+/// of `amplitude` at first, dying down to nothing by the end.
+fn reach(amplitude: usize, progress: f32) -> f32 {
+    amplitude as f32 * (1.0 - progress.clamp(0.0, 1.0))
+}
+
+/// A random jolt of up to `reach` either way, rounded to whole cells.
+fn jolt(reach: f32, rng: &mut impl Rng) -> isize {
+    if reach <= 0.0 {
+        return 0;
+    }
+    rng.gen_range(-reach..=reach).round() as isize
+}
+
+/// Shakes `lines` from side to side (and with `config.vertical`, up and
+/// down), hard at first and settling over the duration, then leaves them
+/// still in their place.
+pub fn apply_shake_effect(
+    lines: &[String],
+    config: &ShakeConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let base_color = rgb_color(config.color);
+    let mut cells: Vec<(usize, usize, char, Color)> = Vec::new();
+    let mut width = 0;
+    for (y, line) in lines.iter().enumerate() {
+        let styled = parse_styled(line);
+        let (columns, line_width) = column_starts(&styled);
+        width = width.max(line_width);
+        for (cell, x) in styled.iter().zip(columns) {
+            cells.push((x, y, cell.ch, cell.color.unwrap_or(base_color)));
+        }
+    }
+    if width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    let (margin_x, margin_y) = shake_margins(config.amplitude, config.vertical);
+    let size = (width + 2 * margin_x, lines.len() + 2 * margin_y);
+    let render = |(dx, dy): (isize, isize)| {
+        let mut frame = Frame::new(size.0, size.1);
+        for &(x, y, ch, color) in &cells {
+            let x = (margin_x + x) as isize + dx;
+            let y = (margin_y + y) as isize + dy;
+            frame.put(x as usize, y as usize, ch, color);
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let duration = config.duration.max(1);
+    let mut rng = rng::new_rng();
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0;
+    while elapsed < duration {
+        let progress = elapsed as f32 / duration as f32;
+        let dx = jolt(reach(margin_x, progress), &mut rng);
+        let dy = jolt(reach(margin_y, progress), &mut rng);
+
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(render((dx, dy)), &mut stdout, centering_offsets)?;
+        stdout.flush()?;
+        frame_timer.finish();
+
+        playback::sleep(frame_duration)?;
+        elapsed += config.speed.max(1);
+    }
+
+    repaint.draw(render((0, 0)), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_shake_config_default() {
+        let config = ShakeConfig::default();
+
+        assert_eq!(config.amplitude, 2);
+        assert!(!config.vertical);
+        assert_eq!(config.duration, 600);
+    }
+
+    #[test]
+    fn test_shaking_dies_down_within_the_margins() {
+        assert_eq!(shake_margins(3, false), (3, 0));
+        assert_eq!(shake_margins(3, true), (3, 2));
+
+        assert_eq!(reach(2, 0.0), 2.0);
+        assert_eq!(reach(2, 0.5), 1.0);
+        assert_eq!(reach(2, 1.0), 0.0);
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let jolts: Vec<isize> = (0..200).map(|_| jolt(2.0, &mut rng)).collect();
+        assert!(jolts.iter().all(|jolt| jolt.abs() <= 2));
+        assert!(jolts.contains(&-2) && jolts.contains(&2));
+        assert_eq!(jolt(0.0, &mut rng), 0);
+    }
+}
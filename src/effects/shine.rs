@@ -1,10 +1,20 @@
+use super::gradient::color_at;
+use super::stage::{rgb_color, ColorStage};
+use super::viewport::Camera;
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::capability::fg;
+use crate::contrast::ensure_contrast;
+use crate::easing::EasingFunction;
+use crate::output::{newline, stdout, Stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::{self, LineStart, TerminalGuard};
+use crate::soak::FrameTimer;
 use crossterm::{
-    cursor, execute,
-    style::{Color, Print, ResetColor, SetForegroundColor},
+    cursor, execute, queue,
+    style::{Color, Print, ResetColor},
     terminal::{self, ClearType},
 };
-use std::io::{self, Write};
-use std::thread;
 use std::time::Duration;
 
 pub struct ShineConfig {
@@ -14,63 +24,267 @@ pub struct ShineConfig {
     pub duration: u64,
     pub cycles: u32,
     pub start: ShineStart,
+    pub alternate_direction: bool,
     pub width: usize,
     pub blur: bool,
     pub padding: usize,
     pub shine_color: (u8, u8, u8),
+    /// Colors across the band from its left edge to its right, in place of
+    /// `shine_color`
+    pub shine_gradient: Option<Vec<(u8, u8, u8)>>,
     pub pause_length: Option<u64>,
     pub pause_position: f32,
     pub cycle_pre_delay: Option<u64>,
     pub cycle_post_delay: Option<u64>,
     pub cycle_switchback_delay: Option<u64>,
     pub opacity: f32,
+    pub min_contrast: Option<f32>,
+    /// Delay in milliseconds between successive lines in all-lines mode
+    pub line_stagger: u64,
+    pub levels: Option<u32>,
+    pub mode: ShineMode,
+    /// In split mode, bring the bands back to the center after they reach the ends
+    pub reflect: bool,
+    /// Fraction of a cycle (0.0 to 1.0) to skip ahead before the first frame
+    pub phase: f32,
+    /// Shine only on characters the input colored; the rest keep the terminal default
+    pub color_mask: bool,
+    /// Columns available for a line; longer lines pan to follow the band
+    /// (default: the terminal's width when writing to one)
+    pub terminal_width: Option<usize>,
 }
 
-#[derive(Clone)]
-pub enum ShineStart {
-    Beginning,
-    End,
+/// How many bands sweep the text and where they travel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShineMode {
+    /// One band sweeps from one end to the other and back
+    Single,
+    /// Two mirrored bands travel outward from the center
+    Split,
 }
 
-#[derive(Debug, Clone)]
-pub enum EasingFunction {
-    Linear,
-    EaseIn,
-    EaseOut,
-    EaseInOut,
+use super::driver::with_phase;
+pub use super::driver::ShineStart;
+
+fn back_and_forth(eased_progress: f32) -> f32 {
+    if eased_progress < 0.5 {
+        eased_progress * 2.0
+    } else {
+        2.0 - (eased_progress * 2.0)
+    }
+}
+
+fn calculate_shine_position(
+    back_and_forth_progress: f32,
+    text_len: usize,
+    padding: usize,
+    start: ShineStart,
+) -> isize {
+    let total_range = text_len + (2 * padding);
+    match start {
+        ShineStart::Beginning => {
+            (back_and_forth_progress * (total_range as f32 - 1.0)) as isize - padding as isize
+        }
+        ShineStart::End => {
+            ((1.0 - back_and_forth_progress) * (total_range as f32 - 1.0)) as isize
+                - padding as isize
+        }
+    }
+}
+
+/// Column of every band for a frame at `eased_progress` through the cycle.
+fn band_positions(
+    eased_progress: f32,
+    text_len: usize,
+    config: &ShineConfig,
+    start: ShineStart,
+) -> Vec<isize> {
+    match config.mode {
+        ShineMode::Single => vec![calculate_shine_position(
+            back_and_forth(eased_progress),
+            text_len,
+            config.padding,
+            start,
+        )],
+        ShineMode::Split => {
+            let travel = if config.reflect {
+                back_and_forth(eased_progress)
+            } else {
+                eased_progress
+            };
+            // Beginning grows outward from the center; End closes in from the ends
+            let travel = match start {
+                ShineStart::Beginning => travel,
+                ShineStart::End => 1.0 - travel,
+            };
+            let center = (text_len as f32 - 1.0) / 2.0;
+            let offset = travel * (text_len as f32 / 2.0 + config.padding as f32);
+            vec![
+                (center - offset).round() as isize,
+                (center + offset).round() as isize,
+            ]
+        }
+    }
+}
+
+/// The band's colors from its left edge to its right: the shine color
+/// alone, or the shine gradient's stops. Each stands out from the base
+/// color as much as `min_contrast` asks.
+fn band_colors(config: &ShineConfig) -> Vec<Color> {
+    let stops = match &config.shine_gradient {
+        Some(stops) => stops.clone(),
+        None => vec![config.shine_color],
+    };
+    stops
+        .into_iter()
+        .map(|stop| match config.min_contrast {
+            Some(min_ratio) => ensure_contrast(stop, config.base_color, config.opacity, min_ratio),
+            None => stop,
+        })
+        .map(rgb_color)
+        .collect()
+}
+
+/// The band's color `distance` columns from its center at `position`,
+/// on the side `column` is on, for a band reaching `radius` either way.
+fn band_color(
+    colors: &[Color],
+    column: usize,
+    position: isize,
+    distance: f32,
+    radius: f32,
+) -> Color {
+    let side = if (column as isize) < position {
+        -1.0
+    } else {
+        1.0
+    };
+    color_at(colors, 0.5 + side * distance / (2.0 * radius.max(1.0)))
 }
 
-impl EasingFunction {
-    fn apply(&self, t: f32) -> f32 {
-        match self {
-            EasingFunction::Linear => t,
-            EasingFunction::EaseIn => t * t,
-            EasingFunction::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
-            EasingFunction::EaseInOut => {
-                if t < 0.5 {
-                    2.0 * t * t
+/// The band's color `shine` over a cell the input colored `cell_color`
+/// itself, which needs to stand out from that color rather than the base.
+fn shine_over(shine: Color, cell_color: Option<Color>, config: &ShineConfig) -> Color {
+    match (config.min_contrast, cell_color, shine) {
+        (
+            Some(min_ratio),
+            Some(Color::Rgb { r, g, b }),
+            Color::Rgb {
+                r: sr,
+                g: sg,
+                b: sb,
+            },
+        ) => rgb_color(ensure_contrast(
+            (sr, sg, sb),
+            (r, g, b),
+            config.opacity,
+            min_ratio,
+        )),
+        _ => shine,
+    }
+}
+
+/// Distance in columns from the shine to a cell spanning `width` columns
+/// from `column`. Wide characters light up when the band reaches either of
+/// their columns.
+fn column_distance(column: usize, width: usize, shine_position: isize) -> f32 {
+    let last_column = column + width.max(1) - 1;
+    shine_position
+        .clamp(column as isize, last_column as isize)
+        .abs_diff(shine_position) as f32
+}
+
+fn print_shine_line(
+    stdout: &mut Stdout,
+    text_chars: &[StyledChar],
+    shine_positions: &[isize],
+    config: &ShineConfig,
+    base_color: Color,
+    shine_colors: &[Color],
+    stage: &mut ColorStage,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (columns, _) = column_starts(text_chars);
+    for (cell, &column) in text_chars.iter().zip(&columns) {
+        let ch = cell.ch;
+        if config.color_mask && cell.color.is_none() {
+            queue!(stdout, fg(Color::Reset), Print(ch))?;
+            continue;
+        }
+
+        // Colors from the input win over the configured base color
+        let base_color = cell.color.unwrap_or(base_color);
+        let (distance_from_shine, nearest) = shine_positions
+            .iter()
+            .map(|&position| (column_distance(column, cell.width(), position), position))
+            .fold((f32::MAX, 0), |closest, band| {
+                if band.0 < closest.0 {
+                    band
                 } else {
-                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                    closest
                 }
-            }
+            });
+        let shine_radius = config.width as f32;
+
+        if distance_from_shine <= shine_radius {
+            let shine_intensity = if config.blur {
+                1.0 - (distance_from_shine / shine_radius)
+            } else if distance_from_shine == 0.0 {
+                1.0
+            } else {
+                0.0
+            };
+            // Apply opacity to the shine intensity
+            let opacity_adjusted_intensity = shine_intensity * config.opacity;
+            let shine_color = band_color(
+                shine_colors,
+                column,
+                nearest,
+                distance_from_shine,
+                shine_radius,
+            );
+            let shine_color = shine_over(shine_color, cell.color, config);
+            let blended_color = stage.shade(base_color, shine_color, opacity_adjusted_intensity);
+            queue!(stdout, fg(blended_color), Print(ch))?;
+        } else {
+            queue!(stdout, fg(base_color), Print(ch))?;
         }
     }
+
+    Ok(())
 }
 
 pub fn apply_shine_effect(
     text: &str,
     config: &ShineConfig,
-    centering_offsets: Option<(u16, u16)>,
+    placement: Placement,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut stdout = io::stdout();
-    let text_chars: Vec<char> = text.chars().collect();
-    let text_len = text_chars.len();
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let text_chars = parse_styled(text);
+    let (columns, text_len) = column_starts(&text_chars);
 
-    if text_len == 0 {
-        println!();
+    if text_chars.is_empty() {
+        newline()?;
         return Ok(());
     }
 
+    // A line wider than the terminal would wrap and smear every frame, so
+    // show a window onto it that follows the band instead
+    let terminal_width = config.terminal_width.or_else(|| {
+        screen::layout_size()
+            .map(|(width, _)| width)
+            // Inline, only the rest of the line is ours to draw on
+            .map(|width| width.saturating_sub(screen::inline_column().unwrap_or(0)) as usize)
+    });
+    let mut camera = terminal_width
+        .filter(|&width| width > 0 && text_len > width)
+        .map(|width| (Camera::new(width, text_len), width));
+    let extents: Vec<(usize, usize)> = text_chars
+        .iter()
+        .zip(&columns)
+        .map(|(cell, &column)| (column, cell.width()))
+        .collect();
+
     let frame_duration = Duration::from_millis(config.speed);
     let total_frames = (config.duration / config.speed) as usize;
     let cycles_to_run = if config.cycles == 0 {
@@ -85,39 +299,30 @@ pub fn apply_shine_effect(
         b: config.base_color.2,
     };
 
-    let shine_color = Color::Rgb {
-        r: config.shine_color.0,
-        g: config.shine_color.1,
-        b: config.shine_color.2,
-    };
+    let shine_colors = band_colors(config);
+    let mut stage = ColorStage::new(config.levels);
 
-    if centering_offsets.is_some() {
-        execute!(
-            stdout,
-            terminal::Clear(ClearType::All),
-            cursor::Hide
-        )?;
-    } else {
-        execute!(
-            stdout,
-            terminal::Clear(ClearType::CurrentLine),
-            cursor::Hide
-        )?;
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+    if centering_offsets.is_none() && screen::may_clear_line() {
+        execute!(stdout, terminal::Clear(ClearType::CurrentLine))?;
     }
 
     for cycle in 0..cycles_to_run {
+        let start = config.start.for_cycle(cycle, config.alternate_direction);
+
         // Apply pre-cycle delay
         if let Some(pre_delay) = config.cycle_pre_delay {
-            thread::sleep(Duration::from_millis(pre_delay));
+            playback::sleep(Duration::from_millis(pre_delay))?;
         }
 
         for frame in 0..total_frames {
-            let progress = frame as f32 / (total_frames - 1) as f32;
+            let progress = with_phase(frame as f32 / (total_frames - 1) as f32, config.phase);
             let eased_progress = config.easing.apply(progress);
 
             // Check if we're at the switchback point (midpoint of cycle)
             let prev_progress = if frame > 0 {
-                let prev_frame_progress = (frame - 1) as f32 / (total_frames - 1) as f32;
+                let prev_frame_progress =
+                    with_phase((frame - 1) as f32 / (total_frames - 1) as f32, config.phase);
                 config.easing.apply(prev_frame_progress)
             } else {
                 0.0
@@ -126,76 +331,82 @@ pub fn apply_shine_effect(
             // Apply switchback delay if we've crossed the midpoint (0.5)
             if let Some(switchback_delay) = config.cycle_switchback_delay {
                 if frame > 0 && prev_progress < 0.5 && eased_progress >= 0.5 {
-                    thread::sleep(Duration::from_millis(switchback_delay));
+                    playback::sleep(Duration::from_millis(switchback_delay))?;
                 }
             }
 
-            let back_and_forth_progress = if eased_progress < 0.5 {
-                eased_progress * 2.0
-            } else {
-                2.0 - (eased_progress * 2.0)
-            };
-
             let total_range = text_len + (2 * config.padding);
-            let shine_position = match config.start {
-                ShineStart::Beginning => {
-                    (back_and_forth_progress * (total_range as f32 - 1.0)) as isize
-                        - config.padding as isize
-                }
-                ShineStart::End => {
-                    ((1.0 - back_and_forth_progress) * (total_range as f32 - 1.0)) as isize
-                        - config.padding as isize
-                }
-            };
+            let shine_positions = band_positions(eased_progress, text_len, config, start);
 
-            // Check if we should pause at the specified position
+            // Check if we should pause at the specified position (of the first band)
             if let Some(pause_length) = config.pause_length {
                 let normalized_position =
-                    (shine_position + config.padding as isize) as f32 / total_range as f32;
+                    (shine_positions[0] + config.padding as isize) as f32 / total_range as f32;
                 let pause_tolerance = 0.05; // 5% tolerance for pause position
 
                 if (normalized_position - config.pause_position).abs() < pause_tolerance {
-                    thread::sleep(Duration::from_millis(pause_length));
+                    playback::sleep(Duration::from_millis(pause_length))?;
                 }
             }
 
+            resize::track(&mut centering_offsets)?;
+            let frame_timer = FrameTimer::start();
             if let Some((top_offset, left_offset)) = centering_offsets {
-                execute!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+                queue!(stdout, cursor::MoveTo(left_offset, top_offset))?;
             } else {
-                execute!(stdout, cursor::MoveToColumn(0))?;
+                queue!(stdout, LineStart)?;
             }
 
-            for (i, &ch) in text_chars.iter().enumerate() {
-                let distance_from_shine = (i as isize - shine_position).abs() as f32;
-                let shine_radius = config.width as f32;
-
-                if distance_from_shine <= shine_radius {
-                    let shine_intensity = if config.blur {
-                        1.0 - (distance_from_shine / shine_radius)
-                    } else if distance_from_shine == 0.0 {
-                        1.0
-                    } else {
-                        0.0
+            match camera.as_mut() {
+                Some((camera, width)) => {
+                    let left = camera.follow(shine_positions[0]);
+                    let visible = camera.visible(&extents, left);
+                    let (first_column, end_column) = match &extents[visible.clone()] {
+                        [] => (left, left),
+                        cells => (
+                            cells[0].0,
+                            cells[cells.len() - 1].0 + cells[cells.len() - 1].1,
+                        ),
                     };
-                    // Apply opacity to the shine intensity
-                    let opacity_adjusted_intensity = shine_intensity * config.opacity;
-                    let blended_color =
-                        blend_colors(base_color, shine_color, opacity_adjusted_intensity);
-                    execute!(stdout, SetForegroundColor(blended_color), Print(ch))?;
-                } else {
-                    execute!(stdout, SetForegroundColor(base_color), Print(ch))?;
+                    let shifted: Vec<isize> = shine_positions
+                        .iter()
+                        .map(|&position| position - first_column as isize)
+                        .collect();
+
+                    // Wide characters cut by either edge leave blanks behind
+                    queue!(stdout, Print(" ".repeat(first_column - left)))?;
+                    print_shine_line(
+                        &mut stdout,
+                        &text_chars[visible],
+                        &shifted,
+                        config,
+                        base_color,
+                        &shine_colors,
+                        &mut stage,
+                    )?;
+                    queue!(stdout, Print(" ".repeat(left + *width - end_column)))?;
                 }
+                None => print_shine_line(
+                    &mut stdout,
+                    &text_chars,
+                    &shine_positions,
+                    config,
+                    base_color,
+                    &shine_colors,
+                    &mut stage,
+                )?,
             }
 
-            execute!(stdout, ResetColor)?;
-            stdout.flush()?;
+            queue!(stdout, ResetColor)?;
+            stdout.flush_frame()?;
+            frame_timer.finish();
 
-            thread::sleep(frame_duration);
+            playback::sleep(frame_duration)?;
         }
 
         // Apply post-cycle delay
         if let Some(post_delay) = config.cycle_post_delay {
-            thread::sleep(Duration::from_millis(post_delay));
+            playback::sleep(Duration::from_millis(post_delay))?;
         }
 
         if config.cycles > 0 && cycle + 1 == cycles_to_run {
@@ -203,205 +414,181 @@ pub fn apply_shine_effect(
         }
     }
 
-    execute!(stdout, cursor::Show)?;
-    println!();
+    newline()?;
     Ok(())
 }
 
-fn blend_colors(base: Color, shine: Color, intensity: f32) -> Color {
-    let intensity = intensity.clamp(0.0, 1.0);
+/// Animates every line within a single frame loop so multi-line banners shine
+/// together. Each line lags the previous one by `config.line_stagger`
+/// milliseconds, giving a cascading sweep when non-zero.
+pub fn apply_shine_all_lines(
+    lines: &[String],
+    config: &ShineConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let line_chars: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let max_len = line_chars
+        .iter()
+        .map(|chars| column_starts(chars).1)
+        .max()
+        .unwrap_or(0);
+
+    if line_chars.iter().all(|chars| chars.is_empty()) {
+        for _ in 0..lines.len().max(1) {
+            newline()?;
+        }
+        return Ok(());
+    }
 
-    let (base_r, base_g, base_b) = match base {
-        Color::Rgb { r, g, b } => (r, g, b),
-        _ => (255, 255, 255),
+    let frame_duration = Duration::from_millis(config.speed);
+    let total_frames = (config.duration / config.speed) as usize;
+    let stagger_frames = (config.line_stagger / config.speed) as usize;
+    let frames_per_cycle = total_frames + stagger_frames * (lines.len() - 1);
+    let cycles_to_run = if config.cycles == 0 {
+        usize::MAX
+    } else {
+        config.cycles as usize
     };
 
-    let (shine_r, shine_g, shine_b) = match shine {
-        Color::Rgb { r, g, b } => (r, g, b),
-        _ => (255, 255, 255),
+    let base_color = Color::Rgb {
+        r: config.base_color.0,
+        g: config.base_color.1,
+        b: config.base_color.2,
     };
 
-    let blended_r = (base_r as f32 * (1.0 - intensity) + shine_r as f32 * intensity) as u8;
-    let blended_g = (base_g as f32 * (1.0 - intensity) + shine_g as f32 * intensity) as u8;
-    let blended_b = (base_b as f32 * (1.0 - intensity) + shine_b as f32 * intensity) as u8;
+    let shine_colors = band_colors(config);
+    let mut stage = ColorStage::new(config.levels);
 
-    Color::Rgb {
-        r: blended_r,
-        g: blended_g,
-        b: blended_b,
-    }
-}
+    let line_progress = |frame: usize, line: usize| -> f32 {
+        let local_frame = frame as isize - (line * stagger_frames) as isize;
+        with_phase(
+            (local_frame as f32 / (total_frames - 1) as f32).clamp(0.0, 1.0),
+            config.phase,
+        )
+    };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use assert_approx_eq::assert_approx_eq;
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
 
-    const TEST_TOLERANCE: f32 = 0.001;
+    let mut drawn = false;
+    for cycle in 0..cycles_to_run {
+        let start = config.start.for_cycle(cycle, config.alternate_direction);
 
-    #[test]
-    fn test_easing_function_linear() {
-        let easing = EasingFunction::Linear;
-
-        assert_approx_eq!(easing.apply(0.0), 0.0, TEST_TOLERANCE);
-        assert_approx_eq!(easing.apply(0.25), 0.25, TEST_TOLERANCE);
-        assert_approx_eq!(easing.apply(0.5), 0.5, TEST_TOLERANCE);
-        assert_approx_eq!(easing.apply(0.75), 0.75, TEST_TOLERANCE);
-        assert_approx_eq!(easing.apply(1.0), 1.0, TEST_TOLERANCE);
-    }
+        if let Some(pre_delay) = config.cycle_pre_delay {
+            playback::sleep(Duration::from_millis(pre_delay))?;
+        }
 
-    #[test]
-    fn test_easing_function_ease_in() {
-        let easing = EasingFunction::EaseIn;
+        for frame in 0..frames_per_cycle {
+            // Delays are driven by the first line, which leads the cascade
+            let eased_progress = config.easing.apply(line_progress(frame, 0));
 
-        assert_approx_eq!(easing.apply(0.0), 0.0, TEST_TOLERANCE);
-        assert_approx_eq!(easing.apply(0.5), 0.25, TEST_TOLERANCE);
-        assert_approx_eq!(easing.apply(1.0), 1.0, TEST_TOLERANCE);
+            if let Some(switchback_delay) = config.cycle_switchback_delay {
+                let prev_progress = if frame > 0 {
+                    config.easing.apply(line_progress(frame - 1, 0))
+                } else {
+                    0.0
+                };
+                if frame > 0 && prev_progress < 0.5 && eased_progress >= 0.5 {
+                    playback::sleep(Duration::from_millis(switchback_delay))?;
+                }
+            }
 
-        // Ease-in should start slow and accelerate
-        assert!(easing.apply(0.1) < 0.1);
-        assert!(easing.apply(0.9) > 0.8);
-    }
+            if let Some(pause_length) = config.pause_length {
+                let lead_position = band_positions(eased_progress, max_len, config, start)[0];
+                let total_range = max_len + (2 * config.padding);
+                let normalized_position =
+                    (lead_position + config.padding as isize) as f32 / total_range as f32;
+                let pause_tolerance = 0.05;
 
-    #[test]
-    fn test_easing_function_ease_out() {
-        let easing = EasingFunction::EaseOut;
+                if (normalized_position - config.pause_position).abs() < pause_tolerance {
+                    playback::sleep(Duration::from_millis(pause_length))?;
+                }
+            }
 
-        assert_approx_eq!(easing.apply(0.0), 0.0, TEST_TOLERANCE);
-        assert_approx_eq!(easing.apply(0.5), 0.75, TEST_TOLERANCE);
-        assert_approx_eq!(easing.apply(1.0), 1.0, TEST_TOLERANCE);
+            resize::track(&mut centering_offsets)?;
+            let frame_timer = FrameTimer::start();
+            if let Some((top_offset, left_offset)) = centering_offsets {
+                queue!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+            } else if drawn && lines.len() > 1 {
+                queue!(stdout, cursor::MoveUp((lines.len() - 1) as u16))?;
+            }
 
-        // Ease-out should start fast and decelerate
-        assert!(easing.apply(0.1) > 0.1);
-        assert!(easing.apply(0.9) < 1.0);
-    }
+            for (row, chars) in line_chars.iter().enumerate() {
+                if let Some((_, left_offset)) = centering_offsets {
+                    queue!(stdout, cursor::MoveToColumn(left_offset))?;
+                } else {
+                    queue!(stdout, cursor::MoveToColumn(0))?;
+                }
 
-    #[test]
-    fn test_easing_function_ease_in_out() {
-        let easing = EasingFunction::EaseInOut;
+                let progress = config.easing.apply(line_progress(frame, row));
+                // Every line shares the widest line's range so the bands stay aligned
+                let shine_positions = band_positions(progress, max_len, config, start);
+                print_shine_line(
+                    &mut stdout,
+                    chars,
+                    &shine_positions,
+                    config,
+                    base_color,
+                    &shine_colors,
+                    &mut stage,
+                )?;
+
+                if row + 1 < line_chars.len() {
+                    queue!(stdout, Print("\r\n"))?;
+                }
+            }
+            drawn = true;
 
-        assert_approx_eq!(easing.apply(0.0), 0.0, TEST_TOLERANCE);
-        assert_approx_eq!(easing.apply(0.5), 0.5, TEST_TOLERANCE);
-        assert_approx_eq!(easing.apply(1.0), 1.0, TEST_TOLERANCE);
+            queue!(stdout, ResetColor)?;
+            stdout.flush_frame()?;
+            frame_timer.finish();
 
-        // Ease-in-out should be symmetric around 0.5
-        let val_25 = easing.apply(0.25);
-        let val_75 = easing.apply(0.75);
-        assert_approx_eq!(val_25, 1.0 - val_75, 0.01); // Allow small tolerance for floating point
-    }
+            playback::sleep(frame_duration)?;
+        }
 
-    #[test]
-    fn test_easing_functions_range() {
-        let functions = vec![
-            EasingFunction::Linear,
-            EasingFunction::EaseIn,
-            EasingFunction::EaseOut,
-            EasingFunction::EaseInOut,
-        ];
+        if let Some(post_delay) = config.cycle_post_delay {
+            playback::sleep(Duration::from_millis(post_delay))?;
+        }
 
-        for easing in functions {
-            // Test edge cases
-            assert_eq!(easing.apply(0.0), 0.0);
-            assert_eq!(easing.apply(1.0), 1.0);
-
-            // Test monotonic increasing property
-            let values: Vec<f32> = (0..=10).map(|i| easing.apply(i as f32 / 10.0)).collect();
-            for i in 1..values.len() {
-                assert!(
-                    values[i] >= values[i - 1],
-                    "Easing function should be monotonic increasing. {:?} at step {}: {} >= {}",
-                    easing,
-                    i,
-                    values[i],
-                    values[i - 1]
-                );
-            }
+        if config.cycles > 0 && cycle + 1 == cycles_to_run {
+            break;
         }
     }
 
-    #[test]
-    fn test_blend_colors_basic() {
-        let base = Color::Rgb {
-            r: 100,
-            g: 100,
-            b: 100,
-        };
-        let shine = Color::Rgb {
-            r: 200,
-            g: 200,
-            b: 200,
-        };
+    newline()?;
+    Ok(())
+}
 
-        // Test no blending (intensity = 0.0)
-        if let Color::Rgb { r, g, b } = blend_colors(base, shine, 0.0) {
-            assert_eq!(r, 100);
-            assert_eq!(g, 100);
-            assert_eq!(b, 100);
-        } else {
-            panic!("Expected RGB color");
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contrast::contrast_ratio;
+    use assert_approx_eq::assert_approx_eq;
 
-        // Test full blending (intensity = 1.0)
-        if let Color::Rgb { r, g, b } = blend_colors(base, shine, 1.0) {
-            assert_eq!(r, 200);
-            assert_eq!(g, 200);
-            assert_eq!(b, 200);
-        } else {
-            panic!("Expected RGB color");
-        }
-    }
+    const TEST_TOLERANCE: f32 = 0.001;
 
     #[test]
-    fn test_blend_colors_midpoint() {
-        let base = Color::Rgb { r: 0, g: 0, b: 0 };
-        let shine = Color::Rgb {
-            r: 255,
-            g: 255,
-            b: 255,
-        };
-
-        if let Color::Rgb { r, g, b } = blend_colors(base, shine, 0.5) {
-            assert_eq!(r, 127);
-            assert_eq!(g, 127);
-            assert_eq!(b, 127);
-        } else {
-            panic!("Expected RGB color");
-        }
+    fn test_calculate_shine_position_endpoints() {
+        // Beginning starts just before the text, End starts just past it
+        assert_eq!(
+            calculate_shine_position(0.0, 10, 5, ShineStart::Beginning),
+            -5
+        );
+        assert_eq!(
+            calculate_shine_position(1.0, 10, 5, ShineStart::Beginning),
+            14
+        );
+        assert_eq!(calculate_shine_position(0.0, 10, 5, ShineStart::End), 14);
+        assert_eq!(calculate_shine_position(1.0, 10, 5, ShineStart::End), -5);
     }
 
     #[test]
-    fn test_blend_colors_clamping() {
-        let base = Color::Rgb {
-            r: 100,
-            g: 100,
-            b: 100,
-        };
-        let shine = Color::Rgb {
-            r: 200,
-            g: 200,
-            b: 200,
-        };
-
-        // Test values outside valid range
-        let result_negative = blend_colors(base, shine, -0.5);
-        let result_over_one = blend_colors(base, shine, 1.5);
-
-        // Should clamp to valid range
-        if let Color::Rgb { r, g, b } = result_negative {
-            assert_eq!(r, 100); // Should be same as base (intensity = 0.0)
-            assert_eq!(g, 100);
-            assert_eq!(b, 100);
-        } else {
-            panic!("Expected RGB color");
-        }
-
-        if let Color::Rgb { r, g, b } = result_over_one {
-            assert_eq!(r, 200); // Should be same as shine (intensity = 1.0)
-            assert_eq!(g, 200);
-            assert_eq!(b, 200);
-        } else {
-            panic!("Expected RGB color");
-        }
+    fn test_back_and_forth() {
+        assert_approx_eq!(back_and_forth(0.0), 0.0, TEST_TOLERANCE);
+        assert_approx_eq!(back_and_forth(0.25), 0.5, TEST_TOLERANCE);
+        assert_approx_eq!(back_and_forth(0.5), 1.0, TEST_TOLERANCE);
+        assert_approx_eq!(back_and_forth(1.0), 0.0, TEST_TOLERANCE);
     }
 
     #[test]
@@ -413,16 +600,26 @@ mod tests {
             duration: 1000,
             cycles: 1,
             start: ShineStart::Beginning,
+            alternate_direction: false,
             width: 2,
             blur: true,
             padding: 5,
             shine_color: (255, 255, 255),
+            shine_gradient: None,
             pause_length: None,
             pause_position: 0.5,
             cycle_pre_delay: None,
             cycle_post_delay: None,
             cycle_switchback_delay: None,
             opacity: 1.0,
+            min_contrast: None,
+            line_stagger: 0,
+            levels: None,
+            mode: ShineMode::Single,
+            reflect: false,
+            phase: 0.0,
+            color_mask: false,
+            terminal_width: None,
         };
 
         assert_eq!(config.base_color, (255, 0, 0));
@@ -436,4 +633,141 @@ mod tests {
         assert_eq!(config.pause_position, 0.5);
         assert_eq!(config.opacity, 1.0);
     }
+
+    #[test]
+    fn test_column_distance_spans_wide_characters() {
+        assert_eq!(column_distance(4, 1, 4), 0.0);
+        assert_eq!(column_distance(4, 1, 1), 3.0);
+        // A double-width cell at columns 4-5 is hit at either column
+        assert_eq!(column_distance(4, 2, 5), 0.0);
+        assert_eq!(column_distance(4, 2, 8), 3.0);
+        assert_eq!(column_distance(4, 2, -1), 5.0);
+    }
+
+    #[test]
+    fn test_band_color_ramps_across_the_band() {
+        let colors = [
+            rgb_color((255, 255, 255)),
+            rgb_color((255, 215, 0)),
+            rgb_color((255, 165, 0)),
+        ];
+
+        // Centered on column 10 and reaching 4 columns either way
+        assert_eq!(band_color(&colors, 10, 10, 0.0, 4.0), colors[1]);
+        assert_eq!(band_color(&colors, 6, 10, 4.0, 4.0), colors[0]);
+        assert_eq!(band_color(&colors, 14, 10, 4.0, 4.0), colors[2]);
+        // One color is the whole band
+        assert_eq!(band_color(&colors[..1], 12, 10, 2.0, 4.0), colors[0]);
+    }
+
+    #[test]
+    fn test_band_colors_use_the_gradient_in_place_of_the_shine_color() {
+        let mut config = split_config(false);
+        assert_eq!(band_colors(&config), vec![rgb_color(config.shine_color)]);
+
+        config.shine_gradient = Some(vec![(255, 255, 255), (255, 165, 0)]);
+        assert_eq!(
+            band_colors(&config),
+            vec![rgb_color((255, 255, 255)), rgb_color((255, 165, 0))]
+        );
+    }
+
+    #[test]
+    fn test_shine_over_input_colors_keeps_its_contrast() {
+        let mut config = split_config(false);
+        let yellow = rgb_color(config.shine_color);
+        let gold = Some(rgb_color((255, 215, 0)));
+        assert_eq!(shine_over(yellow, gold, &config), yellow);
+
+        config.min_contrast = Some(3.0);
+        let Color::Rgb { r, g, b } = shine_over(yellow, gold, &config) else {
+            panic!("not an RGB color");
+        };
+        assert!(contrast_ratio((r, g, b), (255, 215, 0)) >= 3.0);
+        // Cells the input left alone go by the base color, as the band did
+        assert_eq!(shine_over(yellow, None, &config), yellow);
+    }
+
+    fn split_config(reflect: bool) -> ShineConfig {
+        ShineConfig {
+            base_color: (255, 255, 255),
+            speed: 50,
+            easing: EasingFunction::Linear,
+            duration: 1000,
+            cycles: 1,
+            start: ShineStart::Beginning,
+            alternate_direction: false,
+            width: 2,
+            blur: true,
+            padding: 4,
+            shine_color: (255, 255, 0),
+            shine_gradient: None,
+            pause_length: None,
+            pause_position: 0.5,
+            cycle_pre_delay: None,
+            cycle_post_delay: None,
+            cycle_switchback_delay: None,
+            opacity: 1.0,
+            min_contrast: None,
+            line_stagger: 0,
+            levels: None,
+            mode: ShineMode::Split,
+            reflect,
+            phase: 0.0,
+            color_mask: false,
+            terminal_width: None,
+        }
+    }
+
+    #[test]
+    fn test_split_bands_mirror_from_center() {
+        let config = split_config(false);
+
+        assert_eq!(
+            band_positions(0.0, 11, &config, ShineStart::Beginning),
+            vec![5, 5]
+        );
+        // Halfway out, each band has covered half of its run
+        assert_eq!(
+            band_positions(0.5, 11, &config, ShineStart::Beginning),
+            vec![0, 10]
+        );
+        // At the end both bands sit past the text in the padding
+        assert_eq!(
+            band_positions(1.0, 11, &config, ShineStart::Beginning),
+            vec![-5, 15]
+        );
+        // Starting from the end reverses the travel
+        assert_eq!(
+            band_positions(0.0, 11, &config, ShineStart::End),
+            vec![-5, 15]
+        );
+    }
+
+    #[test]
+    fn test_split_reflect_returns_to_center() {
+        let config = split_config(true);
+
+        assert_eq!(
+            band_positions(0.5, 11, &config, ShineStart::Beginning),
+            vec![-5, 15]
+        );
+        assert_eq!(
+            band_positions(1.0, 11, &config, ShineStart::Beginning),
+            vec![5, 5]
+        );
+    }
+
+    #[test]
+    fn test_single_mode_has_one_band() {
+        let config = ShineConfig {
+            mode: ShineMode::Single,
+            ..split_config(false)
+        };
+
+        assert_eq!(
+            band_positions(0.0, 10, &config, ShineStart::Beginning),
+            vec![-4]
+        );
+    }
 }
@@ -1,12 +1,72 @@
+use super::geometry::{project, Layout, Position2D};
+use super::stage::ColorStage;
+use crate::capability::fg;
+use crate::contrast::ensure_contrast;
+use crate::easing::EasingFunction;
+use crate::layout::Flow;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::{self, TerminalGuard};
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
 use crossterm::{
-    cursor, execute,
-    style::{Color, Print, ResetColor, SetForegroundColor},
-    terminal::size,
+    cursor, execute, queue,
+    style::{Color, Print, ResetColor},
+    terminal::{self, ClearType},
 };
-use std::io::{self, Write};
-use std::thread;
 use std::time::Duration;
 
+/// The shape of the highlight as it travels.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Shine2DShape {
+    /// A straight band sweeping across at `--angle`
+    Line,
+    /// One ring spreading out from the origin
+    Radial,
+    /// Rings spreading out from the origin one after another
+    Ripple,
+}
+
+/// Where radial shapes spread out from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShineOrigin {
+    /// The middle of the text
+    Center,
+    /// A column and row of the text, counted from its top-left corner
+    At(usize, usize),
+}
+
+impl ShineOrigin {
+    /// Parses `center` or a column and row as `x,y`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if value.trim().eq_ignore_ascii_case("center") {
+            return Ok(ShineOrigin::Center);
+        }
+        let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+        match parts[..] {
+            [x, y] => match (x.parse(), y.parse()) {
+                (Ok(x), Ok(y)) => Ok(ShineOrigin::At(x, y)),
+                _ => Err(format!("'{value}' isn't a column and row like 4,1")),
+            },
+            _ => Err(format!("expected center or x,y, got '{value}'")),
+        }
+    }
+
+    fn point(&self, layout: &Layout) -> (f32, f32) {
+        match *self {
+            ShineOrigin::Center => (
+                (layout.width as f32 - 1.0) / 2.0,
+                (layout.height() as f32 - 1.0) / 2.0,
+            ),
+            ShineOrigin::At(x, y) => (x as f32, y as f32),
+        }
+    }
+}
+
+/// Ripple rings trail each other by this many shine widths.
+const RIPPLE_SPACING: f32 = 4.0;
+
 pub struct Shine2DConfig {
     pub base_color: (u8, u8, u8),
     pub speed: u64,
@@ -14,6 +74,7 @@ pub struct Shine2DConfig {
     pub duration: u64,
     pub cycles: u32,
     pub start: ShineStart,
+    pub alternate_direction: bool,
     pub width: usize,
     pub blur: bool,
     pub padding: usize,
@@ -24,8 +85,19 @@ pub struct Shine2DConfig {
     pub cycle_post_delay: Option<u64>,
     pub cycle_switchback_delay: Option<u64>,
     pub opacity: f32,
+    pub min_contrast: Option<f32>,
+    pub levels: Option<u32>,
+    pub phase: f32,
     pub angle: f32,
+    /// Turns a line shine from the first angle to the second over each cycle
+    pub angle_sweep: Option<(f32, f32)>,
+    /// Turns a line shine a full circle each cycle, starting from `angle`
+    pub rotate: bool,
+    pub shape: Shine2DShape,
+    pub origin: ShineOrigin,
     pub terminal_width: Option<usize>,
+    /// How the text wraps to the terminal width and lines up
+    pub flow: Flow,
 }
 
 impl Default for Shine2DConfig {
@@ -37,6 +109,7 @@ impl Default for Shine2DConfig {
             duration: 2000,
             cycles: 1,
             start: ShineStart::Beginning,
+            alternate_direction: false,
             width: 3,
             blur: true,
             padding: 5,
@@ -47,99 +120,82 @@ impl Default for Shine2DConfig {
             cycle_post_delay: None,
             cycle_switchback_delay: None,
             opacity: 1.0,
+            min_contrast: None,
+            levels: None,
+            phase: 0.0,
             angle: 90.0, // Default to vertical shine
+            angle_sweep: None,
+            rotate: false,
+            shape: Shine2DShape::Line,
+            origin: ShineOrigin::Center,
             terminal_width: None,
+            flow: Flow::default(),
         }
     }
 }
 
-#[derive(Clone)]
-pub enum ShineStart {
-    Beginning,
-    End,
-}
+use super::driver::with_phase;
+pub use super::driver::ShineStart;
 
-#[derive(Debug, Clone)]
-pub enum EasingFunction {
-    Linear,
-    EaseIn,
-    EaseOut,
-    EaseInOut,
+/// Where `pos` lies along the direction the shine travels, worked out once
+/// per layout so frames only compare it with the shine line.
+fn axis_position(pos: &Position2D, angle: f32) -> f32 {
+    if angle.abs() < 0.01 {
+        // Horizontal shine (angle ≈ 0)
+        pos.y as f32
+    } else if (angle - 90.0).abs() < 0.01 {
+        // Vertical shine (angle ≈ 90)
+        pos.x as f32
+    } else {
+        // Diagonal shine - distance from point to line
+        // Line equation: cos(θ)x + sin(θ)y = shine_line
+        project(pos, angle)
+    }
 }
 
-impl EasingFunction {
-    fn apply(&self, t: f32) -> f32 {
-        match self {
-            EasingFunction::Linear => t,
-            EasingFunction::EaseIn => t * t,
-            EasingFunction::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
-            EasingFunction::EaseInOut => {
-                if t < 0.5 {
-                    2.0 * t * t
-                } else {
-                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
-                }
-            }
-        }
+/// The line's angle `progress` (0.0 to 1.0) of the way through a cycle,
+/// or `None` when it stays at `--angle`.
+fn angle_at(config: &Shine2DConfig, progress: f32) -> Option<f32> {
+    match config.angle_sweep {
+        Some((from, to)) => Some(from + (to - from) * progress),
+        None if config.rotate => Some((config.angle + 360.0 * progress) % 360.0),
+        None => None,
     }
 }
 
-#[derive(Debug, Clone)]
-struct Position2D {
-    x: usize,
-    y: usize,
+/// Where `pos` lies along the direction a turning line travels. It's
+/// measured from `center`, the middle of the text, and shifted by `reach`
+/// so every angle keeps the cells between 0 and twice `reach`, and the
+/// band sweeps the whole text whichever way it points.
+fn turning_axis_position(pos: &Position2D, center: (f32, f32), angle: f32, reach: f32) -> f32 {
+    // The same axis as `axis_position`: rows at 0 degrees, columns at 90
+    let angle_rad = angle.to_radians();
+    angle_rad.sin() * (pos.x as f32 - center.0)
+        + angle_rad.cos() * (pos.y as f32 - center.1)
+        + reach
 }
 
-fn wrap_text_to_grid(text: &str, terminal_width: usize) -> Vec<Vec<char>> {
-    let mut grid = Vec::new();
-    let mut current_line = Vec::new();
-
-    for ch in text.chars() {
-        if ch == '\n' {
-            grid.push(current_line);
-            current_line = Vec::new();
-        } else {
-            current_line.push(ch);
-            if current_line.len() >= terminal_width {
-                grid.push(current_line);
-                current_line = Vec::new();
-            }
-        }
-    }
+/// How far `pos` is from `origin`, with rows counted as two columns so
+/// rings look round rather than squashed.
+fn radial_position(pos: &Position2D, origin: (f32, f32)) -> f32 {
+    (pos.x as f32 - origin.0).hypot((pos.y as f32 - origin.1) * 2.0)
+}
 
-    if !current_line.is_empty() {
-        grid.push(current_line);
+/// Distance from a cell `axis` out from the origin to the nearest ripple
+/// ring, the leading one at `shine_position` and the rest trailing it
+/// every `spacing`. Cells the ripple hasn't reached yet only see the
+/// leading ring.
+fn ripple_distance(axis: f32, shine_position: f32, spacing: f32) -> f32 {
+    let behind = shine_position - axis;
+    if behind < 0.0 {
+        return -behind;
     }
-
-    grid
+    let offset = behind % spacing;
+    offset.min(spacing - offset)
 }
 
-fn calculate_2d_shine_intensity(
-    pos: &Position2D,
-    shine_line: f32,
-    angle: f32,
-    width: f32,
-    blur: bool,
-) -> f32 {
-    let angle_rad = angle.to_radians();
-    let cos_angle = angle_rad.cos();
-    let sin_angle = angle_rad.sin();
-
-    // Calculate distance from point to shine line based on angle
-    let distance = if angle.abs() < 0.01 {
-        // Horizontal shine (angle ≈ 0)
-        (pos.y as f32 - shine_line).abs()
-    } else if (angle - 90.0).abs() < 0.01 {
-        // Vertical shine (angle ≈ 90)
-        (pos.x as f32 - shine_line).abs()
-    } else {
-        // Diagonal shine - distance from point to line
-        // Line equation: cos(θ)x + sin(θ)y = shine_line
-        let line_point_distance =
-            (cos_angle * pos.x as f32 + sin_angle * pos.y as f32 - shine_line).abs();
-        line_point_distance / (cos_angle * cos_angle + sin_angle * sin_angle).sqrt()
-    };
-
+/// Intensity of a cell `distance` from the shine line.
+fn band_intensity(distance: f32, width: f32, blur: bool) -> f32 {
     if distance <= width {
         if blur {
             1.0 - (distance / width)
@@ -156,25 +212,33 @@ fn calculate_2d_shine_intensity(
 pub fn apply_shine2d_effect(
     text: &str,
     config: &Shine2DConfig,
-    centering_offsets: Option<(u16, u16)>,
+    placement: Placement,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut stdout = io::stdout();
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
 
     if text.is_empty() {
-        println!();
+        newline()?;
         return Ok(());
     }
 
     let terminal_width = config
         .terminal_width
-        .unwrap_or_else(|| size().map(|(w, _)| w as usize).unwrap_or(80));
-
-    let grid = wrap_text_to_grid(text, terminal_width);
-    let grid_height = grid.len();
-    let max_width = grid.iter().map(|line| line.len()).max().unwrap_or(0);
+        .unwrap_or_else(|| screen::size().map(|(w, _)| w as usize).unwrap_or(80));
+
+    // The layout pass; it's only redone when the text is rewrapped
+    let axis_positions = |layout: &Layout| match config.shape {
+        Shine2DShape::Line => layout.map(|pos| axis_position(pos, config.angle)),
+        Shine2DShape::Radial | Shine2DShape::Ripple => {
+            let origin = config.origin.point(layout);
+            layout.map(|pos| radial_position(pos, origin))
+        }
+    };
+    let mut layout = Layout::wrap(text, terminal_width, config.flow);
+    let mut axes = axis_positions(&layout);
 
-    if grid_height == 0 || max_width == 0 {
-        println!();
+    if layout.is_empty() {
+        newline()?;
         return Ok(());
     }
 
@@ -192,36 +256,68 @@ pub fn apply_shine2d_effect(
         b: config.base_color.2,
     };
 
-    let shine_color = Color::Rgb {
-        r: config.shine_color.0,
-        g: config.shine_color.1,
-        b: config.shine_color.2,
+    let shine_rgb = match config.min_contrast {
+        Some(min_ratio) => ensure_contrast(
+            config.shine_color,
+            config.base_color,
+            config.opacity,
+            min_ratio,
+        ),
+        None => config.shine_color,
     };
 
-    // Calculate the range for the shine to travel based on angle
-    let diagonal_length = ((max_width * max_width + grid_height * grid_height) as f32).sqrt();
-    let shine_range = diagonal_length + (2 * config.padding) as f32;
+    let shine_color = Color::Rgb {
+        r: shine_rgb.0,
+        g: shine_rgb.1,
+        b: shine_rgb.2,
+    };
+    let mut stage = ColorStage::new(config.levels);
+
+    // Calculate the range for the shine to travel: across the text for a
+    // line, out to the furthest cell for the radial shapes
+    let range_for = |layout: &Layout, axes: &[Vec<f32>]| {
+        let reach = match config.shape {
+            Shine2DShape::Line => {
+                let (width, height) = (layout.width, layout.height());
+                ((width * width + height * height) as f32).sqrt()
+            }
+            Shine2DShape::Radial | Shine2DShape::Ripple => axes
+                .iter()
+                .flatten()
+                .fold(0.0, |furthest: f32, &axis| furthest.max(axis)),
+        };
+        reach + (2 * config.padding) as f32
+    };
+    let mut shine_range = range_for(&layout, &axes);
+    let ripple_spacing = (config.width as f32 * RIPPLE_SPACING).max(2.0);
 
-    if centering_offsets.is_some() {
-        execute!(stdout, 
-            crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
-            cursor::Hide
-        )?;
-    } else {
-        execute!(stdout, cursor::SavePosition, cursor::Hide)?;
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+    if centering_offsets.is_none() {
+        execute!(stdout, cursor::SavePosition)?;
     }
 
     for cycle in 0..cycles_to_run {
+        let start = config.start.for_cycle(cycle, config.alternate_direction);
+
         if let Some(pre_delay) = config.cycle_pre_delay {
-            thread::sleep(Duration::from_millis(pre_delay));
+            playback::sleep(Duration::from_millis(pre_delay))?;
         }
 
         for frame in 0..total_frames {
-            let progress = frame as f32 / (total_frames - 1) as f32;
+            let progress = with_phase(frame as f32 / (total_frames - 1) as f32, config.phase);
             let eased_progress = config.easing.apply(progress);
 
+            if let Some(angle) = angle_at(config, progress) {
+                if config.shape == Shine2DShape::Line {
+                    let center = ShineOrigin::Center.point(&layout);
+                    let reach = (layout.width as f32).hypot(layout.height() as f32) / 2.0;
+                    axes = layout.map(|pos| turning_axis_position(pos, center, angle, reach));
+                }
+            }
+
             let prev_progress = if frame > 0 {
-                let prev_frame_progress = (frame - 1) as f32 / (total_frames - 1) as f32;
+                let prev_frame_progress =
+                    with_phase((frame - 1) as f32 / (total_frames - 1) as f32, config.phase);
                 config.easing.apply(prev_frame_progress)
             } else {
                 0.0
@@ -229,7 +325,7 @@ pub fn apply_shine2d_effect(
 
             if let Some(switchback_delay) = config.cycle_switchback_delay {
                 if frame > 0 && prev_progress < 0.5 && eased_progress >= 0.5 {
-                    thread::sleep(Duration::from_millis(switchback_delay));
+                    playback::sleep(Duration::from_millis(switchback_delay))?;
                 }
             }
 
@@ -239,7 +335,7 @@ pub fn apply_shine2d_effect(
                 2.0 - (eased_progress * 2.0)
             };
 
-            let shine_position = match config.start {
+            let shine_position = match start {
                 ShineStart::Beginning => {
                     back_and_forth_progress * shine_range - config.padding as f32
                 }
@@ -253,56 +349,74 @@ pub fn apply_shine2d_effect(
                 let pause_tolerance = 0.05;
 
                 if (normalized_position - config.pause_position).abs() < pause_tolerance {
-                    thread::sleep(Duration::from_millis(pause_length));
+                    playback::sleep(Duration::from_millis(pause_length))?;
                 }
             }
 
+            if let Some((width, height)) = resize::track(&mut centering_offsets)? {
+                // Rewrap to the new width, unless the width was given
+                if config.terminal_width.is_none() {
+                    layout = Layout::wrap(text, width as usize, config.flow);
+                    axes = axis_positions(&layout);
+                    shine_range = range_for(&layout, &axes);
+                }
+                match centering_offsets.as_mut() {
+                    Some(offsets) => *offsets = layout.centered_in((width, height)),
+                    None => queue!(
+                        stdout,
+                        cursor::RestorePosition,
+                        terminal::Clear(ClearType::FromCursorDown)
+                    )?,
+                }
+            }
+            let frame_timer = FrameTimer::start();
             if let Some((top_offset, left_offset)) = centering_offsets {
-                execute!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+                queue!(stdout, cursor::MoveTo(left_offset, top_offset))?;
             } else {
-                execute!(stdout, cursor::RestorePosition)?;
+                queue!(stdout, cursor::RestorePosition)?;
             }
 
-            for (y, line) in grid.iter().enumerate() {
+            // The paint pass: just color math over the cached layout
+            for (y, (line, line_axes)) in layout.grid.iter().zip(&axes).enumerate() {
                 if let Some((_, left_offset)) = centering_offsets {
-                    execute!(stdout, cursor::MoveToColumn(left_offset))?;
+                    queue!(stdout, cursor::MoveToColumn(left_offset))?;
                 } else {
-                    execute!(stdout, cursor::MoveToColumn(0))?;
+                    queue!(stdout, cursor::MoveToColumn(0))?;
                 }
 
-                for (x, &ch) in line.iter().enumerate() {
-                    let pos = Position2D { x, y };
-                    let intensity = calculate_2d_shine_intensity(
-                        &pos,
-                        shine_position,
-                        config.angle,
-                        config.width as f32,
-                        config.blur,
-                    );
+                for (&ch, &axis) in line.iter().zip(line_axes) {
+                    let distance = match config.shape {
+                        Shine2DShape::Ripple => {
+                            ripple_distance(axis, shine_position, ripple_spacing)
+                        }
+                        Shine2DShape::Line | Shine2DShape::Radial => (axis - shine_position).abs(),
+                    };
+                    let intensity = band_intensity(distance, config.width as f32, config.blur);
 
                     if intensity > 0.0 {
                         let opacity_adjusted_intensity = intensity * config.opacity;
                         let blended_color =
-                            blend_colors(base_color, shine_color, opacity_adjusted_intensity);
-                        execute!(stdout, SetForegroundColor(blended_color), Print(ch))?;
+                            stage.shade(base_color, shine_color, opacity_adjusted_intensity);
+                        queue!(stdout, fg(blended_color), Print(ch))?;
                     } else {
-                        execute!(stdout, SetForegroundColor(base_color), Print(ch))?;
+                        queue!(stdout, fg(base_color), Print(ch))?;
                     }
                 }
 
-                if y < grid.len() - 1 {
-                    execute!(stdout, Print('\n'))?;
+                if y < layout.height() - 1 {
+                    queue!(stdout, Print('\n'))?;
                 }
             }
 
-            execute!(stdout, ResetColor)?;
-            stdout.flush()?;
+            queue!(stdout, ResetColor)?;
+            stdout.flush_frame()?;
+            frame_timer.finish();
 
-            thread::sleep(frame_duration);
+            playback::sleep(frame_duration)?;
         }
 
         if let Some(post_delay) = config.cycle_post_delay {
-            thread::sleep(Duration::from_millis(post_delay));
+            playback::sleep(Duration::from_millis(post_delay))?;
         }
 
         if config.cycles > 0 && cycle + 1 == cycles_to_run {
@@ -310,35 +424,10 @@ pub fn apply_shine2d_effect(
         }
     }
 
-    execute!(stdout, cursor::Show)?;
-    println!();
+    newline()?;
     Ok(())
 }
 
-fn blend_colors(base: Color, shine: Color, intensity: f32) -> Color {
-    let intensity = intensity.clamp(0.0, 1.0);
-
-    let (base_r, base_g, base_b) = match base {
-        Color::Rgb { r, g, b } => (r, g, b),
-        _ => (255, 255, 255),
-    };
-
-    let (shine_r, shine_g, shine_b) = match shine {
-        Color::Rgb { r, g, b } => (r, g, b),
-        _ => (255, 255, 255),
-    };
-
-    let blended_r = (base_r as f32 * (1.0 - intensity) + shine_r as f32 * intensity) as u8;
-    let blended_g = (base_g as f32 * (1.0 - intensity) + shine_g as f32 * intensity) as u8;
-    let blended_b = (base_b as f32 * (1.0 - intensity) + shine_b as f32 * intensity) as u8;
-
-    Color::Rgb {
-        r: blended_r,
-        g: blended_g,
-        b: blended_b,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,6 +435,16 @@ mod tests {
 
     const TEST_TOLERANCE: f32 = 0.001;
 
+    fn calculate_2d_shine_intensity(
+        pos: &Position2D,
+        shine_line: f32,
+        angle: f32,
+        width: f32,
+        blur: bool,
+    ) -> f32 {
+        band_intensity((axis_position(pos, angle) - shine_line).abs(), width, blur)
+    }
+
     #[test]
     fn test_shine2d_config_default() {
         let config = Shine2DConfig::default();
@@ -364,44 +463,6 @@ mod tests {
         assert_eq!(config.terminal_width, None);
     }
 
-    #[test]
-    fn test_wrap_text_to_grid_basic() {
-        let text = "Hello World";
-        let grid = wrap_text_to_grid(text, 5);
-
-        assert_eq!(grid.len(), 3);
-        assert_eq!(grid[0], vec!['H', 'e', 'l', 'l', 'o']);
-        assert_eq!(grid[1], vec![' ', 'W', 'o', 'r', 'l']);
-        assert_eq!(grid[2], vec!['d']);
-    }
-
-    #[test]
-    fn test_wrap_text_to_grid_with_newlines() {
-        let text = "Hello\nWorld";
-        let grid = wrap_text_to_grid(text, 10);
-
-        assert_eq!(grid.len(), 2);
-        assert_eq!(grid[0], vec!['H', 'e', 'l', 'l', 'o']);
-        assert_eq!(grid[1], vec!['W', 'o', 'r', 'l', 'd']);
-    }
-
-    #[test]
-    fn test_wrap_text_to_grid_empty() {
-        let text = "";
-        let grid = wrap_text_to_grid(text, 10);
-
-        assert_eq!(grid.len(), 0);
-    }
-
-    #[test]
-    fn test_wrap_text_to_grid_exact_width() {
-        let text = "12345";
-        let grid = wrap_text_to_grid(text, 5);
-
-        assert_eq!(grid.len(), 1);
-        assert_eq!(grid[0], vec!['1', '2', '3', '4', '5']);
-    }
-
     #[test]
     fn test_calculate_2d_shine_intensity_vertical() {
         let pos = Position2D { x: 5, y: 10 };
@@ -488,6 +549,96 @@ mod tests {
         assert!(intensity > 0.0);
     }
 
+    #[test]
+    fn test_shine_origin_parses_center_or_a_cell() {
+        assert_eq!(ShineOrigin::parse("center"), Ok(ShineOrigin::Center));
+        assert_eq!(ShineOrigin::parse("4, 1"), Ok(ShineOrigin::At(4, 1)));
+        assert!(ShineOrigin::parse("4").is_err());
+        assert!(ShineOrigin::parse("left,top").is_err());
+    }
+
+    #[test]
+    fn test_radial_position_measures_out_from_the_origin() {
+        let layout = Layout::wrap("abcde\nfghij\nklmno", 80, Flow::default());
+        let origin = ShineOrigin::Center.point(&layout);
+        assert_eq!(origin, (2.0, 1.0));
+
+        assert_eq!(radial_position(&Position2D { x: 2, y: 1 }, origin), 0.0);
+        assert_eq!(radial_position(&Position2D { x: 4, y: 1 }, origin), 2.0);
+        // A row away is as far as two columns away
+        assert_eq!(radial_position(&Position2D { x: 2, y: 0 }, origin), 2.0);
+        assert_approx_eq!(
+            radial_position(
+                &Position2D { x: 0, y: 0 },
+                ShineOrigin::At(3, 2).point(&layout)
+            ),
+            5.0,
+            TEST_TOLERANCE
+        );
+    }
+
+    #[test]
+    fn test_ripple_rings_trail_the_leading_one() {
+        // The leading ring, and cells it hasn't reached yet
+        assert_eq!(ripple_distance(10.0, 10.0, 8.0), 0.0);
+        assert_eq!(ripple_distance(13.0, 10.0, 8.0), 3.0);
+        // Rings trailing it every 8 columns
+        assert_eq!(ripple_distance(2.0, 10.0, 8.0), 0.0);
+        assert_eq!(ripple_distance(5.0, 10.0, 8.0), 3.0);
+        assert_eq!(ripple_distance(7.0, 10.0, 8.0), 3.0);
+    }
+
+    #[test]
+    fn test_angle_at_sweeps_or_rotates_over_the_cycle() {
+        let fixed = Shine2DConfig::default();
+        assert_eq!(angle_at(&fixed, 0.5), None);
+
+        let sweep = Shine2DConfig {
+            angle_sweep: Some((0.0, 90.0)),
+            ..Default::default()
+        };
+        assert_eq!(angle_at(&sweep, 0.0), Some(0.0));
+        assert_eq!(angle_at(&sweep, 0.5), Some(45.0));
+        assert_eq!(angle_at(&sweep, 1.0), Some(90.0));
+
+        let rotate = Shine2DConfig {
+            angle: 90.0,
+            rotate: true,
+            ..Default::default()
+        };
+        assert_eq!(angle_at(&rotate, 0.25), Some(180.0));
+        assert_eq!(angle_at(&rotate, 0.75), Some(0.0));
+    }
+
+    #[test]
+    fn test_turning_axis_covers_the_text_at_every_angle() {
+        let layout = Layout::wrap("abcde\nfghij\nklmno", 80, Flow::default());
+        let center = ShineOrigin::Center.point(&layout);
+        let reach = (layout.width as f32).hypot(layout.height() as f32) / 2.0;
+
+        // Matches the fixed axis at 0 and 90 degrees, shifted to the center
+        let pos = Position2D { x: 4, y: 0 };
+        assert_approx_eq!(
+            turning_axis_position(&pos, center, 0.0, reach),
+            axis_position(&pos, 0.0) - center.1 + reach,
+            TEST_TOLERANCE
+        );
+        assert_approx_eq!(
+            turning_axis_position(&pos, center, 90.0, reach),
+            axis_position(&pos, 90.0) - center.0 + reach,
+            TEST_TOLERANCE
+        );
+        for angle in [0.0, 45.0, 135.0, 200.0, 315.0] {
+            for axis in layout
+                .map(|pos| turning_axis_position(pos, center, angle, reach))
+                .iter()
+                .flatten()
+            {
+                assert!((0.0..=2.0 * reach).contains(axis), "{angle}: {axis}");
+            }
+        }
+    }
+
     #[test]
     fn test_easing_function_shine2d_linear() {
         let easing = EasingFunction::Linear;
@@ -511,11 +662,4 @@ mod tests {
         assert!(easing.apply(0.1) < 0.1);
         assert!(easing.apply(0.9) > 0.8);
     }
-
-    #[test]
-    fn test_position2d_creation() {
-        let pos = Position2D { x: 10, y: 20 };
-        assert_eq!(pos.x, 10);
-        assert_eq!(pos.y, 20);
-    }
 }
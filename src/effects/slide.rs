@@ -0,0 +1,280 @@
+use super::frame::Frame;
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled};
+use crate::easing::EasingFunction;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::{self, TerminalGuard};
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
+use crossterm::{cursor, queue, style::Color};
+use std::io::Write;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SlideFrom {
+    /// In from past the left edge
+    Left,
+    /// In from past the right edge
+    Right,
+    /// Down from above the top
+    Top,
+    /// Up from below the bottom
+    Bottom,
+}
+
+impl SlideFrom {
+    fn is_horizontal(self) -> bool {
+        matches!(self, SlideFrom::Left | SlideFrom::Right)
+    }
+}
+
+pub struct SlideConfig {
+    pub color: (u8, u8, u8),
+    pub from: SlideFrom,
+    /// Milliseconds for the text to reach its place
+    pub duration: u64,
+    pub speed: u64,
+    pub easing: EasingFunction,
+    /// How far past its place the text swings before settling back, as
+    /// the strength of a back easing; 0 stops it dead
+    pub overshoot: f32,
+}
+
+impl Default for SlideConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            from: SlideFrom::Left,
+            duration: 800,
+            speed: 20,
+            easing: EasingFunction::EaseOut,
+            overshoot: 0.0,
+        }
+    }
+}
+
+/// `progress` carried past the end and back by `strength`, the way CSS's
+/// ease-out-back overshoots; `progress` itself without any.
+pub fn overshoot(progress: f32, strength: f32) -> f32 {
+    if strength <= 0.0 {
+        return progress;
+    }
+    let t = progress - 1.0;
+    1.0 + (strength + 1.0) * t.powi(3) + strength * t.powi(2)
+}
+
+/// The cleared stretch of screen the text slides through: where its top
+/// left is drawn (at the cursor's line when not centered), its size, and
+/// the cell in it where the text comes to rest.
+#[derive(Debug, PartialEq)]
+struct Region {
+    offsets: Option<(u16, u16)>,
+    size: (usize, usize),
+    rest: (usize, usize),
+}
+
+/// The region for text of `text_size` sliding in `from` a side of a
+/// `screen_size` terminal, to rest at `centering_offsets`. It reaches from
+/// the side the text comes from to the far edge of where it rests. Text
+/// that isn't centered slides along its own rows, so from above or below
+/// it appears at their edge.
+fn region(
+    from: SlideFrom,
+    text_size: (usize, usize),
+    centering_offsets: Option<(u16, u16)>,
+    screen_size: (usize, usize),
+) -> Region {
+    let (text_width, text_height) = text_size;
+    let Some((top, left)) = centering_offsets else {
+        let width = match from {
+            SlideFrom::Right => screen_size.0.max(text_width),
+            _ => text_width,
+        };
+        return Region {
+            offsets: None,
+            size: (width, text_height),
+            rest: (0, 0),
+        };
+    };
+    let (top, left) = (top as usize, left as usize);
+    if from.is_horizontal() {
+        let width = match from {
+            SlideFrom::Right => screen_size.0.max(left + text_width),
+            _ => left + text_width,
+        };
+        Region {
+            offsets: Some((top as u16, 0)),
+            size: (width, text_height),
+            rest: (left, 0),
+        }
+    } else {
+        let height = match from {
+            SlideFrom::Bottom => screen_size.1.max(top + text_height),
+            _ => top + text_height,
+        };
+        Region {
+            offsets: Some((0, left as u16)),
+            size: (text_width, height),
+            rest: (0, top),
+        }
+    }
+}
+
+/// Where in `region` the text's top left starts, just out of sight past
+/// the side it comes `from`.
+fn start(from: SlideFrom, text_size: (usize, usize), region: &Region) -> (isize, isize) {
+    let (rest_x, rest_y) = (region.rest.0 as isize, region.rest.1 as isize);
+    match from {
+        SlideFrom::Left => (-(text_size.0 as isize), rest_y),
+        SlideFrom::Right => (region.size.0 as isize, rest_y),
+        SlideFrom::Top => (rest_x, -(text_size.1 as isize)),
+        SlideFrom::Bottom => (rest_x, region.size.1 as isize),
+    }
+}
+
+/// Slides `lines` into place from past one side of the screen, easing as
+/// they come and, with an overshoot, swinging past their place and back.
+pub fn apply_slide_effect(
+    lines: &[String],
+    config: &SlideConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let base_color = rgb_color(config.color);
+    let mut cells: Vec<(usize, usize, char, Color)> = Vec::new();
+    let mut width = 0;
+    for (y, line) in lines.iter().enumerate() {
+        let styled = parse_styled(line);
+        let (columns, line_width) = column_starts(&styled);
+        width = width.max(line_width);
+        for (cell, x) in styled.iter().zip(columns) {
+            cells.push((x, y, cell.ch, cell.color.unwrap_or(base_color)));
+        }
+    }
+    if width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    let text_size = (width, lines.len());
+    let screen_size = || {
+        let (columns, rows) = screen::layout_size().unwrap_or((80, 24));
+        (columns as usize, rows as usize)
+    };
+    let render = |at: (isize, isize), size: (usize, usize)| {
+        let mut frame = Frame::new(size.0, size.1);
+        for &(x, y, ch, color) in &cells {
+            let (x, y) = (at.0 + x as isize, at.1 + y as isize);
+            if x >= 0 && y >= 0 {
+                frame.put(x as usize, y as usize, ch, color);
+            }
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let duration = config.duration.max(1);
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0;
+    while elapsed < duration {
+        resize::track(&mut centering_offsets)?;
+        let area = region(config.from, text_size, centering_offsets, screen_size());
+        let (from_x, from_y) = start(config.from, text_size, &area);
+        let progress = overshoot(
+            config.easing.apply(elapsed as f32 / duration as f32),
+            config.overshoot,
+        );
+        let along = |from: isize, to: usize| {
+            from + ((to as isize - from) as f32 * progress).round() as isize
+        };
+        let at = (along(from_x, area.rest.0), along(from_y, area.rest.1));
+
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(render(at, area.size), &mut stdout, area.offsets)?;
+        stdout.flush()?;
+        frame_timer.finish();
+
+        playback::sleep(frame_duration)?;
+        elapsed += config.speed.max(1);
+    }
+
+    resize::track(&mut centering_offsets)?;
+    let area = region(config.from, text_size, centering_offsets, screen_size());
+    let rest = (area.rest.0 as isize, area.rest.1 as isize);
+    repaint.draw(render(rest, area.size), &mut stdout, area.offsets)?;
+    // The region can run on past the text, and what follows goes below it
+    if let Some((top, left)) = centering_offsets {
+        queue!(
+            stdout,
+            cursor::MoveTo(left, top + text_size.1.saturating_sub(1) as u16)
+        )?;
+    }
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_slide_config_default() {
+        let config = SlideConfig::default();
+
+        assert_eq!(config.from, SlideFrom::Left);
+        assert_eq!(config.duration, 800);
+        assert_eq!(config.overshoot, 0.0);
+    }
+
+    #[test]
+    fn test_overshoot_swings_past_and_settles() {
+        assert_eq!(overshoot(0.5, 0.0), 0.5);
+        assert_approx_eq!(overshoot(0.0, 1.7), 0.0, 0.001);
+        assert_approx_eq!(overshoot(1.0, 1.7), 1.0, 0.001);
+        assert!(overshoot(0.8, 1.7) > 1.0);
+    }
+
+    #[test]
+    fn test_region_runs_from_the_side_to_the_text() {
+        let text = (4, 2);
+        let screen = (20, 10);
+
+        // Centered, across the text's rows or down its columns
+        assert_eq!(
+            region(SlideFrom::Left, text, Some((3, 8)), screen),
+            Region {
+                offsets: Some((3, 0)),
+                size: (12, 2),
+                rest: (8, 0)
+            }
+        );
+        assert_eq!(
+            region(SlideFrom::Right, text, Some((3, 8)), screen).size,
+            (20, 2)
+        );
+        assert_eq!(
+            region(SlideFrom::Bottom, text, Some((3, 8)), screen),
+            Region {
+                offsets: Some((0, 8)),
+                size: (4, 10),
+                rest: (0, 3)
+            }
+        );
+
+        // Where it is, only its own rows
+        let inline = region(SlideFrom::Top, text, None, screen);
+        assert_eq!(inline.size, (4, 2));
+        assert_eq!(start(SlideFrom::Top, text, &inline), (0, -2));
+        let inline = region(SlideFrom::Right, text, None, screen);
+        assert_eq!(start(SlideFrom::Right, text, &inline), (20, 0));
+    }
+}
@@ -0,0 +1,330 @@
+use super::density::{Density, SpawnBias};
+use super::frame::Frame;
+use super::particles::{density_share, screen_size, Particle, Particles, SpawnPoints};
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::quality::{Governor, Quality};
+use crate::resize;
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use crossterm::{
+    execute,
+    terminal::{self, ClearType},
+};
+use rand::Rng;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Snow drift glyphs from a dusting to a full cell, in eighths.
+const DRIFT_GLYPHS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Rows per second flakes fall, each at its own pace within the range.
+const FALL_SPEED: (f32, f32) = (4.0, 10.0);
+
+pub struct SnowConfig {
+    /// Color of the text the snow falls over
+    pub color: (u8, u8, u8),
+    pub flake_color: (u8, u8, u8),
+    /// Characters flakes are drawn with, each flake one at random
+    pub flake_chars: Vec<char>,
+    /// Share of cells (0.0 to 1.0) holding a flake at any moment
+    pub density: f32,
+    /// Columns a flake moves sideways per row it falls (negative is left)
+    pub wind: f32,
+    /// Which columns flakes are likeliest to fall down: over dense glyphs,
+    /// through the open space around them, or any
+    pub spawn_bias: SpawnBias,
+    /// Flakes pass behind the text instead of over it
+    pub behind: bool,
+    /// Flakes pile up along the bottom row
+    pub accumulate: bool,
+    /// Milliseconds to keep snowing; 0 snows forever
+    pub duration: u64,
+    pub speed: u64,
+    pub quality: Quality,
+}
+
+impl Default for SnowConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            flake_color: (255, 255, 255),
+            flake_chars: vec!['*', '·', '•', '❄'],
+            density: 0.02,
+            wind: 0.0,
+            spawn_bias: SpawnBias::Uniform,
+            behind: false,
+            accumulate: false,
+            duration: 5000,
+            speed: 50,
+            quality: Quality::High,
+        }
+    }
+}
+
+/// A flake at column `x` of row `y`, falling at its own pace and drifting
+/// with the wind.
+fn spawn_flake(rng: &mut impl Rng, config: &SnowConfig, x: f32, y: f32) -> Particle {
+    let fall = rng.gen_range(FALL_SPEED.0..=FALL_SPEED.1) / 1000.0;
+    Particle {
+        x,
+        y,
+        velocity: (config.wind * fall, fall),
+        age: 0,
+        lifetime: u64::MAX,
+        glyph: config.flake_chars[rng.gen_range(0..config.flake_chars.len())],
+        color: rgb_color(config.flake_color),
+    }
+}
+
+/// Snow falling through a `size` field, already spread over it at
+/// `points` so the effect opens mid-snowfall. New flakes arrive just above
+/// the top row about as fast as the slowest ones fall out of the bottom.
+fn snowfall(
+    config: &SnowConfig,
+    size: (usize, usize),
+    points: &SpawnPoints,
+    quality: Quality,
+    rng: &mut impl Rng,
+) -> Particles {
+    let target = Particles::new(size, config.density, 1, true).target();
+    let crossing = (size.1 as f32 / FALL_SPEED.0 * 1000.0) as u64;
+    let frames_to_cross = (crossing / config.speed.max(1)).max(1) as usize;
+    let mut field = Particles::new(size, config.density, target.div_ceil(frames_to_cross), true);
+
+    let starting = (target as f32 * density_share(quality)).round() as usize;
+    for _ in 0..starting {
+        let (x, y) = points.cell(rng);
+        field.particles.push(spawn_flake(rng, config, x, y));
+    }
+    field
+}
+
+/// How deep snow lies in each column, in eighths of a cell.
+struct Drifts(Vec<u8>);
+
+impl Drifts {
+    fn new(width: usize) -> Self {
+        Self(vec![0; width])
+    }
+
+    fn land(&mut self, columns: &[usize]) {
+        for &column in columns {
+            if let Some(depth) = self.0.get_mut(column) {
+                *depth = (*depth + 1).min(DRIFT_GLYPHS.len() as u8);
+            }
+        }
+    }
+
+    /// The glyph for `column`'s drift, if any snow has settled there.
+    fn glyph(&self, column: usize) -> Option<char> {
+        match self.0.get(column) {
+            Some(&depth) if depth > 0 => Some(DRIFT_GLYPHS[depth as usize - 1]),
+            _ => None,
+        }
+    }
+}
+
+/// The part of a `size` screen flakes fall through: all of it, or all but
+/// the bottom row when that's where they settle.
+fn flake_field(size: (usize, usize), config: &SnowConfig) -> (usize, usize) {
+    if config.accumulate {
+        (size.0, size.1.saturating_sub(1))
+    } else {
+        size
+    }
+}
+
+/// Where flakes are born in the field of a `size` screen, with text of
+/// `text_size` in the middle of it.
+fn flake_points(
+    config: &SnowConfig,
+    size: (usize, usize),
+    density: &Density,
+    text_size: (usize, usize),
+) -> SpawnPoints {
+    let origin = (
+        size.0.saturating_sub(text_size.0) / 2,
+        size.1.saturating_sub(text_size.1) / 2,
+    );
+    SpawnPoints::new(
+        flake_field(size, config),
+        config.spawn_bias,
+        density,
+        origin,
+    )
+}
+
+/// Lets snow fall down the whole terminal over `lines`, which sit in the
+/// middle of it. Once the duration is up it stops snowing, and the effect
+/// ends when the last flake has fallen, leaving any drifts in place.
+pub fn apply_snow_effect(
+    lines: &[String],
+    config: &SnowConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let columns: Vec<_> = styled.iter().map(|cells| column_starts(cells)).collect();
+    let text_width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+
+    if config.flake_chars.is_empty() {
+        newline()?;
+        return Ok(());
+    }
+
+    let text_color = rgb_color(config.color);
+    let flake_color = rgb_color(config.flake_color);
+    let mut rng = rng::new_rng();
+    let mut size = screen_size();
+    let density = Density::of(lines);
+    let text_size = (text_width, lines.len());
+    let mut points = flake_points(config, size, &density, text_size);
+    let mut governor = Governor::new(config.quality, Duration::from_millis(config.speed));
+    let mut flakes = snowfall(
+        config,
+        flake_field(size, config),
+        &points,
+        governor.level(),
+        &mut rng,
+    );
+    let mut drifts = Drifts::new(size.0);
+
+    let render = |flakes: &Particles, drifts: &Drifts, size: (usize, usize)| {
+        let mut frame = Frame::new(size.0, size.1);
+        let left = size.0.saturating_sub(text_width) / 2;
+        let top = size.1.saturating_sub(styled.len()) / 2;
+        let draw_text = |frame: &mut Frame| {
+            for (row, cells) in styled.iter().enumerate() {
+                for (index, cell) in cells.iter().enumerate() {
+                    if cell.ch != ' ' {
+                        let color = cell.color.unwrap_or(text_color);
+                        frame.put(left + columns[row].0[index], top + row, cell.ch, color);
+                    }
+                }
+            }
+        };
+
+        if !config.behind {
+            draw_text(&mut frame);
+        }
+        flakes.draw(&mut frame, |flake| flake.glyph);
+        if config.behind {
+            draw_text(&mut frame);
+        }
+        for x in 0..size.0 {
+            if let Some(glyph) = drifts.glyph(x) {
+                frame.put(x, size.1 - 1, glyph, flake_color);
+            }
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let step = config.speed.max(1);
+    let _terminal = TerminalGuard::hide_cursor(true)?;
+
+    // Snow always fills the screen from its top-left corner, so there's
+    // no centering to redo on a resize
+    let origin = Some((0, 0));
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0;
+    loop {
+        let started = Instant::now();
+        let spawning = config.duration == 0 || elapsed < config.duration;
+        let landed = flakes.step(step, spawning, governor.level(), || {
+            let (x, y) = (points.column(&mut rng), -rng.gen::<f32>());
+            spawn_flake(&mut rng, config, x, y)
+        });
+        if config.accumulate {
+            drifts.land(&landed);
+        }
+        if !spawning && flakes.particles.is_empty() {
+            break;
+        }
+
+        if resize::track(&mut None)?.is_some() {
+            size = screen_size();
+            points = flake_points(config, size, &density, text_size);
+            flakes = snowfall(
+                config,
+                flake_field(size, config),
+                &points,
+                governor.level(),
+                &mut rng,
+            );
+            drifts = Drifts::new(size.0);
+            execute!(stdout, terminal::Clear(ClearType::All))?;
+        }
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(render(&flakes, &drifts, size), &mut stdout, origin)?;
+        stdout.flush()?;
+        frame_timer.finish();
+        governor.record(started.elapsed());
+
+        playback::sleep(frame_duration)?;
+        elapsed += step;
+    }
+
+    repaint.draw(render(&flakes, &drifts, size), &mut stdout, origin)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_snow_config_default() {
+        let config = SnowConfig::default();
+
+        assert_eq!(config.density, 0.02);
+        assert_eq!(config.wind, 0.0);
+        assert!(!config.accumulate);
+    }
+
+    #[test]
+    fn test_snowfall_starts_spread_out_and_leans_with_the_wind() {
+        let config = SnowConfig {
+            density: 0.1,
+            wind: -0.5,
+            ..SnowConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(3);
+        let points = SpawnPoints::new((40, 20), SpawnBias::Uniform, &Density::of(&[]), (0, 0));
+        let field = snowfall(&config, (40, 20), &points, Quality::High, &mut rng);
+
+        assert_eq!(field.particles.len(), 80);
+        assert!(field.particles.iter().any(|flake| flake.y > 10.0));
+        for flake in &field.particles {
+            assert!(config.flake_chars.contains(&flake.glyph));
+            assert!(flake.velocity.1 > 0.0);
+            assert_eq!(flake.velocity.0, -0.5 * flake.velocity.1);
+        }
+
+        let low = snowfall(&config, (40, 20), &points, Quality::Low, &mut rng);
+        assert_eq!(low.particles.len(), 24);
+    }
+
+    #[test]
+    fn test_drifts_deepen_to_a_full_cell() {
+        let mut drifts = Drifts::new(4);
+        assert_eq!(drifts.glyph(1), None);
+
+        drifts.land(&[1, 1, 3, 7]);
+        assert_eq!(drifts.glyph(1), Some('▂'));
+        assert_eq!(drifts.glyph(3), Some('▁'));
+
+        drifts.land(&[1; 20]);
+        assert_eq!(drifts.glyph(1), Some('█'));
+        assert_eq!(drifts.glyph(0), None);
+    }
+}
@@ -0,0 +1,236 @@
+use super::density::{Density, SpawnBias};
+use super::frame::Frame;
+use super::particles::{Particle, Particles, SpawnPoints};
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::quality::{Governor, Quality};
+use crate::resize::{self, Placement};
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use rand::Rng;
+use std::f32::consts::TAU;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Glyphs a sparkle shows as it flares up and dies away, indexed by how
+/// far through its life it is.
+const LIFE_GLYPHS: [char; 7] = ['·', '+', '*', '✦', '*', '+', '·'];
+
+/// Columns and rows around the text that sparkles can also appear in.
+pub const MARGIN_X: usize = 2;
+pub const MARGIN_Y: usize = 1;
+
+pub struct SparkleConfig {
+    /// Color of the text under the sparkles
+    pub color: (u8, u8, u8),
+    /// Colors sparkles take, each one at random
+    pub colors: Vec<(u8, u8, u8)>,
+    /// Share of cells (0.0 to 1.0) holding a sparkle at any moment
+    pub density: f32,
+    /// Milliseconds a sparkle lives, give or take a quarter
+    pub lifetime: u64,
+    /// Most cells per second a sparkle drifts
+    pub drift: f32,
+    /// Where sparkles are likeliest to appear: among dense glyphs, in the
+    /// open space around them, or anywhere
+    pub spawn_bias: SpawnBias,
+    /// Milliseconds to keep spawning; 0 sparkles forever
+    pub duration: u64,
+    pub speed: u64,
+    pub quality: Quality,
+}
+
+impl Default for SparkleConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            colors: vec![(255, 255, 255), (255, 215, 0), (135, 206, 250)],
+            density: 0.05,
+            lifetime: 800,
+            drift: 1.5,
+            spawn_bias: SpawnBias::Uniform,
+            duration: 3000,
+            speed: 50,
+            quality: Quality::High,
+        }
+    }
+}
+
+/// A short-lived star at one of `points`, drifting in a random direction.
+fn spawn_sparkle(rng: &mut impl Rng, config: &SparkleConfig, points: &SpawnPoints) -> Particle {
+    let heading = rng.gen_range(0.0..TAU);
+    let speed = rng.gen_range(0.0..=config.drift.max(0.0)) / 1000.0;
+    let jitter = config.lifetime / 4;
+    let (x, y) = points.cell(rng);
+    Particle {
+        x,
+        y,
+        velocity: (heading.cos() * speed, heading.sin() * speed),
+        age: 0,
+        lifetime: rng
+            .gen_range(config.lifetime - jitter..=config.lifetime + jitter)
+            .max(1),
+        glyph: LIFE_GLYPHS[0],
+        color: rgb_color(config.colors[rng.gen_range(0..config.colors.len())]),
+    }
+}
+
+/// The glyph of a sparkle `life` of the way (0.0 to 1.0) through its life.
+fn life_glyph(life: f32) -> char {
+    let index = (life.clamp(0.0, 1.0) * LIFE_GLYPHS.len() as f32) as usize;
+    LIFE_GLYPHS[index.min(LIFE_GLYPHS.len() - 1)]
+}
+
+/// A field of sparkles that arrive over one lifetime rather than all at
+/// once, so they don't all die together either.
+fn sparkle_field(config: &SparkleConfig, size: (usize, usize)) -> Particles {
+    let target = Particles::new(size, config.density, 1, false).target();
+    let frames_per_life = (config.lifetime / config.speed.max(1)).max(1) as usize;
+    Particles::new(
+        size,
+        config.density,
+        target.div_ceil(frames_per_life),
+        false,
+    )
+}
+
+/// Scatters short-lived sparkles over and around `lines`, which stay as
+/// they are underneath. Once the duration is up no new sparkles are born,
+/// and the effect ends when the last one dies.
+pub fn apply_sparkle_effect(
+    lines: &[String],
+    config: &SparkleConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let columns: Vec<_> = styled.iter().map(|cells| column_starts(cells)).collect();
+    let width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+
+    if width == 0 || config.colors.is_empty() {
+        newline()?;
+        return Ok(());
+    }
+
+    let size = (width + 2 * MARGIN_X, styled.len() + 2 * MARGIN_Y);
+    let text_color = rgb_color(config.color);
+    let mut sparkles = sparkle_field(config, size);
+    let points = SpawnPoints::new(
+        size,
+        config.spawn_bias,
+        &Density::of(lines),
+        (MARGIN_X, MARGIN_Y),
+    );
+    let mut rng = rng::new_rng();
+
+    let render = |sparkles: &Particles| {
+        let mut frame = Frame::new(size.0, size.1);
+        for (row, cells) in styled.iter().enumerate() {
+            for (index, cell) in cells.iter().enumerate() {
+                let color = cell.color.unwrap_or(text_color);
+                frame.put(
+                    MARGIN_X + columns[row].0[index],
+                    MARGIN_Y + row,
+                    cell.ch,
+                    color,
+                );
+            }
+        }
+        sparkles.draw(&mut frame, |particle| life_glyph(particle.life()));
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let step = config.speed.max(1);
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut governor = Governor::new(config.quality, frame_duration);
+    let mut repaint = Repaint::default();
+    let mut elapsed = 0;
+    loop {
+        let started = Instant::now();
+        let spawning = config.duration == 0 || elapsed < config.duration;
+        sparkles.step(step, spawning, governor.level(), || {
+            spawn_sparkle(&mut rng, config, &points)
+        });
+        if !spawning && sparkles.particles.is_empty() {
+            break;
+        }
+
+        resize::track(&mut centering_offsets)?;
+        let frame_timer = FrameTimer::start();
+        repaint.draw_on_time(render(&sparkles), &mut stdout, centering_offsets)?;
+        stdout.flush()?;
+        frame_timer.finish();
+        governor.record(started.elapsed());
+
+        playback::sleep(frame_duration)?;
+        elapsed += step;
+    }
+
+    repaint.draw(render(&sparkles), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_sparkle_config_default() {
+        let config = SparkleConfig::default();
+
+        assert_eq!(config.density, 0.05);
+        assert_eq!(config.lifetime, 800);
+        assert_eq!(config.colors.len(), 3);
+    }
+
+    #[test]
+    fn test_life_glyph_flares_and_fades() {
+        assert_eq!(life_glyph(0.0), '·');
+        assert_eq!(life_glyph(0.5), '✦');
+        assert_eq!(life_glyph(1.0), '·');
+    }
+
+    #[test]
+    fn test_sparkles_build_up_to_the_density_then_die_out() {
+        let config = SparkleConfig {
+            density: 0.1,
+            ..SparkleConfig::default()
+        };
+        let size = (20, 10);
+        let mut sparkles = sparkle_field(&config, size);
+        let mut rng = StdRng::seed_from_u64(6);
+        assert_eq!(sparkles.target(), 20);
+
+        let points = SpawnPoints::new(size, SpawnBias::Uniform, &Density::of(&[]), (0, 0));
+        let mut spawn = || spawn_sparkle(&mut rng, &config, &points);
+        sparkles.step(50, true, Quality::High, &mut spawn);
+        assert!(sparkles.particles.len() < sparkles.target());
+        for _ in 0..40 {
+            sparkles.step(50, true, Quality::High, &mut spawn);
+        }
+        assert!(sparkles.particles.len() > 10);
+        assert!(sparkles.particles.len() <= 20);
+        assert!(sparkles.particles.iter().all(|particle| particle
+            .velocity
+            .0
+            .hypot(particle.velocity.1)
+            <= 0.0015));
+
+        for _ in 0..40 {
+            sparkles.step(50, false, Quality::High, &mut spawn);
+        }
+        assert!(sparkles.particles.is_empty());
+    }
+}
@@ -0,0 +1,399 @@
+use super::stage::{rgb_color, ColorStage};
+use crate::capability::fg;
+use crate::center::strip_ansi_codes;
+use crate::output::{stdout, Stdout};
+use crate::playback;
+use crate::screen::{self, LineStart, TerminalGuard};
+use crate::soak::FrameTimer;
+use crossterm::{
+    cursor, queue,
+    style::{Color, Print, ResetColor},
+    terminal::{self, ClearType},
+};
+use std::error::Error;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
+
+/// Braille dots chasing each other around the cell.
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Characters on either side of the shine's center it still brightens.
+const SHINE_HALF_WIDTH: f32 = 4.0;
+
+pub struct SpinConfig {
+    /// Text beside the spinner
+    pub label: String,
+    pub color: (u8, u8, u8),
+    /// Color at the center of the shine crossing the label
+    pub highlight: (u8, u8, u8),
+    /// Color the line ends in when the command succeeds
+    pub success_color: (u8, u8, u8),
+    /// Color the line ends in when the command fails
+    pub failure_color: (u8, u8, u8),
+    pub speed: u64,
+    /// Milliseconds the shine takes to cross the label
+    pub period: u64,
+    /// Milliseconds the ending takes to wash over the label
+    pub flourish: u64,
+    pub levels: Option<u32>,
+}
+
+impl Default for SpinConfig {
+    fn default() -> Self {
+        Self {
+            label: String::new(),
+            color: (150, 150, 150),
+            highlight: (255, 255, 255),
+            success_color: (0, 200, 120),
+            failure_color: (230, 60, 60),
+            speed: 80,
+            period: 1500,
+            flourish: 600,
+            levels: None,
+        }
+    }
+}
+
+/// The wrapped command exited unsuccessfully. `clifx` exits with the same
+/// code, so scripts wrapping a command in `spin` see how it went.
+#[derive(Debug)]
+pub struct Failed {
+    pub code: i32,
+}
+
+impl fmt::Display for Failed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the command exited with status {}", self.code)
+    }
+}
+
+impl Error for Failed {}
+
+fn outcome(status: ExitStatus) -> Result<(), Box<dyn Error>> {
+    if status.success() {
+        return Ok(());
+    }
+    // Killed by a signal, there's no code of its own to pass on
+    Err(Failed {
+        code: status.code().unwrap_or(1),
+    }
+    .into())
+}
+
+/// The label with a shine `phase` (0.0 to 1.0) of the way across it.
+fn label_cells(config: &SpinConfig, stage: &mut ColorStage, phase: f32) -> Vec<(char, Color)> {
+    let length = config.label.chars().count() as f32;
+    // The shine enters before the first character and leaves past the last
+    let center = -SHINE_HALF_WIDTH + phase * (length + 2.0 * SHINE_HALF_WIDTH);
+    let base = rgb_color(config.color);
+    let highlight = rgb_color(config.highlight);
+
+    config
+        .label
+        .chars()
+        .enumerate()
+        .map(|(index, ch)| {
+            let distance = (index as f32 + 0.5 - center).abs();
+            let intensity = (1.0 - distance / SHINE_HALF_WIDTH).max(0.0);
+            (ch, stage.shade(base, highlight, intensity))
+        })
+        .collect()
+}
+
+/// The label as the ending washes over it, `progress` (0.0 to 1.0) of the
+/// way: characters behind the bright leading edge settle into `finish`,
+/// and those ahead of it keep the running color.
+fn flourish_cells(
+    config: &SpinConfig,
+    stage: &mut ColorStage,
+    finish: Color,
+    progress: f32,
+) -> Vec<(char, Color)> {
+    let length = config.label.chars().count() as f32;
+    // The edge ends far enough past the label that its glow has left it too
+    let edge = progress * (length + 2.0 * SHINE_HALF_WIDTH);
+    let base = rgb_color(config.color);
+    let highlight = rgb_color(config.highlight);
+
+    config
+        .label
+        .chars()
+        .enumerate()
+        .map(|(index, ch)| {
+            let behind = edge - (index as f32 + 0.5);
+            let color = if behind < 0.0 {
+                base
+            } else {
+                let intensity = (1.0 - behind / SHINE_HALF_WIDTH).max(0.0);
+                stage.shade(finish, highlight, intensity)
+            };
+            (ch, color)
+        })
+        .collect()
+}
+
+/// Rows `line` takes up on a terminal `width` columns wide, once it wraps.
+fn rows_for(line: &str, width: Option<u16>) -> usize {
+    let columns = strip_ansi_codes(line).width();
+    match width {
+        Some(width) if width > 0 => columns.div_ceil(width as usize).max(1),
+        _ => 1,
+    }
+}
+
+/// Reads `pipe` a line at a time onto `sender` until it closes.
+fn spawn_pipe_reader(pipe: impl Read + Send + 'static, sender: mpsc::Sender<String>) {
+    thread::spawn(move || {
+        let mut pipe = BufReader::new(pipe);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match pipe.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim_end_matches('\n').trim_end_matches('\r');
+            if sender.send(text.to_string()).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// The spinner line, drawn where it's resting `rows_below` rows above the
+/// cursor. Leaves the cursor back where it was.
+fn draw_line(
+    stdout: &mut Stdout,
+    mark: (char, Color),
+    cells: &[(char, Color)],
+    suffix: &str,
+    rows_below: usize,
+) -> Result<(), Box<dyn Error>> {
+    let up = rows_below + 1;
+    queue!(stdout, cursor::MoveUp(up as u16), LineStart)?;
+    queue!(stdout, fg(mark.1), Print(mark.0), Print(' '))?;
+    for &(ch, color) in cells {
+        queue!(stdout, fg(color), Print(ch))?;
+    }
+    queue!(
+        stdout,
+        Print(suffix),
+        ResetColor,
+        terminal::Clear(ClearType::UntilNewLine),
+        cursor::MoveDown(up as u16),
+        LineStart
+    )?;
+    Ok(())
+}
+
+/// Runs `command` with a spinner and the label shining on the line above
+/// its output, which streams in beneath as it comes, stdout and stderr
+/// together. When it exits the line settles into the success or failure
+/// color. Unless `animate`, the command runs as it would on its own.
+pub fn run_spin(
+    command: &[String],
+    config: &SpinConfig,
+    animate: bool,
+) -> Result<(), Box<dyn Error>> {
+    let (program, args) = command
+        .split_first()
+        .ok_or("spin needs a command to run, e.g. clifx spin -- make")?;
+    let spawn_error = |err| format!("couldn't run '{program}': {err}");
+    if !animate {
+        let status = Command::new(program)
+            .args(args)
+            .status()
+            .map_err(spawn_error)?;
+        return outcome(status);
+    }
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(spawn_error)?;
+    let (sender, lines): (_, Receiver<String>) = mpsc::channel();
+    if let Some(pipe) = child.stdout.take() {
+        spawn_pipe_reader(pipe, sender.clone());
+    }
+    if let Some(pipe) = child.stderr.take() {
+        spawn_pipe_reader(pipe, sender);
+    }
+    // Ctrl-C reaches the command too; it's the one to decide to stop, and the
+    // line then shows how it went
+    let _ = ctrlc::set_handler(|| {});
+
+    let mut stdout = stdout();
+    let frame_duration = Duration::from_millis(config.speed);
+    let period = config.period.max(1) as f32;
+    let _terminal = TerminalGuard::hide_cursor(false)?;
+
+    let mut stage = ColorStage::new(config.levels);
+    let started = Instant::now();
+    let spinner_color = rgb_color(config.highlight);
+    // The spinner line is the one the cursor is on, and the output goes below it
+    queue!(stdout, Print("\r\n"))?;
+    let mut rows_below = 0;
+    let mut output_open = true;
+
+    let status = loop {
+        let (width, height) = screen::layout_size().unzip();
+        while output_open {
+            match lines.try_recv() {
+                Ok(line) => {
+                    queue!(
+                        stdout,
+                        Print(&line),
+                        ResetColor,
+                        terminal::Clear(ClearType::UntilNewLine),
+                        Print("\r\n")
+                    )?;
+                    rows_below += rows_for(&line, width);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => output_open = false,
+            }
+        }
+        // Output is never held back like a late frame would be
+        stdout.flush()?;
+
+        let status = child.try_wait()?;
+        if let Some(status) = status.filter(|_| !output_open) {
+            break status;
+        }
+
+        // Once output pushes the line off the top, there's nowhere to draw it
+        if height.is_none_or(|height| rows_below + 1 < height as usize) {
+            let elapsed = started.elapsed().as_millis();
+            let frame = (elapsed / config.speed.max(1) as u128) as usize;
+            let phase = (elapsed as f32 % period) / period;
+            let cells = label_cells(config, &mut stage, phase);
+            let frame_timer = FrameTimer::start();
+            draw_line(
+                &mut stdout,
+                (SPINNER[frame % SPINNER.len()], spinner_color),
+                &cells,
+                "",
+                rows_below,
+            )?;
+            stdout.flush_frame()?;
+            frame_timer.finish();
+        }
+
+        playback::sleep(frame_duration)?;
+    };
+
+    let (mark, finish, suffix) = match status.code() {
+        Some(0) => ('✔', config.success_color, String::new()),
+        Some(code) => ('✘', config.failure_color, format!(" (exit {code})")),
+        None => ('✘', config.failure_color, " (killed)".to_string()),
+    };
+    let finish = rgb_color(finish);
+    let height = screen::layout_size().map(|(_, height)| height as usize);
+    if height.is_some_and(|height| rows_below + 1 >= height) {
+        // The line scrolled away, so the ending goes after the output instead
+        queue!(stdout, Print("\r\n"))?;
+        rows_below = 0;
+    }
+
+    let flourish = Duration::from_millis(config.flourish);
+    let ending = Instant::now();
+    loop {
+        let progress = if flourish.is_zero() {
+            1.0
+        } else {
+            (ending.elapsed().as_secs_f32() / flourish.as_secs_f32()).min(1.0)
+        };
+        let cells = flourish_cells(config, &mut stage, finish, progress);
+        let frame_timer = FrameTimer::start();
+        let suffix = if progress >= 1.0 { suffix.as_str() } else { "" };
+        draw_line(&mut stdout, (mark, finish), &cells, suffix, rows_below)?;
+        stdout.flush_frame()?;
+        frame_timer.finish();
+        if progress >= 1.0 {
+            break;
+        }
+        playback::sleep(frame_duration)?;
+    }
+
+    outcome(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(label: &str) -> SpinConfig {
+        SpinConfig {
+            label: label.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_spin_config_default() {
+        let config = SpinConfig::default();
+
+        assert_eq!(config.speed, 80);
+        assert_eq!(config.period, 1500);
+        assert_eq!(config.flourish, 600);
+        assert_eq!(config.levels, None);
+    }
+
+    #[test]
+    fn test_label_shine_crosses_the_label() {
+        let config = config("building everything");
+        let mut stage = ColorStage::new(None);
+        let base = rgb_color(config.color);
+
+        // At the start and end of its cycle the shine is off either edge
+        for phase in [0.0, 1.0] {
+            let cells = label_cells(&config, &mut stage, phase);
+            assert!(cells.iter().all(|&(_, color)| color == base));
+        }
+        let cells = label_cells(&config, &mut stage, 0.5);
+        assert_eq!(cells.len(), 19);
+        assert_ne!(cells[9].1, base);
+        assert_eq!(cells[0].1, base);
+    }
+
+    #[test]
+    fn test_flourish_settles_into_the_finish_color() {
+        let config = config("deploy");
+        let mut stage = ColorStage::new(None);
+        let finish = rgb_color(config.success_color);
+
+        let cells = flourish_cells(&config, &mut stage, finish, 0.0);
+        assert!(cells
+            .iter()
+            .all(|&(_, color)| color == rgb_color(config.color)));
+        let cells = flourish_cells(&config, &mut stage, finish, 1.0);
+        assert!(cells.iter().all(|&(_, color)| color == finish));
+    }
+
+    #[test]
+    fn test_rows_for_counts_wrapped_rows() {
+        assert_eq!(rows_for("", Some(80)), 1);
+        assert_eq!(rows_for(&"x".repeat(80), Some(80)), 1);
+        assert_eq!(rows_for(&"x".repeat(81), Some(80)), 2);
+        assert_eq!(rows_for("\x1b[31mred\x1b[0m", Some(3)), 1);
+        assert_eq!(rows_for(&"x".repeat(500), None), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_outcome_carries_the_exit_code() {
+        use std::os::unix::process::ExitStatusExt;
+
+        assert!(outcome(ExitStatus::from_raw(0)).is_ok());
+        let err = outcome(ExitStatus::from_raw(3 << 8)).unwrap_err();
+        assert_eq!(err.downcast_ref::<Failed>().unwrap().code, 3);
+    }
+}
@@ -0,0 +1,311 @@
+use super::driver::ShineStart;
+use super::frame::Frame;
+use super::pulse::scale;
+use super::repaint::Repaint;
+use super::stage::{blend_colors, rgb_color};
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::easing::EasingFunction;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::rng;
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
+use rand::Rng;
+use std::f32::consts::TAU;
+use std::io::Write;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SpotlightPath {
+    /// Across the text from left to right
+    Lr,
+    /// Round the text once, clockwise from its right-hand end
+    Circle,
+    /// From one random spot on the text to another
+    Random,
+}
+
+pub struct SpotlightConfig {
+    /// Color of the text where the input doesn't color it itself
+    pub color: (u8, u8, u8),
+    /// Columns from the middle of the spotlight to where its light runs out
+    pub radius: f32,
+    /// Brightness of the text outside the spotlight, as a fraction of its color
+    pub dim_factor: f32,
+    pub path: SpotlightPath,
+    pub speed: u64,
+    /// Milliseconds for one sweep along the path
+    pub duration: u64,
+    pub cycles: u32,
+    /// Sweep back the other way every other cycle
+    pub alternate_direction: bool,
+}
+
+impl Default for SpotlightConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            radius: 4.0,
+            dim_factor: 0.3,
+            path: SpotlightPath::Lr,
+            speed: 50,
+            duration: 2000,
+            cycles: 1,
+            alternate_direction: false,
+        }
+    }
+}
+
+/// How lit (0.0 to 1.0) a cell `distance` columns from the middle of the
+/// spotlight is: fully inside half the radius, fading out towards its edge.
+fn spot_intensity(distance: f32, radius: f32) -> f32 {
+    let radius = radius.max(0.5);
+    ((radius - distance) / (radius / 2.0)).clamp(0.0, 1.0)
+}
+
+/// Distance in columns from `(x, y)` to `center`, with rows counted as two
+/// columns so the spotlight looks round rather than squashed.
+fn spot_distance((x, y): (f32, f32), center: (f32, f32)) -> f32 {
+    (x - center.0).hypot((y - center.1) * 2.0)
+}
+
+/// Where the spotlight's middle is `progress` (0.0 to 1.0) through a sweep
+/// over text `size` columns by rows. Random sweeps go along `leg`, from
+/// one spot to the next.
+fn spot_center(
+    config: &SpotlightConfig,
+    progress: f32,
+    size: (usize, usize),
+    leg: ((f32, f32), (f32, f32)),
+) -> (f32, f32) {
+    let (width, height) = (size.0 as f32, size.1 as f32);
+    let middle = ((width - 1.0) / 2.0, (height - 1.0) / 2.0);
+    match config.path {
+        SpotlightPath::Lr => {
+            // Starts and ends just off the text, so it sweeps on and off
+            let start = -config.radius;
+            let end = width - 1.0 + config.radius;
+            (start + (end - start) * progress, middle.1)
+        }
+        SpotlightPath::Circle => {
+            let angle = TAU * progress;
+            (
+                middle.0 + width / 2.0 * angle.cos(),
+                middle.1 + height / 2.0 * angle.sin(),
+            )
+        }
+        SpotlightPath::Random => {
+            let ((from_x, from_y), (to_x, to_y)) = leg;
+            let t = EasingFunction::EaseInOut.apply(progress);
+            (from_x + (to_x - from_x) * t, from_y + (to_y - from_y) * t)
+        }
+    }
+}
+
+/// How far along its path the spotlight is `progress` through zero-based
+/// `cycle`. With `alternate_direction` every odd cycle runs the path
+/// backwards; random paths already go on from wherever the last one ended.
+fn sweep_progress(config: &SpotlightConfig, cycle: usize, progress: f32) -> f32 {
+    let start = ShineStart::Beginning.for_cycle(cycle, config.alternate_direction);
+    match (start, config.path) {
+        (ShineStart::End, SpotlightPath::Lr | SpotlightPath::Circle) => 1.0 - progress,
+        _ => progress,
+    }
+}
+
+/// A random spot on text `size` columns by rows.
+fn random_spot(rng: &mut impl Rng, size: (usize, usize)) -> (f32, f32) {
+    (
+        rng.gen_range(0.0..size.0.max(1) as f32),
+        rng.gen_range(0.0..size.1.max(1) as f32),
+    )
+}
+
+/// Dims `lines` and sweeps a round spotlight over them along the
+/// configured path, lighting the text up to full brightness beneath it.
+/// The whole text comes up to full brightness once the last sweep ends.
+pub fn apply_spotlight_effect(
+    lines: &[String],
+    config: &SpotlightConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let columns: Vec<_> = styled.iter().map(|cells| column_starts(cells)).collect();
+    let width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+
+    if width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    let size = (width, styled.len());
+    let text_color = rgb_color(config.color);
+
+    let render = |center: Option<(f32, f32)>| {
+        let mut frame = Frame::new(size.0, size.1);
+        for (row, cells) in styled.iter().enumerate() {
+            for (index, cell) in cells.iter().enumerate() {
+                let x = columns[row].0[index];
+                let lit = cell.color.unwrap_or(text_color);
+                let color = match center {
+                    Some(center) => {
+                        let distance = spot_distance((x as f32, row as f32), center);
+                        let intensity = spot_intensity(distance, config.radius);
+                        blend_colors(scale(lit, config.dim_factor), lit, intensity)
+                    }
+                    None => lit,
+                };
+                frame.put(x, row, cell.ch, color);
+            }
+        }
+        frame
+    };
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let total_frames = (config.duration / config.speed.max(1)).max(2) as usize;
+    let cycles_to_run = if config.cycles == 0 {
+        usize::MAX
+    } else {
+        config.cycles as usize
+    };
+
+    let mut rng = rng::new_rng();
+    let mut spot = random_spot(&mut rng, size);
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    for cycle in 0..cycles_to_run {
+        let next_spot = random_spot(&mut rng, size);
+        let leg = (spot, next_spot);
+        for frame_index in 0..total_frames {
+            let progress = frame_index as f32 / (total_frames - 1) as f32;
+            let progress = sweep_progress(config, cycle, progress);
+            let center = spot_center(config, progress, size, leg);
+
+            resize::track(&mut centering_offsets)?;
+            let frame_timer = FrameTimer::start();
+            repaint.draw_on_time(render(Some(center)), &mut stdout, centering_offsets)?;
+            stdout.flush()?;
+            frame_timer.finish();
+
+            playback::sleep(frame_duration)?;
+        }
+        spot = next_spot;
+    }
+
+    repaint.draw(render(None), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const NO_LEG: ((f32, f32), (f32, f32)) = ((0.0, 0.0), (0.0, 0.0));
+
+    #[test]
+    fn test_spotlight_config_default() {
+        let config = SpotlightConfig::default();
+
+        assert_eq!(config.radius, 4.0);
+        assert_eq!(config.dim_factor, 0.3);
+        assert_eq!(config.path, SpotlightPath::Lr);
+        assert_eq!(config.cycles, 1);
+    }
+
+    #[test]
+    fn test_spot_intensity_falls_off_towards_the_edge() {
+        assert_eq!(spot_intensity(0.0, 4.0), 1.0);
+        assert_eq!(spot_intensity(2.0, 4.0), 1.0);
+        assert_approx_eq!(spot_intensity(3.0, 4.0), 0.5, 0.001);
+        assert_eq!(spot_intensity(4.0, 4.0), 0.0);
+        assert_eq!(spot_intensity(10.0, 4.0), 0.0);
+    }
+
+    #[test]
+    fn test_spot_distance_counts_rows_as_two_columns() {
+        assert_eq!(spot_distance((5.0, 3.0), (5.0, 2.0)), 2.0);
+        assert_eq!(spot_distance((7.0, 2.0), (5.0, 2.0)), 2.0);
+    }
+
+    #[test]
+    fn test_left_to_right_sweeps_on_and_off_the_text() {
+        let config = SpotlightConfig::default();
+        let size = (20, 3);
+
+        assert_eq!(spot_center(&config, 0.0, size, NO_LEG), (-4.0, 1.0));
+        assert_eq!(spot_center(&config, 1.0, size, NO_LEG), (23.0, 1.0));
+        assert_eq!(spot_center(&config, 0.5, size, NO_LEG), (9.5, 1.0));
+    }
+
+    #[test]
+    fn test_circle_goes_round_the_middle_of_the_text() {
+        let config = SpotlightConfig {
+            path: SpotlightPath::Circle,
+            ..SpotlightConfig::default()
+        };
+        let size = (21, 5);
+
+        let (x, y) = spot_center(&config, 0.0, size, NO_LEG);
+        assert_approx_eq!(x, 20.5, 0.001);
+        assert_approx_eq!(y, 2.0, 0.001);
+        let (x, y) = spot_center(&config, 0.25, size, NO_LEG);
+        assert_approx_eq!(x, 10.0, 0.001);
+        assert_approx_eq!(y, 4.5, 0.001);
+        let (start, end) = (
+            spot_center(&config, 0.0, size, NO_LEG),
+            spot_center(&config, 1.0, size, NO_LEG),
+        );
+        assert_approx_eq!(start.0, end.0, 0.001);
+        assert_approx_eq!(start.1, end.1, 0.001);
+    }
+
+    #[test]
+    fn test_alternate_direction_runs_every_other_sweep_backwards() {
+        let config = SpotlightConfig {
+            alternate_direction: true,
+            ..SpotlightConfig::default()
+        };
+        assert_eq!(sweep_progress(&config, 0, 0.25), 0.25);
+        assert_eq!(sweep_progress(&config, 1, 0.25), 0.75);
+        assert_eq!(sweep_progress(&config, 2, 0.25), 0.25);
+
+        let random = SpotlightConfig {
+            path: SpotlightPath::Random,
+            ..config
+        };
+        assert_eq!(sweep_progress(&random, 1, 0.25), 0.25);
+        assert_eq!(sweep_progress(&SpotlightConfig::default(), 1, 0.25), 0.25);
+    }
+
+    #[test]
+    fn test_random_sweeps_run_between_spots_on_the_text() {
+        let config = SpotlightConfig {
+            path: SpotlightPath::Random,
+            ..SpotlightConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(12);
+        let size = (30, 4);
+        let leg = (random_spot(&mut rng, size), random_spot(&mut rng, size));
+
+        for (progress, spot) in [(0.0, leg.0), (1.0, leg.1)] {
+            let (x, y) = spot_center(&config, progress, size, leg);
+            assert_approx_eq!(x, spot.0, 0.001);
+            assert_approx_eq!(y, spot.1, 0.001);
+        }
+        for spot in [leg.0, leg.1] {
+            assert!((0.0..30.0).contains(&spot.0));
+            assert!((0.0..4.0).contains(&spot.1));
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::str::FromStr;
+
+/// Frames per second of a sprite that doesn't give its own rate.
+const DEFAULT_FPS: f32 = 8.0;
+
+/// A glyph that animates through a sequence of frames, like a spinning
+/// star `+x*x`, looping at its own frame rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sprite {
+    pub frames: Vec<char>,
+    pub fps: f32,
+}
+
+impl Sprite {
+    /// The frame showing `elapsed_ms` after the sprite appeared.
+    pub fn glyph_at(&self, elapsed_ms: u64) -> char {
+        let frame = (elapsed_ms as f32 / 1000.0 * self.fps) as usize;
+        self.frames[frame % self.frames.len()]
+    }
+}
+
+/// Parses `GLYPHS[@FPS]`, e.g. `+x*x@12`. A trailing `@` with no number is
+/// taken as a glyph.
+impl FromStr for Sprite {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (glyphs, fps) = match text.rsplit_once('@') {
+            Some((glyphs, fps)) if !glyphs.is_empty() && !fps.is_empty() => {
+                let fps: f32 = fps
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("sprite '{text}' has an invalid frame rate '{fps}'"))?;
+                if !(fps.is_finite() && fps > 0.0) {
+                    return Err(format!("sprite '{text}' needs a frame rate above 0"));
+                }
+                (glyphs, fps)
+            }
+            _ => (text, DEFAULT_FPS),
+        };
+        let frames: Vec<char> = glyphs.chars().collect();
+        if frames.is_empty() {
+            return Err("a sprite needs at least one glyph".to_string());
+        }
+        Ok(Self { frames, fps })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sprite() {
+        let sprite: Sprite = "+x*x@4".parse().unwrap();
+        assert_eq!(sprite.frames, vec!['+', 'x', '*', 'x']);
+        assert_eq!(sprite.fps, 4.0);
+
+        assert_eq!("✦✧".parse::<Sprite>().unwrap().fps, DEFAULT_FPS);
+        assert_eq!("a@".parse::<Sprite>().unwrap().frames, vec!['a', '@']);
+        assert!("+x@fast".parse::<Sprite>().is_err());
+        assert!("+x@0".parse::<Sprite>().is_err());
+        assert!("".parse::<Sprite>().is_err());
+    }
+
+    #[test]
+    fn test_glyph_loops_at_frame_rate() {
+        let sprite: Sprite = "+x*@4".parse().unwrap();
+        assert_eq!(sprite.glyph_at(0), '+');
+        assert_eq!(sprite.glyph_at(249), '+');
+        assert_eq!(sprite.glyph_at(250), 'x');
+        assert_eq!(sprite.glyph_at(500), '*');
+        assert_eq!(sprite.glyph_at(750), '+');
+    }
+}
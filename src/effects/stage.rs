@@ -0,0 +1,215 @@
+use crossterm::style::Color;
+use std::collections::HashMap;
+
+/// Converts an RGB tuple from a config into a terminal color.
+pub fn rgb_color(rgb: (u8, u8, u8)) -> Color {
+    Color::Rgb {
+        r: rgb.0,
+        g: rgb.1,
+        b: rgb.2,
+    }
+}
+
+pub fn blend_colors(base: Color, shine: Color, intensity: f32) -> Color {
+    let intensity = intensity.clamp(0.0, 1.0);
+
+    let (base_r, base_g, base_b) = match base {
+        Color::Rgb { r, g, b } => (r, g, b),
+        _ => (255, 255, 255),
+    };
+
+    let (shine_r, shine_g, shine_b) = match shine {
+        Color::Rgb { r, g, b } => (r, g, b),
+        _ => (255, 255, 255),
+    };
+
+    let blended_r = (base_r as f32 * (1.0 - intensity) + shine_r as f32 * intensity) as u8;
+    let blended_g = (base_g as f32 * (1.0 - intensity) + shine_g as f32 * intensity) as u8;
+    let blended_b = (base_b as f32 * (1.0 - intensity) + shine_b as f32 * intensity) as u8;
+
+    Color::Rgb {
+        r: blended_r,
+        g: blended_g,
+        b: blended_b,
+    }
+}
+
+/// The shared intensity-to-color stage every effect runs its highlight
+/// through. With `levels` set, intensity snaps to that many discrete steps
+/// (including the base color) and the resulting colors are cached, so a
+/// frame only ever uses a handful of distinct colors.
+pub struct ColorStage {
+    levels: Option<u32>,
+    cache: HashMap<(Color, Color, u32), Color>,
+}
+
+impl ColorStage {
+    pub fn new(levels: Option<u32>) -> Self {
+        Self {
+            levels: levels.map(|n| n.max(2)),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Snaps `intensity` to the nearest level, or passes it through unchanged.
+    pub fn quantize(&self, intensity: f32) -> f32 {
+        match self.levels {
+            Some(levels) => {
+                let steps = (levels - 1) as f32;
+                (intensity.clamp(0.0, 1.0) * steps).round() / steps
+            }
+            None => intensity,
+        }
+    }
+
+    pub fn shade(&mut self, base: Color, highlight: Color, intensity: f32) -> Color {
+        let Some(levels) = self.levels else {
+            return blend_colors(base, highlight, intensity);
+        };
+
+        let steps = levels - 1;
+        let step = (intensity.clamp(0.0, 1.0) * steps as f32).round() as u32;
+        *self
+            .cache
+            .entry((base, highlight, step))
+            .or_insert_with(|| blend_colors(base, highlight, step as f32 / steps as f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    const BLACK: Color = Color::Rgb { r: 0, g: 0, b: 0 };
+    const WHITE: Color = Color::Rgb {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+
+    #[test]
+    fn test_unquantized_stage_blends_smoothly() {
+        let mut stage = ColorStage::new(None);
+        assert_eq!(
+            stage.shade(BLACK, WHITE, 0.5),
+            blend_colors(BLACK, WHITE, 0.5)
+        );
+        assert_approx_eq!(stage.quantize(0.37), 0.37, 0.0001);
+    }
+
+    #[test]
+    fn test_three_levels() {
+        let stage = ColorStage::new(Some(3));
+        assert_eq!(stage.quantize(0.0), 0.0);
+        assert_eq!(stage.quantize(0.2), 0.0);
+        assert_eq!(stage.quantize(0.3), 0.5);
+        assert_eq!(stage.quantize(0.7), 0.5);
+        assert_eq!(stage.quantize(0.8), 1.0);
+    }
+
+    #[test]
+    fn test_quantized_shades_are_discrete_and_cached() {
+        let mut stage = ColorStage::new(Some(3));
+        let shades: std::collections::HashSet<Color> = (0..=100)
+            .map(|i| stage.shade(BLACK, WHITE, i as f32 / 100.0))
+            .collect();
+
+        assert_eq!(shades.len(), 3);
+        assert_eq!(stage.cache.len(), 3);
+        assert!(shades.contains(&BLACK));
+        assert!(shades.contains(&WHITE));
+    }
+
+    #[test]
+    fn test_levels_below_two_are_raised() {
+        let stage = ColorStage::new(Some(1));
+        assert_eq!(stage.quantize(0.4), 0.0);
+        assert_eq!(stage.quantize(0.6), 1.0);
+    }
+
+    #[test]
+    fn test_blend_colors_basic() {
+        let base = Color::Rgb {
+            r: 100,
+            g: 100,
+            b: 100,
+        };
+        let shine = Color::Rgb {
+            r: 200,
+            g: 200,
+            b: 200,
+        };
+
+        // Test no blending (intensity = 0.0)
+        if let Color::Rgb { r, g, b } = blend_colors(base, shine, 0.0) {
+            assert_eq!(r, 100);
+            assert_eq!(g, 100);
+            assert_eq!(b, 100);
+        } else {
+            panic!("Expected RGB color");
+        }
+
+        // Test full blending (intensity = 1.0)
+        if let Color::Rgb { r, g, b } = blend_colors(base, shine, 1.0) {
+            assert_eq!(r, 200);
+            assert_eq!(g, 200);
+            assert_eq!(b, 200);
+        } else {
+            panic!("Expected RGB color");
+        }
+    }
+
+    #[test]
+    fn test_blend_colors_midpoint() {
+        let base = Color::Rgb { r: 0, g: 0, b: 0 };
+        let shine = Color::Rgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+
+        if let Color::Rgb { r, g, b } = blend_colors(base, shine, 0.5) {
+            assert_eq!(r, 127);
+            assert_eq!(g, 127);
+            assert_eq!(b, 127);
+        } else {
+            panic!("Expected RGB color");
+        }
+    }
+
+    #[test]
+    fn test_blend_colors_clamping() {
+        let base = Color::Rgb {
+            r: 100,
+            g: 100,
+            b: 100,
+        };
+        let shine = Color::Rgb {
+            r: 200,
+            g: 200,
+            b: 200,
+        };
+
+        // Test values outside valid range
+        let result_negative = blend_colors(base, shine, -0.5);
+        let result_over_one = blend_colors(base, shine, 1.5);
+
+        // Should clamp to valid range
+        if let Color::Rgb { r, g, b } = result_negative {
+            assert_eq!(r, 100); // Should be same as base (intensity = 0.0)
+            assert_eq!(g, 100);
+            assert_eq!(b, 100);
+        } else {
+            panic!("Expected RGB color");
+        }
+
+        if let Color::Rgb { r, g, b } = result_over_one {
+            assert_eq!(r, 200); // Should be same as shine (intensity = 1.0)
+            assert_eq!(g, 200);
+            assert_eq!(b, 200);
+        } else {
+            panic!("Expected RGB color");
+        }
+    }
+}
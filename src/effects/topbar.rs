@@ -0,0 +1,389 @@
+use super::frame::Frame;
+use super::gradient::color_at;
+use super::stage::{blend_colors, rgb_color};
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
+use crossterm::{
+    cursor, execute,
+    style::{Color, Print},
+    terminal::{self, ClearType},
+};
+use std::f32::consts::TAU;
+use std::io::{self, IsTerminal, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthChar;
+
+/// Set by Ctrl-C, so the scroll region is released instead of left behind.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+const AURORA: [Color; 4] = [
+    Color::Rgb {
+        r: 0,
+        g: 255,
+        b: 140,
+    },
+    Color::Rgb {
+        r: 0,
+        g: 190,
+        b: 255,
+    },
+    Color::Rgb {
+        r: 150,
+        g: 80,
+        b: 255,
+    },
+    Color::Rgb {
+        r: 0,
+        g: 255,
+        b: 140,
+    },
+];
+
+/// Fraction of the bar's width covered by the shine band.
+const SHINE_BAND: f32 = 0.15;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum TopbarEffect {
+    /// Ribbons of green, teal, and violet drifting across the bar
+    Aurora,
+    /// A bright band sweeping from left to right
+    Shine,
+    /// The whole bar breathing between its color and the highlight
+    Pulse,
+}
+
+pub struct TopbarConfig {
+    /// Label shown near the left of the first row
+    pub text: String,
+    /// Rows reserved at the top of the terminal
+    pub rows: u16,
+    pub effect: TopbarEffect,
+    pub color: (u8, u8, u8),
+    pub highlight: (u8, u8, u8),
+    /// Character drawn across the rest of the bar
+    pub fill: char,
+    pub speed: u64,
+    /// Length of one effect cycle in milliseconds
+    pub period: u64,
+}
+
+impl Default for TopbarConfig {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            rows: 1,
+            effect: TopbarEffect::Aurora,
+            color: (100, 100, 100),
+            highlight: (255, 255, 255),
+            fill: '━',
+            speed: 50,
+            period: 4000,
+        }
+    }
+}
+
+/// Color of the cell at `column` of `row` in a bar `width` columns wide, when
+/// the effect is `progress` (0.0 to 1.0) through its cycle.
+fn bar_color(
+    config: &TopbarConfig,
+    column: usize,
+    row: usize,
+    width: usize,
+    progress: f32,
+) -> Color {
+    let x = column as f32 / width.max(1) as f32;
+    match config.effect {
+        TopbarEffect::Aurora => {
+            // Ribbons ripple as they drift, and each row lags the one above
+            let ripple = 0.08 * (TAU * (2.0 * x + progress)).sin();
+            color_at(
+                &AURORA,
+                (x + ripple + 0.1 * row as f32 - progress).rem_euclid(1.0),
+            )
+        }
+        TopbarEffect::Shine => {
+            // The band enters beyond the left edge and leaves past the right
+            let center = -SHINE_BAND + progress * (1.0 + 2.0 * SHINE_BAND);
+            let intensity = (1.0 - (x - center).abs() / SHINE_BAND).max(0.0);
+            blend_colors(
+                rgb_color(config.color),
+                rgb_color(config.highlight),
+                intensity,
+            )
+        }
+        TopbarEffect::Pulse => {
+            let intensity = 0.5 - 0.5 * (TAU * progress).cos();
+            blend_colors(
+                rgb_color(config.color),
+                rgb_color(config.highlight),
+                intensity,
+            )
+        }
+    }
+}
+
+/// The whole bar for one tick: the label on the first row, the fill
+/// character everywhere else.
+fn bar_frame(config: &TopbarConfig, width: usize, progress: f32) -> Frame {
+    let rows = config.rows as usize;
+    let mut frame = Frame::new(width, rows);
+    let label = if config.text.is_empty() {
+        String::new()
+    } else {
+        format!(" {} ", config.text)
+    };
+
+    for row in 0..rows {
+        let lead: Vec<char> = if row == 0 && !label.is_empty() {
+            [config.fill, config.fill]
+                .into_iter()
+                .chain(label.chars())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut column = 0;
+        let fill = std::iter::repeat(config.fill);
+        for ch in lead.into_iter().chain(fill) {
+            let ch_width = ch.width().unwrap_or(0);
+            if ch_width == 0 {
+                continue;
+            }
+            if column + ch_width > width {
+                break;
+            }
+            frame.put(
+                column,
+                row,
+                ch,
+                bar_color(config, column, row, width, progress),
+            );
+            column += ch_width;
+        }
+    }
+
+    frame
+}
+
+/// Restricts scrolling to the rows below the bar (DECSTBM) and parks the
+/// cursor on the first of them.
+fn reserve_rows(out: &mut impl Write, rows: u16, height: u16) -> io::Result<()> {
+    execute!(
+        out,
+        Print(format!("\x1b[{};{}r", rows + 1, height)),
+        cursor::MoveTo(0, rows)
+    )
+}
+
+/// Gives the whole screen back to scrolling when dropped, however the bar
+/// stopped.
+struct ScrollRegion;
+
+impl Drop for ScrollRegion {
+    fn drop(&mut self) {
+        // Resetting the region homes the cursor, so keep it where output left off
+        let _ = execute!(
+            io::stderr(),
+            cursor::SavePosition,
+            Print("\x1b[r"),
+            cursor::RestorePosition
+        );
+    }
+}
+
+/// Copies stdin to stdout byte for byte, taking `lock` around each write so
+/// it never lands in the middle of a bar redraw. The returned flag is set
+/// once stdin closes or stdout goes away.
+fn spawn_passthrough(lock: Arc<Mutex<()>>) -> Arc<AtomicBool> {
+    let finished = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&finished);
+
+    thread::spawn(move || {
+        let mut stdin = io::stdin().lock();
+        let mut stdout = io::stdout();
+        let mut buffer = [0u8; 8192];
+        loop {
+            let count = match stdin.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(count) => count,
+            };
+            let _guard = lock.lock();
+            if stdout.write_all(&buffer[..count]).is_err() || stdout.flush().is_err() {
+                break;
+            }
+        }
+        flag.store(true, Ordering::Relaxed);
+    });
+
+    finished
+}
+
+/// Animates a bar across the top `config.rows` rows of the terminal while
+/// stdin flows through to stdout beneath it, until stdin closes. The bar is
+/// drawn on stderr; when that isn't a terminal there is nothing to decorate
+/// and input is passed through unchanged.
+pub fn run_topbar(config: &TopbarConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tty = io::stderr();
+    if !tty.is_terminal() {
+        io::copy(&mut io::stdin().lock(), &mut io::stdout().lock())?;
+        return Ok(());
+    }
+
+    let (_, mut height) = terminal::size()?;
+    if height <= config.rows + 1 {
+        return Err(format!(
+            "terminal has {height} rows, too few for a {}-row bar",
+            config.rows
+        )
+        .into());
+    }
+
+    // Only one handler can be installed; a second run reuses the first
+    let _ = ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::Relaxed));
+    INTERRUPTED.store(false, Ordering::Relaxed);
+
+    execute!(tty, terminal::Clear(ClearType::All))?;
+    reserve_rows(&mut tty, config.rows, height)?;
+    let _region = ScrollRegion;
+
+    let lock = Arc::new(Mutex::new(()));
+    let finished = spawn_passthrough(Arc::clone(&lock));
+    let frame_duration = Duration::from_millis(config.speed);
+    let period = config.period.max(1) as u128;
+    let started = Instant::now();
+
+    while !finished.load(Ordering::Relaxed) && !INTERRUPTED.load(Ordering::Relaxed) {
+        let (width, rows) = terminal::size()?;
+        let progress = (started.elapsed().as_millis() % period) as f32 / period as f32;
+        let frame = bar_frame(config, width as usize, progress);
+
+        let frame_timer = FrameTimer::start();
+        {
+            let _guard = lock.lock().map_err(|_| "stdin passthrough failed")?;
+            execute!(tty, cursor::SavePosition)?;
+            // A resize drops the old region on some terminals, so set it again
+            if rows != height {
+                height = rows;
+                reserve_rows(&mut tty, config.rows, height)?;
+                execute!(tty, cursor::RestorePosition, cursor::SavePosition)?;
+            }
+            frame.draw(&mut tty, Some((0, 0)), true)?;
+            execute!(tty, cursor::RestorePosition)?;
+            tty.flush()?;
+        }
+        frame_timer.finish();
+
+        thread::sleep(frame_duration);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row_text(config: &TopbarConfig, width: usize) -> String {
+        let mut out = Vec::new();
+        bar_frame(config, width, 0.0)
+            .draw(&mut out, Some((0, 0)), true)
+            .unwrap();
+        crate::center::strip_ansi_codes(&String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_topbar_config_default() {
+        let config = TopbarConfig::default();
+
+        assert_eq!(config.rows, 1);
+        assert_eq!(config.effect, TopbarEffect::Aurora);
+        assert_eq!(config.fill, '━');
+        assert_eq!(config.speed, 50);
+        assert_eq!(config.period, 4000);
+    }
+
+    #[test]
+    fn test_bar_frame_places_label_and_fill() {
+        let config = TopbarConfig {
+            text: "build".to_string(),
+            fill: '-',
+            ..Default::default()
+        };
+        assert_eq!(row_text(&config, 12), "-- build ---");
+
+        // A label wider than the bar is cut off at the edge
+        assert_eq!(row_text(&config, 5), "-- bu");
+    }
+
+    #[test]
+    fn test_bar_frame_fills_every_row() {
+        let config = TopbarConfig {
+            text: "ci".to_string(),
+            rows: 2,
+            fill: '=',
+            ..Default::default()
+        };
+        assert_eq!(row_text(&config, 6), "== ci \r\n======");
+    }
+
+    #[test]
+    fn test_shine_band_sweeps_across() {
+        let config = TopbarConfig {
+            effect: TopbarEffect::Shine,
+            ..Default::default()
+        };
+        let base = rgb_color(config.color);
+        let red = |color: Color| match color {
+            Color::Rgb { r, .. } => r,
+            _ => 0,
+        };
+
+        // Halfway through, the band sits in the middle of the bar
+        let brightest = (0..100)
+            .max_by_key(|&column| red(bar_color(&config, column, 0, 100, 0.5)))
+            .unwrap();
+        assert_eq!(brightest, 50);
+        assert!(red(bar_color(&config, 50, 0, 100, 0.5)) >= 250);
+        assert_eq!(bar_color(&config, 0, 0, 100, 0.5), base);
+        assert_eq!(bar_color(&config, 0, 0, 100, 0.0), base);
+    }
+
+    #[test]
+    fn test_pulse_breathes_through_highlight() {
+        let config = TopbarConfig {
+            effect: TopbarEffect::Pulse,
+            ..Default::default()
+        };
+
+        assert_eq!(bar_color(&config, 3, 0, 10, 0.0), rgb_color(config.color));
+        assert_eq!(
+            bar_color(&config, 3, 0, 10, 0.5),
+            rgb_color(config.highlight)
+        );
+    }
+
+    #[test]
+    fn test_aurora_drifts_over_time() {
+        let config = TopbarConfig::default();
+        assert_ne!(
+            bar_color(&config, 10, 0, 40, 0.0),
+            bar_color(&config, 10, 0, 40, 0.3)
+        );
+        // Each row lags the one above
+        assert_ne!(
+            bar_color(&config, 10, 0, 40, 0.0),
+            bar_color(&config, 10, 1, 40, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_reserve_rows_sets_scroll_region() {
+        let mut out = Vec::new();
+        reserve_rows(&mut out, 2, 40).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "\x1b[3;40r\x1b[3;1H");
+    }
+}
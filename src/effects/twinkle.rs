@@ -1,13 +1,25 @@
+use super::density::SpawnBias;
+use super::sprite::Sprite;
+use super::stage::ColorStage;
+use crate::ansi::{parse_styled, StyledChar};
+use crate::capability::fg;
+use crate::easing::EasingFunction;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::{self, LineStart, TerminalGuard};
+use crate::soak::FrameTimer;
 use crossterm::{
-    cursor, execute,
-    style::{Color, Print, ResetColor, SetForegroundColor},
+    cursor, execute, queue,
+    style::{Color, Print, ResetColor},
     terminal::{self, ClearType},
 };
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::Rng;
+use regex::Regex;
 use std::collections::HashMap;
-use std::io::{self, Write};
-use std::thread;
 use std::time::Duration;
+use unicode_width::UnicodeWidthChar;
 
 pub struct TwinkleConfig {
     pub base_color: (u8, u8, u8),
@@ -21,6 +33,18 @@ pub struct TwinkleConfig {
     pub max_twinkle_count: Option<usize>,
     pub twinkling_percentage: f32,
     pub star_mode: bool,
+    pub target_chars: Vec<char>,
+    pub target_regex: Option<Regex>,
+    pub levels: Option<u32>,
+    /// Fraction of a cycle (0.0 to 1.0) to simulate before the first frame
+    pub phase: f32,
+    /// Twinkle the characters the input colored instead of the target chars
+    pub color_mask: bool,
+    /// Animated glyphs twinkles show instead of the brightness glyphs, each
+    /// twinkle taking one at random
+    pub sprites: Vec<Sprite>,
+    /// Whether new twinkles favor targets in dense or sparse parts of the art
+    pub spawn_bias: SpawnBias,
 }
 
 impl Default for TwinkleConfig {
@@ -37,31 +61,13 @@ impl Default for TwinkleConfig {
             max_twinkle_count: None,
             twinkling_percentage: 0.8,
             star_mode: false,
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub enum EasingFunction {
-    Linear,
-    EaseIn,
-    EaseOut,
-    EaseInOut,
-}
-
-impl EasingFunction {
-    fn apply(&self, t: f32) -> f32 {
-        match self {
-            EasingFunction::Linear => t,
-            EasingFunction::EaseIn => t * t,
-            EasingFunction::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
-            EasingFunction::EaseInOut => {
-                if t < 0.5 {
-                    2.0 * t * t
-                } else {
-                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
-                }
-            }
+            target_chars: vec!['.'],
+            target_regex: None,
+            levels: None,
+            phase: 0.0,
+            color_mask: false,
+            sprites: Vec::new(),
+            spawn_bias: SpawnBias::Uniform,
         }
     }
 }
@@ -71,6 +77,10 @@ struct TwinkleState {
     phase: f32,
     duration: f32,
     pause_duration: f32,
+    /// Index into `TwinkleConfig::sprites` of the sprite this twinkle shows
+    sprite: Option<usize>,
+    /// Frames since the twinkle started, which drive its sprite
+    age: u64,
 }
 
 const TWINKLE_CHARS: &[char] = &['.', '·', '•', '⋅', '∘', '○', '●'];
@@ -117,68 +127,187 @@ fn get_twinkle_char(progress: f32, star_mode: bool) -> char {
     chars[index.min(chars.len() - 1)]
 }
 
-fn blend_colors(base: Color, twinkle: Color, intensity: f32) -> Color {
-    let intensity = intensity.clamp(0.0, 1.0);
-
-    let (base_r, base_g, base_b) = match base {
-        Color::Rgb { r, g, b } => (r, g, b),
-        _ => (255, 255, 255),
+/// Renders `twinkle_char` in exactly `columns` terminal columns so a twinkle
+/// never shifts the rest of the line: narrow glyphs are padded with spaces and
+/// glyphs too wide for the cell fall back to `*`, or to `original` if even
+/// that doesn't fit.
+fn fit_twinkle_char(twinkle_char: char, columns: usize, original: char) -> String {
+    let glyph = if twinkle_char.width().unwrap_or(0) <= columns {
+        twinkle_char
+    } else if columns >= 1 {
+        '*'
+    } else {
+        original
     };
+    let padding = columns.saturating_sub(glyph.width().unwrap_or(0));
+    format!("{glyph}{}", " ".repeat(padding))
+}
 
-    let (twinkle_r, twinkle_g, twinkle_b) = match twinkle {
-        Color::Rgb { r, g, b } => (r, g, b),
-        _ => (255, 255, 255),
-    };
+/// Returns the char indices that should twinkle: every character contained in
+/// `target_chars`, plus every character covered by a `target_regex` match.
+fn find_target_positions(text: &str, config: &TwinkleConfig) -> Vec<usize> {
+    let mut positions: Vec<usize> = text
+        .chars()
+        .enumerate()
+        .filter_map(|(i, ch)| config.target_chars.contains(&ch).then_some(i))
+        .collect();
+
+    if let Some(regex) = &config.target_regex {
+        let char_starts: Vec<usize> = text.char_indices().map(|(byte, _)| byte).collect();
+        for found in regex.find_iter(text) {
+            let first = char_starts.partition_point(|&byte| byte < found.start());
+            let last = char_starts.partition_point(|&byte| byte < found.end());
+            positions.extend(first..last);
+        }
+        positions.sort_unstable();
+        positions.dedup();
+    }
+
+    positions
+}
+
+/// Char indices of the visible, input-colored characters, which become the
+/// targets when input colors are used as the mask.
+fn colored_positions(text_chars: &[StyledChar]) -> Vec<usize> {
+    text_chars
+        .iter()
+        .enumerate()
+        .filter_map(|(i, cell)| (cell.color.is_some() && !cell.ch.is_whitespace()).then_some(i))
+        .collect()
+}
 
-    let blended_r = (base_r as f32 * (1.0 - intensity) + twinkle_r as f32 * intensity) as u8;
-    let blended_g = (base_g as f32 * (1.0 - intensity) + twinkle_g as f32 * intensity) as u8;
-    let blended_b = (base_b as f32 * (1.0 - intensity) + twinkle_b as f32 * intensity) as u8;
+/// Advances every active twinkle by one frame and starts new ones as needed.
+/// New twinkles pick targets in proportion to `weights`, which run parallel
+/// to `target_positions`; without any, every target is equally likely.
+fn step_twinkles(
+    twinkle_states: &mut HashMap<usize, TwinkleState>,
+    rng: &mut impl Rng,
+    config: &TwinkleConfig,
+    target_positions: &[usize],
+    weights: &[f32],
+) {
+    for state in twinkle_states.values_mut() {
+        state.age += 1;
+    }
+
+    // Determine if twinkling should be active this frame
+    let should_twinkle = rng.gen::<f32>() < config.twinkling_percentage;
+
+    if should_twinkle {
+        // Calculate how many targets should be twinkling
+        let twinkle_count =
+            if let (Some(min), Some(max)) = (config.min_twinkle_count, config.max_twinkle_count) {
+                rng.gen_range(min..=max.min(target_positions.len()))
+            } else if let Some(ratio) = config.twinkle_ratio {
+                ((target_positions.len() as f32 * ratio).round() as usize).max(1)
+            } else if let Some(min) = config.min_twinkle_count {
+                min.min(target_positions.len())
+            } else if let Some(max) = config.max_twinkle_count {
+                max.min(target_positions.len())
+            } else {
+                (target_positions.len() as f32 * 0.3).round() as usize
+            };
 
-    Color::Rgb {
-        r: blended_r,
-        g: blended_g,
-        b: blended_b,
+        // Update existing twinkle states
+        twinkle_states.retain(|_, state| {
+            state.phase += 1.0 / state.duration;
+            state.phase <= 1.0
+        });
+
+        // Add new twinkles if we need more
+        let current_twinkles = twinkle_states.len();
+        if current_twinkles < twinkle_count {
+            let (available_positions, available_weights): (Vec<usize>, Vec<f32>) = target_positions
+                .iter()
+                .enumerate()
+                .filter(|&(_, pos)| !twinkle_states.contains_key(pos))
+                .map(|(i, &pos)| (pos, weights.get(i).copied().unwrap_or(1.0)))
+                .unzip();
+            let weighted = (!weights.is_empty())
+                .then(|| WeightedIndex::new(&available_weights).ok())
+                .flatten();
+
+            let new_twinkles_needed = twinkle_count - current_twinkles;
+            for _ in 0..new_twinkles_needed {
+                if !available_positions.is_empty() {
+                    let pick = match &weighted {
+                        Some(weighted) => weighted.sample(rng),
+                        None => rng.gen_range(0..available_positions.len()),
+                    };
+                    let pos = available_positions[pick];
+                    let duration = rng.gen_range(20.0..60.0); // Random duration between 20-60 frames
+                    let pause_duration = rng.gen_range(0.1..0.2); // 10-20% of total duration as pause
+                    let sprite = (!config.sprites.is_empty())
+                        .then(|| rng.gen_range(0..config.sprites.len()));
+                    twinkle_states.insert(
+                        pos,
+                        TwinkleState {
+                            phase: 0.0,
+                            duration,
+                            pause_duration,
+                            sprite,
+                            age: 0,
+                        },
+                    );
+                }
+            }
+        }
     }
 }
 
+/// Twinkles the targets of `text`. `density` gives the density map value of
+/// each char (see `density::density_map`), which `config.spawn_bias` turns
+/// into spawn weights; without it twinkles spawn uniformly.
 pub fn apply_twinkle_effect(
     text: &str,
     config: &TwinkleConfig,
-    centering_offsets: Option<(u16, u16)>,
+    density: Option<&[f32]>,
+    placement: Placement,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut stdout = io::stdout();
-    let text_chars: Vec<char> = text.chars().collect();
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let text_chars = parse_styled(text);
     let text_len = text_chars.len();
 
     if text_len == 0 {
-        println!();
+        newline()?;
         return Ok(());
     }
 
-    // Find all period positions
-    let period_positions: Vec<usize> = text_chars
-        .iter()
-        .enumerate()
-        .filter_map(|(i, &ch)| if ch == '.' { Some(i) } else { None })
-        .collect();
+    let target_positions = if config.color_mask {
+        colored_positions(&text_chars)
+    } else {
+        let plain_text: String = text_chars.iter().map(|cell| cell.ch).collect();
+        find_target_positions(&plain_text, config)
+    };
 
-    if period_positions.is_empty() {
-        // No periods to twinkle, just print the text normally
+    if target_positions.is_empty() {
+        // Nothing to twinkle, just print the text normally
         let base_color = Color::Rgb {
             r: config.base_color.0,
             g: config.base_color.1,
             b: config.base_color.2,
         };
-        execute!(
-            stdout,
-            SetForegroundColor(base_color),
-            Print(text),
-            ResetColor
-        )?;
-        println!();
+        if let Some((top_offset, left_offset)) = centering_offsets {
+            execute!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+        }
+        execute!(stdout, fg(base_color), Print(text), ResetColor)?;
+        newline()?;
         return Ok(());
     }
 
+    let weights: Vec<f32> = match density {
+        Some(density) if config.spawn_bias != SpawnBias::Uniform => target_positions
+            .iter()
+            .map(|&pos| {
+                config
+                    .spawn_bias
+                    .weight(density.get(pos).copied().unwrap_or(0.0))
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
     let frame_duration = Duration::from_millis(config.speed);
     let total_frames = (config.duration / config.speed) as usize;
     let cycles_to_run = if config.cycles == 0 {
@@ -198,110 +327,77 @@ pub fn apply_twinkle_effect(
         g: config.twinkle_color.1,
         b: config.twinkle_color.2,
     };
+    let mut stage = ColorStage::new(config.levels);
 
-    let mut rng = rand::thread_rng();
+    let mut rng = crate::rng::new_rng();
     let mut twinkle_states: HashMap<usize, TwinkleState> = HashMap::new();
 
-    if centering_offsets.is_some() {
-        execute!(
-            stdout,
-            terminal::Clear(ClearType::All),
-            cursor::Hide
-        )?;
-    } else {
-        execute!(
-            stdout,
-            terminal::Clear(ClearType::CurrentLine),
-            cursor::Hide
-        )?;
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+    if centering_offsets.is_none() && screen::may_clear_line() {
+        execute!(stdout, terminal::Clear(ClearType::CurrentLine))?;
+    }
+
+    // Run the simulation ahead without drawing to start partway into a cycle
+    let preroll_frames = (config.phase * total_frames as f32).round() as usize;
+    for _ in 0..preroll_frames {
+        step_twinkles(
+            &mut twinkle_states,
+            &mut rng,
+            config,
+            &target_positions,
+            &weights,
+        );
     }
 
     for cycle in 0..cycles_to_run {
         for _frame in 0..total_frames {
-            // Determine if twinkling should be active this frame
-            let should_twinkle = rng.gen::<f32>() < config.twinkling_percentage;
-
-            if should_twinkle {
-                // Calculate how many periods should be twinkling
-                let twinkle_count = if let (Some(min), Some(max)) =
-                    (config.min_twinkle_count, config.max_twinkle_count)
-                {
-                    rng.gen_range(min..=max.min(period_positions.len()))
-                } else if let Some(ratio) = config.twinkle_ratio {
-                    ((period_positions.len() as f32 * ratio).round() as usize).max(1)
-                } else if let Some(min) = config.min_twinkle_count {
-                    min.min(period_positions.len())
-                } else if let Some(max) = config.max_twinkle_count {
-                    max.min(period_positions.len())
-                } else {
-                    (period_positions.len() as f32 * 0.3).round() as usize
-                };
-
-                // Update existing twinkle states
-                twinkle_states.retain(|_, state| {
-                    state.phase += 1.0 / state.duration;
-                    state.phase <= 1.0
-                });
-
-                // Add new twinkles if we need more
-                let current_twinkles = twinkle_states.len();
-                if current_twinkles < twinkle_count {
-                    let available_positions: Vec<usize> = period_positions
-                        .iter()
-                        .filter(|&&pos| !twinkle_states.contains_key(&pos))
-                        .copied()
-                        .collect();
-
-                    let new_twinkles_needed = twinkle_count - current_twinkles;
-                    for _ in 0..new_twinkles_needed {
-                        if !available_positions.is_empty() {
-                            let pos =
-                                available_positions[rng.gen_range(0..available_positions.len())];
-                            let duration = rng.gen_range(20.0..60.0); // Random duration between 20-60 frames
-                            let pause_duration = rng.gen_range(0.1..0.2); // 10-20% of total duration as pause
-                            twinkle_states.insert(
-                                pos,
-                                TwinkleState {
-                                    phase: 0.0,
-                                    duration,
-                                    pause_duration,
-                                },
-                            );
-                        }
-                    }
-                }
-            }
-
+            step_twinkles(
+                &mut twinkle_states,
+                &mut rng,
+                config,
+                &target_positions,
+                &weights,
+            );
+
+            resize::track(&mut centering_offsets)?;
+            let frame_timer = FrameTimer::start();
             if let Some((top_offset, left_offset)) = centering_offsets {
-                execute!(stdout, cursor::MoveTo(left_offset, top_offset))?;
+                queue!(stdout, cursor::MoveTo(left_offset, top_offset))?;
             } else {
-                execute!(stdout, cursor::MoveToColumn(0))?;
+                queue!(stdout, LineStart)?;
             }
 
-            for (i, &ch) in text_chars.iter().enumerate() {
+            for (i, cell) in text_chars.iter().enumerate() {
+                let base_color = match cell.color {
+                    Some(color) => color,
+                    // Masked out, so leave it in the terminal's own color
+                    None if config.color_mask => Color::Reset,
+                    None => base_color,
+                };
                 if let Some(state) = twinkle_states.get(&i) {
                     let eased_progress = calculate_three_phase_progress(
                         state.phase,
                         state.pause_duration,
                         &config.easing,
                     );
-                    let twinkle_char = get_twinkle_char(eased_progress, config.star_mode);
-                    let color_intensity = eased_progress;
-                    let blended_color = blend_colors(base_color, twinkle_color, color_intensity);
-                    execute!(
-                        stdout,
-                        SetForegroundColor(blended_color),
-                        Print(twinkle_char)
-                    )?;
+                    let color_intensity = stage.quantize(eased_progress);
+                    let glyph = match state.sprite {
+                        Some(sprite) => config.sprites[sprite].glyph_at(state.age * config.speed),
+                        None => get_twinkle_char(color_intensity, config.star_mode),
+                    };
+                    let twinkle_char = fit_twinkle_char(glyph, cell.width(), cell.ch);
+                    let blended_color = stage.shade(base_color, twinkle_color, color_intensity);
+                    queue!(stdout, fg(blended_color), Print(twinkle_char))?;
                 } else {
-                    execute!(stdout, SetForegroundColor(base_color), Print(ch))?;
+                    queue!(stdout, fg(base_color), Print(cell.ch))?;
                 }
             }
 
-            execute!(stdout, ResetColor)?;
-            stdout.flush()?;
+            queue!(stdout, ResetColor)?;
+            stdout.flush_frame()?;
+            frame_timer.finish();
 
-            thread::sleep(frame_duration);
+            playback::sleep(frame_duration)?;
         }
 
         if config.cycles > 0 && cycle + 1 == cycles_to_run {
@@ -309,8 +405,7 @@ pub fn apply_twinkle_effect(
         }
     }
 
-    execute!(stdout, cursor::Show)?;
-    println!();
+    newline()?;
     Ok(())
 }
 
@@ -335,6 +430,8 @@ mod tests {
         assert_eq!(config.max_twinkle_count, None);
         assert_eq!(config.twinkling_percentage, 0.8);
         assert!(!config.star_mode);
+        assert_eq!(config.target_chars, vec!['.']);
+        assert!(config.target_regex.is_none());
     }
 
     #[test]
@@ -351,6 +448,13 @@ mod tests {
             max_twinkle_count: Some(5),
             twinkling_percentage: 0.9,
             star_mode: true,
+            target_chars: vec!['*'],
+            target_regex: None,
+            levels: None,
+            phase: 0.0,
+            color_mask: false,
+            sprites: Vec::new(),
+            spawn_bias: SpawnBias::Uniform,
         };
 
         assert_eq!(config.base_color, (255, 0, 0));
@@ -496,4 +600,102 @@ mod tests {
         };
         assert!(config_star.star_mode);
     }
+
+    #[test]
+    fn test_find_target_positions_default_periods() {
+        let config = TwinkleConfig::default();
+        assert_eq!(find_target_positions("a.b..c", &config), vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn test_find_target_positions_custom_chars() {
+        let config = TwinkleConfig {
+            target_chars: vec!['*', '•'],
+            ..TwinkleConfig::default()
+        };
+        assert_eq!(find_target_positions("*a•b.", &config), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_find_target_positions_regex_uses_char_indices() {
+        let config = TwinkleConfig {
+            target_chars: Vec::new(),
+            target_regex: Some(Regex::new("[★o]+").unwrap()),
+            ..TwinkleConfig::default()
+        };
+        // '★' is multi-byte, so byte offsets must be mapped back to char indices
+        assert_eq!(find_target_positions("★★ foo", &config), vec![0, 1, 4, 5]);
+    }
+
+    #[test]
+    fn test_find_target_positions_chars_and_regex_union() {
+        let config = TwinkleConfig {
+            target_chars: vec!['.'],
+            target_regex: Some(Regex::new("x").unwrap()),
+            ..TwinkleConfig::default()
+        };
+        assert_eq!(find_target_positions("x.x.", &config), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_colored_positions_skip_plain_text_and_spaces() {
+        let cells = parse_styled("a\x1b[31mb c\x1b[0md");
+        assert_eq!(colored_positions(&cells), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_fit_twinkle_char_keeps_cell_width() {
+        // A wide star doesn't fit a one-column dot
+        assert_eq!(fit_twinkle_char('⭐', 1, '.'), "*");
+        // Narrow glyphs are padded to cover a wide target
+        assert_eq!(fit_twinkle_char('•', 2, '。'), "• ");
+        assert_eq!(fit_twinkle_char('⭐', 2, '。'), "⭐");
+        // Zero-width targets are left alone
+        assert_eq!(fit_twinkle_char('•', 0, '\u{301}'), "\u{301}");
+    }
+
+    #[test]
+    fn test_new_twinkles_take_a_sprite_and_age() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let config = TwinkleConfig {
+            twinkling_percentage: 1.0,
+            twinkle_ratio: Some(1.0),
+            sprites: vec!["+x".parse().unwrap(), "ab@2".parse().unwrap()],
+            ..TwinkleConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut states = HashMap::new();
+        step_twinkles(&mut states, &mut rng, &config, &[0, 1, 2], &[]);
+        assert!(!states.is_empty());
+        assert!(states
+            .values()
+            .all(|state| state.sprite.is_some() && state.age == 0));
+
+        let started: Vec<usize> = states.keys().copied().collect();
+        step_twinkles(&mut states, &mut rng, &config, &[0, 1, 2], &[]);
+        assert!(started.iter().all(|pos| states[pos].age == 1));
+    }
+
+    #[test]
+    fn test_weighted_twinkles_favor_heavy_targets() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let config = TwinkleConfig {
+            twinkling_percentage: 1.0,
+            min_twinkle_count: Some(1),
+            max_twinkle_count: Some(1),
+            ..TwinkleConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut heavy = 0;
+        for _ in 0..200 {
+            let mut states = HashMap::new();
+            step_twinkles(&mut states, &mut rng, &config, &[0, 1], &[0.05, 1.0]);
+            heavy += states.contains_key(&1) as usize;
+        }
+        assert!(heavy > 170);
+    }
 }
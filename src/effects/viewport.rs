@@ -0,0 +1,102 @@
+use std::ops::Range;
+
+/// Share of the remaining distance the camera covers each frame. Lower is
+/// smoother but lags further behind a fast band.
+const CAMERA_EASE: f32 = 0.25;
+
+/// A window `width` columns wide onto content that is wider than the
+/// terminal. Each frame it glides toward keeping a focus column centered,
+/// so a line too long to fit scrolls smoothly instead of wrapping.
+pub struct Camera {
+    width: usize,
+    content_width: usize,
+    left: Option<f32>,
+}
+
+impl Camera {
+    pub fn new(width: usize, content_width: usize) -> Self {
+        Self {
+            width,
+            content_width,
+            left: None,
+        }
+    }
+
+    /// Furthest the window can pan before running off the content's end.
+    fn furthest(&self) -> f32 {
+        self.content_width.saturating_sub(self.width) as f32
+    }
+
+    /// Left column that centers `focus`, kept inside the content.
+    fn target(&self, focus: isize) -> f32 {
+        (focus as f32 - self.width as f32 / 2.0).clamp(0.0, self.furthest())
+    }
+
+    /// Eases toward centering `focus` and returns the new left column. The
+    /// first call jumps straight there, and a focus that outruns the easing
+    /// drags the window along so it never leaves the middle half.
+    pub fn follow(&mut self, focus: isize) -> usize {
+        let target = self.target(focus);
+        let left = match self.left {
+            Some(left) => {
+                let quarter = self.width as f32 / 4.0;
+                let focus = focus as f32;
+                let eased = left + (target - left) * CAMERA_EASE;
+                eased
+                    .clamp(focus - 3.0 * quarter, focus - quarter)
+                    .clamp(0.0, self.furthest())
+            }
+            None => target,
+        };
+        self.left = Some(left);
+        left.round() as usize
+    }
+
+    /// Cells (by index, given each cell's starting column and width) that fit
+    /// entirely inside the window starting at `left`. A wide character cut by
+    /// either edge is left out.
+    pub fn visible(&self, cells: &[(usize, usize)], left: usize) -> Range<usize> {
+        let right = left + self.width;
+        let start = cells.partition_point(|&(column, _)| column < left);
+        let end =
+            start + cells[start..].partition_point(|&(column, width)| column + width <= right);
+        start..end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camera_centers_focus_within_content() {
+        let mut camera = Camera::new(10, 100);
+
+        assert_eq!(camera.follow(50), 45);
+        // Never pans past either end of the content
+        assert_eq!(Camera::new(10, 100).follow(2), 0);
+        assert_eq!(Camera::new(10, 100).follow(99), 90);
+    }
+
+    #[test]
+    fn test_camera_eases_toward_focus() {
+        let mut camera = Camera::new(40, 200);
+        assert_eq!(camera.follow(20), 0);
+
+        // A quarter of the way to the new target
+        assert_eq!(camera.follow(24), 1);
+        // Far jumps drag the window to within a quarter of its width
+        assert_eq!(camera.follow(100), 70);
+    }
+
+    #[test]
+    fn test_visible_drops_cut_wide_characters() {
+        let camera = Camera::new(4, 10);
+        // "a漢bc漢" laid out as (column, width)
+        let cells = [(0, 1), (1, 2), (3, 1), (4, 1), (5, 2)];
+
+        assert_eq!(camera.visible(&cells, 0), 0..3);
+        // The window starts inside the first wide character and ends inside the second
+        assert_eq!(camera.visible(&cells, 2), 2..4);
+    }
+}
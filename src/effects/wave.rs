@@ -0,0 +1,168 @@
+use super::driver::with_phase;
+use super::frame::Frame;
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled};
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use std::f32::consts::TAU;
+use std::io::Write;
+use std::time::Duration;
+
+pub struct WaveConfig {
+    pub color: (u8, u8, u8),
+    /// How many rows characters rise above and sink below the baseline
+    pub amplitude: usize,
+    /// Columns from one crest to the next
+    pub wavelength: f32,
+    pub speed: u64,
+    /// Milliseconds for the wave to travel one wavelength
+    pub duration: u64,
+    pub cycles: u32,
+    pub phase: f32,
+}
+
+impl Default for WaveConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            amplitude: 1,
+            wavelength: 12.0,
+            speed: 50,
+            duration: 2000,
+            cycles: 1,
+            phase: 0.0,
+        }
+    }
+}
+
+/// Row (0 at the top) for a character starting at `column` when the wave is
+/// `progress` (0.0 to 1.0) through its cycle. Row `amplitude` is the baseline.
+fn wave_row(column: usize, progress: f32, config: &WaveConfig) -> usize {
+    let angle = TAU * (column as f32 / config.wavelength.max(1.0) - progress);
+    let rise = (config.amplitude as f32 * angle.sin()).round() as isize;
+    (config.amplitude as isize - rise).clamp(0, 2 * config.amplitude as isize) as usize
+}
+
+/// Moves the characters of `text` up and down in a sine wave that travels
+/// along the line, drawing across `2 * amplitude + 1` rows. The text comes
+/// to rest on the middle row.
+pub fn apply_wave_effect(
+    text: &str,
+    config: &WaveConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = stdout();
+    let cells = parse_styled(text);
+    let (columns, width) = column_starts(&cells);
+
+    if width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    let height = 2 * config.amplitude + 1;
+    let base_color = rgb_color(config.color);
+    let mut centering_offsets = placement.offsets();
+
+    let frame_duration = Duration::from_millis(config.speed);
+    let total_frames = (config.duration / config.speed.max(1)).max(1) as usize;
+    let cycles_to_run = if config.cycles == 0 {
+        usize::MAX
+    } else {
+        config.cycles as usize
+    };
+
+    let render = |progress: Option<f32>| {
+        let mut frame = Frame::new(width, height);
+        for (cell, &column) in cells.iter().zip(&columns) {
+            let row = match progress {
+                Some(progress) => wave_row(column, progress, config),
+                None => config.amplitude,
+            };
+            frame.put(column, row, cell.ch, cell.color.unwrap_or(base_color));
+        }
+        frame
+    };
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    for _cycle in 0..cycles_to_run {
+        for frame_index in 0..total_frames {
+            let progress = with_phase(frame_index as f32 / total_frames as f32, config.phase);
+
+            resize::track(&mut centering_offsets)?;
+            let frame_timer = FrameTimer::start();
+            repaint.draw_on_time(render(Some(progress)), &mut stdout, centering_offsets)?;
+            stdout.flush()?;
+            frame_timer.finish();
+
+            playback::sleep(frame_duration)?;
+        }
+    }
+
+    repaint.draw(render(None), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wave_config_default() {
+        let config = WaveConfig::default();
+
+        assert_eq!(config.amplitude, 1);
+        assert_eq!(config.wavelength, 12.0);
+        assert_eq!(config.speed, 50);
+        assert_eq!(config.duration, 2000);
+        assert_eq!(config.cycles, 1);
+    }
+
+    #[test]
+    fn test_wave_row_follows_sine() {
+        let config = WaveConfig {
+            amplitude: 2,
+            wavelength: 8.0,
+            ..Default::default()
+        };
+
+        // Baseline, crest, baseline, trough over one wavelength
+        assert_eq!(wave_row(0, 0.0, &config), 2);
+        assert_eq!(wave_row(2, 0.0, &config), 0);
+        assert_eq!(wave_row(4, 0.0, &config), 2);
+        assert_eq!(wave_row(6, 0.0, &config), 4);
+        assert_eq!(wave_row(8, 0.0, &config), 2);
+    }
+
+    #[test]
+    fn test_wave_travels_along_the_line() {
+        let config = WaveConfig {
+            amplitude: 2,
+            wavelength: 8.0,
+            ..Default::default()
+        };
+
+        // A quarter cycle later, the crest has moved two columns to the right
+        assert_eq!(wave_row(4, 0.25, &config), 0);
+        assert_eq!(wave_row(4, 0.25, &config), wave_row(2, 0.0, &config));
+    }
+
+    #[test]
+    fn test_wave_rows_stay_in_frame() {
+        let config = WaveConfig::default();
+        for column in 0..40 {
+            for step in 0..10 {
+                assert!(wave_row(column, step as f32 / 10.0, &config) <= 2);
+            }
+        }
+    }
+}
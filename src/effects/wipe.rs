@@ -0,0 +1,263 @@
+use super::frame::Frame;
+use super::repaint::Repaint;
+use super::stage::rgb_color;
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::easing::EasingFunction;
+use crate::output::{newline, stdout};
+use crate::playback;
+use crate::resize::{self, Placement};
+use crate::screen::TerminalGuard;
+use crate::soak::FrameTimer;
+use clap::ValueEnum;
+use std::io::Write;
+use std::time::Duration;
+
+/// The glyph the leading band is drawn with.
+const BAND_GLYPH: char = '█';
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum WipeDirection {
+    /// The edge moves from the right side to the left
+    Left,
+    /// The edge moves from the left side to the right
+    Right,
+    /// The edge moves from the bottom to the top
+    Up,
+    /// The edge moves from the top to the bottom
+    Down,
+    /// The edge grows from the middle out to every side
+    CenterOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum WipeMode {
+    /// Reveal the text behind the edge
+    In,
+    /// Hide the text behind the edge
+    Out,
+    /// Reveal the text, then hide it again
+    Inout,
+}
+
+pub struct WipeConfig {
+    pub color: (u8, u8, u8),
+    pub direction: WipeDirection,
+    pub mode: WipeMode,
+    /// Color of a band leading the edge; there's none when unset
+    pub band_color: Option<(u8, u8, u8)>,
+    /// Columns (or rows, wiping up and down) the band spans
+    pub band_width: usize,
+    /// Milliseconds for the edge to cross the text once
+    pub duration: u64,
+    pub speed: u64,
+    pub easing: EasingFunction,
+}
+
+impl Default for WipeConfig {
+    fn default() -> Self {
+        Self {
+            color: (255, 255, 255),
+            direction: WipeDirection::Right,
+            mode: WipeMode::In,
+            band_color: None,
+            band_width: 2,
+            duration: 800,
+            speed: 30,
+            easing: EasingFunction::Linear,
+        }
+    }
+}
+
+/// How many steps the edge has to travel to reach the cell at column `x`
+/// of row `y` in a `size` frame. Wiping from the center, a row counts as
+/// two columns, since cells are about twice as tall as they are wide.
+pub(super) fn distance(
+    x: usize,
+    y: usize,
+    size: (usize, usize),
+    direction: WipeDirection,
+) -> usize {
+    let (width, height) = size;
+    match direction {
+        WipeDirection::Right => x,
+        WipeDirection::Left => width - 1 - x,
+        WipeDirection::Down => y,
+        WipeDirection::Up => height - 1 - y,
+        WipeDirection::CenterOut => {
+            let from_middle = |at: usize, across: usize| (2 * at).abs_diff(across - 1) / 2;
+            from_middle(x, width).max(2 * from_middle(y, height))
+        }
+    }
+}
+
+/// What a cell shows as the edge passes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Reveal {
+    Text,
+    Band,
+    Hidden,
+}
+
+/// What the cell `distance` steps from the start shows when the edge has
+/// come `edge` steps, with the band just behind it. Revealing, the text
+/// shows behind the band; hiding, it shows ahead of it.
+fn reveal(distance: usize, edge: usize, band_width: usize, revealing: bool) -> Reveal {
+    if distance >= edge {
+        if revealing {
+            Reveal::Hidden
+        } else {
+            Reveal::Text
+        }
+    } else if distance + band_width >= edge {
+        Reveal::Band
+    } else if revealing {
+        Reveal::Text
+    } else {
+        Reveal::Hidden
+    }
+}
+
+/// Reveals or hides `lines` behind an edge that crosses them in
+/// `config.direction`, ahead of an optional band of color. As an entrance
+/// the text is left in full; as an exit, only blank rows are.
+pub fn apply_wipe_effect(
+    lines: &[String],
+    config: &WipeConfig,
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut centering_offsets = placement.offsets();
+    let mut stdout = stdout();
+    let styled: Vec<Vec<StyledChar>> = lines.iter().map(|line| parse_styled(line)).collect();
+    let columns: Vec<_> = styled.iter().map(|cells| column_starts(cells)).collect();
+    let width = columns.iter().map(|&(_, width)| width).max().unwrap_or(0);
+
+    if width == 0 {
+        newline()?;
+        return Ok(());
+    }
+
+    let size = (width, styled.len());
+    let band_width = config.band_color.map_or(0, |_| config.band_width.max(1));
+    let band = config.band_color.map(rgb_color);
+    let base_color = rgb_color(config.color);
+    let span = (0..size.1)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| distance(x, y, size, config.direction))
+        .max()
+        .unwrap_or(0)
+        + 1;
+    // Far enough for the band to pass the last cell too
+    let travel = span + band_width;
+
+    let render = |edge: usize, revealing: bool| {
+        let mut frame = Frame::new(width, size.1);
+        if let Some(band) = band {
+            for y in 0..size.1 {
+                for x in 0..width {
+                    let steps = distance(x, y, size, config.direction);
+                    if reveal(steps, edge, band_width, revealing) == Reveal::Band {
+                        frame.put(x, y, BAND_GLYPH, band);
+                    }
+                }
+            }
+        }
+        for (y, cells) in styled.iter().enumerate() {
+            for (cell, &x) in cells.iter().zip(&columns[y].0) {
+                let steps = distance(x, y, size, config.direction);
+                if reveal(steps, edge, band_width, revealing) == Reveal::Text {
+                    frame.put(x, y, cell.ch, cell.color.unwrap_or(base_color));
+                }
+            }
+        }
+        frame
+    };
+
+    let passes: &[bool] = match config.mode {
+        WipeMode::In => &[true],
+        WipeMode::Out => &[false],
+        WipeMode::Inout => &[true, false],
+    };
+    let frame_duration = Duration::from_millis(config.speed);
+    let duration = config.duration.max(1);
+
+    let _terminal = TerminalGuard::hide_cursor(placement.clears_screen())?;
+
+    let mut repaint = Repaint::default();
+    for &revealing in passes {
+        let mut elapsed = 0;
+        while elapsed < duration {
+            let progress = config.easing.apply(elapsed as f32 / duration as f32);
+            let edge = (progress * travel as f32).round() as usize;
+
+            resize::track(&mut centering_offsets)?;
+            let frame_timer = FrameTimer::start();
+            repaint.draw_on_time(render(edge, revealing), &mut stdout, centering_offsets)?;
+            stdout.flush()?;
+            frame_timer.finish();
+
+            playback::sleep(frame_duration)?;
+            elapsed += config.speed.max(1);
+        }
+    }
+
+    let revealed = passes.last() == Some(&true);
+    repaint.draw(render(travel, revealed), &mut stdout, centering_offsets)?;
+    stdout.flush()?;
+
+    newline()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wipe_config_default() {
+        let config = WipeConfig::default();
+
+        assert_eq!(config.direction, WipeDirection::Right);
+        assert_eq!(config.mode, WipeMode::In);
+        assert!(config.band_color.is_none());
+        assert_eq!(config.duration, 800);
+    }
+
+    #[test]
+    fn test_distance_by_direction() {
+        let size = (5, 3);
+        assert_eq!(distance(0, 1, size, WipeDirection::Right), 0);
+        assert_eq!(distance(0, 1, size, WipeDirection::Left), 4);
+        assert_eq!(distance(3, 0, size, WipeDirection::Down), 0);
+        assert_eq!(distance(3, 0, size, WipeDirection::Up), 2);
+
+        // From the middle, a row out is as far as two columns
+        assert_eq!(distance(2, 1, size, WipeDirection::CenterOut), 0);
+        assert_eq!(distance(0, 1, size, WipeDirection::CenterOut), 2);
+        assert_eq!(distance(2, 0, size, WipeDirection::CenterOut), 2);
+        // Both middle columns of an even width start together
+        assert_eq!(distance(1, 0, (4, 1), WipeDirection::CenterOut), 0);
+        assert_eq!(distance(2, 0, (4, 1), WipeDirection::CenterOut), 0);
+    }
+
+    #[test]
+    fn test_reveal_shows_text_behind_the_band() {
+        let shown: Vec<_> = (0..6).map(|steps| reveal(steps, 4, 2, true)).collect();
+        assert_eq!(
+            shown,
+            [
+                Reveal::Text,
+                Reveal::Text,
+                Reveal::Band,
+                Reveal::Band,
+                Reveal::Hidden,
+                Reveal::Hidden
+            ]
+        );
+
+        // Hiding, the text is still ahead of the edge
+        assert_eq!(reveal(5, 4, 2, false), Reveal::Text);
+        assert_eq!(reveal(0, 4, 2, false), Reveal::Hidden);
+        // Without a band the edge is sharp
+        assert_eq!(reveal(3, 4, 0, true), Reveal::Text);
+    }
+}
@@ -0,0 +1,573 @@
+use crate::center::strip_ansi_codes;
+use clap::{Arg, Command};
+use std::any::TypeId;
+use std::ffi::OsString;
+use std::io::{self, BufRead};
+use std::iter::Peekable;
+use std::str::Chars;
+use unicode_width::UnicodeWidthStr;
+
+/// Names an expression can use, for error messages.
+const VARIABLES: &str = "cols, rows, lines, chars, or longest";
+
+/// Flags giving the milliseconds between frames, which can't be less than 1.
+const FRAME_FLAGS: &[&str] = &["speed"];
+
+/// Values numeric flags can refer to, e.g. `--width "cols/10"`. Terminal
+/// size is read up front; stdin is only read if an expression asks about
+/// the input, and is then kept for the effect.
+pub struct Variables {
+    terminal: (u16, u16),
+    input: Option<Vec<String>>,
+    /// Stand in a one-line input instead of reading stdin
+    placeholder: bool,
+    /// `--text`, which is the input in place of stdin
+    text: Option<Vec<String>>,
+    /// Whether stdin follows `text`, for `--append-stdin`
+    append_stdin: bool,
+}
+
+impl Variables {
+    pub fn new() -> Self {
+        Self {
+            terminal: terminal_size(),
+            input: None,
+            placeholder: false,
+            text: None,
+            append_stdin: false,
+        }
+    }
+
+    /// Variables for checking a command line without running it, so
+    /// validating a preset never waits on stdin.
+    pub fn placeholder() -> Self {
+        Self {
+            placeholder: true,
+            ..Self::new()
+        }
+    }
+
+    /// Takes `text` as the input instead of stdin, or ahead of it when
+    /// `append_stdin` is set.
+    pub fn use_text(&mut self, text: Vec<String>, append_stdin: bool) {
+        self.text = Some(text);
+        self.append_stdin = append_stdin;
+    }
+
+    fn input(&mut self) -> Result<&[String], Box<dyn std::error::Error>> {
+        if self.input.is_none() {
+            self.input = Some(if self.placeholder {
+                vec![String::new()]
+            } else {
+                let mut input = self.text.clone().unwrap_or_default();
+                if self.text.is_none() || self.append_stdin {
+                    for line in io::stdin().lock().lines() {
+                        input.push(line?);
+                    }
+                }
+                input
+            });
+        }
+        Ok(self.input.as_deref().unwrap_or_default())
+    }
+
+    fn get(&mut self, name: &str) -> Result<Option<f64>, Box<dyn std::error::Error>> {
+        let value = match name {
+            "cols" => self.terminal.0 as usize,
+            "rows" => self.terminal.1 as usize,
+            "lines" => self.input()?.len(),
+            "chars" => self.input()?.iter().map(|line| line.chars().count()).sum(),
+            "longest" => self
+                .input()?
+                .iter()
+                .map(|line| strip_ansi_codes(line).width())
+                .max()
+                .unwrap_or(0),
+            _ => return Ok(None),
+        };
+        Ok(Some(value as f64))
+    }
+
+    /// The input as read to evaluate an expression, if one needed it.
+    pub fn into_input(self) -> Option<Vec<String>> {
+        self.input.filter(|_| !self.placeholder)
+    }
+}
+
+/// `--virtual-size`, then the terminal's size, then `COLUMNS`/`LINES`,
+/// then 80x24 when there's no terminal to ask.
+fn terminal_size() -> (u16, u16) {
+    crate::screen::size().unwrap_or_else(|_| {
+        let env = |name: &str, default: u16| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        };
+        (env("COLUMNS", 80), env("LINES", 24))
+    })
+}
+
+/// Finds a variable's value, or `None` for a name that isn't one.
+type Lookup<'a> = dyn FnMut(&str) -> Result<Option<f64>, Box<dyn std::error::Error>> + 'a;
+
+/// Evaluates `source`: numbers and variables joined by `+ - * / %`, with
+/// parentheses and unary minus.
+fn evaluate(source: &str, lookup: &mut Lookup) -> Result<f64, Box<dyn std::error::Error>> {
+    let mut parser = Parser {
+        chars: source.chars().peekable(),
+        lookup,
+    };
+    let value = parser.sum()?;
+    parser.skip_spaces();
+    match parser.chars.next() {
+        None => Ok(value),
+        Some(ch) => Err(format!("unexpected '{ch}'").into()),
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    lookup: &'a mut Lookup<'a>,
+}
+
+impl Parser<'_> {
+    fn skip_spaces(&mut self) {
+        while self.chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+    }
+
+    /// Consumes `ch` if it's the next non-space character.
+    fn eat(&mut self, ch: char) -> bool {
+        self.skip_spaces();
+        self.chars.next_if_eq(&ch).is_some()
+    }
+
+    fn sum(&mut self) -> Result<f64, Box<dyn std::error::Error>> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat('+') {
+                value += self.product()?;
+            } else if self.eat('-') {
+                value -= self.product()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<f64, Box<dyn std::error::Error>> {
+        let mut value = self.unary()?;
+        loop {
+            let op = ['*', '/', '%'].into_iter().find(|&op| self.eat(op));
+            let Some(op) = op else {
+                return Ok(value);
+            };
+            let rhs = self.unary()?;
+            if op != '*' && rhs == 0.0 {
+                return Err("division by zero".into());
+            }
+            match op {
+                '*' => value *= rhs,
+                '/' => value /= rhs,
+                _ => value %= rhs,
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64, Box<dyn std::error::Error>> {
+        if self.eat('-') {
+            return Ok(-self.unary()?);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<f64, Box<dyn std::error::Error>> {
+        if self.eat('(') {
+            let value = self.sum()?;
+            if !self.eat(')') {
+                return Err("missing ')'".into());
+            }
+            return Ok(value);
+        }
+
+        self.skip_spaces();
+        let mut token = String::new();
+        while let Some(ch) = self
+            .chars
+            .next_if(|ch| ch.is_ascii_alphanumeric() || *ch == '.' || *ch == '_')
+        {
+            token.push(ch);
+        }
+
+        if token.is_empty() {
+            return Err(match self.chars.peek() {
+                Some(ch) => format!("unexpected '{ch}'").into(),
+                None => "unexpected end".into(),
+            });
+        }
+        if token.starts_with(|ch: char| ch.is_ascii_digit() || ch == '.') {
+            return token
+                .parse()
+                .map_err(|_| format!("invalid number '{token}'").into());
+        }
+        (self.lookup)(&token)?
+            .ok_or_else(|| format!("unknown variable '{token}' (use {VARIABLES})").into())
+    }
+}
+
+/// Whether `value` for a flag needs to be a whole number, or `None` if the
+/// flag isn't numeric.
+fn integer_flag(arg: &clap::Arg) -> Option<bool> {
+    let id = arg.get_value_parser().type_id();
+    let is = |types: &[TypeId]| types.iter().any(|&ty| id == ty);
+    if is(&[TypeId::of::<f32>(), TypeId::of::<f64>()]) {
+        Some(false)
+    } else if is(&[
+        TypeId::of::<u8>(),
+        TypeId::of::<u16>(),
+        TypeId::of::<u32>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<usize>(),
+        TypeId::of::<i32>(),
+        TypeId::of::<i64>(),
+    ]) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// `value` for `arg`, evaluated if it's an expression given to a numeric
+/// flag. Plain numbers and non-numeric flags pass through for clap to check.
+fn resolve_value(
+    arg: &clap::Arg,
+    value: &str,
+    variables: &mut Variables,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let Some(integer) = integer_flag(arg) else {
+        return Ok(value.to_string());
+    };
+    if value.trim().parse::<f64>().is_ok() {
+        return Ok(value.to_string());
+    }
+
+    let name = arg.get_long().unwrap_or_else(|| arg.get_id().as_str());
+    let result = evaluate(value, &mut |variable| variables.get(variable))
+        .map_err(|err| format!("invalid expression '{value}' for --{name}: {err}"))?;
+    if FRAME_FLAGS.contains(&name) && result.round() < 1.0 {
+        return Err(format!("--{name} must be at least 1, but '{value}' is {result}").into());
+    }
+    Ok(if integer {
+        format!("{}", result.round())
+    } else {
+        format!("{result}")
+    })
+}
+
+/// The flag `--name` of `command`, if it takes a value.
+fn value_flag(command: &Command, name: &str) -> Option<Arg> {
+    command
+        .get_arguments()
+        .find(|arg| arg.get_long() == Some(name))
+        .filter(|arg| arg.get_action().takes_values())
+        .cloned()
+}
+
+/// How many of `args` are clifx's own: all of them, unless they end in the
+/// command `spin` runs, which starts after a `--` or at spin's first
+/// argument that isn't a flag. Its arguments are that command's business,
+/// whatever flags they look like.
+pub fn own_args_len(root: &Command, args: &[OsString]) -> usize {
+    let mut subcommand: Option<&Command> = None;
+    let mut index = 1;
+    while index < args.len() {
+        let text = args[index].to_string_lossy();
+        let spin = subcommand.is_some_and(|command| command.get_name() == "spin");
+        if spin && (text == "--" || !text.starts_with('-')) {
+            return index;
+        }
+
+        if let Some(name) = text.strip_prefix("--") {
+            let takes_value = subcommand
+                .and_then(|command| value_flag(command, name))
+                .or_else(|| value_flag(root, name))
+                .is_some();
+            // Its value is the next argument, unless it came after `=`
+            if takes_value {
+                index += 1;
+            }
+        } else if subcommand.is_none() && !text.starts_with('-') {
+            subcommand = root.find_subcommand(text.as_ref());
+            if subcommand.is_none() {
+                break;
+            }
+        }
+        index += 1;
+    }
+    args.len()
+}
+
+/// `args` with every expression given to a numeric flag of `root` replaced
+/// by its value. Preset commands are left alone; their stored flags are
+/// resolved when they run, and so is the command `spin` runs.
+pub fn resolve_args(
+    root: &Command,
+    args: &[OsString],
+    variables: &mut Variables,
+) -> Result<Vec<OsString>, Box<dyn std::error::Error>> {
+    let (own, command) = args.split_at(own_args_len(root, args));
+    let mut resolved = Vec::with_capacity(args.len());
+    let mut subcommand: Option<&Command> = None;
+    let mut rest = own.iter().enumerate();
+    while let Some((index, arg)) = rest.next() {
+        let text = match arg.to_str() {
+            Some(text) if index > 0 && text != "--" => text,
+            _ => {
+                resolved.push(arg.clone());
+                continue;
+            }
+        };
+
+        if let Some(flag) = text.strip_prefix("--") {
+            let (name, inline) = match flag.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (flag, None),
+            };
+            let found = subcommand
+                .and_then(|command| value_flag(command, name))
+                .or_else(|| value_flag(root, name));
+            match (found, inline) {
+                (Some(found), Some(value)) => {
+                    let value = resolve_value(&found, value, variables)?;
+                    resolved.push(format!("--{name}={value}").into());
+                }
+                (Some(found), None) => {
+                    match rest.next().map(|(_, value)| (value, value.to_str())) {
+                        Some((_, Some(value))) => {
+                            let result = resolve_value(&found, value, variables)?;
+                            // A negative result on its own would be taken for a flag
+                            if result != value && result.starts_with('-') {
+                                resolved.push(format!("--{name}={result}").into());
+                            } else {
+                                resolved.push(arg.clone());
+                                resolved.push(result.into());
+                            }
+                        }
+                        Some((value, None)) => resolved.extend([arg.clone(), value.clone()]),
+                        None => resolved.push(arg.clone()),
+                    }
+                }
+                (None, _) => resolved.push(arg.clone()),
+            }
+            continue;
+        }
+
+        resolved.push(arg.clone());
+        if subcommand.is_none() && !text.starts_with('-') {
+            match root.find_subcommand(text) {
+                Some(command) if command.get_name() != "preset" => subcommand = Some(command),
+                // A preset name or `preset` itself: nothing here to resolve
+                _ => {
+                    resolved.extend(rest.by_ref().map(|(_, arg)| arg.clone()));
+                    break;
+                }
+            }
+        }
+    }
+    resolved.extend_from_slice(command);
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ArgAction;
+
+    fn eval(source: &str) -> Result<f64, Box<dyn std::error::Error>> {
+        evaluate(source, &mut |name| {
+            Ok(match name {
+                "cols" => Some(120.0),
+                "lines" => Some(3.0),
+                _ => None,
+            })
+        })
+    }
+
+    #[test]
+    fn test_evaluate_precedence_and_parentheses() {
+        assert_eq!(eval("1 + 2 * 3").unwrap(), 7.0);
+        assert_eq!(eval("(1 + 2) * 3").unwrap(), 9.0);
+        assert_eq!(eval("10 - 4 - 3").unwrap(), 3.0);
+        assert_eq!(eval("-2 * -3").unwrap(), 6.0);
+        assert_eq!(eval("7 % 4").unwrap(), 3.0);
+        assert_eq!(eval("0.5*4").unwrap(), 2.0);
+    }
+
+    #[test]
+    fn test_evaluate_variables() {
+        assert_eq!(eval("cols/10").unwrap(), 12.0);
+        assert_eq!(eval("lines * 100 + 50").unwrap(), 350.0);
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        assert!(eval("width/2")
+            .unwrap_err()
+            .to_string()
+            .contains("unknown variable"));
+        assert!(eval("cols/0")
+            .unwrap_err()
+            .to_string()
+            .contains("division by zero"));
+        assert!(eval("(1 + 2").is_err());
+        assert!(eval("1 +").is_err());
+        assert!(eval("2 3").is_err());
+    }
+
+    fn command() -> Command {
+        Command::new("clifx")
+            .arg(
+                Arg::new("phase")
+                    .long("phase")
+                    .global(true)
+                    .value_parser(clap::value_parser!(f32)),
+            )
+            .subcommand(
+                Command::new("shine")
+                    .arg(
+                        Arg::new("width")
+                            .long("width")
+                            .value_parser(clap::value_parser!(usize)),
+                    )
+                    .arg(
+                        Arg::new("speed")
+                            .long("speed")
+                            .value_parser(clap::value_parser!(u64)),
+                    )
+                    .arg(Arg::new("color").long("color"))
+                    .arg(
+                        Arg::new("reflect")
+                            .long("reflect")
+                            .action(ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(Command::new("preset"))
+            .subcommand(
+                Command::new("spin")
+                    .arg(Arg::new("text").long("text"))
+                    .arg(Arg::new("run").num_args(1..).trailing_var_arg(true)),
+            )
+    }
+
+    fn try_resolve(args: &[&str]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+        let mut variables = Variables {
+            terminal: (100, 40),
+            input: None,
+            placeholder: true,
+            text: None,
+            append_stdin: false,
+        };
+        let resolved = resolve_args(&command(), &args, &mut variables)?;
+        Ok(resolved
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect())
+    }
+
+    fn resolve(args: &[&str]) -> Vec<String> {
+        try_resolve(args).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_args_evaluates_numeric_flags() {
+        assert_eq!(
+            resolve(&[
+                "clifx",
+                "--phase",
+                "1/4",
+                "shine",
+                "--width=cols/10",
+                "--reflect"
+            ]),
+            vec![
+                "clifx",
+                "--phase",
+                "0.25",
+                "shine",
+                "--width=10",
+                "--reflect"
+            ]
+        );
+        // Whole-number flags are rounded
+        assert_eq!(
+            resolve(&["clifx", "shine", "--width", "rows/3"]),
+            vec!["clifx", "shine", "--width", "13"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_args_checks_results() {
+        // Zero or fewer milliseconds between frames is never a speed
+        let err = try_resolve(&["clifx", "shine", "--speed", "cols/1000"]).unwrap_err();
+        assert!(
+            err.to_string().contains("--speed must be at least 1"),
+            "{err}"
+        );
+        let err = try_resolve(&["clifx", "shine", "--speed=0-5"]).unwrap_err();
+        assert!(
+            err.to_string().contains("--speed must be at least 1"),
+            "{err}"
+        );
+        // Other negative results stay attached to their flag
+        assert_eq!(
+            resolve(&["clifx", "--phase", "0-1/4", "shine"]),
+            vec!["clifx", "--phase=-0.25", "shine"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_args_leaves_other_values_alone() {
+        assert_eq!(
+            resolve(&["clifx", "shine", "--color", "cols", "--width", "4"]),
+            vec!["clifx", "shine", "--color", "cols", "--width", "4"]
+        );
+        assert_eq!(
+            resolve(&["clifx", "preset", "save", "wide", "shine", "--width", "cols"]),
+            vec!["clifx", "preset", "save", "wide", "shine", "--width", "cols"]
+        );
+    }
+
+    #[test]
+    fn test_own_args_stop_at_the_command_spin_runs() {
+        let own = |args: &[&str]| {
+            let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+            own_args_len(&command(), &args)
+        };
+        assert_eq!(own(&["clifx", "shine", "--width", "4"]), 4);
+        assert_eq!(own(&["clifx", "spin", "--", "grep", "--file=x"]), 2);
+        assert_eq!(own(&["clifx", "spin", "--text", "hi", "grep", "--file"]), 4);
+        assert_eq!(
+            own(&["clifx", "--phase", "1", "spin", "--text=hi", "ls"]),
+            5
+        );
+
+        // What spin runs keeps its flags as they were typed
+        assert_eq!(
+            resolve(&["clifx", "spin", "--", "grep", "--width", "cols"]),
+            vec!["clifx", "spin", "--", "grep", "--width", "cols"]
+        );
+    }
+
+    #[test]
+    fn test_text_stands_in_for_stdin() {
+        let mut variables = Variables::new();
+        variables.use_text(vec!["one".into(), "three".into()], false);
+        assert_eq!(variables.get("lines").unwrap(), Some(2.0));
+        assert_eq!(variables.get("longest").unwrap(), Some(5.0));
+        assert_eq!(
+            variables.into_input(),
+            Some(vec!["one".to_string(), "three".to_string()])
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use crate::capability::{self, ColorMode};
+use crate::playback;
+use crate::quality::{self, Quality};
+use clap::ValueEnum;
+use std::io::{self, IsTerminal};
+
+/// What was scaled back from the full experience and why, one line each.
+/// `animated` is false when the text was written once at rest instead.
+fn notes(
+    color: Option<String>,
+    terminal: bool,
+    animated: bool,
+    skipped: u64,
+    quality: Option<Quality>,
+) -> Vec<String> {
+    let mut notes: Vec<String> = color.into_iter().collect();
+    if !animated {
+        notes.push(
+            "stdout isn't a terminal, so the text was written once in the effect's \
+             resting colors; --force-animation animates it anyway"
+                .to_string(),
+        );
+    } else if !terminal {
+        notes.push(
+            "stdout isn't a terminal, so frames are written whole, none are skipped, \
+             and keyboard controls are off; run it in a terminal to animate in place"
+                .to_string(),
+        );
+    }
+    if skipped > 0 {
+        notes.push(format!(
+            "{skipped} frames were skipped because drawing fell behind; \
+             a lower --fps or a higher --speed draws every frame"
+        ));
+    }
+    if let Some(name) = quality.and_then(|quality| quality.to_possible_value()) {
+        notes.push(format!(
+            "--quality auto stepped detail down to {} because frames took longer \
+             than their budget",
+            name.get_name()
+        ));
+    }
+    notes
+}
+
+/// Prints what `--explain-fallbacks` reports to stderr, after the effect
+/// has run, so frames skipped along the way are counted.
+pub fn report(color_mode: ColorMode, animated: bool) {
+    let notes = notes(
+        capability::color_fallback(color_mode),
+        io::stdout().is_terminal(),
+        animated,
+        playback::skipped_frames(),
+        quality::auto_lowest(),
+    );
+    if notes.is_empty() {
+        eprintln!("fallbacks: none, nothing was scaled back");
+    }
+    for note in notes {
+        eprintln!("fallbacks: {note}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notes_cover_each_fallback() {
+        assert!(notes(None, true, true, 0, None).is_empty());
+
+        let written_once = notes(None, false, false, 0, None);
+        assert_eq!(written_once.len(), 1);
+        assert!(written_once[0].contains("--force-animation"));
+
+        let notes = notes(
+            Some("colors reduced".to_string()),
+            false,
+            true,
+            3,
+            Some(Quality::Low),
+        );
+        assert_eq!(notes.len(), 4);
+        assert_eq!(notes[0], "colors reduced");
+        assert!(notes[1].contains("isn't a terminal"));
+        assert!(notes[2].starts_with("3 frames were skipped"));
+        assert!(notes[3].contains("down to low"));
+    }
+}
@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Horizontal layout bits from the FIGfont 2 spec
+const SMUSH_EQUAL: u32 = 1;
+const SMUSH_LOWLINE: u32 = 2;
+const SMUSH_HIERARCHY: u32 = 4;
+const SMUSH_PAIR: u32 = 8;
+const SMUSH_BIG_X: u32 = 16;
+const SMUSH_HARDBLANK: u32 = 32;
+const KERN: u32 = 64;
+const SMUSH: u32 = 128;
+
+/// Characters every font defines, in file order: printable ASCII followed by
+/// the seven Deutsch characters.
+const REQUIRED_CODES: [u32; 102] = {
+    let mut codes = [0; 102];
+    let mut i = 0;
+    while i < 95 {
+        codes[i] = 32 + i as u32;
+        i += 1;
+    }
+    let deutsch = [196, 214, 220, 228, 246, 252, 223];
+    while i < 102 {
+        codes[i] = deutsch[i - 95];
+        i += 1;
+    }
+    codes
+};
+
+/// Block letters covering printable ASCII, so banners work without a font file.
+const BUILTIN: &str = include_str!("fonts/block.flf");
+
+type Glyph = Vec<Vec<char>>;
+
+/// A FIGlet font loaded from an `.flf` file, rendering text as banners with
+/// the font's horizontal layout (full width, kerning, or smushing).
+pub struct FigFont {
+    hardblank: char,
+    height: usize,
+    layout: u32,
+    right_to_left: bool,
+    glyphs: HashMap<char, Glyph>,
+}
+
+impl FigFont {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let bytes = fs::read(path)
+            .map_err(|err| format!("cannot read font file {}: {err}", path.display()))?;
+        // Older fonts are Latin-1, which maps byte for byte onto chars
+        let source = String::from_utf8(bytes)
+            .unwrap_or_else(|err| err.into_bytes().iter().map(|&b| b as char).collect());
+
+        Self::parse(&source).map_err(|err| format!("{}: {err}", path.display()).into())
+    }
+
+    /// The font compiled into clifx, used by `--big`.
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN).expect("the built-in font parses")
+    }
+
+    pub fn parse(source: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut lines = source.lines();
+        let header = lines.next().ok_or("font file is empty")?;
+        let rest = header
+            .strip_prefix("flf2a")
+            .ok_or("not a FIGlet font (missing flf2a signature)")?;
+        let mut rest = rest.chars();
+        let hardblank = rest.next().ok_or("font header has no hardblank")?;
+
+        let fields = rest
+            .as_str()
+            .split_whitespace()
+            .take(7)
+            .map(|field| field.parse::<i64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| "malformed font header")?;
+        if fields.len() < 5 {
+            return Err("font header is missing fields".into());
+        }
+
+        let height = usize::try_from(fields[0])
+            .ok()
+            .filter(|&height| height > 0)
+            .ok_or("font height must be positive")?;
+        let comment_lines = usize::try_from(fields[4]).map_err(|_| "bad comment line count")?;
+        let right_to_left = fields.get(5) == Some(&1);
+        // The full layout, when present, supersedes the old single number
+        let layout = match fields.get(6) {
+            Some(&full_layout) => full_layout as u32 & 0xff,
+            None => match fields[3] {
+                old if old < 0 => 0,
+                0 => KERN,
+                old => SMUSH | (old as u32 & 63),
+            },
+        };
+
+        let mut lines = lines.skip(comment_lines);
+        let mut glyphs = HashMap::new();
+
+        for code in REQUIRED_CODES {
+            // Some fonts stop early; whatever they do define is still usable
+            let Some(glyph) = read_glyph(&mut lines, height)? else {
+                break;
+            };
+            if let Some(ch) = char::from_u32(code) {
+                glyphs.insert(ch, glyph);
+            }
+        }
+
+        while let Some(tag) = lines.next() {
+            let Some(code) = tag.split_whitespace().next().and_then(parse_code) else {
+                continue;
+            };
+            let glyph = read_glyph(&mut lines, height)?
+                .ok_or_else(|| format!("missing glyph for character code {code}"))?;
+            // Negative codes name glyphs figlet only uses for translation tables
+            if let Some(ch) = u32::try_from(code).ok().and_then(char::from_u32) {
+                glyphs.insert(ch, glyph);
+            }
+        }
+
+        Ok(Self {
+            hardblank,
+            height,
+            layout,
+            right_to_left,
+            glyphs,
+        })
+    }
+
+    /// Renders `text` as `height` rows. Characters the font lacks are skipped.
+    pub fn render(&self, text: &str) -> Vec<String> {
+        let mut chars: Vec<char> = text.chars().collect();
+        if self.right_to_left {
+            chars.reverse();
+        }
+
+        let mut rows: Vec<Vec<char>> = vec![Vec::new(); self.height];
+        let mut previous_width = 0;
+
+        for ch in chars {
+            let Some(glyph) = self.glyphs.get(&ch) else {
+                continue;
+            };
+            let width = glyph[0].len();
+            let amount = self.overlap(&rows, glyph, previous_width, width);
+
+            for (row, glyph_row) in rows.iter_mut().zip(glyph) {
+                let len = row.len();
+                for (k, &right) in glyph_row.iter().enumerate().take(amount) {
+                    // Overlap reaching past the start only ever covers blanks
+                    let Some(column) = (len + k).checked_sub(amount) else {
+                        continue;
+                    };
+                    row[column] = self
+                        .smush(row[column], right, previous_width, width)
+                        .unwrap_or(right);
+                }
+                row.extend(&glyph_row[amount..]);
+            }
+
+            previous_width = width;
+        }
+
+        rows.into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|ch| if ch == self.hardblank { ' ' } else { ch })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// How many columns the next glyph can slide left into the output,
+    /// limited by the tightest row.
+    fn overlap(&self, rows: &[Vec<char>], glyph: &Glyph, left_width: usize, width: usize) -> usize {
+        if self.layout & (SMUSH | KERN) == 0 {
+            return 0;
+        }
+
+        rows.iter()
+            .zip(glyph)
+            .map(|(row, glyph_row)| {
+                let glyph_start = glyph_row
+                    .iter()
+                    .position(|&ch| ch != ' ')
+                    .unwrap_or(glyph_row.len());
+
+                match row.iter().rposition(|&ch| ch != ' ') {
+                    None => glyph_start + row.len(),
+                    Some(line_end) => {
+                        let touching = glyph_start + row.len() - 1 - line_end;
+                        let merges = glyph_row.get(glyph_start).is_some_and(|&right| {
+                            self.smush(row[line_end], right, left_width, width)
+                                .is_some()
+                        });
+                        touching + merges as usize
+                    }
+                }
+            })
+            .fold(width, usize::min)
+    }
+
+    /// The character that results from overlapping `left` and `right`, or
+    /// `None` when the layout rules keep them apart.
+    fn smush(
+        &self,
+        left: char,
+        right: char,
+        left_width: usize,
+        right_width: usize,
+    ) -> Option<char> {
+        if left == ' ' {
+            return Some(right);
+        }
+        if right == ' ' {
+            return Some(left);
+        }
+        // Smushing one-column glyphs would make them vanish
+        if left_width < 2 || right_width < 2 || self.layout & SMUSH == 0 {
+            return None;
+        }
+
+        let hardblank = self.hardblank;
+        if self.layout & 63 == 0 {
+            // Universal smushing: the later character simply overwrites
+            return Some(if left == hardblank {
+                right
+            } else if right == hardblank || self.right_to_left {
+                left
+            } else {
+                right
+            });
+        }
+
+        if self.layout & SMUSH_HARDBLANK != 0 && left == hardblank && right == hardblank {
+            return Some(left);
+        }
+        if left == hardblank || right == hardblank {
+            return None;
+        }
+
+        if self.layout & SMUSH_EQUAL != 0 && left == right {
+            return Some(left);
+        }
+
+        if self.layout & SMUSH_LOWLINE != 0 {
+            const REPLACES_LOWLINE: &str = "|/\\[]{}()<>";
+            if left == '_' && REPLACES_LOWLINE.contains(right) {
+                return Some(right);
+            }
+            if right == '_' && REPLACES_LOWLINE.contains(left) {
+                return Some(left);
+            }
+        }
+
+        if self.layout & SMUSH_HIERARCHY != 0 {
+            const CLASSES: [&str; 6] = ["|", "/\\", "[]", "{}", "()", "<>"];
+            let class = |ch: char| CLASSES.iter().position(|class| class.contains(ch));
+            if let (Some(left_class), Some(right_class)) = (class(left), class(right)) {
+                if left_class != right_class {
+                    return Some(if left_class > right_class {
+                        left
+                    } else {
+                        right
+                    });
+                }
+            }
+        }
+
+        if self.layout & SMUSH_PAIR != 0 {
+            if let ('[', ']') | (']', '[') | ('{', '}') | ('}', '{') | ('(', ')') | (')', '(') =
+                (left, right)
+            {
+                return Some('|');
+            }
+        }
+
+        if self.layout & SMUSH_BIG_X != 0 {
+            match (left, right) {
+                ('/', '\\') => return Some('|'),
+                ('\\', '/') => return Some('Y'),
+                ('>', '<') => return Some('X'),
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+/// Reads one glyph of `height` rows, removing the end marks. Returns `None` at
+/// the end of the file.
+fn read_glyph<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    height: usize,
+) -> Result<Option<Glyph>, Box<dyn std::error::Error>> {
+    let mut glyph = Vec::with_capacity(height);
+    for row in 0..height {
+        let Some(line) = lines.next() else {
+            if row == 0 {
+                return Ok(None);
+            }
+            return Err("font file ends in the middle of a character".into());
+        };
+
+        let line = line.trim_end();
+        let row: Vec<char> = match line.chars().last() {
+            Some(end_mark) => line.trim_end_matches(end_mark).chars().collect(),
+            None => Vec::new(),
+        };
+        glyph.push(row);
+    }
+
+    // Rows should already agree, but a ragged glyph would break alignment
+    let width = glyph.iter().map(Vec::len).max().unwrap_or(0);
+    for row in &mut glyph {
+        row.resize(width, ' ');
+    }
+
+    Ok(Some(glyph))
+}
+
+/// Parses a code tag's character code: decimal, `0x` hex, or `0` octal.
+fn parse_code(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+
+    let value = if let Some(hex) = digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        i64::from_str_radix(&digits[1..], 8).ok()?
+    } else {
+        digits.parse().ok()?
+    };
+
+    Some(if negative { -value } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Source for a two-row font in which every required character is drawn
+    /// as itself twice, except for the glyphs given in `overrides`.
+    fn font_source(old_layout: i64, overrides: &[(char, [&str; 2])]) -> String {
+        let mut source = format!("flf2a$ 2 2 8 {old_layout} 1\nTest font\n");
+        for code in REQUIRED_CODES {
+            let ch = char::from_u32(code).unwrap();
+            let rows = match overrides.iter().find(|(c, _)| *c == ch) {
+                Some((_, rows)) => [rows[0].to_string(), rows[1].to_string()],
+                None => [format!("{ch}{ch}"), format!("{ch}{ch}")],
+            };
+            source.push_str(&format!("{}@\n{}@@\n", rows[0], rows[1]));
+        }
+        source
+    }
+
+    fn font(old_layout: i64, overrides: &[(char, [&str; 2])]) -> FigFont {
+        FigFont::parse(&font_source(old_layout, overrides)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_header_and_layout() {
+        assert_eq!(font(-1, &[]).layout, 0);
+        assert_eq!(font(0, &[]).layout, KERN);
+        assert_eq!(font(15, &[]).layout, SMUSH | 15);
+
+        let font = font(0, &[]);
+        assert_eq!(font.hardblank, '$');
+        assert_eq!(font.height, 2);
+        assert_eq!(font.glyphs.len(), REQUIRED_CODES.len());
+    }
+
+    #[test]
+    fn test_parse_rejects_non_fonts() {
+        assert!(FigFont::parse("").is_err());
+        assert!(FigFont::parse("hello world").is_err());
+        assert!(FigFont::parse("flf2a$ 2 2").is_err());
+        assert!(FigFont::parse("flf2a$ 2 2 8 0 0\nab@\n").is_err());
+    }
+
+    #[test]
+    fn test_code_tagged_characters() {
+        let mut source = font_source(0, &[]);
+        source.push_str("0x263A smiley\n:)@\n:(@@\n-2\nxx@\nxx@@\n");
+        let font = FigFont::parse(&source).unwrap();
+
+        assert_eq!(font.render("☺"), vec![":)", ":("]);
+        assert_eq!(font.glyphs.len(), REQUIRED_CODES.len() + 1);
+        assert_eq!(parse_code("0x41"), Some(65));
+        assert_eq!(parse_code("0101"), Some(65));
+        assert_eq!(parse_code("-5"), Some(-5));
+    }
+
+    #[test]
+    fn test_full_width_keeps_every_column() {
+        let font = font(-1, &[('A', [" A ", "A A"]), ('B', ["B  ", "BB "])]);
+        assert_eq!(font.render("AB"), vec![" A B  ", "A ABB "]);
+    }
+
+    #[test]
+    fn test_kerning_closes_gaps_without_overlapping() {
+        let font = font(0, &[('A', [" A ", "A A"]), ('B', ["  B", " BB"])]);
+        // The glyphs slide together until the bottom row touches
+        assert_eq!(font.render("AB"), vec![" A  B", "A ABB"]);
+    }
+
+    #[test]
+    fn test_equal_character_smushing() {
+        let font = font(SMUSH_EQUAL as i64, &[('A', ["|| ", "|| "])]);
+        assert_eq!(font.render("AA"), vec!["||| ", "||| "]);
+    }
+
+    #[test]
+    fn test_smushing_rules() {
+        let font = font(63, &[]);
+        assert_eq!(font.smush('_', '/', 2, 2), Some('/'));
+        assert_eq!(font.smush('|', '}', 2, 2), Some('}'));
+        assert_eq!(font.smush('(', ')', 2, 2), Some('|'));
+        assert_eq!(font.smush('\\', '/', 2, 2), Some('Y'));
+        assert_eq!(font.smush('>', '<', 2, 2), Some('X'));
+        assert_eq!(font.smush('$', '$', 2, 2), Some('$'));
+        assert_eq!(font.smush('$', 'a', 2, 2), None);
+        assert_eq!(font.smush('a', 'b', 2, 2), None);
+        // Single-column glyphs never smush
+        assert_eq!(font.smush('|', '|', 1, 2), None);
+    }
+
+    #[test]
+    fn test_universal_smushing_overwrites() {
+        let font = font(0, &[]);
+        let universal = FigFont {
+            layout: SMUSH,
+            ..font
+        };
+        assert_eq!(universal.smush('a', 'b', 2, 2), Some('b'));
+        assert_eq!(universal.smush('a', '$', 2, 2), Some('a'));
+    }
+
+    #[test]
+    fn test_hardblanks_render_as_spaces_and_block_kerning() {
+        let font = font(0, &[('A', ["A$", "A$"])]);
+        assert_eq!(font.render("AA"), vec!["A A ", "A A "]);
+    }
+
+    #[test]
+    fn test_builtin_font_covers_printable_ascii() {
+        let font = FigFont::builtin();
+        assert!((' '..='~').all(|ch| font.glyphs.contains_key(&ch)));
+
+        let rows = font.render("Hi!");
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0], "█   █ ███ █ ");
+        assert_eq!(font.render("hi!"), rows);
+    }
+
+    #[test]
+    fn test_unknown_characters_are_skipped() {
+        let font = font(-1, &[]);
+        assert_eq!(font.render("a漢b"), vec!["aabb", "aabb"]);
+    }
+}
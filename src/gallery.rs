@@ -0,0 +1,215 @@
+use crate::throughput::{write_static, StaticStyle};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::{
+    cursor, execute, queue,
+    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::io::{self, IsTerminal, Write};
+
+/// Shown along the bottom while browsing.
+const HELP: &str = "arrows/jk scroll · space/b page · g/G top/bottom · q quits";
+
+/// One look in the gallery: how to ask for it, and how it renders at rest.
+pub struct Look {
+    pub label: String,
+    pub style: StaticStyle,
+}
+
+/// `lines` shown once per look, each under its label and followed by a
+/// blank row, as plain text rows ready to print or page through.
+pub fn render(lines: &[String], looks: &[Look]) -> io::Result<Vec<String>> {
+    let mut rows = Vec::new();
+    for look in looks {
+        let mut label = Vec::new();
+        queue!(
+            label,
+            SetAttribute(Attribute::Bold),
+            Print(&look.label),
+            SetAttribute(Attribute::Reset)
+        )?;
+        rows.push(String::from_utf8_lossy(&label).into_owned());
+        for line in lines {
+            let mut row = Vec::new();
+            write_static(&mut row, line, &look.style, "")?;
+            rows.push(String::from_utf8_lossy(&row).into_owned());
+        }
+        rows.push(String::new());
+    }
+    Ok(rows)
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Continue,
+    Done,
+}
+
+/// A window of `height` rows scrolled to `offset` over `len` rows.
+struct Pager {
+    offset: usize,
+    height: usize,
+    len: usize,
+}
+
+impl Pager {
+    fn last_offset(&self) -> usize {
+        self.len.saturating_sub(self.height)
+    }
+
+    fn scroll_by(&mut self, rows: isize) {
+        self.offset = self
+            .offset
+            .saturating_add_signed(rows)
+            .min(self.last_offset());
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        if key.kind != KeyEventKind::Press {
+            return Outcome::Continue;
+        }
+        let page = self.height.max(1) as isize;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => return Outcome::Done,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Outcome::Done
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_by(-1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_by(page),
+            KeyCode::PageUp | KeyCode::Char('b') => self.scroll_by(-page),
+            KeyCode::Home | KeyCode::Char('g') => self.offset = 0,
+            KeyCode::End | KeyCode::Char('G') => self.offset = self.last_offset(),
+            _ => {}
+        }
+        Outcome::Continue
+    }
+}
+
+/// Raw mode and the alternate screen, so browsing leaves the scrollback
+/// as it was.
+struct PagerScreen;
+
+impl PagerScreen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for PagerScreen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn draw(out: &mut impl Write, rows: &[String], pager: &Pager) -> io::Result<()> {
+    queue!(out, terminal::Clear(ClearType::All))?;
+    for (y, row) in rows
+        .iter()
+        .skip(pager.offset)
+        .take(pager.height)
+        .enumerate()
+    {
+        queue!(out, cursor::MoveTo(0, y as u16), Print(row))?;
+    }
+    let status = format!(
+        "{}-{} of {} · {HELP}",
+        (pager.offset + 1).min(pager.len),
+        (pager.offset + pager.height).min(pager.len),
+        pager.len
+    );
+    queue!(
+        out,
+        cursor::MoveTo(0, pager.height as u16),
+        SetAttribute(Attribute::Reverse),
+        Print(status),
+        SetAttribute(Attribute::Reset)
+    )?;
+    out.flush()
+}
+
+/// Pages through `rows` on a terminal, or prints them all when stdout is
+/// piped, so the gallery can be saved or searched too.
+pub fn show(rows: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = io::stdout();
+    if !stdout.is_terminal() {
+        for row in rows {
+            writeln!(stdout, "{row}")?;
+        }
+        return Ok(());
+    }
+
+    let (_, terminal_height) = terminal::size()?;
+    let mut pager = Pager {
+        offset: 0,
+        // The last row holds the status line
+        height: (terminal_height as usize).saturating_sub(1).max(1),
+        len: rows.len(),
+    };
+    let _screen = PagerScreen::enter()?;
+    loop {
+        draw(&mut stdout, rows, &pager)?;
+        match event::read()? {
+            Event::Key(key) if pager.handle_key(key) == Outcome::Done => return Ok(()),
+            Event::Resize(_, height) => {
+                pager.height = (height as usize).saturating_sub(1).max(1);
+                pager.scroll_by(0);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::center::strip_ansi_codes;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_render_labels_each_look() {
+        let looks = vec![
+            Look {
+                label: "one".to_string(),
+                style: StaticStyle::Solid((255, 0, 0)),
+            },
+            Look {
+                label: "two".to_string(),
+                style: StaticStyle::Gradient(vec![(0, 0, 255), (0, 255, 0)]),
+            },
+        ];
+        let rows = render(&["Hi".to_string()], &looks).unwrap();
+
+        assert_eq!(rows.len(), 6);
+        assert!(rows[0].contains("one"));
+        assert_eq!(strip_ansi_codes(&rows[1]), "Hi");
+        assert_eq!(rows[2], "");
+        assert!(rows[3].contains("two"));
+    }
+
+    #[test]
+    fn test_pager_stays_within_the_rows() {
+        let mut pager = Pager {
+            offset: 0,
+            height: 10,
+            len: 25,
+        };
+        pager.handle_key(press(KeyCode::Up));
+        assert_eq!(pager.offset, 0);
+        pager.handle_key(press(KeyCode::Char(' ')));
+        assert_eq!(pager.offset, 10);
+        pager.handle_key(press(KeyCode::PageDown));
+        assert_eq!(pager.offset, 15);
+        pager.handle_key(press(KeyCode::Char('k')));
+        assert_eq!(pager.offset, 14);
+        pager.handle_key(press(KeyCode::Char('g')));
+        assert_eq!(pager.offset, 0);
+        assert_eq!(pager.handle_key(press(KeyCode::Char('q'))), Outcome::Done);
+    }
+}
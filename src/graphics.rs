@@ -0,0 +1,652 @@
+use crate::ansi::parse_styled;
+use crate::vt::{Cell, Screen};
+use clap::ValueEnum;
+use crossterm::style::Color;
+use crossterm::terminal;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+type Rgb = (u8, u8, u8);
+
+/// Cell size in pixels when the terminal doesn't report one.
+const FALLBACK_CELL: (usize, usize) = (10, 20);
+
+/// Color of text drawn without one of its own, as the ANSI backend's
+/// terminal would likely show it.
+const DEFAULT_FOREGROUND: Rgb = (229, 229, 229);
+
+/// The most colors a Sixel image may define; terminals commonly cap it here.
+const SIXEL_COLORS: usize = 256;
+
+/// Base64-encoded bytes per Kitty graphics escape, the most the protocol allows.
+const KITTY_CHUNK: usize = 4096;
+
+/// Thresholds for dithering partly covered pixels where Sixel can't blend.
+const BAYER: [f32; 16] = [
+    0.0, 8.0, 2.0, 10.0, 12.0, 4.0, 14.0, 6.0, 3.0, 11.0, 1.0, 9.0, 15.0, 7.0, 13.0, 5.0,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Backend {
+    /// Characters and escape codes, which every terminal draws
+    Ansi,
+    /// Sixel images (xterm -ti vt340, foot, mlterm, WezTerm, and others)
+    Sixel,
+    /// The Kitty graphics protocol (kitty, WezTerm, Ghostty)
+    Kitty,
+    /// Kitty or Sixel when the terminal looks like it speaks one and the text is all block characters, otherwise ANSI
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Sixel,
+    Kitty,
+}
+
+/// The graphics protocol `var` (a lookup of environment variables) says
+/// the terminal speaks, if any.
+fn detect(var: impl Fn(&str) -> Option<String>) -> Option<Protocol> {
+    let term = var("TERM").unwrap_or_default();
+    let program = var("TERM_PROGRAM").unwrap_or_default();
+    if var("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || matches!(program.as_str(), "WezTerm" | "ghostty")
+    {
+        return Some(Protocol::Kitty);
+    }
+    if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") {
+        return Some(Protocol::Sixel);
+    }
+    None
+}
+
+/// How much of a cell's area (from 0.0 to 1.0) `ch` covers at `(u, v)`,
+/// measured across and down the cell from 0.0 to 1.0. `None` for
+/// characters that aren't block elements.
+fn coverage(ch: char, u: f32, v: f32) -> Option<f32> {
+    let filled = |inside: bool| if inside { 1.0 } else { 0.0 };
+    let (left, top) = (u < 0.5, v < 0.5);
+    let quadrants = |upper_left, upper_right, lower_left, lower_right| {
+        filled(match (left, top) {
+            (true, true) => upper_left,
+            (false, true) => upper_right,
+            (true, false) => lower_left,
+            (false, false) => lower_right,
+        })
+    };
+    let code = ch as u32;
+    Some(match ch {
+        ' ' => 0.0,
+        '▀' => filled(top),
+        '▁'..='▇' => filled(v >= 1.0 - (code - 0x2580) as f32 / 8.0),
+        '█' => 1.0,
+        '▉'..='▏' => filled(u < (0x2590 - code) as f32 / 8.0),
+        '▐' => filled(!left),
+        '░' => 0.25,
+        '▒' => 0.5,
+        '▓' => 0.75,
+        '▔' => filled(v < 1.0 / 8.0),
+        '▕' => filled(u >= 7.0 / 8.0),
+        '▖' => quadrants(false, false, true, false),
+        '▗' => quadrants(false, false, false, true),
+        '▘' => quadrants(true, false, false, false),
+        '▙' => quadrants(true, false, true, true),
+        '▚' => quadrants(true, false, false, true),
+        '▛' => quadrants(true, true, true, false),
+        '▜' => quadrants(true, true, false, true),
+        '▝' => quadrants(false, true, false, false),
+        '▞' => quadrants(false, true, true, false),
+        '▟' => quadrants(false, true, true, true),
+        _ => return None,
+    })
+}
+
+/// Coverage for any character: block elements as they're shaped, and
+/// anything else an effect adds (sparkles, confetti) as a dot in the middle
+/// of its cell, so it still shows.
+fn glyph_coverage(ch: char, u: f32, v: f32) -> f32 {
+    coverage(ch, u, v).unwrap_or_else(|| {
+        let dot = |at: f32| (1.0 / 3.0..2.0 / 3.0).contains(&at);
+        if dot(u) && dot(v) {
+            1.0
+        } else {
+            0.0
+        }
+    })
+}
+
+impl Backend {
+    /// The graphics protocol to draw `lines` with, or `None` to draw them
+    /// as text. Only block characters can be drawn as pixels, so other
+    /// text keeps `auto` on ANSI and is an error for the others.
+    pub fn resolve(self, lines: &[String]) -> Result<Option<Protocol>, Box<dyn std::error::Error>> {
+        let drawable = lines.iter().all(|line| {
+            parse_styled(line)
+                .iter()
+                .all(|cell| coverage(cell.ch, 0.0, 0.0).is_some())
+        });
+        let protocol = match self {
+            Backend::Ansi => return Ok(None),
+            Backend::Auto => {
+                let on_terminal = io::stdout().is_terminal();
+                return Ok(detect(|name| env::var(name).ok()).filter(|_| drawable && on_terminal));
+            }
+            Backend::Sixel => Protocol::Sixel,
+            Backend::Kitty => Protocol::Kitty,
+        };
+        if !drawable {
+            return Err(format!(
+                "--backend {} draws block characters only (e.g. --big banners), but the input has other text",
+                self.to_possible_value().map_or_else(String::new, |value| value.get_name().to_string())
+            )
+            .into());
+        }
+        Ok(Some(protocol))
+    }
+}
+
+/// One frame as pixels, left to right and top to bottom.
+struct Image {
+    width: usize,
+    height: usize,
+    /// Each pixel's color and how much of it is covered
+    pixels: Vec<(Rgb, f32)>,
+}
+
+fn cell_color(cell: &Cell) -> Option<Rgb> {
+    match *cell {
+        Some((' ', _)) => None,
+        Some((_, Some(Color::Rgb { r, g, b }))) => Some((r, g, b)),
+        Some(_) => Some(DEFAULT_FOREGROUND),
+        None => None,
+    }
+}
+
+/// Draws `rows` at `cell` pixels per character. Each pixel's color is
+/// blended from the middles of the nearest drawn cells, so colors that
+/// step from one character to the next run smoothly across it instead.
+fn render(rows: &[Vec<Cell>], width: usize, (cell_width, cell_height): (usize, usize)) -> Image {
+    let (image_width, image_height) = (width * cell_width, rows.len() * cell_height);
+    let color_at = |x: isize, y: isize| {
+        let row = usize::try_from(y).ok().and_then(|y| rows.get(y))?;
+        cell_color(usize::try_from(x).ok().and_then(|x| row.get(x))?)
+    };
+
+    let mut pixels = Vec::with_capacity(image_width * image_height);
+    for py in 0..image_height {
+        let (y, v) = (
+            py / cell_height,
+            (py % cell_height) as f32 / cell_height as f32,
+        );
+        for px in 0..image_width {
+            let (x, u) = (
+                px / cell_width,
+                (px % cell_width) as f32 / cell_width as f32,
+            );
+            let Some((ch, _)) = rows[y].get(x).copied().flatten() else {
+                pixels.push(((0, 0, 0), 0.0));
+                continue;
+            };
+            let covered = glyph_coverage(ch, u, v);
+            if covered == 0.0 {
+                pixels.push(((0, 0, 0), 0.0));
+                continue;
+            }
+
+            // Position among cell middles, and the four middles around it
+            let gx = (px as f32 + 0.5) / cell_width as f32 - 0.5;
+            let gy = (py as f32 + 0.5) / cell_height as f32 - 0.5;
+            let (x0, y0) = (gx.floor(), gy.floor());
+            let (tx, ty) = (gx - x0, gy - y0);
+            let mut weighted = [0.0f32; 3];
+            let mut total = 0.0;
+            for (dx, dy, weight) in [
+                (0, 0, (1.0 - tx) * (1.0 - ty)),
+                (1, 0, tx * (1.0 - ty)),
+                (0, 1, (1.0 - tx) * ty),
+                (1, 1, tx * ty),
+            ] {
+                if let Some((r, g, b)) = color_at(x0 as isize + dx, y0 as isize + dy) {
+                    weighted[0] += r as f32 * weight;
+                    weighted[1] += g as f32 * weight;
+                    weighted[2] += b as f32 * weight;
+                    total += weight;
+                }
+            }
+            let color = if total > 0.0 {
+                let channel = |sum: f32| (sum / total).round() as u8;
+                (
+                    channel(weighted[0]),
+                    channel(weighted[1]),
+                    channel(weighted[2]),
+                )
+            } else {
+                cell_color(&rows[y][x]).unwrap_or(DEFAULT_FOREGROUND)
+            };
+            pixels.push((color, covered));
+        }
+    }
+    Image {
+        width: image_width,
+        height: image_height,
+        pixels,
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// The image as Kitty graphics escapes: transmitted as RGBA under one id,
+/// so each frame replaces the last, and placed without moving the cursor.
+fn kitty(image: &Image) -> String {
+    let rgba: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|&((r, g, b), covered)| [r, g, b, (covered * 255.0).round() as u8])
+        .collect();
+    let data = base64(&rgba);
+    let chunks: Vec<&str> = data
+        .as_bytes()
+        .chunks(KITTY_CHUNK)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect();
+
+    let mut escapes = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        if index == 0 {
+            escapes.push_str(&format!(
+                "\x1b_Ga=T,f=32,s={},v={},i=1,q=2,C=1,m={more};{chunk}\x1b\\",
+                image.width, image.height
+            ));
+        } else {
+            escapes.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+        }
+    }
+    escapes
+}
+
+/// The palette of `colors`, with as few bits dropped from each channel as
+/// fit them in `SIXEL_COLORS`, and the palette index of every color.
+fn sixel_palette(colors: impl Iterator<Item = Rgb> + Clone) -> (Vec<Rgb>, HashMap<Rgb, usize>) {
+    let mut bits = 8;
+    loop {
+        let mask = 0xffu8 << (8 - bits);
+        let quantize = |(r, g, b): Rgb| (r & mask, g & mask, b & mask);
+        let mut palette = Vec::new();
+        let mut seen = HashMap::new();
+        for color in colors.clone() {
+            let quantized = quantize(color);
+            let next = seen.len();
+            let index = *seen.entry(quantized).or_insert(next);
+            if index == next {
+                palette.push(quantized);
+            }
+        }
+        if palette.len() <= SIXEL_COLORS || bits == 1 {
+            let indices = colors
+                .map(|color| (color, seen[&quantize(color)]))
+                .collect();
+            return (palette, indices);
+        }
+        bits -= 1;
+    }
+}
+
+/// Appends one line of sixels, run-length encoded, leaving off blank
+/// sixels at the end.
+fn push_sixels(sixels: &[u8], out: &mut String) {
+    let end = sixels
+        .iter()
+        .rposition(|&bits| bits != 0)
+        .map_or(0, |last| last + 1);
+    let mut column = 0;
+    while column < end {
+        let bits = sixels[column];
+        let run = sixels[column..end]
+            .iter()
+            .take_while(|&&next| next == bits)
+            .count();
+        let ch = (63 + bits) as char;
+        if run > 3 {
+            out.push_str(&format!("!{run}{ch}"));
+        } else {
+            (0..run).for_each(|_| out.push(ch));
+        }
+        column += run;
+    }
+}
+
+/// The image as a Sixel escape with a transparent background. Partly
+/// covered pixels are dithered, since Sixel pixels are on or off.
+fn sixel(image: &Image) -> String {
+    let lit: Vec<Option<Rgb>> = image
+        .pixels
+        .iter()
+        .enumerate()
+        .map(|(index, &(color, covered))| {
+            let (x, y) = (index % image.width, index / image.width);
+            let threshold = (BAYER[(y % 4) * 4 + x % 4] + 0.5) / 16.0;
+            (covered >= threshold).then_some(color)
+        })
+        .collect();
+    let (palette, indices) = sixel_palette(lit.iter().flatten().copied());
+
+    let mut out = format!("\x1bP0;1q\"1;1;{};{}", image.width, image.height);
+    let percent = |channel: u8| channel as u32 * 100 / 255;
+    for (index, &(r, g, b)) in palette.iter().enumerate() {
+        out.push_str(&format!(
+            "#{index};2;{};{};{}",
+            percent(r),
+            percent(g),
+            percent(b)
+        ));
+    }
+    for band in (0..image.height).step_by(6) {
+        let mut sixels: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+        for bit in 0..6.min(image.height - band) {
+            let row = (band + bit) * image.width;
+            for (x, color) in lit[row..row + image.width].iter().enumerate() {
+                if let Some(color) = color {
+                    sixels
+                        .entry(indices[color])
+                        .or_insert_with(|| vec![0; image.width])[x] |= 1 << bit;
+                }
+            }
+        }
+        for (index, line) in &sixels {
+            out.push_str(&format!("#{index}"));
+            push_sixels(line, &mut out);
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static GRAPHICS: Mutex<Option<Graphics>> = Mutex::new(None);
+
+/// The screen effects would have drawn on, rebuilt from their output so
+/// each frame can be drawn as an image in its place.
+struct Graphics {
+    protocol: Protocol,
+    screen: Screen,
+    cell: (usize, usize),
+    /// Rows cleared below the cursor for the image so far
+    reserved: usize,
+    drawn: bool,
+}
+
+/// Draws every frame as an image from now on, in place of the text.
+pub fn start(protocol: Protocol) {
+    let cell = terminal::window_size()
+        .ok()
+        .filter(|size| size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0)
+        .map_or(FALLBACK_CELL, |size| {
+            (
+                (size.width / size.columns) as usize,
+                (size.height / size.rows) as usize,
+            )
+        });
+    if let Ok(mut graphics) = GRAPHICS.lock() {
+        *graphics = Some(Graphics {
+            protocol,
+            screen: Screen::default(),
+            cell,
+            reserved: 0,
+            drawn: false,
+        });
+        ACTIVE.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Takes bytes headed for the terminal onto the screen images are drawn
+/// from. Returns false when frames aren't drawn as images, and the bytes
+/// should be written as they are.
+pub fn record(bytes: &[u8]) -> bool {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return false;
+    }
+    if let Ok(mut graphics) = GRAPHICS.lock() {
+        if let Some(graphics) = graphics.as_mut() {
+            graphics.screen.feed(bytes);
+            graphics.drawn = true;
+        }
+    }
+    true
+}
+
+/// Scrolls room for `rows` rows below the image's top, which is saved
+/// with the cursor, so images never scroll the top of themselves away.
+fn reserve(rows: usize) -> String {
+    let mut escapes = String::from("\x1b8");
+    if rows > 1 {
+        escapes.push_str(&"\n".repeat(rows - 1));
+        escapes.push_str(&format!("\x1b[{}A", rows - 1));
+    }
+    escapes.push_str("\x1b7");
+    escapes
+}
+
+/// Draws the frame just drawn as an image, unless nothing changed.
+pub fn end_frame() {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(mut graphics) = GRAPHICS.lock() else {
+        return;
+    };
+    let Some(graphics) = graphics.as_mut() else {
+        return;
+    };
+    if !std::mem::take(&mut graphics.drawn) || graphics.screen.rows().is_empty() {
+        return;
+    }
+
+    let mut escapes = String::new();
+    let rows = graphics.screen.rows().len();
+    if graphics.reserved == 0 {
+        // The image's top left is where the text would have started
+        escapes.push_str("\r\x1b7");
+    }
+    if rows > graphics.reserved {
+        escapes.push_str(&reserve(rows));
+        graphics.reserved = rows;
+    }
+    escapes.push_str("\x1b8");
+    let image = render(
+        graphics.screen.rows(),
+        graphics.screen.width(),
+        graphics.cell,
+    );
+    escapes.push_str(&match graphics.protocol {
+        Protocol::Sixel => sixel(&image),
+        Protocol::Kitty => kitty(&image),
+    });
+    let mut stdout = io::stdout().lock();
+    let _ = stdout
+        .write_all(escapes.as_bytes())
+        .and_then(|()| stdout.flush());
+}
+
+/// Draws anything left since the last frame, and leaves the cursor on the
+/// line below the image, where text would have left it.
+pub fn finish() {
+    end_frame();
+    let Some(graphics) = GRAPHICS
+        .lock()
+        .ok()
+        .and_then(|mut graphics| graphics.take())
+    else {
+        return;
+    };
+    ACTIVE.store(false, Ordering::Relaxed);
+    if graphics.reserved > 0 {
+        let mut stdout = io::stdout().lock();
+        let _ =
+            write!(stdout, "\x1b8{}", "\n".repeat(graphics.reserved)).and_then(|()| stdout.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(bytes: &[u8]) -> Screen {
+        let mut screen = Screen::default();
+        screen.feed(bytes);
+        screen
+    }
+
+    #[test]
+    fn test_detect_reads_the_terminal_from_the_environment() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(
+            detect(env(&[("TERM", "xterm-kitty")])),
+            Some(Protocol::Kitty)
+        );
+        assert_eq!(
+            detect(env(&[("TERM_PROGRAM", "WezTerm")])),
+            Some(Protocol::Kitty)
+        );
+        assert_eq!(detect(env(&[("TERM", "foot")])), Some(Protocol::Sixel));
+        assert_eq!(detect(env(&[("TERM", "xterm-256color")])), None);
+    }
+
+    #[test]
+    fn test_coverage_shapes_block_elements() {
+        assert_eq!(coverage('█', 0.9, 0.9), Some(1.0));
+        assert_eq!(coverage('▀', 0.5, 0.2), Some(1.0));
+        assert_eq!(coverage('▀', 0.5, 0.7), Some(0.0));
+        assert_eq!(coverage('▂', 0.5, 0.8), Some(1.0));
+        assert_eq!(coverage('▂', 0.5, 0.7), Some(0.0));
+        assert_eq!(coverage('▌', 0.4, 0.5), Some(1.0));
+        assert_eq!(coverage('▌', 0.6, 0.5), Some(0.0));
+        assert_eq!(coverage('▚', 0.2, 0.2), Some(1.0));
+        assert_eq!(coverage('▚', 0.8, 0.2), Some(0.0));
+        assert_eq!(coverage('▒', 0.1, 0.1), Some(0.5));
+        assert_eq!(coverage('a', 0.5, 0.5), None);
+        assert_eq!(glyph_coverage('*', 0.5, 0.5), 1.0);
+        assert_eq!(glyph_coverage('*', 0.1, 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_resolve_only_draws_block_text() {
+        let banner = vec!["█ █".to_string()];
+        let text = vec!["Hi".to_string()];
+        assert_eq!(Backend::Ansi.resolve(&banner).unwrap(), None);
+        assert_eq!(
+            Backend::Sixel.resolve(&banner).unwrap(),
+            Some(Protocol::Sixel)
+        );
+        assert_eq!(
+            Backend::Kitty.resolve(&banner).unwrap(),
+            Some(Protocol::Kitty)
+        );
+        let err = Backend::Kitty.resolve(&text).unwrap_err();
+        assert!(err.to_string().contains("--backend kitty"));
+    }
+
+    #[test]
+    fn test_render_blends_colors_between_cells() {
+        let drawn = screen(b"\x1b[38;2;0;0;0m\xe2\x96\x88\x1b[38;2;200;0;0m\xe2\x96\x88");
+        let image = render(drawn.rows(), drawn.width(), (4, 2));
+        assert_eq!((image.width, image.height), (8, 2));
+
+        let reds: Vec<u8> = image.pixels[..8].iter().map(|&((r, _, _), _)| r).collect();
+        // Flat out to each cell's middle, then a ramp between the two
+        assert_eq!(reds, vec![0, 0, 25, 75, 125, 175, 200, 200]);
+        assert!(image.pixels.iter().all(|&(_, covered)| covered == 1.0));
+    }
+
+    #[test]
+    fn test_render_leaves_blank_cells_clear() {
+        let drawn = screen("▀ ".as_bytes());
+        let image = render(drawn.rows(), drawn.width(), (2, 2));
+        let covered: Vec<f32> = image.pixels.iter().map(|&(_, covered)| covered).collect();
+        assert_eq!(covered, vec![1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(image.pixels[0].0, DEFAULT_FOREGROUND);
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_kitty_chunks_rgba() {
+        let image = Image {
+            width: 1,
+            height: 1,
+            pixels: vec![((255, 0, 0), 1.0)],
+        };
+        assert_eq!(
+            kitty(&image),
+            "\x1b_Ga=T,f=32,s=1,v=1,i=1,q=2,C=1,m=0;/wAA/w==\x1b\\"
+        );
+
+        let large = Image {
+            width: 2000,
+            height: 1,
+            pixels: vec![((0, 0, 0), 0.0); 2000],
+        };
+        let escapes = kitty(&large);
+        assert_eq!(escapes.matches("\x1b_G").count(), 3);
+        assert!(escapes.contains(",m=1;"));
+        assert!(escapes.contains("\x1b_Gm=0;"));
+    }
+
+    #[test]
+    fn test_sixel_encodes_bands_per_color() {
+        let red = ((255, 0, 0), 1.0);
+        let clear = ((0, 0, 0), 0.0);
+        let image = Image {
+            width: 5,
+            height: 2,
+            pixels: vec![red, red, red, red, red, red, clear, clear, clear, clear],
+        };
+        assert_eq!(
+            sixel(&image),
+            "\x1bP0;1q\"1;1;5;2#0;2;100;0;0#0B!4@$-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_sixel_palette_drops_bits_to_fit() {
+        let colors: Vec<Rgb> = (0..=255).flat_map(|r| [(r, 0, 0), (r, 1, 0)]).collect();
+        let (palette, indices) = sixel_palette(colors.iter().copied());
+        assert!(palette.len() <= SIXEL_COLORS);
+        assert_eq!(indices[&(3, 0, 0)], indices[&(3, 1, 0)]);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::vt::{Cell, Screen};
+use crossterm::style::Color;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthChar;
+
+/// Milliseconds the page holds the last frame before replaying from the top.
+const LAST_FRAME_HOLD_MS: u64 = 2000;
+
+/// Text that was drawn without a color of its own.
+const DEFAULT_FOREGROUND: &str = "#e5e5e5";
+
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+/// Every frame drawn while `--export-html` is on, each rebuilt as the
+/// screen looked once it finished.
+struct Recording {
+    screen: Screen,
+    started: Instant,
+    /// When each frame finished, and its rows
+    frames: Vec<(Duration, Vec<Vec<Cell>>)>,
+    /// Whether anything was drawn since the last frame was kept
+    drawn: bool,
+}
+
+impl Recording {
+    fn new() -> Self {
+        Self {
+            screen: Screen::default(),
+            started: Instant::now(),
+            frames: Vec::new(),
+            drawn: false,
+        }
+    }
+
+    /// Keeps the screen as it is now as a frame finishing at `at`, unless
+    /// it looks the same as the last one.
+    fn end_frame(&mut self, at: Duration) {
+        if !std::mem::take(&mut self.drawn) {
+            return;
+        }
+        let rows = self.screen.rows();
+        if self.frames.last().is_some_and(|(_, last)| last == rows) {
+            return;
+        }
+        self.frames.push((at, rows.to_vec()));
+    }
+}
+
+/// Starts keeping every frame, to write out as a web page at the end.
+pub fn start() {
+    if let Ok(mut recording) = RECORDING.lock() {
+        *recording = Some(Recording::new());
+    }
+}
+
+/// Applies bytes effects wrote to the recorded screen. Does nothing unless
+/// `start` was called.
+pub fn record(bytes: &[u8]) {
+    if let Ok(mut recording) = RECORDING.lock() {
+        if let Some(recording) = recording.as_mut() {
+            recording.screen.feed(bytes);
+            recording.drawn = true;
+        }
+    }
+}
+
+/// Keeps the frame just drawn.
+pub fn end_frame() {
+    if let Ok(mut recording) = RECORDING.lock() {
+        if let Some(recording) = recording.as_mut() {
+            let at = recording.started.elapsed();
+            recording.end_frame(at);
+        }
+    }
+}
+
+/// The color as CSS writes it, e.g. `#ff8000`.
+pub fn css_color(color: Option<Color>) -> Option<String> {
+    match color? {
+        Color::Rgb { r, g, b } => Some(format!("#{r:02x}{g:02x}{b:02x}")),
+        _ => None,
+    }
+}
+
+fn escape(ch: char, html: &mut String) {
+    match ch {
+        '&' => html.push_str("&amp;"),
+        '<' => html.push_str("&lt;"),
+        '>' => html.push_str("&gt;"),
+        ch => html.push(ch),
+    }
+}
+
+/// One frame as the inside of a `<pre>`: a line per row, each run of one
+/// color in its own span.
+fn frame_html(rows: &[Vec<Cell>]) -> String {
+    let mut html = String::new();
+    for (y, row) in rows.iter().enumerate() {
+        if y > 0 {
+            html.push('\n');
+        }
+        let mut current: Option<String> = None;
+        let mut covered = 0;
+        for cell in row {
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+            let (ch, color) = cell.unwrap_or((' ', None));
+            let color = css_color(color);
+            if color != current {
+                if current.is_some() {
+                    html.push_str("</span>");
+                }
+                if let Some(color) = &color {
+                    html.push_str(&format!("<span style=\"color:{color}\">"));
+                }
+                current = color;
+            }
+            escape(ch, &mut html);
+            covered = ch.width().unwrap_or(1).saturating_sub(1);
+        }
+        if current.is_some() {
+            html.push_str("</span>");
+        }
+    }
+    html
+}
+
+/// A self-contained page replaying `frames` on a loop. It opens on the
+/// last frame, which is all it shows with scripts turned off.
+fn page(frames: &[(Duration, Vec<Vec<Cell>>)]) -> String {
+    let origin = frames.first().map_or(Duration::ZERO, |&(at, _)| at);
+    let timeline: Vec<serde_json::Value> = frames
+        .iter()
+        .map(|(at, rows)| {
+            serde_json::json!([
+                at.saturating_sub(origin).as_millis() as u64,
+                frame_html(rows)
+            ])
+        })
+        .collect();
+    // Keeps `</span>` in the frames from closing the script early
+    let timeline = serde_json::to_string(&timeline)
+        .unwrap_or_default()
+        .replace("</", "<\\/");
+    let last = frames
+        .last()
+        .map(|(_, rows)| frame_html(rows))
+        .unwrap_or_default();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>clifx</title>
+<style>
+  body {{ margin: 0; background: #000; }}
+  pre.clifx {{ margin: 0; padding: 1em; color: {DEFAULT_FOREGROUND}; background: #000; font: 16px/1.2 ui-monospace, Menlo, Consolas, monospace; }}
+</style>
+</head>
+<body>
+<pre class="clifx" id="clifx">{last}</pre>
+<script>
+(function () {{
+  var frames = {timeline};
+  var screen = document.getElementById("clifx");
+  var index = 0;
+  function show() {{
+    screen.innerHTML = frames[index][1];
+    var next = index + 1 < frames.length ? frames[index + 1][0] - frames[index][0] : {LAST_FRAME_HOLD_MS};
+    index = (index + 1) % frames.length;
+    setTimeout(show, next);
+  }}
+  if (frames.length > 0) show();
+}})();
+</script>
+</body>
+</html>
+"#
+    )
+}
+
+/// Writes the recorded frames to `path` as a web page. Does nothing unless
+/// `start` was called.
+pub fn write(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut recording) = RECORDING
+        .lock()
+        .ok()
+        .and_then(|mut recording| recording.take())
+    else {
+        return Ok(());
+    };
+    // The newline after the last frame may have come after its timer
+    let at = recording.started.elapsed();
+    recording.end_frame(at);
+    fs::write(path, page(&recording.frames))
+        .map_err(|err| format!("couldn't write {}: {err}", path.display()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(bytes: &[u8]) -> Screen {
+        let mut screen = Screen::default();
+        screen.feed(bytes);
+        screen
+    }
+
+    #[test]
+    fn test_frame_html_colors_runs_and_escapes_text() {
+        let drawn = screen(b"\x1b[38;2;255;0;0m<a\x1b[0m&b\nc");
+        assert_eq!(
+            frame_html(drawn.rows()),
+            "<span style=\"color:#ff0000\">&lt;a</span>&amp;b\nc"
+        );
+    }
+
+    #[test]
+    fn test_frame_html_keeps_wide_characters_whole() {
+        let wide = screen("漢x".as_bytes());
+        assert_eq!(frame_html(wide.rows()), "漢x");
+    }
+
+    #[test]
+    fn test_recording_skips_frames_that_changed_nothing() {
+        let mut recording = Recording::new();
+        let mut draw = |bytes: &[u8], at: u64| {
+            recording.screen.feed(bytes);
+            recording.drawn = true;
+            recording.end_frame(Duration::from_millis(at));
+        };
+        draw(b"\x1b[1Ga", 0);
+        draw(b"\x1b[1Ga", 50);
+        draw(b"\x1b[1Gb", 100);
+        recording.end_frame(Duration::from_millis(150));
+
+        let times: Vec<u128> = recording
+            .frames
+            .iter()
+            .map(|(at, _)| at.as_millis())
+            .collect();
+        assert_eq!(times, vec![0, 100]);
+        assert_eq!(recording.frames[1].1[0][0], Some(('b', None)));
+    }
+
+    #[test]
+    fn test_page_times_frames_from_the_first_and_opens_on_the_last() {
+        let frames = vec![
+            (Duration::from_millis(500), screen(b"one").rows().to_vec()),
+            (
+                Duration::from_millis(750),
+                screen(b"\x1b[38;2;0;0;255m<two>").rows().to_vec(),
+            ),
+        ];
+        let page = page(&frames);
+
+        let two = r#"<span style=\"color:#0000ff\">&lt;two&gt;<\/span>"#;
+        assert!(page.contains(&format!(r#"var frames = [[0,"one"],[250,"{two}"]];"#)));
+        assert!(
+            page.contains("id=\"clifx\"><span style=\"color:#0000ff\">&lt;two&gt;</span></pre>")
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use clap::ValueEnum;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Instant;
+
+/// Text filters applied to every input line before any effect runs.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    /// Convert letters to uppercase
+    Uppercase,
+    /// Replace lowercase letters with Unicode small capitals
+    SmallCaps,
+    /// Replace letters with look-alike digits (e.g., "elite" -> "3l173")
+    Leet,
+    /// Replace ASCII with double-width fullwidth forms
+    Fullwidth,
+}
+
+impl Transform {
+    fn apply_char(self, ch: char, out: &mut String) {
+        match self {
+            Transform::Uppercase => out.extend(ch.to_uppercase()),
+            Transform::SmallCaps => out.push(small_cap(ch)),
+            Transform::Leet => out.push(leet(ch)),
+            Transform::Fullwidth => out.push(fullwidth(ch)),
+        }
+    }
+}
+
+/// Applies `transforms` in order to `line`. ANSI escape sequences are copied
+/// through untouched so pre-colored input keeps working.
+pub fn apply_transforms(line: &str, transforms: &[Transform]) -> String {
+    transforms
+        .iter()
+        .fold(line.to_string(), |text, &transform| {
+            let mut result = String::with_capacity(text.len());
+            let mut chars = text.chars().peekable();
+
+            while let Some(ch) = chars.next() {
+                if ch == '\x1b' && chars.peek() == Some(&'[') {
+                    result.push(ch);
+                    result.push(chars.next().unwrap_or('['));
+                    for escape_ch in chars.by_ref() {
+                        result.push(escape_ch);
+                        if escape_ch.is_ascii_alphabetic() {
+                            break;
+                        }
+                    }
+                    continue;
+                }
+
+                transform.apply_char(ch, &mut result);
+            }
+
+            result
+        })
+}
+
+/// What to do with control characters (backspace, carriage return, BEL, ...)
+/// that would otherwise move the cursor or beep mid-animation.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ControlChars {
+    /// Remove them
+    Strip,
+    /// Show them in caret notation (e.g., backspace as "^H")
+    Caret,
+    /// Pass them through unchanged
+    Keep,
+}
+
+/// Handles control characters in `line` according to `mode`. Tabs and ANSI
+/// CSI sequences (colors, etc.) are always kept; a stray ESC is not.
+pub fn sanitize_controls(line: &str, mode: ControlChars) -> String {
+    if mode == ControlChars::Keep {
+        return line.to_string();
+    }
+
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' && chars.peek() == Some(&'[') {
+            result.push(ch);
+            result.push(chars.next().unwrap_or('['));
+            for escape_ch in chars.by_ref() {
+                result.push(escape_ch);
+                if escape_ch.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        if ch == '\t' || !ch.is_ascii_control() {
+            result.push(ch);
+        } else if mode == ControlChars::Caret {
+            result.push('^');
+            // ^@ through ^_ for C0 codes, ^? for DEL
+            result.push((ch as u8 ^ 0x40) as char);
+        }
+    }
+
+    result
+}
+
+/// Everything done to a raw stdin line before an effect sees it.
+#[derive(Debug, Clone)]
+pub struct InputFilter {
+    pub transforms: Vec<Transform>,
+    pub control_chars: ControlChars,
+}
+
+impl InputFilter {
+    pub fn apply(&self, line: &str) -> String {
+        apply_transforms(
+            &sanitize_controls(line, self.control_chars),
+            &self.transforms,
+        )
+    }
+}
+
+/// Reads stdin on a background thread, sending each filtered line as it
+/// arrives. The channel disconnects when stdin reaches end of file.
+pub fn spawn_line_reader(filter: InputFilter) -> Receiver<String> {
+    spawn_reader(filter, |line| line)
+}
+
+/// Like `spawn_line_reader`, but each line comes with the time it arrived,
+/// so how fast input is coming in can be measured however long it waits.
+pub fn spawn_stamped_line_reader(filter: InputFilter) -> Receiver<(Instant, String)> {
+    spawn_reader(filter, |line| (Instant::now(), line))
+}
+
+fn spawn_reader<T: Send + 'static>(filter: InputFilter, wrap: fn(String) -> T) -> Receiver<T> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(wrap(filter.apply(&line))).is_err() {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// The lines of each of `paths` in turn, stopping once there are `limit`
+/// of them so a large file isn't read past what fits on screen.
+pub fn read_files(
+    paths: &[PathBuf],
+    limit: Option<usize>,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut lines = Vec::new();
+    for path in paths {
+        if lines.len() >= limit {
+            break;
+        }
+        let couldnt_read = |err: io::Error| format!("couldn't read {}: {err}", path.display());
+        let mut reader = BufReader::new(File::open(path).map_err(couldnt_read)?);
+        let mut line = Vec::new();
+        while lines.len() < limit {
+            line.clear();
+            if reader.read_until(b'\n', &mut line).map_err(couldnt_read)? == 0 {
+                break;
+            }
+            if line.ends_with(b"\n") {
+                line.pop();
+                if line.ends_with(b"\r") {
+                    line.pop();
+                }
+            }
+            lines.push(String::from_utf8_lossy(&line).into_owned());
+        }
+    }
+    Ok(lines)
+}
+
+fn small_cap(ch: char) -> char {
+    const SMALL_CAPS: [char; 26] = [
+        'ᴀ', 'ʙ', 'ᴄ', 'ᴅ', 'ᴇ', 'ꜰ', 'ɢ', 'ʜ', 'ɪ', 'ᴊ', 'ᴋ', 'ʟ', 'ᴍ', 'ɴ', 'ᴏ', 'ᴘ', 'ǫ', 'ʀ',
+        'ꜱ', 'ᴛ', 'ᴜ', 'ᴠ', 'ᴡ', 'x', 'ʏ', 'ᴢ',
+    ];
+
+    if ch.is_ascii_lowercase() {
+        SMALL_CAPS[(ch as u8 - b'a') as usize]
+    } else {
+        ch
+    }
+}
+
+fn leet(ch: char) -> char {
+    match ch.to_ascii_lowercase() {
+        'a' => '4',
+        'b' => '8',
+        'e' => '3',
+        'g' => '6',
+        'i' => '1',
+        'o' => '0',
+        's' => '5',
+        't' => '7',
+        'z' => '2',
+        _ => ch,
+    }
+}
+
+fn fullwidth(ch: char) -> char {
+    match ch {
+        ' ' => '\u{3000}',
+        '!'..='~' => char::from_u32(ch as u32 - 0x21 + 0xFF01).unwrap_or(ch),
+        _ => ch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unicode_width::UnicodeWidthStr;
+
+    #[test]
+    fn test_read_files_reads_each_in_turn_up_to_the_limit() {
+        let dir = std::env::temp_dir().join(format!("clifx-files-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (first, second) = (dir.join("first.txt"), dir.join("second.txt"));
+        std::fs::write(&first, "one\r\ntwo").unwrap();
+        std::fs::write(&second, b"thr\xffee\nfour\nfive\n").unwrap();
+        let paths = [first, second];
+
+        assert_eq!(
+            read_files(&paths, None).unwrap(),
+            vec!["one", "two", "thr\u{fffd}ee", "four", "five"]
+        );
+        assert_eq!(read_files(&paths, Some(3)).unwrap().len(), 3);
+        assert!(read_files(&[dir.join("missing.txt")], None)
+            .unwrap_err()
+            .to_string()
+            .contains("missing.txt"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_no_transforms_is_identity() {
+        assert_eq!(apply_transforms("Hello, World!", &[]), "Hello, World!");
+    }
+
+    #[test]
+    fn test_uppercase() {
+        assert_eq!(
+            apply_transforms("Hello straße", &[Transform::Uppercase]),
+            "HELLO STRASSE"
+        );
+    }
+
+    #[test]
+    fn test_small_caps() {
+        assert_eq!(apply_transforms("Hello", &[Transform::SmallCaps]), "Hᴇʟʟᴏ");
+    }
+
+    #[test]
+    fn test_leet() {
+        assert_eq!(
+            apply_transforms("Leet Speak", &[Transform::Leet]),
+            "L337 5p34k"
+        );
+    }
+
+    #[test]
+    fn test_fullwidth_doubles_display_width() {
+        let transformed = apply_transforms("Hi there!", &[Transform::Fullwidth]);
+        assert_eq!(transformed, "Ｈｉ\u{3000}ｔｈｅｒｅ！");
+        assert_eq!(transformed.width(), "Hi there!".width() * 2);
+    }
+
+    #[test]
+    fn test_transforms_apply_in_order() {
+        // Uppercase first means small caps has nothing left to convert
+        assert_eq!(
+            apply_transforms("ab", &[Transform::Uppercase, Transform::SmallCaps]),
+            "AB"
+        );
+        assert_eq!(
+            apply_transforms("ab", &[Transform::SmallCaps, Transform::Uppercase]),
+            "ᴀʙ"
+        );
+    }
+
+    #[test]
+    fn test_ansi_sequences_are_preserved() {
+        assert_eq!(
+            apply_transforms("\x1b[31mred\x1b[0m", &[Transform::Uppercase]),
+            "\x1b[31mRED\x1b[0m"
+        );
+        assert_eq!(
+            apply_transforms("\x1b[1ma", &[Transform::Fullwidth]),
+            "\x1b[1mａ"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_controls_caret() {
+        assert_eq!(
+            sanitize_controls("ab\x08c\rd\x07", ControlChars::Caret),
+            "ab^Hc^Md^G"
+        );
+        assert_eq!(sanitize_controls("\x7f\x00", ControlChars::Caret), "^?^@");
+        assert_eq!(sanitize_controls("\x1bx", ControlChars::Caret), "^[x");
+    }
+
+    #[test]
+    fn test_sanitize_controls_strip_and_keep() {
+        assert_eq!(
+            sanitize_controls("ab\x08c\rd\x07", ControlChars::Strip),
+            "abcd"
+        );
+        assert_eq!(
+            sanitize_controls("ab\x08c\r", ControlChars::Keep),
+            "ab\x08c\r"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_controls_keeps_colors_and_tabs() {
+        assert_eq!(
+            sanitize_controls("\x1b[31mred\x1b[0m\tok", ControlChars::Strip),
+            "\x1b[31mred\x1b[0m\tok"
+        );
+    }
+
+    #[test]
+    fn test_input_filter_sanitizes_before_transforming() {
+        let filter = InputFilter {
+            transforms: vec![Transform::Uppercase],
+            control_chars: ControlChars::Caret,
+        };
+        assert_eq!(filter.apply("a\x08b"), "A^HB");
+    }
+}
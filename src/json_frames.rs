@@ -0,0 +1,146 @@
+use crate::ansi::{Pen, ATTRIBUTE_NAMES};
+use crate::html::css_color;
+use crate::vt::{Cell, Screen};
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static FRAMES: Mutex<Option<JsonFrames>> = Mutex::new(None);
+
+/// The screen effects would have drawn on, rebuilt from their output so
+/// each frame can be written as data instead.
+struct JsonFrames {
+    screen: Screen,
+    started: Instant,
+    /// Whether anything was drawn since the last frame was written
+    drawn: bool,
+    /// The last frame written, to skip frames that look the same
+    last: (Vec<Vec<Cell>>, Vec<Vec<Pen>>),
+}
+
+/// Writes every frame to stdout as a line of JSON from now on, in place of
+/// the escape codes that would draw it.
+pub fn start() {
+    if let Ok(mut frames) = FRAMES.lock() {
+        *frames = Some(JsonFrames {
+            screen: Screen::default(),
+            started: Instant::now(),
+            drawn: false,
+            last: Default::default(),
+        });
+        ACTIVE.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Whether frames go out as JSON, so nothing else may write escape codes
+/// to stdout.
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Takes bytes headed for the terminal onto the screen frames are read
+/// from. Returns false when frames aren't JSON, and the bytes should be
+/// written as they are.
+pub fn record(bytes: &[u8]) -> bool {
+    if !is_active() {
+        return false;
+    }
+    if let Ok(mut frames) = FRAMES.lock() {
+        if let Some(frames) = frames.as_mut() {
+            frames.screen.feed(bytes);
+            frames.drawn = true;
+        }
+    }
+    true
+}
+
+fn cell_json(cell: &Cell, pen: &Pen) -> Value {
+    let Some((ch, foreground)) = *cell else {
+        return Value::Null;
+    };
+    let attributes: Vec<&str> = ATTRIBUTE_NAMES
+        .iter()
+        .filter(|&&(bit, _)| pen.attributes & bit != 0)
+        .map(|&(_, name)| name)
+        .collect();
+    json!({
+        "char": ch.to_string(),
+        "fg": css_color(foreground),
+        "bg": css_color(pen.background),
+        "attrs": attributes,
+    })
+}
+
+/// One frame as a JSON object: milliseconds since the run started, and
+/// its rows of cells. Cells nothing was drawn in, and the second column
+/// of wide characters, are `null`.
+fn frame_json(screen: &Screen, at: Duration) -> Value {
+    let rows: Vec<Vec<Value>> = screen
+        .rows()
+        .iter()
+        .zip(screen.pens())
+        .map(|(row, pens)| {
+            row.iter()
+                .zip(pens)
+                .map(|(cell, pen)| cell_json(cell, pen))
+                .collect()
+        })
+        .collect();
+    json!({
+        "time_ms": at.as_millis() as u64,
+        "rows": rows,
+    })
+}
+
+/// Writes the frame just drawn as a line of JSON, unless it looks the same
+/// as the last one.
+pub fn end_frame() {
+    if !is_active() {
+        return;
+    }
+    let Ok(mut frames) = FRAMES.lock() else {
+        return;
+    };
+    let Some(frames) = frames.as_mut() else {
+        return;
+    };
+    if !std::mem::take(&mut frames.drawn) {
+        return;
+    }
+    let (rows, pens) = (frames.screen.rows(), frames.screen.pens());
+    if frames.last.0 == rows && frames.last.1 == pens {
+        return;
+    }
+    frames.last = (rows.to_vec(), pens.to_vec());
+    let line = frame_json(&frames.screen, frames.started.elapsed());
+    let mut stdout = io::stdout().lock();
+    // A reader that stopped listening shouldn't stop the animation
+    let _ = writeln!(stdout, "{line}").and_then(|()| stdout.flush());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_json_describes_every_cell() {
+        let mut screen = Screen::default();
+        screen.feed("\x1b[1;38;2;255;0;0;48;5;4ma\x1b[0m漢".as_bytes());
+
+        let frame = frame_json(&screen, Duration::from_millis(40));
+        assert_eq!(frame["time_ms"], 40);
+        let row = &frame["rows"][0];
+        assert_eq!(
+            row[0],
+            json!({"char": "a", "fg": "#ff0000", "bg": "#0000ee", "attrs": ["bold"]})
+        );
+        assert_eq!(
+            row[1],
+            json!({"char": "漢", "fg": null, "bg": null, "attrs": []})
+        );
+        assert_eq!(row[2], Value::Null);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::capability::fg;
+use crate::vt::{Cell, Screen};
+use crossterm::style::{Color, ResetColor};
+use crossterm::Command;
+use std::sync::Mutex;
+use unicode_width::UnicodeWidthChar;
+
+/// Everything effects have drawn, rebuilt as a screen, while `--keep-final`
+/// is on.
+static KEPT: Mutex<Option<Screen>> = Mutex::new(None);
+
+/// Starts keeping what effects draw, to print once they're done.
+pub fn start() {
+    if let Ok(mut kept) = KEPT.lock() {
+        *kept = Some(Screen::default());
+    }
+}
+
+/// Applies bytes effects wrote to the kept screen. Does nothing unless
+/// `start` was called.
+pub fn record(bytes: &[u8]) {
+    if let Ok(mut kept) = KEPT.lock() {
+        if let Some(screen) = kept.as_mut() {
+            screen.feed(bytes);
+        }
+    }
+}
+
+fn is_blank(cell: &Cell) -> bool {
+    cell.is_none_or(|(ch, _)| ch == ' ')
+}
+
+/// The final frame as lines to print one after another, with colors but no
+/// cursor movement. Blanks trailing each row are dropped, and for `centered`
+/// output so are the empty rows and margin around the content.
+fn serialize(rows: &[Vec<Cell>], centered: bool) -> Vec<String> {
+    let mut rows = rows;
+    let mut margin = 0;
+    if centered {
+        let filled = |row: &&Vec<Cell>| !row.iter().all(is_blank);
+        let first = rows
+            .iter()
+            .position(|row| filled(&row))
+            .unwrap_or(rows.len());
+        let last = rows
+            .iter()
+            .rposition(|row| filled(&row))
+            .map_or(first, |last| last + 1);
+        rows = &rows[first..last];
+        margin = rows
+            .iter()
+            .filter(filled)
+            .map(|row| row.iter().take_while(|cell| is_blank(cell)).count())
+            .min()
+            .unwrap_or(0);
+    }
+
+    rows.iter()
+        .map(|row| {
+            let end = row
+                .iter()
+                .rposition(|cell| !is_blank(cell))
+                .map_or(0, |end| end + 1);
+            let mut line = String::new();
+            let mut current: Option<Color> = None;
+            let mut covered = 0;
+            for cell in row.iter().take(end).skip(margin) {
+                if covered > 0 {
+                    covered -= 1;
+                    continue;
+                }
+                let (ch, color) = cell.unwrap_or((' ', None));
+                if color != current {
+                    // Writing to a String can't fail
+                    let _ = match color {
+                        Some(color) => fg(color).write_ansi(&mut line),
+                        None => ResetColor.write_ansi(&mut line),
+                    };
+                    current = color;
+                }
+                line.push(ch);
+                covered = ch.width().unwrap_or(1).saturating_sub(1);
+            }
+            if current.is_some() {
+                let _ = ResetColor.write_ansi(&mut line);
+            }
+            line
+        })
+        .collect()
+}
+
+/// Prints the final frame again as ordinary lines, so it stays in the
+/// scrollback and in logs after the animation is gone. Does nothing unless
+/// `start` was called.
+pub fn print(centered: bool) {
+    let Some(screen) = KEPT.lock().ok().and_then(|mut kept| kept.take()) else {
+        return;
+    };
+    for line in serialize(screen.rows(), centered) {
+        println!("{line}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(bytes: &[u8]) -> Screen {
+        let mut screen = Screen::default();
+        screen.feed(bytes);
+        screen
+    }
+
+    #[test]
+    fn test_serialize_redrawn_frames() {
+        let frames = screen(b"\x1b[1Gab\x1b[1G\x1b[38;2;255;0;0mAB\x1b[0m\ncd  ");
+        let mut red = String::new();
+        fg(Color::Rgb { r: 255, g: 0, b: 0 })
+            .write_ansi(&mut red)
+            .unwrap();
+        assert_eq!(
+            serialize(frames.rows(), false),
+            vec![format!("{red}AB\x1b[0m"), "cd".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_serialize_trims_centering() {
+        let centered = screen(b"\x1b[3;5Hhi\x1b[4;4Hyou");
+        assert_eq!(serialize(centered.rows(), true), vec![" hi", "you"]);
+        // Without centering, leading blanks belong to the text
+        assert_eq!(serialize(centered.rows(), false)[2], "    hi");
+    }
+
+    #[test]
+    fn test_serialize_keeps_wide_characters_whole() {
+        let wide = screen("漢x".as_bytes());
+        assert_eq!(serialize(wide.rows(), false), vec!["漢x"]);
+    }
+}
@@ -0,0 +1,548 @@
+use crate::capability::fg;
+use crate::center::strip_ansi_codes;
+use clap::ValueEnum;
+use crossterm::{
+    cursor, queue,
+    style::{Color, Print, ResetColor},
+};
+use std::io::{self, Write};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Where a row of text may break when it's too wide for the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum Wrap {
+    /// Between words, breaking a word only when it's wider than a row
+    #[default]
+    Word,
+    /// At whichever character would overflow the row
+    Char,
+    /// Only at newlines, leaving long rows to run off the edge
+    None,
+}
+
+/// How the rows of a wrapped block line up with each other.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Spaces widened so every row but a paragraph's last fills the block
+    Justify,
+}
+
+/// How text is laid out in rows: where it wraps and how it lines up.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Flow {
+    pub wrap: Wrap,
+    pub align: Align,
+}
+
+fn char_width(ch: char) -> usize {
+    ch.width().unwrap_or(0)
+}
+
+fn row_width(row: &[char]) -> usize {
+    row.iter().map(|&ch| char_width(ch)).sum()
+}
+
+/// Adds `ch` to `row`, first moving a full row to `rows` if `ch` would
+/// overflow `width` columns.
+fn push_char(rows: &mut Vec<Vec<char>>, row: &mut Vec<char>, ch: char, width: usize) {
+    if !row.is_empty() && row_width(row) + char_width(ch) > width {
+        rows.push(std::mem::take(row));
+    }
+    row.push(ch);
+}
+
+/// One paragraph's rows, broken to fit `width` columns.
+fn wrap_paragraph(paragraph: &str, width: usize, wrap: Wrap) -> Vec<Vec<char>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    match wrap {
+        Wrap::None => row = paragraph.chars().collect(),
+        Wrap::Char => {
+            for ch in paragraph.chars() {
+                push_char(&mut rows, &mut row, ch, width);
+            }
+        }
+        Wrap::Word => {
+            for (i, word) in paragraph.split(' ').enumerate() {
+                let word: Vec<char> = word.chars().collect();
+                if i > 0 {
+                    if row_width(&row) + 1 + row_width(&word) > width {
+                        if !row.is_empty() {
+                            rows.push(std::mem::take(&mut row));
+                        }
+                    } else if !row.is_empty() || rows.is_empty() {
+                        // Spaces between words are kept, even runs of
+                        // them, but not at the start of a wrapped row
+                        row.push(' ');
+                    }
+                }
+                if row_width(&row) + row_width(&word) <= width {
+                    row.extend(word);
+                } else {
+                    // Too long for any row: break it wherever it overflows
+                    for ch in word {
+                        push_char(&mut rows, &mut row, ch, width);
+                    }
+                }
+            }
+        }
+    }
+    if !row.is_empty() || rows.is_empty() {
+        rows.push(row);
+    }
+    rows
+}
+
+/// `row` widened to `width` columns by spreading extra spaces between its
+/// words, the first gaps taking any left over. A row of a single word is
+/// left as it is.
+fn justify(row: &[char], width: usize) -> Vec<char> {
+    let gaps = row
+        .windows(2)
+        .filter(|pair| pair[0] != ' ' && pair[1] == ' ')
+        .count();
+    let extra = width.saturating_sub(row_width(row));
+    if gaps == 0 || extra == 0 || row.last() == Some(&' ') {
+        return row.to_vec();
+    }
+    let mut justified = Vec::with_capacity(row.len() + extra);
+    let mut gap = 0;
+    for (i, &ch) in row.iter().enumerate() {
+        if ch == ' ' && i > 0 && row[i - 1] != ' ' {
+            let widen = extra / gaps + usize::from(gap < extra % gaps);
+            justified.extend(std::iter::repeat_n(' ', widen));
+            gap += 1;
+        }
+        justified.push(ch);
+    }
+    justified
+}
+
+/// Breaks `text` into rows at newlines and wherever `flow` wraps it to fit
+/// `terminal_width` columns, then lines the rows up within the widest.
+pub fn flow(text: &str, terminal_width: usize, flow: Flow) -> Vec<Vec<char>> {
+    let terminal_width = terminal_width.max(1);
+    let mut paragraphs: Vec<&str> = text.split('\n').collect();
+    // A trailing newline ends the last row rather than starting another
+    if paragraphs.last() == Some(&"") {
+        paragraphs.pop();
+    }
+    // Each row, and whether it's the last of its paragraph
+    let rows: Vec<(Vec<char>, bool)> = paragraphs
+        .iter()
+        .flat_map(|paragraph| {
+            let rows = wrap_paragraph(paragraph, terminal_width, flow.wrap);
+            let last = rows.len() - 1;
+            rows.into_iter()
+                .enumerate()
+                .map(move |(i, row)| (row, i == last))
+        })
+        .collect();
+
+    let width = rows
+        .iter()
+        .map(|(row, _)| row_width(row))
+        .max()
+        .unwrap_or(0);
+    rows.into_iter()
+        .map(|(row, ends_paragraph)| {
+            let pad = width - row_width(&row);
+            match flow.align {
+                Align::Left => row,
+                Align::Center => [vec![' '; pad / 2], row].concat(),
+                Align::Right => [vec![' '; pad], row].concat(),
+                Align::Justify if ends_paragraph => row,
+                Align::Justify => justify(&row, width),
+            }
+        })
+        .collect()
+}
+
+/// `lines` laid out by `flow` to fit `terminal_width` columns, as the rows
+/// effects draw. Escape codes in them are dropped, since a row can break
+/// anywhere in what they color.
+pub fn flow_lines(lines: &[String], terminal_width: usize, flow: Flow) -> Vec<String> {
+    let text = lines
+        .iter()
+        .map(|line| strip_ansi_codes(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    self::flow(&text, terminal_width, flow)
+        .into_iter()
+        .map(|row| row.into_iter().collect())
+        .collect()
+}
+
+/// The lines `--border` draws around the text.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum Border {
+    #[default]
+    None,
+    /// Light lines with rounded corners
+    Rounded,
+    /// Double lines
+    Double,
+    /// Plus signs, dashes, and bars, for terminals without box drawing
+    Ascii,
+}
+
+/// A border's glyphs: its corners clockwise from the top left, then its
+/// horizontal and vertical edges.
+struct Glyphs {
+    corners: [char; 4],
+    horizontal: char,
+    vertical: char,
+}
+
+impl Border {
+    fn glyphs(self) -> Option<Glyphs> {
+        let (corners, horizontal, vertical) = match self {
+            Border::None => return None,
+            Border::Rounded => (['╭', '╮', '╯', '╰'], '─', '│'),
+            Border::Double => (['╔', '╗', '╝', '╚'], '═', '║'),
+            Border::Ascii => (['+'; 4], '-', '|'),
+        };
+        Some(Glyphs {
+            corners,
+            horizontal,
+            vertical,
+        })
+    }
+}
+
+/// The box around the text: `margin` blank cells outside the border and
+/// `pad` inside it, on every side.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Boxing {
+    pub margin: u16,
+    pub pad: u16,
+    pub border: Border,
+}
+
+/// A run of a border's glyphs, and the row and column it starts at,
+/// counting from the box's outer corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub row: u16,
+    pub column: u16,
+    pub glyphs: String,
+}
+
+impl Boxing {
+    /// Whether there's no box at all.
+    pub fn is_empty(&self) -> bool {
+        self.inset() == 0
+    }
+
+    /// Cells between the box's outer edge and the text, on each side.
+    pub fn inset(&self) -> u16 {
+        let border = u16::from(self.border != Border::None);
+        self.margin.saturating_add(border).saturating_add(self.pad)
+    }
+
+    /// The size (width, height) of the box around text of `content` size.
+    pub fn outer_size(&self, (width, height): (u16, u16)) -> (u16, u16) {
+        let sides = self.inset().saturating_mul(2);
+        (width.saturating_add(sides), height.saturating_add(sides))
+    }
+
+    /// The border around text of `content` size: its top and bottom rows,
+    /// and the glyph at each end of every row between.
+    pub fn edges(&self, (width, height): (u16, u16)) -> Vec<Edge> {
+        let Some(glyphs) = self.border.glyphs() else {
+            return Vec::new();
+        };
+        let inner_width = width.saturating_add(self.pad.saturating_mul(2));
+        let inner_height = height.saturating_add(self.pad.saturating_mul(2));
+        let (top, left) = (self.margin, self.margin);
+        let bottom = top.saturating_add(inner_height).saturating_add(1);
+        let right = left.saturating_add(inner_width).saturating_add(1);
+        let across = |first: char, last: char| Edge {
+            row: 0,
+            column: left,
+            glyphs: std::iter::once(first)
+                .chain(std::iter::repeat_n(glyphs.horizontal, inner_width as usize))
+                .chain(std::iter::once(last))
+                .collect(),
+        };
+        let [top_left, top_right, bottom_right, bottom_left] = glyphs.corners;
+
+        let mut edges = vec![Edge {
+            row: top,
+            ..across(top_left, top_right)
+        }];
+        for row in top + 1..bottom {
+            for column in [left, right] {
+                edges.push(Edge {
+                    row,
+                    column,
+                    glyphs: glyphs.vertical.to_string(),
+                });
+            }
+        }
+        edges.push(Edge {
+            row: bottom,
+            ..across(bottom_left, bottom_right)
+        });
+        edges
+    }
+
+    /// `lines` in the box, as rows to print one after another, with
+    /// `paint` coloring the border's glyphs.
+    pub fn around(&self, lines: &[String], paint: impl Fn(&str) -> String) -> Vec<String> {
+        let content = crate::center::content_size(lines);
+        let inset = self.inset();
+        // What each row holds, as (column, text, width) in any order
+        let mut rows = vec![Vec::new(); self.outer_size(content).1 as usize];
+        for (i, line) in lines.iter().enumerate() {
+            let width = strip_ansi_codes(line).width();
+            rows[inset as usize + i].push((inset, line.clone(), width));
+        }
+        for edge in self.edges(content) {
+            let width = edge.glyphs.width();
+            rows[edge.row as usize].push((edge.column, paint(&edge.glyphs), width));
+        }
+
+        rows.into_iter()
+            .map(|mut row| {
+                row.sort_by_key(|&(column, ..)| column);
+                let mut line = String::new();
+                let mut at = 0;
+                for (column, text, width) in row {
+                    line.push_str(&" ".repeat((column as usize).saturating_sub(at)));
+                    line.push_str(&text);
+                    at = at.max(column as usize) + width;
+                }
+                line
+            })
+            .collect()
+    }
+
+    /// Draws the border in `color` around text of `content` size whose
+    /// top-left corner is at `offsets` (top, left). Parts that would be off
+    /// the top or left of the screen are left out.
+    pub fn draw(
+        &self,
+        content: (u16, u16),
+        (top, left): (u16, u16),
+        color: Color,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        let inset = self.inset();
+        for edge in self.edges(content) {
+            let row = (top + edge.row).checked_sub(inset);
+            let column = (left + edge.column).checked_sub(inset);
+            if let (Some(row), Some(column)) = (row, column) {
+                queue!(
+                    out,
+                    cursor::MoveTo(column, row),
+                    fg(color),
+                    Print(&edge.glyphs)
+                )?;
+            }
+        }
+        queue!(out, ResetColor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAR: Flow = Flow {
+        wrap: Wrap::Char,
+        align: Align::Left,
+    };
+
+    fn rows(text: &str, width: usize, flow: Flow) -> Vec<String> {
+        self::flow(text, width, flow)
+            .into_iter()
+            .map(|row| row.into_iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_char_wrap_basic() {
+        let text = "Hello World";
+        let grid = flow(text, 5, CHAR);
+
+        assert_eq!(grid.len(), 3);
+        assert_eq!(grid[0], vec!['H', 'e', 'l', 'l', 'o']);
+        assert_eq!(grid[1], vec![' ', 'W', 'o', 'r', 'l']);
+        assert_eq!(grid[2], vec!['d']);
+    }
+
+    #[test]
+    fn test_char_wrap_with_newlines() {
+        let text = "Hello\nWorld";
+        let grid = flow(text, 10, CHAR);
+
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid[0], vec!['H', 'e', 'l', 'l', 'o']);
+        assert_eq!(grid[1], vec!['W', 'o', 'r', 'l', 'd']);
+    }
+
+    #[test]
+    fn test_char_wrap_empty() {
+        let text = "";
+        let grid = flow(text, 10, CHAR);
+
+        assert_eq!(grid.len(), 0);
+    }
+
+    #[test]
+    fn test_char_wrap_exact_width() {
+        let text = "12345";
+        let grid = flow(text, 5, CHAR);
+
+        assert_eq!(grid.len(), 1);
+        assert_eq!(grid[0], vec!['1', '2', '3', '4', '5']);
+    }
+
+    #[test]
+    fn test_char_wrap_wide_characters() {
+        // Fullwidth glyphs take two columns each
+        let text = "ＡＢＣ";
+        let grid = flow(text, 4, CHAR);
+
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid[0], vec!['Ａ', 'Ｂ']);
+        assert_eq!(grid[1], vec!['Ｃ']);
+    }
+
+    #[test]
+    fn test_word_wrap_breaks_between_words() {
+        let word = Flow::default();
+        assert_eq!(
+            rows("the quick brown fox", 10, word),
+            ["the quick", "brown fox"]
+        );
+        // Runs of spaces inside a row are kept
+        assert_eq!(rows("a  b", 10, word), ["a  b"]);
+        // A word too long for any row still breaks where it overflows
+        assert_eq!(
+            rows("a supercalifragilistic b", 8, word),
+            ["a", "supercal", "ifragili", "stic b"]
+        );
+        assert_eq!(rows("one\n\ntwo", 10, word), ["one", "", "two"]);
+    }
+
+    #[test]
+    fn test_no_wrap_only_breaks_at_newlines() {
+        let none = Flow {
+            wrap: Wrap::None,
+            align: Align::Left,
+        };
+        assert_eq!(rows("much too long\nok", 4, none), ["much too long", "ok"]);
+    }
+
+    #[test]
+    fn test_align_lines_rows_up_within_the_block() {
+        let text = "the quick brown fox jumps";
+        let aligned = |align| {
+            rows(
+                text,
+                11,
+                Flow {
+                    align,
+                    ..Flow::default()
+                },
+            )
+        };
+
+        assert_eq!(aligned(Align::Left), ["the quick", "brown fox", "jumps"]);
+        assert_eq!(
+            aligned(Align::Center),
+            ["the quick", "brown fox", "  jumps"]
+        );
+        assert_eq!(
+            aligned(Align::Right),
+            ["the quick", "brown fox", "    jumps"]
+        );
+
+        // Justified rows fill the block, but a paragraph's last row doesn't
+        let justified = |text| {
+            rows(
+                text,
+                10,
+                Flow {
+                    align: Align::Justify,
+                    ..Flow::default()
+                },
+            )
+        };
+        assert_eq!(
+            justified("a bb ccc ddddddddd e f\nfin"),
+            ["a  bb ccc", "ddddddddd", "e f", "fin"]
+        );
+        assert_eq!(justified("aa b c dddddddddd"), ["aa   b   c", "dddddddddd"]);
+    }
+
+    #[test]
+    fn test_flow_lines_joins_and_rewraps_plain_text() {
+        let lines = vec![
+            "\x1b[31mred words\x1b[0m and".to_string(),
+            "more".to_string(),
+        ];
+        assert_eq!(
+            flow_lines(&lines, 9, Flow::default()),
+            ["red words", "and", "more"]
+        );
+    }
+
+    #[test]
+    fn test_boxing_puts_margin_border_and_pad_around_the_text() {
+        let boxing = Boxing {
+            margin: 1,
+            pad: 1,
+            border: Border::Rounded,
+        };
+        assert_eq!(boxing.inset(), 3);
+        assert_eq!(boxing.outer_size((4, 2)), (10, 8));
+
+        let lines = vec!["ab".to_string(), "\x1b[31mcde\x1b[0m".to_string()];
+        assert_eq!(
+            boxing.around(&lines, |glyphs| glyphs.to_string()),
+            [
+                "",
+                " ╭─────╮",
+                " │     │",
+                " │ ab  │",
+                " │ \x1b[31mcde\x1b[0m │",
+                " │     │",
+                " ╰─────╯",
+                "",
+            ]
+        );
+
+        // Without a border there's only the space around the text
+        let spaced = Boxing {
+            margin: 1,
+            ..Boxing::default()
+        };
+        assert!(spaced.edges((3, 1)).is_empty());
+        assert_eq!(
+            spaced.around(&["abc".to_string()], str::to_string),
+            ["", " abc", ""]
+        );
+        assert!(Boxing::default().is_empty());
+    }
+
+    #[test]
+    fn test_draw_leaves_out_what_is_off_screen() {
+        let boxing = Boxing {
+            border: Border::Ascii,
+            ..Boxing::default()
+        };
+        let mut out = Vec::new();
+        boxing.draw((1, 1), (0, 1), Color::White, &mut out).unwrap();
+        let drawn = String::from_utf8(out).unwrap();
+        // The top row would be above the screen, so only the sides and bottom are drawn
+        assert_eq!(drawn.matches("+-+").count(), 1);
+        assert_eq!(drawn.matches('|').count(), 2);
+    }
+}
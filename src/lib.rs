@@ -0,0 +1,12 @@
+//! Building blocks of the clifx effects for use outside the command line
+//! tool. For now that's the easing curves every animation runs on:
+//!
+//! ```
+//! use clifx::easing::EasingFunction;
+//!
+//! let easing = EasingFunction::parse("ease-in-out")?;
+//! assert_eq!(easing.apply(0.5), 0.5);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod easing;
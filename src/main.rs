@@ -1,34 +1,362 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::parser::ValueSource;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clifx::easing::{self, EasingFunction};
 use rand::Rng;
-use std::io::{self, BufRead, BufReader};
+use std::collections::{BTreeMap, VecDeque};
+use std::ffi::OsString;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthChar;
 
-mod effects;
+mod ansi;
+mod big_digits;
+mod capability;
 mod center;
-use effects::shine::{apply_shine_effect, EasingFunction, ShineConfig, ShineStart};
-use effects::shine2d::{apply_shine2d_effect, Shine2DConfig};
-use effects::twinkle::{
-    apply_twinkle_effect, EasingFunction as TwinkleEasingFunction, TwinkleConfig,
+mod clip;
+mod color;
+mod compare;
+mod config;
+mod contrast;
+#[cfg(unix)]
+mod control;
+mod effects;
+mod expr;
+mod fallbacks;
+mod figlet;
+mod gallery;
+mod graphics;
+mod html;
+mod input;
+mod json_frames;
+mod keep;
+mod layout;
+mod markup;
+mod mask;
+mod output;
+mod palette;
+mod pick;
+mod playback;
+mod profile;
+mod quality;
+mod resize;
+mod rng;
+mod screen;
+mod snapshot;
+mod soak;
+mod themes;
+mod throughput;
+mod vt;
+mod watch;
+use capability::{ColorMode, MonoCue};
+use center::{calculate_centering_offsets, strip_ansi_codes};
+use color::{parse_color, parse_color_list, Rgb};
+use compare::{CompareConfig, Side};
+use effects::blink::{self, apply_blink_effect, parse_pattern, BlinkConfig};
+use effects::bounce::{apply_bounce_effect, BounceConfig};
+use effects::compose::{apply_compose_effect, ComposeConfig};
+use effects::confetti::{apply_confetti_effect, ConfettiConfig, ConfettiOrigin};
+use effects::countdown::{
+    apply_countdown_effect, digit_rows, format_remaining, CountdownConfig, CountdownTransition,
+};
+use effects::countup::{apply_countup_effect, CountupConfig};
+use effects::density::{density_map, SpawnBias};
+use effects::dissolve::{apply_dissolve_effect, DissolveConfig, DissolveMode, DissolveOrder};
+use effects::easing_preview::{apply_easing_preview, EasingPreviewConfig};
+use effects::fade::{apply_fade_effect, FadeConfig, FadeMode};
+use effects::fire::{apply_fire_effect, FireConfig, FireMode, FirePalette};
+use effects::fireworks::{apply_fireworks_effect, FireworksConfig};
+use effects::glowworm::{apply_glowworm_effect, GlowwormConfig};
+use effects::gradient::{apply_gradient_effect, GradientConfig};
+use effects::heartbeat::{apply_heartbeat_effect, HeartbeatConfig};
+use effects::karaoke::{apply_karaoke_effect, load_timing, KaraokeConfig};
+use effects::marquee::{apply_marquee_effect, MarqueeConfig, MarqueeDirection};
+use effects::neon::{self, apply_neon_effect, NeonConfig};
+use effects::progress::{apply_progress_effect, ProgressConfig};
+use effects::pulse::{apply_pulse_effect, PulseConfig};
+use effects::scramble::{apply_scramble_effect, parse_charset, ScrambleConfig};
+use effects::shake::{self, apply_shake_effect, ShakeConfig};
+use effects::shine::{
+    apply_shine_all_lines, apply_shine_effect, ShineConfig, ShineMode, ShineStart,
+};
+use effects::shine2d::{apply_shine2d_effect, Shine2DConfig, Shine2DShape, ShineOrigin};
+use effects::slide::{apply_slide_effect, SlideConfig, SlideFrom};
+use effects::snow::{apply_snow_effect, SnowConfig};
+use effects::sparkle::{self, apply_sparkle_effect, SparkleConfig};
+use effects::spin::{self, run_spin, SpinConfig};
+use effects::spotlight::{apply_spotlight_effect, SpotlightConfig, SpotlightPath};
+use effects::sprite::Sprite;
+use effects::stage::rgb_color;
+use effects::topbar::{run_topbar, TopbarConfig, TopbarEffect};
+use effects::twinkle::{apply_twinkle_effect, TwinkleConfig};
+use effects::wave::{apply_wave_effect, WaveConfig};
+use effects::wipe::{apply_wipe_effect, WipeConfig, WipeDirection, WipeMode};
+use figlet::FigFont;
+use graphics::Backend;
+use input::{
+    read_files, spawn_line_reader, spawn_stamped_line_reader, ControlChars, InputFilter, Transform,
 };
-use center::calculate_centering_offsets;
+use layout::{Align, Border, Boxing, Flow, Wrap};
+use mask::{Mask, Region};
+use quality::Quality;
+use resize::Placement;
+use snapshot::Shell as SnapshotShell;
+use throughput::{Path, StaticStyle, Throughput};
 
 #[derive(Parser)]
 #[command(name = "clifx")]
 #[command(about = "CLI effects for text processing")]
+#[command(
+    after_help = "Numeric flags also take expressions over cols, rows, lines, chars, and longest (e.g. --width \"cols/10\")."
+)]
+// Later flags win, so flags typed after a preset name override the preset's
+#[command(args_override_self = true)]
 struct Cli {
     /// Clear screen and center output in terminal
     #[arg(long, global = true)]
     center: bool,
 
+    /// Center output across the terminal only, indenting it in the normal scrolling output
+    #[arg(long, global = true, conflicts_with = "center")]
+    center_h: bool,
+
+    /// Center output down the terminal only, drawing it from the left edge
+    #[arg(long, global = true, conflicts_with = "center")]
+    center_v: bool,
+
+    /// With --center or --center-v, draw over what's on the screen instead of clearing it
+    #[arg(long, global = true)]
+    no_clear: bool,
+
+    /// Text transforms applied before the effect, in order (comma-separated)
+    #[arg(long, global = true, value_enum, value_delimiter = ',')]
+    transform: Vec<Transform>,
+
+    /// Coordinated colors and flags for the effect, for any it doesn't set itself (default: the config's theme)
+    #[arg(long, global = true, value_parser = theme_names())]
+    theme: Option<String>,
+
+    /// Base colors for the input's lines in turn, repeating (e.g., "red,green,blue" or a palette name); the effect still animates across them all
+    #[arg(
+        long,
+        global = true,
+        value_name = "COLORS",
+        conflicts_with = "line_palette"
+    )]
+    line_colors: Option<String>,
+
+    /// Color the input's lines in turn with a built-in palette (e.g., pastel, neon, solarized, dracula).
+    /// Not --palette, which fire and confetti take for their own colors
+    #[arg(long, global = true, value_name = "NAME")]
+    line_palette: Option<String>,
+
+    /// Where the input wraps to fit the terminal (default: word for shine2d and gradient; other effects wrap only when this or --align is given, dropping the input's escape codes)
+    #[arg(long, global = true, value_enum, conflicts_with = "stream")]
+    wrap: Option<Wrap>,
+
+    /// How the input's wrapped rows line up with each other
+    #[arg(long, global = true, value_enum, conflicts_with = "stream")]
+    align: Option<Align>,
+
+    /// Blank columns and rows around the text, outside any --border
+    #[arg(long, global = true, value_name = "N", default_value_t = 0, conflicts_with_all = ["stream", "inline", "simple_tty"])]
+    margin: u16,
+
+    /// Blank columns and rows between the text and its --border
+    #[arg(long, global = true, value_name = "N", default_value_t = 0, conflicts_with_all = ["stream", "inline", "simple_tty"])]
+    pad: u16,
+
+    /// Draw a box around the text
+    #[arg(long, global = true, value_enum, default_value = "none", conflicts_with_all = ["stream", "inline", "simple_tty"])]
+    border: Border,
+
+    /// Color of the --border's lines
+    #[arg(
+        long,
+        global = true,
+        value_name = "COLOR",
+        default_value = "128,128,128"
+    )]
+    border_color: String,
+
+    /// How to show control characters (backspace, carriage return, BEL) in the input
+    #[arg(long, global = true, value_enum, default_value = "caret")]
+    control_chars: ControlChars,
+
+    /// Seed for all random behavior (twinkle positions, random colors) for reproducible output
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+
+    /// Snap highlight intensity to N discrete levels (e.g., 3 for a retro off/dim/bright glow)
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(2..))]
+    levels: Option<u32>,
+
+    /// Loop the effect for this many minutes while checking for resource leaks
+    #[arg(long, global = true, hide = true, value_name = "MINUTES")]
+    soak: Option<f64>,
+
+    /// Animate each stdin line as soon as it arrives (infinite cycles run once)
+    #[arg(long, global = true, conflicts_with_all = ["center", "center_v"])]
+    stream: bool,
+
+    /// With --stream, lines arriving faster than this per second are shown in the effect's resting colors instead of animated
+    #[arg(long, global = true, default_value = "10", value_name = "LINES")]
+    max_animated_rate: f64,
+
+    /// Animate on the terminal's alternate screen, restoring the previous screen afterward
+    #[arg(long, global = true)]
+    alt_screen: bool,
+
+    /// Draw the text as characters (ansi) or as images smoothed below the cell (sixel, kitty); images need block-character text such as --big banners
+    #[arg(long, global = true, value_enum, default_value = "ansi", conflicts_with_all = ["output", "center", "center_v", "alt_screen", "inline", "simple_tty", "stream"])]
+    backend: Backend,
+
+    /// What to write to stdout: escape codes for a terminal, or each frame as a line of JSON for other programs
+    #[arg(long, global = true, value_enum, default_value = "ansi", conflicts_with_all = ["alt_screen", "inline"])]
+    output: OutputFormat,
+
+    /// Animate this text instead of stdin, which is then left unread (newlines split it into lines); topbar shows it in its bar
+    #[arg(long, global = true, conflicts_with = "stream")]
+    text: Option<String>,
+
+    /// Animate the lines of this file instead of stdin; repeat to animate several, one after another. Only as many lines as fit the terminal are read
+    #[arg(long = "file", global = true, value_name = "PATH", conflicts_with_all = ["stream", "text"])]
+    input_file: Vec<PathBuf>,
+
+    /// With --text, read stdin as well and animate its lines after the text
+    #[arg(long, global = true, requires = "text")]
+    append_stdin: bool,
+
+    /// Repeat the animation until q or Ctrl-C, as --cycles 0 (or --duration 0) would; effects without those run again in place
+    #[arg(long = "loop", global = true, conflicts_with_all = ["stream", "soak", "control_socket"])]
+    loop_forever: bool,
+
+    /// Animate even when stdout isn't a terminal, instead of writing the text once in the effect's resting colors
+    #[arg(long, global = true)]
+    force_animation: bool,
+
+    /// Lay output out for a fixed WIDTHxHEIGHT terminal (e.g. 80x24) instead of the real one, so recordings come out the same anywhere
+    #[arg(long, global = true, value_name = "WxH", value_parser = screen::parse_size)]
+    virtual_size: Option<(u16, u16)>,
+
+    /// Keep running and take commands on this Unix socket: text, preset, trigger, or quit, one per line
+    #[arg(long, global = true, value_name = "PATH", conflicts_with_all = ["stream", "soak", "profile_out", "export_html", "export_ansi"])]
+    control_socket: Option<PathBuf>,
+
+    /// Start partway into the cycle (0.0 to 1.0), e.g. to stagger several panes
+    #[arg(long, global = true, default_value = "0.0")]
+    phase: f32,
+
+    /// Color depth to render with (auto detects from NO_COLOR/COLORTERM/TERM)
+    #[arg(long, global = true, value_enum, default_value = "auto")]
+    color_mode: ColorMode,
+
+    /// Draw without color, as NO_COLOR does; short for --color-mode mono
+    #[arg(long, global = true, conflicts_with = "color_mode")]
+    no_color: bool,
+
+    /// Animate only input that already has a foreground color (shine and twinkle), leaving plain text untouched
+    #[arg(long, global = true)]
+    capture_input_colors_as_mask: bool,
+
+    /// Animate only the cells marked in this file (shine and twinkle), e.g. one saved by pick --save
+    #[arg(long, global = true, value_name = "PATH")]
+    mask_file: Option<PathBuf>,
+
+    /// Animate only this rectangle of the input (shine and twinkle); repeat to add more
+    #[arg(long, global = true, value_name = "X,Y,WIDTH,HEIGHT")]
+    mask_region: Vec<Region>,
+
+    /// Render each input line as a banner in this FIGlet font (.flf)
+    #[arg(long, global = true, value_name = "PATH")]
+    font_file: Option<PathBuf>,
+
+    /// Render each input line as a banner in clifx's built-in block letters
+    #[arg(long, global = true, conflicts_with = "font_file")]
+    big: bool,
+
+    /// Write per-frame timings, bytes written, and the parameters used to this JSON file
+    #[arg(long, global = true, value_name = "FILE")]
+    profile_out: Option<PathBuf>,
+
+    /// Keep the last SECONDS of frames; send the process SIGUSR1 to save them as an asciicast clip
+    #[arg(long, global = true, value_name = "SECONDS")]
+    clip_buffer: Option<f64>,
+
+    /// Write a self-contained web page replaying the animation to this file
+    #[arg(long, global = true, value_name = "FILE")]
+    export_html: Option<PathBuf>,
+
+    /// Record the exact output with its timing to this file, as an asciicast that `clifx replay` plays back
+    #[arg(long, global = true, value_name = "FILE")]
+    export_ansi: Option<PathBuf>,
+
+    /// Frames per second to animate at, in place of the effect's --speed (milliseconds between frames)
+    #[arg(long, global = true, value_name = "FPS")]
+    fps: Option<f64>,
+
+    /// After the animation, tell stderr what was downgraded (colors, skipped frames, piped output) and why
+    #[arg(long, global = true)]
+    explain_fallbacks: bool,
+
+    /// Draw with only carriage returns, one line at a time, for serial consoles (shine and pulse): no cursor moves, hiding, or clears
+    #[arg(long, global = true, conflicts_with_all = ["center", "center_v", "alt_screen"])]
+    simple_tty: bool,
+
+    /// Draw a single line from the cursor's current column (shine, pulse, and twinkle), keeping what's before it and leaving the cursor after it
+    #[arg(long, global = true, conflicts_with_all = ["center", "center_h", "center_v", "alt_screen", "simple_tty", "stream"])]
+    inline: bool,
+
+    /// Detail heavy effects (fire, sparkle) render at; auto steps it down while frames run over budget
+    #[arg(long, global = true, value_enum, default_value = "high")]
+    quality: Quality,
+
+    /// After the animation, print its final frame again as plain lines that stay in the scrollback and logs
+    #[arg(long, global = true)]
+    keep_final: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
 
-#[derive(ValueEnum, Clone)]
-pub enum EasingType {
-    Linear,
-    EaseIn,
-    EaseOut,
-    EaseInOut,
+/// The easing to animate with: `easing` as named, or for `cubic-bezier`
+/// the curve through the control points `--bezier` gave.
+fn with_bezier(
+    easing: EasingFunction,
+    bezier: Option<EasingFunction>,
+) -> Result<EasingFunction, Box<dyn std::error::Error>> {
+    match (easing, bezier) {
+        (EasingFunction::CubicBezier(..), Some(curve)) => Ok(curve),
+        (EasingFunction::CubicBezier(..), None) => Err(
+            "--easing cubic-bezier needs its control points, e.g. --bezier 0.25,0.1,0.25,1.0"
+                .into(),
+        ),
+        (_, Some(_)) => Err("--bezier only applies with --easing cubic-bezier".into()),
+        (easing, None) => Ok(easing),
+    }
+}
+
+fn parse_bezier(value: &str) -> Result<EasingFunction, String> {
+    EasingFunction::cubic_bezier(value).map_err(|err| err.to_string())
+}
+
+fn parse_angle_sweep(value: &str) -> Result<(f32, f32), String> {
+    let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+    match parts[..] {
+        [start, end] => match (start.parse::<f32>(), end.parse::<f32>()) {
+            (Ok(start), Ok(end)) if start.is_finite() && end.is_finite() => Ok((start, end)),
+            _ => Err(format!("'{value}' isn't two angles in degrees like 0,180")),
+        },
+        _ => Err(format!("expected START,END, got '{value}'")),
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// Text and escape codes a terminal draws
+    Ansi,
+    /// One JSON object per frame: its time and every cell's character, colors, and attributes
+    JsonFrames,
 }
 
 #[derive(ValueEnum, Clone)]
@@ -37,393 +365,4122 @@ pub enum StartType {
     End,
 }
 
-#[derive(Subcommand)]
+#[derive(ValueEnum, Clone)]
+pub enum ModeType {
+    /// One band sweeping end to end
+    Single,
+    /// Two mirrored bands emanating from the center
+    Split,
+}
+
+#[derive(Subcommand, Clone)]
 enum Commands {
+    /// Show text as a banner: big letters in a gradient, boxed and centered, with a shine sweeping over them (clifx banner "Release v2.0"); --font-file, --border, and the centering flags override each part
+    Banner(BannerArgs),
     /// Apply shine effect to stdin
-    Shine {
-        /// Base color as RGB values (e.g., "255,255,0" for yellow)
-        #[arg(long)]
-        color: Option<String>,
-
-        /// Animation speed in milliseconds between frames
-        #[arg(long, default_value = "100")]
-        speed: u64,
-
-        /// Easing function for the shine animation
-        #[arg(long, value_enum, default_value = "linear")]
-        easing: EasingType,
-
-        /// Duration of one complete cycle in milliseconds
-        #[arg(long, default_value = "2000")]
-        duration: u64,
-
-        /// Number of complete back-and-forth cycles (0 for infinite)
-        #[arg(long, default_value = "1")]
-        cycles: u32,
-
-        /// Starting direction of the shine effect
-        #[arg(long, value_enum, default_value = "beginning")]
-        start: StartType,
-
-        /// Width of the shine effect in characters
-        #[arg(long, default_value = "2")]
-        width: usize,
-
-        /// Enable blur effect for gradual highlighting
-        #[arg(long, default_value = "true")]
-        blur: bool,
-
-        /// Padding to extend shine position past text boundaries
-        #[arg(long, default_value = "5")]
-        padding: usize,
-
-        /// Shine color as RGB values (e.g., "255,255,255" for white)
-        #[arg(long, default_value = "255,255,255")]
-        shine_color: String,
-
-        /// Length of pause in milliseconds (disabled if not specified)
-        #[arg(long)]
-        pause_length: Option<u64>,
-
-        /// Position where shine pauses (0.0 to 1.0, where 0.5 is center)
-        #[arg(long, default_value = "0.5")]
-        pause_position: f32,
-
-        /// Delay before each cycle starts in milliseconds (disabled if not specified)
-        #[arg(long)]
-        cycle_pre_delay: Option<u64>,
-
-        /// Delay after each cycle completes in milliseconds (disabled if not specified)
-        #[arg(long)]
-        cycle_post_delay: Option<u64>,
-
-        /// Delay when the shine changes direction (switchback) in milliseconds (disabled if not specified)
-        #[arg(long)]
-        cycle_switchback_delay: Option<u64>,
-
-        /// Opacity of the shine effect (0.0 to 1.0, where 1.0 is full opacity)
-        #[arg(long, default_value = "1.0")]
-        opacity: f32,
-    },
+    Shine(ShineArgs),
     /// Apply 2D shine effect to stdin with angle control and word wrapping
-    Shine2d {
-        /// Base color as RGB values (e.g., "255,255,0" for yellow)
-        #[arg(long)]
-        color: Option<String>,
-
-        /// Animation speed in milliseconds between frames
-        #[arg(long, default_value = "50")]
-        speed: u64,
-
-        /// Easing function for the shine animation
-        #[arg(long, value_enum, default_value = "linear")]
-        easing: EasingType,
-
-        /// Duration of one complete cycle in milliseconds
-        #[arg(long, default_value = "2000")]
-        duration: u64,
-
-        /// Number of complete back-and-forth cycles (0 for infinite)
-        #[arg(long, default_value = "1")]
-        cycles: u32,
-
-        /// Starting direction of the shine effect
-        #[arg(long, value_enum, default_value = "beginning")]
-        start: StartType,
-
-        /// Width of the shine effect in characters
-        #[arg(long, default_value = "3")]
-        width: usize,
-
-        /// Enable blur effect for gradual highlighting
-        #[arg(long, default_value = "true")]
-        blur: bool,
-
-        /// Padding to extend shine position past text boundaries
-        #[arg(long, default_value = "5")]
-        padding: usize,
-
-        /// Shine color as RGB values (e.g., "255,255,0" for yellow)
-        #[arg(long, default_value = "255,255,0")]
-        shine_color: String,
-
-        /// Length of pause in milliseconds (disabled if not specified)
-        #[arg(long)]
-        pause_length: Option<u64>,
-
-        /// Position where shine pauses (0.0 to 1.0, where 0.5 is center)
-        #[arg(long, default_value = "0.5")]
-        pause_position: f32,
-
-        /// Delay before each cycle starts in milliseconds (disabled if not specified)
-        #[arg(long)]
-        cycle_pre_delay: Option<u64>,
-
-        /// Delay after each cycle completes in milliseconds (disabled if not specified)
-        #[arg(long)]
-        cycle_post_delay: Option<u64>,
-
-        /// Delay when the shine changes direction (switchback) in milliseconds (disabled if not specified)
-        #[arg(long)]
-        cycle_switchback_delay: Option<u64>,
-
-        /// Opacity of the shine effect (0.0 to 1.0, where 1.0 is full opacity)
-        #[arg(long, default_value = "1.0")]
-        opacity: f32,
-
-        /// Angle of the shine line in degrees (0=horizontal, 90=vertical, 45=diagonal)
-        #[arg(long, default_value = "90.0")]
-        angle: f32,
-
-        /// Terminal width for word wrapping (auto-detected if not specified)
-        #[arg(long)]
-        terminal_width: Option<usize>,
-    },
-    /// Apply twinkle effect to stdin (animates periods with twinkling stars)
-    Twinkle {
-        /// Base color as RGB values (e.g., "255,255,255" for white)
-        #[arg(long, default_value = "255,255,255")]
-        base_color: String,
-
-        /// Twinkle color as RGB values (e.g., "255,255,0" for yellow)
-        #[arg(long, default_value = "255,255,0")]
-        twinkle_color: String,
-
-        /// Animation speed in milliseconds between frames
-        #[arg(long, default_value = "100")]
-        speed: u64,
-
-        /// Easing function for the twinkle animation
-        #[arg(long, value_enum, default_value = "linear")]
-        easing: EasingType,
-
-        /// Duration of one complete cycle in milliseconds
-        #[arg(long, default_value = "3000")]
-        duration: u64,
-
-        /// Number of complete cycles (0 for infinite)
-        #[arg(long, default_value = "1")]
-        cycles: u32,
-
-        /// Ratio of periods to twinkle simultaneously (0.0 to 1.0)
-        #[arg(long, default_value = "0.3")]
-        twinkle_ratio: f32,
-
-        /// Minimum number of periods to twinkle at once
-        #[arg(long)]
-        min_twinkle_count: Option<usize>,
-
-        /// Maximum number of periods to twinkle at once  
-        #[arg(long)]
-        max_twinkle_count: Option<usize>,
-
-        /// Percentage of time twinkling should be active (0.0 to 1.0)
-        #[arg(long, default_value = "0.8")]
-        twinkling_percentage: f32,
-
-        /// Enable star mode using star characters instead of dots
-        #[arg(long)]
-        star_mode: bool,
+    Shine2d(Shine2dArgs),
+    /// Color stdin with a multi-stop gradient, optionally sliding over time
+    Gradient(GradientArgs),
+    /// Apply twinkle effect to stdin (animates periods, or chosen targets, with twinkling stars)
+    Twinkle(TwinkleArgs),
+    /// Fade the whole text between a dim and a bright version of its color
+    Pulse(PulseArgs),
+    /// Blink the text on and off, or between two colors, never faster than five times a second
+    #[command(visible_alias = "strobe")]
+    Blink(BlinkArgs),
+    /// Pulse the text to a heartbeat: two quick thumps of brightness, then a rest
+    Heartbeat(HeartbeatArgs),
+    /// Highlight the text a word at a time, like lyrics on a karaoke screen
+    Karaoke(KaraokeArgs),
+    /// Fade text in from the background, out to it, or both
+    Fade(FadeArgs),
+    /// Reveal or hide the text behind an edge that sweeps across it, as an entrance or exit
+    Wipe(WipeArgs),
+    /// Pop the characters in, or out, one at a time until the text is whole or gone
+    Dissolve(DissolveArgs),
+    /// Slide the text into place from past one edge of the screen
+    Slide(SlideArgs),
+    /// Drop the text from above the screen and let it bounce until it settles, on the alternate screen
+    Bounce(BounceArgs),
+    /// Shake the text from side to side, dying down, to call attention to an error or alert
+    #[command(visible_alias = "earthquake")]
+    Shake(ShakeArgs),
+    /// Send a short bright glowworm crawling through the text in reading order, leaving a fading trail
+    #[command(visible_alias = "snake")]
+    Glowworm(GlowwormArgs),
+    /// Move characters up and down in a sine wave that travels along each line
+    Wave(WaveArgs),
+    /// Dim the text and sweep a bright round spotlight across it
+    Spotlight(SpotlightArgs),
+    /// Cycle every character through random glyphs until it locks into place, left to right
+    Scramble(ScrambleArgs),
+    /// Burn a demoscene-style fire up through the text or in rows below it
+    Fire(FireArgs),
+    /// Scatter short-lived star sparkles over and around the text, drifting as they fade
+    Sparkle(SparkleArgs),
+    /// Light the text up like a neon sign, glowing onto the cells around it, buzzing, and flickering now and then
+    Neon(NeonArgs),
+    /// Let snow fall down the whole terminal over the text, which sits in the middle of it
+    Snow(SnowArgs),
+    /// Fire a burst of confetti that falls with gravity while the text stays highlighted
+    Confetti(ConfettiArgs),
+    /// Launch rockets that burst into fading sparks across the whole terminal
+    Fireworks(FireworksArgs),
+    /// Scroll text sideways through a fixed-width window, looping it seamlessly
+    Marquee(MarqueeArgs),
+    /// Animate a bar in the top rows of the terminal while stdin passes through to stdout below it
+    ///
+    /// --text sets the label shown in the bar.
+    Topbar(TopbarArgs),
+    /// Animate spans marked in stdin with {{fx:NAME}}text{{/fx}}, each with its own effect
+    ///
+    /// NAME is pulse, shine, twinkle, or rainbow, optionally followed by
+    /// color=COLOR, delay=MS, or duration=MS (e.g., {{fx:shine delay=500}}).
+    /// Other effects strip the markers and show the text plainly.
+    Compose(ComposeArgs),
+    /// Plot an easing curve and animate a dot along a ruler with it (reads no input)
+    EasingPreview(EasingPreviewArgs),
+    /// Show an elapsed-time stopwatch in big digits; each stdin line records a lap
+    Countup(CountupArgs),
+    /// Draw a shining progress bar from stdin: each line is a percentage (42 or 42%) or current/total (3/10), optionally followed by a label
+    Progress(ProgressArgs),
+    /// Run a command with a spinner and a shining label above its output, ending in green or red by how it went
+    ///
+    /// The command's stdout and stderr stream in below the label, e.g.
+    /// clifx spin --label Building -- cargo build. clifx exits with the
+    /// command's own status, so scripts can still check it.
+    Spin(SpinArgs),
+    /// Count down in big digits, each second arriving with a transition, ending on a flash (reads no input)
+    Countdown(CountdownArgs),
+    /// Run two effect configurations side by side on the same input
+    ///
+    /// Each side is an effect and its flags as they would follow `clifx`
+    /// (e.g., compare --left "shine --easing linear" --right "shine --easing
+    /// ease-in-out"). Both sides share a seed, so random choices match.
+    Compare(CompareArgs),
+    /// Pick cells of the input to animate, with the keyboard or by dragging the mouse
+    ///
+    /// Prints the --mask-region flags that limit shine and twinkle to the
+    /// picked rectangles (e.g. clifx shine $(clifx pick < banner.txt) <
+    /// banner.txt). The picker draws on stderr, so stdout can be captured.
+    Pick(PickArgs),
+    /// Play back a recording saved with --export-ansi, or a --clip-buffer clip
+    Replay(ReplayArgs),
+    /// Keep a banner on the alternate screen that other programs change over a Unix socket
+    ///
+    /// Takes the --control-socket commands, one per line (e.g. echo 'text
+    /// Deploying' | nc -U /tmp/clifx.sock): text, effect, color, preset,
+    /// trigger, and stop. It starts on --text, or nothing, with the effect
+    /// that follows, as it would follow `clifx` (shine --cycles 0 if none).
+    Daemon(DaemonArgs),
+    /// Print one still frame of an effect, in color, for shell prompts
+    ///
+    /// Nothing moves and the cursor is left alone, so the output can go in
+    /// PS1 or a starship custom segment, e.g. PS1='$(clifx --text "\w"
+    /// snapshot --shell bash gradient) '. The effect and its own flags
+    /// follow, as they would follow `clifx`.
+    Snapshot(SnapshotArgs),
+    /// Run an effect again whenever the text it shows changes
+    ///
+    /// Watches the --file files, or reruns a --run command every interval
+    /// for its output, e.g. clifx watch --file motd.txt shine --cycles 0.
+    /// The effect and its own flags follow, as they would follow `clifx`.
+    /// On a terminal each run starts on a cleared screen.
+    Watch(WatchArgs),
+    /// Run a named preset from ~/.config/clifx/config.toml (clifx preset <name> [flags...])
+    #[command(arg_required_else_help = true)]
+    Preset {
+        #[command(subcommand)]
+        action: PresetAction,
     },
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
-    
-    // Read all input first
-    let stdin = io::stdin();
-    let reader = BufReader::new(stdin.lock());
-    let mut input_lines = Vec::new();
-    
-    for line in reader.lines() {
-        input_lines.push(line?);
-    }
-    
-    // Calculate centering offsets if needed
-    let centering_offsets = if cli.center {
-        let offsets = calculate_centering_offsets(&input_lines)?;
-        Some((offsets.top, offsets.left))
-    } else {
-        None
-    };
+#[derive(Args, Clone)]
+struct BannerArgs {
+    /// The banner's text (default: --text, --file, or stdin)
+    #[arg(value_name = "TEXT")]
+    message: Option<String>,
+
+    /// Comma-separated colors across the letters, or a built-in palette such as "sunset"
+    #[arg(long, default_value = "sunset")]
+    stops: String,
+
+    /// Shine color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    shine_color: String,
+
+    /// Width of the shine in characters
+    #[arg(long, default_value = "6")]
+    width: usize,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "40")]
+    speed: u64,
+
+    /// Duration of one sweep there and back in milliseconds
+    #[arg(long, default_value = "2500")]
+    duration: u64,
+
+    /// Number of sweeps (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+
+    /// Keep the text as it is rather than drawing it in big letters
+    #[arg(long, conflicts_with_all = ["font_file", "big"])]
+    plain: bool,
+
+    /// Leave the banner where the cursor is rather than centering it on the screen
+    #[arg(long, conflicts_with_all = ["center", "center_h", "center_v"])]
+    no_center: bool,
+}
+
+#[derive(Args, Clone)]
+struct ShineArgs {
+    /// Base color as RGB, hex, name, or hsl() (e.g., "255,255,0" or "yellow")
+    #[arg(long)]
+    color: Option<String>,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "100")]
+    speed: u64,
+
+    /// Easing function for the shine animation
+    #[arg(long, value_enum, default_value = "linear")]
+    easing: EasingFunction,
+
+    /// Control points X1,Y1,X2,Y2 of the curve for --easing cubic-bezier, as in CSS
+    #[arg(long, value_name = "X1,Y1,X2,Y2", value_parser = parse_bezier)]
+    bezier: Option<EasingFunction>,
+
+    /// Duration of one complete cycle in milliseconds
+    #[arg(long, default_value = "2000")]
+    duration: u64,
+
+    /// Number of complete back-and-forth cycles (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+
+    /// Starting direction of the shine effect
+    #[arg(long, value_enum, default_value = "beginning")]
+    start: StartType,
+
+    /// Begin each cycle from the opposite end of the previous one
+    #[arg(long)]
+    alternate_direction: bool,
+
+    /// Width of the shine effect in characters
+    #[arg(long, default_value = "2")]
+    width: usize,
+
+    /// Enable blur effect for gradual highlighting
+    #[arg(long, default_value = "true")]
+    blur: bool,
+
+    /// Padding to extend shine position past text boundaries
+    #[arg(long, default_value = "5")]
+    padding: usize,
+
+    /// Shine color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    shine_color: String,
+
+    /// Comma-separated colors across the band from its left edge to its right, in place of --shine-color (e.g., "white,gold,orange"); shows best with --blur
+    #[arg(long, value_name = "COLORS")]
+    shine_gradient: Option<String>,
+
+    /// Length of pause in milliseconds (disabled if not specified)
+    #[arg(long)]
+    pause_length: Option<u64>,
+
+    /// Position where shine pauses (0.0 to 1.0, where 0.5 is center)
+    #[arg(long, default_value = "0.5")]
+    pause_position: f32,
+
+    /// Delay before each cycle starts in milliseconds (disabled if not specified)
+    #[arg(long)]
+    cycle_pre_delay: Option<u64>,
+
+    /// Delay after each cycle completes in milliseconds (disabled if not specified)
+    #[arg(long)]
+    cycle_post_delay: Option<u64>,
+
+    /// Delay when the shine changes direction (switchback) in milliseconds (disabled if not specified)
+    #[arg(long)]
+    cycle_switchback_delay: Option<u64>,
+
+    /// Opacity of the shine effect (0.0 to 1.0, where 1.0 is full opacity)
+    #[arg(long, default_value = "1.0")]
+    opacity: f32,
+
+    /// Minimum WCAG contrast ratio between the shine peak and the color beneath it: the base
+    /// color, or the input's or --line-colors' own (e.g., 3.0)
+    #[arg(long)]
+    min_contrast: Option<f32>,
+
+    /// Animate all lines together in one frame loop instead of one after another
+    #[arg(long)]
+    all_lines: bool,
+
+    /// Delay in milliseconds between successive lines' shine (requires --all-lines)
+    #[arg(long, requires = "all_lines")]
+    line_stagger: Option<u64>,
+
+    /// Shine mode: a single band, or two mirrored bands from the center outward
+    #[arg(long, value_enum, default_value = "single")]
+    mode: ModeType,
+
+    /// In split mode, reflect the bands back to the center after reaching the ends
+    #[arg(long)]
+    reflect: bool,
+
+    /// Terminal width; longer lines pan to follow the shine (auto-detected if not specified)
+    #[arg(long)]
+    terminal_width: Option<usize>,
+}
+
+#[derive(Args, Clone)]
+struct Shine2dArgs {
+    /// Base color as RGB, hex, name, or hsl() (e.g., "255,255,0" or "yellow")
+    #[arg(long)]
+    color: Option<String>,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+
+    /// Easing function for the shine animation
+    #[arg(long, value_enum, default_value = "linear")]
+    easing: EasingFunction,
+
+    /// Control points X1,Y1,X2,Y2 of the curve for --easing cubic-bezier, as in CSS
+    #[arg(long, value_name = "X1,Y1,X2,Y2", value_parser = parse_bezier)]
+    bezier: Option<EasingFunction>,
+
+    /// Duration of one complete cycle in milliseconds
+    #[arg(long, default_value = "2000")]
+    duration: u64,
+
+    /// Number of complete back-and-forth cycles (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+
+    /// Starting direction of the shine effect
+    #[arg(long, value_enum, default_value = "beginning")]
+    start: StartType,
+
+    /// Begin each cycle from the opposite end of the previous one
+    #[arg(long)]
+    alternate_direction: bool,
+
+    /// Width of the shine effect in characters
+    #[arg(long, default_value = "3")]
+    width: usize,
+
+    /// Enable blur effect for gradual highlighting
+    #[arg(long, default_value = "true")]
+    blur: bool,
+
+    /// Padding to extend shine position past text boundaries
+    #[arg(long, default_value = "5")]
+    padding: usize,
+
+    /// Shine color as RGB, hex, name, or hsl() (e.g., "255,255,0" or "yellow")
+    #[arg(long, default_value = "255,255,0")]
+    shine_color: String,
+
+    /// Length of pause in milliseconds (disabled if not specified)
+    #[arg(long)]
+    pause_length: Option<u64>,
+
+    /// Position where shine pauses (0.0 to 1.0, where 0.5 is center)
+    #[arg(long, default_value = "0.5")]
+    pause_position: f32,
+
+    /// Delay before each cycle starts in milliseconds (disabled if not specified)
+    #[arg(long)]
+    cycle_pre_delay: Option<u64>,
+
+    /// Delay after each cycle completes in milliseconds (disabled if not specified)
+    #[arg(long)]
+    cycle_post_delay: Option<u64>,
+
+    /// Delay when the shine changes direction (switchback) in milliseconds (disabled if not specified)
+    #[arg(long)]
+    cycle_switchback_delay: Option<u64>,
+
+    /// Opacity of the shine effect (0.0 to 1.0, where 1.0 is full opacity)
+    #[arg(long, default_value = "1.0")]
+    opacity: f32,
+
+    /// Minimum WCAG contrast ratio between the shine peak and the base color (e.g., 3.0)
+    #[arg(long)]
+    min_contrast: Option<f32>,
+
+    /// Angle of the shine line in degrees (0=horizontal, 90=vertical, 45=diagonal)
+    #[arg(long, default_value = "90.0")]
+    angle: f32,
+
+    /// Turn the shine line from one angle to another over each cycle, as START,END in degrees
+    #[arg(long, value_name = "START,END", value_parser = parse_angle_sweep, conflicts_with = "rotate")]
+    angle_sweep: Option<(f32, f32)>,
+
+    /// Turn the shine line a full circle each cycle, starting from --angle
+    #[arg(long)]
+    rotate: bool,
+
+    /// Shape of the shine: a sweeping line, or rings spreading out from --origin
+    #[arg(long, value_enum, default_value = "line")]
+    shape: Shine2DShape,
+
+    /// Where radial and ripple shines spread out from: center, or a column and row as x,y
+    #[arg(long, default_value = "center", value_parser = ShineOrigin::parse)]
+    origin: ShineOrigin,
+
+    /// Terminal width for word wrapping (auto-detected if not specified)
+    #[arg(long)]
+    terminal_width: Option<usize>,
+}
+
+#[derive(Args, Clone)]
+struct GradientArgs {
+    /// Comma-separated color stops in any color format (e.g., "red,orange,yellow"),
+    /// or a built-in palette such as "sunset"
+    #[arg(long, default_value = "red,orange,yellow")]
+    stops: String,
+
+    /// Show the input at rest in every built-in palette and saved preset, to pick a look
+    #[arg(long, conflicts_with_all = ["animate", "stream", "center", "center_h", "center_v"])]
+    preview_all_palettes: bool,
+
+    /// Direction of the gradient in degrees (0=left to right, 90=top to bottom, 45=diagonal)
+    #[arg(long, default_value = "0.0")]
+    angle: f32,
+
+    /// Slide the gradient across the text instead of drawing it once
+    #[arg(long)]
+    animate: bool,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+
+    /// Duration of one full slide in milliseconds
+    #[arg(long, default_value = "2000")]
+    duration: u64,
+
+    /// Number of slides when animating (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+
+    /// Terminal width for word wrapping (auto-detected if not specified)
+    #[arg(long)]
+    terminal_width: Option<usize>,
+}
+
+#[derive(Args, Clone)]
+struct TwinkleArgs {
+    /// Base color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    base_color: String,
+
+    /// Twinkle color as RGB, hex, name, or hsl() (e.g., "255,255,0" or "yellow")
+    #[arg(long, default_value = "255,255,0")]
+    twinkle_color: String,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "100")]
+    speed: u64,
+
+    /// Easing function for the twinkle animation
+    #[arg(long, value_enum, default_value = "linear")]
+    easing: EasingFunction,
+
+    /// Control points X1,Y1,X2,Y2 of the curve for --easing cubic-bezier, as in CSS
+    #[arg(long, value_name = "X1,Y1,X2,Y2", value_parser = parse_bezier)]
+    bezier: Option<EasingFunction>,
+
+    /// Duration of one complete cycle in milliseconds
+    #[arg(long, default_value = "3000")]
+    duration: u64,
+
+    /// Number of complete cycles (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+
+    /// Ratio of periods to twinkle simultaneously (0.0 to 1.0)
+    #[arg(long, default_value = "0.3")]
+    twinkle_ratio: f32,
+
+    /// Minimum number of periods to twinkle at once
+    #[arg(long)]
+    min_twinkle_count: Option<usize>,
+
+    /// Maximum number of periods to twinkle at once  
+    #[arg(long)]
+    max_twinkle_count: Option<usize>,
+
+    /// Percentage of time twinkling should be active (0.0 to 1.0)
+    #[arg(long, default_value = "0.8")]
+    twinkling_percentage: f32,
+
+    /// Enable star mode using star characters instead of dots
+    #[arg(long)]
+    star_mode: bool,
+
+    /// Characters to twinkle (e.g., "*•"); defaults to "." unless --target-regex is given
+    #[arg(long)]
+    target_chars: Option<String>,
+
+    /// Regular expression whose matches should twinkle (e.g., "[*+]")
+    #[arg(long)]
+    target_regex: Option<String>,
+
+    /// Animate each twinkle through these glyphs at FPS frames per second (e.g., "+x*x@12"),
+    /// or use a sprite named in the config's [sprites]; repeat to mix sprites
+    #[arg(long = "sprite", value_name = "GLYPHS[@FPS]")]
+    sprites: Vec<String>,
+
+    /// Make twinkles likelier among dense glyphs, so they cluster on ASCII art,
+    /// or in sparse areas around it
+    #[arg(long, value_enum, default_value = "uniform")]
+    spawn_bias: SpawnBias,
+}
+
+#[derive(Args, Clone)]
+struct PulseArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Brightness at the dimmest point, from 0.0 (black) to 1.0 (full color)
+    #[arg(long, default_value = "0.2")]
+    min_brightness: f32,
+
+    /// Brightness at the brightest point, from 0.0 (black) to 1.0 (full color)
+    #[arg(long, default_value = "1.0")]
+    max_brightness: f32,
+
+    /// Length of one dim-bright-dim breath in milliseconds
+    #[arg(long, default_value = "2000")]
+    period: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+
+    /// Easing function for fading in and out
+    #[arg(long, value_enum, default_value = "ease-in-out")]
+    easing: EasingFunction,
+
+    /// Control points X1,Y1,X2,Y2 of the curve for --easing cubic-bezier, as in CSS
+    #[arg(long, value_name = "X1,Y1,X2,Y2", value_parser = parse_bezier)]
+    bezier: Option<EasingFunction>,
+
+    /// Number of breaths (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+}
+
+#[derive(Args, Clone)]
+struct BlinkArgs {
+    /// Text color while it's on, as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Text color while it's off (default: hidden)
+    #[arg(long)]
+    off_color: Option<String>,
+
+    /// Milliseconds the text stays on, or a dot lasts with --pattern (at least 100)
+    #[arg(long, default_value = "500")]
+    on_ms: u64,
+
+    /// Milliseconds the text stays off, or the gap after a dot or dash lasts with --pattern (at least 100)
+    #[arg(long, default_value = "500")]
+    off_ms: u64,
+
+    /// Blink in Morse code: text to spell out (e.g., "SOS"), or dots and dashes ("... --- ...") with / between words
+    #[arg(long)]
+    pattern: Option<String>,
+
+    /// Number of blinks, or times through --pattern (0 for infinite)
+    #[arg(long, default_value = "3")]
+    cycles: u32,
+
+    /// At most three flashes a second, dimming the text rather than hiding it, for viewers sensitive to flashing
+    #[arg(long)]
+    photosensitive_safe: bool,
+}
+
+#[derive(Args, Clone)]
+struct HeartbeatArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "220,20,60" or "crimson")
+    #[arg(long, default_value = "220,20,60")]
+    color: String,
+
+    /// Brightness between beats, from 0.0 (black) to 1.0 (full color)
+    #[arg(long, default_value = "0.3")]
+    min_brightness: f32,
+
+    /// Beats per minute
+    #[arg(long, default_value = "72", value_parser = clap::value_parser!(u32).range(20..=240))]
+    bpm: u32,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "20")]
+    speed: u64,
+
+    /// Number of beats (0 for infinite)
+    #[arg(long, default_value = "3")]
+    cycles: u32,
+
+    /// Append a heart to the text that swells with each thump
+    #[arg(long)]
+    heart: bool,
+
+    /// Color of the heart (default: the text's color)
+    #[arg(long, requires = "heart")]
+    heart_color: Option<String>,
+}
+
+#[derive(Args, Clone)]
+struct KaraokeArgs {
+    /// Color of the words still to come, as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "128,128,128")]
+    color: String,
+
+    /// Color of the word being sung
+    #[arg(long, default_value = "255,220,0")]
+    highlight: String,
+
+    /// Color of the words already sung (default: the highlight)
+    #[arg(long)]
+    sung_color: Option<String>,
+
+    /// Words per minute
+    #[arg(long, default_value = "160", value_parser = clap::value_parser!(u32).range(1..=6000))]
+    wpm: u32,
+
+    /// File of word:ms lines, one per word of the text in order, giving how long each is highlighted, to sync with audio
+    #[arg(long, value_name = "PATH")]
+    timing_file: Option<PathBuf>,
+}
+
+#[derive(Args, Clone)]
+struct FadeArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Color to fade from and to (default: the terminal background from COLORFGBG, else black)
+    #[arg(long)]
+    from_color: Option<String>,
+
+    /// Fade in, fade out, or fade in and then out
+    #[arg(long, value_enum, default_value = "in")]
+    mode: FadeMode,
+
+    /// Milliseconds for each character to fade in or out
+    #[arg(long, default_value = "1000")]
+    duration: u64,
+
+    /// Milliseconds each character starts after the one before it (0 fades all together)
+    #[arg(long, default_value = "0")]
+    stagger: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+
+    /// Easing function for the fade
+    #[arg(long, value_enum, default_value = "linear")]
+    easing: EasingFunction,
+
+    /// Control points X1,Y1,X2,Y2 of the curve for --easing cubic-bezier, as in CSS
+    #[arg(long, value_name = "X1,Y1,X2,Y2", value_parser = parse_bezier)]
+    bezier: Option<EasingFunction>,
+}
+
+#[derive(Args, Clone)]
+struct WipeArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Which way the edge moves
+    #[arg(long, value_enum, default_value = "right")]
+    direction: WipeDirection,
+
+    /// Reveal the text, hide it, or reveal and then hide it
+    #[arg(long, value_enum, default_value = "in")]
+    mode: WipeMode,
+
+    /// Lead the edge with a band of this color
+    #[arg(long)]
+    band_color: Option<String>,
+
+    /// Columns the band spans (rows, wiping up or down)
+    #[arg(long, default_value = "2", requires = "band_color", value_parser = clap::value_parser!(u16).range(1..=20))]
+    band_width: u16,
+
+    /// Milliseconds for the edge to cross the text (once each way with --mode inout)
+    #[arg(long, default_value = "800")]
+    duration: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "30")]
+    speed: u64,
+
+    /// Easing function for the edge's movement
+    #[arg(long, value_enum, default_value = "linear")]
+    easing: EasingFunction,
+
+    /// Control points X1,Y1,X2,Y2 of the curve for --easing cubic-bezier, as in CSS
+    #[arg(long, value_name = "X1,Y1,X2,Y2", value_parser = parse_bezier)]
+    bezier: Option<EasingFunction>,
+}
+
+#[derive(Args, Clone)]
+struct DissolveArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Pop characters in, out, or in and then out
+    #[arg(long, value_enum, default_value = "in")]
+    mode: DissolveMode,
+
+    /// Which characters pop first
+    #[arg(long, value_enum, default_value = "random")]
+    order: DissolveOrder,
+
+    /// Milliseconds for every character to pop (once each way with --mode inout)
+    #[arg(long, default_value = "1000")]
+    duration: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "30")]
+    speed: u64,
+
+    /// Easing function for the rate characters pop at
+    #[arg(long, value_enum, default_value = "linear")]
+    easing: EasingFunction,
+
+    /// Control points X1,Y1,X2,Y2 of the curve for --easing cubic-bezier, as in CSS
+    #[arg(long, value_name = "X1,Y1,X2,Y2", value_parser = parse_bezier)]
+    bezier: Option<EasingFunction>,
+}
+
+#[derive(Args, Clone)]
+struct SlideArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Edge the text comes in from (top and bottom mean the screen's with --center, else its own rows')
+    #[arg(long, value_enum, default_value = "left")]
+    from: SlideFrom,
+
+    /// Milliseconds for the text to reach its place
+    #[arg(long, default_value = "800")]
+    duration: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "20")]
+    speed: u64,
+
+    /// Easing function for the slide
+    #[arg(long, value_enum, default_value = "ease-out")]
+    easing: EasingFunction,
+
+    /// Control points X1,Y1,X2,Y2 of the curve for --easing cubic-bezier, as in CSS
+    #[arg(long, value_name = "X1,Y1,X2,Y2", value_parser = parse_bezier)]
+    bezier: Option<EasingFunction>,
+
+    /// How far the text swings past its place before bouncing back (0 for none, 1.7 for a lively one)
+    #[arg(long, default_value = "0")]
+    overshoot: f32,
+}
+
+#[derive(Args, Clone)]
+struct BounceArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// How fast the text speeds up as it falls, in rows per second every second
+    #[arg(long, default_value = "60")]
+    gravity: f32,
+
+    /// Share of its speed the text loses on each bounce, from 0.0 (none) to 1.0 (all)
+    #[arg(long, default_value = "0.45")]
+    damping: f32,
+
+    /// Drop each character on its own, a moment apart
+    #[arg(long)]
+    per_char: bool,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "20")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct ShakeArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "255,0,0" or "red")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Columns the text jumps at most to either side at the start
+    #[arg(long, default_value = "2", value_parser = clap::value_parser!(u16).range(1..=10))]
+    amplitude: u16,
+
+    /// Jolt the text up and down as well, by up to half as many rows
+    #[arg(long)]
+    vertical: bool,
+
+    /// Milliseconds the shaking takes to die down
+    #[arg(long, default_value = "600")]
+    duration: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "30")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct GlowwormArgs {
+    /// Color of the text it isn't lighting, as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "128,128,128")]
+    color: String,
+
+    /// Color of the glowworm
+    #[arg(long, default_value = "120,255,80")]
+    highlight: String,
+
+    /// Characters the bright segment spans
+    #[arg(long, default_value = "4", value_parser = clap::value_parser!(u16).range(1..))]
+    length: u16,
+
+    /// Share of its glow the trail loses each step, from 0.0 (a trail that stays lit) to 1.0 (no trail)
+    #[arg(long, default_value = "0.25")]
+    tail_decay: f32,
+
+    /// Milliseconds per step from one character to the next
+    #[arg(long, default_value = "40")]
+    speed: u64,
+
+    /// Trips through the text (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+}
+
+#[derive(Args, Clone)]
+struct WaveArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Rows characters rise above and sink below the line (the wave spans 2x+1 rows)
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u16).range(1..=10))]
+    amplitude: u16,
+
+    /// Columns from one crest to the next
+    #[arg(long, default_value = "12.0")]
+    wavelength: f32,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+
+    /// Milliseconds for the wave to travel one wavelength
+    #[arg(long, default_value = "2000")]
+    duration: u64,
+
+    /// Number of wavelengths to travel (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+}
+
+#[derive(Args, Clone)]
+struct SpotlightArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Columns from the middle of the spotlight to where its light runs out
+    #[arg(long, default_value = "4.0")]
+    radius: f32,
+
+    /// Brightness of the text outside the spotlight (0.0 to 1.0)
+    #[arg(long, default_value = "0.3")]
+    dim_factor: f32,
+
+    /// Path the spotlight takes over the text
+    #[arg(long, value_enum, default_value = "lr")]
+    path: SpotlightPath,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+
+    /// Milliseconds for one sweep along the path
+    #[arg(long, default_value = "2000")]
+    duration: u64,
+
+    /// Number of sweeps (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+
+    /// Sweep back the other way every other cycle (not for --path random)
+    #[arg(long)]
+    alternate_direction: bool,
+}
+
+#[derive(Args, Clone)]
+struct ScrambleArgs {
+    /// Color of characters once they lock, as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "255,255,255")]
+    color_locked: String,
+
+    /// Color of characters while they are still scrambled
+    #[arg(long, default_value = "0,255,70")]
+    color_scrambled: String,
+
+    /// Glyphs to scramble through: alnum, hex, binary, symbols, blocks, or your own characters
+    #[arg(long, default_value = "alnum")]
+    charset: String,
+
+    /// Milliseconds between one character locking and the next
+    #[arg(long, default_value = "40")]
+    lock_delay: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct FireArgs {
+    /// Text color below the flames and once the fire dies, as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Flame colors
+    #[arg(long, value_enum, default_value = "classic")]
+    palette: FirePalette,
+
+    /// Where the flames burn
+    #[arg(long, value_enum, default_value = "behind")]
+    mode: FireMode,
+
+    /// Rows the flames reach before burning out
+    #[arg(long, default_value = "6", value_parser = clap::value_parser!(u16).range(1..=50))]
+    height: u16,
+
+    /// Columns per row the flames lean (negative blows left, positive right)
+    #[arg(long, default_value = "0.0", allow_hyphen_values = true)]
+    wind: f32,
+
+    /// Milliseconds to feed the fire before it dies down (0 burns forever)
+    #[arg(long, default_value = "3000")]
+    duration: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct SparkleArgs {
+    /// Text color under the sparkles, as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Comma-separated sparkle colors, or a built-in palette (e.g., "gold,white" or "neon")
+    #[arg(long, default_value = "white,gold,lightskyblue")]
+    colors: String,
+
+    /// Share of cells holding a sparkle at any moment (0.0 to 1.0)
+    #[arg(long, default_value = "0.05")]
+    density: f32,
+
+    /// Milliseconds each sparkle lives, give or take a quarter
+    #[arg(long, default_value = "800", value_parser = clap::value_parser!(u64).range(1..))]
+    lifetime: u64,
+
+    /// Most cells per second a sparkle drifts, in a random direction
+    #[arg(long, default_value = "1.5")]
+    drift: f32,
+
+    /// Make sparkles likelier among dense glyphs, so they cluster on ASCII art,
+    /// or in sparse areas around it
+    #[arg(long, value_enum, default_value = "uniform")]
+    spawn_bias: SpawnBias,
+
+    /// Milliseconds to keep spawning sparkles (0 sparkles forever)
+    #[arg(long, default_value = "3000")]
+    duration: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct NeonArgs {
+    /// Color of the tubes, as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "255,40,200")]
+    color: String,
+
+    /// Columns the glow reaches out from each letter (half as many rows; 0 for none)
+    #[arg(long, default_value = "2")]
+    glow_radius: usize,
+
+    /// Chance each frame that a letter starts to flicker (0.0 to 1.0)
+    #[arg(long, default_value = "0.01")]
+    flicker_chance: f32,
+
+    /// How far the whole sign's brightness jitters from frame to frame (0.0 to 1.0)
+    #[arg(long, default_value = "0.1")]
+    buzz: f32,
+
+    /// Milliseconds the sign buzzes and flickers before it's left steady (0 for forever)
+    #[arg(long, default_value = "3000")]
+    duration: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct SnowArgs {
+    /// Text color under the snow, as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Color of the flakes, and of drifts on the bottom row
+    #[arg(long, default_value = "255,255,255")]
+    flake_color: String,
+
+    /// Characters to draw flakes with, each flake one at random
+    #[arg(long, default_value = "*·•❄")]
+    flake_chars: String,
+
+    /// Share of cells holding a flake at any moment (0.0 to 1.0)
+    #[arg(long, default_value = "0.02")]
+    density: f32,
+
+    /// Columns flakes drift sideways per row they fall (negative blows left, positive right)
+    #[arg(long, default_value = "0.0", allow_hyphen_values = true)]
+    wind: f32,
+
+    /// Make flakes likelier to fall down the columns of dense glyphs, so they
+    /// gather on ASCII art, or through the sparse areas around it
+    #[arg(long, value_enum, default_value = "uniform")]
+    spawn_bias: SpawnBias,
+
+    /// Let flakes pass behind the text instead of over it
+    #[arg(long)]
+    behind: bool,
+
+    /// Pile fallen flakes up along the bottom row
+    #[arg(long)]
+    accumulate: bool,
+
+    /// Milliseconds to keep snowing before the last flakes fall (0 snows forever)
+    #[arg(long, default_value = "5000")]
+    duration: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct ConfettiArgs {
+    /// Color the text is highlighted in, as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "gold")]
+    color: String,
+
+    /// Comma-separated confetti colors, or a built-in palette (e.g., "red,white" or "neon")
+    #[arg(long, default_value = "red,gold,limegreen,deepskyblue,magenta")]
+    palette: String,
+
+    /// Pieces of confetti in the burst
+    #[arg(long, default_value = "80", value_parser = clap::value_parser!(u32).range(1..=5000))]
+    burst_count: u32,
+
+    /// How quickly pieces fall back, in rows per second gained each second
+    #[arg(long, default_value = "30.0")]
+    gravity: f32,
+
+    /// Where the confetti bursts from
+    #[arg(long, value_enum, default_value = "bottom")]
+    from: ConfettiOrigin,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "30")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct FireworksArgs {
+    /// Color of the text, as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "white")]
+    color: String,
+
+    /// Comma-separated burst colors, or a built-in palette (e.g., "red,white" or "neon")
+    #[arg(long, default_value = "red,gold,limegreen,deepskyblue,magenta,white")]
+    colors: String,
+
+    /// Sparks in each burst
+    #[arg(long, default_value = "40", value_parser = clap::value_parser!(u32).range(1..=1000))]
+    sparks: u32,
+
+    /// Milliseconds between launches, on average
+    #[arg(long, default_value = "500", value_parser = clap::value_parser!(u64).range(1..))]
+    launch_every: u64,
+
+    /// How quickly rockets and sparks fall back, in rows per second gained each second
+    #[arg(long, default_value = "10.0")]
+    gravity: f32,
+
+    /// Milliseconds to keep launching rockets (0 launches forever)
+    #[arg(long, default_value = "4000")]
+    duration: u64,
+
+    /// Milliseconds in to stop launching and spell out the text, which stays hidden until then
+    #[arg(long)]
+    finale_after: Option<u64>,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "30")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct MarqueeArgs {
+    /// Text color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Columns the text scrolls through (default: the terminal width)
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    window_width: Option<u16>,
+
+    /// Direction the text moves
+    #[arg(long, value_enum, default_value = "left")]
+    direction: MarqueeDirection,
+
+    /// Blank columns between the end of the text and its next repeat
+    #[arg(long, default_value = "4")]
+    gap: usize,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "80")]
+    speed: u64,
+
+    /// Number of times the text scrolls all the way around (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+}
+
+#[derive(Args, Clone)]
+struct TopbarArgs {
+    /// Rows to reserve at the top of the terminal
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u16).range(1..=10))]
+    rows: u16,
+
+    /// Effect animating the bar
+    #[arg(long, value_enum, default_value = "aurora")]
+    effect: TopbarEffect,
+
+    /// Bar color for shine and pulse as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "100,100,100")]
+    color: String,
+
+    /// Highlight color for shine and pulse
+    #[arg(long, default_value = "255,255,255")]
+    highlight: String,
+
+    /// Character drawn across the bar around the label
+    #[arg(long, default_value = "━")]
+    fill: char,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+
+    /// Length of one effect cycle in milliseconds
+    #[arg(long, default_value = "4000")]
+    period: u64,
+}
+
+#[derive(Args, Clone)]
+struct ComposeArgs {
+    /// Color of text outside the spans as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Peak color for shine and twinkle spans
+    #[arg(long, default_value = "255,255,255")]
+    highlight: String,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+
+    /// Milliseconds per cycle, for spans that don't set a duration
+    #[arg(long, default_value = "2000")]
+    duration: u64,
+
+    /// Number of cycles each span runs (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+}
+
+#[derive(Args, Clone)]
+struct EasingPreviewArgs {
+    /// linear, ease-in, ease-out, ease-in-out, ease-out-bounce, ease-out-elastic,
+    /// ease-in-back, sine, expo, circ, or cubic-bezier(x1,y1,x2,y2)
+    #[arg(default_value = "ease-in-out")]
+    easing: String,
+
+    /// Color of the curve and dot as RGB, hex, name, or hsl()
+    #[arg(long, default_value = "0,200,255")]
+    color: String,
+
+    /// Columns across the plot and ruler
+    #[arg(long, default_value = "40", value_parser = clap::value_parser!(u16).range(2..))]
+    width: u16,
+
+    /// Rows in the plot
+    #[arg(long, default_value = "12", value_parser = clap::value_parser!(u16).range(2..))]
+    height: u16,
+
+    /// Milliseconds for the dot to cross the ruler once
+    #[arg(long, default_value = "1500")]
+    duration: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "30")]
+    speed: u64,
+
+    /// Number of times the dot crosses the ruler (0 for infinite)
+    #[arg(long, default_value = "1")]
+    cycles: u32,
+}
+
+#[derive(Args, Clone)]
+struct CountupArgs {
+    /// Digit color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Color of the lap flash and lap text
+    #[arg(long, default_value = "gold")]
+    lap_color: String,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+
+    /// Stop after this many milliseconds (default: when stdin closes)
+    #[arg(long)]
+    duration: Option<u64>,
+
+    /// How long a lap stays on screen in milliseconds
+    #[arg(long, default_value = "1500")]
+    lap_display: u64,
+
+    /// Hide the tenths-of-a-second digit
+    #[arg(long)]
+    no_tenths: bool,
+}
+
+#[derive(Args, Clone)]
+struct ProgressArgs {
+    /// Color of the filled part as RGB, hex, name, or hsl() (e.g., "0,200,120" or "green")
+    #[arg(long, default_value = "0,200,120")]
+    color: String,
+
+    /// Color of the part still to fill
+    #[arg(long, default_value = "60,60,60")]
+    empty_color: String,
+
+    /// Color at the center of the shine
+    #[arg(long, default_value = "255,255,255")]
+    highlight: String,
+
+    /// Width of the bar in columns
+    #[arg(long, default_value = "30", value_parser = clap::value_parser!(u16).range(1..))]
+    width: u16,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+
+    /// Milliseconds the shine takes to cross the filled part
+    #[arg(long, default_value = "1500")]
+    period: u64,
+}
+
+#[derive(Args, Clone)]
+struct SpinArgs {
+    /// Text beside the spinner (default: the command line)
+    #[arg(long)]
+    label: Option<String>,
+
+    /// Label color as RGB, hex, name, or hsl() (e.g., "150,150,150" or "gray")
+    #[arg(long, default_value = "150,150,150")]
+    color: String,
+
+    /// Color of the spinner and at the center of the shine
+    #[arg(long, default_value = "255,255,255")]
+    highlight: String,
+
+    /// Color the line ends in when the command succeeds
+    #[arg(long, default_value = "0,200,120")]
+    success_color: String,
+
+    /// Color the line ends in when the command fails
+    #[arg(long, default_value = "230,60,60")]
+    failure_color: String,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "80")]
+    speed: u64,
+
+    /// Milliseconds the shine takes to cross the label
+    #[arg(long, default_value = "1500")]
+    period: u64,
+
+    /// Milliseconds the ending takes to wash over the label
+    #[arg(long, default_value = "600")]
+    flourish: u64,
+
+    /// The command to run and its arguments, after --
+    #[arg(required = true, trailing_var_arg = true, value_name = "COMMAND")]
+    run: Vec<String>,
+}
+
+#[derive(Args, Clone)]
+struct CountdownArgs {
+    /// Seconds to count down from
+    #[arg(long, default_value = "10", value_parser = clap::value_parser!(u64).range(1..))]
+    from: u64,
+
+    /// Digit color as RGB, hex, name, or hsl() (e.g., "255,255,255" or "white")
+    #[arg(long, default_value = "255,255,255")]
+    color: String,
+
+    /// Color of each second's transition and the final flash
+    #[arg(long, default_value = "gold")]
+    accent_color: String,
+
+    /// How each new number arrives
+    #[arg(long, value_enum, default_value = "shine")]
+    transition: CountdownTransition,
+
+    /// How long zero flashes at the end in milliseconds
+    #[arg(long, default_value = "1000")]
+    flash: u64,
+
+    /// Animation speed in milliseconds between frames
+    #[arg(long, default_value = "50")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct CompareArgs {
+    /// Effect and flags for the left pane
+    #[arg(long, allow_hyphen_values = true)]
+    left: String,
+
+    /// Effect and flags for the right pane
+    #[arg(long, allow_hyphen_values = true)]
+    right: String,
+
+    /// Milliseconds between redraws of both panes
+    #[arg(long, default_value = "30")]
+    speed: u64,
+}
+
+#[derive(Args, Clone)]
+struct PickArgs {
+    /// Save the picked cells as a mask file and print --mask-file instead
+    #[arg(long, value_name = "PATH")]
+    save: Option<PathBuf>,
+}
+
+#[derive(Args, Clone)]
+struct ReplayArgs {
+    /// The recording to play
+    file: PathBuf,
+
+    /// How many times faster than it was recorded to play (e.g., 0.5 for half speed)
+    #[arg(long, default_value = "1.0")]
+    rate: f64,
+}
+
+#[derive(Args, Clone)]
+struct DaemonArgs {
+    /// The Unix socket to listen on
+    #[arg(long, value_name = "PATH")]
+    socket: PathBuf,
+
+    /// The effect and flags to start with, as they would follow `clifx`
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+#[derive(Args, Clone)]
+struct SnapshotArgs {
+    /// How far into the effect's cycle the frame is, from 0.0 to 1.0
+    #[arg(long, default_value = "0.3")]
+    at: f32,
+
+    /// Mark escape codes the way this shell's prompt needs them marked
+    #[arg(long, value_enum, default_value = "plain")]
+    shell: SnapshotShell,
+
+    /// The effect and flags, as they would follow `clifx`
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+#[derive(Args, Clone)]
+struct WatchArgs {
+    /// Show this shell command's output, run again every interval, instead of --file
+    #[arg(long, value_name = "COMMAND")]
+    run: Option<String>,
+
+    /// Milliseconds between checks for a change
+    #[arg(long, default_value = "1000")]
+    interval: u64,
+
+    /// The effect and flags, as they would follow `clifx`
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum PresetAction {
+    /// Save an effect and its flags as a preset (e.g., clifx preset save gold -- shine --color gold)
+    Save {
+        /// Name to save the preset under
+        name: String,
+
+        /// The effect and flags, as they would follow `clifx`
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Any other name runs the preset of that name; extra flags override its own
+    #[command(external_subcommand)]
+    Run(Vec<String>),
+}
+
+impl Cli {
+    /// The banner font: the one in --font-file, or the built-in one for --big.
+    fn font(&self) -> Result<Option<FigFont>, Box<dyn std::error::Error>> {
+        match &self.font_file {
+            Some(path) => FigFont::load(path).map(Some),
+            None => Ok(self.big.then(FigFont::builtin)),
+        }
+    }
+
+    /// The phase wrapped into 0.0..1.0, so 1.25 behaves like 0.25.
+    fn cycle_phase(&self) -> f32 {
+        self.phase.rem_euclid(1.0)
+    }
+
+    /// Whether to write the text once in the effect's resting colors
+    /// instead of animating it: stdout is a pipe or file, where frames would
+    /// only pile up as cursor movements, and nothing asked for them anyway.
+    /// Effects without a look at rest, or that read stdin themselves,
+    /// animate regardless.
+    fn renders_static(&self) -> bool {
+        self.command.has_static_style()
+            && !self.command.consumes_stdin()
+            && !matches!(
+                self.command,
+                Commands::Gradient(GradientArgs {
+                    preview_all_palettes: true,
+                    ..
+                })
+            )
+            && !self.force_animation
+            && !io::stdout().is_terminal()
+            && self.soak.is_none()
+            && self.profile_out.is_none()
+            && self.clip_buffer.is_none()
+            && self.export_html.is_none()
+            && self.export_ansi.is_none()
+            && self.output == OutputFormat::Ansi
+            && matches!(self.backend, Backend::Ansi | Backend::Auto)
+            && self.virtual_size.is_none()
+    }
+
+    /// The color depth asked for, with --no-color standing for mono.
+    fn color_mode(&self) -> ColorMode {
+        if self.no_color {
+            ColorMode::Mono
+        } else {
+            self.color_mode
+        }
+    }
+
+    /// The cells `--mask-file` and `--mask-region` pick out, if either was given.
+    fn mask(&self) -> Result<Option<Mask>, Box<dyn std::error::Error>> {
+        if self.mask_file.is_none() && self.mask_region.is_empty() {
+            return Ok(None);
+        }
+        let mut mask = match &self.mask_file {
+            Some(path) => Mask::load(path)?,
+            None => Mask::default(),
+        };
+        for region in &self.mask_region {
+            mask.add(region);
+        }
+        Ok(Some(mask))
+    }
+
+    /// The colors `--line-colors` or `--line-palette` gives the input's
+    /// lines, one after another.
+    fn line_colors(&self) -> Result<Option<Vec<Rgb>>, Box<dyn std::error::Error>> {
+        if let Some(name) = &self.line_palette {
+            let palette = palette::named(name).ok_or_else(|| {
+                let names: Vec<&str> = palette::PALETTES.iter().map(|p| p.name).collect();
+                format!(
+                    "Unknown palette '{name}'. Built-in palettes: {}",
+                    names.join(", ")
+                )
+            })?;
+            return Ok(Some(palette.stops.to_vec()));
+        }
+        self.line_colors
+            .as_deref()
+            .map(parse_color_list)
+            .transpose()
+    }
+
+    /// Whether output is drawn at centered positions on the screen, as with
+    /// `--center` and `--center-v`, rather than where the cursor is.
+    fn centers_on_screen(&self) -> bool {
+        self.center || self.center_v
+    }
+
+    /// Whether the lines are only indented to the middle of the terminal,
+    /// for `--center-h` on its own.
+    fn centers_across_only(&self) -> bool {
+        self.center_h && !self.centers_on_screen()
+    }
+
+    /// The box `--margin`, `--pad`, and `--border` put around the text.
+    fn boxing(&self) -> Boxing {
+        Boxing {
+            margin: self.margin,
+            pad: self.pad,
+            border: self.border,
+        }
+    }
+
+    /// How `--wrap` and `--align` lay the input out.
+    fn flow(&self) -> Flow {
+        Flow {
+            wrap: self.wrap.unwrap_or_default(),
+            align: self.align.unwrap_or_default(),
+        }
+    }
+
+    fn input_filter(&self) -> InputFilter {
+        InputFilter {
+            transforms: self.transform.clone(),
+            control_chars: self.control_chars,
+        }
+    }
+}
+
+impl Commands {
+    /// Replaces infinite cycles with a single one, so a streamed line can't
+    /// hold up the lines behind it.
+    fn limit_cycles(&mut self) {
+        match self {
+            Commands::Banner(BannerArgs { cycles, .. })
+            | Commands::Shine(ShineArgs { cycles, .. })
+            | Commands::Shine2d(Shine2dArgs { cycles, .. })
+            | Commands::Gradient(GradientArgs { cycles, .. })
+            | Commands::Pulse(PulseArgs { cycles, .. })
+            | Commands::Blink(BlinkArgs { cycles, .. })
+            | Commands::Heartbeat(HeartbeatArgs { cycles, .. })
+            | Commands::Glowworm(GlowwormArgs { cycles, .. })
+            | Commands::Twinkle(TwinkleArgs { cycles, .. })
+            | Commands::Wave(WaveArgs { cycles, .. })
+            | Commands::Spotlight(SpotlightArgs { cycles, .. })
+            | Commands::Marquee(MarqueeArgs { cycles, .. })
+            | Commands::Compose(ComposeArgs { cycles, .. })
+            | Commands::EasingPreview(EasingPreviewArgs { cycles, .. }) => {
+                if *cycles == 0 {
+                    *cycles = 1;
+                }
+            }
+            // A fire that burns forever needs a duration to die down after
+            Commands::Fire(FireArgs { duration, .. }) => {
+                if *duration == 0 {
+                    *duration = FireConfig::default().duration;
+                }
+            }
+            Commands::Sparkle(SparkleArgs { duration, .. }) => {
+                if *duration == 0 {
+                    *duration = SparkleConfig::default().duration;
+                }
+            }
+            Commands::Neon(NeonArgs { duration, .. }) => {
+                if *duration == 0 {
+                    *duration = NeonConfig::default().duration;
+                }
+            }
+            Commands::Snow(SnowArgs { duration, .. }) => {
+                if *duration == 0 {
+                    *duration = SnowConfig::default().duration;
+                }
+            }
+            Commands::Fireworks(FireworksArgs { duration, .. }) => {
+                if *duration == 0 {
+                    *duration = FireworksConfig::default().duration;
+                }
+            }
+            Commands::Fade(_)
+            | Commands::Wipe(_)
+            | Commands::Dissolve(_)
+            | Commands::Slide(_)
+            | Commands::Bounce(_)
+            | Commands::Shake(_)
+            | Commands::Karaoke(_)
+            | Commands::Confetti(_)
+            | Commands::Scramble(_)
+            | Commands::Topbar(_)
+            | Commands::Countup(_)
+            | Commands::Countdown(_)
+            | Commands::Progress(_)
+            | Commands::Spin(_)
+            | Commands::Compare(_)
+            | Commands::Pick(_)
+            | Commands::Replay(_)
+            | Commands::Watch(_)
+            | Commands::Snapshot(_)
+            | Commands::Daemon(_)
+            | Commands::Preset { .. } => {}
+        }
+    }
+
+    /// Makes the effect run until it's stopped, with infinite cycles or an
+    /// endless duration. Returns false for effects that have neither and
+    /// end on their own, which --loop runs again instead.
+    fn loop_forever(&mut self) -> Result<bool, Box<dyn std::error::Error>> {
+        match self {
+            Commands::Banner(BannerArgs { cycles, .. })
+            | Commands::Shine(ShineArgs { cycles, .. })
+            | Commands::Shine2d(Shine2dArgs { cycles, .. })
+            | Commands::Gradient(GradientArgs { cycles, .. })
+            | Commands::Pulse(PulseArgs { cycles, .. })
+            | Commands::Blink(BlinkArgs { cycles, .. })
+            | Commands::Heartbeat(HeartbeatArgs { cycles, .. })
+            | Commands::Glowworm(GlowwormArgs { cycles, .. })
+            | Commands::Twinkle(TwinkleArgs { cycles, .. })
+            | Commands::Wave(WaveArgs { cycles, .. })
+            | Commands::Spotlight(SpotlightArgs { cycles, .. })
+            | Commands::Marquee(MarqueeArgs { cycles, .. })
+            | Commands::Compose(ComposeArgs { cycles, .. })
+            | Commands::EasingPreview(EasingPreviewArgs { cycles, .. }) => *cycles = 0,
+            Commands::Fire(FireArgs { duration, .. })
+            | Commands::Sparkle(SparkleArgs { duration, .. })
+            | Commands::Neon(NeonArgs { duration, .. })
+            | Commands::Snow(SnowArgs { duration, .. })
+            | Commands::Fireworks(FireworksArgs { duration, .. }) => *duration = 0,
+            Commands::Fade(_)
+            | Commands::Wipe(_)
+            | Commands::Dissolve(_)
+            | Commands::Slide(_)
+            | Commands::Bounce(_)
+            | Commands::Shake(_)
+            | Commands::Karaoke(_)
+            | Commands::Confetti(_)
+            | Commands::Scramble(_)
+            | Commands::Countup(_)
+            | Commands::Countdown(_) => return Ok(false),
+            // Each side is passed --loop and keeps itself going
+            Commands::Compare(_) | Commands::Preset { .. } => {}
+            Commands::Topbar(_) => {
+                return Err(
+                    "--loop doesn't apply to topbar, which runs until its input ends".into(),
+                )
+            }
+            Commands::Progress(_) => {
+                return Err(
+                    "--loop doesn't apply to progress, which runs until its input ends".into(),
+                )
+            }
+            Commands::Spin(_) => {
+                return Err(
+                    "--loop doesn't apply to spin, which runs until its command exits".into(),
+                )
+            }
+            Commands::Pick(_) => return Err("--loop doesn't apply to pick".into()),
+            Commands::Replay(_) => return Err("--loop doesn't apply to replay".into()),
+            Commands::Daemon(_) => return Err("--loop doesn't apply to daemon".into()),
+            Commands::Snapshot(_) => {
+                return Err("--loop doesn't apply to snapshot, which draws one frame".into())
+            }
+            Commands::Watch(_) => {
+                return Err(
+                    "--loop goes after the effect watch runs, since watch keeps going itself"
+                        .into(),
+                )
+            }
+        }
+        Ok(true)
+    }
+
+    /// The effect's `--speed`, the milliseconds between its frames.
+    fn speed_mut(&mut self) -> Option<&mut u64> {
+        match self {
+            Commands::Banner(BannerArgs { speed, .. })
+            | Commands::Shine(ShineArgs { speed, .. })
+            | Commands::Shine2d(Shine2dArgs { speed, .. })
+            | Commands::Gradient(GradientArgs { speed, .. })
+            | Commands::Twinkle(TwinkleArgs { speed, .. })
+            | Commands::Pulse(PulseArgs { speed, .. })
+            | Commands::Heartbeat(HeartbeatArgs { speed, .. })
+            | Commands::Fade(FadeArgs { speed, .. })
+            | Commands::Wipe(WipeArgs { speed, .. })
+            | Commands::Dissolve(DissolveArgs { speed, .. })
+            | Commands::Slide(SlideArgs { speed, .. })
+            | Commands::Bounce(BounceArgs { speed, .. })
+            | Commands::Shake(ShakeArgs { speed, .. })
+            | Commands::Glowworm(GlowwormArgs { speed, .. })
+            | Commands::Wave(WaveArgs { speed, .. })
+            | Commands::Spotlight(SpotlightArgs { speed, .. })
+            | Commands::Scramble(ScrambleArgs { speed, .. })
+            | Commands::Fire(FireArgs { speed, .. })
+            | Commands::Sparkle(SparkleArgs { speed, .. })
+            | Commands::Neon(NeonArgs { speed, .. })
+            | Commands::Snow(SnowArgs { speed, .. })
+            | Commands::Confetti(ConfettiArgs { speed, .. })
+            | Commands::Fireworks(FireworksArgs { speed, .. })
+            | Commands::Marquee(MarqueeArgs { speed, .. })
+            | Commands::Topbar(TopbarArgs { speed, .. })
+            | Commands::Compose(ComposeArgs { speed, .. })
+            | Commands::EasingPreview(EasingPreviewArgs { speed, .. })
+            | Commands::Countup(CountupArgs { speed, .. })
+            | Commands::Countdown(CountdownArgs { speed, .. })
+            | Commands::Progress(ProgressArgs { speed, .. })
+            | Commands::Spin(SpinArgs { speed, .. })
+            | Commands::Compare(CompareArgs { speed, .. }) => Some(speed),
+            Commands::Blink(_)
+            | Commands::Karaoke(_)
+            | Commands::Pick(_)
+            | Commands::Replay(_)
+            | Commands::Watch(_)
+            | Commands::Snapshot(_)
+            | Commands::Daemon(_)
+            | Commands::Preset { .. } => None,
+        }
+    }
+
+    /// How the effect looks at rest, for streamed lines that come too fast
+    /// to animate. `None` for effects that always animate.
+    fn static_style(&self) -> Result<Option<StaticStyle>, Box<dyn std::error::Error>> {
+        let color = match self {
+            Commands::Shine(ShineArgs { color, .. })
+            | Commands::Shine2d(Shine2dArgs { color, .. }) => color
+                .clone()
+                .unwrap_or_else(generate_random_saturated_color),
+            Commands::Banner(BannerArgs { stops, .. })
+            | Commands::Gradient(GradientArgs { stops, .. }) => {
+                return Ok(Some(StaticStyle::Gradient(parse_color_list(stops)?)))
+            }
+            Commands::Twinkle(TwinkleArgs { base_color, .. }) => base_color.clone(),
+            Commands::Scramble(ScrambleArgs { color_locked, .. }) => color_locked.clone(),
+            // Once every word is sung
+            Commands::Karaoke(KaraokeArgs {
+                highlight,
+                sung_color,
+                ..
+            }) => sung_color.clone().unwrap_or_else(|| highlight.clone()),
+            Commands::Pulse(PulseArgs { color, .. })
+            | Commands::Blink(BlinkArgs { color, .. })
+            | Commands::Heartbeat(HeartbeatArgs { color, .. })
+            | Commands::Fade(FadeArgs { color, .. })
+            | Commands::Wipe(WipeArgs { color, .. })
+            | Commands::Dissolve(DissolveArgs { color, .. })
+            | Commands::Slide(SlideArgs { color, .. })
+            | Commands::Bounce(BounceArgs { color, .. })
+            | Commands::Shake(ShakeArgs { color, .. })
+            | Commands::Glowworm(GlowwormArgs { color, .. })
+            | Commands::Wave(WaveArgs { color, .. })
+            | Commands::Spotlight(SpotlightArgs { color, .. })
+            | Commands::Fire(FireArgs { color, .. })
+            | Commands::Sparkle(SparkleArgs { color, .. })
+            | Commands::Neon(NeonArgs { color, .. })
+            | Commands::Snow(SnowArgs { color, .. })
+            | Commands::Confetti(ConfettiArgs { color, .. })
+            | Commands::Fireworks(FireworksArgs { color, .. })
+            | Commands::Marquee(MarqueeArgs { color, .. })
+            | Commands::Compose(ComposeArgs { color, .. })
+            | Commands::Topbar(TopbarArgs { color, .. })
+            | Commands::EasingPreview(EasingPreviewArgs { color, .. })
+            | Commands::Countup(CountupArgs { color, .. })
+            | Commands::Countdown(CountdownArgs { color, .. })
+            | Commands::Progress(ProgressArgs { color, .. })
+            | Commands::Spin(SpinArgs { color, .. }) => color.clone(),
+            Commands::Compare(_)
+            | Commands::Pick(_)
+            | Commands::Replay(_)
+            | Commands::Watch(_)
+            | Commands::Snapshot(_)
+            | Commands::Daemon(_)
+            | Commands::Preset { .. } => return Ok(None),
+        };
+        Ok(Some(StaticStyle::Solid(parse_color(&color)?)))
+    }
+
+    /// Whether `static_style` has a look to give, checked without working
+    /// the colors out, since a random one would use up the seeded RNG.
+    fn has_static_style(&self) -> bool {
+        !matches!(
+            self,
+            Commands::Compare(_)
+                | Commands::Pick(_)
+                | Commands::Replay(_)
+                | Commands::Watch(_)
+                | Commands::Snapshot(_)
+                | Commands::Daemon(_)
+                | Commands::Preset { .. }
+        )
+    }
+
+    /// Whether the effect reads stdin itself as it arrives (or ignores it),
+    /// rather than being handed every line up front.
+    fn consumes_stdin(&self) -> bool {
+        matches!(
+            self,
+            Commands::Topbar(_)
+                | Commands::Countup(_)
+                | Commands::Countdown(_)
+                | Commands::Progress(_)
+                | Commands::Spin(_)
+                | Commands::EasingPreview(_)
+                | Commands::Replay(_)
+                | Commands::Watch(_)
+                | Commands::Snapshot(_)
+                | Commands::Daemon(_)
+        )
+    }
+}
+
+/// The built-in palettes, then the saved presets that have a look at rest,
+/// each labelled with the flags that ask for it.
+fn gallery_looks() -> Result<Vec<gallery::Look>, Box<dyn std::error::Error>> {
+    let mut looks: Vec<gallery::Look> = palette::PALETTES
+        .iter()
+        .map(|palette| gallery::Look {
+            label: format!(
+                "gradient --stops {}  ({})",
+                palette.name, palette.description
+            ),
+            style: StaticStyle::Gradient(palette.stops.to_vec()),
+        })
+        .collect();
+
+    for (name, preset) in config::load_presets()? {
+        // Expressions only change numbers, which don't show at rest
+        let command_line = preset_command_line(&preset.args);
+        let args = expr::resolve_args(
+            &Cli::command(),
+            &command_line,
+            &mut expr::Variables::placeholder(),
+        )?;
+        // Presets broken by a newer clifx are left out rather than failing the rest
+        let Ok(preset_cli) = Cli::try_parse_from(&args) else {
+            continue;
+        };
+        if let Some(style) = preset_cli.command.static_style()? {
+            looks.push(gallery::Look {
+                label: format!("preset {name}  ({})", preset.args.join(" ")),
+                style,
+            });
+        }
+    }
+    Ok(looks)
+}
+
+/// A full command line made of `args` typed after `clifx`.
+fn preset_command_line<'a>(args: impl IntoIterator<Item = &'a String>) -> Vec<OsString> {
+    std::iter::once("clifx")
+        .chain(args.into_iter().map(String::as_str))
+        .map(OsString::from)
+        .collect()
+}
+
+/// The effect's name and the value of every flag it ran with, defaults
+/// included, as recorded in profiles.
+fn parameter_values(args: &[OsString]) -> (String, BTreeMap<String, serde_json::Value>) {
+    let cli_command = Cli::command();
+    let matches = cli_command.clone().get_matches_from(args);
+    let Some((command, sub_matches)) = matches.subcommand() else {
+        return Default::default();
+    };
+    let Some(subcommand) = cli_command.find_subcommand(command) else {
+        return Default::default();
+    };
+
+    let parameters = sub_matches
+        .ids()
+        // Argument groups show up as ids too, listing their members
+        .filter(|id| subcommand.get_groups().all(|group| group.get_id() != *id))
+        .filter_map(|id| {
+            let mut values: Vec<serde_json::Value> = sub_matches
+                .try_get_raw(id.as_str())
+                .ok()
+                .flatten()?
+                .map(|value| value.to_string_lossy().into_owned().into())
+                .collect();
+            let value = if values.len() == 1 {
+                values.remove(0)
+            } else {
+                values.into()
+            };
+            Some((id.to_string(), value))
+        })
+        .collect();
+
+    (command.to_string(), parameters)
+}
+
+/// The command line the preset `name` stands for, with `overrides` typed
+/// after its stored flags and every expression resolved.
+fn preset_arguments(
+    name: &str,
+    overrides: &[String],
+    variables: &mut expr::Variables,
+) -> Result<Vec<OsString>, Box<dyn std::error::Error>> {
+    let preset = config::load_preset(name)?;
+    expr::resolve_args(
+        &Cli::command(),
+        &preset_command_line(preset.args.iter().chain(overrides)),
+        variables,
+    )
+}
+
+/// Parses a preset's stored arguments as if they had been typed after `clifx`.
+fn parse_preset_args(args: &[OsString]) -> Result<Cli, Box<dyn std::error::Error>> {
+    // Report bad stored flags the way clap reports bad typed ones
+    let cli = Cli::try_parse_from(args).unwrap_or_else(|err| err.exit());
+    if matches!(cli.command, Commands::Preset { .. }) {
+        return Err("a preset can't run or save another preset".into());
+    }
+    Ok(cli)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Expressions in numeric flags (e.g. `--width cols/10`) become numbers first
+    let typed: Vec<OsString> = std::env::args_os().collect();
+    // Not what spin runs, whose flags are its own
+    let own = &typed[..expr::own_args_len(&Cli::command(), &typed)];
+    // Expressions over cols and rows go by the virtual size too
+    if let Some(size) = virtual_size_arg(own)? {
+        screen::use_virtual_size(size);
+    }
+    let mut variables = expr::Variables::new();
+    // Expressions over lines and chars go by --text when it's given
+    if let Some(text) = typed_value(own, "--text") {
+        let append_stdin = own.iter().any(|arg| arg == "--append-stdin");
+        variables.use_text(text_lines(text), append_stdin);
+    }
+    let files: Vec<PathBuf> = typed_values(own, "--file")
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+    if !files.is_empty() {
+        variables.use_text(read_files(&files, terminal_rows())?, false);
+    }
+    let mut args = expr::resolve_args(&Cli::command(), &typed, &mut variables)?;
+    let mut cli = Cli::parse_from(&args);
+    if let Commands::Preset { action } = &cli.command {
+        match action {
+            PresetAction::Save {
+                name,
+                args: preset_args,
+            } => {
+                if name == "save" {
+                    return Err("'save' can't be used as a preset name".into());
+                }
+                // Expressions stay in the preset, to be evaluated each time it runs
+                let mut placeholders = expr::Variables::placeholder();
+                let command_line = preset_command_line(preset_args);
+                parse_preset_args(&expr::resolve_args(
+                    &Cli::command(),
+                    &command_line,
+                    &mut placeholders,
+                )?)?;
+                let path = config::save_preset(name, preset_args)?;
+                eprintln!("Saved preset '{name}' to {}", path.display());
+                return Ok(());
+            }
+            PresetAction::Run(run_args) => {
+                let (name, overrides) = run_args.split_first().ok_or("missing preset name")?;
+                args = preset_arguments(name, overrides, &mut variables)?;
+                cli = parse_preset_args(&args)?;
+            }
+        }
+    }
+
+    apply_banner(&mut cli, &mut args)?;
+    apply_theme(&mut cli, &mut args)?;
+    apply_fps(&mut cli, &args)?;
+    if let Some(size) = cli.virtual_size {
+        screen::use_virtual_size(size);
+    }
+
+    if let Some(seconds) = cli.clip_buffer {
+        clip::start(seconds)?;
+    }
+
+    if cli.keep_final {
+        keep::start();
+    }
+
+    let export_html = cli.export_html.clone();
+    if export_html.is_some() {
+        html::start();
+    }
+    let export_ansi = cli.export_ansi.clone();
+    if export_ansi.is_some() {
+        clip::start_export();
+    }
+    if cli.output == OutputFormat::JsonFrames {
+        json_frames::start();
+    }
+
+    let input = variables.into_input();
+    if let Commands::Daemon(DaemonArgs { socket, args }) = &cli.command {
+        #[cfg(unix)]
+        return quit_is_success(run_daemon(socket, &cli, args));
+        #[cfg(not(unix))]
+        return Err(format!(
+            "daemon --socket {} needs Unix domain sockets, which this platform lacks",
+            socket.display()
+        )
+        .into());
+    }
+    if let Some(path) = cli.control_socket.clone() {
+        #[cfg(unix)]
+        return quit_is_success(run_controlled(&path, cli, args, input));
+        #[cfg(not(unix))]
+        return Err(format!(
+            "--control-socket {} needs Unix domain sockets, which this platform lacks",
+            path.display()
+        )
+        .into());
+    }
+    let centered = cli.centers_on_screen();
+    let explain = cli
+        .explain_fallbacks
+        .then_some((cli.color_mode(), !cli.renders_static()));
+    let profile_path = cli.profile_out.clone();
+    if profile_path.is_some() {
+        profile::start();
+    }
+    // The speed `--fps` picked is the one that ran
+    let fps_speed = cli.fps.and(cli.command.speed_mut().map(|speed| *speed));
+    let result = quit_is_success(run_effects(cli, input));
+    // Whatever was drawn after the last frame
+    json_frames::end_frame();
+    clip::report();
+    if let Some((color_mode, animated)) = explain {
+        fallbacks::report(color_mode, animated);
+    }
+    if result.is_ok() {
+        keep::print(centered);
+        if let Some(path) = &export_html {
+            html::write(path)?;
+        }
+        if let Some(path) = &export_ansi {
+            clip::write_export(path)?;
+        }
+    }
+    if let Some(profile_path) = profile_path {
+        let (command, mut parameters) = parameter_values(&args);
+        if let Some(speed) = fps_speed {
+            parameters.insert("speed".to_string(), speed.to_string().into());
+        }
+        profile::write(&profile_path, &command, &parameters)?;
+    }
+    exit_like_spun_command(result)
+}
+
+/// Replaces the effect's `--speed` with the frame interval `--fps` asks
+/// for. Giving both is an error, since they'd disagree.
+fn apply_fps(cli: &mut Cli, args: &[OsString]) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(fps) = cli.fps else {
+        return Ok(());
+    };
+    if !(fps.is_finite() && fps > 0.0) {
+        return Err("--fps must be above 0".into());
+    }
+    if speed_typed(args) {
+        return Err("--fps and --speed both set the frame rate, so give only one".into());
+    }
+    if let Some(speed) = cli.command.speed_mut() {
+        *speed = frame_interval(fps);
+    }
+    Ok(())
+}
+
+/// The text to animate, unless an expression already read it: `--text`,
+/// then the `--file` files or, without `--text` or with `--append-stdin`,
+/// stdin.
+fn input_text(cli: &Cli) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut lines = cli.text.as_deref().map(text_lines).unwrap_or_default();
+    if !cli.input_file.is_empty() {
+        lines.extend(read_files(&cli.input_file, terminal_rows())?);
+    } else if cli.text.is_none() || cli.append_stdin {
+        let reader = BufReader::new(io::stdin().lock());
+        for line in reader.lines() {
+            lines.push(line?);
+        }
+    }
+    Ok(lines)
+}
+
+/// `--text` as input lines.
+fn text_lines(text: &str) -> Vec<String> {
+    text.lines().map(String::from).collect()
+}
+
+/// How many rows effects have to draw in, when there's a terminal (or
+/// `--virtual-size`) to go by.
+fn terminal_rows() -> Option<usize> {
+    screen::layout_size().map(|(_, rows)| rows as usize)
+}
+
+/// Every value of `flag` typed in `args`, found before they're parsed.
+fn typed_values<'a>(args: &'a [OsString], flag: &str) -> Vec<&'a str> {
+    let mut values = Vec::new();
+    let mut rest = args.iter().filter_map(|arg| arg.to_str());
+    while let Some(arg) = rest.next() {
+        let value = match arg.strip_prefix(flag) {
+            Some("") => rest.next(),
+            Some(inline) => inline.strip_prefix('='),
+            None => continue,
+        };
+        values.extend(value);
+    }
+    values
+}
+
+/// The first value of `flag` typed in `args`, found before they're parsed.
+fn typed_value<'a>(args: &'a [OsString], flag: &str) -> Option<&'a str> {
+    typed_values(args, flag).into_iter().next()
+}
+
+/// The `--virtual-size` typed in `args`, found before they're parsed.
+fn virtual_size_arg(args: &[OsString]) -> Result<Option<(u16, u16)>, Box<dyn std::error::Error>> {
+    Ok(typed_value(args, "--virtual-size")
+        .map(screen::parse_size)
+        .transpose()?)
+}
+
+/// The built-in themes `--theme` takes, each described in `--help`.
+fn theme_names() -> clap::builder::PossibleValuesParser {
+    themes::THEMES
+        .iter()
+        .map(|theme| clap::builder::PossibleValue::new(theme.name).help(theme.description))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Fills in what makes `banner` a banner, for every part the command line
+/// leaves to it: big letters, centered on the screen, in a rounded box.
+/// Text given after `banner` becomes its `--text`.
+fn apply_banner(cli: &mut Cli, args: &mut Vec<OsString>) -> Result<(), Box<dyn std::error::Error>> {
+    let Commands::Banner(BannerArgs {
+        message,
+        plain,
+        no_center,
+        ..
+    }) = &cli.command
+    else {
+        return Ok(());
+    };
+    if cli.stream {
+        return Err(
+            "banner draws all of its text at once, so it can't be used with --stream".into(),
+        );
+    }
+    let matches = Cli::command().get_matches_from(args.iter());
+    let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+    let mut defaults = Vec::new();
+    if let Some(message) = message {
+        if cli.text.is_some() {
+            return Err("banner's text was given twice, after banner and as --text".into());
+        }
+        defaults.extend(["--text", message.as_str()]);
+    }
+    if !plain && !given("font_file") && !given("big") {
+        defaults.push("--big");
+    }
+    if !given("border") {
+        defaults.extend(["--border", "rounded"]);
+    }
+    if !given("pad") {
+        defaults.extend(["--pad", "1"]);
+    }
+    if !no_center
+        && !["center", "center_h", "center_v"]
+            .iter()
+            .any(|id| given(id))
+    {
+        defaults.push("--center");
+    }
+    if defaults.is_empty() {
+        return Ok(());
+    }
+    args.extend(defaults.into_iter().map(OsString::from));
+    *cli = Cli::try_parse_from(args.iter()).unwrap_or_else(|err| err.exit());
+    Ok(())
+}
+
+/// Gives the effect the `--theme` look, or the config's theme, by adding
+/// its flags to `args` for every one the command line leaves unset.
+fn apply_theme(cli: &mut Cli, args: &mut Vec<OsString>) -> Result<(), Box<dyn std::error::Error>> {
+    let name = match &cli.theme {
+        Some(name) => name.clone(),
+        None => match config::load_theme()? {
+            Some(name) => name,
+            None => return Ok(()),
+        },
+    };
+    let theme = themes::named(&name).ok_or_else(|| {
+        let names: Vec<&str> = themes::THEMES.iter().map(|theme| theme.name).collect();
+        format!(
+            "Unknown theme '{name}' in the config. Built-in themes: {}",
+            names.join(", ")
+        )
+    })?;
+
+    let cli_command = Cli::command();
+    let matches = cli_command.clone().get_matches_from(args.iter());
+    let Some((effect, sub_matches)) = matches.subcommand() else {
+        return Ok(());
+    };
+    let Some(subcommand) = cli_command.find_subcommand(effect) else {
+        return Ok(());
+    };
+    let open = |flag: &str| {
+        subcommand
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(flag))
+            .is_some_and(|arg| {
+                let id = arg.get_id().as_str();
+                !(sub_matches.ids().any(|given| given == id)
+                    && sub_matches.value_source(id) == Some(ValueSource::CommandLine))
+            })
+    };
+    let themed = theme.args(effect, open);
+    if themed.is_empty() {
+        return Ok(());
+    }
+    args.extend(themed.into_iter().map(OsString::from));
+    *cli = Cli::try_parse_from(args.iter()).unwrap_or_else(|err| err.exit());
+    Ok(())
+}
+
+/// Milliseconds between frames at `fps` frames per second.
+fn frame_interval(fps: f64) -> u64 {
+    (1000.0 / fps).round().max(1.0) as u64
+}
+
+/// Whether the command line in `args` sets the effect's `--speed` itself.
+fn speed_typed(args: &[OsString]) -> bool {
+    let matches = Cli::command().get_matches_from(args);
+    matches.subcommand().is_some_and(|(_, sub_matches)| {
+        sub_matches.ids().any(|id| id == "speed")
+            && sub_matches.value_source("speed") == Some(ValueSource::CommandLine)
+    })
+}
+
+/// Shows the effect over and over under `--control-socket`, taking the
+/// commands that came in after each run: new text or a new preset change
+/// what the next run shows, and a trigger runs an effect once over the
+/// text before going back to it. A command stops the running effect, so
+/// effects that animate forever make way too. With nothing left to show,
+/// it waits for the next command.
+#[cfg(unix)]
+fn run_controlled(
+    path: &std::path::Path,
+    cli: Cli,
+    args: Vec<OsString>,
+    input: Option<Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if cli.command.consumes_stdin() || matches!(cli.command, Commands::Preset { .. }) {
+        return Err(
+            "--control-socket reruns the effect over its text, so it can't be used with \
+             effects that read stdin themselves"
+                .into(),
+        );
+    }
+    let socket = control::ControlSocket::bind(path)
+        .map_err(|err| format!("--control-socket can't listen on {}: {err}", path.display()))?;
+    let text = match input {
+        Some(lines) => lines,
+        None => input_text(&cli)?,
+    };
+    serve_controls(&socket, text, args, false)
+}
+
+/// Keeps a banner up on the alternate screen for `daemon`, showing `--text`
+/// (or nothing) until the socket's commands say otherwise. Each run starts
+/// on a cleared screen, so the banner stays at the top.
+#[cfg(unix)]
+fn run_daemon(
+    path: &std::path::Path,
+    cli: &Cli,
+    effect_args: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let look = if effect_args.is_empty() {
+        preset_command_line(&["shine".to_string(), "--cycles".to_string(), "0".to_string()])
+    } else {
+        preset_command_line(effect_args)
+    };
+    if cli.control_socket.is_some() {
+        return Err("daemon listens on its --socket, so it doesn't take --control-socket".into());
+    }
+    controlled_cli(&look)?;
+    let socket = control::ControlSocket::bind(path)
+        .map_err(|err| format!("daemon can't listen on {}: {err}", path.display()))?;
+    socket.quit_on_ctrl_c();
+    let text = cli.text.as_deref().map(text_lines).unwrap_or_default();
+    let _alt_screen = screen::TerminalGuard::alt_screen()?;
+    serve_controls(&socket, text, look, true)
+}
+
+/// Runs the `look` over `text` again after each command that changes
+/// either, for `--control-socket` and `daemon`. `clears` starts each run
+/// on a cleared screen.
+#[cfg(unix)]
+fn serve_controls(
+    socket: &control::ControlSocket,
+    mut text: Vec<String>,
+    mut look: Vec<OsString>,
+    clears: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut once = None;
+    let mut first = true;
+    loop {
+        let run_args: Vec<OsString> = once.take().unwrap_or_else(|| look.clone());
+        let mut cli = controlled_cli(&run_args)?;
+        apply_fps(&mut cli, &run_args)?;
+        if clears {
+            let mut stdout = output::stdout();
+            write!(stdout, "\x1b[H\x1b[2J")?;
+            stdout.flush()?;
+        }
+        // Interrupted effects stop mid-line, without ending it
+        let mut mid_line = false;
+        match run_effects(cli, Some(text.clone())) {
+            Ok(()) => {}
+            Err(err) if err.is::<playback::Interrupted>() => mid_line = true,
+            Err(err) if err.is::<playback::Quit>() => return Ok(()),
+            // A bad look to start with is a bad command line
+            Err(err) if first => return Err(err),
+            Err(err) => eprintln!("clifx: {err}"),
+        }
+        first = false;
+
+        // Take every command that's come in, then wait for more only if
+        // none of them changed what's shown
+        let mut changed = false;
+        loop {
+            let request = match socket.try_next() {
+                Some(request) => request,
+                None if changed => break,
+                None => match socket.next() {
+                    Some(request) => request,
+                    None => return Ok(()),
+                },
+            };
+            let outcome = match &request.command {
+                control::Command::Text(lines) => {
+                    text = lines.clone();
+                    Ok(())
+                }
+                control::Command::Effect(effect) => {
+                    let args = preset_command_line(effect);
+                    controlled_cli(&args).map(|_| look = args)
+                }
+                control::Command::Color(color) => parse_color(color).and_then(|_| {
+                    let mut args = look.clone();
+                    args.extend([OsString::from("--color"), OsString::from(color)]);
+                    controlled_cli(&args).map(|_| look = args)
+                }),
+                control::Command::Preset(name) => {
+                    preset_arguments(name, &[], &mut expr::Variables::new()).and_then(|args| {
+                        controlled_cli(&args)?;
+                        look = args;
+                        Ok(())
+                    })
+                }
+                control::Command::Trigger(trigger) => {
+                    let args = preset_command_line(trigger);
+                    controlled_cli(&args).map(|_| once = Some(args))
+                }
+                control::Command::Quit => {
+                    if mid_line && !clears {
+                        output::newline()?;
+                    }
+                    request.reply(Ok(()));
+                    return Ok(());
+                }
+            };
+            changed |= outcome.is_ok();
+            request.reply(outcome.map_err(|err| err.to_string()));
+        }
+    }
+}
+
+/// Runs the effect in `effect_args` over the text from `source`, and again
+/// each time it changes. Effects that animate forever make way for the new
+/// text; ones that end wait for it. Ctrl-C, or quitting with a key, stops.
+fn run_watched(
+    source: watch::Source,
+    interval: Duration,
+    effect_args: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let limit = terminal_rows();
+    let mut text = source.read(limit)?;
+    let command_line = preset_command_line(effect_args);
+    text_effect_cli(&command_line, &text, "watch")?;
+    let watcher = watch::Watcher::start(source, interval, limit, text.clone());
+    let clears = io::stdout().is_terminal() && !json_frames::is_active();
+
+    let mut mid_line = false;
+    loop {
+        let cli = text_effect_cli(&command_line, &text, "watch")?;
+        let mut stdout = output::stdout();
+        if clears {
+            write!(stdout, "\x1b[H\x1b[2J")?;
+        } else if mid_line {
+            writeln!(stdout)?;
+        }
+        stdout.flush()?;
+        mid_line = false;
+        match run_effects(cli, Some(text.clone())) {
+            Ok(()) => {}
+            Err(err) if err.is::<playback::Interrupted>() => mid_line = true,
+            Err(err) if err.is::<playback::Quit>() => return Ok(()),
+            Err(err) => return Err(err),
+        }
+        match watcher.next() {
+            watch::Event::Changed(lines) => text = lines,
+            watch::Event::Stop => {
+                if mid_line {
+                    output::newline()?;
+                }
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Parses the effect a command such as `watch` runs over `text`, with
+/// expressions over lines and chars going by it. `name` is the command,
+/// for errors.
+fn text_effect_cli(
+    args: &[OsString],
+    text: &[String],
+    name: &str,
+) -> Result<Cli, Box<dyn std::error::Error>> {
+    let mut variables = expr::Variables::new();
+    variables.use_text(text.to_vec(), false);
+    let mut args = expr::resolve_args(&Cli::command(), args, &mut variables)?;
+    // Report bad flags for the effect the way clap reports the rest
+    let mut cli = Cli::try_parse_from(&args).unwrap_or_else(|err| err.exit());
+    apply_theme(&mut cli, &mut args)?;
+    if cli.command.consumes_stdin() || matches!(cli.command, Commands::Preset { .. }) {
+        return Err(format!(
+            "{name} shows the effect over its own text, so it can't run effects that read stdin \
+             themselves"
+        )
+        .into());
+    }
+    apply_fps(&mut cli, &args)?;
+    Ok(cli)
+}
+
+/// Prints the frame the effect in `effect_args` draws first over `text`,
+/// `at` of the way into its cycle, as styled text without any animation.
+fn run_snapshot(
+    text: Vec<String>,
+    at: f32,
+    shell: SnapshotShell,
+    effect_args: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let command_line = preset_command_line(effect_args);
+    let mut cli = text_effect_cli(&command_line, &text, "snapshot")?;
+    cli.phase = at;
+    // A prompt captures stdout, which would otherwise get the text at rest
+    cli.force_animation = true;
+    cli.loop_forever = false;
+    snapshot::start();
+    let result = run_effects(cli, Some(text));
+    let frame = snapshot::finish(shell);
+    match result {
+        Err(err) if !err.is::<playback::Interrupted>() => return Err(err),
+        _ => {}
+    }
+    let mut stdout = output::stdout();
+    stdout.write_all(frame.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Parses a command line given to a running `--control-socket`, which can
+/// pick any effect that shows the text it's handed.
+#[cfg(unix)]
+fn controlled_cli(args: &[OsString]) -> Result<Cli, Box<dyn std::error::Error>> {
+    let cli = Cli::try_parse_from(args)?;
+    if cli.command.consumes_stdin() || matches!(cli.command, Commands::Preset { .. }) {
+        return Err(
+            "that effect reads stdin itself, so it can't show the control socket's text".into(),
+        );
+    }
+    Ok(cli)
+}
+
+/// Quitting with a key is a normal way for an animation to end.
+fn quit_is_success(
+    result: Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match result {
+        Err(err) if err.is::<playback::Quit>() => Ok(()),
+        result => result,
+    }
+}
+
+/// Exits the way the command `spin` ran did, once everything else is
+/// written, rather than reporting its failure as clifx's own.
+fn exit_like_spun_command(
+    result: Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(failed) = result
+        .as_ref()
+        .err()
+        .and_then(|err| err.downcast_ref::<spin::Failed>())
+    {
+        std::process::exit(failed.code);
+    }
+    result
+}
+
+/// Runs the effect on stdin, or on `input` if stdin was already read to
+/// evaluate an expression.
+fn run_effects(mut cli: Cli, input: Option<Vec<String>>) -> Result<(), Box<dyn std::error::Error>> {
+    rng::init(cli.seed);
+    capability::init(cli.color_mode());
+    if cli.simple_tty {
+        match cli.command {
+            Commands::Shine(ShineArgs {
+                all_lines: true, ..
+            }) => {
+                return Err(
+                    "--simple-tty redraws one line at a time, so it can't be used with --all-lines"
+                        .into(),
+                )
+            }
+            Commands::Shine(_) | Commands::Pulse(_) => screen::use_simple_tty(),
+            _ => return Err("--simple-tty works with shine and pulse only".into()),
+        }
+    }
+    if cli.no_clear {
+        screen::use_no_clear();
+    }
+    if cli.inline {
+        match cli.command {
+            Commands::Shine(ShineArgs {
+                all_lines: true, ..
+            }) => {
+                return Err(
+                    "--inline draws a single line, so it can't be used with --all-lines".into(),
+                )
+            }
+            Commands::Shine(_) | Commands::Pulse(_) | Commands::Twinkle(_) => {}
+            _ => return Err("--inline works with shine, pulse, and twinkle only".into()),
+        }
+    }
+    let boxing = cli.boxing();
+    if !boxing.is_empty() && cli.command.consumes_stdin() {
+        return Err(
+            "--margin, --pad, and --border box the input text, and this effect doesn't take any"
+                .into(),
+        );
+    }
+    if !boxing.is_empty() && matches!(cli.command, Commands::Slide(_) | Commands::Bounce(_)) {
+        return Err(
+            "--margin, --pad, and --border box the text where it rests, and this effect brings it in from past the box"
+                .into(),
+        );
+    }
+    if let Commands::Shine2d(Shine2dArgs { terminal_width, .. })
+    | Commands::Gradient(GradientArgs { terminal_width, .. }) = &mut cli.command
+    {
+        // Text that lays itself out wraps to fit inside the box
+        if terminal_width.is_none() && !boxing.is_empty() {
+            let width = screen::size().map_or(80, |(width, _)| width);
+            *terminal_width = Some(width.saturating_sub(2 * boxing.inset()).max(1) as usize);
+        }
+    }
+    let _alt_screen = if cli.alt_screen {
+        Some(screen::TerminalGuard::alt_screen()?)
+    } else {
+        None
+    };
+
+    if let Commands::Watch(WatchArgs {
+        run,
+        interval,
+        args,
+    }) = &cli.command
+    {
+        let source = match run {
+            Some(command) => watch::Source::Command(command.clone()),
+            None if !cli.input_file.is_empty() => watch::Source::Files(cli.input_file.clone()),
+            None => return Err("watch needs a --file to watch or a --run command".into()),
+        };
+        return run_watched(source, Duration::from_millis(*interval), args);
+    }
+    if let Commands::Snapshot(SnapshotArgs { at, shell, args }) = &cli.command {
+        let text = match input {
+            Some(lines) => lines,
+            None => input_text(&cli)?,
+        };
+        return run_snapshot(text, *at, *shell, args);
+    }
+    if let Commands::Spin(SpinArgs {
+        label,
+        color,
+        highlight,
+        success_color,
+        failure_color,
+        speed,
+        period,
+        flourish,
+        run,
+    }) = &cli.command
+    {
+        let config = SpinConfig {
+            label: label.clone().unwrap_or_else(|| run.join(" ")),
+            color: parse_color(color)?,
+            highlight: parse_color(highlight)?,
+            success_color: parse_color(success_color)?,
+            failure_color: parse_color(failure_color)?,
+            speed: *speed,
+            period: *period,
+            flourish: *flourish,
+            levels: cli.levels,
+        };
+        // The command's output passes straight through when nothing watches it
+        let animate = cli.force_animation || io::stdout().is_terminal();
+        return run_spin(run, &config, animate);
+    }
+
+    let font = cli.font()?;
+
+    if cli.text.is_some()
+        && cli.command.consumes_stdin()
+        && !matches!(cli.command, Commands::Topbar(_))
+    {
+        return Err("--text is input to animate, and this effect doesn't take any".into());
+    }
+    if !cli.input_file.is_empty() && cli.command.consumes_stdin() {
+        return Err("--file is input to animate, and this effect doesn't take any".into());
+    }
+    if input.is_some() && (cli.stream || cli.command.consumes_stdin()) {
+        return Err(
+            "lines, chars, and longest need all of stdin up front, so they can't be used \
+             with --stream or effects that read stdin themselves"
+                .into(),
+        );
+    }
+
+    // Effects that handle stdin themselves (countup, topbar) stream either way
+    if cli.stream && !cli.command.consumes_stdin() {
+        cli.command.limit_cycles();
+        let _playback = keyboard_controls(&cli)?;
+        return run_stream(&cli, font.as_ref());
+    }
+
+    // Read all input first, except for effects that consume stdin as it arrives
+    let mut input_lines = Vec::new();
+    if let Some(lines) = input {
+        let filter = cli.input_filter();
+        input_lines.extend(lines.iter().map(|line| filter.apply(line)));
+    } else if !cli.command.consumes_stdin() {
+        let filter = cli.input_filter();
+        input_lines.extend(input_text(&cli)?.iter().map(|line| filter.apply(line)));
+    }
+    let input_lines = strip_markers(&cli.command, render_banner(font.as_ref(), input_lines));
+    let input_lines = flow_lines(&cli, input_lines);
+    let input_lines = match cli.line_colors()? {
+        Some(colors) => tint_lines(&colors, 0, input_lines),
+        None => input_lines,
+    };
+    let border_color = parse_color(&cli.border_color)?;
+    let static_style = if cli.renders_static() {
+        cli.command.static_style()?
+    } else {
+        None
+    };
+    // Written out at rest, the box is part of the lines; animated, it's
+    // drawn around wherever the effect draws them
+    let input_lines = match static_style {
+        Some(_) if !boxing.is_empty() => {
+            boxing.around(&input_lines, |glyphs| ansi::tint(glyphs, border_color))
+        }
+        _ => input_lines,
+    };
+    let input_lines = if cli.centers_across_only() && (static_style.is_some() || boxing.is_empty())
+    {
+        let drawn = drawn_rows(&cli.command, &input_lines, font.as_ref(), cli.flow());
+        indent_to_center(input_lines, center::content_size(&drawn).0)
+    } else {
+        input_lines
+    };
+    if let Some(style) = static_style {
+        let mut stdout = output::stdout();
+        for line in &input_lines {
+            throughput::write_static(&mut stdout, line, &style, "\n")?;
+        }
+        stdout.flush()?;
+        return Ok(());
+    }
+    if cli.inline {
+        if input_lines.len() > 1 {
+            return Err("--inline draws a single line, but the input has several".into());
+        }
+        screen::use_inline()
+            .map_err(|err| format!("--inline can't read the cursor position: {err}"))?;
+    }
+    let reruns = cli.loop_forever && !cli.command.loop_forever()?;
+    if let Some(protocol) = cli.backend.resolve(&input_lines)? {
+        graphics::start(protocol);
+    }
+
+    // Calculate centering offsets if needed
+    let drawn = drawn_rows(&cli.command, &input_lines, font.as_ref(), cli.flow());
+    let content = center::content_size(&drawn);
+    let inset = boxing.inset();
+    let centering_offsets = if cli.centers_on_screen() {
+        let offsets = calculate_centering_offsets(boxing.outer_size(content))?;
+        let horizontally = cli.center || cli.center_h;
+        let offsets = (
+            offsets.top + inset,
+            if horizontally { offsets.left } else { 0 } + inset,
+        );
+        // Effects recenter this content if the terminal is resized
+        resize::center_content(content, offsets, horizontally);
+        Some(offsets)
+    } else if !boxing.is_empty() {
+        Some(make_room_for_box(&cli, boxing, content)?)
+    } else {
+        None
+    };
+    if let Some(offsets) = centering_offsets.filter(|_| !boxing.is_empty()) {
+        let color = effects::stage::rgb_color(border_color);
+        let mut stdout = output::stdout();
+        boxing.draw(content, offsets, color, &mut stdout)?;
+        stdout.flush()?;
+        resize::frame_content(boxing, color);
+    }
+    let placement = Placement::new(centering_offsets);
+
+    let _playback = keyboard_controls(&cli)?;
+    if cli.loop_forever {
+        playback::interrupt_on_ctrl_c();
+    }
+    let result = match cli.soak {
+        Some(minutes) => soak::run(minutes, || run_command(&cli, &input_lines, placement)),
+        None if reruns => loop {
+            if let Err(err) = run_command(&cli, &input_lines, placement) {
+                break Err(err);
+            }
+            // Centered effects draw in place already; others start over where they began
+            if centering_offsets.is_none() && !drawn.is_empty() {
+                let mut stdout = output::stdout();
+                write!(stdout, "\x1b[{}A\r", drawn.len())?;
+                stdout.flush()?;
+            }
+        },
+        None => run_command(&cli, &input_lines, placement),
+    };
+    graphics::finish();
+    // Leave the cursor below the box rather than inside it
+    if let Some((top, _)) = placement
+        .at_row(drawn.len())
+        .offsets()
+        .filter(|_| result.is_ok() && !boxing.is_empty())
+    {
+        let mut stdout = output::stdout();
+        writeln!(stdout, "\x1b[{};1H", top + inset)?;
+        stdout.flush()?;
+    }
+    match result {
+        // Ctrl-C is how a loop ends, so it ends like the last cycle would
+        Err(err) if cli.loop_forever && err.is::<playback::Interrupted>() => {
+            output::newline()?;
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// The rows the effect draws, which are what gets centered, and what a
+/// rerun under --loop steps back over.
+fn drawn_rows(
+    command: &Commands,
+    input_lines: &[String],
+    font: Option<&FigFont>,
+    flow: Flow,
+) -> Vec<String> {
+    match *command {
+        Commands::Countup(_) => big_digits::render("00:00.0"),
+        // The bar and its percentage; labels run on past the centered part
+        Commands::Progress(ProgressArgs { width, .. }) => vec![" ".repeat(width as usize + 5)],
+        Commands::Countdown(CountdownArgs { from, .. }) => {
+            digit_rows(&format_remaining(from, from), font)
+        }
+        // Flames below the text are part of what's centered
+        Commands::Fire(FireArgs {
+            mode: FireMode::Below,
+            height,
+            ..
+        }) => {
+            let mut rows = input_lines.to_vec();
+            rows.extend(std::iter::repeat_n(String::new(), height as usize));
+            rows
+        }
+        // Text that lays itself out is centered as it wraps
+        Commands::Shine2d(Shine2dArgs { terminal_width, .. })
+        | Commands::Gradient(GradientArgs { terminal_width, .. }) => {
+            let width = terminal_width
+                .unwrap_or_else(|| screen::size().map_or(80, |(width, _)| width as usize));
+            layout::flow_lines(input_lines, width, flow)
+        }
+        // Each line waves across the rows above and below it
+        Commands::Wave(WaveArgs { amplitude, .. }) => {
+            let band = vec![String::new(); amplitude as usize];
+            input_lines
+                .iter()
+                .flat_map(|line| [band.clone(), vec![line.clone()], band.clone()].concat())
+                .collect()
+        }
+        // The glow reaches past the text on every side
+        Commands::Neon(NeonArgs { glow_radius, .. }) => {
+            let (width, height) = center::content_size(input_lines);
+            let (margin_x, margin_y) = neon::glow_margins(glow_radius);
+            vec![" ".repeat(width as usize + 2 * margin_x); height as usize + 2 * margin_y]
+        }
+        // The heart beats after the last line
+        Commands::Heartbeat(HeartbeatArgs { heart: true, .. }) => {
+            let mut rows = input_lines.to_vec();
+            match rows.last_mut() {
+                Some(last) if !last.is_empty() => last.push_str(" ♥"),
+                Some(last) => last.push('♥'),
+                None => rows.push("♥".to_string()),
+            }
+            rows
+        }
+        // The text is shaken past its place on either side
+        Commands::Shake(ShakeArgs {
+            amplitude,
+            vertical,
+            ..
+        }) => {
+            let (width, height) = center::content_size(input_lines);
+            let (margin_x, margin_y) = shake::shake_margins(amplitude as usize, vertical);
+            vec![" ".repeat(width as usize + 2 * margin_x); height as usize + 2 * margin_y]
+        }
+        // Sparkles reach past the text on every side
+        Commands::Sparkle(_) => {
+            let (width, height) = center::content_size(input_lines);
+            vec![
+                " ".repeat(width as usize + 2 * sparkle::MARGIN_X);
+                height as usize + 2 * sparkle::MARGIN_Y
+            ]
+        }
+        // Center the window rather than the text scrolling through it
+        Commands::Marquee(MarqueeArgs {
+            window_width: Some(width),
+            ..
+        }) => vec![" ".repeat(width as usize); input_lines.len()],
+        Commands::Compose(_) => input_lines
+            .iter()
+            .map(|line| markup::strip_markers(line))
+            .collect(),
+        _ => input_lines.to_vec(),
+    }
+}
+
+/// Animates stdin line by line as it arrives. When lines come faster than
+/// `--max-animated-rate` or start queueing up, they're written in the
+/// effect's resting style instead, until input slows down again. Off a
+/// terminal every line is written that way.
+fn run_stream(cli: &Cli, font: Option<&FigFont>) -> Result<(), Box<dyn std::error::Error>> {
+    let lines = spawn_stamped_line_reader(cli.input_filter());
+    let style = cli.command.static_style()?;
+    let mut throughput = Throughput::new(cli.max_animated_rate);
+    // Keyboard controls put terminals in raw mode, where newlines don't return the carriage
+    let line_end = if io::stdout().is_terminal() {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut stdout = output::stdout();
+    let line_colors = cli.line_colors()?;
+    // Rows so far, so each line's color follows on from the last one's
+    let mut rows = 0;
+
+    let mut queued = VecDeque::new();
+    loop {
+        if queued.is_empty() {
+            match lines.recv() {
+                Ok(line) => queued.push_back(line),
+                Err(_) => break,
+            }
+        }
+        queued.extend(lines.try_iter());
+        let Some((arrived, line)) = queued.pop_front() else {
+            break;
+        };
+
+        let mut rendered = strip_markers(&cli.command, render_banner(font, vec![line]));
+        if let Some(colors) = &line_colors {
+            let count = rendered.len();
+            rendered = tint_lines(colors, rows, rendered);
+            rows += count;
+        }
+        if cli.center_h {
+            let width = center::content_size(&rendered).0;
+            rendered = indent_to_center(rendered, width);
+        }
+        let path = match &style {
+            Some(_) if cli.renders_static() => Path::Static,
+            Some(_) => throughput.path(arrived, Instant::now(), !queued.is_empty()),
+            None => Path::Animated,
+        };
+        match (path, &style) {
+            (Path::Static, Some(style)) => {
+                for line in &rendered {
+                    throughput::write_static(&mut stdout, line, style, line_end)?;
+                }
+                // Batch everything already read into one write
+                if queued.is_empty() {
+                    stdout.flush()?;
+                }
+            }
+            _ => {
+                stdout.flush()?;
+                run_command(cli, &rendered, Placement::default())?;
+            }
+        }
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Turns on pause, quit, and speed keys when the animation is on a
+/// terminal. Keys are read from the terminal, so stdin must not be one the
+/// effect still reads lines from, and topbar's passthrough output needs the
+/// newline handling raw mode turns off. The runs behind a comparison keep
+/// their own clocks, which the keys can't reach, and pick reads its own keys.
+fn keyboard_controls(cli: &Cli) -> io::Result<Option<playback::Playback>> {
+    let reads_terminal = io::stdin().is_terminal() && (cli.stream || cli.command.consumes_stdin());
+    if !io::stdout().is_terminal()
+        || json_frames::is_active()
+        || snapshot::is_active()
+        || reads_terminal
+        || matches!(
+            cli.command,
+            Commands::Topbar(_) | Commands::Spin(_) | Commands::Compare(_) | Commands::Pick(_)
+        )
+    {
+        return Ok(None);
+    }
+    playback::start().map(Some)
+}
+
+/// Expands every line into banner rows when a FIGlet font was given.
+fn render_banner(font: Option<&FigFont>, lines: Vec<String>) -> Vec<String> {
+    match font {
+        Some(font) => lines
+            .iter()
+            .flat_map(|line| font.render(&strip_ansi_codes(line)))
+            .collect(),
+        None => lines,
+    }
+}
+
+/// Tints each of `lines` with the next of `colors`, the first with the one
+/// `first` rows in, going round again at the end.
+fn tint_lines(colors: &[Rgb], first: usize, lines: Vec<String>) -> Vec<String> {
+    if colors.is_empty() {
+        return lines;
+    }
+    lines
+        .iter()
+        .enumerate()
+        .map(|(row, line)| ansi::tint(line, colors[(first + row) % colors.len()]))
+        .collect()
+}
+
+/// Rewraps and aligns the lines as `--wrap` and `--align` ask, to the
+/// terminal's width. Shine2d and gradient lay their text out themselves
+/// when they animate, so they can rewrap it when the terminal is resized.
+/// Under `--center-h` they wrap here too, so each row is indented alike.
+fn flow_lines(cli: &Cli, lines: Vec<String>) -> Vec<String> {
+    let lays_out_itself = matches!(cli.command, Commands::Shine2d(_) | Commands::Gradient(_));
+    let asked = cli.wrap.is_some() || cli.align.is_some();
+    let flows = if lays_out_itself {
+        cli.centers_across_only() || (asked && cli.renders_static())
+    } else {
+        asked
+    };
+    if !flows {
+        return lines;
+    }
+    let width = screen::size().map(|(width, _)| width).unwrap_or(80);
+    // Boxed text wraps to fit inside the box
+    let width = width.saturating_sub(2 * cli.boxing().inset()).max(1);
+    layout::flow_lines(&lines, width as usize, cli.flow())
+}
+
+/// Makes room below the cursor for the box around content of `content`
+/// size, scrolling if need be, and returns where the content starts in it,
+/// for a box when output isn't centered on the screen.
+fn make_room_for_box(
+    cli: &Cli,
+    boxing: Boxing,
+    content: (u16, u16),
+) -> Result<(u16, u16), Box<dyn std::error::Error>> {
+    let (width, height) = boxing.outer_size(content);
+    let mut stdout = output::stdout();
+    write!(stdout, "{}", "\n".repeat(height as usize))?;
+    stdout.flush()?;
+    let (_, below) = crossterm::cursor::position().map_err(|err| {
+        format!("--margin, --pad, and --border need the cursor position unless centered: {err}")
+    })?;
+    let left = if cli.center_h {
+        screen::size().map_or(0, |(terminal_width, _)| {
+            terminal_width.saturating_sub(width) / 2
+        })
+    } else {
+        0
+    };
+    // The box is drawn among what's already on the screen, not on a cleared one
+    screen::use_no_clear();
+    Ok((
+        below.saturating_sub(height) + boxing.inset(),
+        left + boxing.inset(),
+    ))
+}
+
+/// Indents `lines` so content `width` columns wide sits in the middle of
+/// the terminal, for `--center-h`.
+fn indent_to_center(lines: Vec<String>, width: u16) -> Vec<String> {
+    let terminal_width = screen::size().map_or(80, |(width, _)| width);
+    let indent = " ".repeat((terminal_width.saturating_sub(width) / 2) as usize);
+    lines
+        .into_iter()
+        .map(|line| format!("{indent}{line}"))
+        .collect()
+}
+
+/// Removes `{{fx:...}}` markers for every effect but compose, which runs them.
+fn strip_markers(command: &Commands, lines: Vec<String>) -> Vec<String> {
+    if matches!(command, Commands::Compose(_)) || !markup::has_markers(&lines) {
+        return lines;
+    }
+    lines
+        .iter()
+        .map(|line| markup::strip_markers(line))
+        .collect()
+}
+
+fn run_command(
+    cli: &Cli,
+    input_lines: &[String],
+    placement: Placement,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match cli.command.clone() {
+        Commands::Banner(BannerArgs {
+            stops,
+            shine_color,
+            width,
+            speed,
+            duration,
+            cycles,
+            ..
+        }) => {
+            let stops = parse_color_list(&stops)?;
+            let shine_rgb = parse_color(&shine_color)?;
+            // The gradient the banner rests in is the base the shine passes over
+            let style = StaticStyle::Gradient(stops.clone());
+            let mut lines = Vec::with_capacity(input_lines.len());
+            for line in input_lines {
+                let mut colored = Vec::new();
+                throughput::write_static(&mut colored, line, &style, "")?;
+                lines.push(String::from_utf8_lossy(&colored).into_owned());
+            }
+            let base = stops.first().copied().unwrap_or((255, 255, 255));
+
+            let config = ShineConfig {
+                base_color: base,
+                speed,
+                easing: EasingFunction::EaseInOut,
+                duration,
+                cycles,
+                start: ShineStart::Beginning,
+                alternate_direction: false,
+                width,
+                blur: true,
+                padding: width,
+                shine_color: shine_rgb,
+                shine_gradient: None,
+                pause_length: None,
+                pause_position: 0.5,
+                cycle_pre_delay: None,
+                cycle_post_delay: None,
+                cycle_switchback_delay: None,
+                opacity: 1.0,
+                min_contrast: None,
+                line_stagger: 0,
+                levels: cli.levels,
+                phase: cli.cycle_phase(),
+                color_mask: false,
+                mode: ShineMode::Single,
+                reflect: false,
+                terminal_width: None,
+            };
+
+            capability::cue_mono(MonoCue::reverse(base, shine_rgb));
+            if !lines.is_empty() {
+                apply_shine_all_lines(&lines, &config, placement)?;
+            }
+        }
+        Commands::Shine(ShineArgs {
+            color,
+            speed,
+            easing,
+            bezier,
+            duration,
+            cycles,
+            start,
+            alternate_direction,
+            width,
+            blur,
+            padding,
+            shine_color,
+            shine_gradient,
+            pause_length,
+            pause_position,
+            cycle_pre_delay,
+            cycle_post_delay,
+            cycle_switchback_delay,
+            opacity,
+            min_contrast,
+            all_lines,
+            line_stagger,
+            mode,
+            reflect,
+            terminal_width,
+        }) => {
+            let color_str = color.unwrap_or_else(generate_random_saturated_color);
+            let rgb = parse_color(&color_str)?;
+            let shine_rgb = parse_color(&shine_color)?;
+            let mask = cli.mask()?;
+            let masked = mask
+                .as_ref()
+                .map(|mask| mask.apply(input_lines, rgb_color(rgb)));
+            let input_lines = masked.as_deref().unwrap_or(input_lines);
+
+            let easing_func = with_bezier(easing, bezier)?;
+
+            let start_direction = match start {
+                StartType::Beginning => ShineStart::Beginning,
+                StartType::End => ShineStart::End,
+            };
+
+            let shine_mode = match mode {
+                ModeType::Single => ShineMode::Single,
+                ModeType::Split => ShineMode::Split,
+            };
+
+            let config = ShineConfig {
+                base_color: rgb,
+                speed,
+                easing: easing_func,
+                duration,
+                cycles,
+                start: start_direction,
+                alternate_direction,
+                width,
+                blur,
+                padding,
+                shine_color: shine_rgb,
+                shine_gradient: shine_gradient
+                    .as_deref()
+                    .map(parse_color_list)
+                    .transpose()?,
+                pause_length,
+                pause_position: pause_position.clamp(0.0, 1.0),
+                cycle_pre_delay,
+                cycle_post_delay,
+                cycle_switchback_delay,
+                opacity: opacity.clamp(0.0, 1.0),
+                min_contrast: min_contrast.map(|ratio| ratio.clamp(1.0, 21.0)),
+                line_stagger: line_stagger.unwrap_or(0),
+                levels: cli.levels,
+                phase: cli.cycle_phase(),
+                color_mask: cli.capture_input_colors_as_mask || mask.is_some(),
+                mode: shine_mode,
+                reflect,
+                terminal_width,
+            };
+
+            capability::cue_mono(MonoCue::reverse(rgb, shine_rgb));
+            if all_lines && !input_lines.is_empty() {
+                apply_shine_all_lines(input_lines, &config, placement)?;
+            } else {
+                for (row, line) in input_lines.iter().enumerate() {
+                    apply_shine_effect(line, &config, placement.at_row(row))?;
+                }
+            }
+        }
+        Commands::Shine2d(Shine2dArgs {
+            color,
+            speed,
+            easing,
+            bezier,
+            duration,
+            cycles,
+            start,
+            alternate_direction,
+            width,
+            blur,
+            padding,
+            shine_color,
+            pause_length,
+            pause_position,
+            cycle_pre_delay,
+            cycle_post_delay,
+            cycle_switchback_delay,
+            opacity,
+            min_contrast,
+            angle,
+            angle_sweep,
+            rotate,
+            shape,
+            origin,
+            terminal_width,
+        }) => {
+            use effects::shine2d::ShineStart as Shine2DShineStart;
+
+            let color_str = color.unwrap_or_else(generate_random_saturated_color);
+            let rgb = parse_color(&color_str)?;
+            let shine_rgb = parse_color(&shine_color)?;
+
+            let easing_func = with_bezier(easing, bezier)?;
+
+            let start_direction = match start {
+                StartType::Beginning => Shine2DShineStart::Beginning,
+                StartType::End => Shine2DShineStart::End,
+            };
+
+            let config = Shine2DConfig {
+                base_color: rgb,
+                speed,
+                easing: easing_func,
+                duration,
+                cycles,
+                start: start_direction,
+                alternate_direction,
+                width,
+                blur,
+                padding,
+                shine_color: shine_rgb,
+                pause_length,
+                pause_position: pause_position.clamp(0.0, 1.0),
+                cycle_pre_delay,
+                cycle_post_delay,
+                cycle_switchback_delay,
+                opacity: opacity.clamp(0.0, 1.0),
+                min_contrast: min_contrast.map(|ratio| ratio.clamp(1.0, 21.0)),
+                angle,
+                angle_sweep,
+                rotate,
+                shape,
+                origin,
+                terminal_width,
+                flow: cli.flow(),
+                levels: cli.levels,
+                phase: cli.cycle_phase(),
+            };
+
+            capability::cue_mono(MonoCue::reverse(rgb, shine_rgb));
+            let mut input_text = String::new();
+            for (i, line) in input_lines.iter().enumerate() {
+                if i > 0 {
+                    input_text.push('\n');
+                }
+                input_text.push_str(line);
+            }
+
+            apply_shine2d_effect(&input_text, &config, placement)?;
+        }
+        Commands::Gradient(GradientArgs {
+            stops,
+            angle,
+            animate,
+            speed,
+            duration,
+            cycles,
+            terminal_width,
+            preview_all_palettes,
+        }) => {
+            if preview_all_palettes {
+                let rows = gallery::render(input_lines, &gallery_looks()?)?;
+                return gallery::show(&rows);
+            }
+
+            let config = GradientConfig {
+                stops: parse_color_list(&stops)?,
+                angle,
+                animate,
+                speed,
+                duration,
+                cycles,
+                levels: cli.levels,
+                phase: cli.cycle_phase(),
+                terminal_width,
+                flow: cli.flow(),
+            };
+
+            apply_gradient_effect(&input_lines.join("\n"), &config, placement)?;
+        }
+        Commands::Twinkle(TwinkleArgs {
+            base_color,
+            twinkle_color,
+            speed,
+            easing,
+            bezier,
+            duration,
+            cycles,
+            twinkle_ratio,
+            min_twinkle_count,
+            max_twinkle_count,
+            twinkling_percentage,
+            star_mode,
+            target_chars,
+            target_regex,
+            sprites,
+            spawn_bias,
+        }) => {
+            let base_rgb = parse_color(&base_color)?;
+            let twinkle_rgb = parse_color(&twinkle_color)?;
+            let mask = cli.mask()?;
+            let masked = mask
+                .as_ref()
+                .map(|mask| mask.apply(input_lines, rgb_color(base_rgb)));
+            let input_lines = masked.as_deref().unwrap_or(input_lines);
+
+            let easing_func = with_bezier(easing, bezier)?;
+
+            let config = TwinkleConfig {
+                base_color: base_rgb,
+                twinkle_color: twinkle_rgb,
+                speed,
+                easing: easing_func,
+                duration,
+                cycles,
+                twinkle_ratio: Some(twinkle_ratio.clamp(0.0, 1.0)),
+                min_twinkle_count,
+                max_twinkle_count,
+                twinkling_percentage: twinkling_percentage.clamp(0.0, 1.0),
+                star_mode,
+                target_chars: match (&target_chars, &target_regex) {
+                    (Some(chars), _) => chars.chars().collect(),
+                    (None, Some(_)) => Vec::new(),
+                    (None, None) => vec!['.'],
+                },
+                target_regex: target_regex
+                    .map(|pattern| regex::Regex::new(&pattern))
+                    .transpose()?,
+                levels: cli.levels,
+                phase: cli.cycle_phase(),
+                color_mask: cli.capture_input_colors_as_mask || mask.is_some(),
+                sprites: resolve_sprites(&sprites)?,
+                spawn_bias,
+            };
+
+            capability::cue_mono(MonoCue::reverse(base_rgb, twinkle_rgb));
+            let density = (spawn_bias != SpawnBias::Uniform).then(|| density_map(input_lines));
+            for (row, line) in input_lines.iter().enumerate() {
+                let line_density = density.as_ref().map(|map| map[row].as_slice());
+                apply_twinkle_effect(line, &config, line_density, placement.at_row(row))?;
+            }
+        }
+        Commands::Pulse(PulseArgs {
+            color,
+            min_brightness,
+            max_brightness,
+            period,
+            speed,
+            easing,
+            bezier,
+            cycles,
+        }) => {
+            let min_brightness = min_brightness.clamp(0.0, 1.0);
+            let max_brightness = max_brightness.clamp(0.0, 1.0);
+            if min_brightness > max_brightness {
+                return Err("--min-brightness can't be greater than --max-brightness".into());
+            }
+
+            let config = PulseConfig {
+                color: parse_color(&color)?,
+                min_brightness,
+                max_brightness,
+                period,
+                speed,
+                easing: with_bezier(easing, bezier)?,
+                cycles,
+                levels: cli.levels,
+                phase: cli.cycle_phase(),
+            };
+
+            let (r, g, b) = config.color;
+            let at = |brightness: f32| {
+                let channel = |c: u8| (c as f32 * brightness).round() as u8;
+                (channel(r), channel(g), channel(b))
+            };
+            capability::cue_mono(MonoCue::blink(at(min_brightness), at(max_brightness)));
+            if screen::simple_tty() {
+                // Each line breathes on its own, since there's no moving back up
+                for (row, line) in input_lines.iter().enumerate() {
+                    apply_pulse_effect(std::slice::from_ref(line), &config, placement.at_row(row))?;
+                }
+            } else {
+                apply_pulse_effect(input_lines, &config, placement)?;
+            }
+        }
+        Commands::Blink(BlinkArgs {
+            color,
+            off_color,
+            on_ms,
+            off_ms,
+            pattern,
+            cycles,
+            photosensitive_safe,
+        }) => {
+            let steps = match &pattern {
+                Some(pattern) => parse_pattern(pattern, on_ms, off_ms)?,
+                None => BlinkConfig::default()
+                    .steps
+                    .iter()
+                    .map(|step| blink::Step {
+                        ms: if step.on { on_ms } else { off_ms },
+                        ..*step
+                    })
+                    .collect(),
+            };
+            let config = BlinkConfig {
+                color: parse_color(&color)?,
+                off_color: off_color.as_deref().map(parse_color).transpose()?,
+                steps,
+                cycles,
+                photosensitive_safe,
+            };
+
+            if let Some(off) = config.off_color {
+                capability::cue_mono(MonoCue::bold(off, config.color));
+            }
+            apply_blink_effect(input_lines, &config, placement)?;
+        }
+        Commands::Heartbeat(HeartbeatArgs {
+            color,
+            min_brightness,
+            bpm,
+            speed,
+            cycles,
+            heart,
+            heart_color,
+        }) => {
+            let config = HeartbeatConfig {
+                color: parse_color(&color)?,
+                min_brightness: min_brightness.clamp(0.0, 1.0),
+                bpm,
+                speed,
+                cycles,
+                heart,
+                heart_color: heart_color.as_deref().map(parse_color).transpose()?,
+                levels: cli.levels,
+                phase: cli.cycle_phase(),
+            };
+
+            let (r, g, b) = config.color;
+            let channel = |c: u8| (c as f32 * config.min_brightness).round() as u8;
+            capability::cue_mono(MonoCue::blink(
+                (channel(r), channel(g), channel(b)),
+                config.color,
+            ));
+            apply_heartbeat_effect(input_lines, &config, placement)?;
+        }
+        Commands::Wipe(WipeArgs {
+            color,
+            direction,
+            mode,
+            band_color,
+            band_width,
+            duration,
+            speed,
+            easing,
+            bezier,
+        }) => {
+            let config = WipeConfig {
+                color: parse_color(&color)?,
+                direction,
+                mode,
+                band_color: band_color.as_deref().map(parse_color).transpose()?,
+                band_width: band_width as usize,
+                duration,
+                speed,
+                easing: with_bezier(easing, bezier)?,
+            };
+
+            apply_wipe_effect(input_lines, &config, placement)?;
+        }
+        Commands::Dissolve(DissolveArgs {
+            color,
+            mode,
+            order,
+            duration,
+            speed,
+            easing,
+            bezier,
+        }) => {
+            let config = DissolveConfig {
+                color: parse_color(&color)?,
+                mode,
+                order,
+                duration,
+                speed,
+                easing: with_bezier(easing, bezier)?,
+            };
+
+            apply_dissolve_effect(input_lines, &config, placement)?;
+        }
+        Commands::Slide(SlideArgs {
+            color,
+            from,
+            duration,
+            speed,
+            easing,
+            bezier,
+            overshoot,
+        }) => {
+            let config = SlideConfig {
+                color: parse_color(&color)?,
+                from,
+                duration,
+                speed,
+                easing: with_bezier(easing, bezier)?,
+                overshoot: overshoot.max(0.0),
+            };
+
+            apply_slide_effect(input_lines, &config, placement)?;
+        }
+        Commands::Bounce(BounceArgs {
+            color,
+            gravity,
+            damping,
+            per_char,
+            speed,
+        }) => {
+            let config = BounceConfig {
+                color: parse_color(&color)?,
+                gravity: gravity.max(0.1),
+                damping: damping.clamp(0.0, 1.0),
+                per_char,
+                speed,
+                // Already there, the text goes when the alternate screen does
+                alt_screen: !cli.alt_screen,
+            };
+
+            apply_bounce_effect(input_lines, &config, placement)?;
+        }
+        Commands::Shake(ShakeArgs {
+            color,
+            amplitude,
+            vertical,
+            duration,
+            speed,
+        }) => {
+            let config = ShakeConfig {
+                color: parse_color(&color)?,
+                amplitude: amplitude as usize,
+                vertical,
+                duration,
+                speed,
+            };
 
-    match cli.command {
-        Commands::Shine {
+            apply_shake_effect(input_lines, &config, placement)?;
+        }
+        Commands::Glowworm(GlowwormArgs {
             color,
+            highlight,
+            length,
+            tail_decay,
             speed,
-            easing,
-            duration,
             cycles,
-            start,
-            width,
-            blur,
-            padding,
-            shine_color,
-            pause_length,
-            pause_position,
-            cycle_pre_delay,
-            cycle_post_delay,
-            cycle_switchback_delay,
-            opacity,
-        } => {
-            let color_str = color.unwrap_or_else(generate_random_saturated_color);
-            let rgb = parse_rgb_color(&color_str)?;
-            let shine_rgb = parse_rgb_color(&shine_color)?;
+        }) => {
+            let config = GlowwormConfig {
+                color: parse_color(&color)?,
+                highlight: parse_color(&highlight)?,
+                length: length as usize,
+                tail_decay: tail_decay.clamp(0.0, 1.0),
+                speed,
+                cycles,
+            };
 
-            let easing_func = match easing {
-                EasingType::Linear => EasingFunction::Linear,
-                EasingType::EaseIn => EasingFunction::EaseIn,
-                EasingType::EaseOut => EasingFunction::EaseOut,
-                EasingType::EaseInOut => EasingFunction::EaseInOut,
+            capability::cue_mono(MonoCue::bold(config.color, config.highlight));
+            apply_glowworm_effect(input_lines, &config, placement)?;
+        }
+        Commands::Karaoke(KaraokeArgs {
+            color,
+            highlight,
+            sung_color,
+            wpm,
+            timing_file,
+        }) => {
+            let config = KaraokeConfig {
+                color: parse_color(&color)?,
+                highlight: parse_color(&highlight)?,
+                sung_color: sung_color.as_deref().map(parse_color).transpose()?,
+                wpm,
+                timing: timing_file.as_deref().map(load_timing).transpose()?,
             };
 
-            let start_direction = match start {
-                StartType::Beginning => ShineStart::Beginning,
-                StartType::End => ShineStart::End,
+            capability::cue_mono(MonoCue::bold(config.color, config.highlight));
+            apply_karaoke_effect(input_lines, &config, placement)?;
+        }
+        Commands::Fade(FadeArgs {
+            color,
+            from_color,
+            mode,
+            duration,
+            stagger,
+            speed,
+            easing,
+            bezier,
+        }) => {
+            let config = FadeConfig {
+                color: parse_color(&color)?,
+                from_color: from_color.as_deref().map(parse_color).transpose()?,
+                mode,
+                duration,
+                stagger,
+                speed,
+                easing: with_bezier(easing, bezier)?,
+                levels: cli.levels,
             };
 
-            let config = ShineConfig {
-                base_color: rgb,
+            apply_fade_effect(input_lines, &config, placement)?;
+        }
+        Commands::Wave(WaveArgs {
+            color,
+            amplitude,
+            wavelength,
+            speed,
+            duration,
+            cycles,
+        }) => {
+            let config = WaveConfig {
+                color: parse_color(&color)?,
+                amplitude: amplitude as usize,
+                wavelength,
                 speed,
-                easing: easing_func,
                 duration,
                 cycles,
-                start: start_direction,
-                width,
-                blur,
-                padding,
-                shine_color: shine_rgb,
-                pause_length,
-                pause_position: pause_position.clamp(0.0, 1.0),
-                cycle_pre_delay,
-                cycle_post_delay,
-                cycle_switchback_delay,
-                opacity: opacity.clamp(0.0, 1.0),
+                phase: cli.cycle_phase(),
             };
 
-            for line in &input_lines {
-                apply_shine_effect(line, &config, centering_offsets)?;
+            // Each line waves across its own band of rows
+            let band = 2 * config.amplitude + 1;
+            for (i, line) in input_lines.iter().enumerate() {
+                apply_wave_effect(line, &config, placement.at_row(i * band))?;
             }
         }
-        Commands::Shine2d {
+        Commands::Spotlight(SpotlightArgs {
             color,
+            radius,
+            dim_factor,
+            path,
             speed,
-            easing,
             duration,
             cycles,
-            start,
-            width,
-            blur,
-            padding,
-            shine_color,
-            pause_length,
-            pause_position,
-            cycle_pre_delay,
-            cycle_post_delay,
-            cycle_switchback_delay,
-            opacity,
-            angle,
-            terminal_width,
-        } => {
-            use effects::shine2d::{
-                EasingFunction as Shine2DEasingFunction, ShineStart as Shine2DShineStart,
+            alternate_direction,
+        }) => {
+            let config = SpotlightConfig {
+                color: parse_color(&color)?,
+                radius: radius.max(0.5),
+                dim_factor: dim_factor.clamp(0.0, 1.0),
+                path,
+                speed,
+                duration,
+                cycles,
+                alternate_direction,
             };
 
-            let color_str = color.unwrap_or_else(generate_random_saturated_color);
-            let rgb = parse_rgb_color(&color_str)?;
-            let shine_rgb = parse_rgb_color(&shine_color)?;
+            let dimmed = |channel: u8| (channel as f32 * config.dim_factor).round() as u8;
+            let (r, g, b) = config.color;
+            capability::cue_mono(MonoCue::bold(
+                (dimmed(r), dimmed(g), dimmed(b)),
+                config.color,
+            ));
+            apply_spotlight_effect(input_lines, &config, placement)?;
+        }
+        Commands::Scramble(ScrambleArgs {
+            color_locked,
+            color_scrambled,
+            charset,
+            lock_delay,
+            speed,
+        }) => {
+            let config = ScrambleConfig {
+                color_locked: parse_color(&color_locked)?,
+                color_scrambled: parse_color(&color_scrambled)?,
+                charset: parse_charset(&charset)?,
+                lock_delay,
+                speed,
+            };
 
-            let easing_func = match easing {
-                EasingType::Linear => Shine2DEasingFunction::Linear,
-                EasingType::EaseIn => Shine2DEasingFunction::EaseIn,
-                EasingType::EaseOut => Shine2DEasingFunction::EaseOut,
-                EasingType::EaseInOut => Shine2DEasingFunction::EaseInOut,
+            capability::cue_mono(MonoCue::dim(config.color_locked, config.color_scrambled));
+            apply_scramble_effect(input_lines, &config, placement)?;
+        }
+        Commands::Fire(FireArgs {
+            color,
+            palette,
+            mode,
+            height,
+            wind,
+            duration,
+            speed,
+        }) => {
+            let config = FireConfig {
+                color: parse_color(&color)?,
+                palette,
+                mode,
+                height: height as usize,
+                wind,
+                duration,
+                speed,
+                quality: cli.quality,
             };
 
-            let start_direction = match start {
-                StartType::Beginning => Shine2DShineStart::Beginning,
-                StartType::End => Shine2DShineStart::End,
+            apply_fire_effect(input_lines, &config, placement)?;
+        }
+        Commands::Sparkle(SparkleArgs {
+            color,
+            colors,
+            density,
+            lifetime,
+            drift,
+            spawn_bias,
+            duration,
+            speed,
+        }) => {
+            let config = SparkleConfig {
+                color: parse_color(&color)?,
+                colors: parse_color_list(&colors)?,
+                density: density.clamp(0.0, 1.0),
+                lifetime,
+                drift: drift.max(0.0),
+                spawn_bias,
+                duration,
+                speed,
+                quality: cli.quality,
             };
 
-            let config = Shine2DConfig {
-                base_color: rgb,
+            apply_sparkle_effect(input_lines, &config, placement)?;
+        }
+        Commands::Neon(NeonArgs {
+            color,
+            glow_radius,
+            flicker_chance,
+            buzz,
+            duration,
+            speed,
+        }) => {
+            let config = NeonConfig {
+                color: parse_color(&color)?,
+                glow_radius,
+                flicker_chance: flicker_chance.clamp(0.0, 1.0),
+                buzz: buzz.clamp(0.0, 1.0),
+                duration,
                 speed,
-                easing: easing_func,
+            };
+
+            apply_neon_effect(input_lines, &config, placement)?;
+        }
+        Commands::Snow(SnowArgs {
+            color,
+            flake_color,
+            flake_chars,
+            density,
+            wind,
+            spawn_bias,
+            behind,
+            accumulate,
+            duration,
+            speed,
+        }) => {
+            let flake_chars: Vec<char> = flake_chars
+                .chars()
+                .filter(|ch| !ch.is_whitespace())
+                .collect();
+            if flake_chars.is_empty() {
+                return Err("--flake-chars needs at least one character".into());
+            }
+            let config = SnowConfig {
+                color: parse_color(&color)?,
+                flake_color: parse_color(&flake_color)?,
+                flake_chars,
+                density: density.clamp(0.0, 1.0),
+                wind,
+                spawn_bias,
+                behind,
+                accumulate,
+                duration,
+                speed,
+                quality: cli.quality,
+            };
+
+            apply_snow_effect(input_lines, &config)?;
+        }
+        Commands::Confetti(ConfettiArgs {
+            color,
+            palette,
+            burst_count,
+            gravity,
+            from,
+            speed,
+        }) => {
+            let config = ConfettiConfig {
+                color: parse_color(&color)?,
+                palette: parse_color_list(&palette)?,
+                burst_count: burst_count as usize,
+                gravity: gravity.max(0.1),
+                origin: from,
+                speed,
+                quality: cli.quality,
+            };
+
+            apply_confetti_effect(input_lines, &config)?;
+        }
+        Commands::Fireworks(FireworksArgs {
+            color,
+            colors,
+            sparks,
+            launch_every,
+            gravity,
+            duration,
+            finale_after,
+            speed,
+        }) => {
+            let config = FireworksConfig {
+                color: parse_color(&color)?,
+                colors: parse_color_list(&colors)?,
+                sparks: sparks as usize,
+                launch_every,
+                gravity: gravity.max(0.1),
                 duration,
+                finale_after,
+                speed,
+                quality: cli.quality,
+            };
+
+            apply_fireworks_effect(input_lines, &config)?;
+        }
+        Commands::Marquee(MarqueeArgs {
+            color,
+            window_width,
+            direction,
+            gap,
+            speed,
+            cycles,
+        }) => {
+            let config = MarqueeConfig {
+                color: parse_color(&color)?,
+                window_width: window_width.map(usize::from),
+                direction,
+                gap,
+                speed,
                 cycles,
-                start: start_direction,
-                width,
-                blur,
-                padding,
-                shine_color: shine_rgb,
-                pause_length,
-                pause_position: pause_position.clamp(0.0, 1.0),
-                cycle_pre_delay,
-                cycle_post_delay,
-                cycle_switchback_delay,
-                opacity: opacity.clamp(0.0, 1.0),
-                angle,
-                terminal_width,
             };
 
-            let mut input_text = String::new();
-            for (i, line) in input_lines.iter().enumerate() {
-                if i > 0 {
-                    input_text.push('\n');
-                }
-                input_text.push_str(line);
+            apply_marquee_effect(input_lines, &config, placement)?;
+        }
+        Commands::Topbar(TopbarArgs {
+            rows,
+            effect,
+            color,
+            highlight,
+            fill,
+            speed,
+            period,
+        }) => {
+            if fill.width().unwrap_or(0) == 0 {
+                return Err("--fill must be a visible character".into());
             }
 
-            apply_shine2d_effect(&input_text, &config, centering_offsets)?;
+            let config = TopbarConfig {
+                text: cli.text.clone().unwrap_or_default(),
+                rows,
+                effect,
+                color: parse_color(&color)?,
+                highlight: parse_color(&highlight)?,
+                fill,
+                speed,
+                period,
+            };
+
+            run_topbar(&config)?;
         }
-        Commands::Twinkle {
-            base_color,
-            twinkle_color,
+        Commands::Compose(ComposeArgs {
+            color,
+            highlight,
             speed,
-            easing,
             duration,
             cycles,
-            twinkle_ratio,
-            min_twinkle_count,
-            max_twinkle_count,
-            twinkling_percentage,
-            star_mode,
-        } => {
-            let base_rgb = parse_rgb_color(&base_color)?;
-            let twinkle_rgb = parse_rgb_color(&twinkle_color)?;
+        }) => {
+            let config = ComposeConfig {
+                color: parse_color(&color)?,
+                highlight: parse_color(&highlight)?,
+                speed,
+                duration,
+                cycles,
+                levels: cli.levels,
+                phase: cli.cycle_phase(),
+            };
 
-            let easing_func = match easing {
-                EasingType::Linear => TwinkleEasingFunction::Linear,
-                EasingType::EaseIn => TwinkleEasingFunction::EaseIn,
-                EasingType::EaseOut => TwinkleEasingFunction::EaseOut,
-                EasingType::EaseInOut => TwinkleEasingFunction::EaseInOut,
+            let document = markup::parse_document(input_lines)?;
+            apply_compose_effect(&document, &config, placement)?;
+        }
+        Commands::EasingPreview(EasingPreviewArgs {
+            easing,
+            color,
+            width,
+            height,
+            duration,
+            speed,
+            cycles,
+        }) => {
+            let config = EasingPreviewConfig {
+                easing: EasingFunction::parse(&easing)?,
+                color: parse_color(&color)?,
+                width: width as usize,
+                height: height as usize,
+                duration,
+                speed,
+                cycles,
             };
 
-            let config = TwinkleConfig {
-                base_color: base_rgb,
-                twinkle_color: twinkle_rgb,
+            apply_easing_preview(&config)?;
+        }
+        Commands::Countup(CountupArgs {
+            color,
+            lap_color,
+            speed,
+            duration,
+            lap_display,
+            no_tenths,
+        }) => {
+            let config = CountupConfig {
+                color: parse_color(&color)?,
+                lap_color: parse_color(&lap_color)?,
                 speed,
-                easing: easing_func,
                 duration,
-                cycles,
-                twinkle_ratio: Some(twinkle_ratio.clamp(0.0, 1.0)),
-                min_twinkle_count,
-                max_twinkle_count,
-                twinkling_percentage: twinkling_percentage.clamp(0.0, 1.0),
-                star_mode,
+                lap_display,
+                tenths: !no_tenths,
+                levels: cli.levels,
             };
 
-            for line in &input_lines {
-                apply_twinkle_effect(line, &config, centering_offsets)?;
-            }
+            let laps = spawn_line_reader(cli.input_filter());
+            apply_countup_effect(laps, &config, placement)?;
+        }
+        Commands::Progress(ProgressArgs {
+            color,
+            empty_color,
+            highlight,
+            width,
+            speed,
+            period,
+        }) => {
+            let config = ProgressConfig {
+                color: parse_color(&color)?,
+                empty_color: parse_color(&empty_color)?,
+                highlight: parse_color(&highlight)?,
+                width: width as usize,
+                speed,
+                period,
+                levels: cli.levels,
+            };
+
+            let lines = spawn_line_reader(cli.input_filter());
+            apply_progress_effect(lines, &config, placement)?;
+        }
+        Commands::Countdown(CountdownArgs {
+            from,
+            color,
+            accent_color,
+            transition,
+            flash,
+            speed,
+        }) => {
+            let config = CountdownConfig {
+                from,
+                color: parse_color(&color)?,
+                accent_color: parse_color(&accent_color)?,
+                transition,
+                flash,
+                speed,
+                levels: cli.levels,
+            };
+            let font = cli.font()?;
+
+            capability::cue_mono(MonoCue::reverse(config.color, config.accent_color));
+            apply_countdown_effect(&config, font.as_ref(), placement)?;
+        }
+        Commands::Compare(CompareArgs { left, right, speed }) => {
+            let seed = cli.seed.unwrap_or_else(|| rng::new_rng().gen());
+            let sides = [
+                compare_side(cli, "left", &left, seed)?,
+                compare_side(cli, "right", &right, seed)?,
+            ];
+            let config = CompareConfig { speed };
+            compare::run(&sides, input_lines, &config, placement)?;
         }
+        Commands::Pick(PickArgs { save }) => pick::run(input_lines, save.as_deref())?,
+        Commands::Replay(ReplayArgs { file, rate }) => clip::replay(&file, rate)?,
+        Commands::Preset { .. } => unreachable!("presets are resolved before running"),
+        Commands::Watch(_) => unreachable!("watch runs its effect itself"),
+        Commands::Spin(_) => unreachable!("spin runs before anything reads stdin"),
+        Commands::Snapshot(_) => unreachable!("snapshot runs its effect itself"),
+        Commands::Daemon(_) => unreachable!("daemon runs its effects itself"),
     }
 
     Ok(())
 }
 
+/// One side of a comparison, checked the way a typed command line would be.
+/// Global flags that change the animation itself are passed on; input
+/// transforms and fonts were already applied to the lines both sides get.
+fn compare_side(
+    cli: &Cli,
+    flag: &str,
+    config: &str,
+    seed: u64,
+) -> Result<Side, Box<dyn std::error::Error>> {
+    // Each side draws into a pipe compare reads its frames from
+    let mut args = vec![
+        "--force-animation".to_string(),
+        "--seed".to_string(),
+        seed.to_string(),
+    ];
+    if let Some(levels) = cli.levels {
+        args.extend(["--levels".to_string(), levels.to_string()]);
+    }
+    if cli.phase != 0.0 {
+        args.push(format!("--phase={}", cli.phase));
+    }
+    if let Some(mode) = cli.color_mode().to_possible_value() {
+        args.extend(["--color-mode".to_string(), mode.get_name().to_string()]);
+    }
+    if let Some(fps) = cli.fps {
+        args.push(format!("--fps={fps}"));
+    }
+    if cli.loop_forever {
+        args.push("--loop".to_string());
+    }
+    if cli.capture_input_colors_as_mask {
+        args.push("--capture-input-colors-as-mask".to_string());
+    }
+    if let Some(path) = &cli.mask_file {
+        args.extend(["--mask-file".to_string(), path.display().to_string()]);
+    }
+    for region in &cli.mask_region {
+        args.extend(["--mask-region".to_string(), region.to_string()]);
+    }
+    args.extend(compare::split_words(config).map_err(|err| format!("--{flag}: {err}"))?);
+
+    let mut placeholders = expr::Variables::placeholder();
+    let command_line = expr::resolve_args(
+        &Cli::command(),
+        &preset_command_line(&args),
+        &mut placeholders,
+    )?;
+    let side = Cli::try_parse_from(&command_line).map_err(|err| {
+        // Just clap's one-line reason; its usage text would describe `clifx`
+        let message = err.to_string();
+        let reason = message.lines().next().unwrap_or_default();
+        format!("--{flag}: {}", reason.trim_start_matches("error: "))
+    })?;
+    if matches!(side.command, Commands::Compare(_)) {
+        return Err(format!("--{flag} can't run another compare").into());
+    }
+    if side.fps.is_some() && speed_typed(&command_line) {
+        return Err(format!("--{flag}: --fps already sets the frame rate, so drop --speed").into());
+    }
+
+    Ok(Side {
+        label: config.trim().to_string(),
+        args,
+    })
+}
+
+/// Parses `--sprite` values, looking up the ones that name a sprite in the
+/// config.
+fn resolve_sprites(values: &[String]) -> Result<Vec<Sprite>, Box<dyn std::error::Error>> {
+    if values.is_empty() {
+        return Ok(Vec::new());
+    }
+    let named = config::load_sprites()?;
+    values
+        .iter()
+        .map(|value| {
+            let definition = named.get(value).unwrap_or(value);
+            definition.parse().map_err(Into::into)
+        })
+        .collect()
+}
+
 fn generate_random_saturated_color() -> String {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::new_rng();
     let hue = rng.gen_range(0.0..360.0);
     let saturation = 1.0; // Fully saturated
     let value = 1.0; // Full brightness
@@ -454,57 +4511,14 @@ fn generate_random_saturated_color() -> String {
     format!("{r},{g},{b}")
 }
 
-fn parse_rgb_color(color_str: &str) -> Result<(u8, u8, u8), Box<dyn std::error::Error>> {
-    let parts: Vec<&str> = color_str.split(',').collect();
-    if parts.len() != 3 {
-        return Err("Color must be in RGB format: r,g,b (e.g., 255,255,0)".into());
-    }
-
-    let r = parts[0].trim().parse::<u8>()?;
-    let g = parts[1].trim().parse::<u8>()?;
-    let b = parts[2].trim().parse::<u8>()?;
-
-    Ok((r, g, b))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_parse_rgb_color_valid() {
-        assert_eq!(parse_rgb_color("255,0,0").unwrap(), (255, 0, 0));
-        assert_eq!(parse_rgb_color("0,255,0").unwrap(), (0, 255, 0));
-        assert_eq!(parse_rgb_color("0,0,255").unwrap(), (0, 0, 255));
-        assert_eq!(parse_rgb_color("128,128,128").unwrap(), (128, 128, 128));
-    }
-
-    #[test]
-    fn test_parse_rgb_color_with_whitespace() {
-        assert_eq!(parse_rgb_color(" 255 , 0 , 0 ").unwrap(), (255, 0, 0));
-        assert_eq!(parse_rgb_color("255, 128, 64").unwrap(), (255, 128, 64));
-    }
-
-    #[test]
-    fn test_parse_rgb_color_invalid_format() {
-        assert!(parse_rgb_color("255,0").is_err());
-        assert!(parse_rgb_color("255,0,0,255").is_err());
-        assert!(parse_rgb_color("255").is_err());
-        assert!(parse_rgb_color("").is_err());
-    }
-
-    #[test]
-    fn test_parse_rgb_color_invalid_values() {
-        assert!(parse_rgb_color("256,0,0").is_err());
-        assert!(parse_rgb_color("-1,0,0").is_err());
-        assert!(parse_rgb_color("abc,0,0").is_err());
-        assert!(parse_rgb_color("255,256,0").is_err());
-    }
-
     #[test]
     fn test_generate_random_saturated_color_format() {
         let color = generate_random_saturated_color();
-        let rgb = parse_rgb_color(&color).unwrap();
+        let rgb = parse_color(&color).unwrap();
 
         // Check that at least one component is 255 (fully saturated)
         assert!(rgb.0 == 255 || rgb.1 == 255 || rgb.2 == 255);
@@ -527,4 +4541,57 @@ mod tests {
             colors
         );
     }
+
+    #[test]
+    fn test_fps_replaces_speed() {
+        let args: Vec<OsString> = ["clifx", "--fps", "30", "fade"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        let mut cli = Cli::parse_from(&args);
+        apply_fps(&mut cli, &args).unwrap();
+        assert_eq!(cli.command.speed_mut(), Some(&mut 33));
+
+        let args: Vec<OsString> = ["clifx", "--fps", "30", "fade", "--speed", "10"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        let mut cli = Cli::parse_from(&args);
+        assert!(apply_fps(&mut cli, &args).is_err());
+
+        assert_eq!(frame_interval(2000.0), 1);
+    }
+
+    #[test]
+    fn test_loop_forever_makes_cycles_and_durations_endless() {
+        let mut cli = Cli::parse_from(["clifx", "--loop", "shine", "--cycles", "3"]);
+        assert!(cli.command.loop_forever().unwrap());
+        assert!(matches!(
+            cli.command,
+            Commands::Shine(ShineArgs { cycles: 0, .. })
+        ));
+
+        let mut cli = Cli::parse_from(["clifx", "--loop", "fire", "--duration", "500"]);
+        assert!(cli.command.loop_forever().unwrap());
+        assert!(matches!(
+            cli.command,
+            Commands::Fire(FireArgs { duration: 0, .. })
+        ));
+
+        // Effects without an endless mode are run again instead
+        let mut cli = Cli::parse_from(["clifx", "--loop", "confetti"]);
+        assert!(!cli.command.loop_forever().unwrap());
+
+        assert!(Cli::try_parse_from(["clifx", "--loop", "--stream", "shine"]).is_err());
+    }
+
+    #[test]
+    fn test_typed_values_finds_every_occurrence() {
+        let args: Vec<OsString> = ["clifx", "--file", "a", "--file=b", "--file-x", "shine"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        assert_eq!(typed_values(&args, "--file"), vec!["a", "b"]);
+        assert_eq!(typed_value(&args, "--file"), Some("a"));
+    }
 }
@@ -0,0 +1,219 @@
+use crate::ansi::parse_styled;
+use crate::color::{parse_color, Rgb};
+use clap::ValueEnum;
+use regex::Regex;
+use std::sync::OnceLock;
+
+const OPEN: &str = "{{fx:";
+const CLOSE: &str = "{{/fx}}";
+
+/// Effects a `{{fx:...}}` span can run.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum SpanEffect {
+    /// Fade the span between dim and full color
+    Pulse,
+    /// Sweep a bright band across the span
+    Shine,
+    /// Flash random characters of the span
+    Twinkle,
+    /// Cycle the span through the hues
+    Rainbow,
+}
+
+/// What one marker asks for. Unset options fall back to the command's flags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanStyle {
+    pub effect: SpanEffect,
+    pub color: Option<Rgb>,
+    /// Milliseconds to hold the span still before its effect starts
+    pub delay: u64,
+    /// Milliseconds per cycle of the effect
+    pub duration: Option<u64>,
+}
+
+/// Input with its markers removed, and the span covering each visible cell.
+#[derive(Debug, PartialEq)]
+pub struct Document {
+    pub lines: Vec<String>,
+    pub spans: Vec<SpanStyle>,
+    /// One entry per cell of each line, indexing into `spans`
+    pub owners: Vec<Vec<Option<usize>>>,
+}
+
+fn marker_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\{\{(?:fx:[^}]*|/fx)\}\}").expect("valid marker pattern"))
+}
+
+pub fn has_markers(lines: &[String]) -> bool {
+    lines.iter().any(|line| marker_pattern().is_match(line))
+}
+
+/// `line` with every marker removed, for effects that don't run spans.
+pub fn strip_markers(line: &str) -> String {
+    marker_pattern().replace_all(line, "").into_owned()
+}
+
+/// Parses the inside of `{{fx:...}}`: an effect name, then `key=value` options.
+fn parse_style(body: &str) -> Result<SpanStyle, Box<dyn std::error::Error>> {
+    let mut words = body.split_whitespace();
+    let name = words.next().unwrap_or("");
+    let effect = SpanEffect::from_str(name, true).map_err(|_| {
+        format!("unknown effect `{name}` (expected pulse, shine, twinkle, or rainbow)")
+    })?;
+
+    let mut style = SpanStyle {
+        effect,
+        color: None,
+        delay: 0,
+        duration: None,
+    };
+    for option in words {
+        let (key, value) = option
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, found `{option}`"))?;
+        match key {
+            "color" => style.color = Some(parse_color(value)?),
+            "delay" => style.delay = value.parse()?,
+            "duration" => style.duration = Some(value.parse()?),
+            _ => {
+                return Err(
+                    format!("unknown option `{key}` (expected color, delay, or duration)").into(),
+                )
+            }
+        }
+    }
+    Ok(style)
+}
+
+/// Splits `{{fx:name ...}}text{{/fx}}` markers out of `lines`. A span may run
+/// across lines but spans can't nest, and every span must be closed.
+pub fn parse_document(lines: &[String]) -> Result<Document, Box<dyn std::error::Error>> {
+    let mut document = Document {
+        lines: Vec::new(),
+        spans: Vec::new(),
+        owners: Vec::new(),
+    };
+    let mut open: Option<usize> = None;
+
+    for (number, line) in lines.iter().enumerate() {
+        let at_line = |message: String| format!("line {}: {message}", number + 1);
+        let mut text = String::new();
+        let mut owners = Vec::new();
+        let mut rest = line.as_str();
+
+        loop {
+            let next = match (rest.find(OPEN), rest.find(CLOSE)) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            let segment = &rest[..next.unwrap_or(rest.len())];
+            text.push_str(segment);
+            owners.extend(std::iter::repeat_n(open, parse_styled(segment).len()));
+
+            let Some(start) = next else { break };
+            rest = &rest[start..];
+            if let Some(after) = rest.strip_prefix(CLOSE) {
+                if open.take().is_none() {
+                    return Err(at_line(format!("{CLOSE} without an opening marker")).into());
+                }
+                rest = after;
+            } else {
+                let end = rest
+                    .find("}}")
+                    .ok_or_else(|| at_line("unterminated marker".to_string()))?;
+                if open.is_some() {
+                    return Err(at_line("markers can't be nested".to_string()).into());
+                }
+                let style =
+                    parse_style(&rest[OPEN.len()..end]).map_err(|err| at_line(err.to_string()))?;
+                document.spans.push(style);
+                open = Some(document.spans.len() - 1);
+                rest = &rest[end + 2..];
+            }
+        }
+
+        document.lines.push(text);
+        document.owners.push(owners);
+    }
+
+    if open.is_some() {
+        return Err(format!("a span opened with {OPEN}... is never closed with {CLOSE}").into());
+    }
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_single_span() {
+        let document = parse_document(&lines("Deploy {{fx:pulse}}now{{/fx}}!")).unwrap();
+
+        assert_eq!(document.lines, vec!["Deploy now!"]);
+        assert_eq!(document.spans.len(), 1);
+        assert_eq!(document.spans[0].effect, SpanEffect::Pulse);
+
+        let covered: Vec<bool> = document.owners[0].iter().map(Option::is_some).collect();
+        let mut expected = vec![false; 7];
+        expected.extend([true; 3]);
+        expected.push(false);
+        assert_eq!(covered, expected);
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let document = parse_document(&lines(
+            "{{fx:shine color=gold delay=500 duration=800}}go{{/fx}}",
+        ))
+        .unwrap();
+
+        assert_eq!(
+            document.spans[0],
+            SpanStyle {
+                effect: SpanEffect::Shine,
+                color: Some((255, 215, 0)),
+                delay: 500,
+                duration: Some(800),
+            }
+        );
+    }
+
+    #[test]
+    fn test_span_runs_across_lines() {
+        let document = parse_document(&lines("a{{fx:rainbow}}b\nc{{/fx}}d")).unwrap();
+
+        assert_eq!(document.owners[0], vec![None, Some(0)]);
+        assert_eq!(document.owners[1], vec![Some(0), None]);
+    }
+
+    #[test]
+    fn test_escape_codes_are_not_cells() {
+        let document = parse_document(&lines("{{fx:twinkle}}\x1b[31mab\x1b[0m{{/fx}}c")).unwrap();
+
+        assert_eq!(document.lines, vec!["\x1b[31mab\x1b[0mc"]);
+        assert_eq!(document.owners[0], vec![Some(0), Some(0), None]);
+    }
+
+    #[test]
+    fn test_malformed_markers() {
+        assert!(parse_document(&lines("{{fx:wobble}}x{{/fx}}")).is_err());
+        assert!(parse_document(&lines("x{{/fx}}")).is_err());
+        assert!(parse_document(&lines("{{fx:pulse}}x")).is_err());
+        assert!(parse_document(&lines("{{fx:pulse}}{{fx:shine}}x{{/fx}}{{/fx}}")).is_err());
+        assert!(parse_document(&lines("{{fx:pulse speed=3}}x{{/fx}}")).is_err());
+    }
+
+    #[test]
+    fn test_strip_markers() {
+        assert!(has_markers(&lines("a {{fx:pulse}}b{{/fx}}")));
+        assert!(!has_markers(&lines("a {{b}} c")));
+        assert_eq!(strip_markers("a {{fx:pulse delay=5}}b{{/fx}} c"), "a b c");
+        assert_eq!(strip_markers("{{not a marker}}"), "{{not a marker}}");
+    }
+}
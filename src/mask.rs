@@ -0,0 +1,202 @@
+use crate::ansi::{column_starts, parse_styled};
+use crossterm::style::{Color, ResetColor, SetForegroundColor};
+use crossterm::Command;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A rectangle of cells, in columns and rows from the top left of the input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Region {
+    /// The smallest region covering both corners, in either order.
+    pub fn spanning(a: (usize, usize), b: (usize, usize)) -> Self {
+        Self {
+            x: a.0.min(b.0),
+            y: a.1.min(b.1),
+            width: a.0.abs_diff(b.0) + 1,
+            height: a.1.abs_diff(b.1) + 1,
+        }
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+/// Parses `X,Y,WIDTH,HEIGHT`, the form `--mask-region` takes.
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let numbers = text
+            .split(',')
+            .map(|part| part.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| format!("'{text}': {err}"))?;
+        match numbers[..] {
+            [x, y, width, height] if width > 0 && height > 0 => Ok(Self {
+                x,
+                y,
+                width,
+                height,
+            }),
+            _ => Err(format!(
+                "'{text}' isn't X,Y,WIDTH,HEIGHT with a nonzero size"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+/// The cells shine and twinkle animate, leaving the rest of the input in
+/// the terminal's own color.
+#[derive(Debug, Default, PartialEq)]
+pub struct Mask {
+    rows: Vec<Vec<bool>>,
+}
+
+impl Mask {
+    /// Reads a mask file: one line per input row, where any character other
+    /// than a space marks the cell in that column.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("can't read mask {}: {err}", path.display()))?;
+        Ok(Self::parse(&text))
+    }
+
+    fn parse(text: &str) -> Self {
+        Self {
+            rows: text
+                .lines()
+                .map(|line| line.chars().map(|ch| ch != ' ').collect())
+                .collect(),
+        }
+    }
+
+    /// Writes the mask in the form `load` reads, marking cells with `#`.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.render())
+            .map_err(|err| format!("can't write mask {}: {err}", path.display()).into())
+    }
+
+    fn render(&self) -> String {
+        self.rows
+            .iter()
+            .map(|row| {
+                let line: String = row.iter().map(|&on| if on { '#' } else { ' ' }).collect();
+                format!("{}\n", line.trim_end())
+            })
+            .collect()
+    }
+
+    pub fn add(&mut self, region: &Region) {
+        let bottom = region.y + region.height;
+        if self.rows.len() < bottom {
+            self.rows.resize(bottom, Vec::new());
+        }
+        for row in &mut self.rows[region.y..bottom] {
+            let right = region.x + region.width;
+            if row.len() < right {
+                row.resize(right, false);
+            }
+            row[region.x..right].fill(true);
+        }
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.rows
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Recolors `lines` so the input colors select exactly the masked cells,
+    /// the way `--capture-input-colors-as-mask` reads them: masked cells keep
+    /// their own color or take `color`, and every other cell loses its color.
+    pub fn apply(&self, lines: &[String], color: Color) -> Vec<String> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(y, line)| {
+                let cells = parse_styled(line);
+                let (columns, _) = column_starts(&cells);
+                let mut masked = String::new();
+                let mut current = None;
+                for (cell, &x) in cells.iter().zip(&columns) {
+                    let wanted = self.contains(x, y).then(|| cell.color.unwrap_or(color));
+                    if wanted != current {
+                        // Writing to a String can't fail
+                        let _ = match wanted {
+                            Some(color) => SetForegroundColor(color).write_ansi(&mut masked),
+                            None => ResetColor.write_ansi(&mut masked),
+                        };
+                        current = wanted;
+                    }
+                    masked.push(cell.ch);
+                }
+                if current.is_some() {
+                    let _ = ResetColor.write_ansi(&mut masked);
+                }
+                masked
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_parses_and_prints() {
+        let region: Region = "2, 1,3,4".parse().unwrap();
+        assert_eq!(Region::spanning((4, 4), (2, 1)), region);
+        assert_eq!(region.to_string(), "2,1,3,4");
+        assert!("2,1,0,4".parse::<Region>().is_err());
+        assert!("2,1,3".parse::<Region>().is_err());
+    }
+
+    #[test]
+    fn test_mask_file_round_trips() {
+        let mut mask = Mask::default();
+        mask.add(&Region::spanning((1, 0), (2, 1)));
+        mask.add(&Region::spanning((0, 3), (0, 3)));
+
+        assert_eq!(mask.render(), " ##\n ##\n\n#\n");
+        assert_eq!(Mask::parse(&mask.render()), Mask::parse(" ##\n ##\n\n#\n"));
+        assert!(mask.contains(2, 1));
+        assert!(!mask.contains(0, 1));
+        assert!(!mask.contains(2, 9));
+    }
+
+    #[test]
+    fn test_apply_colors_only_masked_cells() {
+        let mut mask = Mask::default();
+        mask.add(&Region::spanning((1, 0), (2, 0)));
+        let red = Color::Rgb { r: 255, g: 0, b: 0 };
+        let lines = vec!["\x1b[32mabcd\x1b[0m".to_string()];
+
+        let cells = parse_styled(&mask.apply(&lines, red)[0]);
+        let colors: Vec<_> = cells.iter().map(|cell| cell.color).collect();
+        let green = Some(Color::Rgb { r: 0, g: 205, b: 0 });
+        assert_eq!(colors, vec![None, green, green, None]);
+
+        let plain = vec!["abcd".to_string()];
+        let cells = parse_styled(&mask.apply(&plain, red)[0]);
+        assert_eq!(cells[1].color, Some(red));
+        assert_eq!(cells[3].color, None);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{clip, graphics, html, json_frames, keep, playback, screen, snapshot};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+static BYTES_WRITTEN: AtomicU64 = AtomicU64::new(0);
+
+/// The latest frame `flush_frame` skipped for being late. A newer frame
+/// replaces it, and anything else written shows it first, so an effect's
+/// last frame always reaches the screen.
+static HELD: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+/// Standard output as effects draw to it, counting every byte that reaches
+/// the terminal so profiles can report output volume per frame, and keeping
+/// recent output for clips and the final frame.
+///
+/// Writes are held until `flush`, which hands them to the terminal in one
+/// write. Effects queue a whole frame and flush once, so the terminal never
+/// shows a half-drawn one.
+pub struct Stdout {
+    pending: Vec<u8>,
+}
+
+pub fn stdout() -> Stdout {
+    Stdout {
+        pending: Vec::new(),
+    }
+}
+
+/// Total bytes effects have written so far in this process.
+pub fn bytes_written() -> u64 {
+    BYTES_WRITTEN.load(Ordering::Relaxed)
+}
+
+/// Ends the current output line. Effects end with this rather than
+/// `println!`, so the newline is counted and kept like their frames.
+/// Under `--inline` the line is the script's, so the cursor stays put just
+/// after the effect for whatever it prints next.
+pub fn newline() -> io::Result<()> {
+    if screen::inline_column().is_some() {
+        return Ok(());
+    }
+    let mut stdout = stdout();
+    stdout.write_all(b"\n")?;
+    stdout.flush()
+}
+
+impl Stdout {
+    /// Flushes a whole frame drawn over the previous one, unless it's too
+    /// late to be worth drawing; then it's held back in case no newer frame
+    /// comes to replace it.
+    pub fn flush_frame(&mut self) -> io::Result<()> {
+        let Ok(mut held) = HELD.lock() else {
+            return self.flush();
+        };
+        if playback::frame_is_late() {
+            *held = std::mem::take(&mut self.pending);
+            return Ok(());
+        }
+        held.clear();
+        drop(held);
+        self.flush()
+    }
+}
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Ok(mut held) = HELD.lock() {
+            if !held.is_empty() {
+                self.pending.splice(0..0, held.drain(..));
+            }
+        }
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        if !json_frames::record(&self.pending)
+            && !graphics::record(&self.pending)
+            && !snapshot::record(&self.pending)
+        {
+            let mut terminal = io::stdout().lock();
+            terminal.write_all(&self.pending)?;
+            terminal.flush()?;
+        }
+        BYTES_WRITTEN.fetch_add(self.pending.len() as u64, Ordering::Relaxed);
+        clip::record(&self.pending);
+        html::record(&self.pending);
+        keep::record(&self.pending);
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+impl Drop for Stdout {
+    fn drop(&mut self) {
+        // Nothing can be reported from drop; the effect's own flush would have failed first
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writes_wait_for_flush() {
+        let mut stdout = stdout();
+        stdout.write_all(b"fra").unwrap();
+        stdout.write_all(b"me").unwrap();
+        assert_eq!(stdout.pending, b"frame");
+
+        // Flushing would print into the test output
+        stdout.pending.clear();
+        stdout.flush().unwrap();
+    }
+}
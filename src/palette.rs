@@ -0,0 +1,113 @@
+use crate::color::Rgb;
+
+/// A named set of gradient stops that `--stops` accepts in place of a list.
+pub struct Palette {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub stops: &'static [Rgb],
+}
+
+/// The built-in palettes, in the order the gallery shows them. Names stay
+/// clear of CSS color names, so a single color is never mistaken for one.
+pub const PALETTES: &[Palette] = &[
+    Palette {
+        name: "sunset",
+        description: "dusk purple through pink and orange to gold",
+        stops: &[(94, 43, 140), (232, 65, 106), (255, 140, 0), (255, 214, 90)],
+    },
+    Palette {
+        name: "ocean",
+        description: "deep navy up to shallow aqua",
+        stops: &[(0, 45, 98), (0, 119, 182), (0, 180, 216), (144, 224, 239)],
+    },
+    Palette {
+        name: "forest",
+        description: "pine green out to spring leaves",
+        stops: &[(16, 64, 32), (46, 125, 50), (124, 179, 66), (205, 220, 57)],
+    },
+    Palette {
+        name: "neon",
+        description: "magenta, violet, electric blue, and green",
+        stops: &[(255, 0, 200), (120, 0, 255), (0, 210, 255), (0, 255, 140)],
+    },
+    Palette {
+        name: "pastel",
+        description: "soft pink, peach, mint, and sky",
+        stops: &[
+            (255, 179, 186),
+            (255, 223, 186),
+            (186, 255, 201),
+            (186, 225, 255),
+        ],
+    },
+    Palette {
+        name: "ember",
+        description: "smoldering red to a hot yellow glow",
+        stops: &[(80, 0, 0), (200, 30, 0), (255, 120, 0), (255, 220, 120)],
+    },
+    Palette {
+        name: "aurora",
+        description: "northern-lights green, blue, and violet",
+        stops: &[(0, 255, 170), (0, 160, 255), (140, 80, 255), (255, 80, 200)],
+    },
+    Palette {
+        name: "solarized",
+        description: "the Solarized accents, yellow round to green",
+        stops: &[
+            (181, 137, 0),
+            (203, 75, 22),
+            (220, 50, 47),
+            (211, 54, 130),
+            (108, 113, 196),
+            (38, 139, 210),
+            (42, 161, 152),
+            (133, 153, 0),
+        ],
+    },
+    Palette {
+        name: "dracula",
+        description: "Dracula's purple, pink, cyan, green, and orange",
+        stops: &[
+            (189, 147, 249),
+            (255, 121, 198),
+            (139, 233, 253),
+            (80, 250, 123),
+            (255, 184, 108),
+        ],
+    },
+    Palette {
+        name: "mono",
+        description: "grey rising to white and back",
+        stops: &[(80, 80, 80), (255, 255, 255), (80, 80, 80)],
+    },
+];
+
+/// The built-in palette called `name`, ignoring case.
+pub fn named(name: &str) -> Option<&'static Palette> {
+    PALETTES
+        .iter()
+        .find(|palette| palette.name.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::parse_color;
+
+    #[test]
+    fn test_palettes_have_unique_names_that_are_not_colors() {
+        for (i, palette) in PALETTES.iter().enumerate() {
+            assert!(palette.stops.len() >= 2, "{} needs stops", palette.name);
+            assert!(
+                parse_color(palette.name).is_err(),
+                "{} is a color",
+                palette.name
+            );
+            assert!(PALETTES[i + 1..]
+                .iter()
+                .all(|other| other.name != palette.name));
+        }
+        assert_eq!(named("Ocean").unwrap().name, "ocean");
+        assert!(named("plaid").is_none());
+    }
+}
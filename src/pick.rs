@@ -0,0 +1,353 @@
+use crate::ansi::{column_starts, parse_styled, StyledChar};
+use crate::mask::{Mask, Region};
+use crate::playback::Quit;
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::{
+    cursor, execute, queue,
+    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Shown above the input while picking.
+const HELP: &str =
+    "arrows/hjkl move · space or drag marks a corner · u undoes · enter picks · q quits";
+
+/// Rows of the screen above the input.
+const HEADER_ROWS: usize = 1;
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Continue,
+    Done,
+    Cancel,
+}
+
+/// The selection being made: a cursor, the corner a rectangle is being
+/// stretched from, and the rectangles picked so far.
+struct Picker {
+    /// Columns and rows the cursor can reach
+    size: (usize, usize),
+    cursor: (usize, usize),
+    anchor: Option<(usize, usize)>,
+    regions: Vec<Region>,
+}
+
+impl Picker {
+    fn new(size: (usize, usize)) -> Self {
+        Self {
+            size: (size.0.max(1), size.1.max(1)),
+            cursor: (0, 0),
+            anchor: None,
+            regions: Vec::new(),
+        }
+    }
+
+    fn move_to(&mut self, x: isize, y: isize) {
+        self.cursor = (
+            x.clamp(0, self.size.0 as isize - 1) as usize,
+            y.clamp(0, self.size.1 as isize - 1) as usize,
+        );
+    }
+
+    fn move_by(&mut self, dx: isize, dy: isize) {
+        self.move_to(self.cursor.0 as isize + dx, self.cursor.1 as isize + dy);
+    }
+
+    /// Sets a corner at the cursor, or closes the rectangle from the last one.
+    fn mark(&mut self) {
+        match self.anchor.take() {
+            Some(anchor) => self.regions.push(Region::spanning(anchor, self.cursor)),
+            None => self.anchor = Some(self.cursor),
+        }
+    }
+
+    /// Drops the corner being stretched from, or else the last rectangle.
+    fn undo(&mut self) {
+        if self.anchor.take().is_none() {
+            self.regions.pop();
+        }
+    }
+
+    /// The rectangle being stretched, if a corner is set.
+    fn stretching(&self) -> Option<Region> {
+        self.anchor
+            .map(|anchor| Region::spanning(anchor, self.cursor))
+    }
+
+    fn selected(&self, x: usize, y: usize) -> bool {
+        self.regions
+            .iter()
+            .chain(&self.stretching())
+            .any(|region| region.contains(x, y))
+    }
+
+    fn handle(&mut self, event: Event) -> Outcome {
+        match event {
+            Event::Key(key) => self.handle_key(key),
+            Event::Mouse(mouse) => {
+                self.handle_mouse(mouse);
+                Outcome::Continue
+            }
+            _ => Outcome::Continue,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        if key.kind != KeyEventKind::Press {
+            return Outcome::Continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Outcome::Cancel,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Outcome::Cancel
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.move_by(-1, 0),
+            KeyCode::Right | KeyCode::Char('l') => self.move_by(1, 0),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(0, -1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(0, 1),
+            KeyCode::Home => self.move_to(0, self.cursor.1 as isize),
+            KeyCode::End => self.move_to(isize::MAX, self.cursor.1 as isize),
+            KeyCode::Char(' ') => self.mark(),
+            KeyCode::Char('u') | KeyCode::Backspace => self.undo(),
+            KeyCode::Enter => {
+                // A rectangle still being stretched is picked as it stands
+                if self.anchor.is_some() {
+                    self.mark();
+                }
+                return Outcome::Done;
+            }
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    /// Dragging with the left button picks the rectangle it covers.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let x = mouse.column as isize;
+        let y = mouse.row as isize - HEADER_ROWS as isize;
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.move_to(x, y);
+                self.anchor = Some(self.cursor);
+            }
+            MouseEventKind::Drag(MouseButton::Left) => self.move_to(x, y),
+            MouseEventKind::Up(MouseButton::Left) if self.anchor.is_some() => {
+                self.move_to(x, y);
+                self.mark();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Raw mode, the alternate screen, and mouse reporting on stderr, so the
+/// picked arguments can go to stdout untouched, e.g. into `$(clifx pick)`.
+struct PickScreen;
+
+impl PickScreen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(
+            io::stderr(),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            cursor::Hide
+        )?;
+        Ok(Self)
+    }
+}
+
+impl Drop for PickScreen {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stderr(),
+            ResetColor,
+            cursor::Show,
+            DisableMouseCapture,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn draw(
+    out: &mut impl Write,
+    rows: &[(Vec<StyledChar>, Vec<usize>)],
+    picker: &Picker,
+) -> io::Result<()> {
+    queue!(
+        out,
+        terminal::Clear(ClearType::All),
+        cursor::MoveTo(0, 0),
+        SetForegroundColor(Color::DarkGrey),
+        Print(HELP),
+        ResetColor
+    )?;
+    for y in 0..picker.size.1 {
+        queue!(out, cursor::MoveTo(0, (y + HEADER_ROWS) as u16))?;
+        let (cells, columns) = rows
+            .get(y)
+            .map(|(cells, columns)| (cells.as_slice(), columns.as_slice()))
+            .unwrap_or_default();
+        let mut cells = cells.iter().zip(columns).peekable();
+        let mut x = 0;
+        // Pad past the end of the line, so blank cells can be picked too
+        while x < picker.size.0 {
+            let (ch, color, width) = match cells.next_if(|&(_, &column)| column <= x) {
+                Some((cell, _)) => (cell.ch, cell.color, cell.width()),
+                None => (' ', None, 1),
+            };
+            if width == 0 {
+                queue!(out, Print(ch))?;
+                continue;
+            }
+            let selected = picker.selected(x, y);
+            if selected {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            if picker.cursor == (x, y) {
+                queue!(out, SetAttribute(Attribute::Underlined))?;
+            }
+            queue!(
+                out,
+                SetForegroundColor(color.unwrap_or(Color::Reset)),
+                Print(ch),
+                SetAttribute(Attribute::Reset)
+            )?;
+            x += width;
+        }
+    }
+    out.flush()
+}
+
+/// The flags that repeat `picker`'s selection in later runs: a mask file
+/// saved to `save`, or else one `--mask-region` per rectangle.
+fn picked_args(
+    regions: &[Region],
+    save: Option<&Path>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    match save {
+        Some(path) => {
+            let mut mask = Mask::default();
+            for region in regions {
+                mask.add(region);
+            }
+            mask.save(path)?;
+            Ok(format!("--mask-file {}", path.display()))
+        }
+        None => Ok(regions
+            .iter()
+            .map(|region| format!("--mask-region {region}"))
+            .collect::<Vec<_>>()
+            .join(" ")),
+    }
+}
+
+/// Shows `lines` for picking cells with the keyboard or mouse, then prints
+/// the flags that animate just those cells (shine and twinkle).
+pub fn run(lines: &[String], save: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let rows: Vec<_> = lines
+        .iter()
+        .map(|line| {
+            let cells = parse_styled(line);
+            let (columns, width) = column_starts(&cells);
+            (cells, columns, width)
+        })
+        .collect();
+    let width = rows.iter().map(|&(_, _, width)| width).max().unwrap_or(0);
+    let rows: Vec<_> = rows
+        .into_iter()
+        .map(|(cells, columns, _)| (cells, columns))
+        .collect();
+    let mut picker = Picker::new((width, rows.len()));
+
+    let regions = {
+        let _screen = PickScreen::enter()?;
+        let mut stderr = io::stderr();
+        loop {
+            draw(&mut stderr, &rows, &picker)?;
+            match picker.handle(event::read()?) {
+                Outcome::Continue => {}
+                Outcome::Done => break picker.regions,
+                Outcome::Cancel => return Err(Quit.into()),
+            }
+        }
+    };
+
+    if regions.is_empty() {
+        return Err("nothing was picked".into());
+    }
+    println!("{}", picked_args(&regions, save)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    #[test]
+    fn test_keys_pick_a_rectangle() {
+        let mut picker = Picker::new((10, 3));
+        picker.handle(press(KeyCode::Right));
+        picker.handle(press(KeyCode::Char(' ')));
+        for _ in 0..20 {
+            picker.handle(press(KeyCode::Char('l')));
+        }
+        picker.handle(press(KeyCode::Down));
+        assert!(picker.selected(9, 1));
+        assert!(!picker.selected(0, 1));
+
+        assert_eq!(picker.handle(press(KeyCode::Enter)), Outcome::Done);
+        assert_eq!(picker.regions, vec!["1,0,9,2".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_undo_drops_the_corner_then_the_last_rectangle() {
+        let mut picker = Picker::new((10, 3));
+        picker.handle(press(KeyCode::Char(' ')));
+        picker.handle(press(KeyCode::Char(' ')));
+        picker.handle(press(KeyCode::Char(' ')));
+        picker.handle(press(KeyCode::Char('u')));
+        assert!(picker.anchor.is_none());
+        assert_eq!(picker.regions.len(), 1);
+        picker.handle(press(KeyCode::Char('u')));
+        assert!(picker.regions.is_empty());
+        assert_eq!(picker.handle(press(KeyCode::Esc)), Outcome::Cancel);
+    }
+
+    #[test]
+    fn test_drag_picks_below_the_header() {
+        let mut picker = Picker::new((10, 3));
+        picker.handle(mouse(MouseEventKind::Down(MouseButton::Left), 2, 1));
+        picker.handle(mouse(MouseEventKind::Drag(MouseButton::Left), 5, 2));
+        picker.handle(mouse(MouseEventKind::Up(MouseButton::Left), 6, 2));
+        assert_eq!(picker.regions, vec!["2,0,5,2".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_picked_args_per_region() {
+        let regions = vec!["0,0,2,1".parse().unwrap(), "3,1,1,1".parse().unwrap()];
+        assert_eq!(
+            picked_args(&regions, None).unwrap(),
+            "--mask-region 0,0,2,1 --mask-region 3,1,1,1"
+        );
+    }
+}
@@ -0,0 +1,350 @@
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{execute, style::Print, terminal};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Factor each `+` or `-` press changes the playback speed by.
+const SPEED_STEP: f32 = 1.25;
+const MIN_SPEED: f32 = 0.125;
+const MAX_SPEED: f32 = 8.0;
+
+/// How often a paused animation checks for keys.
+const PAUSED_POLL: Duration = Duration::from_millis(100);
+
+/// Lateness beyond which the frame clock starts over rather than rushing
+/// through frames to catch up, e.g. after input kept an effect waiting.
+const MAX_LAG: Duration = Duration::from_secs(1);
+
+/// Returned from `sleep` when the viewer quits. It unwinds the effect like
+/// any error, so terminal guards restore the screen, and `main` then exits
+/// successfully.
+#[derive(Debug)]
+pub struct Quit;
+
+impl fmt::Display for Quit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "quit")
+    }
+}
+
+impl Error for Quit {}
+
+/// Returned from `sleep` while an interrupt is pending, unwinding the
+/// running effect the same way as `Quit`.
+#[derive(Debug)]
+pub struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "interrupted")
+    }
+}
+
+impl Error for Interrupted {}
+
+/// Interrupts asked for and not yet handled.
+static INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Asks effects to stop at their next frame, from any thread, until
+/// `interrupt_handled` says whatever called for it has been seen to.
+pub fn interrupt() {
+    INTERRUPTS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn interrupt_handled() {
+    let _ = INTERRUPTS.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+        count.checked_sub(1)
+    });
+}
+
+/// Turns Ctrl-C into an interrupt, so an effect that would run forever
+/// stops at its next frame and puts the terminal back, rather than dying
+/// with the cursor hidden. Raw mode reads Ctrl-C as a key instead.
+pub fn interrupt_on_ctrl_c() {
+    // Only one handler can be set; a later run in the same process keeps it
+    let _ = ctrlc::set_handler(interrupt);
+}
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    Continue,
+    Quit,
+}
+
+/// Playback state the keys change: Space pauses, `+`/`-` change speed,
+/// and `q`, Esc, or Ctrl-C (which raw mode delivers as a key) quit.
+struct Controller {
+    speed: f32,
+    paused: bool,
+}
+
+impl Controller {
+    fn handle(&mut self, key: KeyEvent) -> Action {
+        if key.kind != KeyEventKind::Press {
+            return Action::Continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Action::Quit
+            }
+            KeyCode::Char(' ') => self.paused = !self.paused,
+            // `=` shares the `+` key, so speeding up doesn't need Shift
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.speed = (self.speed * SPEED_STEP).min(MAX_SPEED)
+            }
+            KeyCode::Char('-') | KeyCode::Char('_') => {
+                self.speed = (self.speed / SPEED_STEP).max(MIN_SPEED)
+            }
+            _ => {}
+        }
+        Action::Continue
+    }
+}
+
+static CONTROLLER: Mutex<Option<Controller>> = Mutex::new(None);
+
+/// When the frame being drawn was due on screen, and how long it stays.
+struct Clock {
+    due: Instant,
+    frame: Duration,
+}
+
+impl Clock {
+    /// The deadline `duration` after the current frame's. Counting from the
+    /// deadline rather than from `now` takes the time spent drawing out of
+    /// the wait, so frames don't drift later and later.
+    fn next(clock: Option<&Clock>, now: Instant, duration: Duration) -> Instant {
+        match clock {
+            Some(clock) if now.saturating_duration_since(clock.due) <= MAX_LAG => {
+                clock.due + duration
+            }
+            _ => now + duration,
+        }
+    }
+
+    /// Whether the next frame is already due, so drawing this one would
+    /// only put the animation further behind.
+    fn is_late(&self, now: Instant) -> bool {
+        let behind = now.saturating_duration_since(self.due);
+        !self.frame.is_zero() && behind >= self.frame && behind <= MAX_LAG
+    }
+}
+
+static CLOCK: Mutex<Option<Clock>> = Mutex::new(None);
+
+/// Starts the frame clock over, so an animation keeps time from its own
+/// first frame rather than from wherever the last one left off.
+pub fn restart_clock() {
+    if let Ok(mut clock) = CLOCK.lock() {
+        *clock = None;
+    }
+}
+
+/// Sets the next frame's deadline `duration` after the current one's.
+fn schedule(duration: Duration) -> Instant {
+    let now = Instant::now();
+    let Ok(mut clock) = CLOCK.lock() else {
+        return now + duration;
+    };
+    let due = Clock::next(clock.as_ref(), now, duration);
+    *clock = Some(Clock {
+        due,
+        frame: duration,
+    });
+    due
+}
+
+/// Frames `frame_is_late` has told effects to skip.
+static SKIPPED: AtomicU64 = AtomicU64::new(0);
+
+/// Whether the frame about to be drawn is so late the next is already due.
+/// Effects skip drawing such frames to catch up, keeping their total
+/// duration. Piped output keeps every frame, for tools reading them.
+pub fn frame_is_late() -> bool {
+    let late = io::stdout().is_terminal()
+        && CLOCK
+            .lock()
+            .ok()
+            .and_then(|clock| clock.as_ref().map(|clock| clock.is_late(Instant::now())))
+            .unwrap_or(false);
+    if late {
+        SKIPPED.fetch_add(1, Ordering::Relaxed);
+    }
+    late
+}
+
+/// How many frames were skipped for running late so far.
+pub fn skipped_frames() -> u64 {
+    SKIPPED.load(Ordering::Relaxed)
+}
+
+/// Keeps keyboard controls on while alive, turning raw mode off again
+/// when dropped.
+pub struct Playback;
+
+impl Drop for Playback {
+    fn drop(&mut self) {
+        if let Ok(mut controller) = CONTROLLER.lock() {
+            *controller = None;
+        }
+        // Raw mode doesn't return the carriage on newlines, so do it before handing back
+        let _ = execute!(io::stdout(), Print('\r'));
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Turns on keyboard controls for every effect until the returned guard is
+/// dropped. Keys are read from the terminal, even when stdin is piped.
+pub fn start() -> io::Result<Playback> {
+    terminal::enable_raw_mode()?;
+    if let Ok(mut controller) = CONTROLLER.lock() {
+        *controller = Some(Controller {
+            speed: 1.0,
+            paused: false,
+        });
+    }
+    Ok(Playback)
+}
+
+/// Waits until one frame's `duration`, at the current playback speed,
+/// after the last frame was due, and for as long as playback is paused,
+/// handling keys as they come in. Effects call this between frames in
+/// place of `thread::sleep`.
+pub fn sleep(duration: Duration) -> Result<(), Box<dyn Error>> {
+    if INTERRUPTS.load(Ordering::Relaxed) > 0 {
+        return Err(Interrupted.into());
+    }
+    let speed = CONTROLLER
+        .lock()
+        .ok()
+        .and_then(|c| c.as_ref().map(|c| c.speed));
+    let deadline = schedule(speed.map_or(duration, |speed| duration.div_f32(speed)));
+    if speed.is_none() {
+        thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        return Ok(());
+    }
+
+    let mut was_paused = false;
+    loop {
+        // A paused effect still makes way when interrupted
+        if INTERRUPTS.load(Ordering::Relaxed) > 0 {
+            return Err(Interrupted.into());
+        }
+        let paused = CONTROLLER
+            .lock()
+            .ok()
+            .and_then(|c| c.as_ref().map(|c| c.paused))
+            .unwrap_or(false);
+        if was_paused && !paused {
+            // Time spent paused isn't owed back, so resume from now
+            restart_clock();
+            return Ok(());
+        }
+        was_paused |= paused;
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !paused && remaining.is_zero() {
+            return Ok(());
+        }
+
+        let wait = if paused { PAUSED_POLL } else { remaining };
+        if !event::poll(wait)? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            let action = CONTROLLER
+                .lock()
+                .ok()
+                .and_then(|mut c| c.as_mut().map(|c| c.handle(key)));
+            if action == Some(Action::Quit) {
+                return Err(Quit.into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn controller() -> Controller {
+        Controller {
+            speed: 1.0,
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn test_space_toggles_pause() {
+        let mut controller = controller();
+        controller.handle(press(KeyCode::Char(' ')));
+        assert!(controller.paused);
+        controller.handle(press(KeyCode::Char(' ')));
+        assert!(!controller.paused);
+    }
+
+    #[test]
+    fn test_speed_steps_within_limits() {
+        let mut controller = controller();
+        controller.handle(press(KeyCode::Char('+')));
+        assert_eq!(controller.speed, 1.25);
+        controller.handle(press(KeyCode::Char('-')));
+        controller.handle(press(KeyCode::Char('-')));
+        assert_eq!(controller.speed, 0.8);
+
+        for _ in 0..50 {
+            controller.handle(press(KeyCode::Char('=')));
+        }
+        assert_eq!(controller.speed, MAX_SPEED);
+    }
+
+    #[test]
+    fn test_quit_keys() {
+        let mut controller = controller();
+        assert_eq!(controller.handle(press(KeyCode::Char('q'))), Action::Quit);
+        assert_eq!(controller.handle(press(KeyCode::Esc)), Action::Quit);
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(controller.handle(ctrl_c), Action::Quit);
+        assert_eq!(
+            controller.handle(press(KeyCode::Char('c'))),
+            Action::Continue
+        );
+    }
+
+    #[test]
+    fn test_deadlines_absorb_drawing_time() {
+        let start = Instant::now();
+        let frame = Duration::from_millis(50);
+        let clock = Clock { due: start, frame };
+
+        // Drawing took 20ms, which comes out of the next wait
+        let drawn = start + Duration::from_millis(20);
+        assert_eq!(Clock::next(Some(&clock), drawn, frame), start + frame);
+        assert_eq!(Clock::next(None, drawn, frame), drawn + frame);
+        // Too far behind to catch up, so start over
+        let stalled = start + MAX_LAG * 2;
+        assert_eq!(Clock::next(Some(&clock), stalled, frame), stalled + frame);
+    }
+
+    #[test]
+    fn test_frames_are_late_once_the_next_is_due() {
+        let start = Instant::now();
+        let clock = Clock {
+            due: start,
+            frame: Duration::from_millis(50),
+        };
+
+        assert!(!clock.is_late(start + Duration::from_millis(49)));
+        assert!(clock.is_late(start + Duration::from_millis(50)));
+        assert!(!clock.is_late(start + MAX_LAG * 2));
+    }
+}
@@ -0,0 +1,154 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Bumped whenever the profile layout changes, so replay tools can tell
+/// which fields to expect.
+const PROFILE_VERSION: u32 = 1;
+
+static PROFILING: AtomicBool = AtomicBool::new(false);
+static STARTED: OnceLock<Instant> = OnceLock::new();
+static FRAMES: Mutex<Vec<FrameRecord>> = Mutex::new(Vec::new());
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+struct FrameRecord {
+    /// When drawing began, in microseconds since the run started
+    start_us: u64,
+    duration_us: u64,
+    bytes: u64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct Summary {
+    frames: usize,
+    total_bytes: u64,
+    p50_us: u64,
+    p95_us: u64,
+    max_us: u64,
+}
+
+#[derive(Serialize)]
+struct Profile<'a> {
+    version: u32,
+    command: &'a str,
+    parameters: &'a BTreeMap<String, Value>,
+    /// Terminal columns and rows, so a replay can use the same geometry
+    terminal: Option<(u16, u16)>,
+    summary: Summary,
+    frames: &'a [FrameRecord],
+}
+
+/// Starts collecting frame records for `--profile-out`.
+pub fn start() {
+    STARTED.get_or_init(Instant::now);
+    PROFILING.store(true, Ordering::Relaxed);
+}
+
+pub fn is_active() -> bool {
+    PROFILING.load(Ordering::Relaxed)
+}
+
+/// Adds one frame that began at `started`, took `duration`, and wrote
+/// `bytes` to the terminal. Does nothing unless profiling.
+pub fn record_frame(started: Instant, duration: Duration, bytes: u64) {
+    if !is_active() {
+        return;
+    }
+
+    let origin = *STARTED.get_or_init(Instant::now);
+    let record = FrameRecord {
+        start_us: started.saturating_duration_since(origin).as_micros() as u64,
+        duration_us: duration.as_micros() as u64,
+        bytes,
+    };
+    if let Ok(mut frames) = FRAMES.lock() {
+        frames.push(record);
+    }
+}
+
+fn summarize(frames: &[FrameRecord]) -> Summary {
+    let mut durations: Vec<u64> = frames.iter().map(|frame| frame.duration_us).collect();
+    durations.sort_unstable();
+    let percentile = |fraction: f64| {
+        if durations.is_empty() {
+            return 0;
+        }
+        durations[((durations.len() - 1) as f64 * fraction).round() as usize]
+    };
+
+    Summary {
+        frames: frames.len(),
+        total_bytes: frames.iter().map(|frame| frame.bytes).sum(),
+        p50_us: percentile(0.50),
+        p95_us: percentile(0.95),
+        max_us: durations.last().copied().unwrap_or(0),
+    }
+}
+
+/// Writes every frame recorded so far to `path` as JSON, along with the
+/// `command` and the `parameters` it ran with.
+pub fn write(
+    path: &Path,
+    command: &str,
+    parameters: &BTreeMap<String, Value>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    PROFILING.store(false, Ordering::Relaxed);
+    let frames = FRAMES
+        .lock()
+        .map(|frames| frames.clone())
+        .unwrap_or_default();
+
+    let profile = Profile {
+        version: PROFILE_VERSION,
+        command,
+        parameters,
+        terminal: crossterm::terminal::size().ok(),
+        summary: summarize(&frames),
+        frames: &frames,
+    };
+
+    fs::write(path, serde_json::to_string_pretty(&profile)?)
+        .map_err(|err| format!("cannot write profile {}: {err}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(duration_us: u64, bytes: u64) -> FrameRecord {
+        FrameRecord {
+            start_us: 0,
+            duration_us,
+            bytes,
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let frames: Vec<FrameRecord> = (1..=10).map(|i| frame(i * 100, 50)).collect();
+        assert_eq!(
+            summarize(&frames),
+            Summary {
+                frames: 10,
+                total_bytes: 500,
+                p50_us: 600,
+                p95_us: 1000,
+                max_us: 1000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_summarize_no_frames() {
+        let summary = summarize(&[]);
+        assert_eq!(summary.frames, 0);
+        assert_eq!(summary.p95_us, 0);
+        assert_eq!(summary.max_us, 0);
+    }
+}
@@ -0,0 +1,146 @@
+use clap::ValueEnum;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+/// Slow frames in a row that make auto quality step down.
+const SLOW_FRAMES: u32 = 3;
+/// Frames in a row with room to spare before auto quality steps back up.
+const FAST_FRAMES: u32 = 60;
+/// A frame has room to spare when it takes under this share of the budget.
+const HEADROOM: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Quality {
+    /// The cheapest rendering, for slow machines and terminals
+    Low,
+    Medium,
+    /// Full detail
+    High,
+    /// Start at high and step down while frames take longer than their budget
+    Auto,
+}
+
+/// The lowest level auto quality stepped down to, as `Quality as u8`, or
+/// `u8::MAX` while it never had to.
+static LOWEST_AUTO: AtomicU8 = AtomicU8::new(u8::MAX);
+
+/// The lowest level auto quality had to step down to this run, if any.
+pub fn auto_lowest() -> Option<Quality> {
+    match LOWEST_AUTO.load(Ordering::Relaxed) {
+        0 => Some(Quality::Low),
+        1 => Some(Quality::Medium),
+        _ => None,
+    }
+}
+
+/// Picks the quality a heavy effect renders each frame at. A fixed quality
+/// never changes; auto watches how long frames take against the frame
+/// budget, stepping down after a few slow ones and back up once a long
+/// run of frames shows headroom again.
+pub struct Governor {
+    auto: bool,
+    level: Quality,
+    budget: Duration,
+    slow_frames: u32,
+    fast_frames: u32,
+}
+
+impl Governor {
+    pub fn new(quality: Quality, budget: Duration) -> Self {
+        Self {
+            auto: quality == Quality::Auto,
+            level: if quality == Quality::Auto {
+                Quality::High
+            } else {
+                quality
+            },
+            budget,
+            slow_frames: 0,
+            fast_frames: 0,
+        }
+    }
+
+    /// The level to render the next frame at: low, medium, or high.
+    pub fn level(&self) -> Quality {
+        self.level
+    }
+
+    /// Notes how long the last frame took to simulate and draw.
+    pub fn record(&mut self, frame_time: Duration) {
+        if !self.auto || self.budget.is_zero() {
+            return;
+        }
+
+        if frame_time > self.budget {
+            self.fast_frames = 0;
+            self.slow_frames += 1;
+            if self.slow_frames >= SLOW_FRAMES && self.level > Quality::Low {
+                self.level = step(self.level, -1);
+                self.slow_frames = 0;
+                LOWEST_AUTO.fetch_min(self.level as u8, Ordering::Relaxed);
+            }
+        } else if frame_time.as_secs_f64() < self.budget.as_secs_f64() * HEADROOM {
+            self.slow_frames = 0;
+            self.fast_frames += 1;
+            if self.fast_frames >= FAST_FRAMES && self.level < Quality::High {
+                self.level = step(self.level, 1);
+                self.fast_frames = 0;
+            }
+        } else {
+            self.slow_frames = 0;
+            self.fast_frames = 0;
+        }
+    }
+}
+
+fn step(level: Quality, by: i8) -> Quality {
+    match (level as i8 + by).clamp(0, 2) {
+        0 => Quality::Low,
+        1 => Quality::Medium,
+        _ => Quality::High,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn test_fixed_quality_never_changes() {
+        let mut governor = Governor::new(Quality::Medium, BUDGET);
+        for _ in 0..10 {
+            governor.record(Duration::from_millis(500));
+        }
+        assert_eq!(governor.level(), Quality::Medium);
+    }
+
+    #[test]
+    fn test_auto_steps_down_on_slow_frames_and_back_up_with_headroom() {
+        let mut governor = Governor::new(Quality::Auto, BUDGET);
+        assert_eq!(governor.level(), Quality::High);
+
+        // A single hiccup isn't enough
+        governor.record(Duration::from_millis(80));
+        governor.record(Duration::from_millis(10));
+        governor.record(Duration::from_millis(80));
+        assert_eq!(governor.level(), Quality::High);
+
+        for _ in 0..SLOW_FRAMES * 2 {
+            governor.record(Duration::from_millis(80));
+        }
+        assert_eq!(governor.level(), Quality::Low);
+        assert_eq!(auto_lowest(), Some(Quality::Low));
+
+        for _ in 0..FAST_FRAMES - 1 {
+            governor.record(Duration::from_millis(40));
+        }
+        // Within budget but without headroom keeps it where it is
+        assert_eq!(governor.level(), Quality::Low);
+        for _ in 0..FAST_FRAMES {
+            governor.record(Duration::from_millis(10));
+        }
+        assert_eq!(governor.level(), Quality::Medium);
+    }
+}
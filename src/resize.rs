@@ -0,0 +1,246 @@
+use crate::center::offsets_for;
+use crate::layout::Boxing;
+use crate::output::stdout;
+use crate::screen;
+use crossterm::{
+    execute,
+    style::Color,
+    terminal::{self, ClearType},
+};
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+/// The content being centered: how big it is, and where its top-left
+/// corner is drawn now. Effects drawing part of it, such as one of its
+/// lines, keep their place within it when it moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Block {
+    size: (u16, u16),
+    offsets: (u16, u16),
+    /// Whether it's centered across the terminal too, or kept at the left
+    /// edge as with `--center-v`
+    horizontally: bool,
+}
+
+/// What's needed to notice a resize between frames and recenter after it.
+struct Watch {
+    /// Terminal size when the last frame was drawn
+    size: Option<(u16, u16)>,
+    /// The centered content, if output is centered
+    block: Option<Block>,
+    /// The box drawn around the content, and its border's color
+    frame: Option<(Boxing, Color)>,
+}
+
+impl Watch {
+    /// Records `size`, returning it if it differs from the last one seen.
+    /// The first size seen is the starting size, not a resize.
+    fn update(&mut self, size: (u16, u16)) -> Option<(u16, u16)> {
+        let previous = self.size.replace(size);
+        previous.filter(|&previous| previous != size).map(|_| size)
+    }
+
+    /// Moves the centered content to the middle of a terminal of `size`,
+    /// returning where `offsets` goes with it: the same row and column of
+    /// the content.
+    fn recenter(&mut self, offsets: (u16, u16), size: (u16, u16)) -> Option<(u16, u16)> {
+        let block = self.block.as_mut()?;
+        let row = offsets.0.saturating_sub(block.offsets.0);
+        let column = offsets.1.saturating_sub(block.offsets.1);
+        let recentered = offsets_for(block.size, size);
+        let left = if block.horizontally {
+            recentered.left
+        } else {
+            0
+        };
+        block.offsets = (recentered.top, left);
+        Some((recentered.top + row, left + column))
+    }
+}
+
+impl Watch {
+    /// Draws the box around the centered content, if there's one.
+    fn draw_frame(&self) -> io::Result<()> {
+        if let (Some((boxing, color)), Some(block)) = (self.frame, self.block) {
+            let mut stdout = stdout();
+            boxing.draw(block.size, block.offsets, color, &mut stdout)?;
+            stdout.flush()?;
+        }
+        Ok(())
+    }
+}
+
+static WATCH: Mutex<Watch> = Mutex::new(Watch {
+    size: None,
+    block: None,
+    frame: None,
+});
+
+/// Remembers the size (width, height) of the content being centered and
+/// the `offsets` it's drawn at, so they can be recomputed when the
+/// terminal is resized. Content not centered `horizontally` stays at the
+/// left edge.
+pub fn center_content(content: (u16, u16), offsets: (u16, u16), horizontally: bool) {
+    if let Ok(mut watch) = WATCH.lock() {
+        watch.block = Some(Block {
+            size: content,
+            offsets,
+            horizontally,
+        });
+        watch.size = screen::size().ok();
+    }
+}
+
+/// Remembers the box drawn around the centered content, to draw again
+/// around it after a resize.
+pub fn frame_content(boxing: Boxing, color: Color) {
+    if let Ok(mut watch) = WATCH.lock() {
+        watch.frame = Some((boxing, color));
+    }
+}
+
+/// Draws the box around the centered content again, for when the screen
+/// has just been cleared.
+pub fn redraw_frame() -> io::Result<()> {
+    match WATCH.lock() {
+        Ok(watch) => watch.draw_frame(),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Where an effect draws: the top-left corner of the centered content, if
+/// output is centered, and the row within it the effect starts on. Effects
+/// run once per line of input start each line on its own row, so the lines
+/// stack up rather than overwrite each other.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Placement {
+    centering_offsets: Option<(u16, u16)>,
+    row: usize,
+}
+
+impl Placement {
+    pub fn new(centering_offsets: Option<(u16, u16)>) -> Self {
+        Placement {
+            centering_offsets,
+            row: 0,
+        }
+    }
+
+    /// The same place, starting `row` rows into the content.
+    pub fn at_row(self, row: usize) -> Self {
+        Placement { row, ..self }
+    }
+
+    /// Where the effect's first row starts, following the content if it's
+    /// been recentered since the placement was made.
+    pub fn offsets(&self) -> Option<(u16, u16)> {
+        let block = WATCH.lock().ok().and_then(|watch| watch.block);
+        self.offsets_within(block.map(|block| block.offsets))
+    }
+
+    /// Whether the effect clears the screen before it starts: only for
+    /// centered output, and only from the first row, so rows drawn one at
+    /// a time don't wipe the ones above them.
+    pub fn clears_screen(&self) -> bool {
+        self.centering_offsets.is_some() && self.row == 0
+    }
+
+    fn offsets_within(&self, block: Option<(u16, u16)>) -> Option<(u16, u16)> {
+        let offsets = self.centering_offsets?;
+        let (top, left) = block.unwrap_or(offsets);
+        Some((top.saturating_add(self.row as u16), left))
+    }
+}
+
+/// Checks for a resize since the last frame. Effects call this before
+/// drawing each frame: after a resize, centered output is moved to the new
+/// middle of a cleared screen. Returns the new size, for effects whose
+/// layout depends on it.
+pub fn track(centering_offsets: &mut Option<(u16, u16)>) -> io::Result<Option<(u16, u16)>> {
+    let Ok(size) = screen::size() else {
+        // Not a terminal, so nothing can be resized
+        return Ok(None);
+    };
+    let Ok(mut watch) = WATCH.lock() else {
+        return Ok(None);
+    };
+    let Some(size) = watch.update(size) else {
+        return Ok(None);
+    };
+
+    if let Some(recentered) = centering_offsets.and_then(|offsets| watch.recenter(offsets, size)) {
+        *centering_offsets = Some(recentered);
+        if screen::may_clear_screen() {
+            execute!(stdout(), terminal::Clear(ClearType::All))?;
+        }
+        watch.draw_frame()?;
+    }
+    Ok(Some(size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_reports_changes_only() {
+        let mut watch = Watch {
+            size: None,
+            block: None,
+            frame: None,
+        };
+
+        assert_eq!(watch.update((80, 24)), None);
+        assert_eq!(watch.update((80, 24)), None);
+        assert_eq!(watch.update((100, 30)), Some((100, 30)));
+        assert_eq!(watch.update((100, 30)), None);
+    }
+
+    #[test]
+    fn test_recenter_keeps_the_place_within_the_content() {
+        let mut watch = Watch {
+            size: None,
+            block: None,
+            frame: None,
+        };
+        assert_eq!(watch.recenter((5, 10), (80, 24)), None);
+
+        // A 20x4 block centered in 40x10, and its third row
+        watch.block = Some(Block {
+            size: (20, 4),
+            offsets: (3, 10),
+            horizontally: true,
+        });
+        assert_eq!(watch.recenter((5, 10), (60, 20)), Some((10, 20)));
+        assert_eq!(watch.block.unwrap().offsets, (8, 20));
+        // A line drawn after the resize finds the block where it went
+        assert_eq!(watch.recenter((8, 20), (60, 20)), Some((8, 20)));
+
+        // Centered only vertically, it stays at the left edge
+        watch.block = Some(Block {
+            size: (20, 4),
+            offsets: (3, 0),
+            horizontally: false,
+        });
+        assert_eq!(watch.recenter((4, 2), (60, 20)), Some((9, 2)));
+    }
+
+    #[test]
+    fn test_placement_rows_follow_the_content() {
+        let placement = Placement::new(Some((3, 10)));
+        assert_eq!(placement.offsets_within(None), Some((3, 10)));
+        assert_eq!(placement.at_row(2).offsets_within(None), Some((5, 10)));
+        // After a recenter, rows start from where the content went
+        assert_eq!(
+            placement.at_row(2).offsets_within(Some((8, 20))),
+            Some((10, 20))
+        );
+
+        // Uncentered output is drawn where the cursor is
+        let inline = Placement::new(None).at_row(2);
+        assert_eq!(inline.offsets_within(Some((8, 20))), None);
+        assert!(!inline.clears_screen());
+        assert!(placement.clears_screen());
+        assert!(!placement.at_row(1).clears_screen());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{json_frames, playback, resize, snapshot};
+use crossterm::{
+    cursor, execute,
+    style::ResetColor,
+    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+    Command,
+};
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, Ordering};
+
+/// Whether `--simple-tty` is on.
+static SIMPLE_TTY: AtomicBool = AtomicBool::new(false);
+
+/// Draws with nothing but carriage returns from now on: no cursor moves,
+/// hiding, or clears, which serial consoles and minimal terminals may not
+/// understand.
+pub fn use_simple_tty() {
+    SIMPLE_TTY.store(true, Ordering::Relaxed);
+}
+
+pub fn simple_tty() -> bool {
+    SIMPLE_TTY.load(Ordering::Relaxed)
+}
+
+/// The `--virtual-size` as `width << 16 | height`, or 0 when it's not set.
+static VIRTUAL_SIZE: AtomicU32 = AtomicU32::new(0);
+
+/// Lays output out for a `(width, height)` terminal from now on, whatever
+/// size the real one is, so recorded frames come out the same anywhere.
+pub fn use_virtual_size((width, height): (u16, u16)) {
+    VIRTUAL_SIZE.store((width as u32) << 16 | height as u32, Ordering::Relaxed);
+}
+
+pub fn virtual_size() -> Option<(u16, u16)> {
+    match VIRTUAL_SIZE.load(Ordering::Relaxed) {
+        0 => None,
+        packed => Some(((packed >> 16) as u16, packed as u16)),
+    }
+}
+
+/// The size (columns, rows) to lay output out for: `--virtual-size` if it
+/// was given, otherwise the terminal's. Only the virtual size is known
+/// when stdout isn't a terminal.
+pub fn size() -> io::Result<(u16, u16)> {
+    match virtual_size() {
+        Some(size) => Ok(size),
+        None => terminal::size(),
+    }
+}
+
+/// The width and height effects that fit themselves to the terminal go by:
+/// `--virtual-size`, or the terminal's own size when stdout is one.
+pub fn layout_size() -> Option<(u16, u16)> {
+    virtual_size().or_else(|| {
+        io::stdout()
+            .is_terminal()
+            .then(|| terminal::size().ok())
+            .flatten()
+    })
+}
+
+/// Parses a `WxH` size such as `80x24`.
+pub fn parse_size(value: &str) -> Result<(u16, u16), String> {
+    let (width, height) = value
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("'{value}' isn't WIDTHxHEIGHT, e.g. 80x24"))?;
+    let dimension = |text: &str| match text.trim().parse::<u16>() {
+        Ok(0) | Err(_) => Err(format!("'{text}' isn't a size from 1 to {}", u16::MAX)),
+        Ok(size) => Ok(size),
+    };
+    Ok((dimension(width)?, dimension(height)?))
+}
+
+/// Whether `--inline` is on, and the column it draws from.
+static INLINE: AtomicBool = AtomicBool::new(false);
+static INLINE_COLUMN: AtomicU16 = AtomicU16::new(0);
+
+/// Draws from the cursor's current column from now on, leaving whatever a
+/// script already printed before it on the line alone.
+pub fn use_inline() -> io::Result<()> {
+    let (column, _) = cursor::position()?;
+    INLINE_COLUMN.store(column, Ordering::Relaxed);
+    INLINE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// The column `--inline` draws from, if it's on.
+pub fn inline_column() -> Option<u16> {
+    INLINE
+        .load(Ordering::Relaxed)
+        .then(|| INLINE_COLUMN.load(Ordering::Relaxed))
+}
+
+/// Whether `--no-clear` is on.
+static NO_CLEAR: AtomicBool = AtomicBool::new(false);
+
+/// Draws centered output over whatever is on the screen from now on,
+/// rather than clearing it first.
+pub fn use_no_clear() {
+    NO_CLEAR.store(true, Ordering::Relaxed);
+}
+
+/// Whether effects drawing at centered positions may clear the screen
+/// first, or after a resize, which `--no-clear` doesn't allow.
+pub fn may_clear_screen() -> bool {
+    !NO_CLEAR.load(Ordering::Relaxed)
+}
+
+/// Whether effects may clear the line before drawing on it, which neither
+/// `--simple-tty` nor `--inline` allows.
+pub fn may_clear_line() -> bool {
+    !simple_tty() && inline_column().is_none()
+}
+
+/// Moves to the start of the current line, with a bare carriage return
+/// under `--simple-tty`, or to the column `--inline` started in.
+pub struct LineStart;
+
+fn write_line_start(f: &mut impl fmt::Write, simple: bool, column: u16) -> fmt::Result {
+    if simple {
+        f.write_char('\r')
+    } else {
+        cursor::MoveToColumn(column).write_ansi(f)
+    }
+}
+
+impl Command for LineStart {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write_line_start(f, simple_tty(), inline_column().unwrap_or(0))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        cursor::MoveToColumn(inline_column().unwrap_or(0)).execute_winapi()
+    }
+}
+
+/// Puts the terminal into the state an effect needs and restores it when
+/// dropped, including when the effect bails out early with an error.
+pub struct TerminalGuard {
+    cursor_hidden: bool,
+    alt_screen: bool,
+}
+
+impl TerminalGuard {
+    /// Hides the cursor for the duration of an effect, clearing the screen
+    /// first when the effect draws at absolute (centered) positions, unless
+    /// `--no-clear` is on; a box around the content is drawn again after.
+    /// Under `--simple-tty`, or when frames go out as JSON, the cursor is
+    /// left alone.
+    pub fn hide_cursor(clear_screen: bool) -> io::Result<Self> {
+        // Effects start here, so this is where their frames start keeping time
+        playback::restart_clock();
+        if simple_tty() || json_frames::is_active() || snapshot::is_active() {
+            return Ok(Self {
+                cursor_hidden: false,
+                alt_screen: false,
+            });
+        }
+        let mut stdout = io::stdout();
+        if clear_screen && may_clear_screen() {
+            execute!(stdout, terminal::Clear(ClearType::All))?;
+            resize::redraw_frame()?;
+        }
+        execute!(stdout, cursor::Hide)?;
+
+        Ok(Self {
+            cursor_hidden: true,
+            alt_screen: false,
+        })
+    }
+
+    /// Switches to the alternate screen so animations leave the scrollback
+    /// untouched; the previous screen comes back on drop.
+    pub fn alt_screen() -> io::Result<Self> {
+        execute!(
+            io::stdout(),
+            EnterAlternateScreen,
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+
+        Ok(Self {
+            cursor_hidden: false,
+            alt_screen: true,
+        })
+    }
+
+    /// Shows the cursor again once an effect's last frame is out, without
+    /// the color reset `drop` adds in case it bails out partway through a
+    /// frame; the frame itself ends with one.
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.cursor_hidden {
+            execute!(io::stdout(), cursor::Show)?;
+            self.cursor_hidden = false;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // Errors can't be reported from drop, and there's nothing left to do
+        let mut stdout = io::stdout();
+        if self.cursor_hidden {
+            let _ = execute!(stdout, ResetColor, cursor::Show);
+        }
+        if self.alt_screen {
+            let _ = execute!(stdout, LeaveAlternateScreen);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("80x24"), Ok((80, 24)));
+        assert_eq!(parse_size("120X40"), Ok((120, 40)));
+        assert!(parse_size("80").is_err());
+        assert!(parse_size("0x24").is_err());
+        assert!(parse_size("80x-1").is_err());
+    }
+
+    #[test]
+    fn test_line_start_is_a_carriage_return_in_simple_tty() {
+        let mut ansi = String::new();
+        write_line_start(&mut ansi, false, 0).unwrap();
+        assert_eq!(ansi, "\x1b[1G");
+
+        let mut simple = String::new();
+        write_line_start(&mut simple, true, 0).unwrap();
+        assert_eq!(simple, "\r");
+    }
+
+    #[test]
+    fn test_line_start_keeps_to_the_inline_column() {
+        let mut ansi = String::new();
+        write_line_start(&mut ansi, false, 8).unwrap();
+        assert_eq!(ansi, "\x1b[9G");
+    }
+}
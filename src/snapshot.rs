@@ -0,0 +1,249 @@
+use crate::ansi::{Pen, BLINK, BOLD, DIM, ITALIC, REVERSE, STRIKETHROUGH, UNDERLINE};
+use crate::capability::{self, ColorMode};
+use crate::playback;
+use crate::vt::{Cell, Screen};
+use clap::ValueEnum;
+use crossterm::style::{Attribute, Color, SetAttribute, SetBackgroundColor, SetForegroundColor};
+use crossterm::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use unicode_width::UnicodeWidthChar;
+
+/// How escape codes are marked in the snapshot, so a shell prompt that
+/// embeds it knows they take up no room.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Shell {
+    /// Bare escape codes, e.g. for starship custom segments
+    Plain,
+    /// Each escape code between readline's \001 and \002 markers, for PS1
+    Bash,
+    /// Each escape code in %{ and %}, with % in the text doubled, for PROMPT
+    Zsh,
+}
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static SNAPSHOT: Mutex<Option<Snapshot>> = Mutex::new(None);
+
+/// The screen the effect's first frame would have drawn on.
+struct Snapshot {
+    screen: Screen,
+    /// Whether anything was drawn since the run started
+    drawn: bool,
+    /// Whether the first frame is in, and the effect was asked to stop
+    taken: bool,
+}
+
+/// Keeps the effect's first frame instead of writing it to stdout, then
+/// interrupts the effect before it draws another.
+pub fn start() {
+    if let Ok(mut snapshot) = SNAPSHOT.lock() {
+        *snapshot = Some(Snapshot {
+            screen: Screen::default(),
+            drawn: false,
+            taken: false,
+        });
+        ACTIVE.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Whether a snapshot is being taken, so nothing else may write escape
+/// codes to stdout.
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Takes bytes headed for the terminal onto the snapshot's screen, until
+/// the first frame is in. Returns false when no snapshot is being taken,
+/// and the bytes should be written as they are.
+pub fn record(bytes: &[u8]) -> bool {
+    if !is_active() {
+        return false;
+    }
+    if let Ok(mut snapshot) = SNAPSHOT.lock() {
+        if let Some(snapshot) = snapshot.as_mut().filter(|snapshot| !snapshot.taken) {
+            snapshot.screen.feed(bytes);
+            snapshot.drawn = true;
+        }
+    }
+    true
+}
+
+/// Keeps the frame just drawn as the snapshot, if it's the first.
+pub fn end_frame() {
+    if !is_active() {
+        return;
+    }
+    if let Ok(mut snapshot) = SNAPSHOT.lock() {
+        if let Some(snapshot) = snapshot.as_mut() {
+            if snapshot.drawn && !snapshot.taken {
+                snapshot.taken = true;
+                playback::interrupt();
+            }
+        }
+    }
+}
+
+/// Stops taking the snapshot and returns it as text for `shell`, one line
+/// per row, each ending in a reset.
+pub fn finish(shell: Shell) -> String {
+    ACTIVE.store(false, Ordering::Relaxed);
+    let Some(snapshot) = SNAPSHOT
+        .lock()
+        .ok()
+        .and_then(|mut snapshot| snapshot.take())
+    else {
+        return String::new();
+    };
+    if snapshot.taken {
+        playback::interrupt_handled();
+    }
+    styled(
+        snapshot.screen.rows(),
+        snapshot.screen.pens(),
+        shell,
+        capability::mode(),
+    )
+}
+
+/// The escape codes that switch to `pen` from the default look, in
+/// colors terminals in `mode` can display.
+fn sgr(pen: &Pen, mode: ColorMode) -> String {
+    let mut codes = String::new();
+    let attributes = [
+        (BOLD, Attribute::Bold),
+        (DIM, Attribute::Dim),
+        (ITALIC, Attribute::Italic),
+        (UNDERLINE, Attribute::Underlined),
+        (BLINK, Attribute::SlowBlink),
+        (REVERSE, Attribute::Reverse),
+        (STRIKETHROUGH, Attribute::CrossedOut),
+    ];
+    for (bit, attribute) in attributes {
+        if pen.attributes & bit != 0 {
+            let _ = SetAttribute(attribute).write_ansi(&mut codes);
+        }
+    }
+    let fitted = |color: Option<Color>| {
+        color
+            .map(|color| capability::fit_to(color, mode))
+            .filter(|&color| color != Color::Reset)
+    };
+    if let Some(color) = fitted(pen.foreground) {
+        let _ = SetForegroundColor(color).write_ansi(&mut codes);
+    }
+    if let Some(color) = fitted(pen.background) {
+        let _ = SetBackgroundColor(color).write_ansi(&mut codes);
+    }
+    codes
+}
+
+/// `codes` marked for `shell` as taking up no room.
+fn invisible(codes: &str, shell: Shell) -> String {
+    match shell {
+        Shell::Plain => codes.to_string(),
+        Shell::Bash => format!("\x01{codes}\x02"),
+        Shell::Zsh => format!("%{{{codes}%}}"),
+    }
+}
+
+/// Rows of cells as text, with an escape code wherever the look changes.
+/// Blank cells at the end of a row are left off.
+fn styled(rows: &[Vec<Cell>], pens: &[Vec<Pen>], shell: Shell, mode: ColorMode) -> String {
+    let reset = invisible("\x1b[0m", shell);
+    let mut text = String::new();
+    for (row, row_pens) in rows.iter().zip(pens) {
+        let end = row
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |end| end + 1);
+        // The codes in effect, empty for the default look
+        let mut current = String::new();
+        let mut covered = 0;
+        for (cell, pen) in row[..end].iter().zip(row_pens) {
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+            let (ch, pen) = match *cell {
+                Some((ch, foreground)) => (ch, Pen { foreground, ..*pen }),
+                None => (' ', Pen::default()),
+            };
+            let codes = sgr(&pen, mode);
+            if codes != current {
+                if !current.is_empty() {
+                    text.push_str(&reset);
+                }
+                if !codes.is_empty() {
+                    text.push_str(&invisible(&codes, shell));
+                }
+                current = codes;
+            }
+            if shell == Shell::Zsh && ch == '%' {
+                text.push('%');
+            }
+            text.push(ch);
+            covered = ch.width().unwrap_or(1).saturating_sub(1);
+        }
+        if !current.is_empty() {
+            text.push_str(&reset);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(bytes: &[u8]) -> Screen {
+        let mut screen = Screen::default();
+        screen.feed(bytes);
+        screen
+    }
+
+    #[test]
+    fn test_styled_keeps_colors_and_drops_cursor_moves() {
+        let drawn = screen(b"\x1b[?25l\x1b[2K\x1b[1G\x1b[38;2;255;0;0mHi\x1b[0m there\x1b[1G");
+        assert_eq!(
+            styled(
+                drawn.rows(),
+                drawn.pens(),
+                Shell::Plain,
+                ColorMode::Truecolor
+            ),
+            "\x1b[38;2;255;0;0mHi\x1b[0m there\n"
+        );
+    }
+
+    #[test]
+    fn test_styled_fits_colors_to_the_depth() {
+        let drawn = screen(b"\x1b[38;2;255;0;0mHi");
+        assert_eq!(
+            styled(drawn.rows(), drawn.pens(), Shell::Plain, ColorMode::Ansi256),
+            "\x1b[38;5;196mHi\x1b[0m\n"
+        );
+        assert_eq!(
+            styled(drawn.rows(), drawn.pens(), Shell::Plain, ColorMode::Mono),
+            "Hi\n"
+        );
+    }
+
+    #[test]
+    fn test_styled_marks_escape_codes_for_prompts() {
+        let drawn = screen(b"\x1b[1;38;2;0;0;255m100%\x1b[0m");
+        assert_eq!(
+            styled(
+                drawn.rows(),
+                drawn.pens(),
+                Shell::Bash,
+                ColorMode::Truecolor
+            ),
+            "\x01\x1b[1m\x1b[38;2;0;0;255m\x02100%\x01\x1b[0m\x02\n"
+        );
+        assert_eq!(
+            styled(drawn.rows(), drawn.pens(), Shell::Zsh, ColorMode::Truecolor),
+            "%{\x1b[1m\x1b[38;2;0;0;255m%}100%%%{\x1b[0m%}\n"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{clip, graphics, html, json_frames, output, profile, snapshot};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Resident memory may grow this much over a soak before it counts as a leak.
+const MAX_RSS_GROWTH_KIB: u64 = 8 * 1024;
+/// Open file descriptors must return to the post-warm-up count.
+const MAX_FD_GROWTH: usize = 0;
+/// Late frames may be this many times slower than early ones (at p95).
+const MAX_FRAME_SLOWDOWN: f64 = 2.0;
+/// Frame-time growth below this is noise, whatever the ratio.
+const FRAME_SLOWDOWN_FLOOR: Duration = Duration::from_millis(5);
+
+static RECORDING: AtomicBool = AtomicBool::new(false);
+static FRAME_TIMES: Mutex<Vec<Duration>> = Mutex::new(Vec::new());
+
+/// Times one rendered frame. Effects start it before drawing and finish it
+/// after flushing; it does nothing unless a soak, profile, clip buffer, or
+/// HTML export is running.
+pub struct FrameTimer(Option<(Instant, u64)>);
+
+impl FrameTimer {
+    pub fn start() -> Self {
+        let active = RECORDING.load(Ordering::Relaxed) || profile::is_active();
+        Self(active.then(|| (Instant::now(), output::bytes_written())))
+    }
+
+    pub fn finish(self) {
+        clip::end_frame();
+        html::end_frame();
+        json_frames::end_frame();
+        graphics::end_frame();
+        snapshot::end_frame();
+        if let Some((started, bytes_before)) = self.0 {
+            let elapsed = started.elapsed();
+            if RECORDING.load(Ordering::Relaxed) {
+                if let Ok(mut frames) = FRAME_TIMES.lock() {
+                    frames.push(elapsed);
+                }
+            }
+            profile::record_frame(started, elapsed, output::bytes_written() - bytes_before);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ResourceSample {
+    rss_kib: u64,
+    open_fds: usize,
+}
+
+/// Reads this process's resident memory and open descriptor count. Only
+/// available where `/proc` exists.
+fn sample_resources() -> Option<ResourceSample> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let rss_kib = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())?;
+    // The directory handle used for listing shows up in the listing itself
+    let open_fds = fs::read_dir("/proc/self/fd")
+        .ok()?
+        .count()
+        .saturating_sub(1);
+
+    Some(ResourceSample { rss_kib, open_fds })
+}
+
+fn percentile(sorted: &[Duration], fraction: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let index = ((sorted.len() - 1) as f64 * fraction).round() as usize;
+    sorted[index]
+}
+
+fn p95(frames: &[Duration]) -> Duration {
+    let mut sorted = frames.to_vec();
+    sorted.sort();
+    percentile(&sorted, 0.95)
+}
+
+/// Compares the end of a soak against its warmed-up start and describes
+/// every threshold that was exceeded.
+fn find_regressions(
+    baseline: Option<ResourceSample>,
+    latest: Option<ResourceSample>,
+    frames: &[Duration],
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if let (Some(baseline), Some(latest)) = (baseline, latest) {
+        let rss_growth = latest.rss_kib.saturating_sub(baseline.rss_kib);
+        if rss_growth > MAX_RSS_GROWTH_KIB {
+            problems.push(format!(
+                "resident memory grew by {rss_growth} KiB (limit {MAX_RSS_GROWTH_KIB} KiB)"
+            ));
+        }
+
+        let fd_growth = latest.open_fds.saturating_sub(baseline.open_fds);
+        if fd_growth > MAX_FD_GROWTH {
+            problems.push(format!(
+                "open file descriptors grew by {fd_growth} (limit {MAX_FD_GROWTH})"
+            ));
+        }
+    }
+
+    let quarter = frames.len() / 4;
+    if quarter > 0 {
+        let early = p95(&frames[..quarter]);
+        let late = p95(&frames[frames.len() - quarter..]);
+        if late > early + FRAME_SLOWDOWN_FLOOR
+            && late.as_secs_f64() > early.as_secs_f64() * MAX_FRAME_SLOWDOWN
+        {
+            problems.push(format!(
+                "p95 frame time grew from {:.2?} to {:.2?}",
+                early, late
+            ));
+        }
+    }
+
+    problems
+}
+
+/// Runs `iteration` back to back for `minutes`, then reports memory,
+/// descriptor, and frame-time figures on stderr. Fails if any of them grew
+/// past the thresholds between the first iteration (the warm-up) and the last.
+pub fn run<F>(minutes: f64, mut iteration: F) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: FnMut() -> Result<(), Box<dyn std::error::Error>>,
+{
+    RECORDING.store(true, Ordering::Relaxed);
+    let deadline = Instant::now() + Duration::from_secs_f64(minutes.max(0.0) * 60.0);
+
+    let mut iterations = 0;
+    let mut baseline = None;
+    let latest = loop {
+        iteration()?;
+        iterations += 1;
+
+        let sample = sample_resources();
+        if iterations == 1 {
+            baseline = sample;
+        }
+
+        if Instant::now() >= deadline {
+            break sample;
+        }
+    };
+    RECORDING.store(false, Ordering::Relaxed);
+
+    let frames = FRAME_TIMES
+        .lock()
+        .map(|frames| frames.clone())
+        .unwrap_or_default();
+    let mut sorted = frames.clone();
+    sorted.sort();
+
+    eprintln!("soak: {iterations} iterations, {} frames", frames.len());
+    eprintln!(
+        "soak: frame time p50 {:.2?}  p95 {:.2?}  p99 {:.2?}  max {:.2?}",
+        percentile(&sorted, 0.50),
+        percentile(&sorted, 0.95),
+        percentile(&sorted, 0.99),
+        sorted.last().copied().unwrap_or_default()
+    );
+    match (baseline, latest) {
+        (Some(baseline), Some(latest)) => eprintln!(
+            "soak: rss {} -> {} KiB, open fds {} -> {}",
+            baseline.rss_kib, latest.rss_kib, baseline.open_fds, latest.open_fds
+        ),
+        _ => eprintln!("soak: memory and descriptor usage unavailable on this platform"),
+    }
+
+    let problems = find_regressions(baseline, latest, &frames);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("soak test failed: {}", problems.join("; ")).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(rss_kib: u64, open_fds: usize) -> Option<ResourceSample> {
+        Some(ResourceSample { rss_kib, open_fds })
+    }
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&ms| Duration::from_millis(ms)).collect()
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sorted = millis(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(percentile(&sorted, 0.0), Duration::from_millis(1));
+        assert_eq!(percentile(&sorted, 0.5), Duration::from_millis(6));
+        assert_eq!(percentile(&sorted, 1.0), Duration::from_millis(10));
+        assert_eq!(percentile(&[], 0.5), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_steady_run_passes() {
+        let frames = millis(&[2; 40]);
+        assert!(find_regressions(sample(10_000, 4), sample(10_500, 4), &frames).is_empty());
+    }
+
+    #[test]
+    fn test_memory_and_fd_growth_fail() {
+        let problems = find_regressions(sample(10_000, 4), sample(30_000, 6), &[]);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("resident memory"));
+        assert!(problems[1].contains("file descriptors"));
+    }
+
+    #[test]
+    fn test_frame_slowdown_fails() {
+        let mut frames = millis(&[2; 30]);
+        frames.extend(millis(&[20; 10]));
+        let problems = find_regressions(None, None, &frames);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("frame time"));
+    }
+
+    #[test]
+    fn test_sample_resources_reads_proc() {
+        if let Some(sample) = sample_resources() {
+            assert!(sample.rss_kib > 0);
+            assert!(sample.open_fds > 0);
+        }
+    }
+}
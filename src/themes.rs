@@ -0,0 +1,176 @@
+use crate::color::Rgb;
+
+/// A coordinated look that `--theme` gives whichever effect runs: its
+/// colors, by the part each plays, and a few flags for particular effects.
+pub struct Theme {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The text's own color
+    pub base: Rgb,
+    /// The highlight that sweeps, twinkles, or flashes over the text
+    pub shine: Rgb,
+    /// What the text fades in from, and the unlit part of a bar
+    pub background: Rgb,
+    /// Colors for effects that take a list, such as gradient stops
+    pub stops: &'static [Rgb],
+    /// Further flags as (effect, flag, value), with no value for a switch
+    pub effect_flags: &'static [(&'static str, &'static str, Option<&'static str>)],
+}
+
+// Each color's part, and the flags effects take it on; the first one an
+// effect has is the one it gets
+const BASE_FLAGS: &[&str] = &["color", "base-color", "color-locked"];
+const SHINE_FLAGS: &[&str] = &[
+    "shine-color",
+    "twinkle-color",
+    "highlight",
+    "lap-color",
+    "accent-color",
+    "color-scrambled",
+];
+const BACKGROUND_FLAGS: &[&str] = &["from-color", "empty-color"];
+const STOPS_FLAGS: &[&str] = &["stops", "colors"];
+
+/// The built-in themes, in the order `--help` lists them.
+pub const THEMES: &[Theme] = &[
+    Theme {
+        name: "gold",
+        description: "burnished gold catching a soft, wide glint",
+        base: (184, 134, 11),
+        shine: (255, 240, 170),
+        background: (40, 28, 0),
+        stops: &[(120, 80, 0), (218, 165, 32), (255, 240, 170)],
+        effect_flags: &[("shine", "--width", Some("4")), ("shine", "--blur", None)],
+    },
+    Theme {
+        name: "ice",
+        description: "frosted blue with a cold white gleam",
+        base: (120, 170, 210),
+        shine: (240, 250, 255),
+        background: (10, 25, 45),
+        stops: &[(20, 60, 120), (120, 190, 230), (230, 250, 255)],
+        effect_flags: &[
+            ("shine", "--easing", Some("sine")),
+            ("fire", "--palette", Some("blue")),
+        ],
+    },
+    Theme {
+        name: "matrix",
+        description: "terminal green, lit up pale",
+        base: (0, 170, 50),
+        shine: (190, 255, 190),
+        background: (0, 20, 0),
+        stops: &[(0, 60, 0), (0, 200, 70), (190, 255, 190)],
+        effect_flags: &[
+            ("scramble", "--charset", Some("binary")),
+            ("fire", "--palette", Some("green")),
+        ],
+    },
+    Theme {
+        name: "synthwave",
+        description: "hot pink under a neon cyan sweep",
+        base: (255, 0, 170),
+        shine: (0, 240, 255),
+        background: (30, 0, 60),
+        stops: &[(120, 0, 255), (255, 0, 170), (255, 150, 0)],
+        effect_flags: &[("shine", "--mode", Some("split"))],
+    },
+    Theme {
+        name: "fire",
+        description: "orange embers flaring yellow-white",
+        base: (255, 110, 0),
+        shine: (255, 240, 150),
+        background: (60, 0, 0),
+        stops: &[(120, 0, 0), (255, 60, 0), (255, 200, 50)],
+        effect_flags: &[
+            ("shine", "--easing", Some("ease-out")),
+            ("twinkle", "--twinkle-ratio", Some("0.5")),
+        ],
+    },
+];
+
+/// The built-in theme called `name`, ignoring case.
+pub fn named(name: &str) -> Option<&'static Theme> {
+    THEMES
+        .iter()
+        .find(|theme| theme.name.eq_ignore_ascii_case(name))
+}
+
+fn rgb_arg(color: Rgb) -> String {
+    format!("{},{},{}", color.0, color.1, color.2)
+}
+
+impl Theme {
+    /// The flags that give `effect` this look, leaving out any for which
+    /// `open` says the effect has no such flag or it was set already.
+    pub fn args(&self, effect: &str, open: impl Fn(&str) -> bool) -> Vec<String> {
+        let stops = self
+            .stops
+            .iter()
+            .map(|&color| rgb_arg(color))
+            .collect::<Vec<_>>()
+            .join(",");
+        let roles = [
+            (BASE_FLAGS, rgb_arg(self.base)),
+            (SHINE_FLAGS, rgb_arg(self.shine)),
+            (BACKGROUND_FLAGS, rgb_arg(self.background)),
+            (STOPS_FLAGS, stops),
+        ];
+
+        let mut args = Vec::new();
+        for (flags, value) in roles {
+            if let Some(flag) = flags.iter().find(|flag| open(flag)) {
+                args.push(format!("--{flag}"));
+                args.push(value);
+            }
+        }
+        for &(name, flag, value) in self.effect_flags {
+            if name == effect && open(flag.trim_start_matches("--")) {
+                args.push(flag.to_string());
+                args.extend(value.map(str::to_string));
+            }
+        }
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::parse_color_list;
+
+    #[test]
+    fn test_themes_have_unique_names_and_valid_stops() {
+        for (i, theme) in THEMES.iter().enumerate() {
+            assert!(THEMES[i + 1..].iter().all(|other| other.name != theme.name));
+            let stops = theme.args("gradient", |flag| flag == "stops");
+            assert_eq!(parse_color_list(&stops[1]).unwrap(), theme.stops);
+        }
+        assert_eq!(named("ICE").unwrap().name, "ice");
+        assert!(named("plaid").is_none());
+    }
+
+    #[test]
+    fn test_args_fill_each_part_on_the_first_flag_open() {
+        let theme = named("gold").unwrap();
+        let shine_flags = ["color", "shine-color", "width", "blur"];
+
+        assert_eq!(
+            theme.args("shine", |flag| shine_flags.contains(&flag)),
+            [
+                "--color",
+                "184,134,11",
+                "--shine-color",
+                "255,240,170",
+                "--width",
+                "4",
+                "--blur"
+            ]
+        );
+        // Flags already given stay as they were, and other effects' are skipped
+        assert_eq!(
+            theme.args("twinkle", |flag| flag == "twinkle-color" || flag == "blur"),
+            ["--twinkle-color", "255,240,170"]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use crate::ansi::{column_starts, parse_styled};
+use crate::capability::fg;
+use crate::effects::gradient::color_at;
+use crate::effects::stage::rgb_color;
+use crossterm::{
+    queue,
+    style::{Color, Print, ResetColor},
+};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// Window input rate is measured over.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// How long a line may wait for its turn before animating counts as
+/// falling behind.
+const MAX_LAG: Duration = Duration::from_millis(500);
+
+/// How a streamed line is shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Path {
+    /// Run through the effect as usual
+    Animated,
+    /// Printed once in the effect's resting colors
+    Static,
+}
+
+/// Picks the path for each streamed line from how fast input arrives, so
+/// a flood of lines is decorated statically rather than queueing up behind
+/// animations.
+pub struct Throughput {
+    /// Lines per second above which animation stops
+    max_rate: f64,
+    arrivals: VecDeque<Instant>,
+    path: Path,
+}
+
+impl Throughput {
+    pub fn new(max_rate: f64) -> Self {
+        Self {
+            max_rate,
+            arrivals: VecDeque::new(),
+            path: Path::Animated,
+        }
+    }
+
+    /// Lines that arrived within `RATE_WINDOW` up to `arrived`, per second.
+    fn rate(&mut self, arrived: Instant) -> f64 {
+        self.arrivals.push_back(arrived);
+        while self
+            .arrivals
+            .front()
+            .is_some_and(|&first| arrived.duration_since(first) > RATE_WINDOW)
+        {
+            self.arrivals.pop_front();
+        }
+        self.arrivals.len() as f64 / RATE_WINDOW.as_secs_f64()
+    }
+
+    /// The path for a line that `arrived` and is being shown at `now`, with
+    /// more lines `waiting` behind it. Animation resumes only once input
+    /// has slowed to half the limit and nothing is queued, so a bursty
+    /// stream doesn't flip back and forth.
+    pub fn path(&mut self, arrived: Instant, now: Instant, waiting: bool) -> Path {
+        let rate = self.rate(arrived);
+        let behind = now.duration_since(arrived) > MAX_LAG;
+        self.path = match self.path {
+            Path::Animated if rate > self.max_rate || behind => Path::Static,
+            Path::Static if rate <= self.max_rate / 2.0 && !behind && !waiting => Path::Animated,
+            path => path,
+        };
+        self.path
+    }
+}
+
+/// An effect's look at rest, used for lines that skip the animation.
+pub enum StaticStyle {
+    Solid((u8, u8, u8)),
+    /// Stops spread across each line, left to right
+    Gradient(Vec<(u8, u8, u8)>),
+}
+
+/// Writes `line` in `style` followed by `line_end`, leaving colors the input
+/// already has in place. Only color changes are written, to keep the cost
+/// per line low.
+pub fn write_static(
+    out: &mut impl Write,
+    line: &str,
+    style: &StaticStyle,
+    line_end: &str,
+) -> io::Result<()> {
+    let cells = parse_styled(line);
+    let (columns, width) = column_starts(&cells);
+    let stops: Vec<Color> = match style {
+        StaticStyle::Solid(color) => vec![rgb_color(*color)],
+        StaticStyle::Gradient(stops) => stops.iter().copied().map(rgb_color).collect(),
+    };
+
+    let mut current = None;
+    for (cell, column) in cells.iter().zip(columns) {
+        let color = cell.color.unwrap_or_else(|| {
+            color_at(
+                &stops,
+                column as f32 / width.saturating_sub(1).max(1) as f32,
+            )
+        });
+        if current != Some(color) {
+            queue!(out, fg(color))?;
+            current = Some(color);
+        }
+        queue!(out, Print(cell.ch))?;
+    }
+    if current.is_some() {
+        queue!(out, ResetColor)?;
+    }
+    queue!(out, Print(line_end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_slow_input_stays_animated() {
+        let start = Instant::now();
+        let mut throughput = Throughput::new(10.0);
+        for line in 0..20 {
+            let arrived = ms(start, line * 500);
+            assert_eq!(throughput.path(arrived, arrived, false), Path::Animated);
+        }
+    }
+
+    #[test]
+    fn test_fast_input_goes_static_until_it_slows() {
+        let start = Instant::now();
+        let mut throughput = Throughput::new(10.0);
+        let paths: Vec<Path> = (0..20)
+            .map(|line| {
+                let arrived = ms(start, line * 20);
+                throughput.path(arrived, arrived, false)
+            })
+            .collect();
+        assert_eq!(paths[9], Path::Animated);
+        assert_eq!(paths[10], Path::Static);
+
+        // Still static while lines queue up, then back once the rate drops
+        let later = ms(start, 1000);
+        assert_eq!(throughput.path(later, later, true), Path::Static);
+        let quiet = ms(start, 3000);
+        assert_eq!(throughput.path(quiet, quiet, false), Path::Animated);
+    }
+
+    #[test]
+    fn test_lines_left_waiting_go_static() {
+        let start = Instant::now();
+        let mut throughput = Throughput::new(10.0);
+        assert_eq!(throughput.path(start, ms(start, 2000), true), Path::Static);
+    }
+
+    #[test]
+    fn test_write_static_switches_color_only_when_it_changes() {
+        let mut out = Vec::new();
+        write_static(
+            &mut out,
+            "ab\x1b[31mc",
+            &StaticStyle::Solid((0, 0, 255)),
+            "\n",
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(out.matches("\x1b[38;").count(), 2);
+        assert!(out.contains("ab"));
+        assert!(out.ends_with("c\x1b[0m\n"));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::ansi::{apply_sgr_pen, Pen};
+use crossterm::style::Color;
+use unicode_width::UnicodeWidthChar;
+
+pub type Cell = Option<(char, Option<Color>)>;
+
+/// The screen a clifx run would have drawn on a terminal, rebuilt from the
+/// text and escape codes it writes. Only what ends up visible is kept:
+/// characters and their foreground colors, with each cell's full pen kept
+/// alongside for the few readers that need backgrounds and attributes.
+#[derive(Default)]
+pub struct Screen {
+    rows: Vec<Vec<Cell>>,
+    /// The pen each cell in `rows` was drawn with
+    pens: Vec<Vec<Pen>>,
+    x: usize,
+    y: usize,
+    saved: (usize, usize),
+    pen: Pen,
+    /// The start of a character or escape code cut off by the end of a read
+    pending: Vec<u8>,
+    /// Widest the content has been, so the layout doesn't jump as it moves
+    width: usize,
+}
+
+impl Screen {
+    /// Every row drawn so far, top first. `None` is a cell nothing was
+    /// drawn in, or the second column of a wide character.
+    pub fn rows(&self) -> &[Vec<Cell>] {
+        &self.rows
+    }
+
+    /// The pen every cell in `rows` was drawn with, in the same layout.
+    pub fn pens(&self) -> &[Vec<Pen>] {
+        &self.pens
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        let pending = std::mem::take(&mut self.pending);
+        let valid = match std::str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            Err(err) => err.valid_up_to(),
+        };
+        let text = std::str::from_utf8(&pending[..valid]).unwrap_or_default();
+        let consumed = self.interpret(text);
+        self.pending = pending[consumed..].to_vec();
+    }
+
+    /// Applies `text` to the screen, returning how much of it was used. An
+    /// escape code still missing its end is left for the next read.
+    fn interpret(&mut self, text: &str) -> usize {
+        let mut chars = text.char_indices();
+        while let Some((start, ch)) = chars.next() {
+            match ch {
+                '\x1b' => match chars.next() {
+                    None => return start,
+                    Some((_, '[')) => {
+                        let mut params = String::new();
+                        loop {
+                            match chars.next() {
+                                None => return start,
+                                Some((_, end @ '@'..='~')) => {
+                                    self.control(&params, end);
+                                    break;
+                                }
+                                Some((_, param)) => params.push(param),
+                            }
+                        }
+                    }
+                    Some((_, '7')) => self.saved = (self.x, self.y),
+                    Some((_, '8')) => (self.x, self.y) = self.saved,
+                    Some(_) => {}
+                },
+                '\r' => self.x = 0,
+                // A terminal returns the carriage on newlines from a cooked-mode program
+                '\n' => {
+                    self.x = 0;
+                    self.y += 1;
+                }
+                '\x08' => self.x = self.x.saturating_sub(1),
+                '\t' => self.x = (self.x / 8 + 1) * 8,
+                ch if ch.is_control() => {}
+                ch => self.print(ch),
+            }
+        }
+        text.len()
+    }
+
+    /// Applies one `ESC [ params end` sequence.
+    fn control(&mut self, params: &str, end: char) {
+        // Private modes (cursor visibility, alternate screen) don't change the content
+        if params.starts_with('?') {
+            return;
+        }
+        let numbers: Vec<usize> = params
+            .split(';')
+            .map(|number| number.parse().unwrap_or(0))
+            .collect();
+        let first = numbers[0];
+        let count = first.max(1);
+
+        match end {
+            'm' => apply_sgr_pen(params, &mut self.pen),
+            'A' => self.y = self.y.saturating_sub(count),
+            'B' => self.y += count,
+            'C' => self.x += count,
+            'D' => self.x = self.x.saturating_sub(count),
+            'G' => self.x = count - 1,
+            'H' | 'f' => {
+                self.y = count - 1;
+                self.x = numbers.get(1).copied().unwrap_or(0).max(1) - 1;
+            }
+            'J' => match first {
+                0 => {
+                    self.rows.truncate(self.y + 1);
+                    self.pens.truncate(self.y + 1);
+                    self.clear_line(self.x..usize::MAX);
+                }
+                1 => {
+                    self.rows
+                        .iter_mut()
+                        .take(self.y)
+                        .for_each(|row| row.fill(None));
+                    self.pens
+                        .iter_mut()
+                        .take(self.y)
+                        .for_each(|row| row.fill(Pen::default()));
+                    self.clear_line(0..self.x + 1);
+                }
+                _ => {
+                    self.rows.clear();
+                    self.pens.clear();
+                }
+            },
+            'K' => match first {
+                0 => self.clear_line(self.x..usize::MAX),
+                1 => self.clear_line(0..self.x + 1),
+                _ => self.clear_line(0..usize::MAX),
+            },
+            's' => self.saved = (self.x, self.y),
+            'u' => (self.x, self.y) = self.saved,
+            _ => {}
+        }
+    }
+
+    fn clear_line(&mut self, columns: std::ops::Range<usize>) {
+        if let Some(row) = self.rows.get_mut(self.y) {
+            let end = columns.end.min(row.len());
+            if columns.start < end {
+                row[columns.start..end].fill(None);
+                self.pens[self.y][columns.start..end].fill(Pen::default());
+            }
+        }
+    }
+
+    fn print(&mut self, ch: char) {
+        let width = ch.width().unwrap_or(0);
+        if width == 0 {
+            return;
+        }
+        if self.rows.len() <= self.y {
+            self.rows.resize_with(self.y + 1, Vec::new);
+            self.pens.resize_with(self.y + 1, Vec::new);
+        }
+        let (row, pens) = (&mut self.rows[self.y], &mut self.pens[self.y]);
+        if row.len() < self.x + width {
+            row.resize(self.x + width, None);
+            pens.resize(self.x + width, Pen::default());
+        }
+        row[self.x] = Some((ch, self.pen.foreground));
+        row[self.x + 1..self.x + width].fill(None);
+        pens[self.x..self.x + width].fill(self.pen);
+        self.x += width;
+        self.width = self.width.max(row.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::stage::rgb_color;
+
+    fn text(screen: &Screen) -> Vec<String> {
+        screen
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.map_or(' ', |(ch, _)| ch))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_screen_redraws_in_place() {
+        let mut screen = Screen::default();
+        screen.feed(b"abc\r\x1b[38;2;255;0;0mX\x1b[0m");
+        screen.feed(b"\nline two\n\x1b[1A\x1b[5Gvo");
+
+        assert_eq!(text(&screen), vec!["Xbc", "linevowo"]);
+        assert_eq!(screen.rows[0][0], Some(('X', Some(rgb_color((255, 0, 0))))));
+        assert_eq!(screen.rows[0][1], Some(('b', None)));
+        assert_eq!(screen.width, 8);
+    }
+
+    #[test]
+    fn test_screen_keeps_codes_split_across_reads() {
+        let mut screen = Screen::default();
+        screen.feed(b"a\x1b[38;2;0;0");
+        screen.feed(b";255mb\xe6\xbc");
+        screen.feed(b"\xa2");
+
+        assert_eq!(text(&screen), vec!["ab漢 "]);
+        assert_eq!(screen.rows[0][1], Some(('b', Some(rgb_color((0, 0, 255))))));
+    }
+
+    #[test]
+    fn test_screen_keeps_backgrounds_and_attributes() {
+        let mut screen = Screen::default();
+        screen.feed(b"\x1b[1;44ma\x1b[22mb\x1b[0mc\x1b[2K");
+        screen.feed(b"\x1b[1G\x1b[7mx");
+
+        assert_eq!(text(&screen), vec!["x  "]);
+        let pens = screen.pens();
+        assert_eq!(pens[0][0].attributes, crate::ansi::REVERSE);
+        assert_eq!(pens[0][1], Pen::default());
+
+        let mut screen = Screen::default();
+        screen.feed(b"\x1b[1;44ma\x1b[22mb\x1b[0mc");
+        let pens = screen.pens();
+        let blue = Some(rgb_color(crate::ansi::BASIC_COLORS[4]));
+        assert_eq!(pens[0][0].attributes, crate::ansi::BOLD);
+        assert_eq!(pens[0][0].background, blue);
+        assert_eq!(pens[0][1].attributes, 0);
+        assert_eq!(pens[0][1].background, blue);
+        assert_eq!(pens[0][2], Pen::default());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::input::read_files;
+use crate::playback;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+/// Where `watch` gets the text it shows.
+pub enum Source {
+    /// Files read one after another, as `--file` reads them
+    Files(Vec<PathBuf>),
+    /// A shell command whose output is the text
+    Command(String),
+}
+
+impl Source {
+    /// The text as it is now, up to `limit` lines.
+    pub fn read(&self, limit: Option<usize>) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        match self {
+            Source::Files(paths) => read_files(paths, limit),
+            Source::Command(command) => {
+                let output = shell(command)
+                    .stdin(Stdio::null())
+                    .stderr(Stdio::inherit())
+                    .output()
+                    .map_err(|err| format!("couldn't run '{command}': {err}"))?;
+                let text = String::from_utf8_lossy(&output.stdout);
+                Ok(text
+                    .lines()
+                    .take(limit.unwrap_or(usize::MAX))
+                    .map(String::from)
+                    .collect())
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(not(unix))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// What stopped the effect, or came in while nothing was running.
+pub enum Event {
+    /// The text changed to this
+    Changed(Vec<String>),
+    /// Ctrl-C was pressed
+    Stop,
+}
+
+/// Reads the source again every interval on a background thread. A change
+/// interrupts whatever effect is running so it can start over on the new
+/// text straight away, and so does Ctrl-C, so an effect that would run
+/// forever still puts the terminal back.
+pub struct Watcher {
+    events: Receiver<Event>,
+}
+
+impl Watcher {
+    /// Watches `source` for text other than `text`, which is shown first.
+    pub fn start(
+        source: Source,
+        interval: Duration,
+        limit: Option<usize>,
+        text: Vec<String>,
+    ) -> Self {
+        let (sender, events) = mpsc::channel();
+        let stop = sender.clone();
+        // Counted before it's sent, so it's never handled before it's asked for
+        let _ = ctrlc::set_handler(move || {
+            playback::interrupt();
+            let _ = stop.send(Event::Stop);
+        });
+        thread::spawn(move || {
+            let mut shown = text;
+            loop {
+                thread::sleep(interval);
+                // A file caught being replaced, or a failed command, keeps the last text up
+                let Ok(text) = source.read(limit) else {
+                    continue;
+                };
+                if text == shown {
+                    continue;
+                }
+                shown = text.clone();
+                playback::interrupt();
+                if sender.send(Event::Changed(text)).is_err() {
+                    playback::interrupt_handled();
+                    return;
+                }
+            }
+        });
+        Self { events }
+    }
+
+    /// Waits for the text to change or Ctrl-C. Effects stop being
+    /// interrupted once it's taken.
+    pub fn next(&self) -> Event {
+        let event = self.events.recv().unwrap_or(Event::Stop);
+        playback::interrupt_handled();
+        event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_source_reads_output_up_to_the_limit() {
+        let source = Source::Command("printf 'one\\ntwo\\nthree\\n'".to_string());
+        assert_eq!(source.read(None).unwrap(), vec!["one", "two", "three"]);
+        assert_eq!(source.read(Some(2)).unwrap(), vec!["one", "two"]);
+    }
+}
@@ -1,3 +1,5 @@
+use clap::ValueEnum;
+use clifx::easing::EasingFunction;
 use std::io::Write;
 use std::process::{Command, Stdio};
 
@@ -167,7 +169,18 @@ fn test_cli_invalid_color_format() {
 
 #[test]
 fn test_cli_easing_types() {
-    let easing_types = ["linear", "ease-in", "ease-out", "ease-in-out"];
+    let easing_types = [
+        "linear",
+        "ease-in",
+        "ease-out",
+        "ease-in-out",
+        "ease-out-bounce",
+        "ease-out-elastic",
+        "ease-in-back",
+        "sine",
+        "expo",
+        "circ",
+    ];
 
     for easing in &easing_types {
         let mut child = Command::new("cargo")
@@ -255,6 +268,71 @@ fn test_cli_shine2d_angles() {
     }
 }
 
+#[test]
+fn test_cli_shine2d_turning_angle() {
+    for turn in [
+        &["--angle-sweep", "0,180"][..],
+        &["--rotate", "--angle", "45"],
+    ] {
+        let mut args = vec!["run", "--", "shine2d", "--duration", "200", "--speed", "50"];
+        args.extend(turn);
+        let output = run_with_input(&args, "A beacon\nturning\n");
+        assert!(
+            output.status.success(),
+            "shine2d {turn:?} failed, stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(String::from_utf8_lossy(&output.stdout).contains('A'));
+    }
+
+    for invalid in [
+        &["--angle-sweep", "90"][..],
+        &["--angle-sweep", "0,north"],
+        &["--angle-sweep", "0,90", "--rotate"],
+    ] {
+        let mut args = vec!["run", "--", "shine2d"];
+        args.extend(invalid);
+        assert!(!run_with_input(&args, "Hi").status.success(), "{invalid:?}");
+    }
+}
+
+#[test]
+fn test_cli_shine2d_radial_shapes() {
+    for (shape, origin) in [
+        ("radial", "center"),
+        ("ripple", "0,0"),
+        ("ripple", "center"),
+    ] {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                "shine2d",
+                "--color",
+                "255,0,0",
+                "--shape",
+                shape,
+                "--origin",
+                origin,
+                "--duration",
+                "200",
+                "--speed",
+                "50",
+            ],
+            "Ripples\nspread out\n",
+        );
+        assert!(
+            output.status.success(),
+            "shine2d --shape {shape} --origin {origin} failed, stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(String::from_utf8_lossy(&output.stdout).contains('R'));
+    }
+
+    let invalid = run_with_input(&["run", "--", "shine2d", "--origin", "middle"], "Hi");
+    assert!(!invalid.status.success());
+}
+
 #[test]
 fn test_cli_twinkle_star_mode() {
     let mut child = Command::new("cargo")
@@ -330,3 +408,3651 @@ fn test_cli_empty_input() {
         );
     }
 }
+
+#[test]
+fn test_cli_hex_named_and_hsl_colors() {
+    let colors = ["#ffcc00", "0xffcc00", "gold", "hsl(40,100%,50%)"];
+
+    for color in &colors {
+        let mut child = Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "shine",
+                "--color",
+                color,
+                "--shine-color",
+                "white",
+                "--cycles",
+                "1",
+                "--duration",
+                "100",
+                "--speed",
+                "50",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to spawn CLI command");
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin
+                .write_all(TEST_TEXT.as_bytes())
+                .expect("Failed to write to stdin");
+        }
+
+        let output = child.wait_with_output().expect("Failed to read stdout");
+
+        assert!(
+            output.status.success(),
+            "Failed with color: {}, stderr: {}",
+            color,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+#[test]
+fn test_cli_twinkle_target_chars_and_regex() {
+    let option_sets = [
+        vec!["--target-chars", "*"],
+        vec!["--target-regex", "[*+]"],
+        vec!["--target-chars", "*", "--target-regex", "o+"],
+    ];
+
+    for options in &option_sets {
+        let mut args = vec![
+            "run",
+            "--",
+            "twinkle",
+            "--cycles",
+            "1",
+            "--duration",
+            "100",
+            "--speed",
+            "50",
+        ];
+        args.extend(options.iter());
+
+        let mut child = Command::new("cargo")
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to spawn CLI command");
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin
+                .write_all("* Hello + World *".as_bytes())
+                .expect("Failed to write to stdin");
+        }
+
+        let output = child.wait_with_output().expect("Failed to read stdout");
+
+        assert!(
+            output.status.success(),
+            "Failed with options: {:?}, stderr: {}",
+            options,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+#[test]
+fn test_cli_alternate_direction() {
+    for subcommand in ["shine", "shine2d"] {
+        let mut child = Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                subcommand,
+                "--alternate-direction",
+                "--cycles",
+                "2",
+                "--duration",
+                "100",
+                "--speed",
+                "50",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to spawn CLI command");
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin
+                .write_all(TEST_TEXT.as_bytes())
+                .expect("Failed to write to stdin");
+        }
+
+        let output = child.wait_with_output().expect("Failed to read stdout");
+
+        assert!(
+            output.status.success(),
+            "Failed with --alternate-direction for {}, stderr: {}",
+            subcommand,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+#[test]
+fn test_cli_transforms() {
+    let transforms = [
+        "uppercase",
+        "small-caps",
+        "leet",
+        "fullwidth",
+        "leet,fullwidth",
+    ];
+
+    for transform in &transforms {
+        let mut child = Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "--transform",
+                transform,
+                "shine",
+                "--cycles",
+                "1",
+                "--duration",
+                "100",
+                "--speed",
+                "50",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to spawn CLI command");
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin
+                .write_all(TEST_TEXT.as_bytes())
+                .expect("Failed to write to stdin");
+        }
+
+        let output = child.wait_with_output().expect("Failed to read stdout");
+
+        assert!(
+            output.status.success(),
+            "Failed with transform: {}, stderr: {}",
+            transform,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+#[test]
+fn test_cli_shine_all_lines_with_stagger() {
+    let mut child = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "shine",
+            "--all-lines",
+            "--line-stagger",
+            "50",
+            "--cycles",
+            "1",
+            "--duration",
+            "100",
+            "--speed",
+            "50",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn CLI command");
+
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin
+            .write_all("Hello\nWorld\n!".as_bytes())
+            .expect("Failed to write to stdin");
+    }
+
+    let output = child.wait_with_output().expect("Failed to read stdout");
+
+    assert!(
+        output.status.success(),
+        "Failed with --all-lines, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_cli_line_stagger_requires_all_lines() {
+    let output = Command::new("cargo")
+        .args(["run", "--", "shine", "--line-stagger", "50"])
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to run CLI command");
+
+    assert!(!output.status.success());
+}
+
+fn run_with_input(args: &[&str], input: &str) -> std::process::Output {
+    // Stdout is a pipe here, which would otherwise get the text once at rest
+    let args = with_forced_animation(args);
+    // Pin the detected color depth so assertions on RGB escapes are stable
+    let mut child = Command::new("cargo")
+        .args(&args)
+        .env("COLORTERM", "truecolor")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn CLI command");
+
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin
+            .write_all(input.as_bytes())
+            .expect("Failed to write to stdin");
+    }
+
+    child.wait_with_output().expect("Failed to read stdout")
+}
+
+/// `args` for `cargo run`, with `--force-animation` ahead of the clifx ones.
+fn with_forced_animation<'a>(args: &[&'a str]) -> Vec<&'a str> {
+    match args {
+        ["run", "--", rest @ ..] => [&["run", "--", "--force-animation"], rest].concat(),
+        _ => args.to_vec(),
+    }
+}
+
+/// Like `run_with_input`, but reading and writing presets under `config_home`.
+fn run_with_config(
+    config_home: &std::path::Path,
+    args: &[&str],
+    input: &str,
+) -> std::process::Output {
+    let mut child = Command::new("cargo")
+        .args(with_forced_animation(args))
+        .env("COLORTERM", "truecolor")
+        .env("XDG_CONFIG_HOME", config_home)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn CLI command");
+
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin
+            .write_all(input.as_bytes())
+            .expect("Failed to write to stdin");
+    }
+
+    child.wait_with_output().expect("Failed to read stdout")
+}
+
+#[test]
+fn test_cli_seed_makes_output_reproducible() {
+    let commands = [
+        vec![
+            "run",
+            "--",
+            "--seed",
+            "7",
+            "twinkle",
+            "--cycles",
+            "1",
+            "--duration",
+            "500",
+            "--speed",
+            "50",
+        ],
+        vec![
+            "run",
+            "--",
+            "--seed",
+            "7",
+            "shine",
+            "--cycles",
+            "1",
+            "--duration",
+            "100",
+            "--speed",
+            "50",
+        ],
+    ];
+
+    for args in &commands {
+        let first = run_with_input(args, "Hello... World... Stars.....");
+        let second = run_with_input(args, "Hello... World... Stars.....");
+
+        assert!(first.status.success());
+        assert_eq!(
+            first.stdout, second.stdout,
+            "Output differed between seeded runs of {:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn test_cli_countup_with_laps() {
+    let output = run_with_input(
+        &["run", "--", "countup", "--duration", "200", "--speed", "50"],
+        "build done\ntests done\n",
+    );
+
+    assert!(
+        output.status.success(),
+        "countup failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_cli_countdown_ends_on_zero() {
+    for transition in ["shine", "pulse"] {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                "countdown",
+                "--from",
+                "1",
+                "--transition",
+                transition,
+                "--flash",
+                "200",
+                "--speed",
+                "100",
+            ],
+            "",
+        );
+        assert!(
+            output.status.success(),
+            "countdown failed, stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // Block digits, lit by the gold accent as each number arrives
+        assert!(stdout.contains('█'));
+        assert!(stdout.contains("38;2;255;215;0"), "{transition} never lit");
+    }
+
+    let output = run_with_input(&["run", "--", "countdown", "--from", "0"], "");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_countup_stops_when_stdin_closes() {
+    let output = run_with_input(&["run", "--", "countup", "--speed", "50"], "");
+
+    assert!(
+        output.status.success(),
+        "countup failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_cli_levels_quantizes_highlight() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--levels",
+            "3",
+            "shine",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+            "--speed",
+            "20",
+            "--color",
+            "0,0,0",
+            "--shine-color",
+            "255,255,255",
+        ],
+        "Retro glow",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut shades: Vec<&str> = stdout
+        .split("\x1b[38;2;")
+        .skip(1)
+        .filter_map(|chunk| chunk.split('m').next())
+        .collect();
+    shades.sort();
+    shades.dedup();
+    assert!(
+        shades.len() <= 3,
+        "Expected at most 3 distinct shades, got {:?}",
+        shades
+    );
+}
+
+#[test]
+fn test_cli_levels_rejects_fewer_than_two() {
+    let output = run_with_input(&["run", "--", "--levels", "1", "twinkle"], "a.b");
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_preserves_input_colors() {
+    for effect in ["shine", "twinkle"] {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                effect,
+                "--cycles",
+                "1",
+                "--duration",
+                "200",
+                "--speed",
+                "50",
+            ],
+            "\x1b[31mred...\x1b[0m plain...",
+        );
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("\x1b[38;2;205;0;0m"),
+            "{} did not keep the input's red foreground",
+            effect
+        );
+        assert!(!stdout.contains("[31m"));
+    }
+}
+
+#[test]
+fn test_cli_soak_reports_resource_usage() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--soak",
+            "0.005",
+            "shine",
+            "--cycles",
+            "1",
+            "--duration",
+            "100",
+            "--speed",
+            "20",
+        ],
+        "Long-lived banner",
+    );
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "soak failed: {}", stderr);
+    assert!(stderr.contains("soak:"));
+    assert!(stderr.contains("p95"));
+}
+
+#[test]
+fn test_cli_soak_is_hidden_from_help() {
+    let output = Command::new("cargo")
+        .args(["run", "--", "--help"])
+        .output()
+        .expect("Failed to run CLI command");
+
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("--soak"));
+}
+
+#[test]
+fn test_cli_wide_characters() {
+    let commands: [&[&str]; 3] = [
+        &[
+            "run",
+            "--",
+            "shine",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        &[
+            "run",
+            "--",
+            "shine2d",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        &[
+            "run",
+            "--",
+            "twinkle",
+            "--star-mode",
+            "--target-chars",
+            "。",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+    ];
+
+    for args in commands {
+        let output = run_with_input(args, "你好。世界 🌟 emoji。");
+        assert!(output.status.success(), "{:?} failed", args);
+    }
+}
+
+#[test]
+fn test_cli_gradient() {
+    let output = run_with_input(
+        &["run", "--", "gradient", "--stops", "255,0,0,blue"],
+        "Gradient text",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[38;2;255;0;0mG"));
+    assert!(stdout.contains("\x1b[38;2;0;0;255mt"));
+}
+
+#[test]
+fn test_cli_gradient_animated() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "gradient",
+            "--animate",
+            "--angle",
+            "45",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "Line one\nLine two",
+    );
+
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_cli_gradient_invalid_stops() {
+    let output = run_with_input(&["run", "--", "gradient", "--stops", "red,nope"], "x");
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_shine_split_mode() {
+    for extra in [&[][..], &["--reflect"][..]] {
+        let mut args = vec![
+            "run",
+            "--",
+            "shine",
+            "--mode",
+            "split",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ];
+        args.extend_from_slice(extra);
+
+        let output = run_with_input(&args, "SYMMETRIC LOGO");
+        assert!(output.status.success(), "{:?} failed", args);
+    }
+}
+
+#[test]
+fn test_cli_stream_mode_animates_each_line() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--stream",
+            "shine",
+            "--cycles",
+            "0",
+            "--duration",
+            "100",
+            "--speed",
+            "50",
+        ],
+        "first line\nsecond line\n",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Each line finishes on its own line even though cycles were infinite
+    assert!(stdout.matches('\n').count() >= 2);
+}
+
+#[test]
+fn test_cli_stream_floods_are_styled_statically() {
+    let input: String = (0..200).map(|n| format!("line {n}\n")).collect();
+    let started = std::time::Instant::now();
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--stream",
+            "shine",
+            "--color",
+            "red",
+            "--duration",
+            "1000",
+            "--speed",
+            "50",
+        ],
+        &input,
+    );
+
+    assert!(output.status.success());
+    // Animating all of them would take over three minutes
+    assert!(started.elapsed() < std::time::Duration::from_secs(60));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[38;2;255;0;0mline 199\x1b[0m\n"));
+}
+
+#[test]
+fn test_cli_stream_conflicts_with_center() {
+    let output = run_with_input(&["run", "--", "--stream", "--center", "shine"], "x");
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_control_chars() {
+    let args = |mode| {
+        vec![
+            "run",
+            "--",
+            "--control-chars",
+            mode,
+            "gradient",
+            "--stops",
+            "white",
+        ]
+    };
+
+    let caret = run_with_input(&args("caret"), "bell\x07 back\x08");
+    assert!(caret.status.success());
+    let stdout = String::from_utf8_lossy(&caret.stdout);
+    assert!(!stdout.contains('\x07') && !stdout.contains('\x08'));
+    assert!(stdout.contains("mG"));
+
+    let strip = run_with_input(&args("strip"), "bell\x07");
+    let stdout = String::from_utf8_lossy(&strip.stdout);
+    assert!(!stdout.contains('\x07') && !stdout.contains("mG"));
+
+    let keep = run_with_input(&args("keep"), "bell\x07");
+    assert!(String::from_utf8_lossy(&keep.stdout).contains('\x07'));
+}
+
+#[test]
+fn test_cli_alt_screen_restores_previous_screen() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--alt-screen",
+            "shine",
+            "--cycles",
+            "1",
+            "--duration",
+            "100",
+            "--speed",
+            "50",
+        ],
+        "full screen",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let enter = stdout
+        .find("\x1b[?1049h")
+        .expect("never entered alt screen");
+    let leave = stdout.rfind("\x1b[?1049l").expect("never left alt screen");
+    assert!(enter < leave);
+    // The cursor is shown again before the original screen comes back
+    assert!(stdout[..leave].contains("\x1b[?25h"));
+}
+
+#[test]
+fn test_cli_phase_offsets_the_cycle() {
+    let run = |phase: &str| {
+        run_with_input(
+            &[
+                "run",
+                "--",
+                "--phase",
+                phase,
+                "shine",
+                "--color",
+                "black",
+                "--cycles",
+                "1",
+                "--duration",
+                "200",
+                "--speed",
+                "50",
+            ],
+            "Staggered wall",
+        )
+    };
+
+    let start = run("0");
+    let offset = run("0.33");
+    let wrapped = run("1.33");
+
+    assert!(start.status.success() && offset.status.success());
+    assert_ne!(start.stdout, offset.stdout);
+    assert_eq!(offset.stdout, wrapped.stdout);
+}
+
+#[test]
+fn test_cli_color_mode_fallbacks() {
+    let run = |mode: &str| {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                "--color-mode",
+                mode,
+                "gradient",
+                "--stops",
+                "red,blue",
+            ],
+            "Depth",
+        );
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    assert!(run("truecolor").contains("38;2;"));
+
+    // crossterm writes even the 16 named colors as palette indices
+    let indices = |output: &str| -> Vec<u8> {
+        output
+            .split("\x1b[38;5;")
+            .skip(1)
+            .filter_map(|rest| rest.split('m').next()?.parse().ok())
+            .collect()
+    };
+
+    let palette = run("256");
+    assert!(!palette.contains("38;2;"));
+    assert!(indices(&palette).contains(&196));
+
+    let basic = run("16");
+    assert!(!basic.contains("38;2;"));
+    let basic = indices(&basic);
+    assert!(!basic.is_empty());
+    assert!(basic.iter().all(|&index| index < 16));
+}
+
+#[test]
+fn test_cli_cubic_bezier_easing_needs_its_points() {
+    let shine = |extra: &[&str]| {
+        let mut args = vec!["run", "--", "shine", "--duration", "100", "--speed", "50"];
+        args.extend(extra);
+        run_with_input(&args, "Curve")
+    };
+
+    let output = shine(&["--easing", "cubic-bezier", "--bezier", "0.25,0.1,0.25,1.0"]);
+    assert!(output.status.success());
+
+    let missing = shine(&["--easing", "cubic-bezier"]);
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("--bezier"));
+
+    let stray = shine(&["--easing", "sine", "--bezier", "0.25,0.1,0.25,1.0"]);
+    assert!(!stray.status.success());
+    assert!(
+        !shine(&["--easing", "cubic-bezier", "--bezier", "2,0,0.5,1"])
+            .status
+            .success()
+    );
+}
+
+#[test]
+fn test_cli_simple_tty_uses_only_carriage_returns() {
+    for (effect, timing) in [("shine", "--duration"), ("pulse", "--period")] {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                "--simple-tty",
+                effect,
+                "--speed",
+                "50",
+                timing,
+                "200",
+            ],
+            "Serial\nconsole",
+        );
+        assert!(
+            output.status.success(),
+            "{effect}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains('\r'));
+        // Colors are the only escapes left: no cursor moves, hiding, or clears
+        for escape in stdout.split('\x1b').skip(1) {
+            let body: String = escape
+                .chars()
+                .take_while(|ch| !ch.is_ascii_alphabetic())
+                .collect();
+            assert!(
+                escape[body.len()..].starts_with('m'),
+                "{effect}: {escape:?}"
+            );
+        }
+    }
+
+    let output = run_with_input(&["run", "--", "--simple-tty", "wave"], "Serial");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_explain_fallbacks_reports_downgrades() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--explain-fallbacks",
+            "--color-mode",
+            "256",
+            "gradient",
+            "--stops",
+            "red,blue",
+        ],
+        "Depth",
+    );
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("fallbacks: colors reduced to the 256-color palette"));
+    assert!(stderr.contains("--color-mode 256 asks for it"));
+    assert!(stderr.contains("stdout isn't a terminal"));
+
+    // Nothing is said unless asked
+    let quiet = run_with_input(&["run", "--", "--color-mode", "256", "gradient"], "Depth");
+    assert!(!String::from_utf8_lossy(&quiet.stderr).contains("fallbacks:"));
+}
+
+#[test]
+fn test_cli_mono_maps_animation_to_attributes() {
+    let pulse = run_with_input(
+        &[
+            "run",
+            "--",
+            "--color-mode",
+            "mono",
+            "pulse",
+            "--period",
+            "400",
+            "--speed",
+            "50",
+        ],
+        "Breathe",
+    );
+    assert!(pulse.status.success());
+    let pulse = String::from_utf8_lossy(&pulse.stdout);
+    assert!(!pulse.contains("38;2;"));
+    // Blinks near the top of the breath and stops blinking near the bottom
+    assert!(pulse.contains("\x1b[5m"));
+    assert!(pulse.contains("\x1b[25m"));
+
+    let shine = run_with_input(
+        &[
+            "run",
+            "--",
+            "--color-mode",
+            "mono",
+            "shine",
+            "--color",
+            "100,100,100",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "Reversed band",
+    );
+    assert!(shine.status.success());
+    let shine = String::from_utf8_lossy(&shine.stdout);
+    assert!(!shine.contains("38;2;"));
+    assert!(shine.contains("\x1b[7m"));
+}
+
+#[test]
+fn test_cli_no_color_flag_draws_highlights_as_attributes() {
+    let spotlight = run_with_input(
+        &[
+            "run",
+            "--",
+            "--no-color",
+            "spotlight",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "Spotlit",
+    );
+    assert!(spotlight.status.success());
+    let spotlight = String::from_utf8_lossy(&spotlight.stdout);
+    assert!(!spotlight.contains("38;2;"));
+    // Bold under the spotlight, normal intensity outside it
+    assert!(spotlight.contains("\x1b[1m"));
+    assert!(spotlight.contains("\x1b[22m"));
+
+    let both = run_with_input(
+        &["run", "--", "--no-color", "--color-mode", "256", "gradient"],
+        "Hi",
+    );
+    assert!(!both.status.success());
+}
+
+#[test]
+fn test_cli_capture_input_colors_as_mask() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--capture-input-colors-as-mask",
+            "shine",
+            "--color",
+            "black",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "ab \x1b[38;2;0;0;255mcd\x1b[0m",
+    );
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Plain text keeps the terminal's default color in every frame
+    assert!(stdout.contains("\x1b[39ma\x1b[39mb"));
+    assert!(!stdout.contains("\x1b[38;2;0;0;0m"));
+    assert!(stdout.contains("\x1b[38;2;0;0;255mc"));
+}
+
+#[test]
+fn test_cli_mask_region_limits_shine_to_picked_cells() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--mask-region",
+            "2,0,2,1",
+            "shine",
+            "--color",
+            "black",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "ab \x1b[38;2;0;0;255mcd\x1b[0m",
+    );
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Blue 'd' falls outside the region, so it loses its color like plain 'a'
+    assert!(stdout.contains("\x1b[39ma\x1b[39mb"));
+    assert!(stdout.contains("\x1b[39md"));
+    // Inside it, plain text takes the base color and colored text keeps its own
+    assert!(stdout.contains("\x1b[38;2;0;0;0m \x1b[38;2;0;0;255mc"));
+}
+
+#[test]
+fn test_cli_font_file_renders_banner() {
+    // Every required glyph is empty except the "H"
+    let mut font = String::from("flf2a$ 2 2 3 0 0\n");
+    for code in (32..=126).chain([196, 214, 220, 228, 246, 252, 223]) {
+        if code == 'H' as u32 {
+            font.push_str("H H@\nHHH@@\n");
+        } else {
+            font.push_str("@\n@@\n");
+        }
+    }
+    let path = std::env::temp_dir().join(format!("clifx-test-{}.flf", std::process::id()));
+    std::fs::write(&path, font).unwrap();
+
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--font-file",
+            path.to_str().unwrap(),
+            "gradient",
+        ],
+        "HH",
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Two rows of "H H" and "HHH", kerned side by side
+    assert_eq!(stdout.matches('H').count(), 10);
+    assert!(stdout.contains("\r\n"));
+
+    let missing = run_with_input(
+        &["run", "--", "--font-file", "/nonexistent.flf", "gradient"],
+        "HH",
+    );
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("cannot read font file"));
+}
+
+#[test]
+fn test_cli_twinkle_sprites_inline_and_from_config() {
+    let config_home = std::env::temp_dir().join(format!("clifx-sprites-{}", std::process::id()));
+    std::fs::create_dir_all(config_home.join("clifx")).unwrap();
+    std::fs::write(
+        config_home.join("clifx/config.toml"),
+        "[sprites]\nspin = \"Q@4\"\n",
+    )
+    .unwrap();
+
+    for (sprite, glyph) in [("spin", 'Q'), ("Z", 'Z')] {
+        let output = run_with_config(
+            &config_home,
+            &[
+                "run",
+                "--",
+                "twinkle",
+                "--sprite",
+                sprite,
+                "--twinkle-ratio",
+                "1",
+                "--twinkling-percentage",
+                "1",
+                "--duration",
+                "500",
+                "--speed",
+                "50",
+            ],
+            "....",
+        );
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains(glyph));
+    }
+
+    let invalid = run_with_input(&["run", "--", "twinkle", "--sprite", "+x@0"], "..");
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("frame rate above 0"));
+
+    let _ = std::fs::remove_dir_all(&config_home);
+}
+
+#[test]
+fn test_cli_presets_save_run_and_override() {
+    let config_home = std::env::temp_dir().join(format!("clifx-presets-{}", std::process::id()));
+
+    let saved = run_with_config(
+        &config_home,
+        &[
+            "run",
+            "--",
+            "preset",
+            "save",
+            "warm",
+            "--",
+            "gradient",
+            "--stops",
+            "255,0,0,blue",
+        ],
+        "",
+    );
+    assert!(saved.status.success());
+    let config = std::fs::read_to_string(config_home.join("clifx/config.toml")).unwrap();
+    assert!(config.contains("[presets.warm]"));
+
+    let output = run_with_config(&config_home, &["run", "--", "preset", "warm"], "Hi");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\x1b[38;2;255;0;0mH"));
+
+    // Flags after the preset name take precedence over the stored ones
+    let output = run_with_config(
+        &config_home,
+        &["run", "--", "preset", "warm", "--stops", "0,255,0,blue"],
+        "Hi",
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\x1b[38;2;0;255;0mH"));
+
+    let missing = run_with_config(&config_home, &["run", "--", "preset", "cold"], "Hi");
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("no preset named 'cold'"));
+
+    // Invalid flags are rejected before anything is written
+    let invalid = run_with_config(
+        &config_home,
+        &[
+            "run", "--", "preset", "save", "bad", "--", "gradient", "--nope",
+        ],
+        "",
+    );
+    assert!(!invalid.status.success());
+    let config = std::fs::read_to_string(config_home.join("clifx/config.toml")).unwrap();
+    assert!(!config.contains("bad"));
+
+    std::fs::remove_dir_all(&config_home).unwrap();
+}
+
+#[test]
+fn test_cli_pulse() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "pulse",
+            "--color",
+            "200,100,0",
+            "--min-brightness",
+            "0.5",
+            "--period",
+            "200",
+            "--speed",
+            "50",
+            "--easing",
+            "linear",
+        ],
+        "Press any key",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Starts dim and reaches full color halfway through the breath
+    assert!(stdout.contains("\x1b[38;2;100;50;0mP"));
+    assert!(stdout.contains("\x1b[38;2;200;100;0mP"));
+
+    let inverted = run_with_input(
+        &[
+            "run",
+            "--",
+            "pulse",
+            "--min-brightness",
+            "0.9",
+            "--max-brightness",
+            "0.1",
+        ],
+        "Press any key",
+    );
+    assert!(!inverted.status.success());
+}
+
+#[test]
+fn test_cli_profile_out() {
+    let path = std::env::temp_dir().join(format!("clifx-profile-{}.json", std::process::id()));
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--profile-out",
+            path.to_str().unwrap(),
+            "shine",
+            "--color",
+            "red",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "Profiled",
+    );
+    assert!(output.status.success());
+
+    let profile: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(profile["command"], "shine");
+    assert_eq!(profile["parameters"]["color"], "red");
+    // Defaults are recorded too, so a replay doesn't depend on the CLI version
+    assert_eq!(profile["parameters"]["width"], "2");
+
+    let frames = profile["frames"].as_array().unwrap();
+    assert_eq!(frames.len(), 4);
+    assert!(frames
+        .iter()
+        .all(|frame| frame["bytes"].as_u64().unwrap() > 0));
+    assert_eq!(profile["summary"]["frames"], 4);
+    let total: u64 = frames
+        .iter()
+        .map(|frame| frame["bytes"].as_u64().unwrap())
+        .sum();
+    assert_eq!(profile["summary"]["total_bytes"], total);
+}
+
+#[test]
+fn test_cli_fps_sets_frame_interval() {
+    let path = std::env::temp_dir().join(format!("clifx-fps-{}.json", std::process::id()));
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--fps",
+            "20",
+            "--profile-out",
+            path.to_str().unwrap(),
+            "shine",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+        ],
+        "Paced",
+    );
+    assert!(output.status.success());
+
+    let profile: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(profile["parameters"]["speed"], "50");
+    assert_eq!(profile["summary"]["frames"], 4);
+
+    let both = run_with_input(
+        &["run", "--", "--fps", "20", "shine", "--speed", "50"],
+        "Paced",
+    );
+    assert!(!both.status.success());
+    assert!(String::from_utf8_lossy(&both.stderr).contains("--fps and --speed"));
+}
+
+#[test]
+fn test_cli_wave_spans_rows() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "wave",
+            "--amplitude",
+            "2",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "Riding the wave",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Five rows per frame, redrawn by climbing back to the top row
+    assert!(stdout.contains("\x1b[4A"));
+    assert_eq!(stdout.matches("\r\n").count(), 5 * 4);
+
+    let flat = run_with_input(&["run", "--", "wave", "--amplitude", "0"], "flat");
+    assert!(!flat.status.success());
+}
+
+#[test]
+fn test_cli_topbar_passes_input_through() {
+    // With stderr piped there is no terminal to draw the bar on, so stdin
+    // must come out exactly as it went in
+    let input = "first line\n\x1b[31msecond\x1b[0m line\nno newline";
+    let output = run_with_input(
+        &[
+            "run", "--", "topbar", "--text", "build", "--effect", "shine",
+        ],
+        input,
+    );
+
+    assert!(output.status.success());
+    assert_eq!(output.stdout, input.as_bytes());
+
+    let blank = run_with_input(&["run", "--", "topbar", "--fill", "\u{200b}"], "");
+    assert!(!blank.status.success());
+}
+
+#[test]
+fn test_cli_compose_runs_marked_spans() {
+    let output = run_with_input(
+        &["run", "--", "compose", "--duration", "200", "--cycles", "1"],
+        "Deploy {{fx:pulse color=gold}}now{{/fx}} or {{fx:shine delay=100}}later{{/fx}}",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("{{"));
+    // The pulsing span breathes through dimmer shades of gold
+    assert!(stdout.contains("\x1b[38;2;255;215;0mn"));
+    assert!(stdout.contains("\x1b[38;2;51;43;0mn"));
+
+    let broken = run_with_input(&["run", "--", "compose"], "{{fx:wobble}}x{{/fx}}");
+    assert!(!broken.status.success());
+    assert!(String::from_utf8_lossy(&broken.stderr).contains("unknown effect `wobble`"));
+}
+
+#[test]
+fn test_cli_other_effects_strip_markers() {
+    let output = run_with_input(
+        &["run", "--", "pulse", "--period", "100"],
+        "Deploy {{fx:pulse}}now{{/fx}}",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("{{"));
+    assert!(!stdout.contains("fx"));
+}
+
+#[test]
+fn test_cli_shine_pans_lines_wider_than_terminal() {
+    let line = "abcdefghijklmnopqrstuvwxyz0123456789";
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "shine",
+            "--terminal-width",
+            "10",
+            "--duration",
+            "1000",
+            "--speed",
+            "50",
+        ],
+        line,
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let frames: Vec<String> = stdout
+        .split("\x1b[1G")
+        .skip(1)
+        .map(|frame| {
+            // Drop escape sequences, keeping only what lands on screen
+            let mut visible = String::new();
+            let mut chars = frame.chars();
+            while let Some(ch) = chars.next() {
+                if ch == '\x1b' {
+                    chars.by_ref().find(|c| c.is_ascii_alphabetic());
+                } else if ch != '\n' {
+                    visible.push(ch);
+                }
+            }
+            visible
+        })
+        .collect();
+
+    // Every frame fills exactly the terminal width, and the window pans
+    // from the start of the line to its end as the shine travels
+    assert_eq!(frames.len(), 20);
+    assert!(frames.iter().all(|frame| frame.chars().count() == 10));
+    assert!(frames.iter().any(|frame| frame.starts_with("abc")));
+    assert!(frames.iter().any(|frame| frame.ends_with("789")));
+}
+
+#[test]
+fn test_cli_scramble_locks_into_text() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "scramble",
+            "--charset",
+            "#",
+            "--lock-delay",
+            "50",
+            "--speed",
+            "50",
+        ],
+        "ab c",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Scrambled glyphs in the scrambled color, then the text in the locked color
+    assert!(stdout.contains("\x1b[38;2;0;255;70m#"));
+    assert!(stdout.contains("\x1b[38;2;255;255;255mc"));
+
+    let empty_charset = run_with_input(&["run", "--", "scramble", "--charset", " "], "text");
+    assert!(!empty_charset.status.success());
+}
+
+#[test]
+fn test_cli_fade_modes() {
+    let fade_in = run_with_input(
+        &[
+            "run",
+            "--",
+            "fade",
+            "--color",
+            "200,100,0",
+            "--from-color",
+            "0,0,0",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "Hello",
+    );
+
+    assert!(fade_in.status.success());
+    let stdout = String::from_utf8_lossy(&fade_in.stdout);
+    // Starts at the background, passes halfway, and ends on the text color
+    assert!(stdout.contains("\x1b[38;2;0;0;0mH"));
+    assert!(stdout.contains("\x1b[38;2;100;50;0mH"));
+    assert!(stdout.contains("\x1b[38;2;200;100;0mo\x1b[0m\n"));
+
+    let fade_out = run_with_input(
+        &[
+            "run",
+            "--",
+            "fade",
+            "--mode",
+            "out",
+            "--from-color",
+            "10,20,30",
+            "--duration",
+            "100",
+            "--stagger",
+            "50",
+        ],
+        "Bye",
+    );
+    assert!(fade_out.status.success());
+    let stdout = String::from_utf8_lossy(&fade_out.stdout);
+    assert!(stdout.contains("\x1b[38;2;10;20;30me\x1b[0m\n"));
+}
+
+#[test]
+fn test_cli_easing_preview() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "easing-preview",
+            "cubic-bezier(0.25,0.1,0.25,1)",
+            "--width",
+            "20",
+            "--height",
+            "6",
+            "--duration",
+            "100",
+            "--speed",
+            "20",
+        ],
+        "",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 ┤"));
+    assert!(stdout.contains("0 ┤"));
+    assert!(stdout.contains("  └────────────────────"));
+    // The dot reaches the end of the ruler on the last frame
+    assert!(stdout.contains("t=1.00 →  1.00"));
+
+    let unknown = run_with_input(&["run", "--", "easing-preview", "bounce"], "");
+    assert!(!unknown.status.success());
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("unknown easing"));
+
+    // Every easing it takes is named in its help
+    let help = run_with_input(&["run", "--", "easing-preview", "--help"], "");
+    let help = String::from_utf8_lossy(&help.stdout);
+    for easing in EasingFunction::value_variants() {
+        let name = easing.to_possible_value().unwrap();
+        assert!(help.contains(name.get_name()), "{help}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_clip_buffer_saves_on_sigusr1() {
+    let dir = std::env::temp_dir().join(format!("clifx-clip-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // Run the binary itself, so the signal reaches clifx rather than cargo
+    let mut child = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args([
+            "--clip-buffer",
+            "5",
+            "shine",
+            "--cycles",
+            "0",
+            "--speed",
+            "20",
+        ])
+        .current_dir(&dir)
+        .env("COLORTERM", "truecolor")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn clifx");
+    child.stdin.take().unwrap().write_all(b"Clipped").unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let pid = child.id().to_string();
+    let signal = |name: &str| {
+        Command::new("kill")
+            .args([name, &pid])
+            .status()
+            .expect("Failed to signal clifx")
+    };
+    assert!(signal("-USR1").success());
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    signal("-TERM");
+    child.wait().unwrap();
+
+    let clips: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(clips.len(), 1);
+    let cast = std::fs::read_to_string(&clips[0]).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let mut lines = cast.lines();
+    let header: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert_eq!(header["version"], 2);
+    let events: Vec<serde_json::Value> = lines
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(events.len() > 5);
+    assert!(events.iter().all(|event| event[1] == "o"));
+    assert!(events[0][2].as_str().unwrap().contains('C'));
+
+    let zero = run_with_input(&["run", "--", "--clip-buffer", "0", "shine"], "Hi");
+    assert!(!zero.status.success());
+    assert!(String::from_utf8_lossy(&zero.stderr).contains("positive number of seconds"));
+}
+
+/// Visible text of each single-line frame drawn from column 1.
+fn marquee_frames(stdout: &str) -> Vec<String> {
+    stdout
+        .split("\x1b[1G")
+        .skip(1)
+        .map(|frame| {
+            let mut visible = String::new();
+            let mut in_escape = false;
+            for ch in frame.chars() {
+                match ch {
+                    '\x1b' => in_escape = true,
+                    _ if in_escape => in_escape = !ch.is_ascii_alphabetic(),
+                    _ => visible.push(ch),
+                }
+            }
+            visible.trim_end_matches('\n').to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_cli_marquee_scrolls_through_window() {
+    let run = |direction: &str| {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                "marquee",
+                "--window-width",
+                "6",
+                "--gap",
+                "2",
+                "--speed",
+                "10",
+                "--direction",
+                direction,
+            ],
+            "abcd",
+        );
+        assert!(output.status.success());
+        marquee_frames(&String::from_utf8_lossy(&output.stdout))
+    };
+
+    // One cycle is text plus gap, ending back where it began
+    let left = run("left");
+    assert_eq!(left.len(), 7);
+    assert_eq!(left[0], "abcd  ");
+    assert_eq!(left[1], "bcd  a");
+    assert_eq!(left[4], "  abcd");
+    assert_eq!(left[6], "abcd  ");
+
+    let right = run("right");
+    assert_eq!(right.len(), 7);
+    assert_eq!(right[1], " abcd ");
+    assert_eq!(right[6], "abcd  ");
+}
+
+#[test]
+fn test_cli_fire_burns_and_dies_down() {
+    let below = run_with_input(
+        &[
+            "run",
+            "--",
+            "--seed",
+            "7",
+            "fire",
+            "--mode",
+            "below",
+            "--height",
+            "4",
+            "--duration",
+            "500",
+            "--speed",
+            "10",
+        ],
+        "Flames",
+    );
+    assert!(below.status.success());
+    let stdout = String::from_utf8_lossy(&below.stdout);
+    assert!(['░', '▒', '▓', '█']
+        .iter()
+        .any(|&glyph| stdout.contains(glyph)));
+
+    // Once the fire is out the text is back in its own color, with no flames after it
+    let last = stdout.rsplit("\x1b[4A").next().unwrap();
+    assert!(last.contains("\x1b[38;2;255;255;255mF"));
+    assert!(!['░', '▒', '▓', '█']
+        .iter()
+        .any(|&glyph| last.contains(glyph)));
+
+    let behind = run_with_input(
+        &[
+            "run",
+            "--",
+            "fire",
+            "--palette",
+            "blue",
+            "--duration",
+            "200",
+            "--speed",
+            "10",
+        ],
+        "Flames",
+    );
+    assert!(behind.status.success());
+    let stdout = String::from_utf8_lossy(&behind.stdout);
+    assert!(!stdout.contains('█'));
+    assert!(stdout.contains('F'));
+}
+
+#[test]
+fn test_cli_numeric_flag_expressions() {
+    let path = std::env::temp_dir().join(format!("clifx-expr-{}.json", std::process::id()));
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--profile-out",
+            path.to_str().unwrap(),
+            "shine",
+            "--duration",
+            "lines * 100",
+            "--speed",
+            "(lines + 1) * 25",
+            "--width=longest/2",
+        ],
+        "one\ntwo\nthree\n",
+    );
+    assert!(output.status.success());
+    // The input read to evaluate the expressions still reaches the effect
+    assert!(String::from_utf8_lossy(&output.stdout).contains('h'));
+
+    let profile: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(profile["parameters"]["duration"], "300");
+    assert_eq!(profile["parameters"]["speed"], "100");
+    // Whole-number flags are rounded: 5 / 2 = 2.5
+    assert_eq!(profile["parameters"]["width"], "3");
+
+    let unknown = run_with_input(&["run", "--", "shine", "--duration", "height*2"], "Hi");
+    assert!(!unknown.status.success());
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("unknown variable 'height'"));
+
+    // A frame every zero milliseconds is refused, not divided by
+    let zero = run_with_input(&["run", "--", "shine", "--speed", "cols/1000"], "Hi");
+    assert!(!zero.status.success());
+    assert!(String::from_utf8_lossy(&zero.stderr).contains("--speed must be at least 1"));
+
+    let streamed = run_with_input(
+        &["run", "--", "--stream", "shine", "--duration", "lines*100"],
+        "Hi",
+    );
+    assert!(!streamed.status.success());
+    assert!(String::from_utf8_lossy(&streamed.stderr).contains("--stream"));
+}
+
+#[test]
+fn test_cli_compare_shows_both_sides() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "compare",
+            "--left",
+            "shine --speed 20 --duration 100",
+            "--right",
+            "shine --speed 20 --duration 100 --width 4",
+        ],
+        "Hi",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains('│'));
+
+    // Labels are drawn a character at a time, so compare the text alone
+    assert!(marquee_frames(&stdout)
+        .iter()
+        .any(|row| row.contains("--duration 100 │ shine")));
+
+    // Both sides share a seed, so the random base color is the same
+    let last_row = stdout.rsplit("\x1b[1G").next().unwrap();
+    let colors: Vec<&str> = last_row
+        .match_indices("mH")
+        .map(|(end, _)| &last_row[last_row[..end].rfind("\x1b[").unwrap()..end])
+        .collect();
+    assert_eq!(colors.len(), 2);
+    assert_eq!(colors[0], colors[1]);
+
+    let invalid = run_with_input(
+        &[
+            "run",
+            "--",
+            "compare",
+            "--left",
+            "shine --bogus",
+            "--right",
+            "pulse",
+        ],
+        "Hi",
+    );
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("--left: unexpected argument"));
+}
+
+#[test]
+fn test_cli_keep_final_prints_last_frame_as_plain_lines() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--keep-final",
+            "shine",
+            "--speed",
+            "20",
+            "--duration",
+            "100",
+            "--color",
+            "red",
+        ],
+        "Hello\nWorld\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.ends_with("\x1b[38;2;255;0;0mHello\x1b[0m\n\x1b[38;2;255;0;0mWorld\x1b[0m\n"));
+
+    // Live frames color every character separately, so only the kept copy reads as one word
+    let without = run_with_input(
+        &["run", "--", "shine", "--speed", "20", "--duration", "100"],
+        "Hello",
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&without.stdout)
+            .matches("Hello")
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn test_cli_twinkle_spawn_bias() {
+    let art = "#####.   .\n##.##.    \n#####.   .\n";
+    for bias in ["uniform", "dense", "sparse"] {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                "--seed",
+                "3",
+                "twinkle",
+                "--spawn-bias",
+                bias,
+                "--duration",
+                "100",
+                "--speed",
+                "50",
+            ],
+            art,
+        );
+        assert!(
+            output.status.success(),
+            "twinkle --spawn-bias {bias} failed, stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let invalid = run_with_input(&["run", "--", "twinkle", "--spawn-bias", "clumpy"], art);
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_cli_big_renders_built_in_banner() {
+    let output = run_with_input(&["run", "--", "--big", "gradient"], "I");
+
+    assert!(
+        output.status.success(),
+        "--big failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // The built-in "I" is five rows of block glyphs, 3 + 1 + 1 + 1 + 3 of them
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).matches('█').count(),
+        9
+    );
+
+    let both = run_with_input(
+        &["run", "--", "--big", "--font-file", "x.flf", "gradient"],
+        "I",
+    );
+    assert!(!both.status.success());
+}
+
+#[test]
+fn test_cli_inline_needs_one_line_and_a_cursor_position() {
+    let several = run_with_input(&["run", "--", "--inline", "shine"], "one\ntwo\n");
+    assert!(!several.status.success());
+    assert!(String::from_utf8_lossy(&several.stderr).contains("single line"));
+
+    let unsupported = run_with_input(&["run", "--", "--inline", "wave"], "one");
+    assert!(String::from_utf8_lossy(&unsupported.stderr).contains("--inline works with"));
+
+    // Piped output has no terminal to report where the cursor is
+    let piped = run_with_input(&["run", "--", "--inline", "pulse"], "one");
+    assert!(!piped.status.success());
+    assert!(String::from_utf8_lossy(&piped.stderr).contains("can't read the cursor position"));
+    assert!(piped.stdout.is_empty());
+}
+
+#[test]
+fn test_cli_gradient_preview_all_palettes() {
+    let config_home = std::env::temp_dir().join(format!("clifx-gallery-{}", std::process::id()));
+    std::fs::create_dir_all(config_home.join("clifx")).unwrap();
+    std::fs::write(
+        config_home.join("clifx/config.toml"),
+        "[presets.alert]\nargs = [\"pulse\", \"--color\", \"red\"]\n",
+    )
+    .unwrap();
+
+    let output = run_with_config(
+        &config_home,
+        &["run", "--", "gradient", "--preview-all-palettes"],
+        "Hi",
+    );
+    std::fs::remove_dir_all(&config_home).unwrap();
+
+    assert!(
+        output.status.success(),
+        "gallery failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("gradient --stops sunset"));
+    assert!(stdout.contains("gradient --stops mono"));
+    assert!(stdout.contains("preset alert  (pulse --color red)"));
+    // The preset's pulse rests in its solid red
+    assert!(stdout.contains("\x1b[38;2;255;0;0mH"));
+
+    // A palette picked from the gallery works as --stops
+    let picked = run_with_input(&["run", "--", "gradient", "--stops", "ocean"], "Hi");
+    assert!(picked.status.success());
+}
+
+#[test]
+fn test_cli_sparkle_over_text() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--seed",
+            "2",
+            "sparkle",
+            "--colors",
+            "neon",
+            "--density",
+            "0.3",
+            "--lifetime",
+            "100",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "Hello\nWorld\n",
+    );
+    assert!(
+        output.status.success(),
+        "sparkle failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(['·', '+', '*', '✦']
+        .iter()
+        .any(|&glyph| stdout.contains(glyph)));
+
+    let invalid = run_with_input(&["run", "--", "sparkle", "--lifetime", "0"], "Hi");
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_cli_snow_falls_and_settles() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--seed",
+            "4",
+            "snow",
+            "--flake-chars",
+            "@",
+            "--density",
+            "0.05",
+            "--wind",
+            "-0.5",
+            "--accumulate",
+            "--duration",
+            "100",
+            "--speed",
+            "50",
+        ],
+        "Hello\n",
+    );
+    assert!(
+        output.status.success(),
+        "snow failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains('@'));
+    assert!(stdout.contains('▁'));
+    assert!(stdout.contains('H'));
+
+    let invalid = run_with_input(&["run", "--", "snow", "--flake-chars", " "], "Hi");
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_cli_sparkle_and_snow_spawn_bias() {
+    let art = "#####.   .\n##.##.    \n#####.   .\n";
+    for effect in ["sparkle", "snow"] {
+        for bias in ["uniform", "dense", "sparse"] {
+            let output = run_with_input(
+                &[
+                    "run",
+                    "--",
+                    "--seed",
+                    "3",
+                    "--virtual-size",
+                    "30x10",
+                    effect,
+                    "--spawn-bias",
+                    bias,
+                    "--duration",
+                    "100",
+                    "--speed",
+                    "50",
+                ],
+                art,
+            );
+            assert!(
+                output.status.success(),
+                "{effect} --spawn-bias {bias} failed, stderr: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            assert!(visible_text(&String::from_utf8_lossy(&output.stdout)).contains("##.##"));
+        }
+
+        let invalid = run_with_input(&["run", "--", effect, "--spawn-bias", "clumpy"], art);
+        assert!(!invalid.status.success());
+    }
+}
+
+#[test]
+fn test_cli_confetti_bursts_around_highlighted_text() {
+    for from in ["bottom", "text"] {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                "--seed",
+                "5",
+                "confetti",
+                "--from",
+                from,
+                "--burst-count",
+                "40",
+                "--palette",
+                "neon",
+                "--gravity",
+                "60",
+                "--speed",
+                "20",
+            ],
+            "Deployed\n",
+        );
+        assert!(
+            output.status.success(),
+            "confetti --from {from} failed, stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains('D'));
+        assert!(['■', '▪', '▬'].iter().any(|&glyph| stdout.contains(glyph)));
+    }
+
+    let invalid = run_with_input(&["run", "--", "confetti", "--burst-count", "0"], "Hi");
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_cli_fireworks_burst_and_spell_out_the_finale() {
+    for finale in [None, Some("600")] {
+        let mut args = vec![
+            "run",
+            "--",
+            "--seed",
+            "6",
+            "fireworks",
+            "--duration",
+            "600",
+            "--launch-every",
+            "200",
+            "--gravity",
+            "40",
+            "--speed",
+            "20",
+        ];
+        if let Some(at) = finale {
+            args.extend(["--finale-after", at]);
+        }
+        let output = run_with_input(&args, "Launch\n");
+        assert!(
+            output.status.success(),
+            "fireworks with finale {finale:?} failed, stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains('L'));
+        assert!(['*', '+', '·'].iter().any(|&glyph| stdout.contains(glyph)));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_control_socket_drives_a_running_effect() {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixStream;
+
+    let path = std::env::temp_dir().join(format!("clifx-control-{}.sock", std::process::id()));
+    let mut child = Command::new("cargo")
+        .args(["run", "--", "--control-socket"])
+        .arg(&path)
+        .args(["pulse", "--cycles", "0", "--speed", "20"])
+        .env("COLORTERM", "truecolor")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn CLI command");
+    child.stdin.take().unwrap().write_all(b"Hello\n").unwrap();
+
+    let mut client = None;
+    for _ in 0..600 {
+        if let Ok(stream) = UnixStream::connect(&path) {
+            client = Some(stream);
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let mut client = client.expect("control socket never came up");
+    let mut replies = BufReader::new(client.try_clone().unwrap()).lines();
+    let mut send = |command: &str| {
+        writeln!(client, "{command}").unwrap();
+        let reply = replies.next().unwrap().unwrap();
+        // Give the effect time to show what the command changed
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        reply
+    };
+
+    assert_eq!(send("text Bye\\nnow"), "ok");
+    assert_eq!(send("trigger shine --cycles 1 --speed 5"), "ok");
+    assert!(send("preset missing").starts_with("error: "));
+    assert!(send("trigger countup").starts_with("error: "));
+    assert!(send("dance").starts_with("error: unknown command"));
+    assert_eq!(send("quit"), "ok");
+
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "control socket run failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Every character is colored on its own, so look for ones only the text has
+    assert!(stdout.contains('H'));
+    assert!(stdout.contains('y') && stdout.contains('w'));
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_cli_virtual_size_fixes_the_layout() {
+    let centered = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "40x10",
+            "--center",
+            "pulse",
+            "--cycles",
+            "1",
+            "--speed",
+            "10",
+        ],
+        "Hi",
+    );
+    assert!(
+        centered.status.success(),
+        "centering in a virtual size failed, stderr: {}",
+        String::from_utf8_lossy(&centered.stderr)
+    );
+    // Row 5, column 20 (one-based) is the middle of 40x10 for a 2x1 line
+    assert!(String::from_utf8_lossy(&centered.stdout).contains("\x1b[5;20H"));
+
+    // Expressions over the terminal's size see the virtual one
+    let window = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size=30x8",
+            "marquee",
+            "--window-width",
+            "cols/3",
+            "--cycles",
+            "1",
+            "--speed",
+            "1",
+        ],
+        "Hello",
+    );
+    assert!(window.status.success());
+    let frames = marquee_frames(&String::from_utf8_lossy(&window.stdout));
+    assert!(frames.iter().all(|frame| frame.chars().count() == 10));
+
+    let invalid = run_with_input(&["run", "--", "--virtual-size", "80", "pulse"], "Hi");
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_cli_quality_levels_for_heavy_effects() {
+    for quality in ["low", "medium", "high", "auto"] {
+        for effect in ["fire", "sparkle"] {
+            let output = run_with_input(
+                &[
+                    "run",
+                    "--",
+                    "--quality",
+                    quality,
+                    effect,
+                    "--duration",
+                    "100",
+                    "--speed",
+                    "50",
+                ],
+                "Hot",
+            );
+            assert!(
+                output.status.success(),
+                "{effect} at --quality {quality} failed, stderr: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_cli_spotlight_sweeps_every_path() {
+    for path in ["lr", "circle", "random"] {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                "spotlight",
+                "--path",
+                path,
+                "--radius",
+                "3",
+                "--dim-factor",
+                "0.2",
+                "--duration",
+                "200",
+                "--speed",
+                "20",
+            ],
+            "Spotlight\n",
+        );
+        assert!(
+            output.status.success(),
+            "spotlight --path {path} failed, stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains('S'));
+        // The text ends at full brightness
+        assert!(stdout.contains("38;2;255;255;255m"));
+    }
+}
+
+#[test]
+fn test_cli_writes_text_once_at_rest_off_a_terminal() {
+    let mut child = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "--explain-fallbacks",
+            "gradient",
+            "--stops",
+            "red,blue",
+            "--animate",
+        ])
+        .env("COLORTERM", "truecolor")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn CLI command");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"Piped\nout\n")
+        .unwrap();
+    let output = child.wait_with_output().expect("Failed to read stdout");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // One line each, starting red, with no cursor movement between frames
+    assert!(stdout.starts_with("\x1b[38;2;255;0;0mP"));
+    assert_eq!(stdout.matches('\n').count(), 2);
+    assert!(!stdout.contains("\x1b[1G"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force-animation"));
+
+    let forced = run_with_input(&["run", "--", "gradient", "--animate"], "Piped");
+    assert!(String::from_utf8_lossy(&forced.stdout).contains("\x1b[1G"));
+}
+
+#[test]
+fn test_cli_export_html() {
+    let path = std::env::temp_dir().join(format!("clifx-export-{}.html", std::process::id()));
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--export-html",
+            path.to_str().unwrap(),
+            "shine",
+            "--color",
+            "red",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "Web <page>",
+    );
+    assert!(output.status.success());
+
+    let page = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains("var frames = [[0,"));
+    assert!(page.contains("<span style=\\\"color:#"));
+    // The page opens on the last frame, escaped for HTML
+    assert!(page.contains("&lt;page&gt;</span></pre>"));
+}
+
+#[test]
+fn test_cli_export_ansi_and_replay() {
+    let path = std::env::temp_dir().join(format!("clifx-export-{}.cast", std::process::id()));
+    let recorded = run_with_input(
+        &[
+            "run",
+            "--",
+            "--export-ansi",
+            path.to_str().unwrap(),
+            "shine",
+            "--color",
+            "red",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "Recorded",
+    );
+    assert!(recorded.status.success());
+
+    let cast = std::fs::read_to_string(&path).unwrap();
+    let header: serde_json::Value = serde_json::from_str(cast.lines().next().unwrap()).unwrap();
+    assert_eq!(header["version"], 2);
+    assert!(cast.lines().count() > 4);
+
+    let replayed = run_with_input(
+        &["run", "--", "replay", path.to_str().unwrap(), "--rate", "4"],
+        "",
+    );
+    std::fs::remove_file(&path).unwrap();
+    assert!(replayed.status.success());
+    // The same bytes come out, frame for frame
+    assert_eq!(
+        String::from_utf8_lossy(&replayed.stdout),
+        String::from_utf8_lossy(&recorded.stdout)
+    );
+
+    let missing = run_with_input(&["run", "--", "replay", "no-such-recording.cast"], "");
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("no-such-recording.cast"));
+}
+
+#[test]
+fn test_cli_json_frames_output() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--output",
+            "json-frames",
+            "shine",
+            "--color",
+            "red",
+            "--cycles",
+            "1",
+            "--duration",
+            "200",
+            "--speed",
+            "50",
+        ],
+        "Hi",
+    );
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains('\x1b'));
+    let frames: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(frames.len() > 1);
+    assert!(frames
+        .windows(2)
+        .all(|pair| pair[0]["time_ms"].as_u64() <= pair[1]["time_ms"].as_u64()));
+
+    let last = &frames[frames.len() - 1]["rows"][0];
+    assert_eq!(last[0]["char"], "H");
+    assert_eq!(last[0]["fg"], "#ff0000");
+    assert_eq!(last[1]["char"], "i");
+    assert_eq!(last[1]["attrs"], serde_json::json!([]));
+}
+
+#[test]
+fn test_cli_graphics_backends() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--backend",
+            "kitty",
+            "--big",
+            "gradient",
+            "--stops",
+            "red,blue",
+        ],
+        "HI",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b_Ga=T,f=32,"));
+    assert!(!stdout.contains('█'));
+
+    let output = run_with_input(&["run", "--", "--backend", "sixel", "--big", "shine"], "HI");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\x1bP0;1q"));
+
+    // Only block characters can be drawn as pixels
+    let output = run_with_input(&["run", "--", "--backend", "sixel", "shine"], "Hi");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("block characters"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_loop_runs_until_ctrl_c() {
+    // Fade has no infinite mode, so --loop reruns it over the same lines
+    let mut child = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args([
+            "--force-animation",
+            "--loop",
+            "fade",
+            "--duration",
+            "100",
+            "--speed",
+            "20",
+        ])
+        .env("COLORTERM", "truecolor")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn clifx");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"Again\nand again")
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(800));
+    let pid = child.id().to_string();
+    let status = Command::new("kill")
+        .args(["-INT", &pid])
+        .status()
+        .expect("Failed to signal clifx");
+    assert!(status.success());
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.matches("\x1b[2A\r").count() >= 2);
+    // The cursor comes back even though the loop never ended on its own
+    assert!(stdout.contains("\x1b[?25h"));
+
+    let topbar = run_with_input(&["run", "--", "--loop", "topbar"], "");
+    assert!(!topbar.status.success());
+    assert!(String::from_utf8_lossy(&topbar.stderr).contains("--loop doesn't apply to topbar"));
+}
+
+#[test]
+fn test_cli_text_flag_takes_the_place_of_stdin() {
+    let run = |args: &[&str]| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_clifx"))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to spawn clifx");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b"from stdin\n")
+            .unwrap();
+        child.wait_with_output().unwrap()
+    };
+
+    let text = run(&["--text", "Hello\nthere", "shine"]);
+    assert!(text.status.success());
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    assert!(stdout.contains("Hello"));
+    assert!(stdout.contains("there"));
+    assert!(!stdout.contains("from stdin"));
+
+    let appended = run(&["--text", "Hello", "--append-stdin", "shine"]);
+    assert!(appended.status.success());
+    let stdout = String::from_utf8_lossy(&appended.stdout);
+    let (hello, stdin) = (stdout.find("Hello"), stdout.find("from stdin"));
+    assert!(hello.is_some() && stdin.is_some() && hello < stdin);
+
+    let without_text = run(&["--append-stdin", "shine"]);
+    assert!(!without_text.status.success());
+}
+
+#[test]
+fn test_cli_file_flag_animates_files_windowed_to_the_screen() {
+    let dir = std::env::temp_dir().join(format!("clifx-file-flag-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (short, long) = (dir.join("short.txt"), dir.join("long.txt"));
+    std::fs::write(&short, "first file\n").unwrap();
+    let numbers: String = (1..=500).map(|n| format!("line {n}\n")).collect();
+    std::fs::write(&long, numbers).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args(["--virtual-size", "80x4", "--file"])
+        .arg(&short)
+        .arg("--file")
+        .arg(&long)
+        .args(["shine", "--cycles", "1", "--speed", "20"])
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to run clifx");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(output.status.success());
+    // Drop the escape codes between the characters of each frame
+    let mut stdout = String::new();
+    let mut chars = String::from_utf8_lossy(&output.stdout).into_owned();
+    while let Some(start) = chars.find('\x1b') {
+        stdout.push_str(&chars[..start]);
+        let rest = &chars[start + 2..];
+        let end = rest
+            .find(|ch: char| ch.is_ascii_alphabetic())
+            .map_or(rest.len(), |end| end + 1);
+        chars = rest[end..].to_string();
+    }
+    stdout.push_str(&chars);
+    assert!(stdout.contains("first file"));
+    assert!(stdout.contains("line 3"));
+    // Only as many lines as the four rows fit are read
+    assert!(!stdout.contains("line 4"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_watch_reruns_the_effect_when_the_file_changes() {
+    let path = std::env::temp_dir().join(format!("clifx-watch-{}.txt", std::process::id()));
+    std::fs::write(&path, "before\n").unwrap();
+
+    let child = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args(["watch", "--file"])
+        .arg(&path)
+        .args([
+            "--interval",
+            "50",
+            "shine",
+            "--cycles",
+            "0",
+            "--speed",
+            "20",
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn clifx");
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    std::fs::write(&path, "after\n").unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let pid = child.id().to_string();
+    let status = Command::new("kill")
+        .args(["-INT", &pid])
+        .status()
+        .expect("Failed to signal clifx");
+    assert!(status.success());
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Off a terminal each run writes the text once at rest
+    let (before, after) = (stdout.find("before"), stdout.find("after"));
+    assert!(before.is_some() && after.is_some() && before < after);
+
+    let without_source = run_with_input(&["run", "--", "watch", "shine"], "");
+    assert!(!without_source.status.success());
+    assert!(String::from_utf8_lossy(&without_source.stderr).contains("watch needs a --file"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_daemon_keeps_a_banner_up_for_socket_commands() {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixStream;
+
+    let path = std::env::temp_dir().join(format!("clifx-daemon-{}.sock", std::process::id()));
+    let child = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args(["--force-animation", "--text", "Hello", "daemon", "--socket"])
+        .arg(&path)
+        .args(["pulse", "--cycles", "0", "--speed", "20"])
+        .env("COLORTERM", "truecolor")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn clifx");
+
+    let mut client = None;
+    for _ in 0..100 {
+        if let Ok(stream) = UnixStream::connect(&path) {
+            client = Some(stream);
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let mut client = client.expect("daemon socket never came up");
+    let mut replies = BufReader::new(client.try_clone().unwrap()).lines();
+    let mut send = |command: &str| {
+        writeln!(client, "{command}").unwrap();
+        let reply = replies.next().unwrap().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        reply
+    };
+
+    assert_eq!(send("color 255,0,0"), "ok");
+    assert!(send("color nope").starts_with("error: "));
+    assert_eq!(send("effect shine --cycles 0 --speed 20"), "ok");
+    assert!(send("effect countup").starts_with("error: "));
+    assert_eq!(send("text Bye"), "ok");
+    assert_eq!(send("stop"), "ok");
+
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "daemon failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The banner lives on the alternate screen, cleared before each run
+    assert!(stdout.starts_with("\x1b[?1049h"));
+    assert!(stdout.contains("\x1b[H\x1b[2J"));
+    assert!(stdout.contains('H') && stdout.contains('y'));
+    assert!(stdout.trim_end().ends_with("\x1b[?1049l"));
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_cli_snapshot_prints_one_frame_without_cursor_codes() {
+    let output = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args(["--text", "Hi", "snapshot", "--shell", "bash", "shine"])
+        .args(["--color", "255,0,0", "--speed", "1000"])
+        .env("COLORTERM", "truecolor")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to run clifx");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains('H') && stdout.contains('i'));
+    // Every escape code is one readline knows takes up no room
+    assert_eq!(
+        stdout.matches('\x1b').count(),
+        stdout.matches('\x01').count()
+    );
+    assert!(stdout.contains("\x01\x1b[0m\x02"));
+    assert!(!stdout.contains("\x1b[?25"));
+    assert!(!stdout.contains("\x1b[2K"));
+    assert_eq!(stdout.lines().count(), 1);
+}
+
+#[test]
+fn test_cli_progress_fills_to_the_last_value() {
+    let output = run_with_input(
+        &["run", "--", "progress", "--width", "10", "--speed", "20"],
+        "10\n50 Compiling\nLinking\n3/4\n",
+    );
+
+    assert!(
+        output.status.success(),
+        "progress failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // A number on its own keeps the label, and the bar ends on the last value
+    let last_frame = stdout.rsplit("\x1b[1G").next().unwrap_or_default();
+    assert!(last_frame.contains(" 75%  Linking"), "{last_frame:?}");
+    assert_eq!(last_frame.matches('█').count(), 7);
+    assert!(last_frame.contains('▌'));
+
+    let output = run_with_input(&["run", "--", "--loop", "progress"], "");
+    assert!(!output.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_spin_streams_output_and_exits_like_the_command() {
+    let output = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args(["--force-animation", "spin", "--label", "Deploying"])
+        .args(["--speed", "20", "--flourish", "100", "--"])
+        .args(["sh", "-c", "echo one; echo two >&2; exit 3"])
+        .env("COLORTERM", "truecolor")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to run clifx");
+
+    assert_eq!(output.status.code(), Some(3));
+    // Nothing of clifx's own about the failure, which was the command's
+    assert!(output.stderr.is_empty());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("one") && stdout.contains("two"));
+    // The line ends marked as failed, all in the failure color
+    assert!(stdout.contains("\x1b[38;2;230;60;60m✘"));
+    assert!(stdout.contains("(exit 3)"));
+
+    // Off a terminal the command's output passes through untouched
+    let output = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args(["spin", "--", "echo", "done"])
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to run clifx");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "done\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_spin_leaves_the_commands_own_flags_alone() {
+    let dir = std::env::temp_dir().join(format!("clifx-spin-flags-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let patterns = dir.join("patterns");
+    std::fs::write(&patterns, "two\n").unwrap();
+    let haystack = dir.join("haystack");
+    std::fs::write(&haystack, "one\ntwo\nthree\n").unwrap();
+
+    // grep's --file names its patterns, not clifx's input
+    let output = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args(["spin", "--", "grep"])
+        .arg(format!("--file={}", patterns.display()))
+        .arg(&haystack)
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to run clifx");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "two\n");
+
+    // Nor are flags after a command given without --
+    let output = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args([
+            "spin",
+            "echo",
+            "--virtual-size",
+            "big",
+            "--file",
+            "/nonexistent",
+        ])
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to run clifx");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "--virtual-size big --file /nonexistent\n"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_line_colors_give_each_line_its_own_base() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--line-colors",
+            "255,0,0,0,0,255",
+            "shine",
+            "--all-lines",
+            "--cycles",
+            "1",
+            "--speed",
+            "50",
+            "--duration",
+            "200",
+        ],
+        "one\ntwo\nthree\n",
+    );
+    assert!(
+        output.status.success(),
+        "shine failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[38;2;255;0;0mo"));
+    assert!(stdout.contains("\x1b[38;2;0;0;255mt"));
+
+    // A palette's colors go round the lines in order too
+    let output = run_with_input(
+        &["run", "--", "--line-palette", "dracula", "twinkle"],
+        "a\nb\n",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("a") && stdout.contains("b"));
+    assert!(stdout.contains("189;147;249"));
+    assert!(stdout.contains("255;121;198"));
+
+    let output = run_with_input(&["run", "--", "--line-palette", "plaid", "shine"], "a\n");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_theme_colors_what_the_command_line_leaves_unset() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--theme",
+            "synthwave",
+            "shine",
+            "--color",
+            "255,0,0",
+            "--speed",
+            "100",
+        ],
+        "hello\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The typed base color stays, under the theme's cyan shine
+    assert!(stdout.contains("38;2;255;0;0m"));
+    assert!(stdout.contains("38;2;0;240;255m"));
+    assert!(!stdout.contains("38;2;255;0;170m"));
+
+    // The config's theme applies when none is given, and presets can pick their own
+    let config_home = std::env::temp_dir().join(format!("clifx-themes-{}", std::process::id()));
+    std::fs::create_dir_all(config_home.join("clifx")).unwrap();
+    std::fs::write(
+        config_home.join("clifx/config.toml"),
+        "theme = \"matrix\"\n\n[presets.hot]\nargs = [\"--theme\", \"fire\", \"gradient\"]\n",
+    )
+    .unwrap();
+    let output = run_with_config(&config_home, &["run", "--", "twinkle"], "Hi");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("38;2;0;170;50m"));
+    let output = run_with_config(&config_home, &["run", "--", "preset", "hot"], "Hi");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\x1b[38;2;120;0;0mH"));
+    std::fs::remove_dir_all(&config_home).unwrap();
+}
+
+#[test]
+fn test_cli_shine_gradient_ramps_the_band() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "shine",
+            "--color",
+            "0,0,255",
+            "--shine-gradient",
+            "white,gold,orange",
+            "--blur",
+            "--width",
+            "3",
+            "--speed",
+            "50",
+        ],
+        "hello world\n",
+    );
+    assert!(
+        output.status.success(),
+        "shine failed, stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The middle stop is the band's center, with white and orange either side
+    assert!(stdout.contains("\x1b[38;2;255;215;0m"));
+    assert!(stdout.contains("\x1b[38;2;169;151;141m"));
+    assert!(stdout.contains("\x1b[38;2;169;131;85m"));
+}
+
+/// `text` without its escape codes or carriage returns.
+fn visible_text(text: &str) -> String {
+    let mut visible = String::new();
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\x1b' => {
+                // Skip to the end of the sequence, a letter
+                for ch in chars.by_ref() {
+                    if ch.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            '\r' => {}
+            ch => visible.push(ch),
+        }
+    }
+    visible
+}
+
+#[test]
+fn test_cli_wrap_and_align() {
+    let text = "the quick brown fox jumps\n";
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--align",
+            "right",
+            "gradient",
+            "--terminal-width",
+            "12",
+        ],
+        text,
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        visible_text(&String::from_utf8_lossy(&output.stdout)).trim_end(),
+        "the quick\nbrown fox\n    jumps"
+    );
+
+    // Effects that don't lay text out themselves are rewrapped to the terminal
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "12x5",
+            "--wrap",
+            "char",
+            "--align",
+            "center",
+            "pulse",
+            "--cycles",
+            "1",
+        ],
+        text,
+    );
+    assert!(output.status.success());
+    let stdout = visible_text(&String::from_utf8_lossy(&output.stdout));
+    assert!(stdout.contains("the quick br"), "{stdout:?}");
+    assert!(stdout.contains("own fox jump"), "{stdout:?}");
+    assert!(stdout.contains("     s"), "{stdout:?}");
+
+    let invalid = run_with_input(&["run", "--", "--wrap", "sometimes", "shine"], text);
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_cli_center_stacks_lines_drawn_one_at_a_time() {
+    // Two rows centered in 20x6 start on the third row, the next below it
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "20x6",
+            "--center",
+            "twinkle",
+            "--cycles",
+            "1",
+            "--duration",
+            "100",
+        ],
+        "o.e\nt.o\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[3;9H"), "{stdout:?}");
+    assert!(stdout.contains("\x1b[4;9H"), "{stdout:?}");
+
+    // Shine2d's block is centered as it wraps, three rows of nine columns
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "12x8",
+            "--center",
+            "shine2d",
+            "--duration",
+            "100",
+        ],
+        "the quick brown fox jumps\n",
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\x1b[3;2H"));
+}
+
+#[test]
+fn test_cli_center_clears_once_for_lines_drawn_one_at_a_time() {
+    // Clearing before each line would leave only the last one on screen
+    for effect in ["shine", "twinkle", "wave"] {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                "--virtual-size",
+                "20x12",
+                "--center",
+                effect,
+                "--cycles",
+                "1",
+                "--duration",
+                "100",
+            ],
+            "Hi.\nab\ncd.\n",
+        );
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(stdout.matches("\x1b[2J").count(), 1, "{effect}: {stdout:?}");
+    }
+    // A line with nothing to twinkle is still drawn in its place
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "20x6",
+            "--center",
+            "twinkle",
+            "--cycles",
+            "1",
+            "--duration",
+            "100",
+        ],
+        "Hi.\nab\ncd.\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[3;9H"), "{stdout:?}");
+}
+
+#[test]
+fn test_cli_center_one_axis() {
+    // Across only: indented in place, with nothing cleared or moved to
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "20x6",
+            "--center-h",
+            "gradient",
+        ],
+        "Hello\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(visible_text(&stdout).trim_end(), "       Hello");
+    assert!(!stdout.contains("\x1b[2J"));
+
+    // Down only, at the left edge, drawn over what's there
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "20x6",
+            "--center-v",
+            "--no-clear",
+            "twinkle",
+            "--cycles",
+            "1",
+            "--duration",
+            "100",
+        ],
+        "o.e\nt.o\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[3;1H"), "{stdout:?}");
+    assert!(stdout.contains("\x1b[4;1H"), "{stdout:?}");
+    assert!(!stdout.contains("\x1b[2J"));
+
+    for invalid in [
+        &["--center", "--center-h"][..],
+        &["--center-v", "--stream"],
+        &["--center-h", "--inline"],
+    ] {
+        let mut args = vec!["run", "--"];
+        args.extend(invalid);
+        args.push("shine");
+        assert!(!run_with_input(&args, "Hi").status.success(), "{invalid:?}");
+    }
+}
+
+#[test]
+fn test_cli_border_boxes_the_text() {
+    // At rest the box is written around the lines, in the border's color
+    let output = Command::new(env!("CARGO_BIN_EXE_clifx"))
+        .args(["--text", "Hi", "--border", "rounded", "--pad", "1"])
+        .args(["--border-color", "255,0,0", "gradient"])
+        .env("COLORTERM", "truecolor")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to run clifx");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        visible_text(&stdout).lines().collect::<Vec<_>>(),
+        ["╭────╮", "│    │", "│ Hi │", "│    │", "╰────╯"]
+    );
+    assert!(stdout.contains("\x1b[38;2;255;0;0m╭"), "{stdout:?}");
+
+    // Animated, it's drawn around where the centered text goes, and the
+    // cursor is left below it
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "20x8",
+            "--center",
+            "--border",
+            "ascii",
+            "--margin",
+            "1",
+            "fade",
+        ],
+        "Hi\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("\x1b[3;9H\x1b[38;2;128;128;128m+--+"),
+        "{stdout:?}"
+    );
+    assert!(
+        stdout.contains("\x1b[4;12H\x1b[38;2;128;128;128m|"),
+        "{stdout:?}"
+    );
+    assert!(stdout.contains("\x1b[4;10H"), "{stdout:?}");
+    assert!(stdout.ends_with("\x1b[6;1H\n"), "{stdout:?}");
+
+    let output = run_with_input(&["run", "--", "--border", "double", "countup"], "");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_banner_combines_big_text_border_and_gradient() {
+    let banner = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_clifx"))
+            .arg("banner")
+            .args(args)
+            .env("COLORTERM", "truecolor")
+            .stdin(Stdio::null())
+            .output()
+            .expect("Failed to run clifx")
+    };
+
+    // At rest: big letters in a rounded box, in the sunset gradient
+    let output = banner(&["Hi"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows: Vec<String> = visible_text(&stdout).lines().map(str::to_string).collect();
+    assert_eq!(rows.len(), 9, "{rows:?}");
+    assert!(rows[0].starts_with('╭') && rows[8].starts_with('╰'));
+    assert!(rows[2].contains('█'));
+    assert!(stdout.contains("\x1b[38;2;128;128;128m╭"), "{stdout:?}");
+
+    // Each part can be changed or turned off
+    let output = banner(&["--plain", "--border", "ascii", "--pad", "0", "Hi"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(visible_text(&stdout), "+--+\n|Hi|\n+--+\n");
+
+    // Animated, it's centered, and the shine sweeps the gradient
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "40x12",
+            "banner",
+            "--speed",
+            "20",
+        ],
+        "Go\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[38;2;255;255;255m"), "{stdout:?}");
+    assert!(stdout.contains("\x1b[2J"));
+
+    assert!(!banner(&["--no-center", "--center", "Hi"]).status.success());
+    assert!(!banner(&["--text", "Hi", "Hi"]).status.success());
+}
+
+#[test]
+fn test_cli_neon_glows_around_the_text() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--seed",
+            "4",
+            "neon",
+            "--color",
+            "200,0,0",
+            "--duration",
+            "200",
+            "--flicker-chance",
+            "0.5",
+        ],
+        "Bar\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The sign ends steady and fully lit, in a halo of its dimmer color
+    assert!(stdout.contains("\x1b[38;2;200;0;0mB"), "{stdout:?}");
+    assert!(stdout.contains("\x1b[38;2;120;0;0m░"), "{stdout:?}");
+    let last_frame = visible_text(&stdout);
+    assert!(last_frame.lines().count() >= 3);
+
+    // Without a glow only the text is drawn
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "neon",
+            "--glow-radius",
+            "0",
+            "--duration",
+            "100",
+        ],
+        "Bar\n",
+    );
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains('░'));
+}
+
+#[test]
+fn test_cli_blink_hides_the_text_and_ends_on() {
+    let started = std::time::Instant::now();
+    let output = run_with_input(
+        &[
+            "run", "--", "strobe", "--color", "0,200,0", "--on-ms", "1", "--off-ms", "1",
+            "--cycles", "2",
+        ],
+        "Hi\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Off, the text is blanked; it finishes shown
+    assert!(stdout.contains("\x1b[1G  "), "{stdout:?}");
+    assert!(
+        stdout.contains("\x1b[38;2;0;200;0mi\x1b[0m\n"),
+        "{stdout:?}"
+    );
+    // However short the steps asked for, four of them take 400ms
+    assert!(started.elapsed() >= std::time::Duration::from_millis(400));
+
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "blink",
+            "--on-ms",
+            "100",
+            "--off-color",
+            "40,40,40",
+            "--pattern",
+            "... ---",
+        ],
+        "Hi\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[38;2;40;40;40mH"), "{stdout:?}");
+
+    let output = run_with_input(&["run", "--", "blink", "--pattern", "S?S"], "Hi\n");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'?' has no Morse code"));
+}
+
+#[test]
+fn test_cli_blink_photosensitive_safe_dims_instead_of_hiding() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "blink",
+            "--color",
+            "200,100,0",
+            "--on-ms",
+            "1",
+            "--off-ms",
+            "1",
+            "--cycles",
+            "1",
+            "--photosensitive-safe",
+        ],
+        "Hi\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[38;2;100;50;0mH"), "{stdout:?}");
+    assert!(!stdout.contains("\x1b[1G  "), "{stdout:?}");
+}
+
+#[test]
+fn test_cli_heartbeat_thumps_with_a_swelling_heart() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "heartbeat",
+            "--color",
+            "200,0,0",
+            "--bpm",
+            "60",
+            "--speed",
+            "10",
+            "--cycles",
+            "1",
+            "--heart",
+            "--heart-color",
+            "0,0,200",
+        ],
+        "Hi\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Full brightness on the thump, the heart swelling with it
+    assert!(stdout.contains("\x1b[38;2;200;0;0mH"), "{stdout:?}");
+    assert!(stdout.contains("\x1b[38;2;0;0;200m♥"), "{stdout:?}");
+    assert!(stdout.contains("\x1b[38;2;0;0;60m♡"), "{stdout:?}");
+
+    let output = run_with_input(&["run", "--", "heartbeat", "--bpm", "600"], "Hi\n");
+    assert!(!output.status.success());
+    let output = run_with_input(&["run", "--", "heartbeat", "--heart-color", "red"], "Hi\n");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_karaoke_highlights_word_by_word_to_a_timing_file() {
+    let timing = std::env::temp_dir().join(format!("clifx-timing-{}.txt", std::process::id()));
+    std::fs::write(&timing, "# first line\nla:50\nlaa:50\n").unwrap();
+
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "karaoke",
+            "--color",
+            "100,100,100",
+            "--highlight",
+            "250,0,0",
+            "--sung-color",
+            "0,0,250",
+            "--timing-file",
+            timing.to_str().unwrap(),
+        ],
+        "la laa\n",
+    );
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The first word lit up whole while the second waits, then sung
+    assert!(
+        stdout.contains(
+            "\x1b[38;2;250;0;0ml\x1b[38;2;250;0;0ma\x1b[38;2;100;100;100m \x1b[38;2;100;100;100ml"
+        ),
+        "{stdout:?}"
+    );
+    assert!(stdout.contains("\x1b[38;2;0;0;250ma\x1b[38;2;100;100;100m \x1b[38;2;250;0;0ml"));
+
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "karaoke",
+            "--timing-file",
+            timing.to_str().unwrap(),
+        ],
+        "la la la\n",
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("the timing has 2 words but the text has 3"));
+    std::fs::remove_file(&timing).unwrap();
+}
+
+#[test]
+fn test_cli_wipe_reveals_behind_a_band_and_hides_on_exit() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "wipe",
+            "--color",
+            "0,200,0",
+            "--band-color",
+            "0,0,255",
+            "--band-width",
+            "1",
+            "--duration",
+            "100",
+            "--speed",
+            "20",
+        ],
+        "abc\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The band passes with the text showing behind it, which is left whole
+    assert!(
+        stdout.contains("\x1b[38;2;0;200;0ma\x1b[38;2;0;0;255m█ "),
+        "{stdout:?}"
+    );
+    assert!(stdout.contains("a\x1b[38;2;0;200;0mb\x1b[38;2;0;200;0mc\x1b[0m\n"));
+
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "wipe",
+            "--direction",
+            "center-out",
+            "--mode",
+            "out",
+            "--duration",
+            "100",
+        ],
+        "abc\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The middle goes first, and the exit leaves nothing behind
+    assert!(
+        stdout.contains("\x1b[38;2;255;255;255ma \x1b[38;2;255;255;255mc"),
+        "{stdout:?}"
+    );
+    assert!(stdout.contains("\x1b[1G   \x1b[0m\n"), "{stdout:?}");
+}
+
+#[test]
+fn test_cli_dissolve_pops_characters_in_order() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "dissolve",
+            "--order",
+            "inside-out",
+            "--duration",
+            "100",
+            "--speed",
+            "20",
+        ],
+        "abcde\n",
+    );
+    assert!(output.status.success());
+    // From the middle out, one character at a time
+    let frames: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split("\x1b[1G")
+        .skip(1)
+        .map(visible_text)
+        .collect();
+    assert_eq!(
+        frames,
+        ["     ", "  c  ", " bc  ", " bcd ", "abcd ", "abcde\n"]
+    );
+
+    // At random, the seed picks the order
+    let random = |seed: &str| {
+        let output = run_with_input(
+            &[
+                "run",
+                "--",
+                "--seed",
+                seed,
+                "dissolve",
+                "--mode",
+                "inout",
+                "--duration",
+                "100",
+            ],
+            "hello world\n",
+        );
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let once = random("7");
+    assert_eq!(once, random("7"));
+    assert!(visible_text(&once).ends_with("           \n"), "{once:?}");
+}
+
+#[test]
+fn test_cli_slide_brings_the_text_in_from_an_edge() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "10x5",
+            "slide",
+            "--from",
+            "right",
+            "--easing",
+            "linear",
+            "--duration",
+            "100",
+            "--speed",
+            "20",
+        ],
+        "ab\n",
+    );
+    assert!(output.status.success());
+    let frames: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split("\x1b[1G")
+        .skip(1)
+        .map(|frame| visible_text(frame).trim_end().to_string())
+        .collect();
+    // From past the right edge to the start of the line
+    assert_eq!(frames.first().map(String::as_str), Some(""));
+    assert!(frames.contains(&"    ab".to_string()), "{frames:?}");
+    assert_eq!(frames.last().map(String::as_str), Some("ab"));
+
+    let output = run_with_input(
+        &["run", "--", "--border", "ascii", "--center", "slide"],
+        "ab\n",
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_bounce_settles_and_leaves_the_text_behind() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--virtual-size",
+            "10x5",
+            "bounce",
+            "--gravity",
+            "400",
+            "--per-char",
+        ],
+        "ab\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // It falls on the alternate screen, then the settled text is printed
+    let (bounced, after) = stdout
+        .split_once("\x1b[?1049l")
+        .expect("left the alt screen");
+    assert!(bounced.starts_with("\x1b[?1049h"));
+    // Down from the top row to the bottom one
+    assert!(
+        bounced.contains("\x1b[1;1H\x1b[1G\x1b[38;2;255;255;255ma"),
+        "{bounced:?}"
+    );
+    assert_eq!(visible_text(after), "ab\n");
+    assert!(
+        bounced.ends_with("\x1b[1G\x1b[38;2;255;255;255ma\x1b[38;2;255;255;255mb\x1b[0m\x1b[?25h")
+    );
+}
+
+#[test]
+fn test_cli_shake_jitters_and_settles_in_place() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "--seed",
+            "1",
+            "earthquake",
+            "--color",
+            "255,0,0",
+            "--amplitude",
+            "2",
+            "--duration",
+            "300",
+        ],
+        "FAILED\n",
+    );
+    assert!(output.status.success());
+    let frames: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split("\x1b[1G")
+        .skip(1)
+        .map(visible_text)
+        .collect();
+    // Every frame keeps within its margins, and the text moves around in them
+    assert!(frames
+        .iter()
+        .all(|frame| frame.trim_end_matches('\n').len() == 10));
+    let places: std::collections::HashSet<usize> = frames
+        .iter()
+        .map(|frame| frame.find('F').unwrap())
+        .collect();
+    assert!(places.len() > 1, "{frames:?}");
+    assert_eq!(frames.last().unwrap(), "  FAILED  \n");
+}
+
+#[test]
+fn test_cli_glowworm_crawls_on_to_the_next_line_and_leaves_the_text_as_it_was() {
+    let output = run_with_input(
+        &[
+            "run",
+            "--",
+            "snake",
+            "--color",
+            "0,0,0",
+            "--highlight",
+            "200,200,200",
+            "--length",
+            "1",
+            "--speed",
+            "5",
+        ],
+        "ab c\nd\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Past the end of the first line, the head lights the start of the next
+    assert!(stdout.contains("\x1b[38;2;200;200;200md"), "{stdout:?}");
+    let last_frame = stdout.rsplit("\x1b[1A").next().unwrap();
+    assert!(!last_frame.contains("200;200;200"), "{last_frame:?}");
+    assert!(last_frame.contains("\x1b[38;2;0;0;0mc"), "{last_frame:?}");
+    assert_eq!(visible_text(last_frame).trim_end(), "ab c\nd");
+}